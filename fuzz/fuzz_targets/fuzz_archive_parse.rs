@@ -44,6 +44,8 @@ fuzz_target!(|data: &[u8]| {
     // Try to read each file - should never panic
     for file in &files {
         let _ = reader.read_file(file);
+        let _ = reader.read_prefix(file, 16);
+        let _ = reader.read_file_range(file, 3, 10);
     }
 
     // Try entry_count - should never panic
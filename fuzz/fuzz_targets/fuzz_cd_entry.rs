@@ -0,0 +1,9 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use engram_rs::EntryInfo;
+
+fuzz_target!(|data: &[u8]| {
+    // Any input must parse or fail with an error - never panic
+    let _ = EntryInfo::read_from(data);
+});
@@ -0,0 +1,425 @@
+use crate::archive::chunking;
+use crate::archive::dictionary::ZSTD_DICTIONARY_PATH;
+use crate::archive::end_record::{EndRecord, END_RECORD_SIZE};
+use crate::archive::format::{EncryptionMode, EntryInfo, FileHeader, HEADER_SIZE};
+use crate::archive::frame_compression::preallocation;
+use crate::archive::reader::ArchiveReader;
+use crate::crypto::SecretKey;
+use crate::error::{EngramError, Result};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, OnceLock};
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::Mutex;
+
+/// Fixed part of a LOCA header, up to and including the Unix mode
+const LOCAL_HEADER_FIXED_SIZE: usize = 40;
+
+/// Offset of the path length within a LOCA header
+const LOCAL_PATH_LEN_OFFSET: usize = 34;
+
+/// Archive reader for async code, built on `tokio::fs::File`
+///
+/// Mirrors the core of [`ArchiveReader`]: `open`, `initialize` and
+/// `read_file`. File I/O is awaited; decryption, decompression and checksum
+/// verification run on tokio's blocking thread pool via `spawn_blocking`, so
+/// large entries do not stall the executor. `read_file` takes `&self`, and
+/// concurrent reads only serialize on the seek and read of the stored bytes.
+///
+/// Only the header, central directory and trailer are read by `initialize`,
+/// except for archive-encrypted files, which are read and decrypted whole.
+///
+/// ```no_run
+/// use engram_rs::AsyncArchiveReader;
+///
+/// # async fn example() -> engram_rs::Result<()> {
+/// let reader = AsyncArchiveReader::open_and_init("assets.eng").await?;
+/// let (index, style) = futures::join!(
+///     reader.read_file("index.html"),
+///     reader.read_file("style.css"),
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncArchiveReader {
+    file: Mutex<File>,
+    header: FileHeader,
+    header_bytes: Vec<u8>,
+    /// Wiped when the reader is dropped (see `crate::crypto`)
+    decryption_key: Option<SecretKey>,
+    /// Reader over the preloaded regions, holding the parsed central
+    /// directory (and the decrypted payload of archive-encrypted files)
+    inner: Option<Arc<ArchiveReader>>,
+    /// Shared Zstd dictionary, loaded on first use
+    zstd_dictionary: OnceLock<Arc<Vec<u8>>>,
+}
+
+impl AsyncArchiveReader {
+    /// Open an archive file for reading
+    ///
+    /// Like `ArchiveReader::open`, this reads the header only; call
+    /// `initialize()` before reading files.
+    pub async fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut file = File::open(path).await?;
+        let mut header_bytes = vec![0u8; HEADER_SIZE];
+        file.read_exact(&mut header_bytes).await?;
+
+        let header = FileHeader::read_from(&header_bytes[..])?;
+        header.validate_version()?;
+
+        Ok(Self {
+            file: Mutex::new(file),
+            header,
+            header_bytes,
+            decryption_key: None,
+            inner: None,
+            zstd_dictionary: OnceLock::new(),
+        })
+    }
+
+    /// Open and initialize an archive in one step
+    pub async fn open_and_init<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut reader = Self::open(path).await?;
+        reader.initialize().await?;
+        Ok(reader)
+    }
+
+    /// Open and initialize an encrypted archive with its decryption key
+    pub async fn open_encrypted<P: AsRef<Path>>(path: P, key: &[u8; 32]) -> Result<Self> {
+        let mut reader = Self::open(path).await?.with_decryption_key(key);
+        reader.initialize().await?;
+        Ok(reader)
+    }
+
+    /// Provide the decryption key for encrypted archives
+    ///
+    /// Errors match `ArchiveReader::with_decryption_key`.
+    pub fn with_decryption_key(mut self, key: &[u8; 32]) -> Self {
+        self.decryption_key = Some(SecretKey::new(*key));
+        self
+    }
+
+    /// Initialize the reader (must be called after open, decrypts if needed)
+    ///
+    /// Reads the central directory and End Record, and validates them like
+    /// `ArchiveReader::initialize`.
+    pub async fn initialize(&mut self) -> Result<()> {
+        let regions = self.read_metadata_regions().await?;
+        let source = RegionSource::new(regions.0, regions.1);
+        let decryption_key = self.decryption_key.clone();
+
+        let inner = blocking(move || {
+            let mut reader = ArchiveReader::from_reader(source)?;
+            if let Some(key) = decryption_key.as_deref() {
+                reader = reader.with_decryption_key(key);
+            }
+            reader.initialize()?;
+            Ok(reader)
+        })
+        .await?;
+        self.inner = Some(Arc::new(inner));
+        Ok(())
+    }
+
+    /// Get archive header information
+    pub fn header(&self) -> &FileHeader {
+        &self.header
+    }
+
+    /// Encryption mode from the header flags
+    ///
+    /// Available right after `open`, like `ArchiveReader::encryption_mode`.
+    pub fn encryption_mode(&self) -> EncryptionMode {
+        self.header.encryption_mode()
+    }
+
+    /// Whether the archive is encrypted, either as a whole or per file
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption_mode() != EncryptionMode::None
+    }
+
+    /// Whether a key must still be supplied with `with_decryption_key`
+    /// before files can be read
+    pub fn requires_key(&self) -> bool {
+        self.is_encrypted() && self.decryption_key.is_none()
+    }
+
+    /// Number of entries (0 before `initialize`)
+    pub fn entry_count(&self) -> usize {
+        self.inner.as_ref().map_or(0, |inner| inner.entry_count())
+    }
+
+    /// Paths of all entries in central directory order
+    pub fn list_files(&self) -> &[String] {
+        self.inner.as_ref().map_or(&[], |inner| inner.list_files())
+    }
+
+    /// Whether the archive holds `path`, matched like `get_entry`
+    pub fn contains(&self, path: &str) -> bool {
+        self.get_entry(path).is_some()
+    }
+
+    /// Entry metadata for `path`, matched like `ArchiveReader::get_entry`
+    pub fn get_entry(&self, path: &str) -> Option<&EntryInfo> {
+        self.inner.as_ref()?.get_entry(path)
+    }
+
+    /// Read, decompress and verify a file
+    pub async fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+        let inner = self
+            .inner
+            .as_ref()
+            .ok_or_else(|| EngramError::FileNotFound(path.to_string()))?;
+        let entry = inner
+            .get_entry(path)
+            .cloned()
+            .ok_or_else(|| EngramError::FileNotFound(path.to_string()))?;
+
+        // The decrypted payload is already in memory
+        if self.header.encryption_mode() == EncryptionMode::Archive {
+            let inner = Arc::clone(inner);
+            let path = entry.path;
+            return blocking(move || inner.read_file_at(&path)).await;
+        }
+
+        if entry.is_chunked() {
+            return self.read_chunked(inner, entry).await;
+        }
+        self.read_plain(inner, entry).await
+    }
+
+    /// `read_file` for an entry stored in one piece
+    async fn read_plain(&self, inner: &Arc<ArchiveReader>, entry: EntryInfo) -> Result<Vec<u8>> {
+        let dictionary = if entry.uses_zstd_dictionary() {
+            Some(self.load_zstd_dictionary(inner).await?)
+        } else {
+            None
+        };
+        self.read_entry(inner, entry, dictionary).await
+    }
+
+    /// `read_file` for a chunked entry: read its chunk list and every chunk,
+    /// then check and join them on the blocking pool
+    async fn read_chunked(&self, inner: &Arc<ArchiveReader>, entry: EntryInfo) -> Result<Vec<u8>> {
+        let stored = self
+            .read_stored(&entry)
+            .await
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))?;
+        let chunks = inner.decode_chunk_list(&entry, &stored)?;
+
+        let mut contents = Vec::with_capacity(chunks.len());
+        for chunk in &chunks {
+            let chunk_entry = inner
+                .lookup_chunk(&chunking::chunk_path(&chunk.hash))
+                .map_err(|e| e.in_entry(&entry.path, entry.data_offset))?;
+            contents.push(self.read_plain(inner, chunk_entry).await?);
+        }
+
+        blocking(move || {
+            let mut contents = contents.into_iter();
+            let mut output = Vec::with_capacity(preallocation(entry.uncompressed_size));
+            ArchiveReader::reassemble_chunks(
+                &entry,
+                &chunks,
+                |_| Ok(contents.next().unwrap_or_default()),
+                |data| {
+                    output.extend_from_slice(data);
+                    Ok(())
+                },
+            )
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))?;
+            Ok(output)
+        })
+        .await
+    }
+
+    /// Read an entry's stored bytes and decode them on the blocking pool
+    async fn read_entry(
+        &self,
+        inner: &Arc<ArchiveReader>,
+        entry: EntryInfo,
+        dictionary: Option<Arc<Vec<u8>>>,
+    ) -> Result<Vec<u8>> {
+        let stored = self
+            .read_stored(&entry)
+            .await
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))?;
+        let inner = Arc::clone(inner);
+        blocking(move || {
+            inner.decode_stored(&entry, &stored, dictionary.as_ref().map(|d| d.as_slice()))
+        })
+        .await
+    }
+
+    /// Bytes from an entry's data offset through the end of its payload,
+    /// LOCA header included
+    async fn read_stored(&self, entry: &EntryInfo) -> Result<Vec<u8>> {
+        let mut file = self.file.lock().await;
+        file.seek(SeekFrom::Start(entry.data_offset)).await?;
+
+        // v0.x archives store the payload alone
+        let mut stored = Vec::new();
+        if !self.header.is_legacy() {
+            stored.resize(LOCAL_HEADER_FIXED_SIZE, 0);
+            file.read_exact(&mut stored).await?;
+            let path_len = u16::from_le_bytes([
+                stored[LOCAL_PATH_LEN_OFFSET],
+                stored[LOCAL_PATH_LEN_OFFSET + 1],
+            ]);
+            // Path and null terminator; the length is checked when parsed
+            stored.resize(LOCAL_HEADER_FIXED_SIZE + path_len as usize + 1, 0);
+            file.read_exact(&mut stored[LOCAL_HEADER_FIXED_SIZE..])
+                .await?;
+        }
+
+        let start = stored.len();
+        stored.resize(start + entry.compressed_size as usize, 0);
+        file.read_exact(&mut stored[start..]).await?;
+        Ok(stored)
+    }
+
+    /// Load (once) the shared Zstd dictionary stored at `ZSTD_DICTIONARY_PATH`
+    async fn load_zstd_dictionary(&self, inner: &Arc<ArchiveReader>) -> Result<Arc<Vec<u8>>> {
+        if let Some(dictionary) = self.zstd_dictionary.get() {
+            return Ok(Arc::clone(dictionary));
+        }
+        let entry = inner
+            .get_entry(ZSTD_DICTIONARY_PATH)
+            .cloned()
+            .ok_or_else(|| {
+                EngramError::InvalidFormat(format!(
+                    "Entry uses a Zstd dictionary but {} is missing",
+                    ZSTD_DICTIONARY_PATH
+                ))
+            })?;
+
+        // Concurrent first reads may both load it; either copy is kept
+        let dictionary = Arc::new(self.read_entry(inner, entry, None).await?);
+        Ok(Arc::clone(self.zstd_dictionary.get_or_init(|| dictionary)))
+    }
+
+    /// Archive length and the regions `ArchiveReader::initialize` reads: the
+    /// header, central directory and trailer, or the whole file when it is
+    /// archive-encrypted
+    async fn read_metadata_regions(&self) -> Result<(u64, Vec<(u64, Vec<u8>)>)> {
+        let mut file = self.file.lock().await;
+        let len = file.metadata().await?.len();
+
+        if self.header.encryption_mode() == EncryptionMode::Archive {
+            let mut bytes = Vec::new();
+            file.seek(SeekFrom::Start(0)).await?;
+            file.read_to_end(&mut bytes).await?;
+            return Ok((len, vec![(0, bytes)]));
+        }
+
+        let mut regions = vec![(0, self.header_bytes.clone())];
+
+        // Out-of-range locations are left for `initialize` to report
+        let cd_offset = self.header.central_directory_offset;
+        let cd_end = cd_offset.checked_add(self.header.central_directory_size);
+        if cd_offset >= HEADER_SIZE as u64 && matches!(cd_end, Some(end) if end <= len) {
+            let size = self.header.central_directory_size as usize;
+            regions.push((cd_offset, read_at(&mut file, cd_offset, size).await?));
+        }
+
+        // End Record, preceded by the recipients block if there is one
+        if !self.header.is_legacy() && len >= (HEADER_SIZE + END_RECORD_SIZE) as u64 {
+            let endr_offset = len - END_RECORD_SIZE as u64;
+            let end_record = read_at(&mut file, endr_offset, END_RECORD_SIZE).await?;
+            let recipients_size = match EndRecord::read_from(&end_record[..]) {
+                Ok(record) if self.header.has_recipients() => record.recipients_size as u64,
+                _ => 0,
+            };
+            match endr_offset.checked_sub(recipients_size) {
+                Some(start) if recipients_size > 0 && start >= HEADER_SIZE as u64 => {
+                    let block = read_at(&mut file, start, recipients_size as usize).await?;
+                    regions.push((start, block));
+                }
+                _ => {}
+            }
+            regions.push((endr_offset, end_record));
+        }
+
+        Ok((len, regions))
+    }
+}
+
+/// Read `len` bytes at `offset`
+async fn read_at(file: &mut File, offset: u64, len: usize) -> Result<Vec<u8>> {
+    let mut buf = vec![0u8; len];
+    file.seek(SeekFrom::Start(offset)).await?;
+    file.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Run CPU-bound work on tokio's blocking pool, resuming its panics here
+async fn blocking<T, F>(work: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    match tokio::task::spawn_blocking(work).await {
+        Ok(result) => result,
+        Err(err) if err.is_panic() => std::panic::resume_unwind(err.into_panic()),
+        Err(err) => Err(EngramError::Other(format!("Blocking task failed: {}", err))),
+    }
+}
+
+/// Seekable source over preloaded regions of an archive
+///
+/// Reports the archive's full length; reads outside the loaded regions fail.
+struct RegionSource {
+    len: u64,
+    /// (offset, bytes), non-overlapping
+    regions: Vec<(u64, Vec<u8>)>,
+    position: u64,
+}
+
+impl RegionSource {
+    fn new(len: u64, regions: Vec<(u64, Vec<u8>)>) -> Self {
+        Self {
+            len,
+            regions,
+            position: 0,
+        }
+    }
+}
+
+impl Read for RegionSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+        let position = self.position;
+        let (offset, bytes) = self
+            .regions
+            .iter()
+            .find(|(offset, bytes)| position >= *offset && position < offset + bytes.len() as u64)
+            .ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!("Archive offset {} was not preloaded", position),
+                )
+            })?;
+        let available = &bytes[(position - offset) as usize..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for RegionSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(delta) => self.len.checked_add_signed(delta),
+            SeekFrom::Current(delta) => self.position.checked_add_signed(delta),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidInput, "Invalid seek position")
+        })?;
+        Ok(self.position)
+    }
+}
@@ -0,0 +1,71 @@
+use crate::error::{EngramError, Result};
+use std::collections::HashMap;
+
+/// Archive path of the BLAKE3 digest table (stored uncompressed)
+///
+/// Written by writers with `ArchiveWriter::with_blake3`, which also set
+/// `HEADER_FLAG_BLAKE3`. Holds one `[data_offset: uint64][blake3: 32 bytes]`
+/// record per stored payload, in offset order: the BLAKE3 of the entry's
+/// uncompressed content. Entries sharing a payload share its record.
+///
+/// The table is an ordinary, unauthenticated entry. It catches payloads that
+/// were changed but still match their CRC32, but not an attacker who also
+/// rewrites the table; that is the same limit as the SHA-256 prefix. Use a
+/// signature when edits must be detected.
+pub const BLAKE3_TABLE_PATH: &str = ".engram/blake3.tbl";
+
+/// Bytes per record of `BLAKE3_TABLE_PATH`
+const RECORD_SIZE: usize = 40;
+
+/// Encode `(data_offset, digest)` records, sorted by offset
+pub(crate) fn table_bytes(records: &[(u64, [u8; 32])]) -> Vec<u8> {
+    let mut records = records.to_vec();
+    records.sort_unstable();
+    records.dedup_by_key(|&mut (offset, _)| offset);
+    let mut bytes = Vec::with_capacity(records.len() * RECORD_SIZE);
+    for (offset, digest) in records {
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        bytes.extend_from_slice(&digest);
+    }
+    bytes
+}
+
+/// Parse the table into data offset -> BLAKE3 of the uncompressed content
+pub(crate) fn parse_table(bytes: &[u8]) -> Result<HashMap<u64, [u8; 32]>> {
+    let records = bytes.chunks_exact(RECORD_SIZE);
+    if !records.remainder().is_empty() {
+        return Err(EngramError::InvalidFormat(format!(
+            "{} is {} bytes, not a multiple of {}",
+            BLAKE3_TABLE_PATH,
+            bytes.len(),
+            RECORD_SIZE
+        )));
+    }
+    Ok(records
+        .map(|record| {
+            (
+                u64::from_le_bytes(record[..8].try_into().unwrap()),
+                record[8..].try_into().unwrap(),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_round_trip() {
+        let records = [(500, [0xAB; 32]), (64, [7; 32]), (500, [0xAB; 32])];
+        let bytes = table_bytes(&records);
+        assert_eq!(bytes.len(), 2 * RECORD_SIZE);
+        assert_eq!(bytes[..8], 64u64.to_le_bytes());
+
+        let table = parse_table(&bytes).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table[&500], [0xAB; 32]);
+        assert!(parse_table(&bytes[1..]).is_err());
+        assert!(parse_table(&[]).unwrap().is_empty());
+    }
+}
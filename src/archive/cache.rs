@@ -0,0 +1,91 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Counters for the entry cache (see `ArchiveReader::with_cache`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Reads served from the cache
+    pub hits: u64,
+    /// Reads that had to read and decode the entry
+    pub misses: u64,
+    /// Entries dropped to stay within the byte limit
+    pub evictions: u64,
+    /// Decompressed bytes currently cached
+    pub bytes: usize,
+    /// Entries currently cached
+    pub entries: usize,
+}
+
+/// Least-recently-used cache of decompressed entries, bounded by total bytes
+pub(crate) struct EntryCache {
+    max_bytes: usize,
+    /// Stored path -> content and its last use
+    entries: HashMap<String, (Arc<Vec<u8>>, u64)>,
+    /// Last use -> stored path, oldest first
+    recency: BTreeMap<u64, String>,
+    clock: u64,
+    stats: CacheStats,
+}
+
+impl EntryCache {
+    pub(crate) fn new(max_bytes: usize) -> Self {
+        Self {
+            max_bytes,
+            entries: HashMap::new(),
+            recency: BTreeMap::new(),
+            clock: 0,
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Byte limit the cache was created with
+    pub(crate) fn max_bytes(&self) -> usize {
+        self.max_bytes
+    }
+
+    pub(crate) fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Cached content of `path`, marking it as most recently used
+    ///
+    /// Counts a hit or a miss.
+    pub(crate) fn get(&mut self, path: &str) -> Option<Arc<Vec<u8>>> {
+        self.clock += 1;
+        let Some((data, last_used)) = self.entries.get_mut(path) else {
+            self.stats.misses += 1;
+            return None;
+        };
+        self.recency.remove(last_used);
+        *last_used = self.clock;
+        self.recency.insert(self.clock, path.to_string());
+        self.stats.hits += 1;
+        Some(Arc::clone(data))
+    }
+
+    /// Cache `data` for `path`, evicting the least recently used entries to
+    /// make room
+    ///
+    /// Content larger than the whole cache is not stored.
+    pub(crate) fn insert(&mut self, path: String, data: Arc<Vec<u8>>) {
+        if data.len() > self.max_bytes || self.entries.contains_key(&path) {
+            return;
+        }
+
+        while self.stats.bytes + data.len() > self.max_bytes {
+            let Some((_, oldest)) = self.recency.pop_first() else {
+                break;
+            };
+            if let Some((evicted, _)) = self.entries.remove(&oldest) {
+                self.stats.bytes -= evicted.len();
+                self.stats.evictions += 1;
+            }
+        }
+
+        self.clock += 1;
+        self.stats.bytes += data.len();
+        self.recency.insert(self.clock, path.clone());
+        self.entries.insert(path, (data, self.clock));
+        self.stats.entries = self.entries.len();
+    }
+}
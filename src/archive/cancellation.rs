@@ -0,0 +1,60 @@
+use crate::error::{EngramError, Result};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Bytes copied between cancellation checks during bulk I/O (1MB)
+pub(crate) const CANCEL_CHECK_INTERVAL: usize = 1024 * 1024;
+
+/// Shared flag for aborting long-running archive operations
+///
+/// Clones share the same flag, so a token can be handed to an
+/// `ArchiveWriter` or `ArchiveReader` and cancelled from another thread.
+/// Cancelled operations return `EngramError::Cancelled`. Once cancelled, a
+/// token stays cancelled.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Request cancellation
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Return `EngramError::Cancelled` if cancellation was requested
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            Err(EngramError::Cancelled)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_state() {
+        let token = CancellationToken::new();
+        let clone = token.clone();
+        assert!(!clone.is_cancelled());
+        assert!(token.check().is_ok());
+
+        token.cancel();
+        assert!(clone.is_cancelled());
+        assert!(matches!(clone.check(), Err(EngramError::Cancelled)));
+    }
+}
@@ -0,0 +1,281 @@
+use crate::error::{EngramError, Result};
+
+/// Archive path prefix of the chunks written by `ArchiveWriter::with_cdc_dedup`
+///
+/// Each chunk is stored once, as an ordinary entry named by the hex SHA-256
+/// of its content, e.g. `.engram/chunks/9f86d081…`.
+pub const CHUNKS_PREFIX: &str = ".engram/chunks/";
+
+/// Smallest `ChunkerConfig::min_size`, the width of the rolling hash window
+pub const MIN_CHUNK_SIZE: usize = 64;
+
+/// Signature at the start of a chunk list
+const CHUNK_LIST_SIGNATURE: [u8; 4] = *b"CHNK";
+
+/// Bytes per chunk list record: SHA-256 and u64 length
+const CHUNK_RECORD_SIZE: usize = 40;
+
+/// Chunk sizes for content-defined chunking (`ArchiveWriter::with_cdc_dedup`)
+///
+/// Chunk boundaries are placed where a rolling hash of the content matches
+/// (FastCDC), so an insertion or deletion only changes the chunks around it
+/// and the rest still deduplicate. Chunks are at least `min_size` and at
+/// most `max_size` bytes, `avg_size` on average; only files larger than
+/// `max_size` are chunked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkerConfig {
+    pub min_size: usize,
+    pub avg_size: usize,
+    pub max_size: usize,
+}
+
+impl ChunkerConfig {
+    /// Chunks of `avg_size` on average, between a quarter and four times that
+    pub fn new(avg_size: usize) -> Self {
+        Self {
+            min_size: avg_size / 4,
+            avg_size,
+            max_size: avg_size.saturating_mul(4),
+        }
+    }
+
+    /// Fail with `InvalidOptions` unless `MIN_CHUNK_SIZE <= min <= avg <= max`
+    pub(crate) fn validate(&self) -> Result<()> {
+        if MIN_CHUNK_SIZE <= self.min_size
+            && self.min_size <= self.avg_size
+            && self.avg_size <= self.max_size
+        {
+            return Ok(());
+        }
+        Err(EngramError::InvalidOptions(format!(
+            "Chunk sizes must satisfy {} <= min <= avg <= max, got {} / {} / {}",
+            MIN_CHUNK_SIZE, self.min_size, self.avg_size, self.max_size
+        )))
+    }
+}
+
+impl Default for ChunkerConfig {
+    /// 1 MiB on average, 256 KiB to 4 MiB
+    fn default() -> Self {
+        Self::new(1 << 20)
+    }
+}
+
+/// Random per-byte values of the gear hash
+///
+/// Generated with SplitMix64 from a fixed seed. Changing them moves chunk
+/// boundaries, which only affects how well new archives deduplicate against
+/// their own content; readers never compute boundaries.
+const GEAR: [u64; 256] = gear_table();
+
+const fn gear_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+}
+
+/// Mask of the `bits` highest bits, which depend on the last 64 bytes hashed
+fn high_bits(bits: u32) -> u64 {
+    match bits {
+        0 => 0,
+        bits => u64::MAX << (64 - bits.min(64)),
+    }
+}
+
+/// Length of the first chunk of `data`
+///
+/// Normalized chunking: before `avg_size` a boundary needs one more zero
+/// bit than the average calls for, after it one fewer, which keeps chunk
+/// sizes close to the average.
+fn cut_point(data: &[u8], config: &ChunkerConfig) -> usize {
+    if data.len() <= config.min_size {
+        return data.len();
+    }
+    let end = data.len().min(config.max_size);
+    let normal = end.min(config.avg_size);
+    let bits = config.avg_size.ilog2();
+    let strict = high_bits(bits + 1);
+    let loose = high_bits(bits.saturating_sub(1));
+
+    let mut hash = 0u64;
+    for (i, &byte) in data.iter().enumerate().take(end).skip(config.min_size) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        let mask = if i < normal { strict } else { loose };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// Split `data` into content-defined chunks
+pub(crate) fn split<'a>(
+    data: &'a [u8],
+    config: &ChunkerConfig,
+) -> impl Iterator<Item = &'a [u8]> + 'a {
+    let config = *config;
+    let mut rest = data;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (chunk, tail) = rest.split_at(cut_point(rest, &config));
+        rest = tail;
+        Some(chunk)
+    })
+}
+
+/// Archive path of the chunk with content digest `hash`
+pub(crate) fn chunk_path(hash: &[u8; 32]) -> String {
+    format!("{}{}", CHUNKS_PREFIX, hex::encode(hash))
+}
+
+/// One chunk of a chunked entry, in content order
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ChunkRef {
+    /// SHA-256 of the chunk's content, which also names its entry
+    pub hash: [u8; 32],
+    pub len: u64,
+}
+
+/// Encode a chunk list: `"CHNK"`, a u32 count, then per chunk its SHA-256
+/// and u64 length
+pub(crate) fn encode_chunk_list(chunks: &[ChunkRef]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(8 + chunks.len() * CHUNK_RECORD_SIZE);
+    bytes.extend_from_slice(&CHUNK_LIST_SIGNATURE);
+    bytes.extend_from_slice(&(chunks.len() as u32).to_le_bytes());
+    for chunk in chunks {
+        bytes.extend_from_slice(&chunk.hash);
+        bytes.extend_from_slice(&chunk.len.to_le_bytes());
+    }
+    bytes
+}
+
+/// Parse a chunk list written by `encode_chunk_list`
+pub(crate) fn parse_chunk_list(bytes: &[u8]) -> Result<Vec<ChunkRef>> {
+    let invalid = |reason: &str| EngramError::InvalidFormat(format!("Chunk list {}", reason));
+    if bytes.len() < 8 || bytes[..4] != CHUNK_LIST_SIGNATURE {
+        return Err(invalid("has no CHNK signature"));
+    }
+    let count = u32::from_le_bytes(bytes[4..8].try_into().unwrap()) as usize;
+    let records = &bytes[8..];
+    if records.len() != count.saturating_mul(CHUNK_RECORD_SIZE) {
+        return Err(invalid("length does not match its chunk count"));
+    }
+    Ok(records
+        .chunks_exact(CHUNK_RECORD_SIZE)
+        .map(|record| ChunkRef {
+            hash: record[..32].try_into().unwrap(),
+            len: u64::from_le_bytes(record[32..].try_into().unwrap()),
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Deterministic pseudo-random bytes
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chunk_sizes_within_bounds() {
+        let config = ChunkerConfig::new(4096);
+        let data = noise(1 << 20, 1);
+        let chunks: Vec<&[u8]> = split(&data, &config).collect();
+
+        assert_eq!(chunks.concat(), data);
+        let (last, rest) = chunks.split_last().unwrap();
+        assert!(!last.is_empty() && last.len() <= config.max_size);
+        for chunk in rest {
+            assert!(chunk.len() >= config.min_size && chunk.len() <= config.max_size);
+        }
+        // Close to the configured average
+        let average = data.len() / chunks.len();
+        assert!((2048..8192).contains(&average), "average {}", average);
+    }
+
+    #[test]
+    fn test_boundaries_follow_content() {
+        let config = ChunkerConfig::new(4096);
+        let data = noise(256 * 1024, 2);
+        let mut shifted = data.clone();
+        shifted.splice(100_000..100_000, *b"inserted");
+
+        let original: Vec<&[u8]> = split(&data, &config).collect();
+        let edited: Vec<&[u8]> = split(&shifted, &config).collect();
+        let shared = edited
+            .iter()
+            .filter(|chunk| original.contains(chunk))
+            .count();
+        // Only the chunks around the insertion differ
+        assert!(
+            shared + 3 >= original.len(),
+            "{} of {}",
+            shared,
+            original.len()
+        );
+    }
+
+    #[test]
+    fn test_small_input_is_one_chunk() {
+        let config = ChunkerConfig::new(4096);
+        assert_eq!(split(b"", &config).count(), 0);
+        let data = noise(config.min_size, 3);
+        assert_eq!(split(&data, &config).collect::<Vec<_>>(), [&data[..]]);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(ChunkerConfig::default().validate().is_ok());
+        assert!(ChunkerConfig::new(64).validate().is_err());
+        let inverted = ChunkerConfig {
+            min_size: 8192,
+            ..ChunkerConfig::new(4096)
+        };
+        assert!(inverted.validate().is_err());
+    }
+
+    #[test]
+    fn test_chunk_list_round_trip() {
+        let chunks = [
+            ChunkRef {
+                hash: [1; 32],
+                len: 5,
+            },
+            ChunkRef {
+                hash: [2; 32],
+                len: u64::MAX,
+            },
+        ];
+        let bytes = encode_chunk_list(&chunks);
+        assert_eq!(bytes.len(), 8 + 2 * CHUNK_RECORD_SIZE);
+        assert_eq!(parse_chunk_list(&bytes).unwrap(), chunks);
+        assert!(parse_chunk_list(&encode_chunk_list(&[]))
+            .unwrap()
+            .is_empty());
+
+        assert!(parse_chunk_list(&bytes[..bytes.len() - 1]).is_err());
+        assert!(parse_chunk_list(b"NOPE\0\0\0\0").is_err());
+        assert!(parse_chunk_list(b"CHN").is_err());
+    }
+}
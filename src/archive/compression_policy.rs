@@ -0,0 +1,60 @@
+use crate::archive::format::CompressionMethod;
+use crate::archive::writer::ArchiveWriter;
+
+/// Chooses the compression method for files added without an explicit one
+///
+/// Install one with `ArchiveWriter::with_compression_policy` to encode
+/// knowledge about a workload that the built-in extension heuristics lack.
+/// `add_file`, `add_file_with_metadata`, `add_file_from_disk` and
+/// `import_zip` consult it; `add_file_with_compression` does not.
+/// The method returned is a request: data that does not shrink is still
+/// stored uncompressed.
+///
+/// ```no_run
+/// use engram_rs::{ArchiveWriter, CompressionMethod, CompressionPolicy, DefaultPolicy};
+///
+/// struct NdjsonPolicy;
+///
+/// impl CompressionPolicy for NdjsonPolicy {
+///     fn choose(&self, path: &str, data: &[u8]) -> CompressionMethod {
+///         if path.ends_with(".ndjson") {
+///             CompressionMethod::Zstd
+///         } else {
+///             DefaultPolicy.choose(path, data)
+///         }
+///     }
+/// }
+///
+/// let mut writer = ArchiveWriter::create("events.eng")?
+///     .with_zstd_level(19)
+///     .with_compression_policy(Box::new(NdjsonPolicy));
+/// writer.add_file("2024/01.ndjson", b"{}\n")?;
+/// writer.finalize()?;
+/// # Ok::<(), engram_rs::EngramError>(())
+/// ```
+pub trait CompressionPolicy: Send {
+    /// Compression method for the file at `path` (normalized) holding `data`
+    fn choose(&self, path: &str, data: &[u8]) -> CompressionMethod;
+}
+
+/// The built-in heuristics: small files stay uncompressed, already
+/// compressed formats are stored, text and databases use Zstd and
+/// everything else LZ4
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultPolicy;
+
+impl CompressionPolicy for DefaultPolicy {
+    fn choose(&self, path: &str, data: &[u8]) -> CompressionMethod {
+        ArchiveWriter::select_compression(path, data.len())
+    }
+}
+
+/// Requests the same method for every file
+#[derive(Debug, Clone, Copy)]
+pub struct ForceMethod(pub CompressionMethod);
+
+impl CompressionPolicy for ForceMethod {
+    fn choose(&self, _path: &str, _data: &[u8]) -> CompressionMethod {
+        self.0
+    }
+}
@@ -0,0 +1,67 @@
+use crate::error::{EngramError, Result};
+
+/// Archive path of the shared Zstd dictionary entry (stored uncompressed)
+pub const ZSTD_DICTIONARY_PATH: &str = ".engram/zstd.dict";
+
+/// Train a Zstd dictionary from sample file contents
+///
+/// `dict_size` is the maximum dictionary size in bytes; a few KB to ~100KB is
+/// typical. Training needs a reasonable number of samples (hundreds) and fails
+/// with `EngramError::CompressionFailed` if they are too few or too small.
+pub fn train_dictionary(samples: &[&[u8]], dict_size: usize) -> Result<Vec<u8>> {
+    zstd::dict::from_samples(samples, dict_size).map_err(|e| {
+        EngramError::CompressionFailed(format!("Zstd dictionary training failed: {}", e))
+    })
+}
+
+/// Compress with Zstd at `level` using a shared dictionary
+pub(crate) fn compress_with_dictionary(
+    data: &[u8],
+    dictionary: &[u8],
+    level: i32,
+) -> Result<Vec<u8>> {
+    zstd::bulk::Compressor::with_dictionary(level, dictionary)
+        .and_then(|mut compressor| compressor.compress(data))
+        .map_err(|e| {
+            EngramError::CompressionFailed(format!("Zstd dictionary compression failed: {}", e))
+        })
+}
+
+/// Decompress Zstd data produced with a shared dictionary
+///
+/// `size` is the expected uncompressed size; larger output is rejected.
+pub(crate) fn decompress_with_dictionary(
+    data: &[u8],
+    dictionary: &[u8],
+    size: usize,
+) -> Result<Vec<u8>> {
+    zstd::bulk::Decompressor::with_dictionary(dictionary)
+        .and_then(|mut decompressor| decompressor.decompress(data, size))
+        .map_err(|e| {
+            EngramError::decompression_failed(format!(
+                "Zstd dictionary decompression failed: {}",
+                e
+            ))
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::format::DEFAULT_ZSTD_LEVEL;
+
+    #[test]
+    fn test_dictionary_roundtrip() {
+        let samples: Vec<Vec<u8>> = (0..500)
+            .map(|i| format!(r#"{{"id":{},"kind":"sample","tags":["a","b"]}}"#, i).into_bytes())
+            .collect();
+        let refs: Vec<&[u8]> = samples.iter().map(|s| s.as_slice()).collect();
+        let dictionary = train_dictionary(&refs, 4096).unwrap();
+
+        let data = br#"{"id":9999,"kind":"sample","tags":["a","b"]}"#;
+        let compressed = compress_with_dictionary(data, &dictionary, DEFAULT_ZSTD_LEVEL).unwrap();
+        let decompressed =
+            decompress_with_dictionary(&compressed, &dictionary, data.len()).unwrap();
+        assert_eq!(decompressed, data);
+    }
+}
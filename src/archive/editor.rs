@@ -0,0 +1,261 @@
+use crate::archive::blake3_table::BLAKE3_TABLE_PATH;
+use crate::archive::dictionary::ZSTD_DICTIONARY_PATH;
+use crate::archive::entry_metadata::ENTRY_METADATA_PATH;
+use crate::archive::format::{is_reserved_path, EncryptionMode, EntryInfo, MAX_PATH_LENGTH};
+use crate::archive::reader::ArchiveReader;
+use crate::archive::stored_crc::COMPRESSED_CRC_PATH;
+use crate::archive::writer::{ArchiveWriter, EntryAttributes};
+use crate::archive::{normalize_path, validate_path};
+use crate::error::{EngramError, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// One entry of the edited archive
+struct EditedEntry {
+    /// Central directory entry in the source archive
+    source: EntryInfo,
+    /// Path in the edited archive
+    path: String,
+    /// New content set by `replace`
+    replacement: Option<Vec<u8>>,
+}
+
+/// Removes, renames and replaces entries of an existing archive
+///
+/// Edits are recorded in memory and applied by `save_to`, which writes a new
+/// archive. Entries that are not replaced keep their stored payload byte for
+/// byte: nothing is decompressed or recompressed, so compression method,
+/// CRC32 and compressed size carry over. Renamed entries get a new LOCA header
+/// with the new path around the same payload.
+///
+/// Encrypted archives are not supported.
+///
+/// ```no_run
+/// use engram_rs::ArchiveEditor;
+///
+/// let mut editor = ArchiveEditor::open("app.eng")?;
+/// editor.remove("secrets/token.txt")?;
+/// editor.rename("docs/old.md", "docs/new.md")?;
+/// editor.save_to("app-clean.eng")?;
+/// # Ok::<(), engram_rs::EngramError>(())
+/// ```
+pub struct ArchiveEditor {
+    source_path: PathBuf,
+    reader: ArchiveReader,
+    /// Remaining entries in central directory order
+    entries: Vec<EditedEntry>,
+}
+
+impl ArchiveEditor {
+    /// Open an archive for editing
+    pub fn open<P: AsRef<Path>>(src: P) -> Result<Self> {
+        let source_path = src.as_ref().to_path_buf();
+        let mut reader = ArchiveReader::open(&source_path)?;
+        if reader.header().encryption_mode() != EncryptionMode::None {
+            return Err(EngramError::Other(
+                "ArchiveEditor does not support encrypted archives".to_string(),
+            ));
+        }
+        reader.initialize()?;
+
+        let entries = reader
+            .list_files()
+            .iter()
+            .filter_map(|path| reader.get_entry(path))
+            .map(|entry| EditedEntry {
+                source: entry.clone(),
+                path: entry.path.clone(),
+                replacement: None,
+            })
+            .collect();
+
+        Ok(Self {
+            source_path,
+            reader,
+            entries,
+        })
+    }
+
+    /// Paths of the edited archive, in central directory order
+    pub fn list_files(&self) -> Vec<&str> {
+        self.entries
+            .iter()
+            .map(|entry| entry.path.as_str())
+            .collect()
+    }
+
+    /// Drop an entry
+    pub fn remove(&mut self, path: &str) -> Result<()> {
+        let index = self.position(path)?;
+        self.check_dictionary_kept(index)?;
+        self.entries.remove(index);
+        Ok(())
+    }
+
+    /// Move an entry to a new path
+    ///
+    /// The new path is normalized and validated like paths given to
+    /// `ArchiveWriter::add_file` (reserved paths are rejected), and must not
+    /// already be in use.
+    pub fn rename(&mut self, old: &str, new: &str) -> Result<()> {
+        let index = self.position(old)?;
+        let new_path = normalize_path(new);
+        validate_path(&new_path)?;
+        if new_path.len() > MAX_PATH_LENGTH {
+            return Err(EngramError::PathError(format!(
+                "Path too long: {} bytes (max {})",
+                new_path.len(),
+                MAX_PATH_LENGTH
+            )));
+        }
+        if is_reserved_path(&new_path) {
+            return Err(EngramError::ReservedPath(new_path));
+        }
+        if self.entries.iter().any(|entry| entry.path == new_path) {
+            return Err(EngramError::PathError(format!(
+                "Path already exists: {}",
+                new_path
+            )));
+        }
+        self.check_dictionary_kept(index)?;
+
+        self.entries[index].path = new_path;
+        Ok(())
+    }
+
+    /// Give a file entry new content
+    ///
+    /// The content is compressed again when saving, choosing the method like
+    /// `ArchiveWriter::add_file`. The entry's mode and MIME type are kept and its modified
+    /// time is set to the current time.
+    pub fn replace(&mut self, path: &str, data: &[u8]) -> Result<()> {
+        let index = self.position(path)?;
+        let entry = &mut self.entries[index];
+        if entry.source.is_directory() {
+            return Err(EngramError::PathError(format!(
+                "Cannot replace directory entry: {}",
+                entry.path
+            )));
+        }
+        if entry.path == ZSTD_DICTIONARY_PATH {
+            return Err(EngramError::PathError(format!(
+                "Cannot replace {}",
+                ZSTD_DICTIONARY_PATH
+            )));
+        }
+
+        entry.replacement = Some(data.to_vec());
+        Ok(())
+    }
+
+    /// Write the edited archive to `dest`
+    ///
+    /// The header's content version, label and sorted-directory setting are
+    /// carried over, as is recording stored-payload checksums and BLAKE3
+    /// digests (which are recorded again for the new offsets). Custom entry metadata (see
+    /// `ArchiveReader::entry_metadata`) follows renamed entries. `dest` must
+    /// not be the source archive.
+    pub fn save_to<P: AsRef<Path>>(&mut self, dest: P) -> Result<()> {
+        let dest = dest.as_ref();
+        if dest.exists() && same_file(dest, &self.source_path)? {
+            return Err(EngramError::PathError(
+                "Cannot save an edited archive over its source".to_string(),
+            ));
+        }
+
+        let header = self.reader.header().clone();
+        let mut writer = ArchiveWriter::create(dest)?
+            .with_content_version(header.content_version)
+            .with_label(header.label.0);
+        if header.has_sorted_directory() {
+            writer = writer.with_sorted_directory();
+        }
+        if self.reader.contains(COMPRESSED_CRC_PATH) {
+            writer = writer.with_compressed_checksums(true);
+        }
+        if header.has_blake3_digests() {
+            writer = writer.with_blake3(true);
+        }
+
+        // Source LOCA offset -> offset of its copy, for entries sharing data
+        let mut copied: HashMap<u64, u64> = HashMap::new();
+        for edited in &self.entries {
+            // Offsets and paths change, so the writer records these afresh
+            if edited.path == COMPRESSED_CRC_PATH
+                || edited.path == ENTRY_METADATA_PATH
+                || edited.path == BLAKE3_TABLE_PATH
+            {
+                continue;
+            }
+            if let Some(metadata) = self.reader.entry_metadata(&edited.source.path)? {
+                writer.set_entry_metadata(&edited.path, &metadata.extra)?;
+            }
+            let entry = EntryInfo {
+                path: edited.path.clone(),
+                ..edited.source.clone()
+            };
+
+            if let Some(data) = &edited.replacement {
+                let compression = ArchiveWriter::select_compression(&entry.path, data.len());
+                let attributes = EntryAttributes {
+                    mode: entry.mode,
+                    mime_id: entry.mime_id,
+                    ..EntryAttributes::now()
+                };
+                writer.write_entry(&entry.path, data, compression, attributes)?;
+                continue;
+            }
+
+            if !self.reader.has_current_layout(&edited.source) {
+                let data = self.reader.read_file(&edited.source.path)?;
+                writer.copy_entry(&entry, &data)?;
+                continue;
+            }
+
+            match copied.get(&entry.data_offset) {
+                Some(&offset) => writer.add_shared_entry(&entry, offset),
+                None => {
+                    let payload = self.reader.read_stored(&edited.source)?;
+                    let offset = writer.write_stored_entry(&entry, &payload)?;
+                    copied.insert(entry.data_offset, offset);
+                }
+            }
+        }
+
+        writer.finalize()?;
+        Ok(())
+    }
+
+    /// Index of the entry at `path` (matched after normalizing separators)
+    fn position(&self, path: &str) -> Result<usize> {
+        let normalized = normalize_path(path);
+        let canonical = normalized.trim_start_matches('/');
+        self.entries
+            .iter()
+            .position(|entry| entry.path == path || entry.path == canonical)
+            .ok_or_else(|| EngramError::FileNotFound(path.to_string()))
+    }
+
+    /// Refuse to drop or move the shared dictionary while entries still need it
+    fn check_dictionary_kept(&self, index: usize) -> Result<()> {
+        if self.entries[index].path != ZSTD_DICTIONARY_PATH {
+            return Ok(());
+        }
+        let in_use = self
+            .entries
+            .iter()
+            .any(|entry| entry.replacement.is_none() && entry.source.uses_zstd_dictionary());
+        if in_use {
+            return Err(EngramError::PathError(format!(
+                "{} is required by dictionary-compressed entries",
+                ZSTD_DICTIONARY_PATH
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Whether two existing paths refer to the same file
+fn same_file(a: &Path, b: &Path) -> Result<bool> {
+    Ok(std::fs::canonicalize(a)? == std::fs::canonicalize(b)?)
+}
@@ -1,5 +1,6 @@
 use crate::error::{EngramError, Result};
-use std::io::{Read, Write};
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, SeekFrom, Write};
 
 /// ENDR signature for End of Central Directory Record
 pub const END_RECORD_SIGNATURE: [u8; 4] = [0x45, 0x4E, 0x44, 0x52]; // "ENDR"
@@ -21,8 +22,11 @@ pub const END_RECORD_SIZE: usize = 64;
 /// - Central Directory Size: uint64 (8 bytes)
 /// - Entry Count: uint32 (4 bytes)
 /// - Archive CRC32: uint32 (4 bytes)
-/// - Reserved: 32 bytes
-#[derive(Debug, Clone)]
+/// - Content Version: uint32 (4 bytes, copy of the header field)
+/// - Recipients Size: uint32 (4 bytes, length of the recipients block before the ENDR)
+/// - Signature Size: uint32 (4 bytes, length of the signature block before the recipients block)
+/// - Reserved: 20 bytes
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EndRecord {
     pub version_major: u16,
     pub version_minor: u16,
@@ -30,6 +34,12 @@ pub struct EndRecord {
     pub central_directory_size: u64,
     pub entry_count: u32,
     pub archive_crc32: u32,
+    /// Application schema version, mirrored from the header (0 in older archives)
+    pub content_version: u32,
+    /// Length of the recipients block stored right before the ENDR (0 if none)
+    pub recipients_size: u32,
+    /// Length of the signature block stored before the recipients block (0 if none)
+    pub signature_size: u32,
 }
 
 impl EndRecord {
@@ -49,6 +59,9 @@ impl EndRecord {
             central_directory_size,
             entry_count,
             archive_crc32,
+            content_version: 0,
+            recipients_size: 0,
+            signature_size: 0,
         }
     }
 
@@ -82,9 +95,21 @@ impl EndRecord {
         writer.write_all(&self.archive_crc32.to_le_bytes())?;
         bytes_written += 4;
 
-        // Reserved (32 bytes)
-        writer.write_all(&[0u8; 32])?;
-        bytes_written += 32;
+        // Content version
+        writer.write_all(&self.content_version.to_le_bytes())?;
+        bytes_written += 4;
+
+        // Recipients block size
+        writer.write_all(&self.recipients_size.to_le_bytes())?;
+        bytes_written += 4;
+
+        // Signature block size
+        writer.write_all(&self.signature_size.to_le_bytes())?;
+        bytes_written += 4;
+
+        // Reserved (20 bytes)
+        writer.write_all(&[0u8; 20])?;
+        bytes_written += 20;
 
         Ok(bytes_written)
     }
@@ -114,8 +139,17 @@ impl EndRecord {
         // Read archive CRC32
         let archive_crc32 = read_u32(&mut reader)?;
 
+        // Read content version (zero in archives written before it was mirrored)
+        let content_version = read_u32(&mut reader)?;
+
+        // Read recipients block size (zero in archives without recipients)
+        let recipients_size = read_u32(&mut reader)?;
+
+        // Read signature block size (zero in unsigned archives)
+        let signature_size = read_u32(&mut reader)?;
+
         // Skip reserved bytes
-        let mut reserved = [0u8; 32];
+        let mut reserved = [0u8; 20];
         reader.read_exact(&mut reserved)?;
 
         Ok(Self {
@@ -125,9 +159,27 @@ impl EndRecord {
             central_directory_size,
             entry_count,
             archive_crc32,
+            content_version,
+            recipients_size,
+            signature_size,
         })
     }
 
+    /// Read the end record from the last 64 bytes of an archive
+    ///
+    /// A quick way to get the entry count or content version without
+    /// reading the header or central directory.
+    pub fn read_from_end<R: Read + Seek>(mut reader: R) -> Result<Self> {
+        let len = reader.seek(SeekFrom::End(0))?;
+        if len < END_RECORD_SIZE as u64 {
+            return Err(EngramError::InvalidFormat(
+                "Archive too small to contain ENDR record".to_string(),
+            ));
+        }
+        reader.seek(SeekFrom::Start(len - END_RECORD_SIZE as u64))?;
+        Self::read_from(reader)
+    }
+
     /// Validate end record matches header
     pub fn validate_against_header(
         &self,
@@ -207,6 +259,12 @@ mod tests {
             10,         // entry_count
             0xDEADBEEF, // archive_crc32
         );
+        let record = EndRecord {
+            content_version: 42,
+            recipients_size: 154,
+            signature_size: 112,
+            ..record
+        };
 
         let mut buf = Vec::new();
         let written = record.write_to(&mut buf).unwrap();
@@ -224,6 +282,21 @@ mod tests {
         assert_eq!(parsed.central_directory_size, record.central_directory_size);
         assert_eq!(parsed.entry_count, record.entry_count);
         assert_eq!(parsed.archive_crc32, record.archive_crc32);
+        assert_eq!(parsed.content_version, 42);
+        assert_eq!(parsed.recipients_size, 154);
+        assert_eq!(parsed.signature_size, 112);
+    }
+
+    #[test]
+    fn test_read_from_end() {
+        let mut buf = vec![0xAAu8; 100];
+        EndRecord::new(1, 0, 64, 320, 1, 0)
+            .write_to(&mut buf)
+            .unwrap();
+
+        let parsed = EndRecord::read_from_end(std::io::Cursor::new(&buf)).unwrap();
+        assert_eq!(parsed.entry_count, 1);
+        assert!(EndRecord::read_from_end(std::io::Cursor::new(&buf[..10])).is_err());
     }
 
     #[test]
@@ -0,0 +1,107 @@
+use crate::error::{EngramError, Result};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use std::io::Write;
+
+/// Signature block signature
+pub const SIGNATURE_BLOCK_SIGNATURE: [u8; 4] = [0x53, 0x49, 0x47, 0x4E]; // "SIGN"
+
+/// Signature block size in bytes (fixed)
+pub const SIGNATURE_BLOCK_SIZE: usize = 112;
+
+/// Algorithm id for Ed25519
+const ALGORITHM_ED25519: u16 = 1;
+
+/// Signed trailer over the End Record and central directory
+/// (see `ArchiveWriter::with_endr_signature`)
+///
+/// Stored between the central directory and the recipients block, that is
+/// immediately before the ENDR when there are no recipients; the ENDR records
+/// its size.
+///
+/// Structure (112 bytes fixed):
+/// - Signature: "SIGN" (4 bytes)
+/// - Algorithm: uint16 (1 = Ed25519)
+/// - Reserved: 2 bytes
+/// - Timestamp: uint64 (Unix epoch seconds, covered by the signature)
+/// - Public Key: 32 bytes
+/// - Signature: 64 bytes, over the first 16 bytes of the block, the 64-byte
+///   ENDR and the SHA-256 of the central directory as stored
+#[derive(Debug, Clone)]
+pub(crate) struct SignatureBlock {
+    pub timestamp: u64,
+    public_key: [u8; 32],
+    signature: [u8; 64],
+}
+
+impl SignatureBlock {
+    /// Sign the ENDR bytes and central directory digest with `key`
+    pub fn sign(
+        key: &SigningKey,
+        timestamp: u64,
+        end_record: &[u8],
+        directory_digest: &[u8; 32],
+    ) -> Self {
+        let message = signed_message(timestamp, end_record, directory_digest);
+        Self {
+            timestamp,
+            public_key: key.verifying_key().to_bytes(),
+            signature: key.sign(&message).to_bytes(),
+        }
+    }
+
+    /// Whether the block was signed by one of `trusted` over these ENDR
+    /// bytes and central directory digest
+    pub fn verify(
+        &self,
+        trusted: &[VerifyingKey],
+        end_record: &[u8],
+        directory_digest: &[u8; 32],
+    ) -> bool {
+        let Some(key) = trusted
+            .iter()
+            .find(|key| key.as_bytes() == &self.public_key)
+        else {
+            return false;
+        };
+        let message = signed_message(self.timestamp, end_record, directory_digest);
+        key.verify_strict(&message, &Signature::from_bytes(&self.signature))
+            .is_ok()
+    }
+
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<usize> {
+        writer.write_all(&header_bytes(self.timestamp))?;
+        writer.write_all(&self.public_key)?;
+        writer.write_all(&self.signature)?;
+        Ok(SIGNATURE_BLOCK_SIZE)
+    }
+
+    pub fn read_from(bytes: &[u8]) -> Result<Self> {
+        if bytes.len() != SIGNATURE_BLOCK_SIZE
+            || bytes[..4] != SIGNATURE_BLOCK_SIGNATURE
+            || u16::from_le_bytes([bytes[4], bytes[5]]) != ALGORITHM_ED25519
+        {
+            return Err(EngramError::InvalidSignature);
+        }
+        Ok(Self {
+            timestamp: u64::from_le_bytes(bytes[8..16].try_into().unwrap()),
+            public_key: bytes[16..48].try_into().unwrap(),
+            signature: bytes[48..112].try_into().unwrap(),
+        })
+    }
+}
+
+/// First 16 bytes of the block: signature, algorithm, reserved, timestamp
+fn header_bytes(timestamp: u64) -> [u8; 16] {
+    let mut bytes = [0u8; 16];
+    bytes[..4].copy_from_slice(&SIGNATURE_BLOCK_SIGNATURE);
+    bytes[4..6].copy_from_slice(&ALGORITHM_ED25519.to_le_bytes());
+    bytes[8..16].copy_from_slice(&timestamp.to_le_bytes());
+    bytes
+}
+
+fn signed_message(timestamp: u64, end_record: &[u8], directory_digest: &[u8; 32]) -> Vec<u8> {
+    let mut message = header_bytes(timestamp).to_vec();
+    message.extend_from_slice(end_record);
+    message.extend_from_slice(directory_digest);
+    message
+}
@@ -0,0 +1,190 @@
+use crate::error::{EngramError, Result};
+use std::collections::{BTreeMap, HashMap};
+
+/// Archive path of the custom entry metadata table (stored uncompressed)
+///
+/// Written by `finalize` when entries were added with `FileMetadata::extra`.
+/// Holds one `[path length: uint16][path][block length: uint16][block]`
+/// record per entry, sorted by path; a block is a uint16 pair count followed
+/// by `[key length: uint16][key][value length: uint16][value]` per pair,
+/// sorted by key.
+pub const ENTRY_METADATA_PATH: &str = ".engram/metadata.kv";
+
+/// Maximum size in bytes of one entry's encoded metadata block
+pub const MAX_ENTRY_METADATA_SIZE: usize = 4096;
+
+/// Metadata of an entry, returned by `ArchiveReader::entry_metadata`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntryMetadata {
+    /// Modification time in Unix epoch seconds
+    pub modified_time: u64,
+    /// See `EntryInfo::is_executable`
+    pub executable: bool,
+    /// See `ArchiveReader::mime_type`
+    pub mime_type: Option<String>,
+    /// Custom key/value pairs from `FileMetadata::extra`
+    pub extra: BTreeMap<String, String>,
+}
+
+/// Encode the metadata block of `path`
+///
+/// Fails with `InvalidOptions` for an empty key or a block larger than
+/// `MAX_ENTRY_METADATA_SIZE`.
+pub(crate) fn encode_block(path: &str, extra: &BTreeMap<String, String>) -> Result<Vec<u8>> {
+    if extra.keys().any(String::is_empty) {
+        return Err(EngramError::InvalidOptions(format!(
+            "Metadata of '{}' has an empty key",
+            path
+        )));
+    }
+    let size = 2 + extra
+        .iter()
+        .map(|(key, value)| 4 + key.len() + value.len())
+        .sum::<usize>();
+    if size > MAX_ENTRY_METADATA_SIZE {
+        return Err(EngramError::InvalidOptions(format!(
+            "Metadata of '{}' is {} bytes, over the {} byte limit",
+            path, size, MAX_ENTRY_METADATA_SIZE
+        )));
+    }
+
+    // Every length fits in a u16 once the block is within the limit
+    let mut block = Vec::with_capacity(size);
+    block.extend_from_slice(&(extra.len() as u16).to_le_bytes());
+    for (key, value) in extra {
+        for field in [key, value] {
+            block.extend_from_slice(&(field.len() as u16).to_le_bytes());
+            block.extend_from_slice(field.as_bytes());
+        }
+    }
+    Ok(block)
+}
+
+/// Contents of `ENTRY_METADATA_PATH` for encoded blocks by entry path
+pub(crate) fn table_bytes(blocks: &BTreeMap<String, Vec<u8>>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for (path, block) in blocks {
+        for field in [path.as_bytes(), block] {
+            bytes.extend_from_slice(&(field.len() as u16).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+    }
+    bytes
+}
+
+/// Parse the contents of `ENTRY_METADATA_PATH` into key/value pairs by path
+pub(crate) fn parse_table(bytes: &[u8]) -> Result<HashMap<String, BTreeMap<String, String>>> {
+    let mut fields = Fields { rest: bytes };
+    let mut table = HashMap::new();
+    while !fields.rest.is_empty() {
+        let path = fields.string()?;
+        let mut block = Fields {
+            rest: fields.field()?,
+        };
+        let count = block.u16()?;
+        let mut extra = BTreeMap::new();
+        for _ in 0..count {
+            let key = block.string()?;
+            extra.insert(key, block.string()?);
+        }
+        if !block.rest.is_empty() {
+            return Err(invalid("has trailing bytes in a metadata block"));
+        }
+        table.insert(path, extra);
+    }
+    Ok(table)
+}
+
+fn invalid(reason: &str) -> EngramError {
+    EngramError::InvalidFormat(format!("{} {}", ENTRY_METADATA_PATH, reason))
+}
+
+/// Cursor over length-prefixed fields
+struct Fields<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Fields<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        if self.rest.len() < len {
+            return Err(invalid("is truncated"));
+        }
+        let (taken, rest) = self.rest.split_at(len);
+        self.rest = rest;
+        Ok(taken)
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn field(&mut self) -> Result<&'a [u8]> {
+        let len = self.u16()?;
+        self.take(usize::from(len))
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let field = self.field()?;
+        String::from_utf8(field.to_vec()).map_err(|_| invalid("is not valid UTF-8"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pairs(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_table_round_trip() {
+        let extra = pairs(&[("rev", "4f2a9c1"), ("cache-control", "max-age=60")]);
+        let mut blocks = BTreeMap::new();
+        blocks.insert("a.txt".to_string(), encode_block("a.txt", &extra).unwrap());
+        blocks.insert(
+            "b.txt".to_string(),
+            encode_block("b.txt", &BTreeMap::new()).unwrap(),
+        );
+
+        let table = parse_table(&table_bytes(&blocks)).unwrap();
+        assert_eq!(table.len(), 2);
+        assert_eq!(table["a.txt"], extra);
+        assert!(table["b.txt"].is_empty());
+        assert!(parse_table(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_block_size_limit() {
+        // 2 + 4 + 1 + value bytes
+        let at_limit = pairs(&[("k", &"v".repeat(MAX_ENTRY_METADATA_SIZE - 7))]);
+        let block = encode_block("a", &at_limit).unwrap();
+        assert_eq!(block.len(), MAX_ENTRY_METADATA_SIZE);
+
+        let over = pairs(&[("k", &"v".repeat(MAX_ENTRY_METADATA_SIZE - 6))]);
+        assert!(matches!(
+            encode_block("a", &over),
+            Err(EngramError::InvalidOptions(_))
+        ));
+        assert!(matches!(
+            encode_block("a", &pairs(&[("", "empty key")])),
+            Err(EngramError::InvalidOptions(_))
+        ));
+    }
+
+    #[test]
+    fn test_malformed_table_rejected() {
+        let mut blocks = BTreeMap::new();
+        let block = encode_block("a", &pairs(&[("key", "value")])).unwrap();
+        blocks.insert("a".to_string(), block);
+        let bytes = table_bytes(&blocks);
+
+        assert!(parse_table(&bytes[..bytes.len() - 1]).is_err());
+        let mut bad_count = bytes.clone();
+        bad_count[5] = 2; // pair count of the block
+        assert!(parse_table(&bad_count).is_err());
+    }
+}
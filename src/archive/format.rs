@@ -1,13 +1,17 @@
+use crate::archive::mime::builtin_mime_type;
 use crate::error::{EngramError, Result};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
 use std::io::{Read, Write};
+use std::str::FromStr;
 
 /// Magic number: 0x89 'E' 'N' 'G' 0x0D 0x0A 0x1A 0x0A
 /// Follows PNG pattern for corruption detection
 pub const MAGIC_NUMBER: [u8; 8] = [0x89, b'E', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
 
-/// Current format version - v1.0 with LOCA, ENDR, and frame compression
+/// Current format version - v1.1 with LOCA, ENDR, and indexed frame compression
 pub const FORMAT_VERSION_MAJOR: u16 = 1;
-pub const FORMAT_VERSION_MINOR: u16 = 0;
+pub const FORMAT_VERSION_MINOR: u16 = 1;
 
 /// Header size in bytes
 pub const HEADER_SIZE: usize = 64;
@@ -18,17 +22,95 @@ pub const CD_ENTRY_SIZE: usize = 320;
 /// Maximum path length in bytes (UTF-8)
 pub const MAX_PATH_LENGTH: usize = 255;
 
+/// Entry flag: entry's payload is a chunk list, its content is reassembled
+/// from entries under `CHUNKS_PREFIX` (see `ArchiveWriter::with_cdc_dedup`)
+pub const ENTRY_FLAG_CHUNKED: u8 = 0b0000_0001;
+
+/// Entry flag: entry is an explicit directory (no data payload)
+pub const ENTRY_FLAG_DIRECTORY: u8 = 0b0000_0010;
+
+/// Entry flag: entry shares the LOCA header and data of an earlier identical entry
+pub const ENTRY_FLAG_DEDUPLICATED: u8 = 0b0000_0100;
+
+/// Entry flag: entry carries a truncated SHA-256 digest of its uncompressed content
+pub const ENTRY_FLAG_SHA256: u8 = 0b0000_1000;
+
+/// Entry flag: entry is Zstd-compressed with the archive's shared dictionary
+pub const ENTRY_FLAG_ZSTD_DICTIONARY: u8 = 0b0001_0000;
+
+/// Entry flag: entry is executable (Unix `x` permission when it was added)
+pub const ENTRY_FLAG_EXECUTABLE: u8 = 0b0010_0000;
+
+/// Entry flag: entry was added as an explicit alias of another entry
+/// (`ArchiveWriter::add_alias`); always set together with `ENTRY_FLAG_DEDUPLICATED`
+pub const ENTRY_FLAG_ALIAS: u8 = 0b0100_0000;
+
+/// Entry flag: entry is frame-compressed (v1.1+); entries of
+/// `MIN_FRAME_COMPRESSION_SIZE` and up are framed with or without it
+pub const ENTRY_FLAG_FRAMED: u8 = 0b1000_0000;
+
+/// Header flag: central directory entries are sorted by path (byte order)
+pub const HEADER_FLAG_SORTED_DIRECTORY: u32 = 0b100;
+
+/// Header flag: per-file encrypted payloads are bound to their entry via AES-GCM
+/// associated data (see `entry_aad`)
+pub const HEADER_FLAG_ENTRY_AAD: u32 = 0b1000;
+
+/// Header flag: the encryption key is wrapped for recipients in a block before
+/// the End Record (see `EndRecord::recipients_size`)
+pub const HEADER_FLAG_RECIPIENTS: u32 = 0b1_0000;
+
+/// Header flag: some entries are chunked (`ENTRY_FLAG_CHUNKED`)
+pub const HEADER_FLAG_CHUNKED: u32 = 0b10_0000;
+
+/// Header flag: BLAKE3 digests of entry contents are stored at
+/// `BLAKE3_TABLE_PATH`
+pub const HEADER_FLAG_BLAKE3: u32 = 0b100_0000;
+
+/// Archive path of the Engram manifest, written by `ArchiveWriter::add_manifest`
+pub const MANIFEST_PATH: &str = "manifest.json";
+
+/// Path prefix reserved for entries the format itself writes (e.g. the Zstd
+/// dictionary)
+pub const RESERVED_PREFIX: &str = ".engram/";
+
+/// Whether a stored path belongs to the format rather than the application
+///
+/// True for `manifest.json` and anything under `.engram/` (including the
+/// `.engram` directory itself).
+pub fn is_reserved_path(path: &str) -> bool {
+    path == MANIFEST_PATH
+        || path.starts_with(RESERVED_PREFIX)
+        || path == RESERVED_PREFIX.trim_end_matches('/')
+}
+
+/// Number of SHA-256 digest bytes stored in a central directory entry
+pub const SHA256_PREFIX_LEN: usize = 16;
+
+/// Zstd level used by `ArchiveWriter` unless `with_zstd_level` sets another
+pub const DEFAULT_ZSTD_LEVEL: i32 = 6;
+
 /// Compression methods supported
+///
+/// Serializes as its lowercase name (`"zstd"`, or `"unknown(7)"` for a
+/// method byte this version does not know), like `Display`.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[repr(u8)]
 pub enum CompressionMethod {
-    None = 0,
-    Lz4 = 1,
-    Zstd = 2,
+    None,
+    Lz4,
+    Zstd,
+    /// Raw Deflate (RFC 1951), as used by ZIP and the legacy engram-core
+    /// crate; written only when requested explicitly, never chosen automatically
+    Deflate,
+    /// Method byte not known to this version, kept so the directory still parses
+    Unknown(u8),
 }
 
 /// Encryption modes
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Serializes as `"none"`, `"archive"` or `"per-file"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 #[repr(u8)]
 pub enum EncryptionMode {
     /// No encryption
@@ -45,10 +127,30 @@ impl CompressionMethod {
             0 => Ok(Self::None),
             1 => Ok(Self::Lz4),
             2 => Ok(Self::Zstd),
+            3 => Ok(Self::Deflate),
             _ => Err(EngramError::InvalidCompression(value)),
         }
     }
 
+    /// Like `from_u8`, but maps unrecognized values to `Unknown`
+    ///
+    /// Used when parsing directory entries, so archives written by newer
+    /// versions can still be listed; reading such an entry fails instead.
+    pub fn from_u8_lossy(value: u8) -> Self {
+        Self::from_u8(value).unwrap_or(Self::Unknown(value))
+    }
+
+    /// On-disk method byte
+    pub fn to_u8(self) -> u8 {
+        match self {
+            Self::None => 0,
+            Self::Lz4 => 1,
+            Self::Zstd => 2,
+            Self::Deflate => 3,
+            Self::Unknown(value) => value,
+        }
+    }
+
     /// Choose best compression based on file type and size
     pub fn choose_for_file(path: &str, size: u64) -> Self {
         // Don't compress small files
@@ -111,6 +213,53 @@ impl CompressionMethod {
     }
 }
 
+impl fmt::Display for CompressionMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::None => f.write_str("none"),
+            Self::Lz4 => f.write_str("lz4"),
+            Self::Zstd => f.write_str("zstd"),
+            Self::Deflate => f.write_str("deflate"),
+            Self::Unknown(value) => write!(f, "unknown({})", value),
+        }
+    }
+}
+
+impl FromStr for CompressionMethod {
+    type Err = EngramError;
+
+    /// Parse a name as written by `Display`
+    fn from_str(name: &str) -> Result<Self> {
+        match name {
+            "none" => Ok(Self::None),
+            "lz4" => Ok(Self::Lz4),
+            "zstd" => Ok(Self::Zstd),
+            "deflate" => Ok(Self::Deflate),
+            _ => name
+                .strip_prefix("unknown(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .and_then(|value| value.parse().ok())
+                .map(Self::from_u8_lossy)
+                .ok_or_else(|| {
+                    EngramError::InvalidFormat(format!("Unknown compression method: {}", name))
+                }),
+        }
+    }
+}
+
+impl Serialize for CompressionMethod {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for CompressionMethod {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
 impl EncryptionMode {
     /// Extract encryption mode from flags field
     pub fn from_flags(flags: u32) -> Self {
@@ -128,8 +277,64 @@ impl EncryptionMode {
     }
 }
 
+/// Length of the application-defined archive label in bytes
+pub const ARCHIVE_LABEL_LEN: usize = 16;
+
+/// Application-defined tag stored in the file header (bytes 44-59)
+///
+/// Engram does not interpret the label. It sits in the unencrypted header, so
+/// it can be read without a key or parsing the manifest, e.g. to tell which
+/// schema generation a backup belongs to. All zeros means no label, which is
+/// what older archives contain. Serializes as 32 lowercase hex digits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ArchiveLabel(pub [u8; ARCHIVE_LABEL_LEN]);
+
+impl ArchiveLabel {
+    /// Label bytes
+    pub fn as_bytes(&self) -> &[u8; ARCHIVE_LABEL_LEN] {
+        &self.0
+    }
+
+    /// True when no label is set (all zeros)
+    pub fn is_empty(&self) -> bool {
+        self.0.iter().all(|&b| b == 0)
+    }
+}
+
+impl From<[u8; ARCHIVE_LABEL_LEN]> for ArchiveLabel {
+    fn from(bytes: [u8; ARCHIVE_LABEL_LEN]) -> Self {
+        Self(bytes)
+    }
+}
+
+impl Serialize for ArchiveLabel {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(self.0))
+    }
+}
+
+impl<'de> Deserialize<'de> for ArchiveLabel {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let digits = String::deserialize(deserializer)?;
+        let mut label = [0u8; ARCHIVE_LABEL_LEN];
+        hex::decode_to_slice(&digits, &mut label).map_err(serde::de::Error::custom)?;
+        Ok(Self(label))
+    }
+}
+
+/// AES-GCM associated data for a per-file encrypted payload
+///
+/// The normalized entry path followed by the uncompressed size (u64 LE), so
+/// a payload cannot be moved under another entry and still decrypt.
+pub(crate) fn entry_aad(path: &str, uncompressed_size: u64) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(path.len() + 8);
+    aad.extend_from_slice(path.as_bytes());
+    aad.extend_from_slice(&uncompressed_size.to_le_bytes());
+    aad
+}
+
 /// File header at the beginning of the archive
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileHeader {
     pub version_major: u16,
     pub version_minor: u16,
@@ -139,6 +344,7 @@ pub struct FileHeader {
     pub entry_count: u32,
     pub content_version: u32,
     pub flags: u32,
+    pub label: ArchiveLabel,
 }
 
 impl FileHeader {
@@ -152,6 +358,7 @@ impl FileHeader {
             entry_count: 0,
             content_version: 0,
             flags: 0,
+            label: ArchiveLabel::default(),
         }
     }
 
@@ -165,6 +372,85 @@ impl FileHeader {
         EncryptionMode::from_flags(self.flags)
     }
 
+    /// Mark the central directory as sorted by path (or not)
+    pub fn set_sorted_directory(&mut self, sorted: bool) {
+        if sorted {
+            self.flags |= HEADER_FLAG_SORTED_DIRECTORY;
+        } else {
+            self.flags &= !HEADER_FLAG_SORTED_DIRECTORY;
+        }
+    }
+
+    /// Whether central directory entries are sorted by path
+    pub fn has_sorted_directory(&self) -> bool {
+        self.flags & HEADER_FLAG_SORTED_DIRECTORY != 0
+    }
+
+    /// Mark per-file encrypted payloads as bound to their entries (or not)
+    pub fn set_entry_aad(&mut self, enabled: bool) {
+        if enabled {
+            self.flags |= HEADER_FLAG_ENTRY_AAD;
+        } else {
+            self.flags &= !HEADER_FLAG_ENTRY_AAD;
+        }
+    }
+
+    /// Whether per-file encrypted payloads use `entry_aad` as associated data
+    pub fn has_entry_aad(&self) -> bool {
+        self.flags & HEADER_FLAG_ENTRY_AAD != 0
+    }
+
+    /// Mark the archive as carrying a recipients block (or not)
+    pub fn set_recipients(&mut self, enabled: bool) {
+        if enabled {
+            self.flags |= HEADER_FLAG_RECIPIENTS;
+        } else {
+            self.flags &= !HEADER_FLAG_RECIPIENTS;
+        }
+    }
+
+    /// Whether the encryption key is wrapped for recipients
+    pub fn has_recipients(&self) -> bool {
+        self.flags & HEADER_FLAG_RECIPIENTS != 0
+    }
+
+    /// Mark the archive as containing chunked entries (or not)
+    pub fn set_chunked(&mut self, enabled: bool) {
+        if enabled {
+            self.flags |= HEADER_FLAG_CHUNKED;
+        } else {
+            self.flags &= !HEADER_FLAG_CHUNKED;
+        }
+    }
+
+    /// Whether some entries are reassembled from chunks
+    pub fn has_chunked_entries(&self) -> bool {
+        self.flags & HEADER_FLAG_CHUNKED != 0
+    }
+
+    /// Mark the archive as carrying a BLAKE3 digest table (or not)
+    pub fn set_blake3_digests(&mut self, enabled: bool) {
+        if enabled {
+            self.flags |= HEADER_FLAG_BLAKE3;
+        } else {
+            self.flags &= !HEADER_FLAG_BLAKE3;
+        }
+    }
+
+    /// Whether entry digests are stored at `BLAKE3_TABLE_PATH`
+    pub fn has_blake3_digests(&self) -> bool {
+        self.flags & HEADER_FLAG_BLAKE3 != 0
+    }
+
+    /// Whether this is a v0.x archive
+    ///
+    /// v0.3/v0.4 archives predate LOCA headers and the End Record: central
+    /// directory offsets point straight at the stored payload, the archive ends
+    /// with the central directory, and no entry is frame-compressed.
+    pub fn is_legacy(&self) -> bool {
+        self.version_major == 0
+    }
+
     /// Write header to a writer
     pub fn write_to<W: Write>(&self, mut writer: W) -> Result<()> {
         writer.write_all(&MAGIC_NUMBER)?;
@@ -177,8 +463,9 @@ impl FileHeader {
         writer.write_all(&self.content_version.to_le_bytes())?;
         writer.write_all(&self.flags.to_le_bytes())?;
 
-        // Write reserved bytes (20 bytes of zeros - was 24, now 20 due to flags)
-        writer.write_all(&[0u8; 20])?;
+        // Write label, then the remaining reserved bytes
+        writer.write_all(self.label.as_bytes())?;
+        writer.write_all(&[0u8; 4])?;
 
         Ok(())
     }
@@ -210,8 +497,10 @@ impl FileHeader {
             0
         };
 
-        // Skip remaining reserved bytes (20 bytes for v0.4+, 20 bytes for v0.3)
-        let mut reserved = [0u8; 20];
+        // Label (zeros in archives written before labels), then reserved bytes
+        let mut label = [0u8; ARCHIVE_LABEL_LEN];
+        reader.read_exact(&mut label)?;
+        let mut reserved = [0u8; 4];
         reader.read_exact(&mut reserved)?;
 
         Ok(Self {
@@ -223,9 +512,19 @@ impl FileHeader {
             entry_count,
             content_version,
             flags,
+            label: ArchiveLabel(label),
         })
     }
 
+    /// Compute the header CRC32 (over header bytes 0-11: magic number and version)
+    pub fn compute_crc(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(&MAGIC_NUMBER);
+        hasher.update(&self.version_major.to_le_bytes());
+        hasher.update(&self.version_minor.to_le_bytes());
+        hasher.finalize()
+    }
+
     /// Validate version compatibility
     pub fn validate_version(&self) -> Result<()> {
         if self.version_major > FORMAT_VERSION_MAJOR {
@@ -244,7 +543,7 @@ impl Default for FileHeader {
 }
 
 /// Central Directory entry metadata
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EntryInfo {
     pub path: String,
     pub data_offset: u64,
@@ -254,9 +553,90 @@ pub struct EntryInfo {
     pub modified_time: u64,
     pub compression: CompressionMethod,
     pub flags: u8,
+    /// Unix file mode (permission and type bits); 0 means unspecified
+    pub mode: u32,
+    /// MIME type id, stored in bits 16-31 of the mode field; 0 means none
+    /// (see `mime_type`)
+    pub mime_id: u16,
+    /// First 16 bytes of the SHA-256 of the uncompressed content
+    /// (present when `ENTRY_FLAG_SHA256` is set)
+    pub sha256: Option<[u8; SHA256_PREFIX_LEN]>,
+    /// BLAKE3 of the uncompressed content, from the archive's
+    /// `BLAKE3_TABLE_PATH` rather than the central directory entry
+    /// (see `blake3`)
+    pub(crate) blake3: Option<[u8; 32]>,
+}
+
+impl Default for EntryInfo {
+    /// An empty, uncompressed file entry at offset 0
+    fn default() -> Self {
+        Self {
+            path: String::new(),
+            data_offset: 0,
+            uncompressed_size: 0,
+            compressed_size: 0,
+            crc32: 0,
+            modified_time: 0,
+            compression: CompressionMethod::None,
+            flags: 0,
+            mode: 0,
+            mime_id: 0,
+            sha256: None,
+            blake3: None,
+        }
+    }
 }
 
 impl EntryInfo {
+    /// Whether this entry is an explicit directory rather than a file
+    pub fn is_directory(&self) -> bool {
+        self.flags & ENTRY_FLAG_DIRECTORY != 0
+    }
+
+    /// Whether this entry was compressed with the shared Zstd dictionary
+    pub fn uses_zstd_dictionary(&self) -> bool {
+        self.flags & ENTRY_FLAG_ZSTD_DICTIONARY != 0
+    }
+
+    /// Whether this entry shares its stored data with an earlier entry
+    pub fn is_deduplicated(&self) -> bool {
+        self.flags & ENTRY_FLAG_DEDUPLICATED != 0
+    }
+
+    /// Whether this entry's content is stored as chunks (see `ENTRY_FLAG_CHUNKED`)
+    pub fn is_chunked(&self) -> bool {
+        self.flags & ENTRY_FLAG_CHUNKED != 0
+    }
+
+    /// Whether this entry was added as an alias of another entry
+    pub fn is_alias(&self) -> bool {
+        self.flags & ENTRY_FLAG_ALIAS != 0
+    }
+
+    /// Whether this entry was marked executable when it was added
+    pub fn is_executable(&self) -> bool {
+        self.flags & ENTRY_FLAG_EXECUTABLE != 0
+    }
+
+    /// MIME type recorded for this entry, if it is one of `BUILTIN_MIME_TYPES`
+    ///
+    /// Custom types live in the archive's `.engram/mime.tbl` entry; use
+    /// `ArchiveReader::mime_type` to resolve those as well.
+    pub fn mime_type(&self) -> Option<&'static str> {
+        builtin_mime_type(self.mime_id)
+    }
+
+    /// BLAKE3 of the uncompressed content, if the archive was written with
+    /// `ArchiveWriter::with_blake3` and its digest table could be read
+    pub fn blake3(&self) -> Option<[u8; 32]> {
+        self.blake3
+    }
+
+    /// Mode field as stored: the mode in bits 0-15, the MIME type id above
+    pub(crate) fn stored_mode(&self) -> u32 {
+        (self.mode & 0xFFFF) | (self.mime_id as u32) << 16
+    }
+
     /// Write entry to central directory
     pub fn write_to<W: Write>(&self, mut writer: W) -> Result<()> {
         // Signature "CENT" (0x43454E54)
@@ -267,7 +647,7 @@ impl EntryInfo {
         writer.write_all(&self.compressed_size.to_le_bytes())?;
         writer.write_all(&self.crc32.to_le_bytes())?;
         writer.write_all(&self.modified_time.to_le_bytes())?;
-        writer.write_all(&[self.compression as u8])?;
+        writer.write_all(&[self.compression.to_u8()])?;
         writer.write_all(&[self.flags])?;
 
         // Path length and path
@@ -288,14 +668,30 @@ impl EntryInfo {
         path_buf[..path_bytes.len()].copy_from_slice(path_bytes);
         writer.write_all(&path_buf)?;
 
-        // Reserved (20 bytes)
-        writer.write_all(&[0u8; 20])?;
+        // Unix mode (4 bytes) + SHA-256 prefix or reserved (16 bytes)
+        writer.write_all(&self.stored_mode().to_le_bytes())?;
+        writer.write_all(&self.sha256.unwrap_or([0u8; SHA256_PREFIX_LEN]))?;
 
         Ok(())
     }
 
     /// Read entry from central directory
-    pub fn read_from<R: Read>(mut reader: R) -> Result<Self> {
+    ///
+    /// The path must be valid UTF-8 without control characters (below 0x20),
+    /// and the rest of its 256-byte buffer must be zero; otherwise this fails
+    /// with `PathError`. Use `read_from_lenient` for archives that break these
+    /// rules.
+    pub fn read_from<R: Read>(reader: R) -> Result<Self> {
+        Self::read_checked(reader, true)
+    }
+
+    /// `read_from` without the control character and padding checks, for
+    /// tools that must read non-conforming archives
+    pub fn read_from_lenient<R: Read>(reader: R) -> Result<Self> {
+        Self::read_checked(reader, false)
+    }
+
+    fn read_checked<R: Read>(mut reader: R, strict: bool) -> Result<Self> {
         // Read and verify signature
         let mut sig = [0u8; 4];
         reader.read_exact(&mut sig)?;
@@ -313,22 +709,35 @@ impl EntryInfo {
 
         let mut compression_byte = [0u8; 1];
         reader.read_exact(&mut compression_byte)?;
-        let compression = CompressionMethod::from_u8(compression_byte[0])?;
+        let compression = CompressionMethod::from_u8_lossy(compression_byte[0]);
 
         let mut flags = [0u8; 1];
         reader.read_exact(&mut flags)?;
 
         let path_len = read_u16(&mut reader)?;
+        if path_len as usize > MAX_PATH_LENGTH {
+            return Err(EngramError::InvalidFormat(format!(
+                "Central directory path length {} exceeds {} bytes",
+                path_len, MAX_PATH_LENGTH
+            )));
+        }
 
         let mut path_buf = [0u8; 256];
         reader.read_exact(&mut path_buf)?;
 
         let path = String::from_utf8(path_buf[..path_len as usize].to_vec())
             .map_err(|e| EngramError::PathError(format!("Invalid UTF-8 in path: {}", e)))?;
+        if strict {
+            check_stored_path(&path, &path_buf[path_len as usize..])?;
+        }
 
-        // Skip reserved bytes
-        let mut reserved = [0u8; 20];
-        reader.read_exact(&mut reserved)?;
+        // Unix mode (zero in archives written before mode support), MIME id above it
+        let stored_mode = read_u32(&mut reader)?;
+
+        // SHA-256 prefix (reserved, and ignored, unless the flag is set)
+        let mut digest = [0u8; SHA256_PREFIX_LEN];
+        reader.read_exact(&mut digest)?;
+        let sha256 = (flags[0] & ENTRY_FLAG_SHA256 != 0).then_some(digest);
 
         Ok(Self {
             path,
@@ -339,10 +748,54 @@ impl EntryInfo {
             modified_time,
             compression,
             flags: flags[0],
+            mode: stored_mode & 0xFFFF,
+            mime_id: (stored_mode >> 16) as u16,
+            sha256,
+            blake3: None,
         })
     }
 }
 
+/// Reject a central directory path with control characters, which could
+/// spoof terminal output when listed, or with data in its padding
+fn check_stored_path(path: &str, padding: &[u8]) -> Result<()> {
+    // Debug formatting escapes the characters rather than emitting them
+    if path.bytes().any(|byte| byte < 0x20) {
+        return Err(EngramError::PathError(format!(
+            "Control character in path: {:?}",
+            path
+        )));
+    }
+    if padding.iter().any(|&byte| byte != 0) {
+        return Err(EngramError::PathError(format!(
+            "Non-zero bytes after path {:?}",
+            path
+        )));
+    }
+    Ok(())
+}
+
+/// An entry's stored payload with what is needed to store it elsewhere
+///
+/// Read by `ArchiveReader::read_raw_entry` and written by
+/// `ArchiveWriter::add_raw_entry`, which copies entries between archives
+/// without decompressing and compressing them again.
+#[derive(Debug, Clone)]
+pub struct RawEntry {
+    /// Payload as stored after the LOCA header (compressed, and encrypted
+    /// when `encrypted` is set)
+    pub data: Vec<u8>,
+    pub compression: CompressionMethod,
+    pub uncompressed_size: u64,
+    /// CRC32 of the uncompressed content, not of `data`
+    pub crc32: u32,
+    /// Whether `data` is encrypted with the archive's per-file key
+    pub encrypted: bool,
+    /// Entry flags describing the layout of `data`: `ENTRY_FLAG_FRAMED` and
+    /// `ENTRY_FLAG_ZSTD_DICTIONARY`
+    pub flags: u8,
+}
+
 // Helper functions for reading primitive types
 fn read_u16<R: Read>(mut reader: R) -> Result<u16> {
     let mut buf = [0u8; 2];
@@ -381,6 +834,11 @@ mod tests {
             CompressionMethod::Zstd
         );
         assert!(CompressionMethod::from_u8(99).is_err());
+        assert_eq!(
+            CompressionMethod::from_u8_lossy(99),
+            CompressionMethod::Unknown(99)
+        );
+        assert_eq!(CompressionMethod::Unknown(99).to_u8(), 99);
     }
 
     #[test]
@@ -426,6 +884,7 @@ mod tests {
             entry_count: 10,
             content_version: 1,
             flags: 0,
+            label: ArchiveLabel(*b"schema-gen-0042\0"),
         };
 
         let mut buf = Vec::new();
@@ -442,6 +901,8 @@ mod tests {
             header.central_directory_offset
         );
         assert_eq!(parsed.entry_count, header.entry_count);
+        assert_eq!(parsed.content_version, header.content_version);
+        assert_eq!(parsed.label, header.label);
     }
 
     #[test]
@@ -454,7 +915,11 @@ mod tests {
             crc32: 0xDEADBEEF,
             modified_time: 1699999999,
             compression: CompressionMethod::Zstd,
-            flags: 0,
+            flags: ENTRY_FLAG_SHA256,
+            mode: 0o100755,
+            mime_id: 3,
+            sha256: Some([0xAB; SHA256_PREFIX_LEN]),
+            blake3: None,
         };
 
         let mut buf = Vec::new();
@@ -469,5 +934,65 @@ mod tests {
         assert_eq!(parsed.compressed_size, entry.compressed_size);
         assert_eq!(parsed.crc32, entry.crc32);
         assert_eq!(parsed.compression, entry.compression);
+        assert_eq!(parsed.mode, entry.mode);
+        assert_eq!(parsed.mime_id, entry.mime_id);
+        assert_eq!(parsed.mime_type(), Some("text/html"));
+        assert_eq!(parsed.sha256, entry.sha256);
+    }
+
+    #[test]
+    fn test_entry_info_oversized_path_length_rejected() {
+        let entry = EntryInfo {
+            path: "a".to_string(),
+            data_offset: HEADER_SIZE as u64,
+            uncompressed_size: 1,
+            compressed_size: 1,
+            crc32: 0,
+            modified_time: 0,
+            compression: CompressionMethod::None,
+            flags: 0,
+            mode: 0,
+            mime_id: 0,
+            sha256: None,
+            blake3: None,
+        };
+        let mut buf = Vec::new();
+        entry.write_to(&mut buf).unwrap();
+        // Path length field at byte 42, after the fixed-size fields
+        buf[42..44].copy_from_slice(&300u16.to_le_bytes());
+
+        let result = EntryInfo::read_from(&buf[..]);
+        assert!(matches!(result, Err(EngramError::InvalidFormat(_))));
+    }
+
+    #[test]
+    fn test_entry_info_path_checks() {
+        let entry = EntryInfo {
+            path: "a".to_string(),
+            data_offset: HEADER_SIZE as u64,
+            uncompressed_size: 1,
+            compressed_size: 1,
+            crc32: 0,
+            modified_time: 0,
+            compression: CompressionMethod::None,
+            flags: 0,
+            mode: 0,
+            mime_id: 0,
+            sha256: None,
+            blake3: None,
+        };
+        let mut buf = Vec::new();
+        entry.write_to(&mut buf).unwrap();
+
+        // Path buffer at byte 44; byte 45 is padding after the one-byte path
+        let mut control = buf.clone();
+        control[44] = b'\n';
+        let mut padding = buf.clone();
+        padding[45] = b'x';
+        for damaged in [control, padding] {
+            let result = EntryInfo::read_from(&damaged[..]);
+            assert!(matches!(result, Err(EngramError::PathError(_))));
+            assert!(EntryInfo::read_from_lenient(&damaged[..]).is_ok());
+        }
     }
 }
@@ -1,6 +1,7 @@
-use crate::archive::format::CompressionMethod;
+use crate::archive::format::{CompressionMethod, DEFAULT_ZSTD_LEVEL};
 use crate::error::{EngramError, Result};
 use std::io::{Read, Write};
+use std::ops::Range;
 
 /// Frame size for frame-based compression (64KB)
 pub const FRAME_SIZE: usize = 65536; // 64KB
@@ -8,60 +9,340 @@ pub const FRAME_SIZE: usize = 65536; // 64KB
 /// Minimum file size for frame-based compression (50MB)
 pub const MIN_FRAME_COMPRESSION_SIZE: usize = 52_428_800; // 50MB
 
+/// Smallest frame size `FrameOptions` accepts (16KB)
+pub const MIN_FRAME_SIZE: usize = 16 * 1024;
+
+/// Largest frame size `FrameOptions` accepts, and readers decompress (8MB)
+pub const MAX_FRAME_SIZE: usize = 8 * 1024 * 1024;
+
+/// Size of one frame index entry in bytes
+pub const FRAME_INDEX_ENTRY_SIZE: usize = 24;
+
+/// Frame size and frame count in front of the index entries
+const FRAME_INDEX_HEADER_SIZE: usize = 8;
+
+/// Largest output buffer reserved up front from a size stored in the archive
+const MAX_PREALLOCATION: u64 = 256 * 1024 * 1024; // 256MB
+
+/// Capacity to reserve for `size` bytes of output read from the archive
+///
+/// Stored sizes are untrusted, so larger outputs grow as data is produced
+/// instead of being allocated up front.
+pub(crate) fn preallocation(size: u64) -> usize {
+    size.min(MAX_PREALLOCATION) as usize
+}
+
+/// How a writer splits large files into frames
+///
+/// Files of `min_file_size` bytes and up are cut into frames of `frame_size`
+/// bytes (the last one shorter), each compressed on its own. Larger frames
+/// compress better; smaller ones make range reads cheaper.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameOptions {
+    /// Uncompressed bytes per frame, between `MIN_FRAME_SIZE` and `MAX_FRAME_SIZE`
+    pub frame_size: usize,
+    /// Smallest file that is frame-compressed, between `frame_size` and
+    /// `MIN_FRAME_COMPRESSION_SIZE`
+    pub min_file_size: usize,
+}
+
+impl Default for FrameOptions {
+    fn default() -> Self {
+        Self {
+            frame_size: FRAME_SIZE,
+            min_file_size: MIN_FRAME_COMPRESSION_SIZE,
+        }
+    }
+}
+
+impl FrameOptions {
+    /// Fail with `EngramError::InvalidOptions` unless both sizes are in range
+    ///
+    /// `min_file_size` cannot exceed `MIN_FRAME_COMPRESSION_SIZE`: readers
+    /// take every compressed entry of that size and up to be framed.
+    pub fn validate(&self) -> Result<()> {
+        if !(MIN_FRAME_SIZE..=MAX_FRAME_SIZE).contains(&self.frame_size) {
+            return Err(EngramError::InvalidOptions(format!(
+                "Frame size {} is outside {}..={}",
+                self.frame_size, MIN_FRAME_SIZE, MAX_FRAME_SIZE
+            )));
+        }
+        if !(self.frame_size..=MIN_FRAME_COMPRESSION_SIZE).contains(&self.min_file_size) {
+            return Err(EngramError::InvalidOptions(format!(
+                "Minimum frame-compressed file size {} is outside {}..={}",
+                self.min_file_size, self.frame_size, MIN_FRAME_COMPRESSION_SIZE
+            )));
+        }
+        Ok(())
+    }
+
+    /// Whether a file of `size` bytes is frame-compressed
+    pub fn applies_to(&self, size: usize) -> bool {
+        size >= self.min_file_size
+    }
+}
+
+/// On-disk layout of frame-compressed payloads, which depends on the format version
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FrameLayout {
+    /// v0.x: large files are compressed as a single stream
+    None,
+    /// v1.0: `[frame_count][frame1_size][frame1_data][frame2_size][frame2_data]...`
+    Interleaved,
+    /// v1.1+: a `FrameIndex` followed by the frame data
+    Indexed,
+}
+
+impl FrameLayout {
+    /// Layout used by archives of format version `major.minor`
+    pub(crate) fn for_version(major: u16, minor: u16) -> Self {
+        match (major, minor) {
+            (0, _) => Self::None,
+            (1, 0) => Self::Interleaved,
+            _ => Self::Indexed,
+        }
+    }
+}
+
+/// Position of one frame in the uncompressed file and in the stored payload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// Offset of the frame's first byte in the uncompressed file
+    pub decompressed_offset: u64,
+    /// Uncompressed length of the frame
+    pub decompressed_size: u32,
+    /// Offset of the compressed frame from the start of the payload
+    pub compressed_offset: u64,
+    /// Compressed length of the frame
+    pub compressed_size: u32,
+}
+
+/// Frame index at the start of an indexed frame-compressed payload
+///
+/// Layout: `[frame_size: uint32][frame_count: uint32]` followed by
+/// `frame_count` entries of `FRAME_INDEX_ENTRY_SIZE` bytes
+/// (`decompressed_offset: uint64, decompressed_size: uint32,
+/// compressed_offset: uint64, compressed_size: uint32`), then the frames.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrameIndex {
+    frame_size: u32,
+    frames: Vec<FrameInfo>,
+}
+
+impl FrameIndex {
+    /// Parse the index from the start of a payload
+    ///
+    /// Fails with `InvalidFormat` if the frame size exceeds `MAX_FRAME_SIZE`
+    /// or a frame is longer than the frame size.
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self> {
+        let frame_size = read_u32(&mut reader)?;
+        if frame_size == 0 || frame_size as usize > MAX_FRAME_SIZE {
+            return Err(EngramError::InvalidFormat(format!(
+                "Frame size {} is outside 1..={}",
+                frame_size, MAX_FRAME_SIZE
+            )));
+        }
+        let frame_count = read_u32(&mut reader)?;
+
+        // Grown as entries are read, so a corrupt count cannot force a huge allocation
+        let mut frames = Vec::new();
+        let mut expected_offset = 0u64;
+        for _ in 0..frame_count {
+            let mut entry = [0u8; FRAME_INDEX_ENTRY_SIZE];
+            reader.read_exact(&mut entry)?;
+            let frame = FrameInfo {
+                decompressed_offset: u64::from_le_bytes(entry[0..8].try_into().unwrap()),
+                decompressed_size: u32::from_le_bytes(entry[8..12].try_into().unwrap()),
+                compressed_offset: u64::from_le_bytes(entry[12..20].try_into().unwrap()),
+                compressed_size: u32::from_le_bytes(entry[20..24].try_into().unwrap()),
+            };
+            if frame.decompressed_size > frame_size {
+                return Err(EngramError::InvalidFormat(format!(
+                    "Frame index entry {} holds {} bytes, more than the frame size {}",
+                    frames.len(),
+                    frame.decompressed_size,
+                    frame_size
+                )));
+            }
+            if frame.decompressed_offset != expected_offset {
+                return Err(EngramError::InvalidFormat(format!(
+                    "Frame index entry {} starts at {}, expected {}",
+                    frames.len(),
+                    frame.decompressed_offset,
+                    expected_offset
+                )));
+            }
+            expected_offset += frame.decompressed_size as u64;
+            frames.push(frame);
+        }
+
+        Ok(Self { frame_size, frames })
+    }
+
+    /// Write the index (header and entries)
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(&self.frame_size.to_le_bytes())?;
+        writer.write_all(&(self.frames.len() as u32).to_le_bytes())?;
+        for frame in &self.frames {
+            writer.write_all(&frame.decompressed_offset.to_le_bytes())?;
+            writer.write_all(&frame.decompressed_size.to_le_bytes())?;
+            writer.write_all(&frame.compressed_offset.to_le_bytes())?;
+            writer.write_all(&frame.compressed_size.to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    /// Uncompressed frame size the payload was split with
+    pub fn frame_size(&self) -> u32 {
+        self.frame_size
+    }
+
+    /// All frames, in file order
+    pub fn frames(&self) -> &[FrameInfo] {
+        &self.frames
+    }
+
+    /// Bytes taken by the index at the start of the payload
+    pub fn encoded_size(&self) -> usize {
+        FRAME_INDEX_HEADER_SIZE + self.frames.len() * FRAME_INDEX_ENTRY_SIZE
+    }
+
+    /// Total uncompressed size of all frames
+    pub fn decompressed_size(&self) -> u64 {
+        self.frames.last().map_or(0, |frame| {
+            frame.decompressed_offset + frame.decompressed_size as u64
+        })
+    }
+
+    /// Indices of the frames overlapping uncompressed bytes `range`
+    pub fn frames_overlapping(&self, range: Range<u64>) -> Range<usize> {
+        if range.start >= range.end {
+            return 0..0;
+        }
+        let first = self.frames.partition_point(|frame| {
+            frame.decompressed_offset + frame.decompressed_size as u64 <= range.start
+        });
+        let end = self
+            .frames
+            .partition_point(|frame| frame.decompressed_offset < range.end);
+        first..end.max(first)
+    }
+}
+
 /// Compress data using frame-based compression
 ///
 /// Each frame is compressed independently, allowing partial decompression.
-/// Format: [frame_count: uint32][frame1_size: uint32][frame1_data][frame2_size: uint32][frame2_data]...
+/// The result starts with a `FrameIndex` locating every frame, followed by
+/// the compressed frames. Frames are `FRAME_SIZE` bytes and `data` must be at
+/// least `MIN_FRAME_COMPRESSION_SIZE` bytes long (see `FrameOptions`).
 ///
 /// # Arguments
 /// * `data` - Input data to compress
 /// * `method` - Compression method (LZ4 or Zstd)
 ///
 /// # Returns
-/// Compressed data with frame headers
+/// Compressed data with its frame index
 pub fn compress_frames(data: &[u8], method: CompressionMethod) -> Result<Vec<u8>> {
-    if data.len() < MIN_FRAME_COMPRESSION_SIZE {
+    compress_frames_with(
+        data,
+        method,
+        DEFAULT_ZSTD_LEVEL,
+        FrameLayout::Indexed,
+        FrameOptions::default(),
+        |_| Ok(()),
+    )
+}
+
+/// Compress data using frame-based compression, reporting progress
+///
+/// Zstd frames are compressed at `zstd_level`, and the frames are laid out
+/// as `layout` (`Interleaved` or `Indexed`) and sized by `options`, which
+/// must have been validated. `on_frame` receives the number of input bytes
+/// consumed after each frame; returning an error aborts compression.
+pub(crate) fn compress_frames_with<F>(
+    data: &[u8],
+    method: CompressionMethod,
+    zstd_level: i32,
+    layout: FrameLayout,
+    options: FrameOptions,
+    mut on_frame: F,
+) -> Result<Vec<u8>>
+where
+    F: FnMut(u64) -> Result<()>,
+{
+    if !options.applies_to(data.len()) {
         return Err(EngramError::InvalidFormat(
             "File too small for frame compression".to_string(),
         ));
     }
 
     // Calculate number of frames
-    let frame_count = data.len().div_ceil(FRAME_SIZE);
-    let mut output = Vec::new();
-
-    // Write frame count
-    output.write_all(&(frame_count as u32).to_le_bytes())?;
+    let frame_size = options.frame_size;
+    let frame_count = data.len().div_ceil(frame_size);
+    let mut frame_data_out = Vec::new();
+    let mut index = FrameIndex {
+        frame_size: frame_size as u32,
+        frames: Vec::with_capacity(frame_count),
+    };
+
+    // Interleaved frames carry their count up front and each size inline
+    if layout == FrameLayout::Interleaved {
+        frame_data_out.write_all(&(frame_count as u32).to_le_bytes())?;
+    }
 
     // Compress each frame
     for frame_idx in 0..frame_count {
-        let start = frame_idx * FRAME_SIZE;
-        let end = std::cmp::min(start + FRAME_SIZE, data.len());
+        let start = frame_idx * frame_size;
+        let end = std::cmp::min(start + frame_size, data.len());
         let frame_data = &data[start..end];
 
         // Compress frame
         let compressed_frame = match method {
             CompressionMethod::Lz4 => compress_lz4_frame(frame_data)?,
-            CompressionMethod::Zstd => compress_zstd_frame(frame_data)?,
-            CompressionMethod::None => {
+            CompressionMethod::Zstd => compress_zstd_frame(frame_data, zstd_level)?,
+            CompressionMethod::None
+            | CompressionMethod::Deflate
+            | CompressionMethod::Unknown(_) => {
                 return Err(EngramError::InvalidFormat(
                     "Frame compression requires LZ4 or Zstd".to_string(),
                 ));
             }
         };
 
-        // Write frame size and data
-        output.write_all(&(compressed_frame.len() as u32).to_le_bytes())?;
-        output.write_all(&compressed_frame)?;
+        // Write frame size (interleaved only) and data
+        if layout == FrameLayout::Interleaved {
+            frame_data_out.write_all(&(compressed_frame.len() as u32).to_le_bytes())?;
+        }
+        index.frames.push(FrameInfo {
+            decompressed_offset: start as u64,
+            decompressed_size: frame_data.len() as u32,
+            compressed_offset: frame_data_out.len() as u64,
+            compressed_size: compressed_frame.len() as u32,
+        });
+        frame_data_out.write_all(&compressed_frame)?;
+
+        on_frame(end as u64)?;
     }
 
+    if layout == FrameLayout::Interleaved {
+        return Ok(frame_data_out);
+    }
+
+    // Frame offsets count from the start of the payload, index included
+    let index_size = index.encoded_size() as u64;
+    for frame in &mut index.frames {
+        frame.compressed_offset += index_size;
+    }
+    let mut output = Vec::with_capacity(index_size as usize + frame_data_out.len());
+    index.write_to(&mut output)?;
+    output.extend_from_slice(&frame_data_out);
     Ok(output)
 }
 
 /// Decompress frame-based compressed data
 ///
 /// # Arguments
-/// * `data` - Frame-compressed data with headers
+/// * `data` - Frame-compressed data with its frame index
 /// * `method` - Compression method used
 /// * `expected_size` - Expected uncompressed size for validation
 ///
@@ -72,49 +353,234 @@ pub fn decompress_frames(
     method: CompressionMethod,
     expected_size: u64,
 ) -> Result<Vec<u8>> {
-    let mut cursor = std::io::Cursor::new(data);
-    let mut output = Vec::with_capacity(expected_size as usize);
-
-    // Read frame count
-    let mut frame_count_bytes = [0u8; 4];
-    cursor.read_exact(&mut frame_count_bytes)?;
-    let frame_count = u32::from_le_bytes(frame_count_bytes);
-
-    // Decompress each frame
-    for _ in 0..frame_count {
-        // Read frame size
-        let mut frame_size_bytes = [0u8; 4];
-        cursor.read_exact(&mut frame_size_bytes)?;
-        let frame_size = u32::from_le_bytes(frame_size_bytes) as usize;
-
-        // Read compressed frame data
-        let mut frame_data = vec![0u8; frame_size];
-        cursor.read_exact(&mut frame_data)?;
-
-        // Decompress frame
-        let decompressed_frame = match method {
-            CompressionMethod::Lz4 => decompress_lz4_frame(&frame_data)?,
-            CompressionMethod::Zstd => decompress_zstd_frame(&frame_data)?,
-            CompressionMethod::None => {
-                return Err(EngramError::InvalidFormat(
-                    "Frame compression requires LZ4 or Zstd".to_string(),
-                ));
+    decompress_frames_with(
+        data,
+        method,
+        expected_size,
+        FrameLayout::Indexed,
+        |_| Ok(()),
+    )
+}
+
+/// Decompress only the frames in `frames` (indices into the frame index)
+///
+/// Returns the concatenated uncompressed frames, which start at the first
+/// frame's `decompressed_offset` in the file.
+pub fn decompress_frame_range(
+    data: &[u8],
+    method: CompressionMethod,
+    frames: Range<usize>,
+) -> Result<Vec<u8>> {
+    let index = FrameIndex::read_from(data)?;
+    decompress_indexed_frames(&index, method, frames, |frame| {
+        Ok(stored_frame(data, frame)?.to_vec())
+    })
+}
+
+/// Decompress the frames in `frames`, fetching each compressed frame with `fetch`
+pub(crate) fn decompress_indexed_frames<F>(
+    index: &FrameIndex,
+    method: CompressionMethod,
+    frames: Range<usize>,
+    mut fetch: F,
+) -> Result<Vec<u8>>
+where
+    F: FnMut(&FrameInfo) -> Result<Vec<u8>>,
+{
+    let selected = index.frames.get(frames.clone()).ok_or_else(|| {
+        EngramError::InvalidFormat(format!(
+            "Frames {:?} out of range ({} frames)",
+            frames,
+            index.frames.len()
+        ))
+    })?;
+
+    let mut output = Vec::new();
+    for frame in selected {
+        let decompressed = decompress_frame(&fetch(frame)?, method, index.frame_size as usize)?;
+        check_frame_size(frame, &decompressed)?;
+        output.extend_from_slice(&decompressed);
+    }
+    Ok(output)
+}
+
+/// Decompress frame-based compressed data, reporting progress
+///
+/// `on_frame` receives the number of output bytes produced after each frame;
+/// returning an error aborts decompression.
+pub(crate) fn decompress_frames_with<F>(
+    data: &[u8],
+    method: CompressionMethod,
+    expected_size: u64,
+    layout: FrameLayout,
+    mut on_frame: F,
+) -> Result<Vec<u8>>
+where
+    F: FnMut(u64) -> Result<()>,
+{
+    let mut output = Vec::with_capacity(preallocation(expected_size));
+    for_each_frame(data, method, expected_size, layout, |frame| {
+        output.extend_from_slice(frame);
+        on_frame(output.len() as u64)
+    })?;
+    Ok(output)
+}
+
+/// Decompress frame-based compressed data one frame at a time
+///
+/// `on_frame` receives each frame's output in order, so callers can checksum
+/// or write it without holding the whole result; returning an error aborts
+/// decompression. Fails if the frames do not add up to `expected_size`.
+pub(crate) fn for_each_frame<F>(
+    data: &[u8],
+    method: CompressionMethod,
+    expected_size: u64,
+    layout: FrameLayout,
+    mut on_frame: F,
+) -> Result<()>
+where
+    F: FnMut(&[u8]) -> Result<()>,
+{
+    let mut produced = 0u64;
+
+    if layout == FrameLayout::Indexed {
+        let index = FrameIndex::read_from(data)?;
+        for frame in &index.frames {
+            let decompressed = decompress_frame(
+                stored_frame(data, frame)?,
+                method,
+                index.frame_size as usize,
+            )?;
+            check_frame_size(frame, &decompressed)?;
+
+            produced += decompressed.len() as u64;
+            if produced > expected_size {
+                break;
             }
-        };
+            on_frame(&decompressed)?;
+        }
+    } else {
+        let mut cursor = std::io::Cursor::new(data);
 
-        output.extend_from_slice(&decompressed_frame);
+        // Read frame count
+        let frame_count = read_u32(&mut cursor)?;
+
+        // Decompress each frame
+        for _ in 0..frame_count {
+            // Read frame size
+            let frame_size = read_u32(&mut cursor)? as usize;
+
+            // Read compressed frame data
+            let mut frame_data = vec![0u8; frame_size];
+            cursor.read_exact(&mut frame_data)?;
+
+            // Decompress frame (v1.0 always used the default frame size)
+            let decompressed_frame = decompress_frame(&frame_data, method, FRAME_SIZE)?;
+
+            produced += decompressed_frame.len() as u64;
+            if produced > expected_size {
+                break;
+            }
+            on_frame(&decompressed_frame)?;
+        }
     }
 
     // Validate size
-    if output.len() != expected_size as usize {
-        return Err(EngramError::DecompressionFailed(format!(
+    if produced != expected_size {
+        return Err(EngramError::decompression_failed(format!(
             "Frame decompression size mismatch: expected {}, got {}",
-            expected_size,
-            output.len()
+            expected_size, produced
         )));
     }
 
-    Ok(output)
+    Ok(())
+}
+
+/// Decompress only the first frame of frame-based compressed data
+///
+/// Reads just the frame count (or index) and the first frame from `reader`,
+/// so a file's leading bytes can be inspected without touching the rest of
+/// the payload.
+pub(crate) fn first_frame<R: Read>(
+    mut reader: R,
+    method: CompressionMethod,
+    layout: FrameLayout,
+) -> Result<Vec<u8>> {
+    if layout == FrameLayout::Indexed {
+        let index = FrameIndex::read_from(&mut reader)?;
+        let Some(frame) = index.frames.first() else {
+            return Ok(Vec::new());
+        };
+        // Skip to the first frame (normally right after the index)
+        let gap = frame
+            .compressed_offset
+            .checked_sub(index.encoded_size() as u64)
+            .ok_or_else(|| EngramError::InvalidFormat("Frame overlaps the frame index".into()))?;
+        std::io::copy(&mut (&mut reader).take(gap), &mut std::io::sink())?;
+        let mut frame_data = vec![0u8; frame.compressed_size as usize];
+        reader.read_exact(&mut frame_data)?;
+        return decompress_frame(&frame_data, method, index.frame_size as usize);
+    }
+
+    if read_u32(&mut reader)? == 0 {
+        return Ok(Vec::new());
+    }
+
+    let frame_size = read_u32(&mut reader)?;
+    let mut frame_data = Vec::new();
+    reader
+        .take(frame_size as u64)
+        .read_to_end(&mut frame_data)?;
+    decompress_frame(&frame_data, method, FRAME_SIZE)
+}
+
+/// Compressed bytes of `frame` within an indexed payload
+pub(crate) fn stored_frame<'a>(data: &'a [u8], frame: &FrameInfo) -> Result<&'a [u8]> {
+    usize::try_from(frame.compressed_offset)
+        .ok()
+        .and_then(|start| data.get(start..start.checked_add(frame.compressed_size as usize)?))
+        .ok_or_else(|| {
+            EngramError::InvalidFormat(format!(
+                "Frame at offset {} ({} bytes) lies outside the payload",
+                frame.compressed_offset, frame.compressed_size
+            ))
+        })
+}
+
+/// Check a decompressed frame against its index entry
+fn check_frame_size(frame: &FrameInfo, decompressed: &[u8]) -> Result<()> {
+    if decompressed.len() != frame.decompressed_size as usize {
+        return Err(EngramError::decompression_failed(format!(
+            "Frame at {} decompressed to {} bytes, expected {}",
+            frame.decompressed_offset,
+            decompressed.len(),
+            frame.decompressed_size
+        )));
+    }
+    Ok(())
+}
+
+fn read_u32<R: Read>(mut reader: R) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    reader.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+/// Decompress a single frame of at most `frame_size` bytes
+pub(crate) fn decompress_frame(
+    data: &[u8],
+    method: CompressionMethod,
+    frame_size: usize,
+) -> Result<Vec<u8>> {
+    match method {
+        CompressionMethod::Lz4 => decompress_lz4_frame(data, frame_size),
+        CompressionMethod::Zstd => decompress_zstd_frame(data, frame_size),
+        CompressionMethod::None | CompressionMethod::Deflate | CompressionMethod::Unknown(_) => {
+            Err(EngramError::InvalidFormat(
+                "Frame compression requires LZ4 or Zstd".to_string(),
+            ))
+        }
+    }
 }
 
 /// Compress a single frame with LZ4
@@ -123,24 +589,49 @@ fn compress_lz4_frame(data: &[u8]) -> Result<Vec<u8>> {
 }
 
 /// Compress a single frame with Zstd
-fn compress_zstd_frame(data: &[u8]) -> Result<Vec<u8>> {
-    zstd::encode_all(data, 6).map_err(|e| {
+fn compress_zstd_frame(data: &[u8], level: i32) -> Result<Vec<u8>> {
+    zstd::encode_all(data, level).map_err(|e| {
         EngramError::CompressionFailed(format!("Zstd frame compression failed: {}", e))
     })
 }
 
 /// Decompress a single LZ4 frame
-fn decompress_lz4_frame(data: &[u8]) -> Result<Vec<u8>> {
+fn decompress_lz4_frame(data: &[u8], frame_size: usize) -> Result<Vec<u8>> {
+    // The prepended size is allocated up front; no frame is larger than the frame size
+    let size = data
+        .get(..4)
+        .map(|size| u32::from_le_bytes(size.try_into().unwrap()) as usize);
+    if !matches!(size, Some(size) if size <= frame_size) {
+        return Err(EngramError::decompression_failed(format!(
+            "LZ4 frame size {:?} exceeds {} bytes",
+            size, frame_size
+        )));
+    }
+
     lz4_flex::decompress_size_prepended(data).map_err(|e| {
-        EngramError::DecompressionFailed(format!("LZ4 frame decompression failed: {}", e))
+        EngramError::decompression_failed(format!("LZ4 frame decompression failed: {}", e))
     })
 }
 
 /// Decompress a single Zstd frame
-fn decompress_zstd_frame(data: &[u8]) -> Result<Vec<u8>> {
-    zstd::decode_all(data).map_err(|e| {
-        EngramError::DecompressionFailed(format!("Zstd frame decompression failed: {}", e))
-    })
+fn decompress_zstd_frame(data: &[u8], frame_size: usize) -> Result<Vec<u8>> {
+    let zstd_error = |e: std::io::Error| {
+        EngramError::decompression_failed(format!("Zstd frame decompression failed: {}", e))
+    };
+    // Stop one byte past the frame size so an oversized frame fails the size check
+    let mut output = Vec::new();
+    zstd::stream::read::Decoder::new(data)
+        .map_err(zstd_error)?
+        .take(frame_size as u64 + 1)
+        .read_to_end(&mut output)
+        .map_err(zstd_error)?;
+    if output.len() > frame_size {
+        return Err(EngramError::decompression_failed(format!(
+            "Zstd frame exceeds {} bytes",
+            frame_size
+        )));
+    }
+    Ok(output)
 }
 
 /// Check if a file should use frame-based compression
@@ -194,6 +685,104 @@ mod tests {
         assert_eq!(decompressed, data);
     }
 
+    #[test]
+    fn test_frame_index_round_trip() {
+        let size = 60 * 1024 * 1024 + 100;
+        let data: Vec<u8> = (0..size).map(|i| (i / 1000 % 251) as u8).collect();
+        let compressed = compress_frames(&data, CompressionMethod::Lz4).unwrap();
+
+        let index = FrameIndex::read_from(compressed.as_slice()).unwrap();
+        assert_eq!(index.frame_size(), FRAME_SIZE as u32);
+        assert_eq!(index.frames().len(), 961);
+        assert_eq!(index.frames()[960].decompressed_size, 100);
+        assert_eq!(index.decompressed_size(), size as u64);
+        assert_eq!(
+            index.frames()[0].compressed_offset,
+            index.encoded_size() as u64
+        );
+
+        let mut encoded = Vec::new();
+        index.write_to(&mut encoded).unwrap();
+        assert_eq!(encoded, compressed[..index.encoded_size()]);
+    }
+
+    #[test]
+    fn test_frame_index_rejects_oversized_frames() {
+        let data = vec![7u8; MIN_FRAME_COMPRESSION_SIZE];
+        let compressed = compress_frames(&data, CompressionMethod::Zstd).unwrap();
+
+        let mut frame_size_too_large = compressed.clone();
+        frame_size_too_large[..4].copy_from_slice(&(MAX_FRAME_SIZE as u32 + 1).to_le_bytes());
+        assert!(matches!(
+            FrameIndex::read_from(frame_size_too_large.as_slice()),
+            Err(EngramError::InvalidFormat(_))
+        ));
+
+        // First entry's decompressed size, one byte past the frame size
+        let mut frame_too_long = compressed;
+        let field = FRAME_INDEX_HEADER_SIZE + 8;
+        frame_too_long[field..field + 4].copy_from_slice(&(FRAME_SIZE as u32 + 1).to_le_bytes());
+        assert!(matches!(
+            FrameIndex::read_from(frame_too_long.as_slice()),
+            Err(EngramError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_frames_overlapping() {
+        let size = 60 * 1024 * 1024;
+        let compressed = compress_frames(&vec![1u8; size], CompressionMethod::Zstd).unwrap();
+        let index = FrameIndex::read_from(compressed.as_slice()).unwrap();
+        let frame = FRAME_SIZE as u64;
+
+        assert_eq!(index.frames_overlapping(0..1), 0..1);
+        assert_eq!(index.frames_overlapping(frame - 1..frame + 1), 0..2);
+        assert_eq!(index.frames_overlapping(frame..2 * frame), 1..2);
+        assert_eq!(index.frames_overlapping(5..5), 0..0);
+        assert_eq!(
+            index.frames_overlapping(size as u64..size as u64 + 10),
+            960..960
+        );
+    }
+
+    #[test]
+    fn test_decompress_frame_range() {
+        let size = 60 * 1024 * 1024;
+        let data: Vec<u8> = (0..size).map(|i| (i % 253) as u8).collect();
+        let compressed = compress_frames(&data, CompressionMethod::Lz4).unwrap();
+
+        let frames = decompress_frame_range(&compressed, CompressionMethod::Lz4, 3..5).unwrap();
+        assert_eq!(frames, data[3 * FRAME_SIZE..5 * FRAME_SIZE]);
+
+        assert!(decompress_frame_range(&compressed, CompressionMethod::Lz4, 959..962).is_err());
+    }
+
+    #[test]
+    fn test_interleaved_layout_round_trip() {
+        let size = 60 * 1024 * 1024;
+        let data: Vec<u8> = (0..size).map(|i| (i % 256) as u8).collect();
+        let compressed = compress_frames_with(
+            &data,
+            CompressionMethod::Lz4,
+            DEFAULT_ZSTD_LEVEL,
+            FrameLayout::Interleaved,
+            FrameOptions::default(),
+            |_| Ok(()),
+        )
+        .unwrap();
+        assert_eq!(compressed[..4], 960u32.to_le_bytes());
+
+        let decompressed = decompress_frames_with(
+            &compressed,
+            CompressionMethod::Lz4,
+            size as u64,
+            FrameLayout::Interleaved,
+            |_| Ok(()),
+        )
+        .unwrap();
+        assert_eq!(decompressed, data);
+    }
+
     #[test]
     fn test_should_use_frames() {
         assert!(!should_use_frames(10 * 1024 * 1024)); // 10MB - no
@@ -0,0 +1,82 @@
+use crate::archive::format::{EncryptionMode, EntryInfo, FileHeader};
+use serde::Serialize;
+
+/// Machine-readable listing of an archive (see `ArchiveReader::inventory`)
+///
+/// Serializes to JSON as:
+///
+/// ```json
+/// {
+///   "version": "1.0",
+///   "entry_count": 1,
+///   "encryption_mode": "none",
+///   "entries": [
+///     {
+///       "path": "config.toml",
+///       "uncompressed_size": 17,
+///       "compressed_size": 17,
+///       "crc32": "0a1b2c3d",
+///       "compression": "none",
+///       "modified_time": 1700000000
+///     }
+///   ]
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ArchiveInventory {
+    /// Format version from the header, as `major.minor`
+    pub version: String,
+    /// Number of entries in the central directory
+    pub entry_count: usize,
+    /// `none`, `archive` or `per-file`
+    pub encryption_mode: String,
+    /// Entries in central directory order
+    pub entries: Vec<InventoryEntry>,
+}
+
+/// One entry of an `ArchiveInventory`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InventoryEntry {
+    pub path: String,
+    pub uncompressed_size: u64,
+    /// Size as stored, including encryption overhead
+    pub compressed_size: u64,
+    /// CRC32 of the uncompressed content, as 8 lowercase hex digits
+    pub crc32: String,
+    /// `none`, `lz4`, `zstd`, `deflate`, or `unknown(<byte>)` for newer methods
+    pub compression: String,
+    /// Modification time in seconds since the Unix epoch
+    pub modified_time: u64,
+}
+
+impl ArchiveInventory {
+    pub(crate) fn new<'a>(
+        header: &FileHeader,
+        entries: impl ExactSizeIterator<Item = &'a EntryInfo>,
+    ) -> Self {
+        let encryption_mode = match header.encryption_mode() {
+            EncryptionMode::None => "none",
+            EncryptionMode::Archive => "archive",
+            EncryptionMode::PerFile => "per-file",
+        };
+        Self {
+            version: format!("{}.{}", header.version_major, header.version_minor),
+            entry_count: entries.len(),
+            encryption_mode: encryption_mode.to_string(),
+            entries: entries.map(InventoryEntry::from).collect(),
+        }
+    }
+}
+
+impl From<&EntryInfo> for InventoryEntry {
+    fn from(entry: &EntryInfo) -> Self {
+        Self {
+            path: entry.path.clone(),
+            uncompressed_size: entry.uncompressed_size,
+            compressed_size: entry.compressed_size,
+            crc32: format!("{:08x}", entry.crc32),
+            compression: entry.compression.to_string(),
+            modified_time: entry.modified_time,
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::archive::format::CompressionMethod;
+use crate::archive::format::{CompressionMethod, MAX_PATH_LENGTH};
 use crate::error::{EngramError, Result};
 use std::io::{Read, Write};
 
@@ -19,7 +19,8 @@ pub const LOCAL_ENTRY_SIGNATURE: [u8; 4] = [0x4C, 0x4F, 0x43, 0x41]; // "LOCA"
 /// - Compression Method: uint8 (1 byte)
 /// - Flags: uint8 (1 byte)
 /// - Path Length: uint16 (2 bytes)
-/// - Reserved: 4 bytes
+/// - Unix Mode: uint32 (4 bytes, 0 = unspecified; reserved before mode support;
+///   bits 16-31 hold the entry's MIME type id, see `EntryInfo::mime_id`)
 /// - File Path: variable (null-terminated UTF-8)
 #[derive(Debug, Clone)]
 pub struct LocalEntryHeader {
@@ -29,6 +30,7 @@ pub struct LocalEntryHeader {
     pub modified_time: u64,
     pub compression: CompressionMethod,
     pub flags: u8,
+    pub mode: u32,
     pub path: String,
 }
 
@@ -49,6 +51,7 @@ impl LocalEntryHeader {
             modified_time,
             compression,
             flags: 0,
+            mode: 0,
             path,
         }
     }
@@ -78,7 +81,7 @@ impl LocalEntryHeader {
         bytes_written += 8;
 
         // Compression method
-        writer.write_all(&[self.compression as u8])?;
+        writer.write_all(&[self.compression.to_u8()])?;
         bytes_written += 1;
 
         // Flags
@@ -87,19 +90,19 @@ impl LocalEntryHeader {
 
         // Path length
         let path_bytes = self.path.as_bytes();
-        if path_bytes.len() > u16::MAX as usize {
+        if path_bytes.len() > MAX_PATH_LENGTH {
             return Err(EngramError::PathError(format!(
                 "Path too long: {} bytes (max {})",
                 path_bytes.len(),
-                u16::MAX
+                MAX_PATH_LENGTH
             )));
         }
         let path_len = path_bytes.len() as u16;
         writer.write_all(&path_len.to_le_bytes())?;
         bytes_written += 2;
 
-        // Reserved (4 bytes)
-        writer.write_all(&[0u8; 4])?;
+        // Unix mode
+        writer.write_all(&self.mode.to_le_bytes())?;
         bytes_written += 4;
 
         // Path (null-terminated)
@@ -129,16 +132,20 @@ impl LocalEntryHeader {
 
         let mut compression_byte = [0u8; 1];
         reader.read_exact(&mut compression_byte)?;
-        let compression = CompressionMethod::from_u8(compression_byte[0])?;
+        let compression = CompressionMethod::from_u8_lossy(compression_byte[0]);
 
         let mut flags = [0u8; 1];
         reader.read_exact(&mut flags)?;
 
         let path_len = read_u16(&mut reader)?;
+        if path_len as usize > MAX_PATH_LENGTH {
+            return Err(EngramError::InvalidFormat(format!(
+                "Local entry path length {} exceeds {} bytes",
+                path_len, MAX_PATH_LENGTH
+            )));
+        }
 
-        // Skip reserved bytes
-        let mut reserved = [0u8; 4];
-        reader.read_exact(&mut reserved)?;
+        let mode = read_u32(&mut reader)?;
 
         // Read path
         let mut path_buf = vec![0u8; path_len as usize];
@@ -163,6 +170,7 @@ impl LocalEntryHeader {
             modified_time,
             compression,
             flags: flags[0],
+            mode,
             path,
         })
     }
@@ -177,7 +185,7 @@ impl LocalEntryHeader {
         1 + // Compression method
         1 + // Flags
         2 + // Path length
-        4 + // Reserved
+        4 + // Unix mode
         self.path.len() + 1 // Path + null terminator
     }
 }
@@ -241,4 +249,16 @@ mod tests {
             .to_string()
             .contains("Invalid local entry signature"));
     }
+
+    #[test]
+    fn test_oversized_path_length_rejected() {
+        let entry = LocalEntryHeader::new(1, 1, 0, 0, CompressionMethod::None, "a".to_string());
+        let mut buf = Vec::new();
+        entry.write_to(&mut buf).unwrap();
+        // Path length field follows signature, sizes, CRC, time, compression and flags
+        buf[34..36].copy_from_slice(&300u16.to_le_bytes());
+
+        let result = LocalEntryHeader::read_from(&buf[..]);
+        assert!(matches!(result, Err(EngramError::InvalidFormat(_))));
+    }
 }
@@ -0,0 +1,202 @@
+use crate::error::{EngramError, Result};
+
+/// Archive path of the custom MIME type table (one type per line, stored uncompressed)
+pub const MIME_TABLE_PATH: &str = ".engram/mime.tbl";
+
+/// First MIME type id that refers to a line of `MIME_TABLE_PATH`
+///
+/// Ids `1..MIME_TABLE_BASE` name `BUILTIN_MIME_TYPES`; 0 means no MIME type.
+pub const MIME_TABLE_BASE: u16 = 0x8000;
+
+/// Maximum length of a MIME type in bytes
+pub const MAX_MIME_TYPE_LENGTH: usize = 255;
+
+/// MIME types with a fixed id: id `n` names `BUILTIN_MIME_TYPES[n - 1]`
+///
+/// Ids are stored in archives, so types are only ever appended.
+pub const BUILTIN_MIME_TYPES: [&str; 34] = [
+    "application/octet-stream",
+    "text/plain",
+    "text/html",
+    "text/css",
+    "text/csv",
+    "text/markdown",
+    "text/javascript",
+    "application/json",
+    "application/x-ndjson",
+    "application/xml",
+    "application/yaml",
+    "application/toml",
+    "application/pdf",
+    "application/zip",
+    "application/gzip",
+    "application/wasm",
+    "application/vnd.sqlite3",
+    "image/png",
+    "image/jpeg",
+    "image/gif",
+    "image/webp",
+    "image/avif",
+    "image/svg+xml",
+    "image/x-icon",
+    "audio/mpeg",
+    "audio/ogg",
+    "audio/wav",
+    "video/mp4",
+    "video/webm",
+    "font/woff",
+    "font/woff2",
+    "font/ttf",
+    "font/otf",
+    "text/xml",
+];
+
+/// File extension (lowercase) -> MIME type, for `mime_type_for_path`
+const EXTENSIONS: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("html", "text/html"),
+    ("htm", "text/html"),
+    ("css", "text/css"),
+    ("csv", "text/csv"),
+    ("md", "text/markdown"),
+    ("js", "text/javascript"),
+    ("mjs", "text/javascript"),
+    ("json", "application/json"),
+    ("ndjson", "application/x-ndjson"),
+    ("xml", "application/xml"),
+    ("yaml", "application/yaml"),
+    ("yml", "application/yaml"),
+    ("toml", "application/toml"),
+    ("pdf", "application/pdf"),
+    ("zip", "application/zip"),
+    ("gz", "application/gzip"),
+    ("wasm", "application/wasm"),
+    ("db", "application/vnd.sqlite3"),
+    ("sqlite", "application/vnd.sqlite3"),
+    ("sqlite3", "application/vnd.sqlite3"),
+    ("png", "image/png"),
+    ("jpg", "image/jpeg"),
+    ("jpeg", "image/jpeg"),
+    ("gif", "image/gif"),
+    ("webp", "image/webp"),
+    ("avif", "image/avif"),
+    ("svg", "image/svg+xml"),
+    ("ico", "image/x-icon"),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("wav", "audio/wav"),
+    ("mp4", "video/mp4"),
+    ("webm", "video/webm"),
+    ("woff", "font/woff"),
+    ("woff2", "font/woff2"),
+    ("ttf", "font/ttf"),
+    ("otf", "font/otf"),
+];
+
+/// MIME type conventionally used for `path`, judged by its extension
+///
+/// `ArchiveWriter::add_file_from_disk` records this for the files it adds.
+/// `None` for extensions not in the built-in table.
+pub fn mime_type_for_path(path: &str) -> Option<&'static str> {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    let (_, extension) = name.rsplit_once('.')?;
+    let extension = extension.to_ascii_lowercase();
+    EXTENSIONS
+        .iter()
+        .find(|(known, _)| *known == extension)
+        .map(|&(_, mime)| mime)
+}
+
+/// Built-in MIME type with id `id`
+pub(crate) fn builtin_mime_type(id: u16) -> Option<&'static str> {
+    BUILTIN_MIME_TYPES
+        .get((id as usize).checked_sub(1)?)
+        .copied()
+}
+
+/// Id of a built-in MIME type (compared ignoring ASCII case)
+pub(crate) fn builtin_mime_id(mime: &str) -> Option<u16> {
+    BUILTIN_MIME_TYPES
+        .iter()
+        .position(|known| known.eq_ignore_ascii_case(mime))
+        .map(|index| index as u16 + 1)
+}
+
+/// Refuse MIME types that cannot be stored as one line of the table
+pub(crate) fn validate_mime_type(mime: &str) -> Result<()> {
+    let valid = !mime.is_empty()
+        && mime.len() <= MAX_MIME_TYPE_LENGTH
+        && mime.contains('/')
+        && !mime.chars().any(char::is_control);
+    if !valid {
+        return Err(EngramError::InvalidOptions(format!(
+            "Invalid MIME type: {:?}",
+            mime
+        )));
+    }
+    Ok(())
+}
+
+/// Contents of `MIME_TABLE_PATH` for `types`
+pub(crate) fn table_bytes(types: &[String]) -> Vec<u8> {
+    types.join("\n").into_bytes()
+}
+
+/// Parse the contents of `MIME_TABLE_PATH`
+pub(crate) fn parse_table(bytes: &[u8]) -> Result<Vec<String>> {
+    let text = std::str::from_utf8(bytes).map_err(|e| {
+        EngramError::InvalidFormat(format!("{} is not valid UTF-8: {}", MIME_TABLE_PATH, e))
+    })?;
+    if text.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(text.split('\n').map(str::to_string).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_ids_round_trip() {
+        for (index, mime) in BUILTIN_MIME_TYPES.iter().enumerate() {
+            let id = builtin_mime_id(mime).unwrap();
+            assert_eq!(id as usize, index + 1);
+            assert_eq!(builtin_mime_type(id), Some(*mime));
+        }
+        assert_eq!(builtin_mime_id("Image/PNG"), builtin_mime_id("image/png"));
+        assert_eq!(builtin_mime_type(0), None);
+        assert_eq!(builtin_mime_type(MIME_TABLE_BASE), None);
+        assert!((BUILTIN_MIME_TYPES.len() as u16) < MIME_TABLE_BASE);
+    }
+
+    #[test]
+    fn test_extensions_map_to_builtin_types() {
+        for (extension, mime) in EXTENSIONS {
+            assert!(builtin_mime_id(mime).is_some(), "{}", extension);
+        }
+        assert_eq!(mime_type_for_path("site/index.HTML"), Some("text/html"));
+        assert_eq!(
+            mime_type_for_path("archive.tar.gz"),
+            Some("application/gzip")
+        );
+        assert_eq!(mime_type_for_path("Makefile"), None);
+        assert_eq!(mime_type_for_path("build.d/Makefile"), None);
+        assert_eq!(mime_type_for_path("data.unknownext"), None);
+    }
+
+    #[test]
+    fn test_table_round_trip() {
+        let types = vec![
+            "application/vnd.acme+json".to_string(),
+            "text/plain; charset=latin1".to_string(),
+        ];
+        assert_eq!(parse_table(&table_bytes(&types)).unwrap(), types);
+        assert!(parse_table(b"").unwrap().is_empty());
+
+        assert!(validate_mime_type("text/plain; charset=utf-8").is_ok());
+        for invalid in ["", "plain", "text/plain\nimage/png", &"a/".repeat(200)] {
+            assert!(validate_mime_type(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+}
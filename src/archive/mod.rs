@@ -1,18 +1,83 @@
+#[cfg(feature = "async")]
+mod async_reader;
+mod blake3_table;
+mod cache;
+mod cancellation;
+mod chunking;
+mod compression_policy;
+mod dictionary;
+mod editor;
 mod end_record;
+mod endr_signature;
+mod entry_metadata;
 mod format;
 mod frame_compression;
+mod inventory;
 mod local_entry;
+mod mime;
+mod progress;
 mod reader;
+mod recipients;
+mod rekey;
+mod shared;
+mod stored_crc;
+mod volume;
 mod writer;
 
+#[cfg(feature = "async")]
+pub use async_reader::AsyncArchiveReader;
+pub use blake3_table::BLAKE3_TABLE_PATH;
+pub use cache::CacheStats;
+pub use cancellation::CancellationToken;
+pub use chunking::{ChunkerConfig, CHUNKS_PREFIX, MIN_CHUNK_SIZE};
+pub use compression_policy::{CompressionPolicy, DefaultPolicy, ForceMethod};
+pub use dictionary::{train_dictionary, ZSTD_DICTIONARY_PATH};
+pub use editor::ArchiveEditor;
 pub use end_record::{EndRecord, END_RECORD_SIGNATURE, END_RECORD_SIZE};
+pub use endr_signature::{SIGNATURE_BLOCK_SIGNATURE, SIGNATURE_BLOCK_SIZE};
+pub use entry_metadata::{EntryMetadata, ENTRY_METADATA_PATH, MAX_ENTRY_METADATA_SIZE};
 pub use format::{
-    CompressionMethod, EntryInfo, FileHeader, CD_ENTRY_SIZE, FORMAT_VERSION_MAJOR,
-    FORMAT_VERSION_MINOR, HEADER_SIZE, MAGIC_NUMBER, MAX_PATH_LENGTH,
+    is_reserved_path, ArchiveLabel, CompressionMethod, EncryptionMode, EntryInfo, FileHeader,
+    RawEntry, ARCHIVE_LABEL_LEN, CD_ENTRY_SIZE, DEFAULT_ZSTD_LEVEL, ENTRY_FLAG_ALIAS,
+    ENTRY_FLAG_CHUNKED, ENTRY_FLAG_DEDUPLICATED, ENTRY_FLAG_DIRECTORY, ENTRY_FLAG_EXECUTABLE,
+    ENTRY_FLAG_FRAMED, ENTRY_FLAG_SHA256, ENTRY_FLAG_ZSTD_DICTIONARY, FORMAT_VERSION_MAJOR,
+    FORMAT_VERSION_MINOR, HEADER_FLAG_BLAKE3, HEADER_FLAG_CHUNKED, HEADER_FLAG_ENTRY_AAD,
+    HEADER_FLAG_RECIPIENTS, HEADER_FLAG_SORTED_DIRECTORY, HEADER_SIZE, MAGIC_NUMBER, MANIFEST_PATH,
+    MAX_PATH_LENGTH, RESERVED_PREFIX, SHA256_PREFIX_LEN,
 };
+#[cfg(feature = "zip-convert")]
+pub(crate) use frame_compression::preallocation;
 pub use frame_compression::{
-    compress_frames, decompress_frames, should_use_frames, FRAME_SIZE, MIN_FRAME_COMPRESSION_SIZE,
+    compress_frames, decompress_frame_range, decompress_frames, should_use_frames, FrameIndex,
+    FrameInfo, FrameOptions, FRAME_INDEX_ENTRY_SIZE, FRAME_SIZE, MAX_FRAME_SIZE,
+    MIN_FRAME_COMPRESSION_SIZE, MIN_FRAME_SIZE,
 };
+pub use inventory::{ArchiveInventory, InventoryEntry};
 pub use local_entry::{LocalEntryHeader, LOCAL_ENTRY_SIGNATURE};
-pub use reader::ArchiveReader;
-pub use writer::ArchiveWriter;
+pub use mime::{
+    mime_type_for_path, BUILTIN_MIME_TYPES, MAX_MIME_TYPE_LENGTH, MIME_TABLE_BASE, MIME_TABLE_PATH,
+};
+pub use progress::{ProgressCallback, ProgressEvent};
+pub use reader::{
+    ArchiveReader, ArchiveSummary, ContentsIter, EntryStatus, VerifyReport, VERIFY_END_RECORD,
+    VERIFY_HEADER,
+};
+pub use recipients::{
+    recipient_public_key, MAX_RECIPIENT_ID_LENGTH, MIN_PASSWORD_ITERATIONS, PASSWORD_ITERATIONS,
+    RECIPIENTS_SIGNATURE,
+};
+pub use rekey::{
+    rekey_archive, rekey_archive_with, update_recipients, RecipientChanges, RekeyOptions,
+    RekeyReport,
+};
+pub use shared::SharedArchive;
+pub use stored_crc::COMPRESSED_CRC_PATH;
+pub use volume::{
+    volume_path, VolumeHeader, MIN_VOLUME_SIZE, VOLUME_HEADER_SIZE, VOLUME_SIGNATURE,
+};
+#[cfg(feature = "zip-convert")]
+pub(crate) use writer::EntryAttributes;
+pub(crate) use writer::{normalize_path, validate_path};
+pub use writer::{
+    ArchiveWriter, ArchiveWriterBuilder, FileMetadata, FinalizeSummary, PRECOMPRESSED_OVERHEAD,
+};
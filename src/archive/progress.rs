@@ -0,0 +1,98 @@
+use crate::error::{EngramError, Result};
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Mutex, PoisonError};
+
+/// Progress notification emitted by `ArchiveWriter` and `ArchiveReader`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// Processing of an entry started; `size` is the uncompressed size
+    FileStarted { path: String, size: u64 },
+    /// Uncompressed bytes processed so far for the current entry
+    ///
+    /// Emitted once per 64KB frame for frame-compressed entries, and once per
+    /// entry otherwise.
+    BytesProcessed {
+        path: String,
+        bytes_done: u64,
+        total: u64,
+    },
+    /// Processing of an entry finished; `compressed_size` is the stored payload size
+    FileFinished { path: String, compressed_size: u64 },
+    /// The writer is writing the central directory and header
+    Finalizing,
+}
+
+/// Boxed callback receiving progress events
+///
+/// `with_progress` accepts any `FnMut(ProgressEvent) + Send` closure; this
+/// alias is for callers that need to name or store one.
+pub type ProgressCallback = Box<dyn FnMut(ProgressEvent) + Send>;
+
+/// Optional progress callback with panic isolation
+///
+/// A panicking callback is dropped and the current operation returns
+/// `EngramError::CallbackPanicked`. Entries completed before the panic are
+/// unaffected; no further events are delivered.
+///
+/// The callback is only reached through `&mut self`, so the mutex is never
+/// locked; it keeps readers and writers `Sync`.
+#[derive(Default)]
+pub(crate) struct Progress {
+    callback: Mutex<Option<ProgressCallback>>,
+}
+
+impl Progress {
+    pub fn new(callback: ProgressCallback) -> Self {
+        Self {
+            callback: Mutex::new(Some(callback)),
+        }
+    }
+
+    /// Deliver an event; `event` is only evaluated when a callback is set
+    pub fn emit(&mut self, event: impl FnOnce() -> ProgressEvent) -> Result<()> {
+        let slot = self
+            .callback
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner);
+        let Some(callback) = slot.as_mut() else {
+            return Ok(());
+        };
+
+        let event = event();
+        if catch_unwind(AssertUnwindSafe(|| callback(event))).is_err() {
+            *slot = None;
+            return Err(EngramError::CallbackPanicked);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn test_emit_without_callback() {
+        let mut progress = Progress::default();
+        progress
+            .emit(|| unreachable!("event built without a callback"))
+            .unwrap();
+    }
+
+    #[test]
+    fn test_panicking_callback_is_dropped() {
+        let calls = Arc::new(Mutex::new(0));
+        let counter = calls.clone();
+        let mut progress = Progress::new(Box::new(move |_| {
+            *counter.lock().unwrap() += 1;
+            panic!("callback failure");
+        }));
+
+        let result = progress.emit(|| ProgressEvent::Finalizing);
+        assert!(matches!(result, Err(EngramError::CallbackPanicked)));
+
+        progress.emit(|| ProgressEvent::Finalizing).unwrap();
+        assert_eq!(*calls.lock().unwrap(), 1);
+    }
+}
@@ -1,38 +1,703 @@
+use crate::archive::blake3_table::{self, BLAKE3_TABLE_PATH};
+use crate::archive::cache::{CacheStats, EntryCache};
+use crate::archive::cancellation::{CancellationToken, CANCEL_CHECK_INTERVAL};
+use crate::archive::chunking::{self, ChunkRef};
+use crate::archive::dictionary::{decompress_with_dictionary, ZSTD_DICTIONARY_PATH};
 use crate::archive::end_record::{EndRecord, END_RECORD_SIZE};
-use crate::archive::format::{CompressionMethod, EncryptionMode, EntryInfo, FileHeader};
-use crate::archive::frame_compression::{decompress_frames, should_use_frames};
+use crate::archive::endr_signature::{SignatureBlock, SIGNATURE_BLOCK_SIZE};
+use crate::archive::entry_metadata::{self, EntryMetadata, ENTRY_METADATA_PATH};
+use crate::archive::format::{
+    entry_aad, is_reserved_path, ArchiveLabel, CompressionMethod, EncryptionMode, EntryInfo,
+    FileHeader, RawEntry, CD_ENTRY_SIZE, ENTRY_FLAG_EXECUTABLE, ENTRY_FLAG_FRAMED,
+    ENTRY_FLAG_SHA256, ENTRY_FLAG_ZSTD_DICTIONARY, HEADER_SIZE, MANIFEST_PATH, SHA256_PREFIX_LEN,
+};
+use crate::archive::frame_compression::{
+    decompress_indexed_frames, first_frame, for_each_frame, preallocation, should_use_frames,
+    stored_frame, FrameIndex, FrameInfo, FrameLayout,
+};
+use crate::archive::inventory::ArchiveInventory;
 use crate::archive::local_entry::LocalEntryHeader;
+use crate::archive::mime::{self, builtin_mime_type, MIME_TABLE_BASE, MIME_TABLE_PATH};
+use crate::archive::progress::{Progress, ProgressEvent};
+use crate::archive::recipients::{self, WrappedKey};
+use crate::archive::stored_crc::{self, COMPRESSED_CRC_PATH};
+use crate::archive::volume::VolumeReader;
+use crate::archive::{normalize_path, validate_path};
+use crate::crypto::SecretKey;
 use crate::error::{EngramError, Result};
 use aes_gcm::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, AeadInPlace, KeyInit, Payload},
     Aes256Gcm, Nonce,
 };
-use std::collections::HashMap;
+use ed25519_dalek::VerifyingKey;
+use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs::File;
-use std::io::{Cursor, Read, Seek, SeekFrom};
-use std::path::Path;
+use std::io::{BufReader, Cursor, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// Canonical form of a path used for lookups
+///
+/// Stored paths use forward slashes and never start with a slash (the writer
+/// applies `normalize_path` and `validate_path`), so backslashes are converted
+/// and leading slashes dropped before matching.
+fn canonical_lookup_path(path: &str) -> String {
+    normalize_path(path).trim_start_matches('/').to_string()
+}
+
+/// Apply stored permission bits to an extracted file
+///
+/// The setuid, setgid and sticky bits are dropped unless `special_bits`.
+#[cfg(unix)]
+fn apply_mode(path: &Path, mode: u32, special_bits: bool) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mask = if special_bits { 0o7777 } else { 0o777 };
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(mode & mask))?;
+    Ok(())
+}
+
+/// Apply stored permission bits to an extracted file (no-op off Unix)
+#[cfg(not(unix))]
+fn apply_mode(_path: &Path, _mode: u32, _special_bits: bool) -> Result<()> {
+    Ok(())
+}
+
+/// Name under which `verify_all` reports a header failure
+pub const VERIFY_HEADER: &str = "<header>";
+
+/// Name under which `verify_all` reports an End Record failure
+pub const VERIFY_END_RECORD: &str = "<end record>";
+
+/// Archive-level facts from the header and End Record, from `ArchiveReader::peek`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ArchiveSummary {
+    pub version_major: u16,
+    pub version_minor: u16,
+    pub entry_count: u32,
+    pub cd_offset: u64,
+    pub cd_size: u64,
+    /// Whole-archive CRC32 from the End Record (0 if not recorded, and in v0.x)
+    pub archive_crc32: u32,
+    pub encryption_mode: EncryptionMode,
+}
+
+/// Result of `ArchiveReader::verify_all`
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// Entries that passed every check
+    pub ok: Vec<String>,
+    /// Entries (or archive structures) that failed, with the first error found
+    pub failed: Vec<(String, EngramError)>,
+    /// Intact entries that `manifest.json` lists no hash for, when it lists files
+    pub missing_from_manifest: Vec<String>,
+    /// Decompressed bytes of the entries read
+    pub bytes_verified: u64,
+    /// Wall-clock time of the check (zero on wasm32-unknown-unknown)
+    pub elapsed: Duration,
+}
+
+impl VerifyReport {
+    /// True when nothing failed and the manifest accounts for every entry
+    pub fn is_ok(&self) -> bool {
+        self.failed.is_empty() && self.missing_from_manifest.is_empty()
+    }
+
+    /// Outcome for one entry (or `VERIFY_HEADER` / `VERIFY_END_RECORD`)
+    ///
+    /// `None` if the report does not mention `path`.
+    pub fn status(&self, path: &str) -> Option<EntryStatus> {
+        if let Some((_, err)) = self.failed.iter().find(|(failed, _)| failed == path) {
+            return Some(match err.root_cause() {
+                EngramError::CrcMismatch { .. } => EntryStatus::CrcMismatch,
+                EngramError::HashMismatch { .. } => EntryStatus::HashMismatch,
+                EngramError::DecompressionFailed { .. } | EngramError::DecryptionFailed => {
+                    EntryStatus::DecompressFailed
+                }
+                _ => EntryStatus::Failed,
+            });
+        }
+        if self.missing_from_manifest.iter().any(|p| p == path) {
+            return Some(EntryStatus::MissingFromManifest);
+        }
+        self.ok.iter().any(|p| p == path).then_some(EntryStatus::Ok)
+    }
+}
+
+/// Per-entry outcome of `verify_all` (see `VerifyReport::status`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntryStatus {
+    /// Passed every check
+    Ok,
+    /// Decompressed content does not match the stored CRC32
+    CrcMismatch,
+    /// Content does not match the stored SHA-256 or the manifest's hash
+    HashMismatch,
+    /// Payload could not be decompressed (or decrypted)
+    DecompressFailed,
+    /// Intact, but `manifest.json` lists files and not this one
+    MissingFromManifest,
+    /// Any other error, such as a LOCA header that disagrees with the directory
+    Failed,
+}
+
+/// `Write` sink for `verify_all`: counts bytes and hashes them for the manifest
+struct VerifySink {
+    bytes: u64,
+    sha256: Option<Sha256>,
+}
+
+impl Write for VerifySink {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.bytes += buf.len() as u64;
+        if let Some(sha256) = &mut self.sha256 {
+            sha256.update(buf);
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Running CRC32, SHA-256 and BLAKE3 over an entry's uncompressed content
+struct ContentCheck {
+    crc: crc32fast::Hasher,
+    /// Only for entries that record a SHA-256 prefix
+    sha256: Option<Sha256>,
+    /// Only for entries with a digest in `BLAKE3_TABLE_PATH`
+    blake3: Option<blake3::Hasher>,
+}
+
+impl ContentCheck {
+    fn new(entry: &EntryInfo) -> Self {
+        Self {
+            crc: crc32fast::Hasher::new(),
+            sha256: entry.sha256.map(|_| Sha256::new()),
+            blake3: entry.blake3().map(|_| blake3::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.crc.update(data);
+        if let Some(sha256) = &mut self.sha256 {
+            sha256.update(data);
+        }
+        if let Some(blake3) = &mut self.blake3 {
+            blake3.update(data);
+        }
+    }
+
+    /// Compare against the entry's recorded CRC32, SHA-256 and BLAKE3
+    fn finish(self, entry: &EntryInfo) -> Result<()> {
+        let computed_crc = self.crc.finalize();
+        if computed_crc != entry.crc32 {
+            return Err(EngramError::CrcMismatch {
+                path: entry.path.clone(),
+                expected: entry.crc32,
+                actual: computed_crc,
+            });
+        }
+
+        if let (Some(expected), Some(sha256)) = (entry.sha256, self.sha256) {
+            let digest = sha256.finalize();
+            let actual = &digest[..SHA256_PREFIX_LEN];
+            if actual != expected {
+                return Err(EngramError::HashMismatch {
+                    path: entry.path.clone(),
+                    expected: hex::encode(expected),
+                    actual: hex::encode(actual),
+                });
+            }
+        }
+
+        if let (Some(expected), Some(blake3)) = (entry.blake3(), self.blake3) {
+            let actual = blake3.finalize();
+            if actual.as_bytes() != &expected {
+                return Err(EngramError::HashMismatch {
+                    path: entry.path.clone(),
+                    expected: hex::encode(expected),
+                    actual: actual.to_hex().to_string(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Read one central directory entry and check its payload lies before
+/// `payload_end`
+///
+/// Paths are checked as `EntryInfo::read_from` does unless `lenient_paths`.
+fn read_directory_entry<R: Read>(
+    mut reader: R,
+    payload_end: u64,
+    lenient_paths: bool,
+) -> Result<EntryInfo> {
+    let mut buf = [0u8; CD_ENTRY_SIZE];
+    reader.read_exact(&mut buf)?;
+    let entry = if lenient_paths {
+        EntryInfo::read_from_lenient(&buf[..])?
+    } else {
+        EntryInfo::read_from(&buf[..])?
+    };
+    check_entry_bounds(&entry, payload_end)?;
+    Ok(entry)
+}
+
+/// Set each entry's `blake3` from `digests`, keyed by data offset
+fn attach_blake3(digests: &HashMap<u64, [u8; 32]>, entries: &mut [EntryInfo]) {
+    for entry in entries {
+        entry.blake3 = digests.get(&entry.data_offset).copied();
+    }
+}
+
+/// Decompression error for a malformed Deflate stream
+fn deflate_error(e: std::io::Error) -> EngramError {
+    EngramError::decompression_failed(format!("Deflate decompression failed: {}", e))
+}
+
+/// Attach the index and offset of the central directory entry `err` came from
+fn directory_entry_error(err: EngramError, index: u32, cd_offset: u64) -> EngramError {
+    EngramError::DirectoryEntryError {
+        index,
+        offset: cd_offset + index as u64 * CD_ENTRY_SIZE as u64,
+        source: Box::new(err),
+    }
+}
+
+/// Seekable byte source backing an `ArchiveReader`
+trait ReadSeek: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ReadSeek for T {}
+
+/// Byte source owned by one reader
+///
+/// Only ever accessed through `&mut ArchiveReader`, so the mutex is never
+/// contended; it makes the reader `Sync` for shared `read_file_at` calls.
+struct Source(Mutex<Box<dyn ReadSeek>>);
+
+impl Source {
+    fn new(inner: Box<dyn ReadSeek>) -> Self {
+        Self(Mutex::new(inner))
+    }
+
+    fn get(&mut self) -> &mut Box<dyn ReadSeek> {
+        self.0.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// `Read` adapter over positioned reads, leaving no shared cursor to race on
+struct PositionedReader<'a> {
+    file: &'a File,
+    offset: u64,
+}
+
+impl Read for PositionedReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = read_at(self.file, buf, self.offset)?;
+        self.offset += n as u64;
+        Ok(n)
+    }
+}
+
+#[cfg(unix)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+    std::os::unix::fs::FileExt::read_at(file, buf, offset)
+}
+
+#[cfg(windows)]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> std::io::Result<usize> {
+    std::os::windows::fs::FileExt::seek_read(file, buf, offset)
+}
+
+#[cfg(not(any(unix, windows)))]
+fn read_at(_file: &File, _buf: &mut [u8], _offset: u64) -> std::io::Result<usize> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "positioned reads are not supported on this platform",
+    ))
+}
+
+/// Fill `buf` from `reader`, checking for cancellation between chunks
+fn read_chunked<R: Read>(
+    mut reader: R,
+    buf: &mut [u8],
+    cancellation: &CancellationToken,
+) -> Result<()> {
+    for chunk in buf.chunks_mut(CANCEL_CHECK_INTERVAL) {
+        cancellation.check()?;
+        reader.read_exact(chunk)?;
+    }
+    Ok(())
+}
+
+/// Central directory entries per lazily allocated cache chunk
+const LAZY_CHUNK_SIZE: usize = 1024;
+
+/// Largest expansion of an LZ4 block (a run of 255-byte match lengths)
+const MAX_LZ4_RATIO: u64 = 255;
+
+/// Check that an entry's stored data lies between the header and `payload_end`
+///
+/// Sizes come straight from the central directory, so this bounds every
+/// allocation made for the stored payload by the length of the archive.
+fn check_entry_bounds(entry: &EntryInfo, payload_end: u64) -> Result<()> {
+    let end = entry.data_offset.checked_add(entry.compressed_size);
+    if entry.data_offset < HEADER_SIZE as u64 || !matches!(end, Some(end) if end <= payload_end) {
+        return Err(EngramError::InvalidFormat(format!(
+            "Data of '{}' at offset {} ({} bytes) lies outside the archive payload",
+            entry.path, entry.data_offset, entry.compressed_size
+        )));
+    }
+    Ok(())
+}
+
+/// Entries worth preallocating for `count` central directory entries read
+/// from `len` bytes
+///
+/// `count` comes from the header, so it is trusted only as far as the bytes to
+/// hold that many entries exist; a forged count then fails on the first
+/// missing entry instead of aborting on the allocation.
+fn directory_capacity(count: u32, len: u64) -> usize {
+    (count as u64).min(len / CD_ENTRY_SIZE as u64) as usize
+}
+
+/// Fully parsed central directory
+#[derive(Default)]
+struct Directory {
+    /// Entries in central directory order
+    entries: Vec<EntryInfo>,
+    /// Path -> position in `entries`; `None` when sorted by path (binary search)
+    index: Option<HashMap<String, usize>>,
+    /// Paths in central directory order
+    entry_list: Arc<Vec<String>>,
+    /// CRC32 -> positions in `entries` of files with it, built on first use
+    crc_index: OnceLock<HashMap<u32, Vec<usize>>>,
+}
+
+impl Directory {
+    /// Parse `count` consecutive central directory entries from at most `len`
+    /// bytes of `reader`
+    ///
+    /// Every entry's payload must lie between the header and `payload_end`
+    /// (the central directory offset, where the entries start). Errors name
+    /// the failing entry. A directory flagged as sorted is searched in place.
+    /// The order is checked first, and a hash index is built anyway if it does
+    /// not hold. See `read_directory_entry` for `lenient_paths`.
+    fn parse<R: Read>(
+        mut reader: R,
+        count: u32,
+        len: u64,
+        sorted: bool,
+        payload_end: u64,
+        lenient_paths: bool,
+    ) -> Result<Self> {
+        let mut entries = Vec::with_capacity(directory_capacity(count, len));
+        for index in 0..count {
+            let entry = read_directory_entry(&mut reader, payload_end, lenient_paths)
+                .map_err(|e| directory_entry_error(e, index, payload_end))?;
+            entries.push(entry);
+        }
+        Ok(Self::from_entries(entries, sorted))
+    }
+
+    /// Index `entries`, searching in place if `sorted` holds
+    fn from_entries(entries: Vec<EntryInfo>, sorted: bool) -> Self {
+        let entry_list = entries.iter().map(|entry| entry.path.clone()).collect();
+
+        let sorted = sorted && entries.windows(2).all(|pair| pair[0].path < pair[1].path);
+        let index = (!sorted).then(|| {
+            entries
+                .iter()
+                .enumerate()
+                .map(|(position, entry)| (entry.path.clone(), position))
+                .collect()
+        });
+
+        Self {
+            entries,
+            index,
+            entry_list: Arc::new(entry_list),
+            crc_index: OnceLock::new(),
+        }
+    }
+
+    /// Files by CRC32 of their content, in central directory order
+    fn crc_index(&self) -> &HashMap<u32, Vec<usize>> {
+        self.crc_index.get_or_init(|| {
+            let mut index: HashMap<u32, Vec<usize>> = HashMap::new();
+            for (position, entry) in self.entries.iter().enumerate() {
+                if !entry.is_directory() {
+                    index.entry(entry.crc32).or_default().push(position);
+                }
+            }
+            index
+        })
+    }
+
+    /// Entry stored under exactly `path`
+    fn get(&self, path: &str) -> Option<&EntryInfo> {
+        let position = match &self.index {
+            Some(index) => *index.get(path)?,
+            None => self
+                .entries
+                .binary_search_by(|entry| entry.path.as_str().cmp(path))
+                .ok()?,
+        };
+        Some(&self.entries[position])
+    }
+
+    /// Number of distinct paths
+    fn len(&self) -> usize {
+        self.index
+            .as_ref()
+            .map_or(self.entries.len(), |index| index.len())
+    }
+}
+
+/// Cache slots for `LAZY_CHUNK_SIZE` consecutive central directory entries
+type EntryChunk = Box<[OnceLock<EntryInfo>]>;
+
+/// Central directory entries read one at a time, by index
+///
+/// Used by lazy readers for binary search over a sorted directory and for
+/// hashed lookups in an unsorted one. Each entry is parsed at most once;
+/// cache slots are allocated in chunks as they are first touched.
+struct LazyEntries {
+    chunks: Box<[OnceLock<EntryChunk>]>,
+}
+
+impl LazyEntries {
+    fn new(count: usize) -> Self {
+        Self {
+            chunks: (0..count.div_ceil(LAZY_CHUNK_SIZE))
+                .map(|_| OnceLock::new())
+                .collect(),
+        }
+    }
+
+    fn slot(&self, index: usize) -> &OnceLock<EntryInfo> {
+        let chunk = self.chunks[index / LAZY_CHUNK_SIZE]
+            .get_or_init(|| (0..LAZY_CHUNK_SIZE).map(|_| OnceLock::new()).collect());
+        &chunk[index % LAZY_CHUNK_SIZE]
+    }
+}
+
+/// 64-bit FNV-1a hash of a stored path
+fn path_hash(path: &[u8]) -> u64 {
+    path.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Path hash -> central directory index for every entry, sorted by hash
+///
+/// Built by lazy readers of unsorted directories from the stored path bytes
+/// alone, at 16 bytes per entry instead of a parsed `EntryInfo`. A matching
+/// hash only names a candidate: lookups parse that entry and compare its path.
+struct PathHashes(Box<[(u64, u32)]>);
+
+impl PathHashes {
+    /// Hash the paths of `count` consecutive central directory entries, read
+    /// from at most `len` bytes of `reader`
+    fn build<R: Read>(mut reader: R, count: u32, len: u64, cd_offset: u64) -> Result<Self> {
+        let mut hashes = Vec::with_capacity(directory_capacity(count, len));
+        let mut buf = [0u8; CD_ENTRY_SIZE];
+        for index in 0..count {
+            reader
+                .read_exact(&mut buf)
+                .map_err(|e| directory_entry_error(e.into(), index, cd_offset))?;
+            let path_len = (u16::from_le_bytes([buf[42], buf[43]]) as usize).min(256);
+            hashes.push((path_hash(&buf[44..44 + path_len]), index));
+        }
+        hashes.sort_unstable();
+        Ok(Self(hashes.into()))
+    }
 
-/// Normalize path to forward slashes (cross-platform compatibility)
-fn normalize_path(path: &str) -> String {
-    path.replace('\\', "/")
+    /// Indices of the entries whose path hashes like `path`, last one first
+    fn candidates(&self, path: &str) -> impl Iterator<Item = usize> + '_ {
+        let hash = path_hash(path.as_bytes());
+        let start = self.0.partition_point(|&(h, _)| h < hash);
+        let end = self.0.partition_point(|&(h, _)| h <= hash);
+        self.0[start..end]
+            .iter()
+            .rev()
+            .map(|&(_, index)| index as usize)
+    }
+}
+
+/// Iterator over the contents of every file, see `ArchiveReader::iter_contents`
+pub struct ContentsIter<'a> {
+    reader: &'a mut ArchiveReader,
+    /// Next central directory index
+    index: usize,
+    count: usize,
+    skip_errors: bool,
+    /// Set after `Cancelled` or `CallbackPanicked`
+    stopped: bool,
+}
+
+impl ContentsIter<'_> {
+    /// Leave out entries that fail to read instead of yielding their errors
+    ///
+    /// `Cancelled` and `CallbackPanicked` still end the iteration with an
+    /// error.
+    pub fn skip_errors(mut self, skip: bool) -> Self {
+        self.skip_errors = skip;
+        self
+    }
+
+    /// Read the entry at `index`, or `None` for a directory
+    fn read_at(&mut self, index: usize) -> Option<Result<(String, Vec<u8>)>> {
+        let entry = match self.reader.entry_at(index) {
+            Ok(entry) if entry.is_directory() => return None,
+            Ok(entry) => entry,
+            Err(err) => return Some(Err(err)),
+        };
+        Some(
+            self.reader
+                .read_file_entry(&entry)
+                .map(|data| (entry.path, data)),
+        )
+    }
+}
+
+impl Iterator for ContentsIter<'_> {
+    type Item = Result<(String, Vec<u8>)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.stopped && self.index < self.count {
+            let index = self.index;
+            self.index += 1;
+            match self.read_at(index) {
+                None => {}
+                Some(Err(err @ (EngramError::Cancelled | EngramError::CallbackPanicked))) => {
+                    self.stopped = true;
+                    return Some(Err(err));
+                }
+                Some(Err(_)) if self.skip_errors => {}
+                Some(result) => return Some(result),
+            }
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.stopped {
+            return (0, Some(0));
+        }
+        (0, Some(self.count - self.index))
+    }
+}
+
+/// Central directory state, shared between handles created with `try_clone`
+struct CentralDirectory {
+    /// Fully parsed directory; left empty by lazy readers until first needed
+    parsed: OnceLock<Directory>,
+    /// On-demand entries (lazy readers only)
+    entries: Option<LazyEntries>,
+    /// Whether `entries` are sorted by path and can be binary searched
+    sorted: bool,
+    /// Path hashes of an unsorted directory, built on the first lookup
+    hashes: OnceLock<PathHashes>,
+    /// Lowercased path -> stored path, built on the first case-insensitive miss
+    lowercase_index: OnceLock<HashMap<String, String>>,
+    /// Data offset -> BLAKE3 digest from `BLAKE3_TABLE_PATH`, attached to
+    /// entries as they are parsed
+    blake3: HashMap<u64, [u8; 32]>,
+}
+
+impl CentralDirectory {
+    fn parsed(directory: Directory) -> Self {
+        Self {
+            parsed: OnceLock::from(directory),
+            entries: None,
+            sorted: false,
+            hashes: OnceLock::new(),
+            lowercase_index: OnceLock::new(),
+            blake3: HashMap::new(),
+        }
+    }
+
+    fn deferred(count: usize, sorted: bool) -> Self {
+        Self {
+            parsed: OnceLock::new(),
+            entries: Some(LazyEntries::new(count)),
+            sorted,
+            hashes: OnceLock::new(),
+            lowercase_index: OnceLock::new(),
+            blake3: HashMap::new(),
+        }
+    }
 }
 
-/// Archive reader with O(1) file lookup
+/// Archive reader with O(1) file lookup (O(log n) for sorted directories)
+///
+/// The parsed central directory (and the decrypted payload of archive-encrypted
+/// files) is shared between handles created with `try_clone`.
 pub struct ArchiveReader {
-    file: File,
+    file: Source,
+    /// Separate handle for `read_file_at`, opened on first use
+    positioned_file: OnceLock<File>,
+    /// Path the archive was opened from (`None` for `from_reader`)
+    source_path: Option<PathBuf>,
     header: FileHeader,
-    entries: HashMap<String, EntryInfo>,
-    entry_list: Vec<String>,
+    directory: Arc<CentralDirectory>,
+    /// Defer parsing the central directory (see `with_lazy_central_directory`)
+    lazy: bool,
     encryption_mode: EncryptionMode,
-    decryption_key: Option<[u8; 32]>,
-    decrypted_payload: Option<Vec<u8>>,
+    /// Key material is wiped when the reader is dropped (see `crate::crypto`)
+    decryption_key: Option<SecretKey>,
+    /// Recipient id and key that unwrap `decryption_key` on `initialize`
+    recipient_key: Option<(Option<String>, SecretKey)>,
+    /// Password of a password recipient, see `with_password`
+    password: Option<Zeroizing<String>>,
+    decrypted_payload: Option<Arc<Vec<u8>>>,
+    progress: Progress,
+    cancellation: CancellationToken,
+    /// Shared Zstd dictionary, loaded on first use
+    zstd_dictionary: OnceLock<Arc<Vec<u8>>>,
+    /// Custom MIME types from `MIME_TABLE_PATH`, loaded on first use
+    mime_table: OnceLock<Arc<Vec<String>>>,
+    /// Custom entry metadata from `ENTRY_METADATA_PATH`, loaded on first use
+    entry_metadata_table: OnceLock<Arc<HashMap<String, BTreeMap<String, String>>>>,
+    /// Stored-payload checksums from `COMPRESSED_CRC_PATH`, loaded on first use
+    compressed_crc_table: OnceLock<Arc<HashMap<u64, u32>>>,
+    case_insensitive: bool,
+    /// Accept non-conforming central directory paths, see `with_lenient_paths`
+    lenient_paths: bool,
+    /// Restore setuid, setgid and sticky bits, see `with_special_mode_bits`
+    special_mode_bits: bool,
+    /// Decompressed entries for `read_file_cached` (see `with_cache`)
+    cache: Option<EntryCache>,
 }
 
 /// This is essentially our "API"; the public facing portion of our code.
 impl ArchiveReader {
     /// Open an archive file for reading
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let mut file = File::open(path)?;
+        let path = path.as_ref();
+        let mut reader = Self::from_reader(File::open(path)?)?;
+        reader.source_path = Some(path.to_path_buf());
+        Ok(reader)
+    }
+
+    /// Open an archive from any seekable byte source
+    ///
+    /// Use this for archives that are not on disk, e.g. a `Cursor<Vec<u8>>`
+    /// holding bytes fetched over the network (the only data source in the browser).
+    /// Like `open()`, this reads the header only; call `initialize()` before reading files.
+    pub fn from_reader<R: Read + Seek + Send + 'static>(reader: R) -> Result<Self> {
+        let mut file: Box<dyn ReadSeek> = Box::new(reader);
+        file.seek(SeekFrom::Start(0))?;
 
         // Read header
         let header = FileHeader::read_from(&mut file)?;
@@ -42,16 +707,43 @@ impl ArchiveReader {
         let encryption_mode = header.encryption_mode();
 
         Ok(Self {
-            file,
+            file: Source::new(file),
+            positioned_file: OnceLock::new(),
+            source_path: None,
             header,
-            entries: HashMap::new(),
-            entry_list: Vec::new(),
+            directory: Arc::new(CentralDirectory::parsed(Directory::default())),
+            lazy: false,
             encryption_mode,
             decryption_key: None,
+            recipient_key: None,
+            password: None,
             decrypted_payload: None,
+            progress: Progress::default(),
+            cancellation: CancellationToken::default(),
+            zstd_dictionary: OnceLock::new(),
+            mime_table: OnceLock::new(),
+            entry_metadata_table: OnceLock::new(),
+            compressed_crc_table: OnceLock::new(),
+            case_insensitive: false,
+            lenient_paths: false,
+            special_mode_bits: false,
+            cache: None,
         })
     }
 
+    /// Open a split archive written by `ArchiveWriter::create_split`
+    ///
+    /// `first_volume` is the `*.001` file; the remaining volumes are found
+    /// next to it and read as one continuous archive. A missing volume fails
+    /// with `EngramError::MissingVolume` naming the file, and volumes that are
+    /// out of order or from another archive fail with `InvalidFormat`.
+    /// Like `open()`, this reads the header only; call `initialize()` before
+    /// reading files. `try_clone` and `read_file_at` are not available for
+    /// split archives.
+    pub fn open_split<P: AsRef<Path>>(first_volume: P) -> Result<Self> {
+        Self::from_reader(VolumeReader::open(first_volume.as_ref())?)
+    }
+
     /// Open and initialize archive in one step (recommended for most use cases)
     ///
     /// This is a convenience method that combines `open()` and `initialize()`.
@@ -64,235 +756,2321 @@ impl ArchiveReader {
         Ok(reader)
     }
 
+    /// Open and initialize an archive without parsing its central directory
+    ///
+    /// Same as `open()` followed by `with_lazy_central_directory(true)` and
+    /// `initialize()`. For archive-level encryption, chain those calls with
+    /// `with_decryption_key()` instead.
+    pub fn open_lazy<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut reader = Self::open(path)?.with_lazy_central_directory(true);
+        reader.initialize()?;
+        Ok(reader)
+    }
+
+    /// Read an archive's summary from its header and End Record only
+    ///
+    /// Reads 128 bytes whatever the entry count: no central directory entry
+    /// is parsed and no key is needed, which suits tools listing many
+    /// archives. The End Record must agree with the header, as in
+    /// `initialize`; v0.x archives have none and are summarized from the
+    /// header alone.
+    pub fn peek<P: AsRef<Path>>(path: P) -> Result<ArchiveSummary> {
+        let mut file = File::open(path)?;
+        let header = FileHeader::read_from(&mut file)?;
+        header.validate_version()?;
+
+        let mut archive_crc32 = 0;
+        if !header.is_legacy() {
+            let end_record = EndRecord::read_from_end(&mut file)?;
+            end_record.validate_against_header(
+                header.version_major,
+                header.version_minor,
+                header.central_directory_offset,
+                header.central_directory_size,
+                header.entry_count,
+            )?;
+            archive_crc32 = end_record.archive_crc32;
+        }
+
+        Ok(ArchiveSummary {
+            version_major: header.version_major,
+            version_minor: header.version_minor,
+            entry_count: header.entry_count,
+            cd_offset: header.central_directory_offset,
+            cd_size: header.central_directory_size,
+            archive_crc32,
+            encryption_mode: header.encryption_mode(),
+        })
+    }
+
+    /// Open a damaged archive by rebuilding its directory from LOCA headers
+    ///
+    /// Ignores the central directory and End Record entirely: the payload is
+    /// walked from the end of the header, one self-describing LOCA header and
+    /// stored payload at a time, until the first region that is not a
+    /// readable LOCA header or whose payload runs past the end of the file.
+    /// Everything before that point can be listed and read as usual;
+    /// `entry_count` reports how many entries were recovered.
+    ///
+    /// What the LOCA headers do not record is lost: deduplicated copies
+    /// (which have no LOCA header of their own) and stored SHA-256 prefixes,
+    /// so only CRC32 is checked. Per-file encrypted archives can be recovered
+    /// by chaining `with_decryption_key`. Archive-encrypted and v0.x archives
+    /// have no readable LOCA headers and fail with `InvalidFormat`.
+    pub fn recover<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let mut reader = Self::open(path)?;
+        if reader.encryption_mode == EncryptionMode::Archive || reader.header.is_legacy() {
+            return Err(EngramError::InvalidFormat(
+                "Only archives with plaintext LOCA headers can be recovered".to_string(),
+            ));
+        }
+
+        let archive_len = reader.source_len()?;
+        let mut offset = HEADER_SIZE as u64;
+        let mut entries = Vec::new();
+        while offset < archive_len {
+            reader.cancellation.check()?;
+            let file = reader.file.get();
+            file.seek(SeekFrom::Start(offset))?;
+            let Ok(local) = LocalEntryHeader::read_from(file) else {
+                break;
+            };
+            let end = (offset + local.header_size() as u64).checked_add(local.compressed_size);
+            let Some(end) = end.filter(|&end| end <= archive_len) else {
+                break;
+            };
+
+            entries.push(EntryInfo {
+                path: local.path,
+                data_offset: offset,
+                uncompressed_size: local.uncompressed_size,
+                compressed_size: local.compressed_size,
+                crc32: local.crc32,
+                modified_time: local.modified_time,
+                compression: local.compression,
+                flags: local.flags & !ENTRY_FLAG_SHA256,
+                mode: local.mode & 0xFFFF,
+                mime_id: (local.mode >> 16) as u16,
+                sha256: None,
+                blake3: None,
+            });
+            offset = end;
+        }
+
+        reader.directory = Arc::new(CentralDirectory::parsed(Directory::from_entries(
+            entries, false,
+        )));
+        Ok(reader)
+    }
+
     /// Open and initialize an encrypted archive with decryption key
     ///
-    /// Convenience method for archive-level encrypted files.
+    /// Convenience method for `open`, `with_decryption_key` and `initialize`,
+    /// for archive-level and per-file encrypted files alike.
     pub fn open_encrypted<P: AsRef<Path>>(path: P, key: &[u8; 32]) -> Result<Self> {
         let mut reader = Self::open(path)?.with_decryption_key(key);
         reader.initialize()?;
         Ok(reader)
     }
 
+    /// Open and initialize an archive with a password
+    ///
+    /// Convenience method for `open`, `with_password` and `initialize`.
+    pub fn open_with_password<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        let mut reader = Self::open(path)?.with_password(password);
+        reader.initialize()?;
+        Ok(reader)
+    }
+
     /// Provide decryption key for encrypted archives
+    ///
+    /// Errors tell a missing key from a wrong one: `MissingDecryptionKey` when
+    /// an encrypted archive is read without a key, `DecryptionFailed` when the
+    /// key does not authenticate the data (wrong key or tampering), and
+    /// `NotEncrypted` from `initialize` when the archive is not encrypted.
+    /// Archive-encrypted files report these from `initialize`; per-file
+    /// encrypted files from the first read of an entry.
+    ///
+    /// The reader keeps its own copy of the key, wiped on drop; `key` itself
+    /// is left to the caller.
     pub fn with_decryption_key(mut self, key: &[u8; 32]) -> Self {
-        self.decryption_key = Some(*key);
+        self.decryption_key = Some(SecretKey::new(*key));
+        self
+    }
+
+    /// Open an archive as one of its recipients
+    ///
+    /// `key` is the recipient's symmetric key (`ArchiveWriter::add_recipient_key`)
+    /// or X25519 secret key (`ArchiveWriter::add_recipient_pubkey`). On
+    /// `initialize` it unwraps the archive's encryption key, which is then
+    /// used like one passed to `with_decryption_key`. Fails with
+    /// `RecipientNotFound` if `id` is not a recipient and `DecryptionFailed`
+    /// if the key does not match.
+    pub fn with_recipient_key(mut self, id: &str, key: &[u8; 32]) -> Self {
+        self.recipient_key = Some((Some(id.to_string()), SecretKey::new(*key)));
+        self
+    }
+
+    /// Open an archive with an X25519 secret key, whichever recipient it belongs to
+    ///
+    /// Like `with_recipient_key`, but every recipient's wrapped key is tried
+    /// until one opens, so the recipient id is not needed (see
+    /// `ArchiveWriter::add_recipient`). Fails with `DecryptionFailed` on
+    /// `initialize` if the key opens none of them.
+    pub fn with_private_key(mut self, secret_key: &[u8; 32]) -> Self {
+        self.recipient_key = Some((None, SecretKey::new(*secret_key)));
+        self
+    }
+
+    /// Open an archive with the password of a password recipient
+    ///
+    /// See `ArchiveWriter::add_recipient_password`. On `initialize` every
+    /// password recipient is tried, each costing one key derivation; fails
+    /// with `DecryptionFailed` if the password opens none of them.
+    pub fn with_password(mut self, password: &str) -> Self {
+        self.password = Some(Zeroizing::new(password.to_string()));
+        self
+    }
+
+    /// Report progress while reading files
+    ///
+    /// Every `read_file` call, and therefore every file written by
+    /// `extract_all`, emits `FileStarted`, `BytesProcessed` and `FileFinished`.
+    /// Frame-compressed entries report progress per frame. A panicking callback
+    /// is dropped and the current call returns `EngramError::CallbackPanicked`;
+    /// the archive itself is never modified.
+    pub fn with_progress<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(ProgressEvent) + Send + 'static,
+    {
+        self.progress = Progress::new(Box::new(callback));
+        self
+    }
+
+    /// Abort reads when `token` is cancelled
+    ///
+    /// `read_file`, `extract_all` and the archive-level decryption done by
+    /// `initialize` check the token between 1MB chunks and 64KB frames, and
+    /// return `EngramError::Cancelled` once it is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Match paths case-insensitively when no exact match exists
+    ///
+    /// For archives produced on case-insensitive filesystems. Exact matches are
+    /// still tried first; otherwise lookups go through a secondary index of
+    /// lowercased paths, built on the first lookup that needs it. If several
+    /// stored paths differ only in case, the first one in the central
+    /// directory wins.
+    pub fn with_case_insensitive_lookup(mut self, enabled: bool) -> Self {
+        self.set_case_insensitive_lookup(enabled);
+        self
+    }
+
+    /// Accept central directory paths with control characters or data after
+    /// the path
+    ///
+    /// By default such entries fail with `PathError` (see
+    /// `EntryInfo::read_from`), since a path with an escape sequence can spoof
+    /// a terminal listing it. Enable this in tools that must read
+    /// non-conforming archives, and escape paths before printing them. Set it
+    /// before `initialize`.
+    pub fn with_lenient_paths(mut self, enabled: bool) -> Self {
+        self.lenient_paths = enabled;
+        self
+    }
+
+    /// Restore the setuid, setgid and sticky bits of stored modes in
+    /// `extract_all`
+    ///
+    /// Off by default: an archive from an untrusted source could otherwise
+    /// plant a setuid executable. Only the permission bits (`0o777`) are
+    /// applied unless this is enabled.
+    pub fn with_special_mode_bits(mut self, enabled: bool) -> Self {
+        self.special_mode_bits = enabled;
+        self
+    }
+
+    /// In-place form of `with_case_insensitive_lookup`
+    pub(crate) fn set_case_insensitive_lookup(&mut self, enabled: bool) {
+        self.case_insensitive = enabled;
+    }
+
+    /// Defer parsing the central directory until it is needed
+    ///
+    /// Takes effect at `initialize()`, which then only records where the
+    /// central directory is. For archives written with
+    /// `ArchiveWriter::with_sorted_directory`, lookups binary-search
+    /// the on-disk directory, parsing and caching just the entries they touch.
+    /// Otherwise the first lookup hashes every stored path into a compact
+    /// index (16 bytes per entry), and lookups parse only the entries whose
+    /// hash matches. Listing (`list_files`, `list_prefix`), `extract_all`,
+    /// `verify_all` and case-insensitive fallbacks parse the directory in
+    /// full; `iter_files` streams paths without keeping them. This keeps
+    /// opening, the first read and memory use small on archives with very
+    /// many entries.
+    ///
+    /// Readers over other sources (`from_reader`, `open_split`) parse the
+    /// directory up front as usual, unless the archive is archive-encrypted.
+    pub fn with_lazy_central_directory(mut self, enabled: bool) -> Self {
+        self.lazy = enabled;
         self
     }
 
     /// Initialize the reader (must be called after open, decrypts if needed)
     pub fn initialize(&mut self) -> Result<()> {
+        self.check_truncation()?;
+
+        if let Some((id, key)) = self.recipient_key.take() {
+            self.decryption_key = Some(match id {
+                Some(id) => self.unwrap_recipient_key(&id, &key)?,
+                None => self.unwrap_any_recipient_key(&key)?,
+            });
+        }
+        if let Some(password) = self.password.take() {
+            self.decryption_key = Some(self.unwrap_password_key(&password)?);
+        }
+
         match self.encryption_mode {
             EncryptionMode::None => {
-                // Validate ENDR for unencrypted archives
-                self.validate_end_record()?;
+                // A key for a plain archive is a mix-up, not something to ignore
+                if self.decryption_key.is_some() {
+                    return Err(EngramError::NotEncrypted);
+                }
+                let archive_len = self.source_len()?;
+                self.check_central_directory_bounds(archive_len)?;
+                // Validate ENDR for unencrypted archives (v0.x has none)
+                if !self.header.is_legacy() {
+                    self.validate_end_record()?;
+                }
                 // Read central directory normally from file
-                self.read_central_directory_from_file()?;
+                self.read_central_directory()?;
             }
             EncryptionMode::Archive => {
                 // For encrypted archives, skip ENDR validation for now
                 // TODO: Validate ENDR after decryption
                 // Decrypt entire payload, then read central directory from memory
                 self.decrypt_archive_payload()?;
-                self.read_central_directory_from_memory()?;
+                // Offsets count from the start of the file, header included
+                let payload_len = self.decrypted_payload.as_ref().map_or(0, |p| p.len());
+                self.check_central_directory_bounds((HEADER_SIZE + payload_len) as u64)?;
+                self.read_central_directory()?;
             }
             EncryptionMode::PerFile => {
-                // Validate ENDR for per-file encryption
-                self.validate_end_record()?;
+                let archive_len = self.source_len()?;
+                self.check_central_directory_bounds(archive_len)?;
+                // Validate ENDR for per-file encryption (v0.x has none)
+                if !self.header.is_legacy() {
+                    self.validate_end_record()?;
+                }
                 // Central directory not encrypted, read normally
-                self.read_central_directory_from_file()?;
+                self.read_central_directory()?;
             }
         }
+        if self.header.has_blake3_digests() {
+            self.load_blake3_digests()?;
+        }
         Ok(())
     }
 
-    /// Read central directory from file
-    fn read_central_directory_from_file(&mut self) -> Result<()> {
-        // Seek to central directory
-        self.file
-            .seek(SeekFrom::Start(self.header.central_directory_offset))?;
-
-        // Read all entries
-        let mut entries = HashMap::with_capacity(self.header.entry_count as usize);
-        let mut entry_list = Vec::with_capacity(self.header.entry_count as usize);
+    /// Attach the digests stored at `BLAKE3_TABLE_PATH` to the central
+    /// directory entries
+    ///
+    /// Under per-file encryption the table is encrypted like any entry, so
+    /// without a key the entries are left without digests.
+    fn load_blake3_digests(&mut self) -> Result<()> {
+        if self.encryption_mode == EncryptionMode::PerFile && self.decryption_key.is_none() {
+            return Ok(());
+        }
+        if self.find_exact(BLAKE3_TABLE_PATH)?.is_none() {
+            return Err(EngramError::InvalidFormat(format!(
+                "Header flags BLAKE3 digests, but {} is missing",
+                BLAKE3_TABLE_PATH
+            )));
+        }
 
-        for _ in 0..self.header.entry_count {
-            let entry = EntryInfo::read_from(&mut self.file)?;
-            entry_list.push(entry.path.clone());
-            entries.insert(entry.path.clone(), entry);
+        // Internal read: don't report it to the progress callback
+        let progress = std::mem::take(&mut self.progress);
+        let result = self.read_file(BLAKE3_TABLE_PATH);
+        self.progress = progress;
+        let digests = blake3_table::parse_table(&result?)?;
+
+        let count = self.header.entry_count as usize;
+        let directory = Arc::get_mut(&mut self.directory)
+            .expect("central directory is not shared while initializing");
+        if let Some(parsed) = directory.parsed.get_mut() {
+            attach_blake3(&digests, &mut parsed.entries);
+        }
+        // Entries parsed while looking up the table have no digests yet
+        if directory.entries.is_some() {
+            directory.entries = Some(LazyEntries::new(count));
         }
+        directory.blake3 = digests;
+        Ok(())
+    }
+
+    /// Fail with `TruncatedArchive` when the source is shorter than the
+    /// header's central directory (and the trailer after it) requires
+    ///
+    /// If the End Record at the tail is intact and accounts for the whole
+    /// length, the file is complete and it is the header that is damaged;
+    /// that is left to `check_central_directory_bounds` and
+    /// `validate_end_record` to report.
+    fn check_truncation(&mut self) -> Result<()> {
+        let actual_len = self.source_len()?;
+        let expected_len = self.minimum_len(
+            self.header.central_directory_offset,
+            self.header.central_directory_size,
+        );
+        if actual_len >= expected_len {
+            return Ok(());
+        }
+
+        if !self.header.is_legacy() {
+            if let Ok(end_record) = EndRecord::read_from_end(self.file.get()) {
+                let tail_len = self
+                    .minimum_len(
+                        end_record.central_directory_offset,
+                        end_record.central_directory_size,
+                    )
+                    .saturating_add(end_record.recipients_size as u64)
+                    .saturating_add(end_record.signature_size as u64);
+                if tail_len == actual_len {
+                    return Ok(());
+                }
+            }
+        }
+
+        Err(EngramError::TruncatedArchive {
+            expected_len,
+            actual_len,
+        })
+    }
+
+    /// Smallest archive length that holds a central directory ending at
+    /// `cd_offset + cd_size`, not counting a recipients block
+    ///
+    /// Archive-mode offsets count in the decrypted payload, which is stored
+    /// with a 12-byte nonce before it and a 16-byte tag after it.
+    fn minimum_len(&self, cd_offset: u64, cd_size: u64) -> u64 {
+        let mut len = cd_offset.saturating_add(cd_size);
+        if self.encryption_mode == EncryptionMode::Archive {
+            len = len.saturating_add(12 + 16);
+        }
+        if !self.header.is_legacy() {
+            len = len.saturating_add(END_RECORD_SIZE as u64);
+        }
+        len
+    }
+
+    /// Check the header's central directory location before seeking to it
+    ///
+    /// The directory must hold exactly `entry_count` entries and lie between
+    /// the header and `archive_len`, so a corrupt header fails here with the
+    /// offending values rather than with an I/O error further on.
+    fn check_central_directory_bounds(&self, archive_len: u64) -> Result<()> {
+        let header = &self.header;
+        let expected_size = header.entry_count as u64 * CD_ENTRY_SIZE as u64;
+        if header.central_directory_size != expected_size {
+            return Err(EngramError::InvalidFormat(format!(
+                "Central directory size {} does not match {} entries of {} bytes",
+                header.central_directory_size, header.entry_count, CD_ENTRY_SIZE
+            )));
+        }
+
+        let end = header
+            .central_directory_offset
+            .checked_add(header.central_directory_size);
+        if header.central_directory_offset < HEADER_SIZE as u64
+            || !matches!(end, Some(end) if end <= archive_len)
+        {
+            return Err(EngramError::InvalidFormat(format!(
+                "Central directory at offset {} ({} bytes) lies outside the archive ({} bytes)",
+                header.central_directory_offset, header.central_directory_size, archive_len
+            )));
+        }
+        Ok(())
+    }
+
+    /// Read the central directory, or record where it is for lazy readers
+    fn read_central_directory(&mut self) -> Result<()> {
+        // Lazy reads need positioned reads on the file or the decrypted payload
+        let deferrable = self.source_path.is_some() || self.decrypted_payload.is_some();
+        if self.lazy && deferrable {
+            self.directory = Arc::new(CentralDirectory::deferred(
+                self.header.entry_count as usize,
+                self.header.has_sorted_directory(),
+            ));
+            return Ok(());
+        }
+
+        let directory = match self.encryption_mode {
+            EncryptionMode::Archive => self.read_central_directory_from_memory()?,
+            _ => self.read_central_directory_from_file()?,
+        };
+        self.directory = Arc::new(CentralDirectory::parsed(directory));
+        Ok(())
+    }
+
+    /// Read central directory from file
+    fn read_central_directory_from_file(&mut self) -> Result<Directory> {
+        // Seek to central directory
+        let offset = self.header.central_directory_offset;
+        let len = self.source_len()?.saturating_sub(offset);
+        self.file.get().seek(SeekFrom::Start(offset))?;
+
+        Directory::parse(
+            self.file.get(),
+            self.header.entry_count,
+            len,
+            self.header.has_sorted_directory(),
+            self.header.central_directory_offset,
+            self.lenient_paths,
+        )
+    }
+
+    /// Read central directory from decrypted payload buffer
+    fn read_central_directory_from_memory(&self) -> Result<Directory> {
+        let payload = self
+            .decrypted_payload
+            .as_ref()
+            .ok_or(EngramError::DecryptionFailed)?;
+
+        // Create cursor at central directory offset (payload-relative, so subtract header size)
+        // The decrypted payload starts at what would be byte 64 in the file
+        let cd_len = self.header.entry_count as u64 * CD_ENTRY_SIZE as u64;
+        let directory = self
+            .header
+            .central_directory_offset
+            .checked_sub(HEADER_SIZE as u64)
+            .and_then(|start| Some(start..start.checked_add(cd_len)?))
+            .and_then(|range| {
+                let range = usize::try_from(range.start).ok()?..usize::try_from(range.end).ok()?;
+                payload.get(range)
+            })
+            .ok_or_else(|| {
+                EngramError::InvalidFormat(format!(
+                    "Central directory at offset {} ({} entries) lies outside the decrypted payload ({} bytes)",
+                    self.header.central_directory_offset,
+                    self.header.entry_count,
+                    payload.len()
+                ))
+            })?;
+        let len = directory.len() as u64;
+        let cursor = Cursor::new(directory);
+
+        Directory::parse(
+            cursor,
+            self.header.entry_count,
+            len,
+            self.header.has_sorted_directory(),
+            self.header.central_directory_offset,
+            self.lenient_paths,
+        )
+    }
+
+    /// Get archive header information
+    pub fn header(&self) -> &FileHeader {
+        &self.header
+    }
+
+    /// Application schema version from the header (0 if never set)
+    ///
+    /// Available right after `open`, without `initialize` or a decryption key.
+    pub fn content_version(&self) -> u32 {
+        self.header.content_version
+    }
+
+    /// Application-defined label from the header (empty if never set)
+    ///
+    /// Available right after `open`, without `initialize` or a decryption key.
+    pub fn label(&self) -> ArchiveLabel {
+        self.header.label
+    }
+
+    /// Encryption mode from the header flags
+    ///
+    /// Available right after `open`, without `initialize` or a decryption key.
+    pub fn encryption_mode(&self) -> EncryptionMode {
+        self.encryption_mode
+    }
+
+    /// Whether the archive is encrypted, either as a whole or per file
+    pub fn is_encrypted(&self) -> bool {
+        self.encryption_mode != EncryptionMode::None
+    }
+
+    /// Whether a key must still be supplied before files can be read
+    ///
+    /// True for encrypted archives until `with_decryption_key`,
+    /// `with_recipient_key`, `with_private_key` or `with_password` is called;
+    /// the key itself is only checked by `initialize` (archive mode) or the
+    /// first read (per file). Without a key, `initialize` on an archive-encrypted file fails
+    /// with `MissingDecryptionKey`.
+    pub fn requires_key(&self) -> bool {
+        self.is_encrypted()
+            && self.decryption_key.is_none()
+            && self.recipient_key.is_none()
+            && self.password.is_none()
+    }
+
+    /// Create another handle to the same archive
+    ///
+    /// The new handle reopens the archive file, so it has its own seek position
+    /// and can read concurrently with this one from another thread. The parsed
+    /// central directory is shared rather than read again, and so is the
+    /// decrypted payload of archive-encrypted files. The cancellation token is
+    /// shared too; the progress callback is not carried over.
+    ///
+    /// Only readers created with `open` (or `open_and_init`/`open_encrypted`)
+    /// can be cloned; readers over an arbitrary `from_reader` source return
+    /// `EngramError::Other`.
+    pub fn try_clone(&self) -> Result<ArchiveReader> {
+        let path = self.source_path.as_ref().ok_or_else(|| {
+            EngramError::Other("try_clone requires an archive opened from a path".to_string())
+        })?;
+
+        Ok(Self {
+            file: Source::new(Box::new(File::open(path)?)),
+            positioned_file: OnceLock::new(),
+            source_path: Some(path.clone()),
+            header: self.header.clone(),
+            directory: Arc::clone(&self.directory),
+            lazy: self.lazy,
+            encryption_mode: self.encryption_mode,
+            decryption_key: self.decryption_key.clone(),
+            recipient_key: self.recipient_key.clone(),
+            password: self.password.clone(),
+            decrypted_payload: self.decrypted_payload.clone(),
+            progress: Progress::default(),
+            cancellation: self.cancellation.clone(),
+            zstd_dictionary: self.zstd_dictionary.clone(),
+            mime_table: self.mime_table.clone(),
+            entry_metadata_table: self.entry_metadata_table.clone(),
+            compressed_crc_table: self.compressed_crc_table.clone(),
+            case_insensitive: self.case_insensitive,
+            lenient_paths: self.lenient_paths,
+            special_mode_bits: self.special_mode_bits,
+            cache: self
+                .cache
+                .as_ref()
+                .map(|cache| EntryCache::new(cache.max_bytes())),
+        })
+    }
+
+    /// Get number of entries in archive
+    ///
+    /// Lazy readers report the count recorded in the header until the central
+    /// directory has been parsed.
+    pub fn entry_count(&self) -> usize {
+        match self.directory.parsed.get() {
+            Some(directory) => directory.len(),
+            None => self.header.entry_count as usize,
+        }
+    }
+
+    /// List all file paths in the archive
+    ///
+    /// Lazy readers parse the whole central directory here; if that fails the
+    /// list is empty and the error surfaces on the next read.
+    pub fn list_files(&self) -> &[String] {
+        self.load_directory()
+            .map(|directory| directory.entry_list.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Stream the file paths in central directory order
+    ///
+    /// Yields the same paths as `list_files`, but lazy readers read the
+    /// directory one entry at a time instead of parsing and keeping all of
+    /// it, so memory stays flat however many entries there are. Unlike
+    /// `list_files`, an unreadable entry is reported, naming its index.
+    pub fn iter_files(&self) -> Result<impl Iterator<Item = Result<String>> + '_> {
+        if let Some(directory) = self.directory.parsed.get() {
+            let paths = directory.entry_list.iter().cloned().map(Ok);
+            return Ok(Box::new(paths) as Box<dyn Iterator<Item = Result<String>> + '_>);
+        }
+
+        let mut reader = BufReader::new(self.directory_reader(0)?);
+        let cd_offset = self.header.central_directory_offset;
+        Ok(Box::new((0..self.header.entry_count).map(move |index| {
+            read_directory_entry(&mut reader, cd_offset, self.lenient_paths)
+                .map(|entry| entry.path)
+                .map_err(|e| directory_entry_error(e, index, cd_offset))
+        })))
+    }
+
+    /// Listing of the archive for tooling, from the central directory alone
+    ///
+    /// Nothing is read or decompressed beyond the directory; lazy readers
+    /// parse it here. Serialize the result (or use `inventory_json`) to dump
+    /// an archive's contents without extracting it.
+    pub fn inventory(&self) -> Result<ArchiveInventory> {
+        let directory = self.load_directory()?;
+        Ok(ArchiveInventory::new(
+            &self.header,
+            directory.entries.iter(),
+        ))
+    }
+
+    /// `inventory` as a JSON value
+    pub fn inventory_json(&self) -> Result<serde_json::Value> {
+        Ok(serde_json::to_value(self.inventory()?)?)
+    }
+
+    /// Header, central directory and totals as JSON, for tooling
+    ///
+    /// Unlike `inventory_json`, this is the format structs as they are:
+    ///
+    /// ```json
+    /// {
+    ///   "header": { "version_major": 1, "version_minor": 1, ... },
+    ///   "encryption_mode": "none",
+    ///   "entries": [
+    ///     { "path": "config.toml", "crc32": 171601981, "compression": "zstd", ... }
+    ///   ],
+    ///   "totals": {
+    ///     "entries": 1,
+    ///     "files": 1,
+    ///     "directories": 0,
+    ///     "uncompressed_size": 17,
+    ///     "stored_size": 17
+    ///   }
+    /// }
+    /// ```
+    ///
+    /// `header` and each of `entries` deserialize back into `FileHeader` and
+    /// `EntryInfo`. `stored_size` counts each stored payload once, so
+    /// deduplicated entries add nothing to it. Only the central directory is
+    /// read, as for `inventory`.
+    pub fn metadata_json(&self) -> Result<serde_json::Value> {
+        let directory = self.load_directory()?;
+        let entries = &directory.entries;
+        let directories = entries.iter().filter(|e| e.is_directory()).count();
+        let uncompressed_size: u64 = entries.iter().map(|e| e.uncompressed_size).sum();
+        let stored_size: u64 = entries
+            .iter()
+            .filter(|e| !e.is_deduplicated())
+            .map(|e| e.compressed_size)
+            .sum();
+
+        Ok(serde_json::json!({
+            "header": self.header,
+            "encryption_mode": self.header.encryption_mode(),
+            "entries": entries,
+            "totals": {
+                "entries": entries.len(),
+                "files": entries.len() - directories,
+                "directories": directories,
+                "uncompressed_size": uncompressed_size,
+                "stored_size": stored_size,
+            },
+        }))
+    }
+
+    /// Files whose content has CRC32 `crc`, in central directory order
+    ///
+    /// Only the central directory is consulted; the CRC index is built on
+    /// first use. A CRC32 match is cheap but not proof of equal content:
+    /// compare sizes, or use `find_by_sha256`. Directories are never
+    /// returned, and lazy readers whose directory cannot be parsed find
+    /// nothing.
+    pub fn find_by_crc32(&self, crc: u32) -> Vec<&EntryInfo> {
+        let Ok(directory) = self.load_directory() else {
+            return Vec::new();
+        };
+        directory
+            .crc_index()
+            .get(&crc)
+            .map(|positions| positions.iter().map(|&i| &directory.entries[i]).collect())
+            .unwrap_or_default()
+    }
+
+    /// Paths of files whose content has SHA-256 `digest`, in central
+    /// directory order
+    ///
+    /// Nothing is hashed: a file matches when `manifest.json` lists it with
+    /// this digest or, failing that, when the entry records a SHA-256 prefix
+    /// (`ArchiveWriter::with_strong_checksums`) equal to the digest's first
+    /// `SHA256_PREFIX_LEN` bytes. Files with neither are never returned.
+    pub fn find_by_sha256(&mut self, digest: &[u8; 32]) -> Result<Vec<String>> {
+        let inventory = self.manifest_inventory().unwrap_or_default();
+        let hex_digest = hex::encode(digest);
+        let directory = self.load_directory()?;
+        Ok(directory
+            .entries
+            .iter()
+            .filter(|entry| !entry.is_directory())
+            .filter(|entry| match inventory.get(&entry.path) {
+                Some(listed) => listed.eq_ignore_ascii_case(&hex_digest),
+                None => entry
+                    .sha256
+                    .is_some_and(|prefix| prefix[..] == digest[..SHA256_PREFIX_LEN]),
+            })
+            .map(|entry| entry.path.clone())
+            .collect())
+    }
+
+    /// Files stored with compression `method`, in central directory order
+    ///
+    /// Only the central directory is consulted. Finds, for example, the
+    /// files stored with `CompressionMethod::None` because compressing them
+    /// did not pay off, or the LZ4 entries to recompress as Zstd. Internal
+    /// entries are included; directories are never returned, and lazy
+    /// readers whose directory cannot be parsed find nothing.
+    pub fn entries_with_compression(&self, method: CompressionMethod) -> Vec<&EntryInfo> {
+        let Ok(directory) = self.load_directory() else {
+            return Vec::new();
+        };
+        directory
+            .entries
+            .iter()
+            .filter(|entry| entry.compression == method && !entry.is_directory())
+            .collect()
+    }
+
+    /// Groups of non-empty files with the same size and CRC32, the
+    /// candidates for deduplication
+    ///
+    /// Groups are in central directory order of their first file, as are
+    /// the files within each group. Built from the central directory alone,
+    /// so files in a group are likely, not certain, to be identical. Entries
+    /// already deduplicated are included with the file they share data with.
+    pub fn duplicate_groups(&self) -> Vec<Vec<&EntryInfo>> {
+        let Ok(directory) = self.load_directory() else {
+            return Vec::new();
+        };
+        let mut groups: Vec<Vec<usize>> = Vec::new();
+        for positions in directory.crc_index().values() {
+            let mut by_size: HashMap<u64, Vec<usize>> = HashMap::new();
+            for &position in positions {
+                let size = directory.entries[position].uncompressed_size;
+                if size > 0 {
+                    by_size.entry(size).or_default().push(position);
+                }
+            }
+            groups.extend(by_size.into_values().filter(|group| group.len() > 1));
+        }
+        groups.sort_unstable_by_key(|group| group[0]);
+        groups
+            .into_iter()
+            .map(|group| group.iter().map(|&i| &directory.entries[i]).collect())
+            .collect()
+    }
+
+    /// Check if a file exists in the archive
+    ///
+    /// Paths are matched like `get_entry`.
+    pub fn contains(&self, path: &str) -> bool {
+        self.get_entry(path).is_some()
+    }
+
+    /// Get entry information without reading data
+    ///
+    /// Stored paths use forward slashes with no leading slash. Lookups accept
+    /// backslashes and leading slashes as well, and ignore case when
+    /// `with_case_insensitive_lookup` is enabled. All path-taking accessors
+    /// match paths this way; the stored form is `EntryInfo::path`.
+    ///
+    /// For lazy readers, a central directory entry that cannot be read is
+    /// treated as missing; `read_file` reports the underlying error instead.
+    pub fn get_entry(&self, path: &str) -> Option<&EntryInfo> {
+        self.find_entry(path).ok().flatten()
+    }
+
+    /// MIME type recorded for a file, if any
+    ///
+    /// Unlike `EntryInfo::mime_type`, this also resolves custom types, which
+    /// are stored in the archive's `MIME_TABLE_PATH`. Archives written before
+    /// MIME types were recorded return `None` for every file.
+    pub fn mime_type(&mut self, path: &str) -> Result<Option<String>> {
+        let id = self.lookup_entry(path)?.mime_id;
+        if id < MIME_TABLE_BASE {
+            return Ok(builtin_mime_type(id).map(str::to_string));
+        }
+        let table = self.load_mime_table()?;
+        Ok(table.get(usize::from(id - MIME_TABLE_BASE)).cloned())
+    }
+
+    /// Metadata of an entry added with custom key/value pairs
+    ///
+    /// Pairs from `FileMetadata::extra` are stored in the archive's
+    /// `ENTRY_METADATA_PATH`; the rest is read from the central directory, as
+    /// `get_entry` and `mime_type` do. `None` for entries added without
+    /// custom pairs.
+    pub fn entry_metadata(&mut self, path: &str) -> Result<Option<EntryMetadata>> {
+        let entry = self.lookup_entry(path)?;
+        let table = self.load_entry_metadata_table()?;
+        let Some(extra) = table.get(&entry.path) else {
+            return Ok(None);
+        };
+        Ok(Some(EntryMetadata {
+            modified_time: entry.modified_time,
+            executable: entry.is_executable(),
+            mime_type: self.mime_type(&entry.path)?,
+            extra: extra.clone(),
+        }))
+    }
+
+    /// CRC32 of a file's stored (compressed) payload, if the archive records one
+    ///
+    /// Recorded by writers with `ArchiveWriter::with_compressed_checksums` in
+    /// `COMPRESSED_CRC_PATH`; `None` for other archives and for directories.
+    pub fn compressed_crc32(&mut self, path: &str) -> Result<Option<u32>> {
+        let entry = self.lookup_entry(path)?;
+        if entry.is_directory() {
+            return Ok(None);
+        }
+        let table = self.load_compressed_crc_table()?;
+        Ok(table.get(&entry.data_offset).copied())
+    }
+
+    /// Read a file's stored payload without decompressing it
+    ///
+    /// The payload is returned exactly as stored: compressed, and still
+    /// encrypted in per-file mode (archive-level encryption is undone, since
+    /// it covers the whole archive rather than the entry). The LOCA header is
+    /// checked against the central directory, but the content is not: the
+    /// CRC32 covers the uncompressed form, so consumers that pass the data on
+    /// should keep `RawEntry::crc32` with it. Use
+    /// `ArchiveWriter::add_raw_entry` to store it in another archive.
+    ///
+    /// Large LZ4/Zstd entries of archives before v1.1 are framed in a layout
+    /// that current archives do not use; copy those with `read_file` instead.
+    pub fn read_raw_entry(&mut self, path: &str) -> Result<RawEntry> {
+        let entry = self.lookup_file_entry(path)?;
+        let data = self
+            .read_stored(&entry)
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))?;
+
+        // Entries of v1.1 archives from before the framed flag are framed by size
+        let framed = if Self::has_frames(&entry) {
+            ENTRY_FLAG_FRAMED
+        } else {
+            0
+        };
+        Ok(RawEntry {
+            data,
+            compression: entry.compression,
+            uncompressed_size: entry.uncompressed_size,
+            crc32: entry.crc32,
+            encrypted: self.encryption_mode == EncryptionMode::PerFile,
+            flags: entry.flags & ENTRY_FLAG_ZSTD_DICTIONARY | framed,
+        })
+    }
+
+    /// Read a file's compressed data without decompressing it
+    ///
+    /// Like `read_raw_entry`, except that per-file encryption is undone too
+    /// (which needs the decryption key), so the data is exactly what the
+    /// compression method produced. The entry's CRC32 and SHA-256 cover the
+    /// uncompressed form and are not checked here; consumers that relay the
+    /// data should keep the entry's `EntryInfo` with it. To store the data
+    /// in another archive, pass it to `ArchiveWriter::add_raw_entry` as an
+    /// unencrypted `RawEntry`.
+    pub fn read_file_raw(&mut self, path: &str) -> Result<(CompressionMethod, Vec<u8>)> {
+        let entry = self.lookup_file_entry(path)?;
+        let data = self
+            .read_stored(&entry)
+            .and_then(|payload| self.decrypt_raw(&entry, payload))
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))?;
+        Ok((entry.compression, data))
+    }
+
+    /// `lookup_entry`, refusing directories, which have no data to read raw,
+    /// and chunked entries, whose payload is a chunk list
+    fn lookup_file_entry(&self, path: &str) -> Result<EntryInfo> {
+        let entry = self.lookup_entry(path)?;
+        let kind = if entry.is_directory() {
+            "directory"
+        } else if entry.is_chunked() {
+            "chunked"
+        } else {
+            return Ok(entry);
+        };
+        Err(EngramError::PathError(format!(
+            "Cannot read raw data of {} entry: {}",
+            kind, entry.path
+        )))
+    }
+
+    /// Read a file from the archive
+    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>> {
+        let entry = self.lookup_entry(path)?;
+        self.read_file_entry(&entry)
+    }
+
+    /// Read every file once, in the order the payloads are stored
+    ///
+    /// Yields `(path, content)` for each file entry (directories are skipped)
+    /// sorted by data offset, so the archive is read front to back instead of
+    /// seeking back and forth as `read_file` per path would. Only one file's
+    /// content is held at a time: write it out and drop it before taking the
+    /// next. Entries are checked like `read_file`; a failing entry is yielded
+    /// as an error and the walk goes on, except after `Cancelled` or
+    /// `CallbackPanicked`, which end it. A central directory that cannot be
+    /// read is yielded as the only item.
+    pub fn drain_entries(&mut self) -> impl Iterator<Item = Result<(String, Vec<u8>)>> + '_ {
+        let (entries, error) = match self.load_directory() {
+            Ok(directory) => {
+                let mut entries: Vec<EntryInfo> = directory
+                    .entries
+                    .iter()
+                    .filter(|entry| !entry.is_directory())
+                    .cloned()
+                    .collect();
+                entries.sort_by_key(|entry| entry.data_offset);
+                (entries, None)
+            }
+            Err(err) => (Vec::new(), Some(err)),
+        };
+
+        let mut stopped = false;
+        let files = entries.into_iter().map_while(move |entry| {
+            if stopped {
+                return None;
+            }
+            let result = self.read_file_entry(&entry).map(|data| (entry.path, data));
+            stopped = matches!(
+                result,
+                Err(EngramError::Cancelled | EngramError::CallbackPanicked)
+            );
+            Some(result)
+        });
+        error.map(Err).into_iter().chain(files)
+    }
+
+    /// Read every file in central directory order, one at a time
+    ///
+    /// Yields `(path, content)` for each file entry (directories are
+    /// skipped), reading and decompressing an entry only when the iterator
+    /// gets to it, so only one file's content is held at a time; lazy
+    /// readers also parse the directory one entry at a time. Entries are
+    /// decrypted and checked like `read_file`. A failing entry is yielded as
+    /// an error and the walk goes on, or is left out with
+    /// `ContentsIter::skip_errors`; `Cancelled` and `CallbackPanicked` end
+    /// it either way. `drain_entries` reads in storage order instead.
+    ///
+    /// The iterator borrows the reader, so a plain `for` loop works without
+    /// copying the path list first:
+    ///
+    /// ```no_run
+    /// use engram_rs::ArchiveReader;
+    ///
+    /// let mut reader = ArchiveReader::open_and_init("data.eng")?;
+    /// let mut total = 0;
+    /// for item in reader.iter_contents() {
+    ///     let (path, data) = item?;
+    ///     total += data.len();
+    ///     println!("{}: {} bytes", path, data.len());
+    /// }
+    /// // Usable again once the loop is done
+    /// assert_eq!(reader.entry_count(), reader.list_files().len());
+    /// # Ok::<(), engram_rs::EngramError>(())
+    /// ```
+    pub fn iter_contents(&mut self) -> ContentsIter<'_> {
+        ContentsIter {
+            count: self.entry_count(),
+            reader: self,
+            index: 0,
+            skip_errors: false,
+            stopped: false,
+        }
+    }
+
+    /// The `index`-th central directory entry, parsing only that entry of a
+    /// lazy directory
+    fn entry_at(&self, index: usize) -> Result<EntryInfo> {
+        let entry = match (self.directory.parsed.get(), &self.directory.entries) {
+            (Some(directory), _) => directory.entries.get(index),
+            (None, Some(entries)) => Some(self.lazy_entry(entries, index)?),
+            (None, None) => self.load_directory()?.entries.get(index),
+        };
+        entry
+            .cloned()
+            .ok_or_else(|| EngramError::Internal(format!("No central directory entry {}", index)))
+    }
+
+    /// `read_file` for an entry already looked up
+    fn read_file_entry(&mut self, entry: &EntryInfo) -> Result<Vec<u8>> {
+        self.cancellation.check()?;
+        if entry.is_chunked() {
+            let mut output = Vec::with_capacity(preallocation(entry.uncompressed_size));
+            self.read_chunked(entry, |data| {
+                output.extend_from_slice(data);
+                Ok(())
+            })?;
+            return Ok(output);
+        }
+        let dictionary = if entry.uses_zstd_dictionary() {
+            Some(self.load_zstd_dictionary()?)
+        } else {
+            None
+        };
+
+        // The callback is moved out so decoding can borrow the reader immutably
+        let mut progress = std::mem::take(&mut self.progress);
+        let dictionary = dictionary.as_ref().map(|d| d.as_slice());
+        let result = self.read_entry(entry, &mut progress, |reader, raw, report| {
+            reader.decode_entry(entry, raw, dictionary, report)
+        });
+        self.progress = progress;
+        result
+    }
+
+    /// Read a file into `out`, returning the number of bytes written
+    ///
+    /// Frame-compressed entries (50 MB and up) are decompressed and written one
+    /// 64 KB frame at a time, with the CRC32 (and SHA-256, if recorded) updated
+    /// as each frame is produced, so the decompressed file is never held in
+    /// memory. The stored payload is still read in full. Smaller entries are
+    /// decoded like `read_file` and then written.
+    ///
+    /// Content is verified once the last frame is written: on `CrcMismatch`
+    /// or `HashMismatch`, `out` has already received the corrupt data and
+    /// should be discarded.
+    pub fn read_file_to<W: Write>(&mut self, path: &str, mut out: W) -> Result<u64> {
+        let entry = self.lookup_entry(path)?;
+        self.cancellation.check()?;
+        if entry.is_chunked() {
+            self.read_chunked(&entry, |data| Ok(out.write_all(data)?))?;
+            return Ok(entry.uncompressed_size);
+        }
+        let dictionary = if entry.uses_zstd_dictionary() {
+            Some(self.load_zstd_dictionary()?)
+        } else {
+            None
+        };
+
+        let mut progress = std::mem::take(&mut self.progress);
+        let dictionary = dictionary.as_ref().map(|d| d.as_slice());
+        let result = self.read_entry(&entry, &mut progress, |reader, raw, report| {
+            reader.decode_entry_to(&entry, raw, dictionary, &mut out, report)
+        });
+        self.progress = progress;
+        result
+    }
+
+    /// Cache decompressed entries read with `read_file_cached`
+    ///
+    /// Up to `max_bytes` of decompressed content is kept; the least recently
+    /// used entries are evicted to make room, and entries larger than
+    /// `max_bytes` are read but not cached. Archives do not change once open,
+    /// so cached entries never go stale. Entries of encrypted archives are
+    /// cached as decrypted content. Handles from `try_clone` start with an
+    /// empty cache of the same size.
+    pub fn with_cache(mut self, max_bytes: usize) -> Self {
+        self.cache = Some(EntryCache::new(max_bytes));
+        self
+    }
+
+    /// Read a file, serving repeated reads from the cache
+    ///
+    /// Paths are matched like `get_entry` and cached under the stored path. A
+    /// cache hit skips reading, decompression and CRC checks and returns the
+    /// shared buffer without copying; progress events are only emitted for
+    /// misses. Without `with_cache` this is `read_file` wrapped in an `Arc`.
+    pub fn read_file_cached(&mut self, path: &str) -> Result<Arc<Vec<u8>>> {
+        if self.cache.is_none() {
+            return Ok(Arc::new(self.read_file(path)?));
+        }
+
+        let stored_path = self.lookup_entry(path)?.path;
+        if let Some(data) = self
+            .cache
+            .as_mut()
+            .and_then(|cache| cache.get(&stored_path))
+        {
+            return Ok(data);
+        }
+
+        let data = Arc::new(self.read_file(&stored_path)?);
+        if let Some(cache) = &mut self.cache {
+            cache.insert(stored_path, Arc::clone(&data));
+        }
+        Ok(data)
+    }
+
+    /// Hit, miss and size counters of the entry cache (`None` without `with_cache`)
+    pub fn cache_stats(&self) -> Option<CacheStats> {
+        self.cache.as_ref().map(EntryCache::stats)
+    }
+
+    /// Read up to `len` leading bytes of a file without decoding all of it
+    ///
+    /// Only as much of the stored payload as needed is read: the bytes
+    /// themselves for uncompressed entries, the start of the Zstd stream, or
+    /// the first 64 KB frame of frame-compressed entries. Smaller LZ4 entries
+    /// and per-file encrypted entries are decoded in full and truncated.
+    ///
+    /// A prefix cannot be checked against the entry's CRC32, so treat it as a
+    /// hint (e.g. for file type sniffing) rather than verified content.
+    pub fn read_prefix(&mut self, path: &str, len: usize) -> Result<Vec<u8>> {
+        let entry = self.lookup_entry(path)?;
+        Self::check_compression(&entry)?;
+        self.cancellation.check()?;
+        let len = len.min(entry.uncompressed_size as usize);
+
+        let per_file = self.encryption_mode == EncryptionMode::PerFile && !entry.is_directory();
+        if per_file || entry.is_chunked() {
+            // AES-GCM only authenticates the payload as a whole
+            let progress = std::mem::take(&mut self.progress);
+            let result = self.read_file(path);
+            self.progress = progress;
+            let mut data = result?;
+            data.truncate(len);
+            return Ok(data);
+        }
+
+        let dictionary = if entry.uses_zstd_dictionary() {
+            Some(self.load_zstd_dictionary()?)
+        } else {
+            None
+        };
+        let dictionary = dictionary.as_ref().map(|d| d.as_slice());
+
+        let layout = self.framed_layout(&entry);
+        let legacy = self.header.is_legacy();
+        let prefix = match self.encryption_mode {
+            EncryptionMode::Archive => self.stored_in_payload(&entry).and_then(|stored| {
+                Self::decode_prefix(&entry, layout, stored, dictionary, len)
+            }),
+            _ => {
+                let file = self.file.get();
+                file.seek(SeekFrom::Start(entry.data_offset))
+                    .map_err(EngramError::from)
+                    .and_then(|_| Self::check_local_header(&mut *file, &entry, legacy))
+                    .and_then(|_| {
+                        let stored = file.take(entry.compressed_size);
+                        Self::decode_prefix(&entry, layout, stored, dictionary, len)
+                    })
+            }
+        };
+        prefix.map_err(|e| e.in_entry(&entry.path, entry.data_offset))
+    }
+
+    /// Decode up to `len` leading bytes from an unencrypted stored payload
+    pub(crate) fn decode_prefix<R: Read>(
+        entry: &EntryInfo,
+        layout: FrameLayout,
+        mut stored: R,
+        dictionary: Option<&[u8]>,
+        len: usize,
+    ) -> Result<Vec<u8>> {
+        let mut prefix = Vec::with_capacity(len);
+        if layout != FrameLayout::None {
+            let frame = first_frame(stored, entry.compression, layout)?;
+            prefix.extend_from_slice(&frame[..len.min(frame.len())]);
+            return Ok(prefix);
+        }
+
+        match entry.compression {
+            CompressionMethod::None => {
+                stored.take(len as u64).read_to_end(&mut prefix)?;
+            }
+            CompressionMethod::Lz4 => {
+                let mut data = Vec::with_capacity(entry.compressed_size as usize);
+                stored.read_to_end(&mut data)?;
+                let decompressed = Self::decompress_lz4(&data, entry)?;
+                prefix.extend_from_slice(&decompressed[..len.min(decompressed.len())]);
+            }
+            CompressionMethod::Zstd => {
+                let zstd_error = |e: std::io::Error| {
+                    EngramError::decompression_failed(format!("Zstd decompression failed: {}", e))
+                };
+                let decoder: Box<dyn Read + '_> = match dictionary {
+                    Some(dictionary) => Box::new(
+                        zstd::stream::read::Decoder::with_dictionary(
+                            BufReader::new(stored),
+                            dictionary,
+                        )
+                        .map_err(zstd_error)?,
+                    ),
+                    None => Box::new(zstd::stream::read::Decoder::new(stored).map_err(zstd_error)?),
+                };
+                decoder
+                    .take(len as u64)
+                    .read_to_end(&mut prefix)
+                    .map_err(zstd_error)?;
+            }
+            CompressionMethod::Deflate => {
+                flate2::read::DeflateDecoder::new(stored)
+                    .take(len as u64)
+                    .read_to_end(&mut prefix)
+                    .map_err(deflate_error)?;
+            }
+            CompressionMethod::Unknown(value) => {
+                return Err(EngramError::InvalidCompression(value));
+            }
+        }
+        Ok(prefix)
+    }
+
+    /// Read `len` bytes of a file starting at uncompressed `offset`
+    ///
+    /// The range is clamped to the file's size. Frame-compressed entries
+    /// (50 MB and up) written with format v1.1 or later carry a frame index,
+    /// so only the 64 KB frames overlapping the range are read and
+    /// decompressed; uncompressed entries read just the requested bytes.
+    /// Other entries, including all per-file encrypted entries, are decoded
+    /// in full and sliced.
+    ///
+    /// Like `read_prefix`, a range cannot be checked against the entry's
+    /// CRC32; use `read_file` when the content must be verified.
+    pub fn read_file_range(&mut self, path: &str, offset: u64, len: usize) -> Result<Vec<u8>> {
+        let entry = self.lookup_entry(path)?;
+        Self::check_compression(&entry)?;
+        self.cancellation.check()?;
+        let start = offset.min(entry.uncompressed_size);
+        let end = start
+            .saturating_add(len as u64)
+            .min(entry.uncompressed_size);
+
+        let layout = self.framed_layout(&entry);
+        let partial = self.encryption_mode != EncryptionMode::PerFile
+            && !entry.is_chunked()
+            && (layout == FrameLayout::Indexed || entry.compression == CompressionMethod::None);
+        if !partial {
+            let progress = std::mem::take(&mut self.progress);
+            let result = self.read_file(path);
+            self.progress = progress;
+            return Ok(result?[start as usize..end as usize].to_vec());
+        }
+
+        self.read_stored_range(&entry, layout, start, end)
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))
+    }
+
+    /// Read the stored bytes of an uncompressed or indexed framed entry
+    /// covering `start..end` and decode them (see `read_file_range`)
+    fn read_stored_range(
+        &mut self,
+        entry: &EntryInfo,
+        layout: FrameLayout,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<u8>> {
+        match self.encryption_mode {
+            EncryptionMode::Archive => {
+                let stored = self.stored_in_payload(entry)?;
+                if layout == FrameLayout::Indexed {
+                    let index = FrameIndex::read_from(stored)?;
+                    Self::decode_frame_range(entry, &index, start..end, |frame| {
+                        Ok(stored_frame(stored, frame)?.to_vec())
+                    })
+                } else {
+                    Ok(stored[start as usize..end as usize].to_vec())
+                }
+            }
+            _ => {
+                let legacy = self.header.is_legacy();
+                let file = self.file.get();
+                file.seek(SeekFrom::Start(entry.data_offset))?;
+                Self::check_local_header(&mut *file, entry, legacy)?;
+                let payload_start = file.stream_position()?;
+                if layout == FrameLayout::Indexed {
+                    let index = FrameIndex::read_from(BufReader::new(
+                        (&mut *file).take(entry.compressed_size),
+                    ))?;
+                    Self::decode_frame_range(entry, &index, start..end, |frame| {
+                        if frame.compressed_offset + frame.compressed_size as u64
+                            > entry.compressed_size
+                        {
+                            return Err(EngramError::InvalidFormat(format!(
+                                "Frame at offset {} lies outside the payload",
+                                frame.compressed_offset
+                            )));
+                        }
+                        file.seek(SeekFrom::Start(payload_start + frame.compressed_offset))?;
+                        let mut data = vec![0u8; frame.compressed_size as usize];
+                        file.read_exact(&mut data)?;
+                        Ok(data)
+                    })
+                } else {
+                    file.seek(SeekFrom::Start(payload_start + start))?;
+                    let mut data = vec![0u8; (end - start) as usize];
+                    file.read_exact(&mut data)?;
+                    Ok(data)
+                }
+            }
+        }
+    }
+
+    /// Decompress the frames overlapping `range` and cut the range out of them
+    fn decode_frame_range<F>(
+        entry: &EntryInfo,
+        index: &FrameIndex,
+        range: Range<u64>,
+        fetch: F,
+    ) -> Result<Vec<u8>>
+    where
+        F: FnMut(&FrameInfo) -> Result<Vec<u8>>,
+    {
+        if index.decompressed_size() != entry.uncompressed_size {
+            return Err(EngramError::InvalidFormat(format!(
+                "Frame index covers {} bytes, expected {}",
+                index.decompressed_size(),
+                entry.uncompressed_size
+            )));
+        }
+        let frames = index.frames_overlapping(range.clone());
+        if frames.is_empty() {
+            return Ok(Vec::new());
+        }
+        let base = index.frames()[frames.start].decompressed_offset;
+        let data = decompress_indexed_frames(index, entry.compression, frames, fetch)?;
+        Ok(data[(range.start - base) as usize..(range.end - base) as usize].to_vec())
+    }
+
+    /// Read a file without mutable access to the reader
+    ///
+    /// Uses positioned reads on a separate handle to the archive file instead of
+    /// the reader's seek position, so one reader (e.g. in an `Arc`) can serve
+    /// concurrent calls from many threads without locking. Decompression and
+    /// CRC/SHA-256 verification are the same as `read_file`; no progress events
+    /// are emitted.
+    ///
+    /// Requires a reader created with `open` (or `open_and_init`/`open_encrypted`),
+    /// except for archive-encrypted files whose payload is already in memory.
+    /// Positioned reads are available on Unix and Windows.
+    pub fn read_file_at(&self, path: &str) -> Result<Vec<u8>> {
+        let entry = self.lookup_entry(path)?;
+        self.read_entry_at(&entry)
+    }
+
+    /// `read_file_at` for an entry already looked up
+    fn read_entry_at(&self, entry: &EntryInfo) -> Result<Vec<u8>> {
+        Self::check_compression(entry)?;
+        self.cancellation.check()?;
+        let dictionary = if entry.uses_zstd_dictionary() {
+            Some(self.load_zstd_dictionary_at()?)
+        } else {
+            None
+        };
+
+        let raw = match self.encryption_mode {
+            EncryptionMode::Archive => self.read_raw_from_payload(entry),
+            _ => {
+                let reader = PositionedReader {
+                    file: self.positioned_file()?,
+                    offset: entry.data_offset,
+                };
+                Self::read_raw_from(reader, entry, self.header.is_legacy(), &self.cancellation)
+            }
+        };
+
+        if entry.is_chunked() {
+            let mut output = Vec::with_capacity(preallocation(entry.uncompressed_size));
+            return raw
+                .and_then(|raw| self.decrypt_raw(entry, raw))
+                .and_then(|list| chunking::parse_chunk_list(&list))
+                .and_then(|chunks| {
+                    Self::reassemble_chunks(
+                        entry,
+                        &chunks,
+                        |path| self.read_entry_at(&self.lookup_chunk(path)?),
+                        |data| {
+                            output.extend_from_slice(data);
+                            Ok(())
+                        },
+                    )
+                })
+                .map(|_| output)
+                .map_err(|e| e.in_entry(&entry.path, entry.data_offset));
+        }
+
+        raw.and_then(|raw| {
+            self.decode_entry(
+                entry,
+                raw,
+                dictionary.as_ref().map(|d| d.as_slice()),
+                |_| Ok(()),
+            )
+        })
+        .map_err(|e| e.in_entry(&entry.path, entry.data_offset))
+    }
+
+    /// Read a file, borrowing its bytes when the archive is already in memory
+    ///
+    /// Entries stored uncompressed are returned as `Cow::Borrowed` straight
+    /// from the in-memory archive, with CRC32 (and SHA-256) verified over the
+    /// borrowed slice. The archive is in memory for archive-encrypted files,
+    /// whose payload is decrypted by `initialize`. Everything else is read like
+    /// `read_file_at` and returned as `Cow::Owned`.
+    pub fn read_file_ref(&self, path: &str) -> Result<Cow<'_, [u8]>> {
+        let entry = self.lookup_entry(path)?;
+        if self.decrypted_payload.is_some()
+            && entry.compression == CompressionMethod::None
+            && !entry.is_chunked()
+        {
+            self.cancellation.check()?;
+            let data = self.stored_in_payload(&entry)?;
+            Self::verify_content(&entry, data)?;
+            return Ok(Cow::Borrowed(data));
+        }
+        Ok(Cow::Owned(self.read_file_at(path)?))
+    }
+
+    /// Look up the chunk at `path`, named in a chunk list
+    ///
+    /// Chunks are plain entries; a chunk that is itself chunked is refused,
+    /// so a crafted list cannot make reads recurse.
+    pub(crate) fn lookup_chunk(&self, path: &str) -> Result<EntryInfo> {
+        let entry = self.lookup_entry(path)?;
+        if entry.is_chunked() || entry.is_directory() {
+            return Err(EngramError::InvalidFormat(format!(
+                "Chunk {} is not a plain file entry",
+                path
+            )));
+        }
+        Ok(entry)
+    }
+
+    /// `read_file_entry` and `read_file_to` for chunked entries: read the
+    /// chunk list, then each chunk in turn, passing the content to `sink`
+    fn read_chunked(
+        &mut self,
+        entry: &EntryInfo,
+        mut sink: impl FnMut(&[u8]) -> Result<()>,
+    ) -> Result<()> {
+        // Chunks are read as entries of their own, without progress events
+        let mut progress = std::mem::take(&mut self.progress);
+        let total = entry.uncompressed_size;
+        let mut bytes_done = 0;
+        let result = progress
+            .emit(|| ProgressEvent::FileStarted {
+                path: entry.path.clone(),
+                size: total,
+            })
+            .and_then(|_| self.read_stored(entry))
+            .and_then(|raw| self.decrypt_raw(entry, raw))
+            .and_then(|list| chunking::parse_chunk_list(&list))
+            .and_then(|chunks| {
+                Self::reassemble_chunks(
+                    entry,
+                    &chunks,
+                    |path| {
+                        let chunk = self.lookup_chunk(path)?;
+                        self.read_file_entry(&chunk)
+                    },
+                    |data| {
+                        sink(data)?;
+                        bytes_done += data.len() as u64;
+                        progress.emit(|| ProgressEvent::BytesProcessed {
+                            path: entry.path.clone(),
+                            bytes_done,
+                            total,
+                        })
+                    },
+                )
+            })
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))
+            .and_then(|_| {
+                progress.emit(|| ProgressEvent::FileFinished {
+                    path: entry.path.clone(),
+                    compressed_size: entry.compressed_size,
+                })
+            });
+        self.progress = progress;
+        result
+    }
+
+    /// Reassemble a chunked entry from the chunks in its list, in order
+    ///
+    /// `read_chunk` reads the chunk at a path; each chunk is checked against
+    /// the SHA-256 in the list before it is passed to `sink`, and the whole
+    /// content against the entry's CRC32 (and SHA-256, if recorded).
+    pub(crate) fn reassemble_chunks(
+        entry: &EntryInfo,
+        chunks: &[ChunkRef],
+        mut read_chunk: impl FnMut(&str) -> Result<Vec<u8>>,
+        mut sink: impl FnMut(&[u8]) -> Result<()>,
+    ) -> Result<()> {
+        let listed = chunks
+            .iter()
+            .try_fold(0u64, |sum, chunk| sum.checked_add(chunk.len));
+        if listed != Some(entry.uncompressed_size) {
+            return Err(EngramError::InvalidFormat(format!(
+                "Chunk list does not add up to the entry size of {} bytes",
+                entry.uncompressed_size
+            )));
+        }
+
+        let mut check = ContentCheck::new(entry);
+        for chunk in chunks {
+            let path = chunking::chunk_path(&chunk.hash);
+            let data = read_chunk(&path)?;
+            let digest = Sha256::digest(&data);
+            if digest[..] != chunk.hash || data.len() as u64 != chunk.len {
+                return Err(EngramError::HashMismatch {
+                    path,
+                    expected: hex::encode(chunk.hash),
+                    actual: hex::encode(digest),
+                });
+            }
+            check.update(&data);
+            sink(&data)?;
+        }
+        check.finish(entry)
+    }
+
+    /// Look up an entry by path, matched like `get_entry`
+    fn lookup_entry(&self, path: &str) -> Result<EntryInfo> {
+        self.find_entry(path)?
+            .cloned()
+            .ok_or_else(|| EngramError::FileNotFound(path.to_string()))
+    }
+
+    /// `get_entry`, surfacing errors from reading a lazy central directory
+    fn find_entry(&self, path: &str) -> Result<Option<&EntryInfo>> {
+        if let Some(entry) = self.find_exact(path)? {
+            return Ok(Some(entry));
+        }
+
+        let canonical = canonical_lookup_path(path);
+        if let Some(entry) = self.find_exact(&canonical)? {
+            return Ok(Some(entry));
+        }
+
+        if self.case_insensitive {
+            let directory = self.load_directory()?;
+            let index = self.directory.lowercase_index.get_or_init(|| {
+                let mut index = HashMap::with_capacity(directory.entry_list.len());
+                for path in directory.entry_list.iter() {
+                    index
+                        .entry(path.to_lowercase())
+                        .or_insert_with(|| path.clone());
+                }
+                index
+            });
+            if let Some(stored) = index.get(&canonical.to_lowercase()) {
+                return Ok(directory.get(stored));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Find the entry stored under exactly `path`
+    fn find_exact(&self, path: &str) -> Result<Option<&EntryInfo>> {
+        if let Some(directory) = self.directory.parsed.get() {
+            return Ok(directory.get(path));
+        }
+        match &self.directory.entries {
+            Some(entries) if self.directory.sorted => self.search_sorted(entries, path),
+            Some(entries) => self.search_hashed(entries, path),
+            None => Ok(self.load_directory()?.get(path)),
+        }
+    }
+
+    /// Binary search a sorted central directory, parsing only the probed entries
+    fn search_sorted<'a>(
+        &'a self,
+        entries: &'a LazyEntries,
+        path: &str,
+    ) -> Result<Option<&'a EntryInfo>> {
+        let (mut low, mut high) = (0, self.header.entry_count as usize);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let entry = self.lazy_entry(entries, mid)?;
+            match entry.path.as_str().cmp(path) {
+                std::cmp::Ordering::Less => low = mid + 1,
+                std::cmp::Ordering::Greater => high = mid,
+                std::cmp::Ordering::Equal => return Ok(Some(entry)),
+            }
+        }
+        Ok(None)
+    }
+
+    /// Look up `path` by hash in an unsorted central directory, parsing only
+    /// the entries whose path hashes the same
+    fn search_hashed<'a>(
+        &'a self,
+        entries: &'a LazyEntries,
+        path: &str,
+    ) -> Result<Option<&'a EntryInfo>> {
+        let hashes = match self.directory.hashes.get() {
+            Some(hashes) => hashes,
+            None => {
+                let hashes = PathHashes::build(
+                    BufReader::new(self.directory_reader(0)?),
+                    self.header.entry_count,
+                    self.header.central_directory_size,
+                    self.header.central_directory_offset,
+                )?;
+                self.directory.hashes.get_or_init(|| hashes)
+            }
+        };
+        // Later entries for the same path win, as in a parsed directory
+        for index in hashes.candidates(path) {
+            let entry = self.lazy_entry(entries, index)?;
+            if entry.path == path {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+
+    /// The `index`-th central directory entry, parsed on first use
+    fn lazy_entry<'a>(&'a self, entries: &'a LazyEntries, index: usize) -> Result<&'a EntryInfo> {
+        let slot = entries.slot(index);
+        match slot.get() {
+            Some(entry) => Ok(entry),
+            None => {
+                let entry = self.read_directory_entry(index)?;
+                Ok(slot.get_or_init(|| entry))
+            }
+        }
+    }
+
+    /// Parsed central directory, parsing it now if the reader is lazy
+    fn load_directory(&self) -> Result<&Directory> {
+        if let Some(directory) = self.directory.parsed.get() {
+            return Ok(directory);
+        }
+
+        let reader = BufReader::new(self.directory_reader(0)?);
+        let mut directory = Directory::parse(
+            reader,
+            self.header.entry_count,
+            self.header.central_directory_size,
+            self.header.has_sorted_directory(),
+            self.header.central_directory_offset,
+            self.lenient_paths,
+        )?;
+        attach_blake3(&self.directory.blake3, &mut directory.entries);
+        Ok(self.directory.parsed.get_or_init(|| directory))
+    }
+
+    /// Read the `index`-th central directory entry
+    fn read_directory_entry(&self, index: usize) -> Result<EntryInfo> {
+        let cd_offset = self.header.central_directory_offset;
+        let mut entry = self
+            .directory_reader((index * CD_ENTRY_SIZE) as u64)
+            .and_then(|reader| read_directory_entry(reader, cd_offset, self.lenient_paths))
+            .map_err(|e| directory_entry_error(e, index as u32, cd_offset))?;
+        entry.blake3 = self.directory.blake3.get(&entry.data_offset).copied();
+        Ok(entry)
+    }
+
+    /// Reader over the central directory, `offset` bytes in, without mutable access
+    fn directory_reader(&self, offset: u64) -> Result<Box<dyn Read + '_>> {
+        let offset = self.header.central_directory_offset + offset;
+        match &self.decrypted_payload {
+            Some(payload) => {
+                // The decrypted payload starts at what would be byte 64 in the file
+                let start = offset
+                    .checked_sub(HEADER_SIZE as u64)
+                    .and_then(|start| payload.get(start as usize..))
+                    .ok_or_else(|| {
+                        EngramError::InvalidFormat(
+                            "Central directory offset outside decrypted payload".to_string(),
+                        )
+                    })?;
+                Ok(Box::new(start))
+            }
+            None => Ok(Box::new(PositionedReader {
+                file: self.positioned_file()?,
+                offset,
+            })),
+        }
+    }
+
+    /// Read, decode and verify one entry, reporting progress
+    fn read_entry<T>(
+        &mut self,
+        entry: &EntryInfo,
+        progress: &mut Progress,
+        decode: impl FnOnce(&Self, Vec<u8>, &mut dyn FnMut(u64) -> Result<()>) -> Result<T>,
+    ) -> Result<T> {
+        Self::check_compression(entry)?;
+        let is_file = !entry.is_directory();
+        let total = entry.uncompressed_size;
+
+        if is_file {
+            progress.emit(|| ProgressEvent::FileStarted {
+                path: entry.path.clone(),
+                size: total,
+            })?;
+        }
+
+        // Read data (from file or from decrypted payload)
+        let raw = match self.encryption_mode {
+            EncryptionMode::Archive => self.read_raw_from_payload(entry),
+            _ => {
+                // Read from file (normal or per-file encrypted)
+                let file = self.file.get();
+                file.seek(SeekFrom::Start(entry.data_offset))
+                    .map_err(EngramError::from)
+                    .and_then(|_| {
+                        Self::read_raw_from(
+                            file,
+                            entry,
+                            self.header.is_legacy(),
+                            &self.cancellation,
+                        )
+                    })
+            }
+        };
+
+        let decompressed = raw
+            .and_then(|raw| {
+                decode(self, raw, &mut |bytes_done| {
+                    if !is_file {
+                        return Ok(());
+                    }
+                    progress.emit(|| ProgressEvent::BytesProcessed {
+                        path: entry.path.clone(),
+                        bytes_done,
+                        total,
+                    })
+                })
+            })
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))?;
+
+        if is_file {
+            progress.emit(|| ProgressEvent::FileFinished {
+                path: entry.path.clone(),
+                compressed_size: entry.compressed_size,
+            })?;
+        }
+
+        Ok(decompressed)
+    }
+
+    /// Read an entry's stored payload without decrypting or decompressing it
+    ///
+    /// The LOCA header is still checked against the central directory entry.
+    pub(crate) fn read_stored(&mut self, entry: &EntryInfo) -> Result<Vec<u8>> {
+        match self.encryption_mode {
+            EncryptionMode::Archive => self.read_raw_from_payload(entry),
+            _ => {
+                let file = self.file.get();
+                file.seek(SeekFrom::Start(entry.data_offset))?;
+                Self::read_raw_from(file, entry, self.header.is_legacy(), &self.cancellation)
+            }
+        }
+    }
+
+    /// Decode an entry from the bytes at its data offset (LOCA header and
+    /// stored payload), read by the caller
+    ///
+    /// Used by readers that do their own I/O, such as `AsyncArchiveReader`.
+    #[cfg(feature = "async")]
+    pub(crate) fn decode_stored(
+        &self,
+        entry: &EntryInfo,
+        stored: &[u8],
+        dictionary: Option<&[u8]>,
+    ) -> Result<Vec<u8>> {
+        Self::check_compression(entry)?;
+        Self::read_raw_from(stored, entry, self.header.is_legacy(), &self.cancellation)
+            .and_then(|raw| self.decode_entry(entry, raw, dictionary, |_| Ok(())))
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))
+    }
+
+    /// Decode a chunked entry's chunk list from the bytes at its data offset
+    /// (see `decode_stored`)
+    #[cfg(feature = "async")]
+    pub(crate) fn decode_chunk_list(
+        &self,
+        entry: &EntryInfo,
+        stored: &[u8],
+    ) -> Result<Vec<ChunkRef>> {
+        Self::read_raw_from(stored, entry, self.header.is_legacy(), &self.cancellation)
+            .and_then(|raw| self.decrypt_raw(entry, raw))
+            .and_then(|list| chunking::parse_chunk_list(&list))
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))
+    }
+
+    /// Read an entry's stored payload from the decrypted archive payload
+    fn read_raw_from_payload(&self, entry: &EntryInfo) -> Result<Vec<u8>> {
+        Ok(self.stored_in_payload(entry)?.to_vec())
+    }
+
+    /// Locate an entry's stored payload inside the decrypted archive payload
+    fn stored_in_payload(&self, entry: &EntryInfo) -> Result<&[u8]> {
+        let payload = self
+            .decrypted_payload
+            .as_ref()
+            .ok_or(EngramError::DecryptionFailed)?;
+
+        // entry.data_offset is absolute (file offset), subtract header size for payload index
+        let out_of_range = || {
+            EngramError::InvalidFormat(format!(
+                "Payload of '{}' lies outside the decrypted archive",
+                entry.path
+            ))
+        };
+        let loca_start = entry
+            .data_offset
+            .checked_sub(HEADER_SIZE as u64)
+            .and_then(|start| usize::try_from(start).ok())
+            .filter(|&start| start <= payload.len())
+            .ok_or_else(out_of_range)?;
+
+        // Read and validate LOCA header from memory
+        let mut cursor = Cursor::new(&payload[loca_start..]);
+        Self::check_local_header(&mut cursor, entry, self.header.is_legacy())?;
+
+        // Calculate data start position (after LOCA header)
+        let data_start = loca_start + cursor.position() as usize;
+        let stored = usize::try_from(entry.compressed_size)
+            .ok()
+            .and_then(|size| payload.get(data_start..data_start.checked_add(size)?))
+            .ok_or_else(out_of_range)?;
+        Ok(stored)
+    }
+
+    /// Read an entry's LOCA header and stored payload
+    ///
+    /// For v1.0, `entry.data_offset` points to the LOCA header, and `reader`
+    /// must be positioned there. For `legacy` (v0.x) archives it points
+    /// straight at the payload.
+    fn read_raw_from<R: Read>(
+        mut reader: R,
+        entry: &EntryInfo,
+        legacy: bool,
+        cancellation: &CancellationToken,
+    ) -> Result<Vec<u8>> {
+        Self::check_local_header(&mut reader, entry, legacy)?;
+
+        // Read file data (reader is now positioned after LOCA header)
+        let mut data = vec![0u8; entry.compressed_size as usize];
+        read_chunked(&mut reader, &mut data, cancellation)?;
+        Ok(data)
+    }
+
+    /// Read the LOCA header in front of an entry's payload and check it
+    /// against the central directory
+    ///
+    /// `legacy` (v0.x) archives have no LOCA headers: nothing is read.
+    fn check_local_header<R: Read>(reader: R, entry: &EntryInfo, legacy: bool) -> Result<()> {
+        if legacy {
+            return Ok(());
+        }
+        let local_header = LocalEntryHeader::read_from(reader)?;
+        Self::validate_local_header(&local_header, entry)
+    }
+
+    /// Decrypt, decompress and verify a stored payload
+    ///
+    /// `on_progress` receives the number of uncompressed bytes produced: once per
+    /// frame for frame-compressed entries, once at the end otherwise.
+    fn decode_entry<F>(
+        &self,
+        entry: &EntryInfo,
+        raw: Vec<u8>,
+        dictionary: Option<&[u8]>,
+        mut on_progress: F,
+    ) -> Result<Vec<u8>>
+    where
+        F: FnMut(u64) -> Result<()>,
+    {
+        let compressed_data = self.decrypt_raw(entry, raw)?;
+
+        if self.is_framed(entry) {
+            let mut output = Vec::with_capacity(preallocation(entry.uncompressed_size));
+            self.stream_frames(
+                entry,
+                &compressed_data,
+                |frame| {
+                    output.extend_from_slice(frame);
+                    Ok(())
+                },
+                on_progress,
+            )?;
+            return Ok(output);
+        }
+
+        let report = |bytes_done| {
+            self.cancellation.check()?;
+            on_progress(bytes_done)
+        };
+        let decompressed = self
+            .decompress_entry(entry, compressed_data, dictionary, report)
+            .map_err(|e| e.with_path(&entry.path))?;
 
-        self.entries = entries;
-        self.entry_list = entry_list;
-        Ok(())
+        Self::verify_content(entry, &decompressed)?;
+        Ok(decompressed)
     }
 
-    /// Read central directory from decrypted payload buffer
-    fn read_central_directory_from_memory(&mut self) -> Result<()> {
-        let payload = self
-            .decrypted_payload
-            .as_ref()
-            .ok_or(EngramError::DecryptionFailed)?;
-
-        // Create cursor at central directory offset (payload-relative, so subtract header size)
-        // The decrypted payload starts at what would be byte 64 in the file
-        let cd_offset = (self.header.central_directory_offset - 64) as usize;
-        let mut cursor = Cursor::new(&payload[cd_offset..]);
+    /// `decode_entry`, writing the content to `out` (frame by frame when framed)
+    fn decode_entry_to<W, F>(
+        &self,
+        entry: &EntryInfo,
+        raw: Vec<u8>,
+        dictionary: Option<&[u8]>,
+        out: &mut W,
+        on_progress: F,
+    ) -> Result<u64>
+    where
+        W: Write,
+        F: FnMut(u64) -> Result<()>,
+    {
+        if !self.is_framed(entry) {
+            let data = self.decode_entry(entry, raw, dictionary, on_progress)?;
+            out.write_all(&data)?;
+            return Ok(data.len() as u64);
+        }
 
-        // Read all entries from memory
-        let mut entries = HashMap::with_capacity(self.header.entry_count as usize);
-        let mut entry_list = Vec::with_capacity(self.header.entry_count as usize);
+        let compressed_data = self.decrypt_raw(entry, raw)?;
+        self.stream_frames(
+            entry,
+            &compressed_data,
+            |frame| Ok(out.write_all(frame)?),
+            on_progress,
+        )?;
+        Ok(entry.uncompressed_size)
+    }
 
-        for _ in 0..self.header.entry_count {
-            let entry = EntryInfo::read_from(&mut cursor)?;
-            entry_list.push(entry.path.clone());
-            entries.insert(entry.path.clone(), entry);
+    /// Decrypt a per-file encrypted payload (directories carry no payload)
+    ///
+    /// The associated data names the central directory path, so a payload
+    /// only opens under the entry it was encrypted for. The LOCA path is no
+    /// substitute: deduplicated entries may carry another entry's, and
+    /// `ENTRY_FLAG_DEDUPLICATED` itself is not authenticated.
+    fn decrypt_raw(&self, entry: &EntryInfo, raw: Vec<u8>) -> Result<Vec<u8>> {
+        if self.encryption_mode == EncryptionMode::PerFile && !entry.is_directory() {
+            let aad = self
+                .header
+                .has_entry_aad()
+                .then(|| entry_aad(&entry.path, entry.uncompressed_size));
+            self.decrypt_file_data(&raw, aad.as_deref())
+        } else {
+            Ok(raw)
         }
-
-        self.entries = entries;
-        self.entry_list = entry_list;
-        Ok(())
     }
 
-    /// Get archive header information
-    pub fn header(&self) -> &FileHeader {
-        &self.header
+    /// Whether an entry was stored with frame compression (>= 50MB uncompressed)
+    fn is_framed(&self, entry: &EntryInfo) -> bool {
+        self.framed_layout(entry) != FrameLayout::None
     }
 
-    /// Get number of entries in archive
-    pub fn entry_count(&self) -> usize {
-        self.entries.len()
+    /// Frame layout of an entry's payload, `FrameLayout::None` if not framed
+    ///
+    /// Frame compression was introduced with v1.0 and indexed with v1.1.
+    fn framed_layout(&self, entry: &EntryInfo) -> FrameLayout {
+        if Self::has_frames(entry) {
+            FrameLayout::for_version(self.header.version_major, self.header.version_minor)
+        } else {
+            FrameLayout::None
+        }
     }
 
-    /// List all file paths in the archive
-    pub fn list_files(&self) -> &[String] {
-        &self.entry_list
+    /// Whether an entry's stored payload can be copied into an archive of the
+    /// current format version as is
+    ///
+    /// Large compressed entries of older archives are stored without frames
+    /// (v0.x) or without a frame index (v1.0) and have to be compressed again.
+    pub(crate) fn has_current_layout(&self, entry: &EntryInfo) -> bool {
+        !Self::has_frames(entry) || self.framed_layout(entry) == FrameLayout::Indexed
     }
 
-    /// Check if a file exists in the archive
-    pub fn contains(&self, path: &str) -> bool {
-        let normalized = normalize_path(path);
-        self.entries.contains_key(&normalized) || self.entries.contains_key(path)
+    /// Whether an entry would be frame-compressed in the current format
+    ///
+    /// LZ4 and Zstd entries of `MIN_FRAME_COMPRESSION_SIZE` and up always
+    /// are; smaller ones only when flagged (`ENTRY_FLAG_FRAMED`).
+    fn has_frames(entry: &EntryInfo) -> bool {
+        let sized = entry.flags & ENTRY_FLAG_FRAMED != 0
+            || should_use_frames(entry.uncompressed_size as usize);
+        sized
+            && matches!(
+                entry.compression,
+                CompressionMethod::Lz4 | CompressionMethod::Zstd
+            )
     }
 
-    /// Get entry information without reading data
-    pub fn get_entry(&self, path: &str) -> Option<&EntryInfo> {
-        self.entries.get(path)
+    /// Decompress frames into `sink`, checking the content as it goes
+    ///
+    /// CRC32 and SHA-256 are updated per frame and compared after the last one.
+    fn stream_frames<S, F>(
+        &self,
+        entry: &EntryInfo,
+        compressed_data: &[u8],
+        mut sink: S,
+        mut on_progress: F,
+    ) -> Result<()>
+    where
+        S: FnMut(&[u8]) -> Result<()>,
+        F: FnMut(u64) -> Result<()>,
+    {
+        let mut check = ContentCheck::new(entry);
+        let mut bytes_done = 0u64;
+        for_each_frame(
+            compressed_data,
+            entry.compression,
+            entry.uncompressed_size,
+            self.framed_layout(entry),
+            |frame| {
+                check.update(frame);
+                sink(frame)?;
+                bytes_done += frame.len() as u64;
+                self.cancellation.check()?;
+                on_progress(bytes_done)
+            },
+        )
+        .map_err(|e| e.with_path(&entry.path))?;
+        check.finish(entry)
     }
 
-    /// Read a file from the archive
-    pub fn read_file(&mut self, path: &str) -> Result<Vec<u8>> {
-        // Normalize path and try both normalized and original
-        let normalized = normalize_path(path);
-        let entry = self
-            .entries
-            .get(&normalized)
-            .or_else(|| self.entries.get(path))
-            .ok_or_else(|| EngramError::FileNotFound(path.to_string()))?
-            .clone();
+    /// Check uncompressed content against the entry's CRC32 and SHA-256
+    fn verify_content(entry: &EntryInfo, data: &[u8]) -> Result<()> {
+        let mut check = ContentCheck::new(entry);
+        check.update(data);
+        check.finish(entry)
+    }
 
-        // Read data (from file or from decrypted payload)
-        // For v1.0: entry.data_offset points to LOCA header, not file data
-        let raw_data = match self.encryption_mode {
-            EncryptionMode::Archive => {
-                // Read from decrypted payload buffer
-                let payload = self
-                    .decrypted_payload
-                    .as_ref()
-                    .ok_or(EngramError::DecryptionFailed)?;
+    /// Refuse entries whose compression method this version cannot decode
+    fn check_compression(entry: &EntryInfo) -> Result<()> {
+        match entry.compression {
+            CompressionMethod::Unknown(value) => Err(EngramError::InvalidCompression(value)),
+            _ => Ok(()),
+        }
+    }
 
-                // entry.data_offset is absolute (file offset), subtract header size for payload index
-                let loca_start = (entry.data_offset - 64) as usize;
+    /// Decompress a stored payload that is not frame-compressed, reporting
+    /// progress through `report`
+    fn decompress_entry(
+        &self,
+        entry: &EntryInfo,
+        compressed_data: Vec<u8>,
+        dictionary: Option<&[u8]>,
+        mut report: impl FnMut(u64) -> Result<()>,
+    ) -> Result<Vec<u8>> {
+        let decompressed = match entry.compression {
+            CompressionMethod::None => compressed_data,
+            CompressionMethod::Lz4 => Self::decompress_lz4(&compressed_data, entry)?,
+            CompressionMethod::Zstd => match dictionary {
+                // Frame-sized entries are never written against the dictionary, and
+                // the bulk decoder allocates the whole size up front
+                Some(_) if should_use_frames(entry.uncompressed_size as usize) => {
+                    return Err(EngramError::InvalidFormat(format!(
+                        "Dictionary-compressed entry of {} bytes exceeds the frame threshold",
+                        entry.uncompressed_size
+                    )));
+                }
+                Some(dictionary) => decompress_with_dictionary(
+                    &compressed_data,
+                    dictionary,
+                    entry.uncompressed_size as usize,
+                )?,
+                None => Self::decompress_zstd(&compressed_data, entry)?,
+            },
+            CompressionMethod::Deflate => Self::decompress_deflate(&compressed_data, entry)?,
+            CompressionMethod::Unknown(value) => {
+                return Err(EngramError::InvalidCompression(value));
+            }
+        };
+        report(decompressed.len() as u64)?;
+        Ok(decompressed)
+    }
 
-                // Read and validate LOCA header from memory
-                let mut cursor = Cursor::new(&payload[loca_start..]);
-                let local_header = LocalEntryHeader::read_from(&mut cursor)?;
+    /// Separate handle to the archive file for positioned reads, opened on first use
+    fn positioned_file(&self) -> Result<&File> {
+        if let Some(file) = self.positioned_file.get() {
+            return Ok(file);
+        }
+        let path = self.source_path.as_ref().ok_or_else(|| {
+            EngramError::Other("read_file_at requires an archive opened from a path".to_string())
+        })?;
+        // Another thread may win the race; its handle is equally good
+        let _ = self.positioned_file.set(File::open(path)?);
+        Ok(self
+            .positioned_file
+            .get()
+            .expect("positioned file was just set"))
+    }
 
-                // Validate LOCA header matches central directory
-                self.validate_local_header(&local_header, &entry)?;
+    /// Error for an entry flagged for the Zstd dictionary in an archive without one
+    fn missing_dictionary() -> EngramError {
+        EngramError::InvalidFormat(format!(
+            "Entry uses a Zstd dictionary but {} is missing",
+            ZSTD_DICTIONARY_PATH
+        ))
+    }
 
-                // Calculate data start position (after LOCA header)
-                let data_start = loca_start + local_header.header_size();
-                let data_end = data_start + entry.compressed_size as usize;
-                payload[data_start..data_end].to_vec()
-            }
-            _ => {
-                // Read from file (normal or per-file encrypted)
-                // Seek to LOCA header
-                self.file.seek(SeekFrom::Start(entry.data_offset))?;
+    /// Load (once) the shared Zstd dictionary stored at `ZSTD_DICTIONARY_PATH`
+    fn load_zstd_dictionary(&mut self) -> Result<Arc<Vec<u8>>> {
+        if let Some(dictionary) = self.zstd_dictionary.get() {
+            return Ok(Arc::clone(dictionary));
+        }
+        if self.find_exact(ZSTD_DICTIONARY_PATH)?.is_none() {
+            return Err(Self::missing_dictionary());
+        }
 
-                // Read and validate LOCA header
-                let local_header = LocalEntryHeader::read_from(&mut self.file)?;
+        // Internal read: don't report it to the progress callback
+        let progress = std::mem::take(&mut self.progress);
+        let result = self.read_file(ZSTD_DICTIONARY_PATH);
+        self.progress = progress;
 
-                // Validate LOCA header matches central directory
-                self.validate_local_header(&local_header, &entry)?;
+        let dictionary = Arc::new(result?);
+        let _ = self.zstd_dictionary.set(Arc::clone(&dictionary));
+        Ok(dictionary)
+    }
 
-                // Read file data (file cursor is now positioned after LOCA header)
-                let mut data = vec![0u8; entry.compressed_size as usize];
-                self.file.read_exact(&mut data)?;
-                data
-            }
+    /// Load (once) the custom MIME types stored at `MIME_TABLE_PATH`
+    fn load_mime_table(&mut self) -> Result<Arc<Vec<String>>> {
+        if let Some(table) = self.mime_table.get() {
+            return Ok(Arc::clone(table));
+        }
+        let table = if self.find_exact(MIME_TABLE_PATH)?.is_some() {
+            // Internal read: don't report it to the progress callback
+            let progress = std::mem::take(&mut self.progress);
+            let result = self.read_file(MIME_TABLE_PATH);
+            self.progress = progress;
+            mime::parse_table(&result?)?
+        } else {
+            Vec::new()
         };
 
-        // Decrypt if per-file encryption
-        let compressed_data = if self.encryption_mode == EncryptionMode::PerFile {
-            self.decrypt_file_data(&raw_data)?
+        let table = Arc::new(table);
+        let _ = self.mime_table.set(Arc::clone(&table));
+        Ok(table)
+    }
+
+    /// Load (once) the custom entry metadata stored at `ENTRY_METADATA_PATH`
+    fn load_entry_metadata_table(
+        &mut self,
+    ) -> Result<Arc<HashMap<String, BTreeMap<String, String>>>> {
+        if let Some(table) = self.entry_metadata_table.get() {
+            return Ok(Arc::clone(table));
+        }
+        let table = if self.find_exact(ENTRY_METADATA_PATH)?.is_some() {
+            // Internal read: don't report it to the progress callback
+            let progress = std::mem::take(&mut self.progress);
+            let result = self.read_file(ENTRY_METADATA_PATH);
+            self.progress = progress;
+            entry_metadata::parse_table(&result?)?
         } else {
-            raw_data
+            HashMap::new()
         };
 
-        // Decompress if needed
-        // Check if file used frame-based compression (>= 50MB uncompressed)
-        let decompressed = if should_use_frames(entry.uncompressed_size as usize)
-            && entry.compression != CompressionMethod::None
-        {
-            // Use frame decompression for large files
-            decompress_frames(&compressed_data, entry.compression, entry.uncompressed_size)?
+        let table = Arc::new(table);
+        let _ = self.entry_metadata_table.set(Arc::clone(&table));
+        Ok(table)
+    }
+
+    /// Load (once) the stored-payload checksums of `COMPRESSED_CRC_PATH`
+    ///
+    /// Empty when the archive has no such table.
+    fn load_compressed_crc_table(&mut self) -> Result<Arc<HashMap<u64, u32>>> {
+        if let Some(table) = self.compressed_crc_table.get() {
+            return Ok(Arc::clone(table));
+        }
+        let table = if self.find_exact(COMPRESSED_CRC_PATH)?.is_some() {
+            // Internal read: don't report it to the progress callback
+            let progress = std::mem::take(&mut self.progress);
+            let result = self.read_file(COMPRESSED_CRC_PATH);
+            self.progress = progress;
+            stored_crc::parse_table(&result?)?
         } else {
-            // Regular decompression for files < 50MB
-            match entry.compression {
-                CompressionMethod::None => compressed_data,
-                CompressionMethod::Lz4 => Self::decompress_lz4(&compressed_data, &entry)?,
-                CompressionMethod::Zstd => Self::decompress_zstd(&compressed_data)?,
-            }
+            HashMap::new()
         };
 
-        // Verify CRC
-        let computed_crc = crc32fast::hash(&decompressed);
-        if computed_crc != entry.crc32 {
-            return Err(EngramError::CrcMismatch {
-                expected: entry.crc32,
-                actual: computed_crc,
-            });
+        let table = Arc::new(table);
+        let _ = self.compressed_crc_table.set(Arc::clone(&table));
+        Ok(table)
+    }
+
+    /// `load_zstd_dictionary` for `read_file_at`
+    fn load_zstd_dictionary_at(&self) -> Result<Arc<Vec<u8>>> {
+        if let Some(dictionary) = self.zstd_dictionary.get() {
+            return Ok(Arc::clone(dictionary));
+        }
+        if self.find_exact(ZSTD_DICTIONARY_PATH)?.is_none() {
+            return Err(Self::missing_dictionary());
         }
 
-        Ok(decompressed)
+        let dictionary = Arc::new(self.read_file_at(ZSTD_DICTIONARY_PATH)?);
+        let _ = self.zstd_dictionary.set(Arc::clone(&dictionary));
+        Ok(dictionary)
     }
 
     /// Decompress LZ4 data
-    fn decompress_lz4(data: &[u8], _entry: &EntryInfo) -> Result<Vec<u8>> {
-        // lz4_flex::compress_prepend_size prepends the size, so we use decompress_size_prepended
-        lz4_flex::decompress_size_prepended(data).map_err(|e| {
-            EngramError::DecompressionFailed(format!("LZ4 decompression failed: {}", e))
-        })
+    ///
+    /// The output buffer is sized from the central directory, which the size
+    /// `lz4_flex::compress_prepend_size` put in front of the block has to
+    /// agree with; neither can exceed what the block could expand to.
+    fn decompress_lz4(data: &[u8], entry: &EntryInfo) -> Result<Vec<u8>> {
+        let prepended = data
+            .get(..4)
+            .map(|size| u32::from_le_bytes(size.try_into().unwrap()) as u64);
+        if prepended != Some(entry.uncompressed_size)
+            || entry.uncompressed_size > data.len() as u64 * MAX_LZ4_RATIO
+        {
+            return Err(EngramError::decompression_failed(format!(
+                "LZ4 block size {:?} does not match the entry size {}",
+                prepended, entry.uncompressed_size
+            )));
+        }
+
+        let mut decompressed = vec![0u8; entry.uncompressed_size as usize];
+        let len = lz4_flex::block::decompress_into(&data[4..], &mut decompressed).map_err(|e| {
+            EngramError::decompression_failed(format!("LZ4 decompression failed: {}", e))
+        })?;
+        if len != decompressed.len() {
+            return Err(EngramError::decompression_failed(format!(
+                "LZ4 block decompressed to {} bytes, expected {}",
+                len, entry.uncompressed_size
+            )));
+        }
+        Ok(decompressed)
     }
 
-    /// Decompress Zstd data
-    fn decompress_zstd(data: &[u8]) -> Result<Vec<u8>> {
-        zstd::decode_all(data).map_err(|e| {
-            EngramError::DecompressionFailed(format!("Zstd decompression failed: {}", e))
-        })
+    /// Decompress Zstd data, reading at most one byte past the entry's size
+    /// so a stream that inflates further fails instead of exhausting memory
+    fn decompress_zstd(data: &[u8], entry: &EntryInfo) -> Result<Vec<u8>> {
+        let zstd_error = |e: std::io::Error| {
+            EngramError::decompression_failed(format!("Zstd decompression failed: {}", e))
+        };
+        let mut decompressed = Vec::with_capacity(preallocation(entry.uncompressed_size));
+        zstd::stream::read::Decoder::new(data)
+            .map_err(zstd_error)?
+            .take(entry.uncompressed_size.saturating_add(1))
+            .read_to_end(&mut decompressed)
+            .map_err(zstd_error)?;
+        if decompressed.len() as u64 > entry.uncompressed_size {
+            return Err(EngramError::decompression_failed(format!(
+                "Zstd data decompresses past the entry size {}",
+                entry.uncompressed_size
+            )));
+        }
+        Ok(decompressed)
+    }
+
+    /// Inflate a raw Deflate payload, reading at most one byte past the
+    /// entry's size so an oversized stream fails the CRC check instead of
+    /// exhausting memory
+    fn decompress_deflate(data: &[u8], entry: &EntryInfo) -> Result<Vec<u8>> {
+        let mut decompressed = Vec::new();
+        flate2::read::DeflateDecoder::new(data)
+            .take(entry.uncompressed_size.saturating_add(1))
+            .read_to_end(&mut decompressed)
+            .map_err(deflate_error)?;
+        Ok(decompressed)
     }
 
     /// Read the Engram format manifest
     ///
-    /// Returns the archive-level metadata from `manifest.json`.
+    /// Returns the archive-level metadata from `manifest.json`. The manifest
+    /// is decompressed like any other entry, whatever method it was stored with.
     pub fn read_manifest(&mut self) -> Result<Option<serde_json::Value>> {
-        if !self.contains("manifest.json") {
+        self.read_manifest_as()
+    }
+
+    /// Read the Engram format manifest into a typed value, such as `Manifest`
+    ///
+    /// Like `read_manifest`; content that does not match `T` fails with
+    /// `EngramError::InvalidManifest`.
+    pub fn read_manifest_as<T: DeserializeOwned>(&mut self) -> Result<Option<T>> {
+        if !self.contains(MANIFEST_PATH) {
             return Ok(None);
         }
 
-        let data = self.read_file("manifest.json")?;
-        let manifest: serde_json::Value = serde_json::from_slice(&data)
+        let data = self.read_file(MANIFEST_PATH)?;
+        let manifest = serde_json::from_slice(&data)
             .map_err(|e| EngramError::InvalidManifest(format!("Invalid manifest.json: {}", e)))?;
 
         Ok(Some(manifest))
@@ -315,6 +3093,28 @@ impl ArchiveReader {
     /// # }
     /// ```
     pub fn read_app_manifest(&mut self, app_name: &str) -> Result<serde_json::Value> {
+        self.read_app_manifest_as(app_name)
+    }
+
+    /// Read an application-specific manifest into the application's own type
+    ///
+    /// Like `read_app_manifest`, but deserializes `<app_name>.json` straight
+    /// into `T`. Content that does not match `T` fails with
+    /// `EngramError::InvalidManifest` naming the file.
+    ///
+    /// ```no_run
+    /// # use engram_rs::ArchiveReader;
+    /// #[derive(serde::Deserialize)]
+    /// struct BackupInfo {
+    ///     services: Vec<String>,
+    ///     backup_type: String,
+    /// }
+    ///
+    /// let mut archive = ArchiveReader::open_and_init("backup.eng")?;
+    /// let info: BackupInfo = archive.read_app_manifest_as("crisis-frame")?;
+    /// # Ok::<(), engram_rs::EngramError>(())
+    /// ```
+    pub fn read_app_manifest_as<T: DeserializeOwned>(&mut self, app_name: &str) -> Result<T> {
         let path = format!("{}.json", app_name);
         let data = self.read_file(&path)?;
         serde_json::from_slice(&data)
@@ -326,9 +3126,277 @@ impl ArchiveReader {
         self.contains(&format!("{}.json", app_name))
     }
 
+    /// Extract every entry in the archive under `dest`
+    ///
+    /// Parent directories are created as needed, and explicit directory entries
+    /// are created even when empty. Entries with unsafe paths (absolute or
+    /// containing `..`) are rejected with `EngramError::PathError`.
+    /// On Unix, the permission bits of a stored non-zero `mode` are applied to
+    /// the extracted file (setuid, setgid and sticky bits only with
+    /// `with_special_mode_bits`); entries without one get 0o755 when flagged
+    /// executable and 0o644 otherwise. Elsewhere permissions are left alone.
+    ///
+    /// Returns the number of entries extracted.
+    pub fn extract_all<P: AsRef<Path>>(&mut self, dest: P) -> Result<usize> {
+        let dest = dest.as_ref();
+        let paths = Arc::clone(&self.load_directory()?.entry_list);
+
+        for path in paths.iter() {
+            self.cancellation.check()?;
+            validate_path(path)?;
+            let target = dest.join(path);
+            let entry = self.lookup_entry(path)?;
+            if entry.is_directory() {
+                std::fs::create_dir_all(&target)?;
+                continue;
+            }
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+
+            let data = self.read_file(path)?;
+            std::fs::write(&target, data)?;
+
+            let mode = match entry.mode {
+                0 if entry.is_executable() => 0o755,
+                0 => 0o644,
+                mode => mode,
+            };
+            apply_mode(&target, mode, self.special_mode_bits)?;
+        }
+
+        Ok(paths.len())
+    }
+
+    /// Check the integrity of the whole archive
+    ///
+    /// Verifies the header CRC, the End Record against the header, and reads
+    /// every entry: its LOCA header must match the central directory and its
+    /// decompressed CRC (and SHA-256, when stored) must match. Unlike
+    /// `read_file`, failures are collected instead of stopping at the first
+    /// one. Archive-level failures are reported under the names
+    /// `VERIFY_HEADER` and `VERIFY_END_RECORD`.
+    ///
+    /// When `manifest.json` lists files, each entry's content is also checked
+    /// against the listed SHA-256, and entries it does not list (other than
+    /// directories and reserved paths) are reported in `missing_from_manifest`.
+    /// Entries are streamed like `read_file_to` and discarded, so memory stays
+    /// bounded by the largest non-framed entry.
+    ///
+    /// Progress events and cancellation work as for `read_file`. Only
+    /// cancellation and a panicking progress callback abort the check.
+    pub fn verify_all(&mut self) -> Result<VerifyReport> {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        let started = std::time::Instant::now();
+        let mut report = VerifyReport::default();
+        self.verify_structure(&mut report);
+
+        let inventory = self.manifest_inventory();
+        let paths = Arc::clone(&self.load_directory()?.entry_list);
+        for path in paths.iter() {
+            let listed = inventory.as_ref().map(|files| files.get(path.as_str()));
+            let mut sink = VerifySink {
+                bytes: 0,
+                sha256: listed.flatten().map(|_| Sha256::new()),
+            };
+            match self.read_file_to(path, &mut sink) {
+                Ok(_) => {}
+                Err(err @ (EngramError::Cancelled | EngramError::CallbackPanicked)) => {
+                    return Err(err)
+                }
+                Err(err) => {
+                    report.failed.push((path.clone(), err));
+                    continue;
+                }
+            }
+            report.bytes_verified += sink.bytes;
+
+            match (listed, sink.sha256) {
+                (Some(Some(expected)), Some(sha256)) => {
+                    let actual = hex::encode(sha256.finalize());
+                    if !expected.eq_ignore_ascii_case(&actual) {
+                        report.failed.push((
+                            path.clone(),
+                            EngramError::HashMismatch {
+                                path: path.clone(),
+                                expected: expected.clone(),
+                                actual,
+                            },
+                        ));
+                        continue;
+                    }
+                }
+                (Some(None), _)
+                    if !is_reserved_path(path)
+                        && !self.get_entry(path).is_some_and(EntryInfo::is_directory) =>
+                {
+                    report.missing_from_manifest.push(path.clone());
+                    continue;
+                }
+                _ => {}
+            }
+            report.ok.push(path.clone());
+        }
+
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        {
+            report.elapsed = started.elapsed();
+        }
+        Ok(report)
+    }
+
+    /// Check the archive for damage without decompressing anything
+    ///
+    /// Like `verify_all`, except that entries with a checksum in
+    /// `COMPRESSED_CRC_PATH` (see `ArchiveWriter::with_compressed_checksums`)
+    /// are only read as stored and compared with it: nothing is decompressed
+    /// or decrypted, and manifest hashes are not checked. Every other entry,
+    /// which is all of them in archives written without the table, gets the
+    /// full check of `verify_all`. `bytes_verified` counts stored bytes for
+    /// the entries checked this way.
+    ///
+    /// Cancellation works as for `verify_all`; progress is only reported for
+    /// entries that get the full check.
+    pub fn verify_fast(&mut self) -> Result<VerifyReport> {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        let started = std::time::Instant::now();
+        let mut report = VerifyReport::default();
+        self.verify_structure(&mut report);
+
+        // A damaged table is reported once; its entries get the full check
+        let table = match self.load_compressed_crc_table() {
+            Ok(table) => table,
+            Err(err) => {
+                report.failed.push((COMPRESSED_CRC_PATH.to_string(), err));
+                Arc::new(HashMap::new())
+            }
+        };
+
+        // Payloads shared by deduplicated entries are read once
+        let mut intact = HashSet::new();
+        let paths = Arc::clone(&self.load_directory()?.entry_list);
+        for path in paths.iter() {
+            if report.failed.iter().any(|(failed, _)| failed == path) {
+                continue;
+            }
+            let entry = self.lookup_entry(path)?;
+            let expected = table
+                .get(&entry.data_offset)
+                .filter(|_| !entry.is_directory());
+            let result = match expected {
+                Some(_) if intact.contains(&entry.data_offset) => Ok(0),
+                Some(&expected) => self.check_stored_crc(&entry, expected),
+                None => {
+                    let mut sink = VerifySink {
+                        bytes: 0,
+                        sha256: None,
+                    };
+                    self.read_file_to(path, &mut sink).map(|_| sink.bytes)
+                }
+            };
+            match result {
+                Ok(bytes) => {
+                    if expected.is_some() {
+                        intact.insert(entry.data_offset);
+                    }
+                    report.bytes_verified += bytes;
+                    report.ok.push(path.clone());
+                }
+                Err(err @ (EngramError::Cancelled | EngramError::CallbackPanicked)) => {
+                    return Err(err)
+                }
+                Err(err) => report.failed.push((path.clone(), err)),
+            }
+        }
+
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        {
+            report.elapsed = started.elapsed();
+        }
+        Ok(report)
+    }
+
+    /// Compare an entry's stored payload with its CRC32 from `COMPRESSED_CRC_PATH`
+    ///
+    /// Returns the number of stored bytes read.
+    fn check_stored_crc(&mut self, entry: &EntryInfo, expected: u32) -> Result<u64> {
+        self.cancellation.check()?;
+        let stored = self
+            .read_stored(entry)
+            .map_err(|e| e.in_entry(&entry.path, entry.data_offset))?;
+        let actual = crc32fast::hash(&stored);
+        if actual != expected {
+            return Err(EngramError::CrcMismatch {
+                path: entry.path.clone(),
+                expected,
+                actual,
+            });
+        }
+        Ok(stored.len() as u64)
+    }
+
+    /// Check the header CRC and the End Record, adding failures to `report`
+    /// under `VERIFY_HEADER` and `VERIFY_END_RECORD`
+    fn verify_structure(&mut self, report: &mut VerifyReport) {
+        // Archives written before the header CRC was filled in store zero
+        let header_crc = self.header.compute_crc();
+        if self.header.header_crc != 0 && self.header.header_crc != header_crc {
+            report.failed.push((
+                VERIFY_HEADER.to_string(),
+                EngramError::CrcMismatch {
+                    path: VERIFY_HEADER.to_string(),
+                    expected: self.header.header_crc,
+                    actual: header_crc,
+                },
+            ));
+        }
+
+        // The ENDR follows the encrypted payload in plaintext, so this holds for
+        // every mode; v0.x archives end with the central directory instead
+        if !self.header.is_legacy() {
+            if let Err(err) = self.validate_end_record() {
+                report.failed.push((VERIFY_END_RECORD.to_string(), err));
+            }
+        }
+    }
+
+    /// Path -> SHA-256 (hex) from the `files` list of `manifest.json`
+    ///
+    /// `None` when there is no readable manifest or it lists no files; a
+    /// damaged manifest is reported by `verify_all` when it reads the entry.
+    fn manifest_inventory(&mut self) -> Option<HashMap<String, String>> {
+        let progress = std::mem::take(&mut self.progress);
+        let manifest = self.read_manifest();
+        self.progress = progress;
+
+        let manifest = manifest.ok()??;
+        let inventory: HashMap<String, String> = manifest
+            .get("files")?
+            .as_array()?
+            .iter()
+            .filter_map(|file| {
+                let path = file.get("path")?.as_str()?;
+                let sha256 = file.get("sha256")?.as_str()?;
+                Some((path.to_string(), sha256.to_string()))
+            })
+            .collect();
+        (!inventory.is_empty()).then_some(inventory)
+    }
+
+    /// List application file paths, leaving out entries the format reserves
+    ///
+    /// Skips `manifest.json` and everything under `.engram/` (see
+    /// `is_reserved_path`).
+    pub fn list_user_files(&self) -> Vec<&String> {
+        self.list_files()
+            .iter()
+            .filter(|path| !is_reserved_path(path))
+            .collect()
+    }
+
     /// Extract all entries with a given prefix
     pub fn list_prefix(&self, prefix: &str) -> Vec<&String> {
-        self.entry_list
+        self.list_files()
             .iter()
             .filter(|path| path.starts_with(prefix))
             .collect()
@@ -337,7 +3405,7 @@ impl ArchiveReader {
     /// Read and validate End Record (ENDR) from archive end
     fn validate_end_record(&mut self) -> Result<()> {
         // Seek to last 64 bytes (ENDR location)
-        let file_size = self.file.metadata()?.len();
+        let file_size = self.source_len()?;
         if file_size < (END_RECORD_SIZE as u64) {
             return Err(EngramError::InvalidFormat(
                 "Archive too small to contain ENDR record".to_string(),
@@ -345,10 +3413,10 @@ impl ArchiveReader {
         }
 
         let endr_offset = file_size - (END_RECORD_SIZE as u64);
-        self.file.seek(SeekFrom::Start(endr_offset))?;
+        self.file.get().seek(SeekFrom::Start(endr_offset))?;
 
         // Read End Record
-        let end_record = EndRecord::read_from(&mut self.file)?;
+        let end_record = EndRecord::read_from(self.file.get())?;
 
         // Validate against header
         end_record.validate_against_header(
@@ -362,10 +3430,137 @@ impl ArchiveReader {
         Ok(())
     }
 
+    /// Check the trailer written with `ArchiveWriter::with_endr_signature`
+    ///
+    /// Returns `true` if the End Record and central directory were signed by
+    /// one of `trusted` and are unchanged since, and `false` if the signer is
+    /// not trusted or anything the signature covers was modified. Archives
+    /// without a signature fail with `EngramError::SignatureNotFound`, and a
+    /// malformed signature block with `InvalidSignature`. Works before
+    /// `initialize` and without a decryption key.
+    pub fn verify_endr_signature(&mut self, trusted: &[VerifyingKey]) -> Result<bool> {
+        if self.header.is_legacy() {
+            return Err(EngramError::SignatureNotFound);
+        }
+        let end_record = EndRecord::read_from_end(self.file.get())?;
+        if end_record.signature_size == 0 {
+            return Err(EngramError::SignatureNotFound);
+        }
+        if end_record.signature_size as usize != SIGNATURE_BLOCK_SIZE {
+            return Err(EngramError::InvalidSignature);
+        }
+        end_record.validate_against_header(
+            self.header.version_major,
+            self.header.version_minor,
+            self.header.central_directory_offset,
+            self.header.central_directory_size,
+            self.header.entry_count,
+        )?;
+
+        // [central directory][signature block][recipients block][ENDR]
+        let len = self.source_len()?;
+        let directory_end = end_record
+            .central_directory_offset
+            .saturating_add(end_record.central_directory_size);
+        let start = len
+            .checked_sub(
+                (SIGNATURE_BLOCK_SIZE + END_RECORD_SIZE) as u64 + end_record.recipients_size as u64,
+            )
+            .filter(|&start| start >= directory_end)
+            .ok_or(EngramError::InvalidSignature)?;
+        let file = self.file.get();
+        let mut block = [0u8; SIGNATURE_BLOCK_SIZE];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(&mut block)?;
+        let block = SignatureBlock::read_from(&block)?;
+
+        let mut end_bytes = [0u8; END_RECORD_SIZE];
+        file.seek(SeekFrom::Start(len - END_RECORD_SIZE as u64))?;
+        file.read_exact(&mut end_bytes)?;
+
+        let mut hash = Sha256::new();
+        file.seek(SeekFrom::Start(end_record.central_directory_offset))?;
+        std::io::copy(&mut file.take(end_record.central_directory_size), &mut hash)?;
+        let digest: [u8; 32] = hash.finalize().into();
+
+        Ok(block.verify(trusted, &end_bytes, &digest))
+    }
+
+    /// Ids of the recipients the encryption key is wrapped for
+    ///
+    /// Empty for archives without a recipients block.
+    pub fn recipients(&mut self) -> Result<Vec<String>> {
+        Ok(self
+            .read_recipients()?
+            .into_iter()
+            .map(|recipient| recipient.id)
+            .collect())
+    }
+
+    /// Parse the recipients block stored before the ENDR
+    pub(crate) fn read_recipients(&mut self) -> Result<Vec<WrappedKey>> {
+        let size = self.recipients_size()?;
+        if size == 0 {
+            return Ok(Vec::new());
+        }
+        let end = self.source_len()?;
+        let start = end
+            .checked_sub(size + END_RECORD_SIZE as u64)
+            .filter(|&start| start >= 64)
+            .ok_or_else(|| {
+                EngramError::InvalidFormat("Recipients block out of range".to_string())
+            })?;
+        let file = self.file.get();
+        file.seek(SeekFrom::Start(start))?;
+        recipients::read_block(file.take(size))
+    }
+
+    /// Encryption key wrapped for recipient `id`
+    pub(crate) fn unwrap_recipient_key(&mut self, id: &str, key: &[u8; 32]) -> Result<SecretKey> {
+        self.read_recipients()?
+            .iter()
+            .find(|recipient| recipient.id == id)
+            .ok_or_else(|| EngramError::RecipientNotFound(id.to_string()))?
+            .unwrap(key)
+    }
+
+    /// Encryption key wrapped for whichever recipient `key` belongs to
+    fn unwrap_any_recipient_key(&mut self, key: &[u8; 32]) -> Result<SecretKey> {
+        self.read_recipients()?
+            .iter()
+            .find_map(|recipient| recipient.unwrap(key).ok())
+            .ok_or(EngramError::DecryptionFailed)
+    }
+
+    /// Encryption key wrapped for whichever password recipient `password` opens
+    fn unwrap_password_key(&mut self, password: &str) -> Result<SecretKey> {
+        self.read_recipients()?
+            .iter()
+            .find_map(|recipient| recipient.unwrap_password(password).ok())
+            .ok_or(EngramError::DecryptionFailed)
+    }
+
+    /// Length of the recipients block (0 unless `HEADER_FLAG_RECIPIENTS` is set)
+    fn recipients_size(&mut self) -> Result<u64> {
+        if !self.header.has_recipients() {
+            return Ok(0);
+        }
+        Ok(EndRecord::read_from_end(self.file.get())?.recipients_size as u64)
+    }
+
+    /// Total length of the underlying byte source
+    fn source_len(&mut self) -> Result<u64> {
+        Ok(self.file.get().seek(SeekFrom::End(0))?)
+    }
+
     /// Validate Local Entry Header against Central Directory entry
-    fn validate_local_header(&self, local: &LocalEntryHeader, central: &EntryInfo) -> Result<()> {
+    fn validate_local_header(local: &LocalEntryHeader, central: &EntryInfo) -> Result<()> {
+        // Deduplicated entries share the LOCA header of the first copy, so the
+        // per-entry fields (path, mode) legitimately differ
+        let shared = central.is_deduplicated();
+
         // Verify path matches
-        if local.path != central.path {
+        if !shared && local.path != central.path {
             return Err(EngramError::InvalidFormat(format!(
                 "LOCA header path mismatch: expected '{}', found '{}'",
                 central.path, local.path
@@ -395,6 +3590,24 @@ impl ArchiveReader {
             )));
         }
 
+        // Verify mode matches
+        if !shared && local.mode != central.stored_mode() {
+            return Err(EngramError::InvalidFormat(format!(
+                "LOCA header mode mismatch for '{}': expected {:o}, found {:o}",
+                central.path,
+                central.stored_mode(),
+                local.mode
+            )));
+        }
+
+        // Verify the executable flag matches
+        if !shared && (local.flags ^ central.flags) & ENTRY_FLAG_EXECUTABLE != 0 {
+            return Err(EngramError::InvalidFormat(format!(
+                "LOCA header executable flag mismatch for '{}'",
+                central.path
+            )));
+        }
+
         // Verify compression method matches
         if local.compression != central.compression {
             return Err(EngramError::InvalidFormat(format!(
@@ -407,54 +3620,68 @@ impl ArchiveReader {
     }
 
     /// Decrypt entire archive payload (archive-level encryption)
+    ///
+    /// The payload is decrypted in place, so no intermediate copy of the
+    /// plaintext is left behind, and the working copy of the key is wiped on
+    /// return.
     fn decrypt_archive_payload(&mut self) -> Result<()> {
         let key = self
             .decryption_key
-            .as_ref()
+            .clone()
             .ok_or(EngramError::MissingDecryptionKey)?;
 
-        // Calculate encrypted payload size (file - header - ENDR)
-        let file_size = self.file.metadata()?.len();
-        let encrypted_size = file_size - 64 - (END_RECORD_SIZE as u64);
+        // Calculate encrypted payload size (file - header - recipients - ENDR)
+        let file_size = self.source_len()?;
+        let trailer_size = if self.header.is_legacy() {
+            0
+        } else {
+            self.recipients_size()? + END_RECORD_SIZE as u64
+        };
+        let encrypted_size = file_size
+            .checked_sub(64 + trailer_size)
+            .filter(|&size| size >= 12)
+            .ok_or(EngramError::DecryptionFailed)?;
 
         // Read encrypted payload: [nonce 12 bytes][ciphertext||tag]
-        self.file.seek(SeekFrom::Start(64))?; // After header
+        self.file.get().seek(SeekFrom::Start(64))?; // After header
 
         // Read nonce
         let mut nonce_bytes = [0u8; 12];
-        self.file.read_exact(&mut nonce_bytes)?;
+        self.file.get().read_exact(&mut nonce_bytes)?;
         #[allow(deprecated)]
         let nonce = Nonce::from_slice(&nonce_bytes);
 
         // Read ciphertext + tag (excluding ENDR at end)
         let ciphertext_size = encrypted_size - 12; // Subtract nonce size
-        let mut ciphertext_with_tag = vec![0u8; ciphertext_size as usize];
-        self.file.read_exact(&mut ciphertext_with_tag)?;
+        let mut payload = vec![0u8; ciphertext_size as usize];
+        read_chunked(self.file.get(), &mut payload, &self.cancellation)?;
 
-        // Decrypt
-        let cipher = Aes256Gcm::new(key.into());
-        let plaintext = cipher
-            .decrypt(nonce, ciphertext_with_tag.as_ref())
+        // Decrypt in place; the tag is checked before anything is decrypted
+        let cipher = Aes256Gcm::new((&*key).into());
+        cipher
+            .decrypt_in_place(nonce, b"", &mut payload)
             .map_err(|_| EngramError::DecryptionFailed)?;
 
-        self.decrypted_payload = Some(plaintext);
+        self.decrypted_payload = Some(Arc::new(payload));
         Ok(())
     }
 
     /// Decrypt file data for per-file encryption mode
     /// Input: [nonce 12 bytes][ciphertext||tag]
     /// Output: plaintext (compressed data)
-    fn decrypt_file_data(&self, payload: &[u8]) -> Result<Vec<u8>> {
+    ///
+    /// `aad` is the associated data the payload was encrypted with, if any.
+    fn decrypt_file_data(&self, payload: &[u8], aad: Option<&[u8]>) -> Result<Vec<u8>> {
+        let key = self
+            .decryption_key
+            .as_deref()
+            .ok_or(EngramError::MissingDecryptionKey)?;
+
         if payload.len() < 28 {
             // 12 nonce + 16 tag minimum
             return Err(EngramError::DecryptionFailed);
         }
 
-        let key = self
-            .decryption_key
-            .as_ref()
-            .ok_or(EngramError::MissingDecryptionKey)?;
-
         // Extract nonce (first 12 bytes)
         #[allow(deprecated)]
         let nonce = Nonce::from_slice(&payload[0..12]);
@@ -464,8 +3691,178 @@ impl ArchiveReader {
 
         // Decrypt
         let cipher = Aes256Gcm::new(key.into());
+        let payload = Payload {
+            msg: ciphertext_with_tag,
+            aad: aad.unwrap_or_default(),
+        };
         cipher
-            .decrypt(nonce, ciphertext_with_tag)
+            .decrypt(nonce, payload)
             .map_err(|_| EngramError::DecryptionFailed)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::archive::ArchiveWriter;
+
+    #[test]
+    fn test_from_reader_in_memory() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        {
+            let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+            writer.add_file("hello.txt", b"Hello, browser!").unwrap();
+            writer
+                .add_file("large.json", "{\"key\": \"value\"}".repeat(500).as_bytes())
+                .unwrap();
+            writer.finalize().unwrap();
+        }
+
+        // Only a byte slice is available in the browser
+        let bytes: &[u8] = &std::fs::read(temp_file.path()).unwrap();
+
+        let mut reader = ArchiveReader::from_reader(Cursor::new(bytes.to_vec())).unwrap();
+        reader.initialize().unwrap();
+
+        assert_eq!(reader.entry_count(), 2);
+        assert_eq!(reader.read_file("hello.txt").unwrap(), b"Hello, browser!");
+        assert_eq!(
+            reader.read_file("large.json").unwrap(),
+            "{\"key\": \"value\"}".repeat(500).as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_read_prefix() {
+        let text = "Prefix sniffing test. ".repeat(200);
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        {
+            let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+            for (path, compression) in [
+                ("none.txt", CompressionMethod::None),
+                ("lz4.txt", CompressionMethod::Lz4),
+                ("zstd.txt", CompressionMethod::Zstd),
+            ] {
+                writer
+                    .add_file_with_compression(path, text.as_bytes(), compression)
+                    .unwrap();
+            }
+            writer.add_file("tiny.txt", b"abc").unwrap();
+            writer.finalize().unwrap();
+        }
+
+        let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+        for path in ["none.txt", "lz4.txt", "zstd.txt"] {
+            assert_eq!(
+                reader.read_prefix(path, 16).unwrap(),
+                &text.as_bytes()[..16]
+            );
+        }
+        assert_eq!(reader.read_prefix("tiny.txt", 16).unwrap(), b"abc");
+    }
+
+    #[test]
+    fn test_directory_outside_decrypted_payload() {
+        let key = [0x5Cu8; 32];
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        {
+            let mut writer = ArchiveWriter::create(temp_file.path())
+                .unwrap()
+                .with_archive_encryption(&key);
+            writer.add_file("a.txt", b"alpha").unwrap();
+            writer.finalize().unwrap();
+        }
+        let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+
+        // Used to underflow, then to slice past the payload
+        for offset in [10, u64::MAX / 2, u64::MAX] {
+            reader.header.central_directory_offset = offset;
+            assert!(matches!(
+                reader.read_central_directory_from_memory(),
+                Err(EngramError::InvalidFormat(_))
+            ));
+        }
+        reader.header.central_directory_offset = HEADER_SIZE as u64;
+        reader.header.entry_count = 1000;
+        assert!(matches!(
+            reader.read_central_directory_from_memory(),
+            Err(EngramError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_forged_entry_count_is_not_preallocated() {
+        // One entry's worth of bytes; a count of u32::MAX used to abort here
+        let bytes = vec![0u8; CD_ENTRY_SIZE];
+        assert_eq!(directory_capacity(u32::MAX, bytes.len() as u64), 1);
+        assert!(matches!(
+            Directory::parse(
+                Cursor::new(&bytes),
+                u32::MAX,
+                bytes.len() as u64,
+                false,
+                64,
+                false
+            ),
+            Err(EngramError::DirectoryEntryError { .. })
+        ));
+        assert!(matches!(
+            PathHashes::build(Cursor::new(&bytes), u32::MAX, bytes.len() as u64, 64),
+            Err(EngramError::DirectoryEntryError { index: 1, .. })
+        ));
+    }
+
+    #[test]
+    fn test_hash_collisions_fall_back_to_path() {
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        {
+            let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+            for name in ["a.txt", "b.txt", "target.txt", "c.txt"] {
+                writer.add_file(name, name.as_bytes()).unwrap();
+            }
+            writer.add_file("target.txt", b"rewritten").unwrap();
+            writer.finalize().unwrap();
+        }
+        let mut reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+
+        // Every entry claims the hash of "target.txt"
+        let hash = path_hash(b"target.txt");
+        let hashes = PathHashes((0..5).map(|index| (hash, index)).collect());
+        assert!(reader.directory.hashes.set(hashes).is_ok());
+
+        assert_eq!(reader.read_file("target.txt").unwrap(), b"rewritten");
+        assert_eq!(reader.get_entry("target.txt").unwrap().path, "target.txt");
+        // A path that is not stored matches no candidate
+        assert!(reader.get_entry("missing.txt").is_none());
+        assert!(reader.directory.parsed.get().is_none());
+    }
+
+    #[test]
+    fn test_key_material_is_zeroizing() {
+        let key = [0x42u8; 32];
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        {
+            let mut writer = ArchiveWriter::create(temp_file.path())
+                .unwrap()
+                .with_archive_encryption(&key);
+            writer.add_recipient_password("ops", "hunter2").unwrap();
+            writer.add_file("secret.txt", b"classified").unwrap();
+            writer.finalize().unwrap();
+        }
+
+        let reader = ArchiveReader::open(temp_file.path())
+            .unwrap()
+            .with_recipient_key("ops", &key)
+            .with_password("hunter2");
+        let _: &Option<Zeroizing<[u8; 32]>> = &reader.decryption_key;
+        let _: &Option<(Option<String>, Zeroizing<[u8; 32]>)> = &reader.recipient_key;
+        let _: &Option<Zeroizing<String>> = &reader.password;
+
+        let mut reader = ArchiveReader::open(temp_file.path())
+            .unwrap()
+            .with_password("hunter2");
+        reader.initialize().unwrap();
+        assert_eq!(reader.decryption_key.as_deref(), Some(&key));
+        assert_eq!(reader.read_file("secret.txt").unwrap(), b"classified");
+    }
+}
@@ -0,0 +1,420 @@
+use crate::crypto::{ct_eq, SecretKey};
+use crate::error::{EngramError, Result};
+use aes_gcm::aes::cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit};
+use aes_gcm::aes::Aes256;
+use curve25519_dalek::montgomery::MontgomeryPoint;
+use sha2::{Digest, Sha256};
+use std::io::{Read, Write};
+use zeroize::Zeroize;
+
+/// Recipients block signature
+pub const RECIPIENTS_SIGNATURE: [u8; 4] = [0x52, 0x43, 0x50, 0x54]; // "RCPT"
+
+/// Maximum length of a recipient id in bytes
+pub const MAX_RECIPIENT_ID_LENGTH: usize = 255;
+
+/// Content key wrapped with AES key wrap under a 32-byte symmetric key
+const KIND_SYMMETRIC: u8 = 1;
+
+/// Content key sealed to an X25519 public key
+const KIND_X25519: u8 = 2;
+
+/// Content key wrapped under a key derived from a password with PBKDF2
+const KIND_PASSWORD: u8 = 3;
+
+/// PBKDF2-HMAC-SHA256 iterations used by `ArchiveWriter::add_recipient_password`
+pub const PASSWORD_ITERATIONS: u32 = 600_000;
+
+/// Fewest PBKDF2 iterations a password recipient may be added with
+pub const MIN_PASSWORD_ITERATIONS: u32 = 10_000;
+
+/// Most PBKDF2 iterations a reader will run, so a crafted archive cannot
+/// stall `initialize`
+const MAX_PASSWORD_ITERATIONS: u32 = 100_000_000;
+
+/// Length of the random salt of a password recipient
+const PASSWORD_SALT_SIZE: usize = 16;
+
+/// AES key wrap initial value (RFC 3394, section 2.2.3.1)
+const KW_IV: [u8; 8] = [0xA6; 8];
+
+/// Length of a 32-byte key after AES key wrap
+const WRAPPED_KEY_SIZE: usize = 40;
+
+/// Domain separator for the key derived from an X25519 shared secret
+const X25519_KDF_LABEL: &[u8] = b"engram-recipient-x25519";
+
+/// Key a recipient is added with
+#[derive(Clone)]
+pub(crate) enum RecipientKey {
+    /// Shared 32-byte secret
+    Symmetric(SecretKey),
+    /// X25519 public key
+    PublicKey([u8; 32]),
+    /// Key derived from a password, with the salt and iteration count to
+    /// derive it again
+    Password {
+        salt: [u8; PASSWORD_SALT_SIZE],
+        iterations: u32,
+        kek: SecretKey,
+    },
+}
+
+impl RecipientKey {
+    /// Derive a password recipient's key with a fresh random salt
+    pub fn from_password(password: &str, iterations: u32) -> Result<Self> {
+        if password.is_empty() {
+            return Err(EngramError::InvalidOptions(
+                "Password must not be empty".to_string(),
+            ));
+        }
+        if iterations < MIN_PASSWORD_ITERATIONS {
+            return Err(EngramError::InvalidOptions(format!(
+                "Password recipients need at least {} PBKDF2 iterations, got {}",
+                MIN_PASSWORD_ITERATIONS, iterations
+            )));
+        }
+        let salt: [u8; PASSWORD_SALT_SIZE] = rand::random();
+        Ok(Self::Password {
+            salt,
+            iterations,
+            kek: derive_password_kek(password, &salt, iterations),
+        })
+    }
+}
+
+/// Content key wrapped for one recipient
+#[derive(Debug, Clone)]
+pub(crate) struct WrappedKey {
+    pub id: String,
+    kind: u8,
+    /// AES-KW output, preceded by the ephemeral public key for X25519
+    wrapped: Vec<u8>,
+}
+
+impl WrappedKey {
+    /// Wrap `content_key` for a recipient
+    pub fn wrap(id: &str, key: &RecipientKey, content_key: &[u8; 32]) -> Result<Self> {
+        let (kind, wrapped) = match key {
+            RecipientKey::Symmetric(key) => {
+                (KIND_SYMMETRIC, aes_kw_wrap(key, content_key).to_vec())
+            }
+            RecipientKey::PublicKey(public) => {
+                let ephemeral: [u8; 32] = rand::random();
+                let ephemeral_public = MontgomeryPoint::mul_base_clamped(ephemeral);
+                let shared = MontgomeryPoint(*public).mul_clamped(ephemeral);
+                let kek = derive_kek(&shared, &ephemeral_public, public)
+                    .ok_or(EngramError::InvalidPublicKey)?;
+
+                let mut wrapped = ephemeral_public.to_bytes().to_vec();
+                wrapped.extend_from_slice(&aes_kw_wrap(&kek, content_key));
+                (KIND_X25519, wrapped)
+            }
+            RecipientKey::Password {
+                salt,
+                iterations,
+                kek,
+            } => {
+                let mut wrapped = salt.to_vec();
+                wrapped.extend_from_slice(&iterations.to_le_bytes());
+                wrapped.extend_from_slice(&aes_kw_wrap(kek, content_key));
+                (KIND_PASSWORD, wrapped)
+            }
+        };
+        Ok(Self {
+            id: id.to_string(),
+            kind,
+            wrapped,
+        })
+    }
+
+    /// Recover the content key with the recipient's symmetric key or X25519 secret key
+    pub fn unwrap(&self, key: &[u8; 32]) -> Result<SecretKey> {
+        match self.kind {
+            KIND_SYMMETRIC => aes_kw_unwrap(key, &self.wrapped),
+            KIND_X25519 => {
+                let (ephemeral_public, wrapped) = self.wrapped.split_at(32);
+                let ephemeral_public = MontgomeryPoint(ephemeral_public.try_into().unwrap());
+                let public = MontgomeryPoint::mul_base_clamped(*key);
+                let shared = ephemeral_public.mul_clamped(*key);
+                let kek = derive_kek(&shared, &ephemeral_public, &public.to_bytes())
+                    .ok_or(EngramError::DecryptionFailed)?;
+                aes_kw_unwrap(&kek, wrapped)
+            }
+            // Opened with `unwrap_password` instead
+            KIND_PASSWORD => Err(EngramError::DecryptionFailed),
+            kind => Err(EngramError::InvalidFormat(format!(
+                "Unknown recipient key type: {}",
+                kind
+            ))),
+        }
+    }
+
+    /// Recover the content key of a password recipient
+    ///
+    /// Fails with `DecryptionFailed` for other recipients and wrong passwords.
+    pub fn unwrap_password(&self, password: &str) -> Result<SecretKey> {
+        if self.kind != KIND_PASSWORD {
+            return Err(EngramError::DecryptionFailed);
+        }
+        let (salt, rest) = self.wrapped.split_at(PASSWORD_SALT_SIZE);
+        let (iterations, wrapped) = rest.split_at(4);
+        let iterations = u32::from_le_bytes(iterations.try_into().unwrap());
+        if !(1..=MAX_PASSWORD_ITERATIONS).contains(&iterations) {
+            return Err(EngramError::InvalidFormat(format!(
+                "Password recipient '{}' asks for {} PBKDF2 iterations",
+                self.id, iterations
+            )));
+        }
+        let kek = derive_password_kek(password, salt, iterations);
+        aes_kw_unwrap(&kek, wrapped)
+    }
+}
+
+/// Serialized size of a recipients block
+pub(crate) fn block_size(recipients: &[WrappedKey]) -> usize {
+    6 + recipients
+        .iter()
+        .map(|r| 3 + r.id.len() + r.wrapped.len())
+        .sum::<usize>()
+}
+
+/// Write a recipients block
+///
+/// Structure:
+/// - Signature: "RCPT" (4 bytes)
+/// - Recipient count: uint16
+/// - Per recipient: type (uint8), id length (uint8), id (UTF-8),
+///   wrapped key length (uint8), wrapped key
+pub(crate) fn write_block<W: Write>(recipients: &[WrappedKey], mut writer: W) -> Result<usize> {
+    let count = u16::try_from(recipients.len())
+        .map_err(|_| EngramError::Other("Too many recipients".to_string()))?;
+    writer.write_all(&RECIPIENTS_SIGNATURE)?;
+    writer.write_all(&count.to_le_bytes())?;
+    for recipient in recipients {
+        writer.write_all(&[recipient.kind, recipient.id.len() as u8])?;
+        writer.write_all(recipient.id.as_bytes())?;
+        writer.write_all(&[recipient.wrapped.len() as u8])?;
+        writer.write_all(&recipient.wrapped)?;
+    }
+    Ok(block_size(recipients))
+}
+
+/// Parse a recipients block
+pub(crate) fn read_block<R: Read>(mut reader: R) -> Result<Vec<WrappedKey>> {
+    let mut signature = [0u8; 4];
+    reader.read_exact(&mut signature)?;
+    if signature != RECIPIENTS_SIGNATURE {
+        return Err(EngramError::InvalidFormat(
+            "Invalid recipients block signature".to_string(),
+        ));
+    }
+
+    let mut count = [0u8; 2];
+    reader.read_exact(&mut count)?;
+    let count = u16::from_le_bytes(count);
+
+    let mut recipients = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let mut kind_and_len = [0u8; 2];
+        reader.read_exact(&mut kind_and_len)?;
+        let [kind, id_len] = kind_and_len;
+        let mut id = vec![0u8; id_len as usize];
+        reader.read_exact(&mut id)?;
+        let id = String::from_utf8(id).map_err(|_| {
+            EngramError::InvalidFormat("Recipient id is not valid UTF-8".to_string())
+        })?;
+
+        let mut wrapped_len = [0u8; 1];
+        reader.read_exact(&mut wrapped_len)?;
+        let mut wrapped = vec![0u8; wrapped_len[0] as usize];
+        reader.read_exact(&mut wrapped)?;
+
+        let expected = match kind {
+            KIND_SYMMETRIC => Some(WRAPPED_KEY_SIZE),
+            KIND_X25519 => Some(32 + WRAPPED_KEY_SIZE),
+            KIND_PASSWORD => Some(PASSWORD_SALT_SIZE + 4 + WRAPPED_KEY_SIZE),
+            _ => None,
+        };
+        if expected.is_some_and(|expected| expected != wrapped.len()) {
+            return Err(EngramError::InvalidFormat(format!(
+                "Invalid wrapped key length for recipient '{}'",
+                id
+            )));
+        }
+        recipients.push(WrappedKey { id, kind, wrapped });
+    }
+    Ok(recipients)
+}
+
+/// Check a recipient id before it is stored
+pub(crate) fn validate_id(id: &str) -> Result<()> {
+    if id.is_empty() || id.len() > MAX_RECIPIENT_ID_LENGTH {
+        return Err(EngramError::Other(format!(
+            "Recipient id must be 1 to {} bytes",
+            MAX_RECIPIENT_ID_LENGTH
+        )));
+    }
+    Ok(())
+}
+
+/// X25519 public key for a secret key, for use with `ArchiveWriter::add_recipient_pubkey`
+///
+/// The secret key is clamped as usual for X25519, so any 32 random bytes work.
+pub fn recipient_public_key(secret_key: &[u8; 32]) -> [u8; 32] {
+    MontgomeryPoint::mul_base_clamped(*secret_key).to_bytes()
+}
+
+/// Key-encryption key from an X25519 shared secret (`None` for low-order points)
+fn derive_kek(
+    shared: &MontgomeryPoint,
+    ephemeral_public: &MontgomeryPoint,
+    public: &[u8; 32],
+) -> Option<SecretKey> {
+    if shared.to_bytes() == [0u8; 32] {
+        return None;
+    }
+    let mut hasher = Sha256::new();
+    hasher.update(X25519_KDF_LABEL);
+    hasher.update(shared.as_bytes());
+    hasher.update(ephemeral_public.as_bytes());
+    hasher.update(public);
+    Some(SecretKey::new(hasher.finalize().into()))
+}
+
+/// Key-encryption key from a password: PBKDF2-HMAC-SHA256 over its UTF-8 bytes
+fn derive_password_kek(password: &str, salt: &[u8], iterations: u32) -> SecretKey {
+    let mut kek = SecretKey::default();
+    pbkdf2::pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, iterations, kek.as_mut());
+    kek
+}
+
+/// AES-256 key wrap of a 32-byte key (RFC 3394)
+fn aes_kw_wrap(kek: &[u8; 32], key: &[u8; 32]) -> [u8; WRAPPED_KEY_SIZE] {
+    let cipher = Aes256::new(kek.into());
+    let mut a = KW_IV;
+    let mut r = [[0u8; 8]; 4];
+    for (block, chunk) in r.iter_mut().zip(key.chunks_exact(8)) {
+        block.copy_from_slice(chunk);
+    }
+
+    for j in 0..6 {
+        for (i, block) in r.iter_mut().enumerate() {
+            let mut b = GenericArray::default();
+            b[..8].copy_from_slice(&a);
+            b[8..].copy_from_slice(block);
+            cipher.encrypt_block(&mut b);
+
+            let t = (4 * j + i + 1) as u64;
+            a.copy_from_slice(&b[..8]);
+            for (byte, t) in a.iter_mut().zip(t.to_be_bytes()) {
+                *byte ^= t;
+            }
+            block.copy_from_slice(&b[8..]);
+        }
+    }
+
+    let mut out = [0u8; WRAPPED_KEY_SIZE];
+    out[..8].copy_from_slice(&a);
+    for (chunk, block) in out[8..].chunks_exact_mut(8).zip(&r) {
+        chunk.copy_from_slice(block);
+    }
+    r.zeroize();
+    out
+}
+
+/// Inverse of `aes_kw_wrap`; fails if the integrity check does not match
+fn aes_kw_unwrap(kek: &[u8; 32], wrapped: &[u8]) -> Result<SecretKey> {
+    if wrapped.len() != WRAPPED_KEY_SIZE {
+        return Err(EngramError::DecryptionFailed);
+    }
+    let cipher = Aes256::new(kek.into());
+    let mut a: [u8; 8] = wrapped[..8].try_into().unwrap();
+    let mut r = [[0u8; 8]; 4];
+    for (block, chunk) in r.iter_mut().zip(wrapped[8..].chunks_exact(8)) {
+        block.copy_from_slice(chunk);
+    }
+
+    for j in (0..6).rev() {
+        for (i, block) in r.iter_mut().enumerate().rev() {
+            let t = (4 * j + i + 1) as u64;
+            let mut b = GenericArray::default();
+            b[..8].copy_from_slice(&a);
+            for (byte, t) in b[..8].iter_mut().zip(t.to_be_bytes()) {
+                *byte ^= t;
+            }
+            b[8..].copy_from_slice(block);
+            cipher.decrypt_block(&mut b);
+
+            a.copy_from_slice(&b[..8]);
+            block.copy_from_slice(&b[8..]);
+        }
+    }
+
+    let mut key = SecretKey::default();
+    for (chunk, block) in key.chunks_exact_mut(8).zip(&r) {
+        chunk.copy_from_slice(block);
+    }
+    r.zeroize();
+    if !ct_eq(&a, &KW_IV) {
+        return Err(EngramError::DecryptionFailed);
+    }
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aes_kw_rfc3394_vector() {
+        // RFC 3394, section 4.6: 256 bits of key data with a 256-bit KEK
+        let kek: [u8; 32] = std::array::from_fn(|i| i as u8);
+        let key: [u8; 32] =
+            hex::decode("00112233445566778899AABBCCDDEEFF000102030405060708090A0B0C0D0E0F")
+                .unwrap()
+                .try_into()
+                .unwrap();
+        let expected = hex::decode(
+            "28C9F404C4B810F4CBCCB35CFB87F8263F5786E2D80ED326CBC7F0E71A99F43BFB988B9B7A02DD21",
+        )
+        .unwrap();
+
+        let wrapped = aes_kw_wrap(&kek, &key);
+        assert_eq!(wrapped.as_slice(), expected.as_slice());
+        assert_eq!(*aes_kw_unwrap(&kek, &wrapped).unwrap(), key);
+        assert!(aes_kw_unwrap(&[0u8; 32], &wrapped).is_err());
+    }
+
+    #[test]
+    fn test_block_roundtrip() {
+        let content_key = [7u8; 32];
+        let secret = [9u8; 32];
+        let recipients = vec![
+            WrappedKey::wrap(
+                "ops",
+                &RecipientKey::Symmetric(SecretKey::new([1u8; 32])),
+                &content_key,
+            )
+            .unwrap(),
+            WrappedKey::wrap(
+                "audit",
+                &RecipientKey::PublicKey(recipient_public_key(&secret)),
+                &content_key,
+            )
+            .unwrap(),
+        ];
+
+        let mut buf = Vec::new();
+        let written = write_block(&recipients, &mut buf).unwrap();
+        assert_eq!(written, buf.len());
+        assert_eq!(written, block_size(&recipients));
+
+        let parsed = read_block(&buf[..]).unwrap();
+        assert_eq!(parsed[0].id, "ops");
+        assert_eq!(*parsed[0].unwrap(&[1u8; 32]).unwrap(), content_key);
+        assert_eq!(parsed[1].id, "audit");
+        assert_eq!(*parsed[1].unwrap(&secret).unwrap(), content_key);
+        assert!(parsed[1].unwrap(&[1u8; 32]).is_err());
+    }
+}
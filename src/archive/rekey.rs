@@ -0,0 +1,351 @@
+use crate::archive::end_record::{EndRecord, END_RECORD_SIZE};
+use crate::archive::format::{entry_aad, EncryptionMode, HEADER_SIZE};
+use crate::archive::local_entry::LocalEntryHeader;
+use crate::archive::reader::ArchiveReader;
+use crate::archive::recipients::{self, RecipientKey, WrappedKey};
+use crate::crypto::SecretKey;
+use crate::error::{EngramError, Result};
+use aes_gcm::{
+    aead::{Aead, KeyInit, Payload},
+    Aes256Gcm, Nonce,
+};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+
+/// AES-GCM nonce length
+const NONCE_SIZE: usize = 12;
+
+/// Options for [`rekey_archive_with`] and [`update_recipients`]
+#[derive(Debug, Clone, Default)]
+pub struct RekeyOptions {
+    /// Allow `dest` to be the source archive.
+    ///
+    /// The rotated archive is written to a temporary file next to it and
+    /// renamed over the source once it has been verified, so a failure leaves the
+    /// source untouched.
+    pub in_place: bool,
+}
+
+/// Summary of a key rotation
+#[derive(Debug, Clone)]
+pub struct RekeyReport {
+    /// Encryption mode of the archive (unchanged by rotation)
+    pub encryption_mode: EncryptionMode,
+    /// Number of ciphertexts re-encrypted: one per stored payload in per-file
+    /// mode (entries sharing deduplicated data count once), one in archive mode
+    pub payloads_reencrypted: usize,
+    /// Number of entries that read back and passed CRC checks under the new key
+    pub entries_verified: usize,
+}
+
+/// Recipients to add to and remove from an archive, see [`update_recipients`]
+#[derive(Clone, Default)]
+pub struct RecipientChanges {
+    add: Vec<(String, RecipientKey)>,
+    remove: Vec<String>,
+}
+
+impl RecipientChanges {
+    /// No changes
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a recipient holding a 32-byte symmetric key
+    pub fn add_key(mut self, id: &str, key: &[u8; 32]) -> Self {
+        self.add.push((
+            id.to_string(),
+            RecipientKey::Symmetric(SecretKey::new(*key)),
+        ));
+        self
+    }
+
+    /// Add a recipient holding the secret key for an X25519 public key
+    pub fn add_pubkey(mut self, id: &str, public_key: &[u8; 32]) -> Self {
+        self.add
+            .push((id.to_string(), RecipientKey::PublicKey(*public_key)));
+        self
+    }
+
+    /// Remove a recipient
+    pub fn remove(mut self, id: &str) -> Self {
+        self.remove.push(id.to_string());
+        self
+    }
+}
+
+/// Re-encrypt an archive under a new key
+///
+/// Same as [`rekey_archive_with`] with default options, so `dest` must not be
+/// the source archive.
+pub fn rekey_archive<P: AsRef<Path>>(
+    src: P,
+    dest: P,
+    old_key: &[u8; 32],
+    new_key: &[u8; 32],
+) -> Result<RekeyReport> {
+    rekey_archive_with(src, dest, old_key, new_key, RekeyOptions::default())
+}
+
+/// Re-encrypt an archive under a new key, writing the result to `dest`
+///
+/// Per-file encrypted archives have each payload decrypted and encrypted again
+/// with a fresh nonce; the compressed data inside is not touched. Archive
+/// encrypted files have their whole payload re-encrypted. Ciphertexts keep
+/// their length, so the header, LOCA headers, central directory and End Record
+/// carry over byte for byte, along with timestamps, compression and flags.
+///
+/// The result is opened with `new_key` and fully verified before it is moved
+/// to `dest`.
+///
+/// ```no_run
+/// use engram_rs::rekey_archive;
+///
+/// let report = rekey_archive("vault.eng", "vault-2026.eng", &[1u8; 32], &[2u8; 32])?;
+/// println!("Re-encrypted {} payloads", report.payloads_reencrypted);
+/// # Ok::<(), engram_rs::EngramError>(())
+/// ```
+pub fn rekey_archive_with<P: AsRef<Path>>(
+    src: P,
+    dest: P,
+    old_key: &[u8; 32],
+    new_key: &[u8; 32],
+    options: RekeyOptions,
+) -> Result<RekeyReport> {
+    let (src, dest) = (src.as_ref(), dest.as_ref());
+    if dest.exists() && same_file(src, dest)? && !options.in_place {
+        return Err(EngramError::PathError(
+            "Cannot rekey an archive onto itself without in_place".to_string(),
+        ));
+    }
+
+    let mut reader = ArchiveReader::open(src)?;
+    let header = reader.header().clone();
+    if header.has_recipients() {
+        // The recipients' keys would be needed to wrap the new key
+        return Err(EngramError::Other(
+            "Cannot rekey an archive with recipients; use update_recipients".to_string(),
+        ));
+    }
+    let mut bytes = std::fs::read(src)?;
+
+    let encryption_mode = header.encryption_mode();
+    let payloads_reencrypted = match encryption_mode {
+        EncryptionMode::None => {
+            return Err(EngramError::Other(
+                "Cannot rekey an unencrypted archive".to_string(),
+            ));
+        }
+        EncryptionMode::Archive => {
+            let end = bytes
+                .len()
+                .checked_sub(END_RECORD_SIZE)
+                .filter(|&end| end >= HEADER_SIZE + NONCE_SIZE)
+                .ok_or(EngramError::DecryptionFailed)?;
+            reencrypt(&mut bytes[HEADER_SIZE..end], old_key, new_key, &[])?;
+            1
+        }
+        EncryptionMode::PerFile => {
+            reader.initialize()?;
+
+            // Deduplicated entries point at a payload that is rotated once
+            let mut done = HashSet::new();
+            for path in reader.list_files() {
+                let Some(entry) = reader.get_entry(path) else {
+                    continue;
+                };
+                if entry.is_directory() || !done.insert(entry.data_offset) {
+                    continue;
+                }
+
+                let start = usize::try_from(entry.data_offset)
+                    .ok()
+                    .filter(|&start| start < bytes.len())
+                    .ok_or_else(|| {
+                        EngramError::InvalidFormat(format!(
+                            "Data offset out of range for '{}'",
+                            entry.path
+                        ))
+                    })?;
+                let local = LocalEntryHeader::read_from(&bytes[start..])?;
+                let start = start + local.header_size();
+                let end = start
+                    .checked_add(entry.compressed_size as usize)
+                    .filter(|&end| end <= bytes.len())
+                    .ok_or_else(|| {
+                        EngramError::InvalidFormat(format!(
+                            "Payload out of range for '{}'",
+                            entry.path
+                        ))
+                    })?;
+
+                let aad = if header.has_entry_aad() {
+                    entry_aad(&local.path, entry.uncompressed_size)
+                } else {
+                    Vec::new()
+                };
+                reencrypt(&mut bytes[start..end], old_key, new_key, &aad)?;
+            }
+            done.len()
+        }
+    };
+    drop(reader);
+
+    let entries_verified = write_verified(&bytes, dest, new_key)?;
+    Ok(RekeyReport {
+        encryption_mode,
+        payloads_reencrypted,
+        entries_verified,
+    })
+}
+
+/// Add and remove recipients of an archive without re-encrypting it
+///
+/// `id` and `key` must unlock the archive as an existing recipient (see
+/// `ArchiveReader::with_recipient_key`). Only the recipients block and the
+/// End Record are rewritten; payloads, and the encryption key they use, stay
+/// as they are. Removals are applied before additions, and at least one
+/// recipient must remain.
+///
+/// Removing a recipient stops them from opening copies made from now on. It
+/// cannot revoke access to copies they already have, and the encryption key is
+/// unchanged; re-encrypt the archive's contents if that key may have leaked.
+pub fn update_recipients<P: AsRef<Path>>(
+    src: P,
+    dest: P,
+    id: &str,
+    key: &[u8; 32],
+    changes: RecipientChanges,
+    options: RekeyOptions,
+) -> Result<RekeyReport> {
+    let (src, dest) = (src.as_ref(), dest.as_ref());
+    if dest.exists() && same_file(src, dest)? && !options.in_place {
+        return Err(EngramError::PathError(
+            "Cannot update an archive onto itself without in_place".to_string(),
+        ));
+    }
+
+    let mut reader = ArchiveReader::open(src)?;
+    let encryption_mode = reader.header().encryption_mode();
+    let content_key = reader.unwrap_recipient_key(id, key)?;
+    let mut wrapped = reader.read_recipients()?;
+    drop(reader);
+
+    for removed in &changes.remove {
+        let before = wrapped.len();
+        wrapped.retain(|recipient| &recipient.id != removed);
+        if wrapped.len() == before {
+            return Err(EngramError::RecipientNotFound(removed.clone()));
+        }
+    }
+    for (added, recipient) in &changes.add {
+        recipients::validate_id(added)?;
+        if wrapped.iter().any(|existing| &existing.id == added) {
+            return Err(EngramError::Other(format!(
+                "Recipient already added: {}",
+                added
+            )));
+        }
+        wrapped.push(WrappedKey::wrap(added, recipient, &content_key)?);
+    }
+    if wrapped.is_empty() {
+        return Err(EngramError::Other(
+            "An archive must keep at least one recipient".to_string(),
+        ));
+    }
+
+    // Everything before the old recipients block stays byte for byte
+    let mut bytes = std::fs::read(src)?;
+    let mut end_record = EndRecord::read_from(&bytes[bytes.len() - END_RECORD_SIZE..])?;
+    let block_start = bytes.len() - END_RECORD_SIZE - end_record.recipients_size as usize;
+    bytes.truncate(block_start);
+    end_record.recipients_size = recipients::write_block(&wrapped, &mut bytes)? as u32;
+    end_record.write_to(&mut bytes)?;
+
+    let entries_verified = write_verified(&bytes, dest, &content_key)?;
+    Ok(RekeyReport {
+        encryption_mode,
+        payloads_reencrypted: 0,
+        entries_verified,
+    })
+}
+
+/// Write `bytes` to a temporary file next to `dest`, verify it with `key` and
+/// move it to `dest`, returning the number of verified entries
+fn write_verified(bytes: &[u8], dest: &Path, key: &[u8; 32]) -> Result<usize> {
+    let temp = temp_path(dest);
+    let result = std::fs::write(&temp, bytes)
+        .map_err(EngramError::from)
+        .and_then(|()| verify(&temp, key))
+        .and_then(|entries_verified| {
+            std::fs::rename(&temp, dest)?;
+            Ok(entries_verified)
+        });
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Replace `[nonce][ciphertext||tag]` in `buf` with the same plaintext
+/// encrypted under `new_key` and a fresh nonce
+fn reencrypt(buf: &mut [u8], old_key: &[u8; 32], new_key: &[u8; 32], aad: &[u8]) -> Result<()> {
+    if buf.len() < NONCE_SIZE + 16 {
+        // 12 nonce + 16 tag minimum
+        return Err(EngramError::DecryptionFailed);
+    }
+    let (nonce, ciphertext) = buf.split_at(NONCE_SIZE);
+
+    #[allow(deprecated)]
+    let nonce = Nonce::from_slice(nonce);
+    let plaintext = Aes256Gcm::new(old_key.into())
+        .decrypt(
+            nonce,
+            Payload {
+                msg: ciphertext,
+                aad,
+            },
+        )
+        .map_err(|_| EngramError::DecryptionFailed)?;
+
+    let nonce_bytes: [u8; NONCE_SIZE] = rand::random();
+    #[allow(deprecated)]
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let ciphertext = Aes256Gcm::new(new_key.into())
+        .encrypt(
+            nonce,
+            Payload {
+                msg: &plaintext,
+                aad,
+            },
+        )
+        .map_err(|_| EngramError::EncryptionFailed)?;
+
+    buf[..NONCE_SIZE].copy_from_slice(&nonce_bytes);
+    buf[NONCE_SIZE..].copy_from_slice(&ciphertext);
+    Ok(())
+}
+
+/// Open the rotated archive with `key` and read back every entry
+fn verify(path: &Path, key: &[u8; 32]) -> Result<usize> {
+    let mut reader = ArchiveReader::open_encrypted(path, key)?;
+    let report = reader.verify_all()?;
+    match report.failed.into_iter().next() {
+        Some((_, err)) => Err(err),
+        None => Ok(report.ok.len()),
+    }
+}
+
+/// Temporary file next to `dest`, renamed over it once verified
+fn temp_path(dest: &Path) -> PathBuf {
+    let name = dest
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    dest.with_file_name(format!(".{}.rekey-{}", name, std::process::id()))
+}
+
+/// Whether two existing paths refer to the same file
+fn same_file(a: &Path, b: &Path) -> Result<bool> {
+    Ok(std::fs::canonicalize(a)? == std::fs::canonicalize(b)?)
+}
@@ -0,0 +1,78 @@
+use crate::archive::format::{EntryInfo, FileHeader};
+use crate::archive::reader::ArchiveReader;
+use crate::error::Result;
+use std::path::Path;
+
+/// Archive that many threads read from at once
+///
+/// The central directory is parsed once when opening. Reads go through
+/// positioned reads (`read_exact_at` on Unix, `seek_read` on Windows) on a
+/// file handle no thread moves, so every method takes `&self` and one
+/// `Arc<SharedArchive>` serves all threads without locking or reopening.
+///
+/// ```no_run
+/// use engram_rs::SharedArchive;
+/// use std::sync::Arc;
+///
+/// let archive = Arc::new(SharedArchive::open("assets.eng")?);
+/// let worker = Arc::clone(&archive);
+/// std::thread::spawn(move || worker.read_file("textures/a.png")).join().unwrap()?;
+/// # Ok::<(), engram_rs::EngramError>(())
+/// ```
+pub struct SharedArchive {
+    reader: ArchiveReader,
+}
+
+impl SharedArchive {
+    /// Open an archive and parse its central directory
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(Self {
+            reader: ArchiveReader::open_and_init(path)?,
+        })
+    }
+
+    /// Open an encrypted archive and parse its central directory
+    pub fn open_encrypted<P: AsRef<Path>>(path: P, key: &[u8; 32]) -> Result<Self> {
+        Ok(Self {
+            reader: ArchiveReader::open_encrypted(path, key)?,
+        })
+    }
+
+    /// Read a file's decompressed, verified content
+    ///
+    /// Same checks as `ArchiveReader::read_file`; safe to call from any
+    /// number of threads concurrently.
+    pub fn read_file(&self, path: &str) -> Result<Vec<u8>> {
+        self.reader.read_file_at(path)
+    }
+
+    /// Archive header
+    pub fn header(&self) -> &FileHeader {
+        self.reader.header()
+    }
+
+    /// Number of entries in the archive
+    pub fn entry_count(&self) -> usize {
+        self.reader.entry_count()
+    }
+
+    /// List all files in the archive
+    pub fn list_files(&self) -> &[String] {
+        self.reader.list_files()
+    }
+
+    /// Check whether a file exists
+    pub fn contains(&self, path: &str) -> bool {
+        self.reader.contains(path)
+    }
+
+    /// Get entry metadata
+    pub fn get_entry(&self, path: &str) -> Option<&EntryInfo> {
+        self.reader.get_entry(path)
+    }
+
+    /// The underlying reader, for methods that need `&mut self`
+    pub fn into_inner(self) -> ArchiveReader {
+        self.reader
+    }
+}
@@ -0,0 +1,65 @@
+use crate::error::{EngramError, Result};
+use std::collections::HashMap;
+
+/// Archive path of the stored-payload CRC table (stored uncompressed)
+///
+/// Written by writers with `ArchiveWriter::with_compressed_checksums`. Holds
+/// one `[data_offset: uint64][crc32: uint32]` record per stored payload, in
+/// offset order: the CRC32 of the bytes following the entry's LOCA header,
+/// exactly as stored (compressed, and encrypted in per-file mode).
+pub const COMPRESSED_CRC_PATH: &str = ".engram/compressed.crc";
+
+/// Bytes per record of `COMPRESSED_CRC_PATH`
+const RECORD_SIZE: usize = 12;
+
+/// Encode `(data_offset, crc32)` records, sorted by offset
+pub(crate) fn table_bytes(records: &[(u64, u32)]) -> Vec<u8> {
+    let mut records = records.to_vec();
+    records.sort_unstable();
+    let mut bytes = Vec::with_capacity(records.len() * RECORD_SIZE);
+    for (offset, crc32) in records {
+        bytes.extend_from_slice(&offset.to_le_bytes());
+        bytes.extend_from_slice(&crc32.to_le_bytes());
+    }
+    bytes
+}
+
+/// Parse the table into data offset -> CRC32 of the stored payload
+pub(crate) fn parse_table(bytes: &[u8]) -> Result<HashMap<u64, u32>> {
+    let records = bytes.chunks_exact(RECORD_SIZE);
+    if !records.remainder().is_empty() {
+        return Err(EngramError::InvalidFormat(format!(
+            "{} is {} bytes, not a multiple of {}",
+            COMPRESSED_CRC_PATH,
+            bytes.len(),
+            RECORD_SIZE
+        )));
+    }
+    Ok(records
+        .map(|record| {
+            (
+                u64::from_le_bytes(record[..8].try_into().unwrap()),
+                u32::from_le_bytes(record[8..].try_into().unwrap()),
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_round_trip() {
+        let records = [(500, 0xDEAD_BEEF), (64, 7), (u64::MAX, 0)];
+        let bytes = table_bytes(&records);
+        assert_eq!(bytes.len(), 3 * RECORD_SIZE);
+        assert_eq!(bytes[..8], 64u64.to_le_bytes());
+
+        let table = parse_table(&bytes).unwrap();
+        assert_eq!(table.len(), 3);
+        assert_eq!(table[&500], 0xDEAD_BEEF);
+        assert!(parse_table(&bytes[1..]).is_err());
+        assert!(parse_table(&[]).unwrap().is_empty());
+    }
+}
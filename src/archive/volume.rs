@@ -0,0 +1,425 @@
+use crate::error::{EngramError, Result};
+use std::ffi::OsString;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// ENGV signature at the start of every volume of a split archive
+pub const VOLUME_SIGNATURE: [u8; 4] = [0x45, 0x4E, 0x47, 0x56]; // "ENGV"
+
+/// Volume header size in bytes (fixed)
+pub const VOLUME_HEADER_SIZE: usize = 16;
+
+/// Smallest accepted volume size (64KB)
+pub const MIN_VOLUME_SIZE: u64 = 64 * 1024;
+
+/// Path of volume `number` (1-based) of a split archive: `name.eng` -> `name.eng.001`
+pub fn volume_path<P: AsRef<Path>>(base_path: P, number: u32) -> PathBuf {
+    let mut path = OsString::from(base_path.as_ref().as_os_str());
+    path.push(format!(".{:03}", number));
+    PathBuf::from(path)
+}
+
+/// Volume header of a split archive
+///
+/// Each volume starts with this header, followed by the next slice of the
+/// archive. Concatenating the volumes without their headers yields an
+/// ordinary archive, so all offsets stored in the archive are unchanged.
+///
+/// Structure (16 bytes fixed):
+/// - Signature: "ENGV" (4 bytes)
+/// - Volume Number: uint32 (4 bytes, 1-based)
+/// - Volume Count: uint32 (4 bytes, 0 while the archive is being written)
+/// - Reserved: 4 bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VolumeHeader {
+    pub volume_number: u32,
+    pub volume_count: u32,
+}
+
+impl VolumeHeader {
+    /// Write volume header to a writer
+    pub fn write_to<W: Write>(&self, mut writer: W) -> Result<()> {
+        writer.write_all(&VOLUME_SIGNATURE)?;
+        writer.write_all(&self.volume_number.to_le_bytes())?;
+        writer.write_all(&self.volume_count.to_le_bytes())?;
+        writer.write_all(&[0u8; 4])?;
+        Ok(())
+    }
+
+    /// Read volume header from a reader
+    pub fn read_from<R: Read>(mut reader: R) -> Result<Self> {
+        let mut bytes = [0u8; VOLUME_HEADER_SIZE];
+        reader.read_exact(&mut bytes)?;
+        if bytes[0..4] != VOLUME_SIGNATURE {
+            return Err(EngramError::InvalidFormat(
+                "Invalid volume signature (expected ENGV)".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            volume_number: u32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+            volume_count: u32::from_le_bytes(bytes[8..12].try_into().unwrap()),
+        })
+    }
+}
+
+/// Find the volume holding logical position `pos`
+///
+/// Returns the volume index and the offset within its body. A position at or
+/// past the end maps to the last volume.
+fn locate(lens: &[u64], pos: u64) -> (usize, u64) {
+    let mut start = 0;
+    for (index, &len) in lens.iter().enumerate() {
+        if pos < start + len || index == lens.len() - 1 {
+            return (index, pos - start);
+        }
+        start += len;
+    }
+    unreachable!("a volume set always has at least one volume")
+}
+
+/// Resolve a seek against the current position and total length
+fn seek_target(pos: u64, total: u64, target: SeekFrom) -> io::Result<u64> {
+    let new_pos = match target {
+        SeekFrom::Start(offset) => Some(offset),
+        SeekFrom::End(delta) => total.checked_add_signed(delta),
+        SeekFrom::Current(delta) => pos.checked_add_signed(delta),
+    };
+    new_pos.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid seek to a negative or overflowing position",
+        )
+    })
+}
+
+/// Writes one logical archive stream across numbered volume files
+///
+/// Appends start a new volume once the current one is full. `start_volume_for`
+/// moves to a new volume early so an entry is not split when it fits in a
+/// volume of its own; after `seal`, everything goes to the last volume.
+pub(crate) struct VolumeWriter {
+    base_path: PathBuf,
+    /// Body capacity of each volume (volume size minus the volume header)
+    capacity: u64,
+    files: Vec<File>,
+    /// Body length of each volume
+    lens: Vec<u64>,
+    pos: u64,
+    sealed: bool,
+}
+
+impl VolumeWriter {
+    /// Create the first volume of `base_path`
+    pub fn create(base_path: &Path, volume_size: u64) -> Result<Self> {
+        if volume_size < MIN_VOLUME_SIZE {
+            return Err(EngramError::InvalidOptions(format!(
+                "Volume size must be at least {} bytes, got {}",
+                MIN_VOLUME_SIZE, volume_size
+            )));
+        }
+
+        let mut writer = Self {
+            base_path: base_path.to_path_buf(),
+            capacity: volume_size - VOLUME_HEADER_SIZE as u64,
+            files: Vec::new(),
+            lens: Vec::new(),
+            pos: 0,
+            sealed: false,
+        };
+        writer.new_volume()?;
+        Ok(writer)
+    }
+
+    /// Paths of the volumes written so far
+    pub fn paths(&self) -> Vec<PathBuf> {
+        (1..=self.files.len() as u32)
+            .map(|number| volume_path(&self.base_path, number))
+            .collect()
+    }
+
+    /// Start a new volume if `len` more bytes would overflow the current one
+    /// but fit in an empty one
+    ///
+    /// Only applies when appending at the end of the stream.
+    pub fn start_volume_for(&mut self, len: u64) -> Result<()> {
+        let last = self.lens[self.lens.len() - 1];
+        if !self.sealed
+            && self.pos == self.total_len()
+            && last > 0
+            && last + len > self.capacity
+            && len <= self.capacity
+        {
+            self.new_volume()?;
+        }
+        Ok(())
+    }
+
+    /// Cut the stream back to `len` bytes, deleting volumes that start at or
+    /// after it (the first volume is always kept)
+    pub fn truncate(&mut self, len: u64) -> Result<()> {
+        let (mut index, mut offset) = locate(&self.lens, len);
+        if offset == 0 && index > 0 {
+            index -= 1;
+            offset = self.lens[index];
+        }
+
+        let count = self.files.len() as u32;
+        self.files.truncate(index + 1);
+        self.lens.truncate(index + 1);
+        for number in index as u32 + 2..=count {
+            std::fs::remove_file(volume_path(&self.base_path, number))?;
+        }
+
+        self.files[index].set_len(VOLUME_HEADER_SIZE as u64 + offset)?;
+        self.lens[index] = offset;
+        self.pos = self.pos.min(len);
+        Ok(())
+    }
+
+    /// Keep all further data in the last volume, even past the volume size
+    pub fn seal(&mut self) {
+        self.sealed = true;
+    }
+
+    /// Record the final volume count in every volume header
+    pub fn finish(&mut self) -> Result<()> {
+        let volume_count = self.files.len() as u32;
+        for (index, file) in self.files.iter_mut().enumerate() {
+            file.seek(SeekFrom::Start(0))?;
+            VolumeHeader {
+                volume_number: index as u32 + 1,
+                volume_count,
+            }
+            .write_to(&mut *file)?;
+            file.flush()?;
+        }
+        Ok(())
+    }
+
+    fn total_len(&self) -> u64 {
+        self.lens.iter().sum()
+    }
+
+    fn new_volume(&mut self) -> Result<()> {
+        let number = self.files.len() as u32 + 1;
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(volume_path(&self.base_path, number))?;
+
+        // Placeholder until `finish` knows the volume count
+        VolumeHeader {
+            volume_number: number,
+            volume_count: 0,
+        }
+        .write_to(&mut file)?;
+
+        self.files.push(file);
+        self.lens.push(0);
+        Ok(())
+    }
+}
+
+impl Write for VolumeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let (mut index, mut offset) = locate(&self.lens, self.pos);
+        if index == self.lens.len() - 1 && !self.sealed && offset >= self.capacity {
+            self.new_volume().map_err(io::Error::other)?;
+            index += 1;
+            offset = 0;
+        }
+
+        // Earlier volumes are only overwritten in place; the last one grows
+        let room = if index < self.lens.len() - 1 {
+            self.lens[index] - offset
+        } else if self.sealed {
+            u64::MAX
+        } else {
+            self.capacity - offset
+        };
+        let len = buf.len().min(usize::try_from(room).unwrap_or(usize::MAX));
+
+        let file = &mut self.files[index];
+        file.seek(SeekFrom::Start(VOLUME_HEADER_SIZE as u64 + offset))?;
+        let written = file.write(&buf[..len])?;
+        self.lens[index] = self.lens[index].max(offset + written as u64);
+        self.pos += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        for file in &mut self.files {
+            file.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl Read for VolumeWriter {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        read_volumes(&mut self.files, &self.lens, &mut self.pos, buf)
+    }
+}
+
+impl Seek for VolumeWriter {
+    fn seek(&mut self, target: SeekFrom) -> io::Result<u64> {
+        self.pos = seek_target(self.pos, self.total_len(), target)?;
+        Ok(self.pos)
+    }
+}
+
+/// Reads the logical archive stream of a split archive
+///
+/// Presents the volume bodies as one continuous `Read + Seek` source.
+pub(crate) struct VolumeReader {
+    files: Vec<File>,
+    /// Body length of each volume
+    lens: Vec<u64>,
+    pos: u64,
+}
+
+impl VolumeReader {
+    /// Open every volume of the split archive starting at `first_volume` (`*.001`)
+    ///
+    /// The volume count comes from the first volume's header. Each volume must
+    /// exist and carry its own number; otherwise the error names the offending file.
+    pub fn open(first_volume: &Path) -> Result<Self> {
+        let base_path = match first_volume.extension() {
+            Some(extension) if extension == "001" => first_volume.with_extension(""),
+            _ => {
+                return Err(EngramError::PathError(format!(
+                    "First volume must end in .001: {}",
+                    first_volume.display()
+                )))
+            }
+        };
+
+        let mut files = Vec::new();
+        let mut lens = Vec::new();
+        let mut volume_count = 1;
+        let mut number = 1;
+        while number <= volume_count {
+            let path = volume_path(&base_path, number);
+            let mut file = match File::open(&path) {
+                Ok(file) => file,
+                Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                    return Err(EngramError::MissingVolume(path.display().to_string()));
+                }
+                Err(err) => return Err(err.into()),
+            };
+
+            let header = VolumeHeader::read_from(&mut file)?;
+            if number == 1 {
+                if header.volume_count == 0 {
+                    return Err(EngramError::InvalidFormat(format!(
+                        "Split archive was not finalized: {}",
+                        path.display()
+                    )));
+                }
+                volume_count = header.volume_count;
+            }
+            if header.volume_number != number || header.volume_count != volume_count {
+                return Err(EngramError::InvalidFormat(format!(
+                    "Volume out of order: {} holds part {} of {}, expected part {} of {}",
+                    path.display(),
+                    header.volume_number,
+                    header.volume_count,
+                    number,
+                    volume_count
+                )));
+            }
+
+            lens.push(file.metadata()?.len() - VOLUME_HEADER_SIZE as u64);
+            files.push(file);
+            number += 1;
+        }
+
+        Ok(Self {
+            files,
+            lens,
+            pos: 0,
+        })
+    }
+}
+
+impl Read for VolumeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        read_volumes(&mut self.files, &self.lens, &mut self.pos, buf)
+    }
+}
+
+impl Seek for VolumeReader {
+    fn seek(&mut self, target: SeekFrom) -> io::Result<u64> {
+        self.pos = seek_target(self.pos, self.lens.iter().sum(), target)?;
+        Ok(self.pos)
+    }
+}
+
+/// Read from the volume holding `pos`, stopping at its end
+fn read_volumes(
+    files: &mut [File],
+    lens: &[u64],
+    pos: &mut u64,
+    buf: &mut [u8],
+) -> io::Result<usize> {
+    let (index, offset) = locate(lens, *pos);
+    let remaining = lens[index].saturating_sub(offset);
+    let len = buf
+        .len()
+        .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+    if len == 0 {
+        return Ok(0);
+    }
+
+    let file = &mut files[index];
+    file.seek(SeekFrom::Start(VOLUME_HEADER_SIZE as u64 + offset))?;
+    let read = file.read(&mut buf[..len])?;
+    *pos += read as u64;
+    Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_volume_header_roundtrip() {
+        let header = VolumeHeader {
+            volume_number: 3,
+            volume_count: 7,
+        };
+        let mut buf = Vec::new();
+        header.write_to(&mut buf).unwrap();
+        assert_eq!(buf.len(), VOLUME_HEADER_SIZE);
+        assert_eq!(VolumeHeader::read_from(Cursor::new(&buf)).unwrap(), header);
+
+        buf[0] = b'X';
+        assert!(VolumeHeader::read_from(Cursor::new(&buf)).is_err());
+    }
+
+    #[test]
+    fn test_volume_path() {
+        assert_eq!(
+            volume_path("dir/name.eng", 2),
+            PathBuf::from("dir/name.eng.002")
+        );
+    }
+
+    #[test]
+    fn test_locate() {
+        let lens = [10, 5, 8];
+        assert_eq!(locate(&lens, 0), (0, 0));
+        assert_eq!(locate(&lens, 10), (1, 0));
+        assert_eq!(locate(&lens, 16), (2, 1));
+        assert_eq!(locate(&lens, 30), (2, 15));
+    }
+}
@@ -1,99 +1,1592 @@
-use crate::archive::end_record::EndRecord;
+use crate::archive::blake3_table::{self, BLAKE3_TABLE_PATH};
+use crate::archive::cancellation::{CancellationToken, CANCEL_CHECK_INTERVAL};
+use crate::archive::chunking::{self, ChunkRef, ChunkerConfig};
+use crate::archive::compression_policy::{CompressionPolicy, ForceMethod};
+use crate::archive::dictionary::{compress_with_dictionary, ZSTD_DICTIONARY_PATH};
+use crate::archive::end_record::{EndRecord, END_RECORD_SIZE};
+use crate::archive::endr_signature::{SignatureBlock, SIGNATURE_BLOCK_SIZE};
+use crate::archive::entry_metadata::{self, ENTRY_METADATA_PATH};
 use crate::archive::format::{
-    CompressionMethod, EncryptionMode, EntryInfo, FileHeader, FORMAT_VERSION_MAJOR,
-    FORMAT_VERSION_MINOR,
+    entry_aad, is_reserved_path, ArchiveLabel, CompressionMethod, EncryptionMode, EntryInfo,
+    FileHeader, RawEntry, ARCHIVE_LABEL_LEN, DEFAULT_ZSTD_LEVEL, ENTRY_FLAG_ALIAS,
+    ENTRY_FLAG_CHUNKED, ENTRY_FLAG_DEDUPLICATED, ENTRY_FLAG_DIRECTORY, ENTRY_FLAG_EXECUTABLE,
+    ENTRY_FLAG_FRAMED, ENTRY_FLAG_SHA256, ENTRY_FLAG_ZSTD_DICTIONARY, FORMAT_VERSION_MAJOR,
+    FORMAT_VERSION_MINOR, MANIFEST_PATH, SHA256_PREFIX_LEN,
+};
+use crate::archive::frame_compression::{
+    compress_frames_with, FrameLayout, FrameOptions, FRAME_SIZE,
 };
-use crate::archive::frame_compression::{compress_frames, should_use_frames};
 use crate::archive::local_entry::LocalEntryHeader;
+use crate::archive::mime::{self, mime_type_for_path, MIME_TABLE_BASE, MIME_TABLE_PATH};
+use crate::archive::progress::{Progress, ProgressEvent};
+use crate::archive::reader::ArchiveReader;
+use crate::archive::recipients::{self, RecipientKey, WrappedKey, PASSWORD_ITERATIONS};
+use crate::archive::stored_crc::{self, COMPRESSED_CRC_PATH};
+use crate::archive::volume::VolumeWriter;
+use crate::crypto::SecretKey;
 use crate::error::{EngramError, Result};
 use aes_gcm::{
-    aead::{Aead, KeyInit},
+    aead::{Aead, AeadInPlace, KeyInit, Payload},
     Aes256Gcm, Nonce,
 };
+use ed25519_dalek::SigningKey;
+use rand::{CryptoRng, RngCore};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{File, OpenOptions};
-use std::io::{BufWriter, Seek, SeekFrom, Write};
-use std::path::Path;
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
 use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Threshold below which files are not compressed (4KB)
 const MIN_COMPRESSION_SIZE: usize = 4096;
 
+/// Bytes a pre-compressed payload may exceed ten times its content by,
+/// covering the container overhead of tiny streams
+pub const PRECOMPRESSED_OVERHEAD: u64 = 64;
+
 /// Normalize path to forward slashes (cross-platform compatibility)
-fn normalize_path(path: &str) -> String {
+pub(crate) fn normalize_path(path: &str) -> String {
     path.replace('\\', "/")
 }
 
+/// Reject paths that could escape the extraction root
+///
+/// Expects a path that has already been through `normalize_path`.
+pub(crate) fn validate_path(path: &str) -> Result<()> {
+    if path.is_empty() {
+        return Err(EngramError::PathError("Path is empty".to_string()));
+    }
+
+    if path.contains('\0') {
+        return Err(EngramError::PathError(format!(
+            "Path contains null byte: {:?}",
+            path
+        )));
+    }
+
+    // Readers reject these by default (see `EntryInfo::read_from`)
+    if path.bytes().any(|byte| byte < 0x20) {
+        return Err(EngramError::PathError(format!(
+            "Path contains control characters: {:?}",
+            path
+        )));
+    }
+
+    // Absolute paths (Unix root or Windows drive letter)
+    let bytes = path.as_bytes();
+    if path.starts_with('/')
+        || (bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':')
+    {
+        return Err(EngramError::PathError(format!(
+            "Absolute paths are not allowed: {}",
+            path
+        )));
+    }
+
+    // Parent directory traversal
+    if path.split('/').any(|component| component == "..") {
+        return Err(EngramError::PathError(format!(
+            "Path traversal is not allowed: {}",
+            path
+        )));
+    }
+
+    Ok(())
+}
+
+/// Unix mode for a file on disk
+#[cfg(unix)]
+fn file_mode(metadata: &std::fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode()
+}
+
+/// Unix mode for a file on disk
+///
+/// Only the read-only attribute is available off Unix; map it to equivalent
+/// regular-file permissions.
+#[cfg(not(unix))]
+fn file_mode(metadata: &std::fs::Metadata) -> u32 {
+    if metadata.permissions().readonly() {
+        0o100444
+    } else {
+        0o100644
+    }
+}
+
+/// Entry flags implied by a Unix mode
+fn mode_flags(mode: u32) -> u8 {
+    if mode & 0o111 != 0 {
+        ENTRY_FLAG_EXECUTABLE
+    } else {
+        0
+    }
+}
+
+/// Metadata for [`ArchiveWriter::add_file_with_metadata`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FileMetadata<'a> {
+    /// Modification time in Unix epoch seconds; the writer's default when `None`
+    pub modified_time: Option<u64>,
+    /// Mark the entry executable (see `EntryInfo::is_executable`)
+    pub executable: bool,
+    /// MIME type to record in the central directory (see `EntryInfo::mime_type`)
+    pub mime_type: Option<&'a str>,
+    /// Custom key/value pairs, such as an upstream content hash or source
+    /// revision (see `ArchiveReader::entry_metadata`)
+    pub extra: Option<&'a BTreeMap<String, String>>,
+}
+
+/// Per-entry metadata that is not derived from the file contents
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct EntryAttributes {
+    pub modified_time: u64,
+    pub mode: u32,
+    pub flags: u8,
+    /// See `EntryInfo::mime_id`
+    pub mime_id: u16,
+}
+
+impl EntryAttributes {
+    /// Attributes for a new entry stamped with the current time
+    pub fn now() -> Self {
+        Self {
+            modified_time: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            ..Self::default()
+        }
+    }
+}
+
+/// Byte sink given to `ArchiveWriter::from_writer`
+trait WriteTarget: Read + Write + Seek + Send {}
+
+impl<T: Read + Write + Seek + Send> WriteTarget for T {}
+
+/// Destination of the archive bytes: one file, numbered volumes, or a
+/// caller's stream
+enum Output {
+    File(File),
+    Volumes(VolumeWriter),
+    Stream(Box<dyn WriteTarget>),
+}
+
+impl Output {
+    /// Files written so far
+    fn paths(&self, path: Option<&Path>) -> Vec<PathBuf> {
+        match self {
+            Output::File(_) => path.into_iter().map(Path::to_path_buf).collect(),
+            Output::Volumes(volumes) => volumes.paths(),
+            Output::Stream(_) => Vec::new(),
+        }
+    }
+
+    /// Cut the output back to `len` bytes, returning false if it cannot be
+    /// shortened (streams)
+    fn truncate(&mut self, len: u64) -> Result<bool> {
+        match self {
+            Output::File(file) => file.set_len(len)?,
+            Output::Volumes(volumes) => volumes.truncate(len)?,
+            Output::Stream(_) => return Ok(false),
+        }
+        Ok(true)
+    }
+}
+
+impl Read for Output {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        match self {
+            Output::File(file) => file.read(buf),
+            Output::Volumes(volumes) => volumes.read(buf),
+            Output::Stream(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match self {
+            Output::File(file) => file.write(buf),
+            Output::Volumes(volumes) => volumes.write(buf),
+            Output::Stream(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        match self {
+            Output::File(file) => file.flush(),
+            Output::Volumes(volumes) => volumes.flush(),
+            Output::Stream(stream) => stream.flush(),
+        }
+    }
+}
+
+impl Seek for Output {
+    fn seek(&mut self, target: SeekFrom) -> std::io::Result<u64> {
+        match self {
+            Output::File(file) => file.seek(target),
+            Output::Volumes(volumes) => volumes.seek(target),
+            Output::Stream(stream) => stream.seek(target),
+        }
+    }
+}
+
+/// Summary of a finalized archive, returned by `ArchiveWriter::finalize`
+#[derive(Debug, Clone)]
+pub struct FinalizeSummary {
+    /// Archive path (the base path for split archives); `None` for archives
+    /// written with `ArchiveWriter::from_writer`
+    pub path: Option<PathBuf>,
+    /// Number of central directory entries
+    pub total_entries: u32,
+    /// Size of the archive on disk (all volumes for split archives)
+    pub archive_size: u64,
+    /// Sum of all entries' uncompressed sizes
+    pub total_uncompressed: u64,
+    /// Stored payload bytes; deduplicated entries count once
+    pub total_compressed: u64,
+    /// Offset of the central directory
+    pub cd_offset: u64,
+    /// Encryption mode of the archive
+    pub encryption: EncryptionMode,
+}
+
+/// Options for a new `ArchiveWriter`, checked before the archive is created
+///
+/// The `with_*` methods on `ArchiveWriter` apply one option at a time after
+/// `create`, so a later call silently overrides an earlier one. The builder
+/// collects the options first and `build` rejects combinations that make no
+/// sense, such as both archive and per-file encryption.
+///
+/// ```no_run
+/// use engram_rs::{ArchiveWriter, CompressionMethod};
+///
+/// let mut writer = ArchiveWriter::builder()
+///     .with_per_file_encryption(&[7u8; 32])
+///     .with_compression(CompressionMethod::Zstd)
+///     .with_zstd_level(19)
+///     .with_fixed_time(1_700_000_000)
+///     .build("assets.eng")?;
+/// writer.add_file("config.json", b"{}")?;
+/// writer.finalize()?;
+/// # Ok::<(), engram_rs::EngramError>(())
+/// ```
+#[derive(Clone, Default)]
+pub struct ArchiveWriterBuilder {
+    compression: Option<CompressionMethod>,
+    archive_key: Option<SecretKey>,
+    per_file_key: Option<SecretKey>,
+    zstd_level: Option<i32>,
+    fixed_time: Option<u64>,
+}
+
+impl ArchiveWriterBuilder {
+    /// Builder with every option at its default
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// See `ArchiveWriter::with_default_compression`
+    pub fn with_compression(mut self, compression: CompressionMethod) -> Self {
+        self.compression = Some(compression);
+        self
+    }
+
+    /// See `ArchiveWriter::with_archive_encryption`
+    pub fn with_archive_encryption(mut self, key: &[u8; 32]) -> Self {
+        self.archive_key = Some(SecretKey::new(*key));
+        self
+    }
+
+    /// See `ArchiveWriter::with_per_file_encryption`
+    pub fn with_per_file_encryption(mut self, key: &[u8; 32]) -> Self {
+        self.per_file_key = Some(SecretKey::new(*key));
+        self
+    }
+
+    /// See `ArchiveWriter::with_zstd_level`
+    pub fn with_zstd_level(mut self, level: i32) -> Self {
+        self.zstd_level = Some(level);
+        self
+    }
+
+    /// See `ArchiveWriter::with_fixed_time`
+    pub fn with_fixed_time(mut self, modified_time: u64) -> Self {
+        self.fixed_time = Some(modified_time);
+        self
+    }
+
+    /// Check the options and create the archive at `path`
+    ///
+    /// Fails with `EngramError::InvalidOptions` if both encryption modes are
+    /// set or the Zstd level is outside `zstd::compression_level_range()`, and
+    /// with `InvalidCompression` for an `Unknown` compression method. Nothing
+    /// is written to `path` unless the options are valid.
+    pub fn build<P: AsRef<Path>>(self, path: P) -> Result<ArchiveWriter> {
+        self.validate()?;
+        let mut writer = ArchiveWriter::create(path)?;
+        if let Some(key) = &self.archive_key {
+            writer = writer.with_archive_encryption(key);
+        }
+        if let Some(key) = &self.per_file_key {
+            writer = writer.with_per_file_encryption(key);
+        }
+        if let Some(compression) = self.compression {
+            writer = writer.with_default_compression(compression);
+        }
+        if let Some(level) = self.zstd_level {
+            writer = writer.with_zstd_level(level);
+        }
+        if let Some(modified_time) = self.fixed_time {
+            writer = writer.with_fixed_time(modified_time);
+        }
+        Ok(writer)
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.archive_key.is_some() && self.per_file_key.is_some() {
+            return Err(EngramError::InvalidOptions(
+                "Archive and per-file encryption cannot be combined".to_string(),
+            ));
+        }
+        if let Some(level) = self.zstd_level {
+            let range = zstd::compression_level_range();
+            if !range.contains(&level) {
+                return Err(EngramError::InvalidOptions(format!(
+                    "Zstd level {} is outside {}..={}",
+                    level,
+                    range.start(),
+                    range.end()
+                )));
+            }
+        }
+        if let Some(compression) = self.compression {
+            check_writable(compression)?;
+        }
+        Ok(())
+    }
+}
+
+/// Refuse compression methods this crate cannot write
+fn check_writable(compression: CompressionMethod) -> Result<()> {
+    match compression {
+        CompressionMethod::Unknown(value) => Err(EngramError::InvalidCompression(value)),
+        _ => Ok(()),
+    }
+}
+
+/// Callback set with `with_drop_warning`
+type DropWarning = Box<dyn FnOnce(usize) + Send>;
+
+/// Source of AES-GCM nonces set with `with_rng` or `with_insecure_nonce_source`
+type NonceSource = Box<dyn FnMut() -> [u8; 12] + Send>;
+
 /// Archive writer for creating .eng files
 pub struct ArchiveWriter {
-    writer: BufWriter<File>,
+    /// Archive path (the base path for split archives); `None` for streams
+    path: Option<PathBuf>,
+    writer: BufWriter<Output>,
     entries: Vec<EntryInfo>,
     current_offset: u64,
+    /// End of the bytes a rolled-back entry left in a stream, which cannot
+    /// be cut off; `finalize` pads the entries up to it
+    rolled_back_end: u64,
+    /// Set when a failed write could not be undone, see `WriterPoisoned`
+    poisoned: bool,
+    /// Set once finalization starts, see `AlreadyFinalized`
+    finalized: bool,
+    /// Finish the archive if dropped unfinalized, see `with_finalize_on_drop`
+    finalize_on_drop: bool,
+    /// Called if dropped unfinalized, see `with_drop_warning`
+    drop_warning: Option<DropWarning>,
     encryption_mode: EncryptionMode,
-    encryption_key: Option<[u8; 32]>,
+    /// Wiped when the writer is dropped (see `crate::crypto`)
+    encryption_key: Option<SecretKey>,
+    /// SHA-256 of uncompressed content -> index of the first entry storing it
+    dedup_index: Option<HashMap<[u8; 32], usize>>,
+    /// Splits large files into shared chunks, see `with_cdc_dedup`
+    chunker: Option<ChunkerConfig>,
+    /// SHA-256 of each chunk written -> index of its entry
+    chunk_index: HashMap<[u8; 32], usize>,
+    strong_checksums: bool,
+    /// Record BLAKE3 digests at `BLAKE3_TABLE_PATH`, see `with_blake3`
+    blake3: bool,
+    /// Data offset -> CRC32 of each stored payload, written to
+    /// `COMPRESSED_CRC_PATH` (see `with_compressed_checksums`)
+    compressed_checksums: Option<Vec<(u64, u32)>>,
+    /// Test-decode `add_precompressed` payloads (see `with_precompressed_verification`)
+    verify_precompressed: bool,
+    progress: Progress,
+    cancellation: CancellationToken,
+    fixed_time: Option<u64>,
+    zstd_dictionary: Option<Vec<u8>>,
+    zstd_level: i32,
+    /// Frame size and threshold for large files, see `with_frame_options`
+    frame_options: FrameOptions,
+    /// Replaces the built-in choice of compression for `add_file`, see
+    /// `with_compression_policy` and `with_default_compression`
+    compression_policy: Option<Box<dyn CompressionPolicy>>,
+    /// Serialized manifest and its compression, written by `finalize` (the
+    /// last `add_manifest` wins)
+    manifest: Option<(Vec<u8>, CompressionMethod)>,
+    content_version: u32,
+    label: ArchiveLabel,
+    sorted_directory: bool,
+    /// Sort the directory and pin timestamps, see `with_deterministic_output`
+    deterministic: bool,
+    /// Replaces system RNG nonces, see `with_rng`
+    nonce_source: Option<NonceSource>,
+    /// Signs the End Record and central directory, see `with_endr_signature`
+    endr_signing_key: Option<SigningKey>,
+    /// Recipients the encryption key is wrapped for, in the order added
+    recipients: Vec<(String, RecipientKey)>,
+    /// Format version written to the header, see `with_format_version`
+    format_version: (u16, u16),
+    /// Custom MIME types, written to `MIME_TABLE_PATH` by `finalize`
+    mime_table: Vec<String>,
+    /// Encoded custom metadata by entry path, written to `ENTRY_METADATA_PATH`
+    /// by `finalize`
+    entry_metadata: BTreeMap<String, Vec<u8>>,
 }
 
 impl ArchiveWriter {
+    /// Collect options before creating an archive, see `ArchiveWriterBuilder`
+    pub fn builder() -> ArchiveWriterBuilder {
+        ArchiveWriterBuilder::new()
+    }
+
     /// Create a new archive file
     pub fn create<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+
         // Open with read+write for encryption support (need to read back for archive encryption)
         let file = OpenOptions::new()
             .read(true)
             .write(true)
             .create(true)
             .truncate(true)
-            .open(path)?;
-        let mut writer = BufWriter::new(file);
+            .open(&path)?;
+        Self::with_output(Some(path), Output::File(file))
+    }
+
+    /// Write an archive to any seekable byte sink
+    ///
+    /// The writing counterpart of `ArchiveReader::from_reader`, for
+    /// destinations that are not a path of their own. The archive is written
+    /// from position 0, so `target` should start out empty. It is read back
+    /// only for archive-level encryption. `FinalizeSummary::path` is `None`.
+    ///
+    /// If writing an entry fails, a file is cut back to where the entry
+    /// started; a stream cannot be shortened, so the entry's bytes are
+    /// overwritten by later entries and any left over are zeroed by `finalize`.
+    pub fn from_writer<W: Read + Write + Seek + Send + 'static>(target: W) -> Result<Self> {
+        let mut stream: Box<dyn WriteTarget> = Box::new(target);
+        stream.seek(SeekFrom::Start(0))?;
+        Self::with_output(None, Output::Stream(stream))
+    }
+
+    /// Create an archive split into volumes of at most `volume_size` bytes
+    ///
+    /// Writes `base_path.001`, `base_path.002`, ... (e.g. `name.eng.001`), for
+    /// media with a file size limit such as FAT32. Volumes are split between
+    /// entries where possible; only entries larger than a volume span several.
+    /// The central directory and End Record go in the final volume, which may
+    /// exceed `volume_size` if the central directory alone does not fit in a
+    /// volume. Read the result with `ArchiveReader::open_split`.
+    ///
+    /// `volume_size` must be at least `MIN_VOLUME_SIZE`.
+    pub fn create_split<P: AsRef<Path>>(base_path: P, volume_size: u64) -> Result<Self> {
+        let path = base_path.as_ref().to_path_buf();
+        let volumes = VolumeWriter::create(&path, volume_size)?;
+        Self::with_output(Some(path), Output::Volumes(volumes))
+    }
+
+    fn with_output(path: Option<PathBuf>, output: Output) -> Result<Self> {
+        let mut writer = BufWriter::new(output);
 
         // Write placeholder header (will be updated at finalization)
         let header = FileHeader::new();
         header.write_to(&mut writer)?;
 
         Ok(Self {
+            path,
             writer,
             entries: Vec::new(),
             current_offset: 64, // After header
+            rolled_back_end: 0,
+            poisoned: false,
+            finalized: false,
+            finalize_on_drop: false,
+            drop_warning: None,
             encryption_mode: EncryptionMode::None,
             encryption_key: None,
+            dedup_index: None,
+            chunker: None,
+            chunk_index: HashMap::new(),
+            strong_checksums: false,
+            blake3: false,
+            compressed_checksums: None,
+            verify_precompressed: false,
+            progress: Progress::default(),
+            cancellation: CancellationToken::default(),
+            fixed_time: None,
+            zstd_dictionary: None,
+            zstd_level: DEFAULT_ZSTD_LEVEL,
+            frame_options: FrameOptions::default(),
+            compression_policy: None,
+            manifest: None,
+            content_version: 0,
+            label: ArchiveLabel::default(),
+            sorted_directory: false,
+            deterministic: false,
+            nonce_source: None,
+            endr_signing_key: None,
+            recipients: Vec::new(),
+            format_version: (FORMAT_VERSION_MAJOR, FORMAT_VERSION_MINOR),
+            mime_table: Vec::new(),
+            entry_metadata: BTreeMap::new(),
         })
     }
 
     /// Enable archive-level encryption (entire archive encrypted after finalization)
+    ///
+    /// The writer keeps its own copy of the key, wiped on drop; `key` itself
+    /// is left to the caller.
     pub fn with_archive_encryption(mut self, key: &[u8; 32]) -> Self {
         self.encryption_mode = EncryptionMode::Archive;
-        self.encryption_key = Some(*key);
+        self.encryption_key = Some(SecretKey::new(*key));
         self
     }
 
     /// Enable per-file encryption (each file encrypted individually)
     pub fn with_per_file_encryption(mut self, key: &[u8; 32]) -> Self {
         self.encryption_mode = EncryptionMode::PerFile;
-        self.encryption_key = Some(*key);
+        self.encryption_key = Some(SecretKey::new(*key));
+        self
+    }
+
+    /// Enable content deduplication
+    ///
+    /// Each file's uncompressed content is hashed with SHA-256. When a file is
+    /// identical to one already written, its central directory entry points at
+    /// the existing LOCA header and data instead of storing the payload again.
+    /// Such entries carry `ENTRY_FLAG_DEDUPLICATED`.
+    ///
+    /// Per-file encrypted payloads are bound to their own path (see
+    /// `HEADER_FLAG_ENTRY_AAD`), so with per-file encryption every file is
+    /// stored in full; `with_cdc_dedup` chunks are still shared.
+    pub fn with_dedup(mut self) -> Self {
+        self.dedup_index = Some(HashMap::new());
+        self
+    }
+
+    /// Deduplicate parts of large files with content-defined chunking
+    ///
+    /// Files larger than `config.max_size` are split where a rolling hash of
+    /// their content matches (FastCDC), and each distinct chunk is stored
+    /// once, as an entry under `CHUNKS_PREFIX` named by its SHA-256. The
+    /// file's own entry then holds the list of its chunks and carries
+    /// `ENTRY_FLAG_CHUNKED`; `ArchiveReader::read_file` and
+    /// `read_file_to` put it back together, checking every chunk against
+    /// its SHA-256. Because boundaries follow the content, files that differ
+    /// by an insertion or a few edits (VM images, database snapshots, build
+    /// outputs) share all chunks away from the changes.
+    ///
+    /// Chunks are compressed and encrypted like any other entry. Archives
+    /// with chunked entries set `HEADER_FLAG_CHUNKED`; readers that predate
+    /// it find a chunked entry's CRC32 does not match its list and fail with
+    /// `CrcMismatch` instead of returning the list. Not available in the
+    /// v0.x format.
+    pub fn with_cdc_dedup(mut self, config: ChunkerConfig) -> Self {
+        self.chunker = Some(config);
+        self
+    }
+
+    /// Store a SHA-256 digest (truncated to 128 bits) for each entry
+    ///
+    /// The digest lives in the central directory entry and is verified by
+    /// `ArchiveReader::read_file` in addition to the CRC32. Readers that predate
+    /// this flag ignore the digest and still read the archive.
+    pub fn with_strong_checksums(mut self, enabled: bool) -> Self {
+        self.strong_checksums = enabled;
+        self
+    }
+
+    /// Record a BLAKE3 digest of each entry's uncompressed content
+    ///
+    /// The central directory entry has no room for a 32-byte digest, so the
+    /// digests go into a table at `BLAKE3_TABLE_PATH` when the archive is
+    /// finalized, and the header gets `HEADER_FLAG_BLAKE3`.
+    /// `ArchiveReader` attaches them to the entries (`EntryInfo::blake3`) and
+    /// verifies them on every read in addition to the CRC32, so content changed
+    /// to keep its CRC32 is still rejected. The table is an ordinary entry, so
+    /// like `with_strong_checksums` this does not stop anyone who rewrites the
+    /// digests as well. Entries added pre-compressed or raw carry no digest,
+    /// since their content is never seen uncompressed.
+    pub fn with_blake3(mut self, enabled: bool) -> Self {
+        self.blake3 = enabled;
+        self
+    }
+
+    /// Record a CRC32 of each entry's stored (compressed) payload
+    ///
+    /// The checksums go into a table at `COMPRESSED_CRC_PATH` when the archive
+    /// is finalized, so `ArchiveReader::verify_fast` can check the archive for
+    /// damage without decompressing anything. The CRC32 of the uncompressed
+    /// content is stored as always. With per-file encryption the checksums
+    /// cover the ciphertext, but the table is encrypted like any other entry,
+    /// so checking still needs the key.
+    pub fn with_compressed_checksums(mut self, enabled: bool) -> Self {
+        self.compressed_checksums = enabled.then(Vec::new);
+        self
+    }
+
+    /// Test-decode the start of every `add_precompressed` payload
+    ///
+    /// The first 64 KB of content is decompressed before the entry is
+    /// written, so a payload in the wrong format or a truncated stream is
+    /// rejected up front instead of failing on read. Payloads that decode in
+    /// full within that window are also checked against their declared CRC32.
+    pub fn with_precompressed_verification(mut self, enabled: bool) -> Self {
+        self.verify_precompressed = enabled;
+        self
+    }
+
+    /// Report progress while adding files and finalizing
+    ///
+    /// The callback receives `FileStarted`, `BytesProcessed` and `FileFinished`
+    /// for every file (directories are silent), and `Finalizing` once
+    /// `finalize()` starts. Frame-compressed files report progress per frame, so
+    /// multi-gigabyte files keep reporting while they are compressed. Without a
+    /// callback no events are built.
+    ///
+    /// If the callback panics, the panic is caught, the callback is dropped, and
+    /// the current call returns `EngramError::CallbackPanicked`. Files added
+    /// before the panic are intact, so a panic from `add_file` still leaves an
+    /// archive that can be finalized; a panic on `Finalizing` aborts `finalize()`.
+    pub fn with_progress<F>(mut self, callback: F) -> Self
+    where
+        F: FnMut(ProgressEvent) + Send + 'static,
+    {
+        self.progress = Progress::new(Box::new(callback));
+        self
+    }
+
+    /// Abort writing when `token` is cancelled
+    ///
+    /// The token is checked before each entry, between frames of large files,
+    /// and while finalizing. A cancelled operation returns
+    /// `EngramError::Cancelled` and deletes the partially written archive; the
+    /// writer should then be dropped.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = token;
+        self
+    }
+
+    /// Stamp every subsequently added entry with `modified_time` (Unix epoch seconds)
+    ///
+    /// Combined with identical inputs this makes archive creation reproducible:
+    /// unencrypted archives come out byte-identical. The timestamp also replaces
+    /// `metadata.created` in manifests passed to `add_manifest`, unless the
+    /// manifest is already signed. Explicit times given to `add_file_with_time`
+    /// and timestamps imported from ZIP entries take precedence.
+    pub fn with_fixed_time(mut self, modified_time: u64) -> Self {
+        self.fixed_time = Some(modified_time);
+        self
+    }
+
+    /// Compress Zstd entries with a shared dictionary
+    ///
+    /// Many small, similar files (e.g. JSON documents) compress far better
+    /// against a dictionary trained on samples of them; see `train_dictionary`.
+    /// While a dictionary is set, `add_file` also Zstd-compresses files below
+    /// the usual 4KB threshold. Entries using the dictionary carry
+    /// `ENTRY_FLAG_ZSTD_DICTIONARY`, and the dictionary is stored uncompressed
+    /// at `ZSTD_DICTIONARY_PATH` when the archive is finalized. Frame-compressed
+    /// files (>= 50MB) do not use the dictionary.
+    pub fn with_zstd_dictionary(mut self, dictionary: Vec<u8>) -> Self {
+        self.zstd_dictionary = Some(dictionary);
+        self
+    }
+
+    /// Compress Zstd entries (and frames of large files) at `level`
+    ///
+    /// Defaults to `DEFAULT_ZSTD_LEVEL`. Higher levels trade write speed for
+    /// smaller archives; reading speed is about the same. Out-of-range levels
+    /// are clamped by Zstd, while `ArchiveWriterBuilder` rejects them.
+    pub fn with_zstd_level(mut self, level: i32) -> Self {
+        self.zstd_level = level;
+        self
+    }
+
+    /// Split LZ4 and Zstd files of `options.min_file_size` and up into frames
+    /// of `options.frame_size`
+    ///
+    /// Defaults to 64KB frames for files of 50MB and up (`FrameOptions::default`).
+    /// Takes effect for files added afterwards, so one archive can hold
+    /// entries framed both ways. Fails with `EngramError::InvalidOptions` if
+    /// `options` is out of range (see `FrameOptions::validate`). Only v1.1
+    /// archives record the frame size; other options than the default fail
+    /// with `InvalidOptions` when the next file is added to an archive pinned
+    /// to an older format with `with_format_version`.
+    pub fn with_frame_options(mut self, options: FrameOptions) -> Result<Self> {
+        options.validate()?;
+        self.frame_options = options;
+        Ok(self)
+    }
+
+    /// Compress every file added with `add_file`, `add_file_from_disk` or
+    /// `import_zip` with `compression`
+    ///
+    /// Replaces the per-file choice by size and extension. As with
+    /// `add_file_with_compression`, files that would not get smaller are
+    /// still stored uncompressed. Shorthand for
+    /// `with_compression_policy(Box::new(ForceMethod(compression)))`.
+    pub fn with_default_compression(self, compression: CompressionMethod) -> Self {
+        self.with_compression_policy(Box::new(ForceMethod(compression)))
+    }
+
+    /// Choose the compression of files added with `add_file`,
+    /// `add_file_with_metadata`, `add_file_from_disk` or `import_zip` with
+    /// `policy`
+    ///
+    /// Replaces the built-in heuristics (`DefaultPolicy`) and any method set
+    /// with `with_default_compression`; the last of the two calls wins.
+    pub fn with_compression_policy(mut self, policy: Box<dyn CompressionPolicy>) -> Self {
+        self.compression_policy = Some(policy);
+        self
+    }
+
+    /// Record an application schema version in the file header
+    ///
+    /// Read back with `ArchiveReader::content_version`; archives written
+    /// without one report 0. The header is never encrypted. The version is
+    /// also copied into the End Record, for `EndRecord::read_from_end`.
+    pub fn with_content_version(mut self, content_version: u32) -> Self {
+        self.content_version = content_version;
+        self
+    }
+
+    /// Record an application-defined label in the file header
+    ///
+    /// Read back with `ArchiveReader::label`; see `ArchiveLabel`.
+    pub fn with_label(mut self, label: [u8; ARCHIVE_LABEL_LEN]) -> Self {
+        self.label = ArchiveLabel(label);
+        self
+    }
+
+    /// Write central directory entries sorted by path instead of insertion order
+    ///
+    /// Sets `HEADER_FLAG_SORTED_DIRECTORY`. Readers then find entries by
+    /// binary search over the fixed-size records rather than hashing every
+    /// path, and `ArchiveReader::open_lazy` can look entries up without
+    /// parsing the whole directory. `ArchiveReader::list_files` returns paths
+    /// in sorted order for such archives.
+    pub fn with_sorted_directory(mut self) -> Self {
+        self.sorted_directory = true;
+        self
+    }
+
+    /// Produce bit-identical archives from identical inputs
+    ///
+    /// When enabled, central directory entries are sorted by path at
+    /// `finalize` (as with `with_sorted_directory`), whatever order the files
+    /// were added in; payloads stay in insertion order. Entry times are taken,
+    /// in order of precedence, from an explicit time (`add_file_with_time`,
+    /// `FileMetadata::modified_time`, ZIP imports), the `with_fixed_time`
+    /// timestamp, `SOURCE_DATE_EPOCH` from the environment, or else 0.
+    /// Manifests built with `Manifest::new` pick up `SOURCE_DATE_EPOCH` on
+    /// their own.
+    ///
+    /// Encrypted archives still draw random nonces, and recipient key
+    /// wrapping always draws fresh randomness; for byte-level tests of
+    /// encrypted output see `with_rng`.
+    pub fn with_deterministic_output(mut self, enabled: bool) -> Self {
+        self.deterministic = enabled;
+        self
+    }
+
+    /// Draw AES-GCM nonces from `rng` instead of the system RNG
+    ///
+    /// Only cryptographically secure generators are accepted. Two writers
+    /// given the same seed produce the same nonces, so with the same key and
+    /// `with_deterministic_output` they write byte-identical encrypted
+    /// archives; that is what a seeded RNG is for (tests). Never reuse a seed
+    /// to encrypt different data under the same key in production, as
+    /// repeated nonces break AES-GCM. Without this the system RNG is used.
+    /// Recipient key wrapping still draws from the system RNG.
+    pub fn with_rng<R>(mut self, mut rng: R) -> Self
+    where
+        R: RngCore + CryptoRng + Send + 'static,
+    {
+        self.nonce_source = Some(Box::new(move || {
+            let mut nonce = [0u8; 12];
+            rng.fill_bytes(&mut nonce);
+            nonce
+        }));
+        self
+    }
+
+    /// Take AES-GCM nonces from `source` instead of the system RNG
+    ///
+    /// **Insecure; for tests only.** Reusing a nonce with the same key breaks
+    /// AES-GCM confidentiality and integrity, so a source that repeats
+    /// values must never be used for real data. Meant for reproducing
+    /// encrypted archives byte for byte together with
+    /// `with_deterministic_output`.
+    pub fn with_insecure_nonce_source<F>(mut self, source: F) -> Self
+    where
+        F: FnMut() -> [u8; 12] + Send + 'static,
+    {
+        self.nonce_source = Some(Box::new(source));
+        self
+    }
+
+    /// Finalize the archive when the writer is dropped without `finalize`
+    ///
+    /// A writer that goes out of scope with entries added but never
+    /// finalized otherwise leaves a file that fails to open. With this set,
+    /// dropping it finishes the archive as `finalize` would, best effort:
+    /// errors cannot be returned from `drop`, so they are only logged (and
+    /// reported to `with_drop_warning`). Nothing is finalized while the
+    /// thread is panicking, where the entries added so far may be incomplete.
+    /// Call `finalize` explicitly to see errors and the summary.
+    pub fn with_finalize_on_drop(mut self, enabled: bool) -> Self {
+        self.finalize_on_drop = enabled;
+        self
+    }
+
+    /// Call `warning` if the writer is dropped with entries but unfinalized
+    ///
+    /// It receives the number of entries that were added. The archive left
+    /// behind is incomplete and cannot be opened. A warning is also logged
+    /// through `tracing` either way; this hook is for callers that want to
+    /// treat the mistake as an error, e.g. in tests.
+    pub fn with_drop_warning<F>(mut self, warning: F) -> Self
+    where
+        F: FnOnce(usize) + Send + 'static,
+    {
+        self.drop_warning = Some(Box::new(warning));
+        self
+    }
+
+    /// Sign the archive's structure with `signing_key` in a trailer
+    ///
+    /// At `finalize`, an Ed25519 signature over the End Record and the
+    /// SHA-256 of the central directory is stored in a fixed-size block
+    /// between the central directory and the recipients block (immediately
+    /// before the ENDR without recipients), together with the public key and
+    /// a timestamp. Check it with `ArchiveReader::verify_endr_signature`. This
+    /// makes changes to entry names, sizes and checksums evident without a
+    /// signed manifest; payloads are covered through their CRC32 and SHA-256
+    /// in the directory.
+    ///
+    /// Readers that do not know the block still open the archive, since no
+    /// offset they rely on moves. For the same reason it cannot be combined
+    /// with archive encryption, whose payload length older readers derive from
+    /// the ENDR position, or with the v0.x format, which has no End Record;
+    /// both fail with `EngramError::InvalidOptions` when the next file is
+    /// added or the archive is finalized.
+    pub fn with_endr_signature(mut self, signing_key: &SigningKey) -> Self {
+        self.endr_signing_key = Some(signing_key.clone());
         self
     }
 
+    /// Write an archive in an older format version for readers that predate v1.0
+    ///
+    /// Accepts the current version, 1.0 and 0.4. A v1.0 archive stores frames
+    /// of files of 50MB and up without a frame index, so readers cannot seek
+    /// within them. A v0.4 archive has no LOCA headers and no End Record:
+    /// central directory offsets point straight at the stored payload, and
+    /// files of 50MB and up are compressed as a single stream instead of in
+    /// frames. Features the v0.4 layout cannot express
+    /// (per-file encryption, recipients, Zstd dictionaries) fail with
+    /// `EngramError::InvalidOptions` when the next file is added or the archive
+    /// is finalized. Other versions fail with `UnsupportedVersion`.
+    pub fn with_format_version(mut self, major: u16, minor: u16) -> Result<Self> {
+        match (major, minor) {
+            (FORMAT_VERSION_MAJOR, FORMAT_VERSION_MINOR) | (1, 0) | (0, 4) => {
+                self.format_version = (major, minor);
+                Ok(self)
+            }
+            _ => Err(EngramError::UnsupportedVersion(major << 8 | minor)),
+        }
+    }
+
+    /// Whether the archive is written in the v0.x layout
+    fn is_legacy(&self) -> bool {
+        self.format_version.0 == 0
+    }
+
+    /// Layout of frame-compressed payloads in the format being written
+    fn frame_layout(&self) -> FrameLayout {
+        FrameLayout::for_version(self.format_version.0, self.format_version.1)
+    }
+
+    /// Reject options that need a newer format than the one being written
+    fn check_format_features(&self) -> Result<()> {
+        if self.endr_signing_key.is_some() && self.encryption_mode == EncryptionMode::Archive {
+            return Err(EngramError::InvalidOptions(
+                "ENDR signatures cannot be combined with archive encryption".to_string(),
+            ));
+        }
+        if self.frame_options != FrameOptions::default()
+            && self.frame_layout() != FrameLayout::Indexed
+        {
+            return Err(EngramError::InvalidOptions(format!(
+                "Custom frame options require format v1.1 or later, but the archive is pinned to v{}.{}",
+                self.format_version.0, self.format_version.1
+            )));
+        }
+        if let Some(chunker) = &self.chunker {
+            chunker.validate()?;
+        }
+        if !self.is_legacy() {
+            return Ok(());
+        }
+        let unsupported = if self.encryption_mode == EncryptionMode::PerFile {
+            "Per-file encryption"
+        } else if self.chunker.is_some() {
+            "Chunked deduplication"
+        } else if !self.recipients.is_empty() {
+            "Recipients"
+        } else if self.zstd_dictionary.is_some() {
+            "Zstd dictionaries"
+        } else if self.endr_signing_key.is_some() {
+            "ENDR signatures"
+        } else {
+            return Ok(());
+        };
+        Err(EngramError::InvalidOptions(format!(
+            "{} require format v1.0 or later, but the archive is pinned to v{}.{}",
+            unsupported, self.format_version.0, self.format_version.1
+        )))
+    }
+
+    /// Let the holder of a 32-byte symmetric key read the archive
+    ///
+    /// The encryption key is wrapped for each recipient (AES key wrap,
+    /// RFC 3394) and stored in a recipients block, so every recipient opens
+    /// the archive with their own key through
+    /// `ArchiveReader::with_recipient_key`. If no encryption was configured,
+    /// archive-level encryption is enabled with a random key; a key set with
+    /// `with_archive_encryption` or `with_per_file_encryption` is wrapped
+    /// instead and keeps working on its own.
+    pub fn add_recipient_key(&mut self, id: &str, key: &[u8; 32]) -> Result<()> {
+        self.push_recipient(id, RecipientKey::Symmetric(SecretKey::new(*key)))
+    }
+
+    /// Let the holder of an X25519 secret key read the archive
+    ///
+    /// Like `add_recipient_key`, but the encryption key is sealed to
+    /// `public_key` with an ephemeral X25519 key agreement. Derive the public
+    /// key with `recipient_public_key`.
+    pub fn add_recipient_pubkey(&mut self, id: &str, public_key: &[u8; 32]) -> Result<()> {
+        self.push_recipient(id, RecipientKey::PublicKey(*public_key))
+    }
+
+    /// Let the holder of the X25519 secret key for `public_key` read the archive
+    ///
+    /// `add_recipient_pubkey` with the hex-encoded public key as the recipient
+    /// id. The recipient opens the archive with
+    /// `ArchiveReader::with_private_key`, without knowing the id.
+    pub fn add_recipient(&mut self, public_key: &[u8; 32]) -> Result<()> {
+        self.push_recipient(
+            &hex::encode(public_key),
+            RecipientKey::PublicKey(*public_key),
+        )
+    }
+
+    /// Let anyone who knows `password` read the archive
+    ///
+    /// Like `add_recipient_key`, with the recipient's key derived from the
+    /// password by PBKDF2-HMAC-SHA256 over a random salt, with
+    /// `PASSWORD_ITERATIONS` iterations. The salt and iteration count are
+    /// stored with the recipient, so `ArchiveReader::open_with_password`
+    /// needs only the password.
+    pub fn add_recipient_password(&mut self, id: &str, password: &str) -> Result<()> {
+        self.add_recipient_password_with_iterations(id, password, PASSWORD_ITERATIONS)
+    }
+
+    /// `add_recipient_password` with a chosen PBKDF2 iteration count
+    ///
+    /// More iterations make guessing the password slower, and opening the
+    /// archive too. At least `MIN_PASSWORD_ITERATIONS` are required.
+    pub fn add_recipient_password_with_iterations(
+        &mut self,
+        id: &str,
+        password: &str,
+        iterations: u32,
+    ) -> Result<()> {
+        recipients::validate_id(id)?;
+        let key = RecipientKey::from_password(password, iterations)?;
+        self.push_recipient(id, key)
+    }
+
+    fn push_recipient(&mut self, id: &str, key: RecipientKey) -> Result<()> {
+        recipients::validate_id(id)?;
+        if self.recipients.iter().any(|(existing, _)| existing == id) {
+            return Err(EngramError::InvalidOptions(format!(
+                "Recipient already added: {}",
+                id
+            )));
+        }
+        if self.encryption_mode == EncryptionMode::None {
+            self.encryption_mode = EncryptionMode::Archive;
+            self.encryption_key = Some(SecretKey::new(rand::random()));
+        }
+        self.recipients.push((id.to_string(), key));
+        self.check_format_features()
+    }
+
     /// Add a file to the archive with automatic compression selection
-    pub fn add_file(&mut self, path: &str, data: &[u8]) -> Result<()> {
-        // Determine compression method based on file size and type
-        let compression = Self::select_compression(path, data.len());
+    ///
+    /// Returns the compression method actually used, which is `None` when
+    /// compressing would not have made the payload smaller.
+    pub fn add_file(&mut self, path: &str, data: &[u8]) -> Result<CompressionMethod> {
+        let compression = self.auto_compression(path, data);
         self.add_file_with_compression(path, data, compression)
     }
 
+    /// Add a file with explicit metadata and automatic compression selection
+    ///
+    /// Use this to mark in-memory data executable or record its MIME type,
+    /// which `add_file` cannot express. Returns the compression method
+    /// actually used.
+    ///
+    /// A MIME type from `BUILTIN_MIME_TYPES` is stored as a 16-bit id in the
+    /// central directory entry; any other is added to the archive's
+    /// `.engram/mime.tbl` and the entry refers to it. Invalid types (empty,
+    /// without a `/`, containing control characters or longer than
+    /// `MAX_MIME_TYPE_LENGTH` bytes) fail with `InvalidOptions`. v0.x writers
+    /// (`with_format_version(0, 4)`) ignore MIME types.
+    ///
+    /// Non-empty `extra` pairs are stored in the archive's
+    /// `ENTRY_METADATA_PATH`, which readers without support for it see as an
+    /// ordinary internal file. Keys must not be empty, and the encoded pairs
+    /// of one entry (two bytes per count and length, plus the UTF-8 bytes) may
+    /// not exceed `MAX_ENTRY_METADATA_SIZE`; either fails with `InvalidOptions`
+    /// before anything is written.
+    ///
+    /// ```no_run
+    /// use engram_rs::{ArchiveWriter, FileMetadata};
+    ///
+    /// let mut writer = ArchiveWriter::create("tools.eng")?;
+    /// let metadata = FileMetadata {
+    ///     executable: true,
+    ///     mime_type: Some("text/x-shellscript"),
+    ///     ..FileMetadata::default()
+    /// };
+    /// writer.add_file_with_metadata("bin/setup.sh", b"#!/bin/sh\n", metadata)?;
+    /// writer.finalize()?;
+    /// # Ok::<(), engram_rs::EngramError>(())
+    /// ```
+    pub fn add_file_with_metadata(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        metadata: FileMetadata,
+    ) -> Result<CompressionMethod> {
+        Self::check_user_path(path)?;
+        let extra = match metadata.extra {
+            Some(extra) if !extra.is_empty() => Some(entry_metadata::encode_block(path, extra)?),
+            _ => None,
+        };
+        let mime_id = match metadata.mime_type {
+            Some(mime) => self.register_mime_type(mime)?,
+            None => 0,
+        };
+        let compression = self.auto_compression(path, data);
+        let defaults = self.default_attributes();
+        let attributes = EntryAttributes {
+            modified_time: metadata.modified_time.unwrap_or(defaults.modified_time),
+            flags: if metadata.executable {
+                ENTRY_FLAG_EXECUTABLE
+            } else {
+                0
+            },
+            mime_id,
+            ..defaults
+        };
+        let compression = self.write_entry(path, data, compression, attributes)?;
+        if let Some(block) = extra {
+            self.entry_metadata.insert(normalize_path(path), block);
+        }
+        Ok(compression)
+    }
+
     /// Add a file with specific compression method
+    ///
+    /// Returns the compression method actually used; the requested method falls
+    /// back to `None` if it does not reduce the size. `CompressionMethod::Deflate`
+    /// is never chosen automatically, so request it here (or with
+    /// `with_default_compression`) for readers that only know Deflate, such as
+    /// the legacy engram-core crate.
     pub fn add_file_with_compression(
         &mut self,
         path: &str,
         data: &[u8],
         compression: CompressionMethod,
+    ) -> Result<CompressionMethod> {
+        Self::check_user_path(path)?;
+        self.write_entry(path, data, compression, self.default_attributes())
+    }
+
+    /// Add a file with specific compression method and modification time
+    ///
+    /// `modified_time` is in Unix epoch seconds. Returns the compression method
+    /// actually used.
+    pub fn add_file_with_time(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        compression: CompressionMethod,
+        modified_time: u64,
+    ) -> Result<CompressionMethod> {
+        Self::check_user_path(path)?;
+        let attributes = EntryAttributes {
+            modified_time,
+            ..EntryAttributes::default()
+        };
+        self.write_entry(path, data, compression, attributes)
+    }
+
+    /// Add a file whose payload is already compressed, such as one read by
+    /// `ArchiveReader::read_raw_entry`
+    ///
+    /// `raw.data` is written as is. Its compression method, sizes, CRC32 and
+    /// flags are recorded as given and not checked against the data, so a
+    /// mismatch only shows when the entry is read. The modification time is
+    /// set like `add_file`'s, and the entry is never deduplicated.
+    ///
+    /// Unencrypted payloads are encrypted when this archive uses per-file
+    /// encryption. Encrypted ones are stored as they are, which only works in
+    /// a per-file encrypted archive with the same key and under the path the
+    /// payload was encrypted for; in other archives they are rejected with
+    /// `InvalidOptions`. So are payloads compressed against a Zstd dictionary
+    /// and framed payloads in archives older than v1.1.
+    pub fn add_raw_entry(&mut self, path: &str, raw: RawEntry) -> Result<()> {
+        Self::check_user_path(path)?;
+        let path = normalize_path(path);
+        validate_path(&path)?;
+
+        if raw.flags & !ENTRY_FLAG_FRAMED != 0 {
+            return Err(EngramError::InvalidOptions(format!(
+                "Raw entry {} needs its Zstd dictionary or has unknown flags ({:#04x})",
+                path, raw.flags
+            )));
+        }
+        if raw.flags & ENTRY_FLAG_FRAMED != 0 && self.frame_layout() != FrameLayout::Indexed {
+            return Err(EngramError::InvalidOptions(format!(
+                "Raw entry {} is framed, which needs format v1.1 or later",
+                path
+            )));
+        }
+        if raw.encrypted && self.encryption_mode != EncryptionMode::PerFile {
+            return Err(EngramError::InvalidOptions(format!(
+                "Raw entry {} is encrypted, which needs per-file encryption",
+                path
+            )));
+        }
+        if raw.compression == CompressionMethod::None
+            && !raw.encrypted
+            && raw.data.len() as u64 != raw.uncompressed_size
+        {
+            return Err(EngramError::InvalidOptions(format!(
+                "Raw entry {} is stored uncompressed but is {} bytes, not {}",
+                path,
+                raw.data.len(),
+                raw.uncompressed_size
+            )));
+        }
+
+        let result = self.transaction(|writer| {
+            writer.check_format_features()?;
+            let payload = if writer.encryption_mode == EncryptionMode::PerFile && !raw.encrypted {
+                writer.encrypt_file_data(&raw.data, &entry_aad(&path, raw.uncompressed_size))?
+            } else {
+                raw.data
+            };
+            let entry = EntryInfo {
+                path,
+                data_offset: 0,
+                uncompressed_size: raw.uncompressed_size,
+                compressed_size: payload.len() as u64,
+                crc32: raw.crc32,
+                modified_time: writer.default_attributes().modified_time,
+                compression: raw.compression,
+                flags: raw.flags,
+                mode: 0,
+                mime_id: 0,
+                sha256: None,
+                blake3: None,
+            };
+            writer.write_stored_entry_inner(&entry, &payload)
+        });
+        self.discard_if_cancelled(result).map(|_| ())
+    }
+
+    /// Add a file compressed outside engram, stored as is
+    ///
+    /// `compressed` must be a complete stream of `method` (a Zstd frame, an
+    /// LZ4 block with its size prepended as written by `lz4_flex`, or raw
+    /// Deflate) decoding to `uncompressed_size` bytes with CRC32
+    /// `crc32_of_uncompressed`. `read_file` decompresses it like any other
+    /// entry. The declared values are trusted unless
+    /// `with_precompressed_verification` is enabled.
+    ///
+    /// `CompressionMethod::None` is rejected (use `add_file`), as are unknown
+    /// methods and payloads over ten times larger than the declared content
+    /// (plus `PRECOMPRESSED_OVERHEAD`), all with `InvalidOptions`. Otherwise
+    /// this behaves like `add_raw_entry`.
+    pub fn add_precompressed(
+        &mut self,
+        path: &str,
+        compressed: &[u8],
+        method: CompressionMethod,
+        uncompressed_size: u64,
+        crc32_of_uncompressed: u32,
     ) -> Result<()> {
+        match method {
+            CompressionMethod::None => {
+                return Err(EngramError::InvalidOptions(format!(
+                    "Pre-compressed {} declares no compression; use add_file",
+                    path
+                )));
+            }
+            CompressionMethod::Unknown(value) => {
+                return Err(EngramError::InvalidOptions(format!(
+                    "Pre-compressed {} uses unknown compression method {}",
+                    path, value
+                )));
+            }
+            _ => {}
+        }
+        let max_len = uncompressed_size
+            .saturating_mul(10)
+            .saturating_add(PRECOMPRESSED_OVERHEAD);
+        if compressed.len() as u64 > max_len {
+            return Err(EngramError::InvalidOptions(format!(
+                "Pre-compressed {} is {} bytes, too large for {} bytes of content",
+                path,
+                compressed.len(),
+                uncompressed_size
+            )));
+        }
+
+        let raw = RawEntry {
+            data: compressed.to_vec(),
+            compression: method,
+            uncompressed_size,
+            crc32: crc32_of_uncompressed,
+            encrypted: false,
+            flags: 0,
+        };
+        if self.verify_precompressed {
+            Self::check_precompressed(path, &raw)?;
+        }
+        self.add_raw_entry(path, raw)
+    }
+
+    /// Decode the first `FRAME_SIZE` bytes of a pre-compressed payload
+    fn check_precompressed(path: &str, raw: &RawEntry) -> Result<()> {
+        let entry = EntryInfo {
+            path: path.to_string(),
+            data_offset: 0,
+            uncompressed_size: raw.uncompressed_size,
+            compressed_size: raw.data.len() as u64,
+            crc32: raw.crc32,
+            modified_time: 0,
+            compression: raw.compression,
+            flags: 0,
+            mode: 0,
+            mime_id: 0,
+            sha256: None,
+            blake3: None,
+        };
+        let len = (raw.uncompressed_size as usize).min(FRAME_SIZE);
+        let prefix =
+            ArchiveReader::decode_prefix(&entry, FrameLayout::None, &raw.data[..], None, len)
+                .map_err(|e| e.in_entry(path, 0))?;
+        if prefix.len() < len {
+            return Err(EngramError::InvalidOptions(format!(
+                "Pre-compressed {} decodes to {} bytes, not {}",
+                path,
+                prefix.len(),
+                raw.uncompressed_size
+            )));
+        }
+        if len as u64 == raw.uncompressed_size {
+            let actual = crc32fast::hash(&prefix);
+            if actual != raw.crc32 {
+                return Err(EngramError::CrcMismatch {
+                    path: path.to_string(),
+                    expected: raw.crc32,
+                    actual,
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an explicit directory entry
+    ///
+    /// Directories are stored with the `ENTRY_FLAG_DIRECTORY` flag, zero size and
+    /// no data payload, so empty directories survive a round trip. A trailing `/`
+    /// in `path` is accepted and stripped.
+    pub fn add_directory(&mut self, path: &str) -> Result<()> {
+        let normalized = normalize_path(path);
+        let trimmed = normalized.trim_end_matches('/');
+        Self::check_user_path(trimmed)?;
+
+        let attributes = EntryAttributes {
+            flags: ENTRY_FLAG_DIRECTORY,
+            ..self.default_attributes()
+        };
+        self.write_entry(trimmed, &[], CompressionMethod::None, attributes)?;
+        Ok(())
+    }
+
+    /// Add `new_path` as another name for the already added `existing_path`
+    ///
+    /// Like a hard link: the new entry points at the existing entry's LOCA
+    /// header and data, copying its sizes, CRC32, compression, time and mode,
+    /// and carries `ENTRY_FLAG_ALIAS` (plus `ENTRY_FLAG_DEDUPLICATED`, so
+    /// readers treat it like a deduplicated copy). Nothing is hashed or
+    /// written until `finalize`. The alias is bound to the payload
+    /// `existing_path` has now; adding `existing_path` again later does not
+    /// change what the alias reads.
+    ///
+    /// Fails with `FileNotFound` if `existing_path` has not been added yet and
+    /// with `InvalidOptions` if it is a directory, or with per-file
+    /// encryption, whose payloads are bound to their own path (see
+    /// `HEADER_FLAG_ENTRY_AAD`) and would not decrypt under another.
+    pub fn add_alias(&mut self, new_path: &str, existing_path: &str) -> Result<()> {
+        self.cancellation.check()?;
+        if self.encryption_mode == EncryptionMode::PerFile {
+            return Err(EngramError::InvalidOptions(
+                "Aliases are not supported with per-file encryption".to_string(),
+            ));
+        }
+        Self::check_user_path(new_path)?;
+        let new_path = normalize_path(new_path);
+        validate_path(&new_path)?;
+
+        let existing = normalize_path(existing_path);
+        let target = self
+            .entries
+            .iter()
+            .rev()
+            .find(|entry| entry.path == existing)
+            .ok_or_else(|| EngramError::FileNotFound(existing.clone()))?;
+        if target.is_directory() {
+            return Err(EngramError::InvalidOptions(format!(
+                "Cannot alias directory: {}",
+                existing
+            )));
+        }
+
+        let entry = EntryInfo {
+            path: new_path,
+            flags: target.flags | ENTRY_FLAG_DEDUPLICATED | ENTRY_FLAG_ALIAS,
+            ..target.clone()
+        };
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    /// Write a LOCA header and payload, and record the central directory entry
+    ///
+    /// Returns the compression method recorded for the entry.
+    pub(crate) fn write_entry(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        compression: CompressionMethod,
+        attributes: EntryAttributes,
+    ) -> Result<CompressionMethod> {
+        let result = self
+            .transaction(|writer| writer.write_entry_inner(path, data, compression, attributes));
+        self.discard_if_cancelled(result)
+    }
+
+    fn write_entry_inner(
+        &mut self,
+        path: &str,
+        data: &[u8],
+        compression: CompressionMethod,
+        attributes: EntryAttributes,
+    ) -> Result<CompressionMethod> {
+        self.cancellation.check()?;
+        self.check_format_features()?;
+
+        let EntryAttributes {
+            modified_time,
+            mode,
+            flags,
+            mime_id,
+        } = attributes;
+        // Readers unaware of MIME ids apply a non-zero mode field as is, so
+        // record the permissions they would otherwise have defaulted to
+        let mode = match mode {
+            0 if mime_id != 0 && flags & ENTRY_FLAG_EXECUTABLE != 0 => 0o100755,
+            0 if mime_id != 0 => 0o100644,
+            mode => mode,
+        };
+
         // Normalize path (cross-platform: always use forward slashes)
         let normalized_path = normalize_path(path);
+        validate_path(&normalized_path)?;
+
+        let is_directory = flags & ENTRY_FLAG_DIRECTORY != 0;
+        let total = data.len() as u64;
+        if !is_directory {
+            self.progress.emit(|| ProgressEvent::FileStarted {
+                path: normalized_path.clone(),
+                size: total,
+            })?;
+        }
+
+        // Content digest for deduplication and/or strong checksums
+        let digest: Option<[u8; 32]> =
+            if !is_directory && (self.strong_checksums || self.dedup_index.is_some()) {
+                Some(Sha256::digest(data).into())
+            } else {
+                None
+            };
+        let (flags, sha256) = match digest {
+            Some(digest) if self.strong_checksums => {
+                let mut prefix = [0u8; SHA256_PREFIX_LEN];
+                prefix.copy_from_slice(&digest[..SHA256_PREFIX_LEN]);
+                (flags | ENTRY_FLAG_SHA256, Some(prefix))
+            }
+            _ => (flags & !ENTRY_FLAG_SHA256, None),
+        };
+        let blake3 = (self.blake3 && !is_directory).then(|| *blake3::hash(data).as_bytes());
+        // Set below if this entry's payload uses the dictionary, frames or chunks
+        let flags = flags & !(ENTRY_FLAG_ZSTD_DICTIONARY | ENTRY_FLAG_FRAMED | ENTRY_FLAG_CHUNKED);
+
+        // Deduplication: point at the payload of an identical earlier file,
+        // unless payloads are bound to their path
+        let digest = digest.filter(|_| !data.is_empty());
+        let shareable = self.encryption_mode != EncryptionMode::PerFile;
+        if let (Some(index), Some(digest), true) = (&self.dedup_index, &digest, shareable) {
+            if let Some(&first) = index.get(digest) {
+                let original = &self.entries[first];
+                let compression = original.compression;
+                let compressed_size = original.compressed_size;
+                let entry = EntryInfo {
+                    path: normalized_path,
+                    data_offset: original.data_offset,
+                    uncompressed_size: original.uncompressed_size,
+                    compressed_size: original.compressed_size,
+                    crc32: original.crc32,
+                    modified_time,
+                    compression: original.compression,
+                    flags: flags
+                        | ENTRY_FLAG_DEDUPLICATED
+                        | (original.flags
+                            & (ENTRY_FLAG_ZSTD_DICTIONARY
+                                | ENTRY_FLAG_FRAMED
+                                | ENTRY_FLAG_CHUNKED)),
+                    mode,
+                    mime_id,
+                    sha256,
+                    blake3,
+                };
+                self.entries.push(entry);
+
+                let path = &self.entries[self.entries.len() - 1].path;
+                self.progress.emit(|| ProgressEvent::BytesProcessed {
+                    path: path.clone(),
+                    bytes_done: total,
+                    total,
+                })?;
+                self.progress.emit(|| ProgressEvent::FileFinished {
+                    path: path.clone(),
+                    compressed_size,
+                })?;
+                return Ok(compression);
+            }
+        }
+
+        if let Some(config) = self.chunker {
+            if !is_directory && data.len() > config.max_size && !is_reserved_path(&normalized_path)
+            {
+                let entry = EntryInfo {
+                    path: normalized_path,
+                    data_offset: 0,
+                    uncompressed_size: total,
+                    compressed_size: 0,
+                    crc32: crc32fast::hash(data),
+                    modified_time,
+                    compression: CompressionMethod::None,
+                    flags: flags | ENTRY_FLAG_CHUNKED,
+                    mode,
+                    mime_id,
+                    sha256,
+                    blake3,
+                };
+                self.write_chunked_entry(entry, data, compression, &config)?;
+                if let (Some(index), Some(digest)) = (&mut self.dedup_index, digest) {
+                    index.insert(digest, self.entries.len() - 1);
+                }
+                return Ok(CompressionMethod::None);
+            }
+        }
 
         // CRITICAL: Compress FIRST, then encrypt (if per-file mode)
-        let (compressed_data, actual_compression) = self.compress_data(data, compression)?;
+        let layout = self.frame_layout();
+        let frame_options = self.frame_options;
+        let progress = &mut self.progress;
+        let cancellation = &self.cancellation;
+        let dictionary = self.zstd_dictionary.as_deref();
+        let (compressed_data, actual_compression) = Self::compress_data(
+            data,
+            compression,
+            dictionary,
+            self.zstd_level,
+            layout,
+            frame_options,
+            |bytes_done| {
+                cancellation.check()?;
+                if is_directory {
+                    return Ok(());
+                }
+                progress.emit(|| ProgressEvent::BytesProcessed {
+                    path: normalized_path.clone(),
+                    bytes_done,
+                    total,
+                })
+            },
+        )?;
+        let framed = layout != FrameLayout::None
+            && frame_options.applies_to(data.len())
+            && matches!(
+                actual_compression,
+                CompressionMethod::Lz4 | CompressionMethod::Zstd
+            );
+        let flags = if framed {
+            // v1.0 readers predate the flag; their frames are all 50MB and up
+            if layout == FrameLayout::Indexed {
+                flags | ENTRY_FLAG_FRAMED
+            } else {
+                flags
+            }
+        } else if actual_compression == CompressionMethod::Zstd && dictionary.is_some() {
+            flags | ENTRY_FLAG_ZSTD_DICTIONARY
+        } else {
+            flags
+        };
 
-        // Prepare final payload (encrypted if per-file mode)
-        let final_payload = if self.encryption_mode == EncryptionMode::PerFile {
-            self.encrypt_file_data(&compressed_data)?
+        // Prepare final payload (encrypted if per-file mode; directories have no payload)
+        let final_payload = if self.encryption_mode == EncryptionMode::PerFile
+            && flags & ENTRY_FLAG_DIRECTORY == 0
+        {
+            self.encrypt_file_data(&compressed_data, &entry_aad(&normalized_path, total))?
         } else {
             compressed_data
         };
@@ -101,17 +1594,11 @@ impl ArchiveWriter {
         // Calculate CRC32 of uncompressed data
         let crc32 = crc32fast::hash(data);
 
-        // Get current timestamp
-        let modified_time = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
         // Record offset to LOCAL ENTRY HEADER (v1.0 format)
         let entry_start_offset = self.current_offset;
 
         // Create and write Local Entry Header (LOCA)
-        let local_header = LocalEntryHeader::new(
+        let mut local_header = LocalEntryHeader::new(
             data.len() as u64,          // uncompressed_size
             final_payload.len() as u64, // compressed_size
             crc32,
@@ -119,13 +1606,32 @@ impl ArchiveWriter {
             actual_compression,
             normalized_path.clone(),
         );
+        local_header.flags = flags;
+        local_header.mode = mode | u32::from(mime_id) << 16;
+
+        // v0.x archives store the payload alone
+        let local_header = (!self.is_legacy()).then_some(local_header);
+        let header_size = local_header.as_ref().map_or(0, |h| h.header_size());
+
+        // Keep the entry in one volume when it fits in one
+        self.start_volume_for(header_size as u64 + final_payload.len() as u64)?;
 
-        let header_bytes_written = local_header.write_to(&mut self.writer)?;
-        self.current_offset += header_bytes_written as u64;
+        if let Some(local_header) = &local_header {
+            let header_bytes_written = local_header.write_to(&mut self.writer)?;
+            self.current_offset += header_bytes_written as u64;
+        }
 
         // Write file data after LOCA header
-        self.writer.write_all(&final_payload)?;
+        for chunk in final_payload.chunks(CANCEL_CHECK_INTERVAL) {
+            self.cancellation.check()?;
+            self.writer.write_all(chunk)?;
+        }
         self.current_offset += final_payload.len() as u64;
+        if let Some(checksums) = &mut self.compressed_checksums {
+            if !is_directory {
+                checksums.push((entry_start_offset, crc32fast::hash(&final_payload)));
+            }
+        }
 
         // Create central directory entry (data_offset points to LOCA header)
         let entry = EntryInfo {
@@ -136,39 +1642,483 @@ impl ArchiveWriter {
             crc32,
             modified_time,
             compression: actual_compression,
-            flags: 0,
+            flags,
+            mode,
+            mime_id,
+            sha256,
+            blake3,
         };
 
         // Store entry for central directory
         self.entries.push(entry);
+        if let (Some(index), Some(digest)) = (&mut self.dedup_index, digest) {
+            index.insert(digest, self.entries.len() - 1);
+        }
 
-        Ok(())
+        if !is_directory {
+            let path = &self.entries[self.entries.len() - 1].path;
+            self.progress.emit(|| ProgressEvent::FileFinished {
+                path: path.clone(),
+                compressed_size: final_payload.len() as u64,
+            })?;
+        }
+
+        Ok(actual_compression)
+    }
+
+    /// Store `data` as chunks plus a chunk list for `entry` (see
+    /// `with_cdc_dedup`)
+    ///
+    /// Chunks not yet in the archive are written with `compression`; the
+    /// list is written under `entry`, which carries the whole file's size
+    /// and CRC32.
+    fn write_chunked_entry(
+        &mut self,
+        entry: EntryInfo,
+        data: &[u8],
+        compression: CompressionMethod,
+        config: &ChunkerConfig,
+    ) -> Result<()> {
+        // Chunks are written as entries of their own, without progress events
+        let mut progress = std::mem::take(&mut self.progress);
+        let result = self.write_chunks(&entry, data, compression, config, &mut progress);
+        self.progress = progress;
+        let chunks = result?;
+
+        let list = chunking::encode_chunk_list(&chunks);
+        let payload = if self.encryption_mode == EncryptionMode::PerFile {
+            self.encrypt_file_data(&list, &entry_aad(&entry.path, entry.uncompressed_size))?
+        } else {
+            list
+        };
+        self.write_stored_entry_inner(&entry, &payload)?;
+
+        self.progress.emit(|| ProgressEvent::FileFinished {
+            path: entry.path.clone(),
+            compressed_size: payload.len() as u64,
+        })
+    }
+
+    /// Write the chunks of `data` not already stored, returning the list of
+    /// all its chunks
+    fn write_chunks(
+        &mut self,
+        entry: &EntryInfo,
+        data: &[u8],
+        compression: CompressionMethod,
+        config: &ChunkerConfig,
+        progress: &mut Progress,
+    ) -> Result<Vec<ChunkRef>> {
+        let total = entry.uncompressed_size;
+        let mut chunks = Vec::new();
+        let mut bytes_done = 0;
+        for chunk in chunking::split(data, config) {
+            let hash: [u8; 32] = Sha256::digest(chunk).into();
+            if !self.chunk_index.contains_key(&hash) {
+                let attributes = EntryAttributes {
+                    modified_time: entry.modified_time,
+                    ..EntryAttributes::default()
+                };
+                let path = chunking::chunk_path(&hash);
+                self.write_entry_inner(&path, chunk, compression, attributes)?;
+                self.chunk_index.insert(hash, self.entries.len() - 1);
+            }
+            chunks.push(ChunkRef {
+                hash,
+                len: chunk.len() as u64,
+            });
+
+            bytes_done += chunk.len() as u64;
+            progress.emit(|| ProgressEvent::BytesProcessed {
+                path: entry.path.clone(),
+                bytes_done,
+                total,
+            })?;
+        }
+        Ok(chunks)
+    }
+
+    /// Re-add an entry read from another archive, keeping its metadata
+    ///
+    /// `data` is the uncompressed content; the entry's compression method is
+    /// requested again and may fall back to `None` as usual.
+    pub(crate) fn copy_entry(
+        &mut self,
+        entry: &EntryInfo,
+        data: &[u8],
+    ) -> Result<CompressionMethod> {
+        let attributes = EntryAttributes {
+            modified_time: entry.modified_time,
+            mode: entry.mode,
+            // The payload is stored again, so it is no longer shared
+            flags: entry.flags & !(ENTRY_FLAG_DEDUPLICATED | ENTRY_FLAG_ALIAS),
+            mime_id: entry.mime_id,
+        };
+        self.write_entry(&entry.path, data, entry.compression, attributes)
+    }
+
+    /// Store an entry's payload exactly as read from another archive
+    ///
+    /// `payload` is the stored (compressed) data and is written verbatim;
+    /// sizes, CRC32, compression method, flags and mode come from `entry`,
+    /// except that the entry no longer shares another entry's data.
+    /// Returns the offset of the new LOCA header.
+    pub(crate) fn write_stored_entry(&mut self, entry: &EntryInfo, payload: &[u8]) -> Result<u64> {
+        let result = self.transaction(|writer| writer.write_stored_entry_inner(entry, payload));
+        self.discard_if_cancelled(result)
+    }
+
+    fn write_stored_entry_inner(&mut self, entry: &EntryInfo, payload: &[u8]) -> Result<u64> {
+        self.cancellation.check()?;
+
+        let flags = entry.flags & !(ENTRY_FLAG_DEDUPLICATED | ENTRY_FLAG_ALIAS);
+        let mut local_header = LocalEntryHeader::new(
+            entry.uncompressed_size,
+            payload.len() as u64,
+            entry.crc32,
+            entry.modified_time,
+            entry.compression,
+            entry.path.clone(),
+        );
+        local_header.flags = flags;
+        local_header.mode = entry.stored_mode();
+
+        self.start_volume_for(local_header.header_size() as u64 + payload.len() as u64)?;
+
+        let entry_start_offset = self.current_offset;
+        self.current_offset += local_header.write_to(&mut self.writer)? as u64;
+        for chunk in payload.chunks(CANCEL_CHECK_INTERVAL) {
+            self.cancellation.check()?;
+            self.writer.write_all(chunk)?;
+        }
+        self.current_offset += payload.len() as u64;
+        if let Some(checksums) = &mut self.compressed_checksums {
+            checksums.push((entry_start_offset, crc32fast::hash(payload)));
+        }
+
+        self.entries.push(EntryInfo {
+            data_offset: entry_start_offset,
+            compressed_size: payload.len() as u64,
+            flags,
+            ..entry.clone()
+        });
+        Ok(entry_start_offset)
+    }
+
+    /// Record an entry sharing the LOCA header and data at `data_offset`
+    ///
+    /// The data must already have been written to this archive, e.g. by
+    /// `write_stored_entry`.
+    pub(crate) fn add_shared_entry(&mut self, entry: &EntryInfo, data_offset: u64) {
+        self.entries.push(EntryInfo {
+            data_offset,
+            flags: entry.flags | ENTRY_FLAG_DEDUPLICATED,
+            ..entry.clone()
+        });
     }
 
     /// Add a file from disk
-    pub fn add_file_from_disk(&mut self, archive_path: &str, disk_path: &Path) -> Result<()> {
+    ///
+    /// The file's permission bits are stored in the entry's `mode` so they can be
+    /// restored by `ArchiveReader::extract_all`, and a file with any execute bit
+    /// set is flagged executable (`EntryInfo::is_executable`). The MIME type
+    /// is inferred from the extension (see `mime_type_for_path`).
+    ///
+    /// Returns the compression method actually used.
+    pub fn add_file_from_disk(
+        &mut self,
+        archive_path: &str,
+        disk_path: &Path,
+    ) -> Result<CompressionMethod> {
+        Self::check_user_path(archive_path)?;
         let data = std::fs::read(disk_path)?;
-        self.add_file(archive_path, &data)
+        let mode = file_mode(&std::fs::metadata(disk_path)?);
+        let mime_id = match mime_type_for_path(archive_path) {
+            Some(mime) => self.register_mime_type(mime)?,
+            None => 0,
+        };
+
+        let compression = self.choose_compression(archive_path, &data);
+        let attributes = EntryAttributes {
+            mode,
+            flags: mode_flags(mode),
+            mime_id,
+            ..self.default_attributes()
+        };
+
+        self.write_entry(archive_path, &data, compression, attributes)
+    }
+
+    /// Import every file from a ZIP archive
+    ///
+    /// Directory entries are skipped. Each file is re-added through the normal
+    /// write path, so Engram's compression heuristics and path validation apply
+    /// (unsafe paths such as `../` or absolute paths are rejected). ZIP
+    /// modification times are preserved where present.
+    ///
+    /// Returns the number of files imported.
+    #[cfg(feature = "zip-convert")]
+    pub fn import_zip<R: Read + Seek>(&mut self, zip: R) -> Result<usize> {
+        use crate::archive::frame_compression::preallocation;
+
+        let mut archive = zip::ZipArchive::new(zip)?;
+        let mut imported = 0;
+
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            if entry.is_dir() {
+                continue;
+            }
+
+            let path = entry.name().to_string();
+            let mut data = Vec::with_capacity(preallocation(entry.size()));
+            entry.read_to_end(&mut data)?;
+
+            let mut attributes = EntryAttributes {
+                mode: entry.unix_mode().unwrap_or(0),
+                ..self.default_attributes()
+            };
+            if let Some(dt) = entry.last_modified() {
+                attributes.modified_time = crate::convert::zip_datetime_to_unix(dt);
+            }
+
+            let compression = self.choose_compression(&path, &data);
+            Self::check_user_path(&path)?;
+            self.write_entry(&path, &data, compression, attributes)?;
+            imported += 1;
+        }
+
+        Ok(imported)
     }
 
     /// Add manifest.json from a serde_json::Value
+    ///
+    /// With `with_fixed_time`, an unsigned manifest's `metadata.created` is
+    /// replaced by the fixed timestamp; with `with_deterministic_output` and no
+    /// fixed time, by `SOURCE_DATE_EPOCH` (or 0). The manifest is written by
+    /// `finalize`; calling this again replaces it.
     pub fn add_manifest(&mut self, manifest: &serde_json::Value) -> Result<()> {
-        let json = serde_json::to_vec_pretty(manifest).map_err(|e| {
+        self.add_manifest_with_compression(manifest, CompressionMethod::None)
+    }
+
+    /// Add manifest.json compressed with `compression`
+    ///
+    /// Like `add_manifest`, which stores it uncompressed, but saves space for
+    /// large file inventories; `read_manifest` decompresses it transparently.
+    /// As with other entries, the manifest is stored uncompressed when
+    /// compression does not make it smaller.
+    pub fn add_manifest_with_compression(
+        &mut self,
+        manifest: &serde_json::Value,
+        compression: CompressionMethod,
+    ) -> Result<()> {
+        check_writable(compression)?;
+
+        let mut manifest = manifest.clone();
+        if let Some(time) = self.pinned_time() {
+            Self::stamp_manifest_created(&mut manifest, time);
+        }
+
+        let json = serde_json::to_vec_pretty(&manifest).map_err(|e| {
             EngramError::InvalidManifest(format!("Failed to serialize manifest: {}", e))
         })?;
 
-        // Manifests are typically small, store uncompressed for instant access
-        self.add_file_with_compression("manifest.json", &json, CompressionMethod::None)
+        self.manifest = Some((json, compression));
+        Ok(())
+    }
+
+    /// Reject paths reserved for the format (see `is_reserved_path`)
+    fn check_user_path(path: &str) -> Result<()> {
+        let normalized = normalize_path(path);
+        if is_reserved_path(&normalized) {
+            return Err(EngramError::ReservedPath(normalized));
+        }
+        Ok(())
+    }
+
+    /// Number of entries added so far (directories and deduplicated copies included)
+    pub fn pending_entries(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Bytes written so far: the header, LOCA headers and stored payloads
+    ///
+    /// The central directory and End Record are not written until `finalize`,
+    /// which adds 320 bytes per entry plus the 64-byte End Record.
+    pub fn bytes_written_so_far(&self) -> u64 {
+        self.current_offset
     }
 
     /// Finalize the archive by writing central directory and updating header
-    pub fn finalize(mut self) -> Result<()> {
+    ///
+    /// Returns a summary of the finished archive.
+    pub fn finalize(mut self) -> Result<FinalizeSummary> {
+        self.finalize_in_place()
+    }
+
+    /// Finalize without consuming the writer, at most once
+    ///
+    /// Later calls fail with `AlreadyFinalized`, including after a failed
+    /// attempt, whose output cannot be resumed.
+    fn finalize_in_place(&mut self) -> Result<FinalizeSummary> {
+        if self.finalized {
+            return Err(EngramError::AlreadyFinalized);
+        }
+        self.finalized = true;
+        if self.poisoned {
+            return Err(EngramError::WriterPoisoned);
+        }
+        let result = self.finish_entries();
+        self.discard_if_cancelled(result)?;
+
+        // No further volumes are started, so these are all the files written
+        let paths = self.writer.get_ref().paths(self.path.as_deref());
+        let result = self.finalize_inner();
+        if matches!(result, Err(EngramError::Cancelled)) {
+            Self::remove_partial(&paths);
+        }
+        result
+    }
+
+    /// Write the trailing internal entries and select the final volume
+    fn finish_entries(&mut self) -> Result<()> {
+        self.cancellation.check()?;
+        self.check_format_features()?;
+
+        // Manifests are typically small and stored uncompressed for instant
+        // access, unless added with `add_manifest_with_compression`
+        if let Some((manifest, compression)) = self.manifest.take() {
+            let attributes = self.default_attributes();
+            self.write_entry_inner(MANIFEST_PATH, &manifest, compression, attributes)?;
+        }
+
+        // Store the shared dictionary so readers can decompress flagged entries
+        if let Some(dictionary) = self.zstd_dictionary.take() {
+            let attributes = self.default_attributes();
+            self.write_entry_inner(
+                ZSTD_DICTIONARY_PATH,
+                &dictionary,
+                CompressionMethod::None,
+                attributes,
+            )?;
+        }
+
+        // Custom MIME types, which entries refer to by line
+        if !self.mime_table.is_empty() {
+            if self.entries.iter().any(|e| e.path == MIME_TABLE_PATH) {
+                return Err(EngramError::InvalidOptions(format!(
+                    "Custom MIME types cannot be added next to a copied {}",
+                    MIME_TABLE_PATH
+                )));
+            }
+            let table = mime::table_bytes(&std::mem::take(&mut self.mime_table));
+            let attributes = self.default_attributes();
+            self.write_entry_inner(MIME_TABLE_PATH, &table, CompressionMethod::None, attributes)?;
+        }
+
+        // Custom entry metadata, keyed by path
+        if !self.entry_metadata.is_empty() {
+            if self.entries.iter().any(|e| e.path == ENTRY_METADATA_PATH) {
+                return Err(EngramError::InvalidOptions(format!(
+                    "Entry metadata cannot be added next to a copied {}",
+                    ENTRY_METADATA_PATH
+                )));
+            }
+            let table = entry_metadata::table_bytes(&std::mem::take(&mut self.entry_metadata));
+            let attributes = self.default_attributes();
+            self.write_entry_inner(
+                ENTRY_METADATA_PATH,
+                &table,
+                CompressionMethod::None,
+                attributes,
+            )?;
+        }
+
+        // Content digests, keyed like the stored-payload checksums below
+        if self.blake3 {
+            if self.entries.iter().any(|e| e.path == BLAKE3_TABLE_PATH) {
+                return Err(EngramError::InvalidOptions(format!(
+                    "BLAKE3 digests cannot be recorded next to a copied {}",
+                    BLAKE3_TABLE_PATH
+                )));
+            }
+            let digests: Vec<(u64, [u8; 32])> = self
+                .entries
+                .iter()
+                .filter_map(|e| Some((e.data_offset, e.blake3?)))
+                .collect();
+            let table = blake3_table::table_bytes(&digests);
+            let attributes = self.default_attributes();
+            self.write_entry_inner(
+                BLAKE3_TABLE_PATH,
+                &table,
+                CompressionMethod::None,
+                attributes,
+            )?;
+        }
+
+        // Checksums of every payload above; the table itself is checked in full
+        if let Some(checksums) = self.compressed_checksums.take() {
+            if self.entries.iter().any(|e| e.path == COMPRESSED_CRC_PATH) {
+                return Err(EngramError::InvalidOptions(format!(
+                    "Compressed checksums cannot be recorded next to a copied {}",
+                    COMPRESSED_CRC_PATH
+                )));
+            }
+            let table = stored_crc::table_bytes(&checksums);
+            let attributes = self.default_attributes();
+            self.write_entry_inner(
+                COMPRESSED_CRC_PATH,
+                &table,
+                CompressionMethod::None,
+                attributes,
+            )?;
+        }
+
+        // Leftovers of a rolled-back entry in a stream end up between the
+        // entries and the central directory
+        if self.rolled_back_end > self.current_offset {
+            let padding = self.rolled_back_end - self.current_offset;
+            std::io::copy(&mut std::io::repeat(0).take(padding), &mut self.writer)?;
+            self.current_offset = self.rolled_back_end;
+        }
+
+        // The central directory, trailer blocks and ENDR go in the final volume, together
+        let recipients_size = recipients::block_size(&self.wrap_recipient_keys()?) as u64;
+        let signature_size = match self.endr_signing_key {
+            Some(_) => SIGNATURE_BLOCK_SIZE as u64,
+            None => 0,
+        };
+        self.start_volume_for(
+            self.entries.len() as u64 * 320
+                + signature_size
+                + recipients_size
+                + END_RECORD_SIZE as u64,
+        )?;
+        if let Output::Volumes(volumes) = self.writer.get_mut() {
+            volumes.seal();
+        }
+        Ok(())
+    }
+
+    fn finalize_inner(&mut self) -> Result<FinalizeSummary> {
+        self.progress.emit(|| ProgressEvent::Finalizing)?;
+
         // Record central directory start
         let cd_offset = self.current_offset;
 
-        // Write central directory entries
+        let sorted_directory = self.sorted_directory || self.deterministic;
+        if sorted_directory {
+            self.entries.sort_by(|a, b| a.path.cmp(&b.path));
+        }
+
+        // Write central directory entries, hashing them for the ENDR signature
+        let mut directory_hash = self.endr_signing_key.as_ref().map(|_| Sha256::new());
         for entry in &self.entries {
             entry.write_to(&mut self.writer)?;
+            if let Some(hash) = &mut directory_hash {
+                entry.write_to(hash)?;
+            }
         }
 
         let cd_size = self.current_offset - cd_offset + (self.entries.len() as u64 * 320);
@@ -177,49 +2127,337 @@ impl ArchiveWriter {
         self.writer.flush()?;
 
         // Capture needed values before moving writer
+        let (version_major, version_minor) = self.format_version;
+        let legacy = self.is_legacy();
+        let content_version = self.content_version;
+        let label = self.label;
         let encryption_mode = self.encryption_mode;
-        let encryption_key = self.encryption_key;
+        let encryption_key = self.encryption_key.clone();
         let entry_count = self.entries.len() as u32;
+        let cancellation = self.cancellation.clone();
+        let archive_nonce = (encryption_mode == EncryptionMode::Archive).then(|| self.next_nonce());
+        let recipients = self.wrap_recipient_keys()?;
+        let chunked = self.entries.iter().any(EntryInfo::is_chunked);
+        let blake3_digests = self.blake3;
+        let signing_key = self.endr_signing_key.clone();
+        let signed_at = self
+            .pinned_time()
+            .unwrap_or_else(|| EntryAttributes::now().modified_time);
+        let path = self.path.clone();
+        let total_uncompressed = self.entries.iter().map(|e| e.uncompressed_size).sum();
+        let total_compressed = self
+            .entries
+            .iter()
+            .filter(|e| !e.is_deduplicated())
+            .map(|e| e.compressed_size)
+            .sum();
 
-        // Get inner file for encryption and header writing
-        let mut file = self.writer.into_inner().map_err(|e| e.into_error())?;
+        // Write to the inner file directly for encryption and the header
+        self.writer.flush()?;
+        let mut file = self.writer.get_mut();
 
         // Handle archive-level encryption
-        if encryption_mode == EncryptionMode::Archive {
+        if let Some(nonce) = archive_nonce {
             Self::encrypt_archive_payload_static(
                 &mut file,
-                &encryption_key.ok_or(EngramError::InvalidEncryptionMode)?,
+                encryption_key
+                    .as_deref()
+                    .ok_or(EngramError::InvalidEncryptionMode)?,
+                nonce,
+                &cancellation,
             )?;
         }
+        cancellation.check()?;
 
         // Write final header with encryption flags
         file.seek(SeekFrom::Start(0))?;
         let mut header = FileHeader::new();
+        header.version_major = version_major;
+        header.version_minor = version_minor;
         header.central_directory_offset = cd_offset;
         header.central_directory_size = cd_size;
         header.entry_count = entry_count;
+        header.content_version = content_version;
+        header.label = label;
         header.set_encryption_mode(encryption_mode);
+        header.set_sorted_directory(sorted_directory);
+        header.set_entry_aad(encryption_mode == EncryptionMode::PerFile);
+        header.set_recipients(!recipients.is_empty());
+        header.set_chunked(chunked);
+        header.set_blake3_digests(blake3_digests);
+        header.header_crc = header.compute_crc();
         header.write_to(&mut file)?;
 
-        // Write End Record (ENDR) at end of archive (v1.0)
+        // End Record (ENDR) at end of archive (v1.0; v0.x ends with the CD),
+        // serialized first since the signature covers it
+        let recipients_size = if recipients.is_empty() {
+            0
+        } else {
+            recipients::block_size(&recipients) as u32
+        };
+        let mut end_record = Vec::with_capacity(END_RECORD_SIZE);
+        if !legacy {
+            let mut record = EndRecord::new(
+                version_major,
+                version_minor,
+                cd_offset,
+                cd_size,
+                entry_count,
+                0, // archive_crc32 - TODO: calculate full archive checksum
+            );
+            record.content_version = content_version;
+            record.recipients_size = recipients_size;
+            if signing_key.is_some() {
+                record.signature_size = SIGNATURE_BLOCK_SIZE as u32;
+            }
+            record.write_to(&mut end_record)?;
+        }
+
+        // Signature block, then recipients block, right before the ENDR and
+        // outside any encryption
         file.seek(SeekFrom::End(0))?;
-        let end_record = EndRecord::new(
-            FORMAT_VERSION_MAJOR,
-            FORMAT_VERSION_MINOR,
-            cd_offset,
-            cd_size,
-            entry_count,
-            0, // archive_crc32 - TODO: calculate full archive checksum
-        );
-        end_record.write_to(&mut file)?;
+        if let (Some(key), Some(hash)) = (&signing_key, directory_hash) {
+            let digest: [u8; 32] = hash.finalize().into();
+            SignatureBlock::sign(key, signed_at, &end_record, &digest).write_to(&mut file)?;
+        }
+        if !recipients.is_empty() {
+            recipients::write_block(&recipients, &mut file)?;
+        }
+        file.write_all(&end_record)?;
 
         file.flush()?;
+        if let Output::Volumes(volumes) = &mut file {
+            volumes.finish()?;
+        }
+
+        let archive_size = match &mut file {
+            Output::File(file) => file.metadata()?.len(),
+            Output::Stream(stream) => stream.seek(SeekFrom::End(0))?,
+            Output::Volumes(volumes) => volumes
+                .paths()
+                .iter()
+                .map(|volume| Ok(std::fs::metadata(volume)?.len()))
+                .sum::<Result<u64>>()?,
+        };
+
+        Ok(FinalizeSummary {
+            path,
+            total_entries: entry_count,
+            archive_size,
+            total_uncompressed,
+            total_compressed,
+            cd_offset,
+            encryption: encryption_mode,
+        })
+    }
+
+    /// Encryption key wrapped for every recipient
+    ///
+    /// Wrapping draws fresh randomness, so only the length of the result is
+    /// stable across calls.
+    fn wrap_recipient_keys(&self) -> Result<Vec<WrappedKey>> {
+        let Some(key) = self
+            .encryption_key
+            .as_deref()
+            .filter(|_| !self.recipients.is_empty())
+        else {
+            return Ok(Vec::new());
+        };
+        self.recipients
+            .iter()
+            .map(|(id, recipient)| WrappedKey::wrap(id, recipient, key))
+            .collect()
+    }
+
+    /// Timestamp for entries without an explicit time, if not the current time
+    ///
+    /// The `with_fixed_time` timestamp, or with deterministic output
+    /// `SOURCE_DATE_EPOCH` (0 if unset or invalid).
+    fn pinned_time(&self) -> Option<u64> {
+        self.fixed_time.or_else(|| {
+            self.deterministic
+                .then(|| crate::manifest::source_date_epoch().unwrap_or(0))
+        })
+    }
+
+    /// Nonce for the next AES-GCM encryption
+    fn next_nonce(&mut self) -> [u8; 12] {
+        match &mut self.nonce_source {
+            Some(source) => source(),
+            None => rand::random(),
+        }
+    }
+
+    /// MIME type id for `mime`, adding it to the custom table if it is not built in
+    ///
+    /// v0.x archives record no MIME types, so this is always 0 for them.
+    fn register_mime_type(&mut self, mime: &str) -> Result<u16> {
+        mime::validate_mime_type(mime)?;
+        if self.is_legacy() {
+            return Ok(0);
+        }
+        if let Some(id) = mime::builtin_mime_id(mime) {
+            return Ok(id);
+        }
+        let index = match self.mime_table.iter().position(|known| known == mime) {
+            Some(index) => index,
+            None => {
+                self.mime_table.push(mime.to_string());
+                self.mime_table.len() - 1
+            }
+        };
+        u16::try_from(index)
+            .ok()
+            .and_then(|index| MIME_TABLE_BASE.checked_add(index))
+            .ok_or_else(|| {
+                EngramError::InvalidOptions(format!(
+                    "More than {} custom MIME types",
+                    u16::MAX - MIME_TABLE_BASE + 1
+                ))
+            })
+    }
+
+    /// Record custom metadata for an entry copied with its path, as
+    /// `add_file_with_metadata` does for `FileMetadata::extra`
+    pub(crate) fn set_entry_metadata(
+        &mut self,
+        path: &str,
+        extra: &BTreeMap<String, String>,
+    ) -> Result<()> {
+        let block = entry_metadata::encode_block(path, extra)?;
+        self.entry_metadata.insert(path.to_string(), block);
+        Ok(())
+    }
+
+    /// Attributes for a new entry: the pinned time if any, otherwise now
+    fn default_attributes(&self) -> EntryAttributes {
+        match self.pinned_time() {
+            Some(modified_time) => EntryAttributes {
+                modified_time,
+                ..EntryAttributes::default()
+            },
+            None => EntryAttributes::now(),
+        }
+    }
+
+    /// Set `metadata.created` in a manifest, leaving signed manifests untouched
+    fn stamp_manifest_created(manifest: &mut serde_json::Value, time: u64) {
+        // Rewriting a signed manifest would invalidate its signatures
+        let signed = manifest
+            .get("signatures")
+            .and_then(|s| s.as_array())
+            .is_some_and(|s| !s.is_empty());
+        if signed {
+            return;
+        }
+
+        if let Some(metadata) = manifest.get_mut("metadata").and_then(|m| m.as_object_mut()) {
+            if metadata.contains_key("created") {
+                metadata.insert("created".to_string(), time.into());
+            }
+        }
+    }
 
+    /// Delete the partial archive if `result` is a cancellation
+    fn discard_if_cancelled<T>(&mut self, result: Result<T>) -> Result<T> {
+        if matches!(result, Err(EngramError::Cancelled)) {
+            Self::remove_partial(&self.writer.get_ref().paths(self.path.as_deref()));
+        }
+        result
+    }
+
+    /// Write one entry with `write`, undoing everything it did if it fails
+    ///
+    /// The output is cut back to where the entry started and the entry is
+    /// forgotten, so later entries' offsets still match the bytes on disk.
+    /// If that cannot be done, the writer is poisoned: this and every later
+    /// write, and `finalize`, fail with `WriterPoisoned`.
+    fn transaction<T>(&mut self, write: impl FnOnce(&mut Self) -> Result<T>) -> Result<T> {
+        if self.poisoned {
+            return Err(EngramError::WriterPoisoned);
+        }
+        let offset = self.current_offset;
+        let entry_count = self.entries.len();
+
+        let result = write(self);
+        if result.is_err() && self.rollback(offset, entry_count).is_err() {
+            self.poisoned = true;
+        }
+        result
+    }
+
+    /// Return the output and entry list to their state at `offset` and
+    /// `entry_count`
+    fn rollback(&mut self, offset: u64, entry_count: usize) -> Result<()> {
+        self.entries.truncate(entry_count);
+        if let Some(index) = &mut self.dedup_index {
+            index.retain(|_, first| *first < entry_count);
+        }
+        self.chunk_index.retain(|_, index| *index < entry_count);
+        if let Some(checksums) = &mut self.compressed_checksums {
+            checksums.retain(|&(data_offset, _)| data_offset < offset);
+        }
+
+        // Seeking writes out what is still buffered; it is cut off below
+        let end = self.writer.seek(SeekFrom::End(0))?;
+        self.writer.seek(SeekFrom::Start(offset))?;
+        if !self.writer.get_mut().truncate(offset)? {
+            self.rolled_back_end = self.rolled_back_end.max(end);
+        }
+        self.current_offset = offset;
+        Ok(())
+    }
+
+    /// Best-effort removal of unfinished archive files
+    fn remove_partial(paths: &[PathBuf]) {
+        // The cancellation is what gets reported; a failed cleanup leaves the
+        // (invalid) partial file behind
+        for path in paths {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    /// Move to a new volume before writing `len` bytes that would not fit in
+    /// the current one (no-op for single-file archives)
+    fn start_volume_for(&mut self, len: u64) -> Result<()> {
+        if matches!(self.writer.get_ref(), Output::Volumes(_)) {
+            self.writer.flush()?;
+            if let Output::Volumes(volumes) = self.writer.get_mut() {
+                volumes.start_volume_for(len)?;
+            }
+        }
         Ok(())
     }
 
+    /// Compression for `add_file`: the policy's choice, or one chosen from
+    /// the path and size
+    fn auto_compression(&self, path: &str, data: &[u8]) -> CompressionMethod {
+        if let Some(policy) = &self.compression_policy {
+            policy.choose(&normalize_path(path), data)
+        } else if self.zstd_dictionary.is_some() && !data.is_empty() {
+            // Small files benefit from the dictionary, so skip the size threshold
+            match Self::select_compression(path, data.len().max(MIN_COMPRESSION_SIZE)) {
+                CompressionMethod::None => CompressionMethod::None,
+                _ => CompressionMethod::Zstd,
+            }
+        } else {
+            Self::select_compression(path, data.len())
+        }
+    }
+
+    /// Compression for files added from disk or a ZIP archive: the policy's
+    /// choice, or one chosen from the path and size
+    fn choose_compression(&self, path: &str, data: &[u8]) -> CompressionMethod {
+        match &self.compression_policy {
+            Some(policy) => policy.choose(&normalize_path(path), data),
+            None => Self::select_compression(path, data.len()),
+        }
+    }
+
     /// Select appropriate compression method based on file characteristics
-    fn select_compression(path: &str, size: usize) -> CompressionMethod {
+    ///
+    /// Never selects `Deflate`, which only beats LZ4 and Zstd in compatibility.
+    pub(crate) fn select_compression(path: &str, size: usize) -> CompressionMethod {
         // Don't compress small files
         if size < MIN_COMPRESSION_SIZE {
             return CompressionMethod::None;
@@ -245,29 +2483,67 @@ impl ArchiveWriter {
     }
 
     /// Compress data with fallback to uncompressed if not beneficial
-    fn compress_data(
-        &self,
+    ///
+    /// Data of `frame_options.min_file_size` and up is frame-compressed unless
+    /// `layout` is `None`. `on_progress`
+    /// receives the number of input bytes processed: once per frame for
+    /// frame-compressed data, once at the end otherwise.
+    fn compress_data<F>(
         data: &[u8],
         compression: CompressionMethod,
-    ) -> Result<(Vec<u8>, CompressionMethod)> {
-        // Check if file should use frame-based compression (>= 50MB)
-        if should_use_frames(data.len()) {
+        dictionary: Option<&[u8]>,
+        zstd_level: i32,
+        layout: FrameLayout,
+        frame_options: FrameOptions,
+        mut on_progress: F,
+    ) -> Result<(Vec<u8>, CompressionMethod)>
+    where
+        F: FnMut(u64) -> Result<()>,
+    {
+        // Check if file should use frame-based compression (>= 50MB by default)
+        if layout != FrameLayout::None && frame_options.applies_to(data.len()) {
             match compression {
-                CompressionMethod::None => return Ok((data.to_vec(), CompressionMethod::None)),
+                CompressionMethod::None => {
+                    on_progress(data.len() as u64)?;
+                    return Ok((data.to_vec(), CompressionMethod::None));
+                }
                 CompressionMethod::Lz4 | CompressionMethod::Zstd => {
                     // Use frame-based compression for large files
-                    let compressed = compress_frames(data, compression)?;
+                    let compressed = compress_frames_with(
+                        data,
+                        compression,
+                        zstd_level,
+                        layout,
+                        frame_options,
+                        on_progress,
+                    )?;
                     // Frame compression is always beneficial for large files
                     return Ok((compressed, compression));
                 }
+                // Deflate is never framed: large files are one stream, as in ZIP
+                CompressionMethod::Deflate => {}
+                CompressionMethod::Unknown(value) => {
+                    return Err(EngramError::InvalidCompression(value));
+                }
             }
         }
 
         // Regular compression for files < 50MB
         let compressed = match compression {
-            CompressionMethod::None => return Ok((data.to_vec(), CompressionMethod::None)),
-            CompressionMethod::Lz4 => Self::compress_lz4(data)?,
-            CompressionMethod::Zstd => Self::compress_zstd(data)?,
+            CompressionMethod::None => None,
+            CompressionMethod::Lz4 => Some(Self::compress_lz4(data)?),
+            CompressionMethod::Zstd => Some(match dictionary {
+                Some(dictionary) => compress_with_dictionary(data, dictionary, zstd_level)?,
+                None => Self::compress_zstd(data, zstd_level)?,
+            }),
+            CompressionMethod::Deflate => Some(Self::compress_deflate(data)?),
+            CompressionMethod::Unknown(value) => {
+                return Err(EngramError::InvalidCompression(value));
+            }
+        };
+        on_progress(data.len() as u64)?;
+        let Some(compressed) = compressed else {
+            return Ok((data.to_vec(), CompressionMethod::None));
         };
 
         // Use compressed only if it's actually smaller
@@ -283,29 +2559,39 @@ impl ArchiveWriter {
         Ok(lz4_flex::compress_prepend_size(data))
     }
 
-    /// Compress with Zstd (level 6 for balanced compression)
-    fn compress_zstd(data: &[u8]) -> Result<Vec<u8>> {
-        zstd::encode_all(data, 6)
+    /// Compress with raw Deflate at the default level
+    fn compress_deflate(data: &[u8]) -> Result<Vec<u8>> {
+        let mut encoder =
+            flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(data)?;
+        Ok(encoder.finish()?)
+    }
+
+    /// Compress with Zstd at `level`
+    fn compress_zstd(data: &[u8], level: i32) -> Result<Vec<u8>> {
+        zstd::encode_all(data, level)
             .map_err(|e| EngramError::CompressionFailed(format!("Zstd compression failed: {}", e)))
     }
 
     /// Encrypt file data for per-file encryption mode
     /// Returns: [nonce 12 bytes][ciphertext||tag]
-    fn encrypt_file_data(&self, data: &[u8]) -> Result<Vec<u8>> {
-        let key = self
-            .encryption_key
-            .as_ref()
-            .ok_or(EngramError::InvalidEncryptionMode)?;
-
+    ///
+    /// `aad` binds the payload to its entry (see `entry_aad`).
+    fn encrypt_file_data(&mut self, data: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
         // Generate unique nonce for this file
-        let nonce_bytes: [u8; 12] = rand::random();
+        let nonce_bytes = self.next_nonce();
         #[allow(deprecated)]
         let nonce = Nonce::from_slice(&nonce_bytes);
 
+        let key = self
+            .encryption_key
+            .as_deref()
+            .ok_or(EngramError::InvalidEncryptionMode)?;
+
         // Encrypt compressed data
         let cipher = Aes256Gcm::new(key.into());
         let ciphertext_with_tag = cipher
-            .encrypt(nonce, data)
+            .encrypt(nonce, Payload { msg: data, aad })
             .map_err(|_| EngramError::EncryptionFailed)?;
 
         // Build payload: [nonce][ciphertext||tag]
@@ -319,22 +2605,33 @@ impl ArchiveWriter {
     /// Encrypt entire archive payload (archive-level encryption)
     /// Reads everything after header, encrypts it, writes back
     ///
+    /// The payload is encrypted in place, so no plaintext copy outlives the
+    /// call.
+    ///
     /// This is a static method to avoid borrowing issues with BufWriter
-    fn encrypt_archive_payload_static(file: &mut File, key: &[u8; 32]) -> Result<()> {
-        // Read everything after header (from byte 64 to EOF)
+    fn encrypt_archive_payload_static<F: Read + Write + Seek>(
+        file: &mut F,
+        key: &[u8; 32],
+        nonce_bytes: [u8; 12],
+        cancellation: &CancellationToken,
+    ) -> Result<()> {
+        // Read everything after header (from byte 64 to EOF), checking for
+        // cancellation between chunks
+        let payload_len = file.seek(SeekFrom::End(0))? - 64;
         file.seek(SeekFrom::Start(64))?;
-        let mut payload = Vec::new();
-        std::io::Read::read_to_end(file, &mut payload)?;
+        let mut payload = vec![0u8; payload_len as usize];
+        for chunk in payload.chunks_mut(CANCEL_CHECK_INTERVAL) {
+            cancellation.check()?;
+            file.read_exact(chunk)?;
+        }
 
-        // Generate nonce for archive encryption
-        let nonce_bytes: [u8; 12] = rand::random();
         #[allow(deprecated)]
         let nonce = Nonce::from_slice(&nonce_bytes);
 
         // Encrypt the entire payload
         let cipher = Aes256Gcm::new(key.into());
-        let ciphertext_with_tag = cipher
-            .encrypt(nonce, payload.as_ref())
+        cipher
+            .encrypt_in_place(nonce, b"", &mut payload)
             .map_err(|_| EngramError::EncryptionFailed)?;
 
         // Seek back to position 64 (after header)
@@ -342,14 +2639,57 @@ impl ArchiveWriter {
 
         // Write: [nonce][ciphertext||tag]
         file.write_all(&nonce_bytes)?;
-        file.write_all(&ciphertext_with_tag)?;
+        file.write_all(&payload)?;
 
-        // Truncate file to remove any leftover data
-        let new_size = 64 + 12 + ciphertext_with_tag.len() as u64;
-        file.set_len(new_size)?;
+        // The ciphertext is longer than the plaintext, so it overwrites all of it
         file.flush()?;
 
         // Note: Central directory offsets in header are payload-relative (will be interpreted after decryption)
         Ok(())
     }
 }
+
+impl Drop for ArchiveWriter {
+    fn drop(&mut self) {
+        if self.finalized || self.entries.is_empty() {
+            return;
+        }
+        if self.finalize_on_drop && !std::thread::panicking() {
+            match self.finalize_in_place() {
+                Ok(_) => return,
+                Err(err) => tracing::warn!("Finalizing dropped archive writer failed: {}", err),
+            }
+        } else {
+            tracing::warn!(
+                "Archive writer dropped without finalize; {} entries were not finalized",
+                self.entries.len()
+            );
+        }
+        if let Some(warning) = self.drop_warning.take() {
+            warning(self.entries.len());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use zeroize::Zeroizing;
+
+    #[test]
+    fn test_key_material_is_zeroizing() {
+        let key = [0x42u8; 32];
+        let builder = ArchiveWriterBuilder::new().with_per_file_encryption(&key);
+        let _: &Option<Zeroizing<[u8; 32]>> = &builder.per_file_key;
+        let _: &Option<Zeroizing<[u8; 32]>> = &builder.archive_key;
+
+        let temp_file = tempfile::NamedTempFile::new().unwrap();
+        let mut writer = builder.build(temp_file.path()).unwrap();
+        let _: &Option<Zeroizing<[u8; 32]>> = &writer.encryption_key;
+        writer.add_file("secret.txt", b"classified").unwrap();
+        writer.finalize().unwrap();
+
+        let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+        assert_eq!(reader.read_file("secret.txt").unwrap(), b"classified");
+    }
+}
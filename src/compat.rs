@@ -2,6 +2,7 @@
 //!
 //! Provides `EngramVfs` wrapper around new `VfsReader` for backward compatibility.
 
+use crate::archive::ArchiveWriter;
 use crate::error::{EngramError, Result};
 use crate::vfs::VfsReader;
 use rusqlite::Connection;
@@ -13,6 +14,8 @@ use std::path::Path;
 /// while using the new unified `VfsReader` internally.
 pub struct EngramVfs {
     archive_path: std::path::PathBuf,
+    /// Passed to `VfsReader::with_temp_dir`
+    temp_dir: Option<std::path::PathBuf>,
 }
 
 impl EngramVfs {
@@ -20,14 +23,32 @@ impl EngramVfs {
     pub fn new<P: AsRef<Path>>(archive_path: P) -> Self {
         Self {
             archive_path: archive_path.as_ref().to_path_buf(),
+            temp_dir: None,
         }
     }
 
+    /// Extract databases under `dir` instead of the system temp directory
+    ///
+    /// See `VfsReader::with_temp_dir`.
+    pub fn with_temp_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.temp_dir = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    /// `VfsReader` for the archive, honoring `with_temp_dir`
+    fn reader(&self) -> Result<VfsReader> {
+        let vfs = VfsReader::open(&self.archive_path)?;
+        Ok(match &self.temp_dir {
+            Some(dir) => vfs.with_temp_dir(dir),
+            None => vfs,
+        })
+    }
+
     /// Open a database from the archive
     ///
     /// Extracts the database to a temporary file and returns a read-only connection.
     pub fn open_database(&self, db_path_in_archive: &str) -> Result<Connection> {
-        let mut vfs = VfsReader::open(&self.archive_path)?;
+        let mut vfs = self.reader()?;
         vfs.open_database(db_path_in_archive)
     }
 
@@ -36,7 +57,7 @@ impl EngramVfs {
     /// Loads the entire database into an in-memory SQLite database.
     pub fn open_database_in_memory(&self, db_path_in_archive: &str) -> Result<Connection> {
         // First extract to temp file
-        let mut vfs = VfsReader::open(&self.archive_path)?;
+        let mut vfs = self.reader()?;
         let temp_conn = vfs.open_database(db_path_in_archive)?;
 
         // Create in-memory database
@@ -44,18 +65,12 @@ impl EngramVfs {
 
         // Use backup API to copy database into memory
         {
-            let backup = rusqlite::backup::Backup::new(&temp_conn, &mut mem_conn)
-                .map_err(|e| EngramError::Internal(format!("Failed to create backup: {}", e)))?;
-
-            backup
-                .run_to_completion(5, std::time::Duration::from_millis(100), None)
-                .map_err(|e| EngramError::Internal(format!("Failed to restore backup: {}", e)))?;
+            let backup = rusqlite::backup::Backup::new(&temp_conn, &mut mem_conn)?;
+            backup.run_to_completion(5, std::time::Duration::from_millis(100), None)?;
         }
 
         // Configure for read-only access
-        mem_conn
-            .execute_batch("PRAGMA query_only = ON;")
-            .map_err(|e| EngramError::Internal(format!("Failed to configure database: {}", e)))?;
+        mem_conn.execute_batch("PRAGMA query_only = ON;")?;
 
         Ok(mem_conn)
     }
@@ -65,6 +80,66 @@ impl EngramVfs {
         let vfs = VfsReader::open(&self.archive_path)?;
         Ok(vfs.list_databases())
     }
+
+    /// Modify a database and write the result to a new archive
+    ///
+    /// Opens `db_path` from `archive_in` writable, runs `f` against it, then writes
+    /// `archive_out` containing every other entry unchanged plus the modified
+    /// database, keeping the source's content version, label and
+    /// sorted-directory setting. `archive_in` is never modified and must not
+    /// be the same file as `archive_out`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use engram_rs::EngramVfs;
+    /// # fn main() -> engram_rs::Result<()> {
+    /// EngramVfs::update_database("app.eng", "app-migrated.eng", "data.db", |conn| {
+    ///     conn.execute_batch("ALTER TABLE users ADD COLUMN email TEXT;")?;
+    ///     Ok(())
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_database<P, Q, F>(
+        archive_in: P,
+        archive_out: Q,
+        db_path: &str,
+        f: F,
+    ) -> Result<()>
+    where
+        P: AsRef<Path>,
+        Q: AsRef<Path>,
+        F: FnOnce(&Connection) -> Result<()>,
+    {
+        let (archive_in, archive_out) = (archive_in.as_ref(), archive_out.as_ref());
+        let same_file = if archive_out.exists() {
+            std::fs::canonicalize(archive_in)? == std::fs::canonicalize(archive_out)?
+        } else {
+            archive_in == archive_out
+        };
+        if same_file {
+            return Err(EngramError::PathError(
+                "Output archive must differ from input archive".to_string(),
+            ));
+        }
+
+        let mut vfs = VfsReader::open(archive_in)?;
+        let (conn, handle) = vfs.open_database_writable(db_path)?;
+        f(&conn)?;
+        conn.close().map_err(|(_, e)| e)?;
+
+        let header = vfs.archive().header();
+        let mut writer = ArchiveWriter::create(archive_out)?
+            .with_content_version(header.content_version)
+            .with_label(header.label.0);
+        if header.has_sorted_directory() {
+            writer = writer.with_sorted_directory();
+        }
+        handle.save_into(&mut writer)?;
+        writer.finalize()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -128,4 +203,99 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_update_database_wal() -> Result<()> {
+        let temp_db = NamedTempFile::new()?;
+        {
+            let conn = Connection::open(temp_db.path())?;
+            conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, name TEXT)", [])?;
+            conn.execute("INSERT INTO test (name) VALUES (?1)", params!["Alice"])?;
+        }
+        let db_data = std::fs::read(temp_db.path())?;
+
+        let archive_in = NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&archive_in)?;
+            writer.add_file("data.db", &db_data)?;
+            writer.add_file("config.json", b"{}")?;
+            writer.finalize()?;
+        }
+
+        let archive_out = NamedTempFile::new()?.into_temp_path();
+        EngramVfs::update_database(&archive_in, &archive_out, "data.db", |conn| {
+            // WAL content must be checkpointed into the embedded database
+            conn.query_row("PRAGMA journal_mode = WAL", [], |_| Ok(()))?;
+            conn.execute("INSERT INTO test (name) VALUES (?1)", params!["Bob"])?;
+            Ok(())
+        })?;
+
+        let vfs = EngramVfs::new(&archive_out);
+        let conn = vfs.open_database("data.db")?;
+        let mut stmt = conn.prepare("SELECT name FROM test ORDER BY id")?;
+        let names: Vec<String> = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<std::result::Result<Vec<_>, _>>()?;
+        assert_eq!(names, vec!["Alice", "Bob"]);
+
+        let reader = crate::ArchiveReader::open_and_init(&archive_out)?;
+        assert_eq!(reader.entry_count(), 2);
+        assert!(reader.contains("config.json"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_database_same_path_rejected() -> Result<()> {
+        let archive = NamedTempFile::new()?.into_temp_path();
+        let result = EngramVfs::update_database(&archive, &archive, "data.db", |_| Ok(()));
+        assert!(matches!(result, Err(EngramError::PathError(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_database_same_file_spelled_differently() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        std::fs::create_dir(dir.path().join("sub"))?;
+        let archive_in = dir.path().join("app.eng");
+        {
+            let mut writer = ArchiveWriter::create(&archive_in)?;
+            writer.add_file("config.json", b"{}")?;
+            writer.finalize()?;
+        }
+
+        let archive_out = dir.path().join(".").join("sub/../app.eng");
+        let result = EngramVfs::update_database(&archive_in, &archive_out, "data.db", |_| Ok(()));
+        assert!(matches!(result, Err(EngramError::PathError(_))));
+
+        // The input is left intact
+        let mut reader = crate::ArchiveReader::open_and_init(&archive_in)?;
+        assert_eq!(reader.read_file("config.json")?, b"{}");
+        Ok(())
+    }
+
+    #[test]
+    fn test_update_database_keeps_header_settings() -> Result<()> {
+        let temp_db = NamedTempFile::new()?;
+        Connection::open(temp_db.path())?.execute("CREATE TABLE test (id INTEGER)", [])?;
+        let archive_in = NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&archive_in)?
+                .with_content_version(7)
+                .with_label(*b"app-data\0\0\0\0\0\0\0\0")
+                .with_sorted_directory();
+            writer.add_file("data.db", &std::fs::read(temp_db.path())?)?;
+            writer.finalize()?;
+        }
+
+        let archive_out = NamedTempFile::new()?.into_temp_path();
+        EngramVfs::update_database(&archive_in, &archive_out, "data.db", |_| Ok(()))?;
+
+        let before = crate::ArchiveReader::open(&archive_in)?;
+        let after = crate::ArchiveReader::open(&archive_out)?;
+        assert_eq!(after.header().content_version, 7);
+        assert_eq!(after.header().label, before.header().label);
+        assert!(after.header().has_sorted_directory());
+        Ok(())
+    }
 }
@@ -0,0 +1,255 @@
+//! Conversion between ZIP and Engram archives
+//!
+//! Requires the `zip-convert` feature (enabled by default).
+//!
+//! # Mapping Rules
+//!
+//! - ZIP directory entries are dropped on import and counted in
+//!   [`ConversionReport::directories_dropped`]; explicit Engram directory
+//!   entries become ZIP directory entries on export
+//! - ZIP modification times map to `modified_time`
+//! - Deflated ZIP entries are recompressed using Engram's compression selection,
+//!   or deflated again with [`ConversionOptions::preserve_compression`]
+//! - Entries with unsafe paths (`..`, absolute) or paths longer than 255 bytes are
+//!   reported in the [`ConversionReport`] and skipped instead of failing the conversion
+//!
+//! # Example
+//!
+//! ```no_run
+//! use engram_rs::convert::{eng_to_zip, zip_to_eng, ConversionOptions};
+//! # use engram_rs::error::Result;
+//!
+//! # fn main() -> Result<()> {
+//! let zip = std::fs::File::open("assets.zip")?;
+//! let report = zip_to_eng(zip, "assets.eng", ConversionOptions::default())?;
+//! println!("Imported {} files, skipped {}", report.imported, report.skipped.len());
+//!
+//! let out = std::fs::File::create("roundtrip.zip")?;
+//! eng_to_zip("assets.eng", out)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::archive::{
+    normalize_path, preallocation, validate_path, ArchiveReader, ArchiveWriter, EntryAttributes,
+};
+use crate::archive::{CompressionMethod, MAX_PATH_LENGTH};
+use crate::error::{EngramError, Result};
+use std::io::{Read, Seek, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+
+/// Options for [`zip_to_eng`]
+#[derive(Debug, Clone, Default)]
+pub struct ConversionOptions {
+    /// Keep the ZIP entry's compression instead of re-selecting it.
+    ///
+    /// Stored entries stay uncompressed and deflated entries are stored with
+    /// `CompressionMethod::Deflate`. Entries using any other ZIP method are
+    /// recompressed using Engram's selection logic.
+    pub preserve_compression: bool,
+}
+
+/// Entry that was not converted
+#[derive(Debug, Clone)]
+pub struct SkippedEntry {
+    /// Path as stored in the source archive
+    pub path: String,
+    /// Why the entry was skipped
+    pub reason: String,
+}
+
+/// Summary of a ZIP to Engram conversion
+#[derive(Debug, Clone, Default)]
+pub struct ConversionReport {
+    /// Number of files written to the Engram archive
+    pub imported: usize,
+    /// Number of directory entries dropped
+    pub directories_dropped: usize,
+    /// Entries skipped due to invalid paths
+    pub skipped: Vec<SkippedEntry>,
+}
+
+/// Convert a ZIP archive into a new Engram archive at `dest`
+pub fn zip_to_eng<R: Read + Seek, P: AsRef<Path>>(
+    zip: R,
+    dest: P,
+    options: ConversionOptions,
+) -> Result<ConversionReport> {
+    let mut archive = zip::ZipArchive::new(zip)?;
+    let mut writer = ArchiveWriter::create(dest)?;
+    let mut report = ConversionReport::default();
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if entry.is_dir() {
+            report.directories_dropped += 1;
+            continue;
+        }
+
+        let path = entry.name().to_string();
+        let normalized = normalize_path(&path);
+        if let Err(e) = validate_path(&normalized) {
+            report.skipped.push(SkippedEntry {
+                path,
+                reason: e.to_string(),
+            });
+            continue;
+        }
+        if normalized.len() > MAX_PATH_LENGTH {
+            report.skipped.push(SkippedEntry {
+                reason: format!(
+                    "Path too long: {} bytes (max {})",
+                    normalized.len(),
+                    MAX_PATH_LENGTH
+                ),
+                path,
+            });
+            continue;
+        }
+
+        let mut data = Vec::with_capacity(preallocation(entry.size()));
+        entry.read_to_end(&mut data)?;
+
+        let mut attributes = EntryAttributes {
+            mode: entry.unix_mode().unwrap_or(0),
+            ..EntryAttributes::now()
+        };
+        if let Some(dt) = entry.last_modified() {
+            attributes.modified_time = zip_datetime_to_unix(dt);
+        }
+
+        let compression = match entry.compression() {
+            zip::CompressionMethod::Stored if options.preserve_compression => {
+                CompressionMethod::None
+            }
+            zip::CompressionMethod::Deflated if options.preserve_compression => {
+                CompressionMethod::Deflate
+            }
+            _ => ArchiveWriter::select_compression(&normalized, data.len()),
+        };
+
+        writer.write_entry(&normalized, &data, compression, attributes)?;
+        report.imported += 1;
+    }
+
+    writer.finalize()?;
+    Ok(report)
+}
+
+/// Export an Engram archive to ZIP
+///
+/// Entries stored uncompressed in the Engram archive are stored in the ZIP;
+/// everything else is deflated. Explicit directory entries become ZIP
+/// directory entries. Returns the number of entries written.
+pub fn eng_to_zip<P: AsRef<Path>, W: Write + Seek>(archive: P, out: W) -> Result<usize> {
+    let mut reader = ArchiveReader::open_and_init(archive)?;
+    let mut zip = zip::ZipWriter::new(out);
+
+    let paths = reader.list_files().to_vec();
+    for path in &paths {
+        let entry = reader
+            .get_entry(path)
+            .cloned()
+            .ok_or_else(|| EngramError::FileNotFound(path.clone()))?;
+        if entry.is_directory() {
+            zip.add_directory(path.as_str(), SimpleFileOptions::default())?;
+            continue;
+        }
+        let data = reader.read_file(path)?;
+
+        let method = match entry.compression {
+            CompressionMethod::None => zip::CompressionMethod::Stored,
+            _ => zip::CompressionMethod::Deflated,
+        };
+        let mut options = SimpleFileOptions::default()
+            .compression_method(method)
+            .large_file(data.len() as u64 >= u32::MAX as u64);
+        if let Some(dt) = unix_to_zip_datetime(entry.modified_time) {
+            options = options.last_modified_time(dt);
+        }
+        if entry.mode != 0 {
+            options = options.unix_permissions(entry.mode);
+        }
+
+        zip.start_file(path.as_str(), options)?;
+        zip.write_all(&data)?;
+    }
+
+    zip.finish()?;
+    Ok(paths.len())
+}
+
+/// Convert a ZIP (MS-DOS) timestamp to Unix epoch seconds
+///
+/// ZIP timestamps carry no timezone, so they are interpreted as UTC.
+pub(crate) fn zip_datetime_to_unix(dt: zip::DateTime) -> u64 {
+    // Days from civil date (proleptic Gregorian calendar)
+    let (year, month, day) = (dt.year() as i64, dt.month() as i64, dt.day() as i64);
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (month + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + day - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146_097 + doe - 719_468;
+
+    let secs =
+        days * 86_400 + dt.hour() as i64 * 3_600 + dt.minute() as i64 * 60 + dt.second() as i64;
+    secs.max(0) as u64
+}
+
+/// Convert Unix epoch seconds to a ZIP timestamp
+///
+/// Returns `None` outside the MS-DOS range (1980-2107).
+fn unix_to_zip_datetime(secs: u64) -> Option<zip::DateTime> {
+    let days = (secs / 86_400) as i64;
+    let rem = secs % 86_400;
+
+    // Civil date from days (inverse of zip_datetime_to_unix)
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    zip::DateTime::from_date_and_time(
+        u16::try_from(year).ok()?,
+        month as u8,
+        day as u8,
+        (rem / 3_600) as u8,
+        ((rem % 3_600) / 60) as u8,
+        (rem % 60) as u8,
+    )
+    .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_roundtrip() {
+        let dt = zip::DateTime::from_date_and_time(2024, 2, 29, 23, 59, 58).unwrap();
+        let secs = zip_datetime_to_unix(dt);
+        assert_eq!(secs, 1_709_251_198);
+
+        let back = unix_to_zip_datetime(secs).unwrap();
+        assert_eq!(back.year(), 2024);
+        assert_eq!(back.month(), 2);
+        assert_eq!(back.day(), 29);
+        assert_eq!(back.hour(), 23);
+        assert_eq!(back.minute(), 59);
+        assert_eq!(back.second(), 58);
+    }
+
+    #[test]
+    fn test_timestamp_out_of_dos_range() {
+        // 1970 predates the MS-DOS epoch (1980)
+        assert!(unix_to_zip_datetime(0).is_none());
+    }
+}
@@ -0,0 +1,46 @@
+//! Key material helpers
+//!
+//! Keys held by readers and writers are stored as [`SecretKey`], which wipes
+//! its bytes when dropped. This is best effort: keys passed in by the caller,
+//! copies made by moves or by the compiler, and memory swapped to disk before
+//! the drop are out of reach.
+
+use subtle::ConstantTimeEq;
+use zeroize::Zeroizing;
+
+/// 32-byte AES key, zeroed on drop
+pub type SecretKey = Zeroizing<[u8; 32]>;
+
+/// Compare two byte strings without an early exit on the first difference
+///
+/// Use this for derived keys, tags and other secrets. Only the contents are
+/// compared in constant time: slices of different lengths return `false`
+/// immediately.
+///
+/// # Example
+///
+/// ```
+/// use engram_rs::crypto::ct_eq;
+///
+/// assert!(ct_eq(b"derived key", b"derived key"));
+/// assert!(!ct_eq(b"derived key", b"derived kez"));
+/// ```
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    a.ct_eq(b).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ct_eq() {
+        assert!(ct_eq(&[], &[]));
+        assert!(ct_eq(&[7u8; 32], &[7u8; 32]));
+        assert!(!ct_eq(&[7u8; 32], &[7u8; 31]));
+
+        let mut other = [7u8; 32];
+        other[31] = 8;
+        assert!(!ct_eq(&[7u8; 32], &other));
+    }
+}
@@ -26,11 +26,50 @@ pub enum EngramError {
     #[error("Compression failed: {0}")]
     CompressionFailed(String),
 
-    #[error("Decompression failed: {0}")]
-    DecompressionFailed(String),
-
-    #[error("CRC mismatch: expected {expected:08x}, got {actual:08x}")]
-    CrcMismatch { expected: u32, actual: u32 },
+    /// `path` is the archive entry being read; it is empty when raised outside
+    /// an entry, e.g. by `decompress_frames` on raw data
+    #[error("Decompression failed for '{path}': {reason}")]
+    DecompressionFailed { path: String, reason: String },
+
+    #[error("CRC mismatch for '{path}': expected {expected:08x}, got {actual:08x}")]
+    CrcMismatch {
+        path: String,
+        expected: u32,
+        actual: u32,
+    },
+
+    #[error("SHA-256 mismatch for '{path}': expected {expected}, got {actual}")]
+    HashMismatch {
+        path: String,
+        expected: String,
+        actual: String,
+    },
+
+    /// The archive is shorter than its header says it should be, typically
+    /// because writing or copying it was interrupted
+    #[error("Archive is truncated: expected at least {expected_len} bytes, found {actual_len}")]
+    TruncatedArchive { expected_len: u64, actual_len: u64 },
+
+    #[error("Missing archive volume: {0}")]
+    MissingVolume(String),
+
+    /// Format or I/O error while reading the entry at `path`, whose data
+    /// (LOCA header) starts at byte `offset` of the archive
+    #[error("Entry '{path}' at offset {offset}: {source}")]
+    EntryError {
+        path: String,
+        offset: u64,
+        source: Box<EngramError>,
+    },
+
+    /// Error parsing the `index`-th central directory entry, stored at byte
+    /// `offset` of the archive
+    #[error("Central directory entry {index} at offset {offset}: {source}")]
+    DirectoryEntryError {
+        index: u32,
+        offset: u64,
+        source: Box<EngramError>,
+    },
 
     // VFS errors
     #[error("Database not found in archive: {0}")]
@@ -39,9 +78,25 @@ pub enum EngramError {
     #[error("Failed to extract database: {0}")]
     ExtractionFailed(String),
 
+    #[error("Database {path} is {size} bytes, over the {limit} byte limit")]
+    DatabaseTooLarge { path: String, size: u64, limit: u64 },
+
+    #[error("Not a SQLite database: {0}")]
+    NotADatabase(String),
+
+    #[cfg(feature = "vfs")]
     #[error("SQLite error: {0}")]
     SqliteError(#[from] rusqlite::Error),
 
+    /// SQLite error from one of the `VfsReader` query helpers; `sql` is the
+    /// statement, shortened if long
+    #[cfg(feature = "vfs")]
+    #[error("SQLite error in `{sql}`: {source}")]
+    QueryFailed {
+        sql: String,
+        source: rusqlite::Error,
+    },
+
     // Manifest errors
     #[error("Invalid manifest: {0}")]
     InvalidManifest(String),
@@ -78,18 +133,24 @@ pub enum EngramError {
     #[error("Encryption failed")]
     EncryptionFailed,
 
-    #[error("Decryption failed")]
+    #[error("Decryption failed (wrong key or tampered data)")]
     DecryptionFailed,
 
     #[error("Missing decryption key for encrypted archive")]
     MissingDecryptionKey,
 
+    #[error("Decryption key given for an archive that is not encrypted")]
+    NotEncrypted,
+
     #[error("Invalid encryption mode for this operation")]
     InvalidEncryptionMode,
 
     #[error("Invalid nonce size or format")]
     InvalidNonce,
 
+    #[error("Recipient not found in archive: {0}")]
+    RecipientNotFound(String),
+
     // I/O errors
     #[error("I/O error: {0}")]
     Io(#[from] io::Error),
@@ -97,6 +158,19 @@ pub enum EngramError {
     #[error("Path error: {0}")]
     PathError(String),
 
+    #[error("Path is reserved for the Engram format: {0}")]
+    ReservedPath(String),
+
+    /// A write failed and the partial entry could not be removed, so the
+    /// output no longer matches the writer's state; the archive cannot be
+    /// finished
+    #[error("Archive writer is unusable after a failed write could not be undone")]
+    WriterPoisoned,
+
+    /// The writer was already finalized (or its finalization already failed)
+    #[error("Archive writer was already finalized")]
+    AlreadyFinalized,
+
     // Serialization errors
     #[error("JSON error: {0}")]
     JsonError(#[from] serde_json::Error),
@@ -104,6 +178,21 @@ pub enum EngramError {
     #[error("TOML error: {0}")]
     TomlError(String),
 
+    #[cfg(feature = "zip-convert")]
+    #[error("ZIP error: {0}")]
+    ZipError(String),
+
+    // Callback and cancellation errors
+    #[error("Progress callback panicked")]
+    CallbackPanicked,
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    // Configuration errors
+    #[error("Invalid options: {0}")]
+    InvalidOptions(String),
+
     // General errors
     #[error("Internal error: {0}")]
     Internal(String),
@@ -112,6 +201,63 @@ pub enum EngramError {
     Other(String),
 }
 
+impl EngramError {
+    /// Decompression failure not yet attributed to an entry (see `with_path`)
+    pub(crate) fn decompression_failed(reason: impl Into<String>) -> Self {
+        EngramError::DecompressionFailed {
+            path: String::new(),
+            reason: reason.into(),
+        }
+    }
+
+    /// Fill in the entry path of a data error raised without one
+    pub(crate) fn with_path(mut self, entry_path: &str) -> Self {
+        if let EngramError::DecompressionFailed { path, .. }
+        | EngramError::CrcMismatch { path, .. }
+        | EngramError::HashMismatch { path, .. } = &mut self
+        {
+            if path.is_empty() {
+                *path = entry_path.to_string();
+            }
+        }
+        self
+    }
+
+    /// Attach the entry being read: fills in the path of data errors (see
+    /// `with_path`) and wraps format and I/O errors in `EntryError`
+    ///
+    /// Other errors (keys, cancellation, errors that already carry context)
+    /// are returned as they are.
+    pub(crate) fn in_entry(self, entry_path: &str, offset: u64) -> Self {
+        match self.with_path(entry_path) {
+            err @ (EngramError::InvalidFormat(_) | EngramError::Io(_)) => EngramError::EntryError {
+                path: entry_path.to_string(),
+                offset,
+                source: Box::new(err),
+            },
+            err => err,
+        }
+    }
+
+    /// The underlying error, looking through `EntryError` and
+    /// `DirectoryEntryError` context
+    ///
+    /// ```
+    /// use engram_rs::EngramError;
+    ///
+    /// fn is_format_error(err: &EngramError) -> bool {
+    ///     matches!(err.root_cause(), EngramError::InvalidFormat(_))
+    /// }
+    /// ```
+    pub fn root_cause(&self) -> &EngramError {
+        match self {
+            EngramError::EntryError { source, .. }
+            | EngramError::DirectoryEntryError { source, .. } => source.root_cause(),
+            err => err,
+        }
+    }
+}
+
 impl From<toml::de::Error> for EngramError {
     fn from(err: toml::de::Error) -> Self {
         EngramError::TomlError(err.to_string())
@@ -124,8 +270,67 @@ impl From<toml::ser::Error> for EngramError {
     }
 }
 
+#[cfg(feature = "zip-convert")]
+impl From<zip::result::ZipError> for EngramError {
+    fn from(err: zip::result::ZipError) -> Self {
+        EngramError::ZipError(err.to_string())
+    }
+}
+
 impl From<ed25519_dalek::SignatureError> for EngramError {
     fn from(err: ed25519_dalek::SignatureError) -> Self {
         EngramError::SignatureVerificationFailed(err.to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_io_error_source() {
+        let err = EngramError::from(io::Error::new(io::ErrorKind::NotFound, "missing"));
+        let source = err.source().expect("I/O errors expose their source");
+        assert_eq!(source.to_string(), "missing");
+        assert!(source.downcast_ref::<io::Error>().is_some());
+
+        assert!(EngramError::Cancelled.source().is_none());
+    }
+
+    #[test]
+    fn test_with_path() {
+        let err = EngramError::decompression_failed("bad frame").with_path("a/b.txt");
+        assert_eq!(
+            err.to_string(),
+            "Decompression failed for 'a/b.txt': bad frame"
+        );
+
+        // An existing path is kept
+        let err = EngramError::CrcMismatch {
+            path: "first.txt".to_string(),
+            expected: 1,
+            actual: 2,
+        }
+        .with_path("second.txt");
+        assert!(matches!(err, EngramError::CrcMismatch { path, .. } if path == "first.txt"));
+    }
+
+    #[test]
+    fn test_in_entry() {
+        let err =
+            EngramError::InvalidFormat("bad LOCA signature".to_string()).in_entry("a.txt", 64);
+        assert_eq!(
+            err.to_string(),
+            "Entry 'a.txt' at offset 64: Invalid archive format: bad LOCA signature"
+        );
+        assert!(matches!(err.root_cause(), EngramError::InvalidFormat(_)));
+        assert!(err.source().is_some());
+
+        // Data errors name the entry themselves; key errors are not entry specific
+        let err = EngramError::decompression_failed("bad frame").in_entry("b.txt", 64);
+        assert!(matches!(err, EngramError::DecompressionFailed { path, .. } if path == "b.txt"));
+        let err = EngramError::DecryptionFailed.in_entry("c.txt", 64);
+        assert!(matches!(err, EngramError::DecryptionFailed));
+    }
+}
@@ -7,6 +7,15 @@
 //! - VFS (Virtual File System) via embedded SQLite databases
 //! - Fast O(1) file lookup
 //!
+//! # Features
+//!
+//! - `vfs` (default): SQLite access to embedded databases via [`VfsReader`] and [`EngramVfs`]
+//! - `zip-convert` (default): ZIP import/export via `convert` and `ArchiveWriter::import_zip`
+//! - `async`: [`AsyncArchiveReader`], reading archives through tokio without blocking the executor
+//! - `core-reader`: reader-only build without native SQLite, for `wasm32-unknown-unknown`
+//!   (`cargo build --target wasm32-unknown-unknown --no-default-features --features core-reader`).
+//!   Use [`ArchiveReader::from_reader`] to parse archives from in-memory bytes.
+//!
 //! # Example
 //!
 //! ```no_run
@@ -25,20 +34,42 @@
 
 // Core modules
 pub mod archive;
+#[cfg(feature = "vfs")]
 pub mod compat;
+#[cfg(feature = "zip-convert")]
+pub mod convert;
+pub mod crypto;
 pub mod error;
 pub mod manifest;
+#[cfg(feature = "vfs")]
 pub mod vfs;
 
 // Re-export commonly used types
+#[cfg(feature = "async")]
+pub use archive::AsyncArchiveReader;
 pub use archive::{
-    ArchiveReader, ArchiveWriter, CompressionMethod, EntryInfo, FileHeader, CD_ENTRY_SIZE,
-    FORMAT_VERSION_MAJOR, FORMAT_VERSION_MINOR, HEADER_SIZE, MAGIC_NUMBER, MAX_PATH_LENGTH,
+    is_reserved_path, mime_type_for_path, recipient_public_key, rekey_archive, rekey_archive_with,
+    train_dictionary, update_recipients, ArchiveEditor, ArchiveInventory, ArchiveLabel,
+    ArchiveReader, ArchiveSummary, ArchiveWriter, ArchiveWriterBuilder, CacheStats,
+    CancellationToken, ChunkerConfig, CompressionMethod, CompressionPolicy, ContentsIter,
+    DefaultPolicy, EncryptionMode, EntryInfo, EntryMetadata, EntryStatus, FileHeader, FileMetadata,
+    FinalizeSummary, ForceMethod, FrameOptions, InventoryEntry, ProgressCallback, ProgressEvent,
+    RawEntry, RecipientChanges, RekeyOptions, RekeyReport, SharedArchive, VerifyReport,
+    BLAKE3_TABLE_PATH, BUILTIN_MIME_TYPES, CD_ENTRY_SIZE, CHUNKS_PREFIX, COMPRESSED_CRC_PATH,
+    ENTRY_FLAG_ALIAS, ENTRY_FLAG_CHUNKED, ENTRY_FLAG_DEDUPLICATED, ENTRY_FLAG_DIRECTORY,
+    ENTRY_FLAG_EXECUTABLE, ENTRY_FLAG_FRAMED, ENTRY_FLAG_SHA256, ENTRY_FLAG_ZSTD_DICTIONARY,
+    ENTRY_METADATA_PATH, FORMAT_VERSION_MAJOR, FORMAT_VERSION_MINOR, HEADER_FLAG_BLAKE3,
+    HEADER_FLAG_CHUNKED, HEADER_FLAG_ENTRY_AAD, HEADER_FLAG_RECIPIENTS,
+    HEADER_FLAG_SORTED_DIRECTORY, HEADER_SIZE, MAGIC_NUMBER, MANIFEST_PATH, MAX_PATH_LENGTH,
+    MIME_TABLE_PATH, RESERVED_PREFIX, SHA256_PREFIX_LEN, VERIFY_END_RECORD, VERIFY_HEADER,
+    ZSTD_DICTIONARY_PATH,
 };
+#[cfg(feature = "vfs")]
 pub use compat::EngramVfs;
 pub use error::{EngramError, Result};
-pub use manifest::{Author, FileEntry, Manifest, Metadata, SignatureEntry};
-pub use vfs::VfsReader;
+pub use manifest::{Author, FileEntry, Manifest, Metadata, SignatureEntry, SignatureVerification};
+#[cfg(feature = "vfs")]
+pub use vfs::{DatabaseHandle, VfsReader};
 
 #[cfg(test)]
 mod tests {
@@ -161,6 +161,80 @@ pub struct FileEntry {
     pub mime_type: Option<String>,
 }
 
+impl FileEntry {
+    /// Guess a MIME type from the file extension, then from magic bytes
+    ///
+    /// Returns `None` when neither identifies the content.
+    pub fn guess_mime(path: &str, data: &[u8]) -> Option<String> {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let by_extension = name
+            .rsplit_once('.')
+            .and_then(|(_, extension)| mime_for_extension(&extension.to_lowercase()));
+        by_extension
+            .or_else(|| mime_for_magic(data))
+            .map(str::to_string)
+    }
+}
+
+/// MIME type for a lowercase file extension
+fn mime_for_extension(extension: &str) -> Option<&'static str> {
+    let mime = match extension {
+        // Text and data
+        "json" => "application/json",
+        "toml" => "application/toml",
+        "yaml" | "yml" => "application/yaml",
+        "xml" => "application/xml",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" => "text/javascript",
+        "wasm" => "application/wasm",
+        // Databases
+        "db" | "sqlite" | "sqlite3" => "application/vnd.sqlite3",
+        // Images
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "ico" => "image/x-icon",
+        // Audio and video
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
+        "ogg" => "audio/ogg",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        // Documents and archives
+        "pdf" => "application/pdf",
+        "zip" => "application/zip",
+        "gz" => "application/gzip",
+        "zst" => "application/zstd",
+        "tar" => "application/x-tar",
+        _ => return None,
+    };
+    Some(mime)
+}
+
+/// MIME type for content with a recognizable signature
+fn mime_for_magic(data: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: [(&[u8], &str); 8] = [
+        (b"SQLite format 3\0", "application/vnd.sqlite3"),
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xFF\xD8\xFF", "image/jpeg"),
+        (b"GIF8", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1F\x8B", "application/gzip"),
+        (b"\x28\xB5\x2F\xFD", "application/zstd"),
+    ];
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| data.starts_with(magic))
+        .map(|(_, mime)| *mime)
+}
+
 /// Cryptographic signature entry
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignatureEntry {
@@ -181,9 +255,65 @@ pub struct SignatureEntry {
     pub signer: Option<String>,
 }
 
+/// Outcome of checking one signature, from `Manifest::verify_detailed`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SignatureVerification {
+    /// Position of the signature in `Manifest::signatures`
+    pub index: usize,
+
+    /// Signer identity, as recorded (not authenticated by the signature)
+    pub signer: Option<String>,
+
+    /// Public key (hex-encoded)
+    pub public_key: String,
+
+    /// Signature algorithm (e.g., "ed25519")
+    pub algorithm: String,
+
+    /// Whether the signature matches the manifest and public key
+    pub valid: bool,
+
+    /// Timestamp when signature was created
+    pub timestamp: u64,
+}
+
+/// `SOURCE_DATE_EPOCH` from the environment, if set to a valid timestamp
+///
+/// The reproducible-builds convention for pinning the times a build records.
+pub(crate) fn source_date_epoch() -> Option<u64> {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+}
+
+/// `SOURCE_DATE_EPOCH` if set, or else the current time
+fn default_timestamp() -> u64 {
+    source_date_epoch().unwrap_or_else(|| {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    })
+}
+
 impl Manifest {
     /// Create a new manifest
+    ///
+    /// `metadata.created` is `SOURCE_DATE_EPOCH` when that environment
+    /// variable holds a valid Unix timestamp, and the current time otherwise;
+    /// use `new_with_timestamp` to set it explicitly.
     pub fn new(id: String, name: String, author: Author, version: String) -> Self {
+        Self::new_with_timestamp(id, name, author, version, default_timestamp())
+    }
+
+    /// Create a new manifest created at `created` (Unix epoch seconds)
+    pub fn new_with_timestamp(
+        id: String,
+        name: String,
+        author: Author,
+        version: String,
+        created: u64,
+    ) -> Self {
         Self {
             version: "0.4.0".to_string(),
             id,
@@ -192,10 +322,7 @@ impl Manifest {
             author,
             metadata: Metadata {
                 version,
-                created: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
+                created,
                 modified: None,
                 license: None,
                 tags: Vec::new(),
@@ -207,8 +334,12 @@ impl Manifest {
     }
 
     /// Add a file entry to the manifest
+    ///
+    /// When `mime_type` is `None` it is guessed with [`FileEntry::guess_mime`];
+    /// pass `Some` to set it explicitly.
     pub fn add_file(&mut self, path: String, data: &[u8], mime_type: Option<String>) {
         let sha256 = hex::encode(Sha256::digest(data));
+        let mime_type = mime_type.or_else(|| FileEntry::guess_mime(&path, data));
         self.files.push(FileEntry {
             path,
             sha256,
@@ -229,22 +360,51 @@ impl Manifest {
 
     /// Calculate canonical hash for signing
     ///
-    /// This creates a deterministic representation of the manifest
-    /// (excluding signatures) for cryptographic signing.
+    /// The manifest, without its signatures, is written as canonical JSON:
+    /// object keys sorted lexicographically at every level and no whitespace.
+    /// The hash depends only on the manifest's content, not on the key order or
+    /// formatting of the JSON it was parsed from.
     pub fn canonical_hash(&self) -> Result<[u8; 32]> {
         // Create a copy without signatures
         let mut manifest_copy = self.clone();
         manifest_copy.signatures.clear();
 
-        // Serialize to JSON with sorted keys (serde_json does this by default)
-        let json = serde_json::to_vec(&manifest_copy)?;
+        let value = serde_json::to_value(&manifest_copy)?;
+        let mut json = Vec::new();
+        write_canonical_json(&value, &mut json)?;
 
         // Hash the JSON
         Ok(Sha256::digest(&json).into())
     }
 
+    /// Hash used for signing before canonical JSON (struct field order)
+    ///
+    /// Still accepted when verifying, so earlier signatures stay valid.
+    fn legacy_hash(&self) -> Result<[u8; 32]> {
+        let mut manifest_copy = self.clone();
+        manifest_copy.signatures.clear();
+        let json = serde_json::to_vec(&manifest_copy)?;
+        Ok(Sha256::digest(&json).into())
+    }
+
     /// Sign the manifest with a signing key
+    ///
+    /// The signature is timestamped like `new` stamps `metadata.created`:
+    /// with `SOURCE_DATE_EPOCH` if set, or else the current time.
     pub fn sign(&mut self, signing_key: &SigningKey, signer: Option<String>) -> Result<()> {
+        self.sign_with_timestamp(signing_key, signer, default_timestamp())
+    }
+
+    /// Sign the manifest, recording `timestamp` (Unix epoch seconds) as the
+    /// signing time
+    ///
+    /// The timestamp is not covered by the signature.
+    pub fn sign_with_timestamp(
+        &mut self,
+        signing_key: &SigningKey,
+        signer: Option<String>,
+        timestamp: u64,
+    ) -> Result<()> {
         let hash = self.canonical_hash()?;
         let signature = signing_key.sign(&hash);
 
@@ -254,10 +414,7 @@ impl Manifest {
             algorithm: "ed25519".to_string(),
             public_key: hex::encode(public_key.to_bytes()),
             signature: hex::encode(signature.to_bytes()),
-            timestamp: std::time::SystemTime::now()
-                .duration_since(std::time::UNIX_EPOCH)
-                .unwrap()
-                .as_secs(),
+            timestamp,
             signer,
         });
 
@@ -266,12 +423,64 @@ impl Manifest {
 
     /// Verify all signatures in the manifest
     pub fn verify_signatures(&self) -> Result<Vec<bool>> {
+        self.verify_signatures_within(None, None)
+    }
+
+    /// Verify all signatures, reporting each one with the signer and key it names
+    ///
+    /// Same checks as `verify_signatures`, in the same order. A signature with
+    /// an unsupported algorithm or a malformed key is reported as invalid.
+    pub fn verify_detailed(&self) -> Result<Vec<SignatureVerification>> {
+        let results = self.verify_signatures()?;
+        Ok(self
+            .signatures
+            .iter()
+            .zip(results)
+            .enumerate()
+            .map(|(index, (entry, valid))| SignatureVerification {
+                index,
+                signer: entry.signer.clone(),
+                public_key: entry.public_key.clone(),
+                algorithm: entry.algorithm.clone(),
+                valid,
+                timestamp: entry.timestamp,
+            })
+            .collect())
+    }
+
+    /// Verify all signatures, also requiring their timestamps to fall within
+    /// `not_before..=not_after` (Unix epoch seconds; `None` leaves that side
+    /// open)
+    ///
+    /// A signature outside the window is reported as invalid even when it is
+    /// cryptographically valid. The timestamp is not covered by the signature,
+    /// so this enforces a freshness policy on honest signers rather than
+    /// proving when a signature was made.
+    ///
+    /// ```no_run
+    /// # use engram_rs::Manifest;
+    /// # fn check(manifest: &Manifest, now: u64) -> engram_rs::Result<()> {
+    /// // Reject signatures older than 90 days or dated in the future
+    /// let valid = manifest.verify_signatures_within(Some(now - 90 * 86_400), Some(now))?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn verify_signatures_within(
+        &self,
+        not_before: Option<u64>,
+        not_after: Option<u64>,
+    ) -> Result<Vec<bool>> {
         let mut results = Vec::new();
         let hash = self.canonical_hash()?;
+        let legacy_hash = self.legacy_hash()?;
 
+        let window = not_before.unwrap_or(0)..=not_after.unwrap_or(u64::MAX);
         for sig_entry in &self.signatures {
-            let result = self.verify_signature_entry(sig_entry, &hash);
-            results.push(result.is_ok());
+            let in_window = window.contains(&sig_entry.timestamp);
+            let result = self
+                .verify_signature_entry(sig_entry, &hash)
+                .or_else(|_| self.verify_signature_entry(sig_entry, &legacy_hash));
+            results.push(in_window && result.is_ok());
         }
 
         Ok(results)
@@ -316,6 +525,38 @@ impl Manifest {
     }
 }
 
+/// Write `value` as JSON with object keys sorted and no whitespace
+fn write_canonical_json(value: &serde_json::Value, out: &mut Vec<u8>) -> Result<()> {
+    match value {
+        serde_json::Value::Array(items) => {
+            out.push(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_canonical_json(item, out)?;
+            }
+            out.push(b']');
+        }
+        serde_json::Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push(b'{');
+            for (index, key) in keys.into_iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                serde_json::to_writer(&mut *out, key)?;
+                out.push(b':');
+                write_canonical_json(&map[key], out)?;
+            }
+            out.push(b'}');
+        }
+        scalar => serde_json::to_writer(&mut *out, scalar)?,
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -360,6 +601,31 @@ mod tests {
         assert_eq!(manifest.files[0].size, 13);
     }
 
+    #[test]
+    fn test_add_file_guesses_mime() {
+        let mut manifest = Manifest::new(
+            "test".to_string(),
+            "Test".to_string(),
+            Author::new("Test"),
+            "0.1.0".to_string(),
+        );
+
+        manifest.add_file("config/settings.json".to_string(), b"{}", None);
+        manifest.add_file("data".to_string(), b"SQLite format 3\0....", None);
+        manifest.add_file("unknown.bin".to_string(), &[0u8; 16], None);
+        manifest.add_file(
+            "schema.json".to_string(),
+            b"{}",
+            Some("application/schema+json".to_string()),
+        );
+
+        let mime = |i: usize| manifest.files[i].mime_type.as_deref();
+        assert_eq!(mime(0), Some("application/json"));
+        assert_eq!(mime(1), Some("application/vnd.sqlite3"));
+        assert_eq!(mime(2), None);
+        assert_eq!(mime(3), Some("application/schema+json"));
+    }
+
     #[test]
     fn test_signature_roundtrip() {
         let mut manifest = Manifest::new(
@@ -393,6 +659,85 @@ mod tests {
         assert!(manifest.is_fully_signed().unwrap());
     }
 
+    /// Write `value` as pretty JSON with object keys in reverse order
+    fn write_reversed_json(value: &serde_json::Value, out: &mut String) {
+        match value {
+            serde_json::Value::Array(items) => {
+                out.push_str("[\n");
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(",\n");
+                    }
+                    write_reversed_json(item, out);
+                }
+                out.push_str("\n]");
+            }
+            serde_json::Value::Object(map) => {
+                let mut keys: Vec<&String> = map.keys().collect();
+                keys.sort_by(|a, b| b.cmp(a));
+                out.push_str("{\n");
+                for (index, key) in keys.into_iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(",\n");
+                    }
+                    out.push_str(&format!("  {:?} : ", key));
+                    write_reversed_json(&map[key], out);
+                }
+                out.push_str("\n}");
+            }
+            scalar => out.push_str(&scalar.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_signature_survives_key_reordering() {
+        let mut manifest = Manifest::new(
+            "test".to_string(),
+            "Test".to_string(),
+            Author::new("Test"),
+            "0.1.0".to_string(),
+        );
+        manifest.description = Some("Reordered".to_string());
+        manifest.add_file("data/a.json".to_string(), b"{}", None);
+        manifest
+            .sign(&SigningKey::generate(&mut OsRng), None)
+            .unwrap();
+
+        let mut reordered = String::new();
+        write_reversed_json(&serde_json::to_value(&manifest).unwrap(), &mut reordered);
+        assert_ne!(reordered, serde_json::to_string_pretty(&manifest).unwrap());
+
+        let parsed = Manifest::from_json(reordered.as_bytes()).unwrap();
+        assert_eq!(
+            parsed.canonical_hash().unwrap(),
+            manifest.canonical_hash().unwrap()
+        );
+        assert!(parsed.is_fully_signed().unwrap());
+    }
+
+    #[test]
+    fn test_legacy_signature_still_verifies() {
+        let mut manifest = Manifest::new(
+            "test".to_string(),
+            "Test".to_string(),
+            Author::new("Test"),
+            "0.1.0".to_string(),
+        );
+
+        // Signed over struct-ordered JSON, as before canonical hashing
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let signature = signing_key.sign(&manifest.legacy_hash().unwrap());
+        manifest.signatures.push(SignatureEntry {
+            algorithm: "ed25519".to_string(),
+            public_key: hex::encode(signing_key.verifying_key().to_bytes()),
+            signature: hex::encode(signature.to_bytes()),
+            timestamp: 0,
+            signer: None,
+        });
+
+        assert!(manifest.is_fully_signed().unwrap());
+    }
+
     #[test]
     fn test_json_roundtrip() {
         let manifest = Manifest::new(
@@ -1,70 +1,159 @@
 //! Virtual File System support for Engram archives
 //!
 //! Provides access to SQLite databases embedded within engram archives,
-//! allowing SQL queries against archived data.
+//! allowing SQL queries against archived data. Databases can also be opened
+//! writable and re-embedded into a new archive via [`DatabaseHandle`].
 
-use crate::archive::ArchiveReader;
+use crate::archive::{ArchiveReader, ArchiveWriter, EntryInfo};
 use crate::error::{EngramError, Result};
-use rusqlite::{Connection, OpenFlags};
+use rusqlite::backup::Backup;
+use rusqlite::{Connection, DatabaseName, OpenFlags, Params, Row};
+use std::io::{BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use tempfile::TempDir;
 
 /// VFS wrapper for accessing SQLite databases in archives
 pub struct VfsReader {
     reader: ArchiveReader,
+    archive_path: PathBuf,
     temp_dir: Option<TempDir>,
+    /// Directory temporary directories are created in (see `with_temp_dir`)
+    temp_root: Option<PathBuf>,
     extracted_dbs: Vec<(String, PathBuf)>,
+    /// Writable copies made by `open_database_mut`, kept until `commit_database_to`
+    writable_dbs: Vec<DatabaseHandle>,
+    /// Largest database (uncompressed bytes) that will be extracted
+    max_database_size: Option<u64>,
+}
+
+/// First 16 bytes of every SQLite database file
+const SQLITE_MAGIC: &[u8; 16] = b"SQLite format 3\0";
+
+/// SQLite sidecar files that must never be embedded alongside a database
+const SQLITE_SIDECAR_SUFFIXES: [&str; 3] = ["-wal", "-shm", "-journal"];
+
+/// Writable copy of a database extracted from an archive
+///
+/// Created by [`VfsReader::open_database_writable`]. After modifying the database,
+/// drop the `Connection` (or at least commit all transactions) and call
+/// [`DatabaseHandle::save_into`] to write a new archive containing the changes.
+/// The temporary copy is removed when the handle is dropped.
+pub struct DatabaseHandle {
+    archive_path: PathBuf,
+    db_path: String,
+    // Owns the temporary copy; removed when the handle is dropped
+    _temp_dir: TempDir,
+    extract_path: PathBuf,
 }
 
 impl VfsReader {
     /// Open an archive for VFS access
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let mut reader = ArchiveReader::open(path)?;
+        let archive_path = path.as_ref().to_path_buf();
+        let mut reader = ArchiveReader::open(&archive_path)?;
         reader.initialize()?;
         Ok(Self {
             reader,
+            archive_path,
             temp_dir: None,
+            temp_root: None,
             extracted_dbs: Vec::new(),
+            writable_dbs: Vec::new(),
+            max_database_size: None,
         })
     }
 
+    /// Match database paths case-insensitively
+    ///
+    /// See `ArchiveReader::with_case_insensitive_lookup`.
+    pub fn with_case_insensitive_lookup(mut self, enabled: bool) -> Self {
+        self.reader.set_case_insensitive_lookup(enabled);
+        self
+    }
+
+    /// Refuse to extract databases larger than `bytes`
+    ///
+    /// Opening a database over the limit fails with
+    /// `EngramError::DatabaseTooLarge` before anything is written to disk.
+    pub fn with_max_database_size(mut self, bytes: u64) -> Self {
+        self.max_database_size = Some(bytes);
+        self
+    }
+
+    /// Extract databases under `dir` instead of the system temp directory
+    ///
+    /// Each extraction still gets its own randomly named subdirectory, removed
+    /// when no longer needed. `dir` must exist and should be on a filesystem
+    /// with room for the largest database opened.
+    pub fn with_temp_dir<P: AsRef<Path>>(mut self, dir: P) -> Self {
+        self.temp_root = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
     /// List all SQLite database files in the archive
+    ///
+    /// Matches on the `.db`, `.sqlite` and `.sqlite3` extensions only, without
+    /// reading any data, so a database stored under another name is missed and
+    /// any file with those extensions is listed. Use [`detect_databases`]
+    /// to check contents instead.
+    ///
+    /// [`detect_databases`]: VfsReader::detect_databases
     pub fn list_databases(&self) -> Vec<String> {
         self.reader
             .list_files()
             .iter()
-            .filter(|path| {
-                path.ends_with(".db") || path.ends_with(".sqlite") || path.ends_with(".sqlite3")
-            })
+            .filter(|path| has_database_extension(path))
             .cloned()
             .collect()
     }
 
+    /// List SQLite database files in the archive by their content
+    ///
+    /// Reads the first 16 bytes of every file and checks for the SQLite header
+    /// (`"SQLite format 3\0"`), whatever the file is named. Entries that cannot
+    /// be read without a key (per-file encrypted archives) fall back to the
+    /// extension match used by [`list_databases`].
+    ///
+    /// [`list_databases`]: VfsReader::list_databases
+    pub fn detect_databases(&mut self) -> Result<Vec<String>> {
+        let mut databases = Vec::new();
+        for path in self.reader.list_files().to_vec() {
+            let is_database = match self.reader.read_prefix(&path, SQLITE_MAGIC.len()) {
+                Ok(prefix) => prefix == SQLITE_MAGIC,
+                Err(EngramError::MissingDecryptionKey | EngramError::DecryptionFailed) => {
+                    has_database_extension(&path)
+                }
+                Err(e) => return Err(e),
+            };
+            if is_database {
+                databases.push(path);
+            }
+        }
+        Ok(databases)
+    }
+
     /// Open a SQLite connection to a database in the archive
     ///
-    /// The database is extracted to a temporary location for access.
-    /// The temporary file is cleaned up when the VfsReader is dropped.
+    /// The database is extracted to a temporary location for access; large
+    /// databases are decompressed straight to disk frame by frame rather than
+    /// held in memory. Every call extracts a fresh copy under a random name,
+    /// readable only by the current user on Unix (see `with_temp_dir` for
+    /// where). The temporary files are cleaned up when the VfsReader is
+    /// dropped.
     pub fn open_database(&mut self, db_path: &str) -> Result<Connection> {
         // Check if database exists in archive
-        if !self.reader.contains(db_path) {
-            return Err(EngramError::DatabaseNotFound(db_path.to_string()));
-        }
+        let db_path = self.resolve_database(db_path)?;
+        let db_path = db_path.as_str();
 
         // Ensure temp directory exists
         if self.temp_dir.is_none() {
-            self.temp_dir = Some(tempfile::tempdir()?);
+            self.temp_dir = Some(self.create_temp_dir()?);
         }
-
-        let temp_dir = self.temp_dir.as_ref().unwrap();
-
-        // Create a safe filename for extraction
-        let safe_name = db_path.replace(['/', '\\'], "_");
-        let extract_path = temp_dir.path().join(safe_name);
+        let temp_dir = self.temp_dir.as_ref().unwrap().path().to_path_buf();
 
         // Extract database to temp location
-        let db_data = self.reader.read_file(db_path)?;
-        std::fs::write(&extract_path, db_data)
-            .map_err(|e| EngramError::ExtractionFailed(e.to_string()))?;
+        let extract_path = self.extract_database(db_path, &temp_dir)?;
 
         // Track extracted database
         self.extracted_dbs
@@ -76,6 +165,261 @@ impl VfsReader {
         Ok(conn)
     }
 
+    /// Open several databases in the archive as one read-only connection
+    ///
+    /// The first database becomes `main`; the others are attached under the
+    /// schema names given by [`attach_name_for`], so queries can join across
+    /// them (`SELECT ... FROM users JOIN events.log ...`). Each database is
+    /// extracted to a temporary file only long enough to copy it into memory
+    /// with SQLite's backup API: nothing is left on disk once this returns, and
+    /// the copies are freed with the connection. The size limit from
+    /// [`with_max_database_size`] applies to each database.
+    ///
+    /// Fails with `EngramError::NotADatabase` for an entry without the SQLite
+    /// header, and with `EngramError::InvalidOptions` if two databases would be
+    /// attached under the same name.
+    ///
+    /// [`attach_name_for`]: VfsReader::attach_name_for
+    /// [`with_max_database_size`]: VfsReader::with_max_database_size
+    pub fn open_databases(&mut self, paths: &[&str]) -> Result<Connection> {
+        let Some((main, attached)) = paths.split_first() else {
+            return Err(EngramError::InvalidOptions(
+                "open_databases needs at least one database".to_string(),
+            ));
+        };
+
+        // Resolve and check every database before extracting any of them
+        let main = self.resolve_database(main)?;
+        let mut schemas: Vec<(String, String)> = Vec::new();
+        for path in attached {
+            let path = self.resolve_database(path)?;
+            let schema = Self::attach_name_for(&path);
+            if ["main", "temp"]
+                .iter()
+                .any(|reserved| schema.eq_ignore_ascii_case(reserved))
+            {
+                return Err(EngramError::InvalidOptions(format!(
+                    "Database '{}' cannot be attached as reserved schema '{}'",
+                    path, schema
+                )));
+            }
+            if let Some((_, other)) = schemas
+                .iter()
+                .find(|(existing, _)| existing.eq_ignore_ascii_case(&schema))
+            {
+                return Err(EngramError::InvalidOptions(format!(
+                    "Databases '{}' and '{}' would both be attached as '{}'",
+                    other, path, schema
+                )));
+            }
+            schemas.push((schema, path));
+        }
+        for path in std::iter::once(&main).chain(schemas.iter().map(|(_, path)| path)) {
+            let prefix = self.reader.read_prefix(path, SQLITE_MAGIC.len())?;
+            if prefix != SQLITE_MAGIC {
+                return Err(EngramError::NotADatabase(path.clone()));
+            }
+        }
+
+        // Removed with everything in it when this returns
+        let temp_dir = self.create_temp_dir()?;
+        let mut conn = Connection::open_in_memory()?;
+        self.copy_into_memory(temp_dir.path(), &main, &mut conn, DatabaseName::Main)?;
+        for (schema, path) in &schemas {
+            // Schema names are sanitized to ASCII letters, digits and underscores
+            conn.execute_batch(&format!("ATTACH DATABASE ':memory:' AS \"{}\";", schema))?;
+            let name = DatabaseName::Attached(schema);
+            self.copy_into_memory(temp_dir.path(), path, &mut conn, name)?;
+        }
+        conn.execute_batch("PRAGMA query_only = ON;")?;
+
+        Ok(conn)
+    }
+
+    /// Schema name `open_databases` attaches a database under
+    ///
+    /// The file stem of `path`, with every character other than an ASCII
+    /// letter, digit or underscore replaced by `_`, and a leading `_` added if
+    /// it would otherwise not start with a letter: `data/users.db` attaches as
+    /// `users`, `logs/2024-events.sqlite` as `_2024_events`.
+    pub fn attach_name_for(path: &str) -> String {
+        let file_name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+        let stem = match file_name.rfind('.') {
+            Some(dot) if dot > 0 => &file_name[..dot],
+            _ => file_name,
+        };
+        let mut name: String = stem
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            name.insert(0, '_');
+        }
+        name
+    }
+
+    /// Run one query against a database and collect the mapped rows
+    ///
+    /// The database is loaded into memory as with [`open_databases`], `sql`
+    /// is prepared and run with `params`, and every row is passed to `map`;
+    /// the connection is closed and nothing is left on disk when this
+    /// returns. SQLite errors, including those returned by `map`, fail with
+    /// `EngramError::QueryFailed` naming the statement.
+    ///
+    /// ```no_run
+    /// # use engram_rs::VfsReader;
+    /// let mut vfs = VfsReader::open("app.eng")?;
+    /// let names: Vec<String> = vfs.query(
+    ///     "users.db",
+    ///     "SELECT name FROM users WHERE age > ?1",
+    ///     [30],
+    ///     |row| row.get(0),
+    /// )?;
+    /// # Ok::<(), engram_rs::EngramError>(())
+    /// ```
+    ///
+    /// [`open_databases`]: VfsReader::open_databases
+    pub fn query<T, P, F>(&mut self, db_path: &str, sql: &str, params: P, map: F) -> Result<Vec<T>>
+    where
+        P: Params,
+        F: FnMut(&Row<'_>) -> rusqlite::Result<T>,
+    {
+        let conn = self.open_databases(&[db_path])?;
+        let mut stmt = conn.prepare(sql).map_err(|e| query_failed(sql, e))?;
+        let rows = stmt
+            .query_map(params, map)
+            .and_then(|rows| rows.collect())
+            .map_err(|e| query_failed(sql, e));
+        rows
+    }
+
+    /// Run a query expected to return a row and map the first one
+    ///
+    /// Like [`query`]; a query returning no rows fails with `QueryFailed`
+    /// wrapping `rusqlite::Error::QueryReturnedNoRows`.
+    ///
+    /// [`query`]: VfsReader::query
+    pub fn query_row<T, P, F>(&mut self, db_path: &str, sql: &str, params: P, map: F) -> Result<T>
+    where
+        P: Params,
+        F: FnOnce(&Row<'_>) -> rusqlite::Result<T>,
+    {
+        let conn = self.open_databases(&[db_path])?;
+        conn.query_row(sql, params, map)
+            .map_err(|e| query_failed(sql, e))
+    }
+
+    /// Run one or more `;`-separated statements that return no rows
+    ///
+    /// The connection is read-only (`PRAGMA query_only`), so statements that
+    /// would modify the database fail with `QueryFailed`; use
+    /// [`open_database_writable`] for changes. Useful for checks such as
+    /// `PRAGMA integrity_check` run for their errors.
+    ///
+    /// [`open_database_writable`]: VfsReader::open_database_writable
+    pub fn execute_batch_readonly(&mut self, db_path: &str, sql: &str) -> Result<()> {
+        let conn = self.open_databases(&[db_path])?;
+        conn.execute_batch(sql).map_err(|e| query_failed(sql, e))
+    }
+
+    /// Open a writable SQLite connection to a copy of a database in the archive
+    ///
+    /// The database is extracted to its own temporary location, so changes never
+    /// touch the source archive. Use the returned [`DatabaseHandle`] to embed the
+    /// modified database into a new archive.
+    pub fn open_database_writable(
+        &mut self,
+        db_path: &str,
+    ) -> Result<(Connection, DatabaseHandle)> {
+        let db_path = self.resolve_database(db_path)?;
+
+        let temp_dir = self.create_temp_dir()?;
+        let extract_path = self.extract_database(&db_path, temp_dir.path())?;
+
+        let conn = Connection::open(&extract_path)?;
+
+        let handle = DatabaseHandle {
+            archive_path: self.archive_path.clone(),
+            db_path,
+            _temp_dir: temp_dir,
+            extract_path,
+        };
+
+        Ok((conn, handle))
+    }
+
+    /// Open a writable SQLite connection to a copy of a database, kept by the VFS
+    ///
+    /// Like [`open_database_writable`], except that the copy is tracked here:
+    /// opening the same database again connects to the same copy, and
+    /// [`commit_database_to`] writes it into a new archive. Copies are removed
+    /// when the `VfsReader` is dropped.
+    ///
+    /// [`open_database_writable`]: VfsReader::open_database_writable
+    /// [`commit_database_to`]: VfsReader::commit_database_to
+    pub fn open_database_mut(&mut self, db_path: &str) -> Result<Connection> {
+        let db_path = self.resolve_database(db_path)?;
+        if let Some(handle) = self.writable_handle(&db_path) {
+            return Ok(Connection::open(&handle.extract_path)?);
+        }
+        let (conn, handle) = self.open_database_writable(&db_path)?;
+        self.writable_dbs.push(handle);
+        Ok(conn)
+    }
+
+    /// Write a new archive at `out_archive` with a database modified through
+    /// [`open_database_mut`]
+    ///
+    /// `conn` is closed first, so all of its changes are on disk; other
+    /// connections to the copy must have committed their transactions. The
+    /// new archive holds every entry of the source archive, with the database
+    /// replaced as by [`DatabaseHandle::save_into`], and keeps the source's
+    /// content version, label and sorted-directory setting. Archives are
+    /// never changed in place, so `out_archive` must not be the source.
+    ///
+    /// [`open_database_mut`]: VfsReader::open_database_mut
+    pub fn commit_database_to<P: AsRef<Path>>(
+        &self,
+        db_path: &str,
+        conn: Connection,
+        out_archive: P,
+    ) -> Result<()> {
+        let db_path = self.resolve_database(db_path)?;
+        let handle = self.writable_handle(&db_path).ok_or_else(|| {
+            EngramError::InvalidOptions(format!(
+                "{} was not opened with open_database_mut",
+                db_path
+            ))
+        })?;
+        let out_archive = out_archive.as_ref();
+        if out_archive.exists()
+            && std::fs::canonicalize(out_archive)? == std::fs::canonicalize(&self.archive_path)?
+        {
+            return Err(EngramError::PathError(
+                "Cannot commit a database over its source archive".to_string(),
+            ));
+        }
+        conn.close().map_err(|(_, err)| err)?;
+
+        let header = self.reader.header();
+        let mut writer = ArchiveWriter::create(out_archive)?
+            .with_content_version(header.content_version)
+            .with_label(header.label.0);
+        if header.has_sorted_directory() {
+            writer = writer.with_sorted_directory();
+        }
+        handle.write_into(&mut writer)?;
+        writer.finalize()?;
+        Ok(())
+    }
+
+    /// Copy made by `open_database_mut` for a resolved database path
+    fn writable_handle(&self, db_path: &str) -> Option<&DatabaseHandle> {
+        self.writable_dbs
+            .iter()
+            .find(|handle| handle.db_path == db_path)
+    }
+
     /// Get the underlying archive reader
     pub fn archive(&self) -> &ArchiveReader {
         &self.reader
@@ -88,16 +432,190 @@ impl VfsReader {
 
     /// Check if a database is already extracted
     pub fn is_extracted(&self, db_path: &str) -> bool {
-        self.extracted_dbs.iter().any(|(path, _)| path == db_path)
+        self.get_extracted_path(db_path).is_some()
     }
 
     /// Get the path to an extracted database
     pub fn get_extracted_path(&self, db_path: &str) -> Option<&PathBuf> {
+        let db_path = self.resolve_database(db_path).ok()?;
         self.extracted_dbs
             .iter()
-            .find(|(path, _)| path == db_path)
+            .find(|(path, _)| *path == db_path)
             .map(|(_, extracted_path)| extracted_path)
     }
+
+    /// New temporary directory, under `with_temp_dir` if set
+    ///
+    /// Randomly named and, on Unix, accessible only to the current user.
+    fn create_temp_dir(&self) -> Result<TempDir> {
+        let mut builder = tempfile::Builder::new();
+        builder.prefix("engram-vfs-");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            builder.permissions(std::fs::Permissions::from_mode(0o700));
+        }
+        Ok(match &self.temp_root {
+            Some(root) => builder.tempdir_in(root)?,
+            None => builder.tempdir()?,
+        })
+    }
+
+    /// Decompress a database into a new file in `dir`, enforcing the size limit
+    ///
+    /// The file name is the database path with separators replaced, plus a
+    /// random suffix so repeated and concurrent extractions never collide; on
+    /// Unix it is created with mode 0o600. Returns the path of the file. A
+    /// partially written file is removed if extraction fails.
+    fn extract_database(&mut self, db_path: &str, dir: &Path) -> Result<PathBuf> {
+        let size = self
+            .reader
+            .get_entry(db_path)
+            .map(|entry| entry.uncompressed_size)
+            .ok_or_else(|| EngramError::DatabaseNotFound(db_path.to_string()))?;
+        if let Some(limit) = self.max_database_size.filter(|&limit| size > limit) {
+            return Err(EngramError::DatabaseTooLarge {
+                path: db_path.to_string(),
+                size,
+                limit,
+            });
+        }
+
+        let safe_name = db_path.replace(['/', '\\'], "_");
+        let (file, extract_path) = tempfile::Builder::new()
+            .prefix(&format!("{}.", safe_name))
+            .tempfile_in(dir)
+            .and_then(|file| file.keep().map_err(|e| e.error))
+            .map_err(|e| EngramError::ExtractionFailed(e.to_string()))?;
+        let mut out = BufWriter::new(file);
+        let result = self
+            .reader
+            .read_file_to(db_path, &mut out)
+            .and_then(|_| Ok(out.flush()?));
+        if let Err(err) = result {
+            drop(out);
+            let _ = std::fs::remove_file(&extract_path);
+            return Err(err);
+        }
+        Ok(extract_path)
+    }
+
+    /// Extract a database into `temp_dir` and copy it into schema `name` of `conn`
+    ///
+    /// The extracted file is removed once copied.
+    fn copy_into_memory(
+        &mut self,
+        temp_dir: &Path,
+        db_path: &str,
+        conn: &mut Connection,
+        name: DatabaseName<'_>,
+    ) -> Result<()> {
+        let extract_path = self.extract_database(db_path, temp_dir)?;
+        {
+            let source =
+                Connection::open_with_flags(&extract_path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+            Backup::new_with_names(&source, DatabaseName::Main, conn, name)?.run_to_completion(
+                64,
+                Duration::ZERO,
+                None,
+            )?;
+        }
+        std::fs::remove_file(&extract_path)?;
+        Ok(())
+    }
+
+    /// Stored archive path of a database, matched like `ArchiveReader::get_entry`
+    fn resolve_database(&self, db_path: &str) -> Result<String> {
+        self.reader
+            .get_entry(db_path)
+            .map(|entry| entry.path.clone())
+            .ok_or_else(|| EngramError::DatabaseNotFound(db_path.to_string()))
+    }
+}
+
+/// Longest part of a statement quoted in `EngramError::QueryFailed`
+const SQL_SNIPPET_LEN: usize = 120;
+
+/// `QueryFailed` for `sql`, quoting at most `SQL_SNIPPET_LEN` characters
+fn query_failed(sql: &str, source: rusqlite::Error) -> EngramError {
+    let sql = sql.trim();
+    let sql = match sql.char_indices().nth(SQL_SNIPPET_LEN) {
+        Some((end, _)) => format!("{}...", &sql[..end]),
+        None => sql.to_string(),
+    };
+    EngramError::QueryFailed { sql, source }
+}
+
+/// Whether a path has a SQLite database extension
+fn has_database_extension(path: &str) -> bool {
+    path.ends_with(".db") || path.ends_with(".sqlite") || path.ends_with(".sqlite3")
+}
+
+impl DatabaseHandle {
+    /// Path of the database inside the archive
+    pub fn db_path(&self) -> &str {
+        &self.db_path
+    }
+
+    /// Path of the writable temporary copy
+    pub fn temp_path(&self) -> &Path {
+        &self.extract_path
+    }
+
+    /// Write the source archive's entries into `writer`, replacing the database
+    ///
+    /// Every entry except the database (and any `-wal`, `-shm`, or `-journal`
+    /// sidecars) is copied with its metadata. The modified database is
+    /// checkpointed first so WAL contents are folded into the main file; the
+    /// sidecar files themselves are never embedded. The caller still has to
+    /// call `finalize()` on the writer.
+    pub fn save_into(self, writer: &mut ArchiveWriter) -> Result<()> {
+        self.write_into(writer)
+    }
+
+    /// `save_into`, keeping the handle
+    fn write_into(&self, writer: &mut ArchiveWriter) -> Result<()> {
+        // Fold any WAL content into the main database file
+        {
+            let conn = Connection::open(&self.extract_path)?;
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))?;
+            conn.query_row("PRAGMA journal_mode = DELETE", [], |_| Ok(()))?;
+        }
+        let db_data = std::fs::read(&self.extract_path)?;
+
+        let mut source = ArchiveReader::open_and_init(&self.archive_path)?;
+        let is_sidecar = |path: &str| {
+            SQLITE_SIDECAR_SUFFIXES
+                .iter()
+                .any(|suffix| path.strip_suffix(suffix) == Some(self.db_path.as_str()))
+        };
+
+        for path in source.list_files().to_vec() {
+            if is_sidecar(&path) {
+                continue;
+            }
+
+            let entry = source
+                .get_entry(&path)
+                .cloned()
+                .ok_or_else(|| EngramError::FileNotFound(path.clone()))?;
+            if path == self.db_path {
+                let modified = EntryInfo {
+                    modified_time: SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .unwrap()
+                        .as_secs(),
+                    ..entry
+                };
+                writer.copy_entry(&modified, &db_data)?;
+            } else {
+                let data = source.read_file(&path)?;
+                writer.copy_entry(&entry, &data)?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 impl Drop for VfsReader {
@@ -110,7 +628,7 @@ impl Drop for VfsReader {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::archive::ArchiveWriter;
+    use crate::archive::{ArchiveWriter, CompressionMethod};
     use rusqlite::params;
 
     #[test]
@@ -159,6 +677,111 @@ mod tests {
         Ok(())
     }
 
+    /// Helper: Archive holding `people.db` with three rows
+    fn people_archive() -> Result<tempfile::TempPath> {
+        let temp_db = tempfile::NamedTempFile::new()?;
+        {
+            let conn = Connection::open(temp_db.path())?;
+            conn.execute(
+                "CREATE TABLE people (id INTEGER PRIMARY KEY, name TEXT, age INTEGER)",
+                [],
+            )?;
+            for (name, age) in [("Alice", 34), ("Bob", 27), ("Carol", 41)] {
+                conn.execute(
+                    "INSERT INTO people (name, age) VALUES (?1, ?2)",
+                    params![name, age],
+                )?;
+            }
+        }
+
+        let archive_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        let mut writer = ArchiveWriter::create(&archive_path)?;
+        writer.add_file("people.db", &std::fs::read(temp_db.path())?)?;
+        writer.finalize()?;
+        Ok(archive_path)
+    }
+
+    #[test]
+    fn test_query_binds_parameters() -> Result<()> {
+        let archive_path = people_archive()?;
+        let mut vfs = VfsReader::open(&archive_path)?;
+
+        let names: Vec<String> = vfs.query(
+            "people.db",
+            "SELECT name FROM people WHERE age > ?1 ORDER BY id",
+            [30],
+            |row| row.get(0),
+        )?;
+        assert_eq!(names, vec!["Alice", "Carol"]);
+
+        let age: i64 = vfs.query_row(
+            "people.db",
+            "SELECT age FROM people WHERE name = ?1",
+            params!["Bob"],
+            |row| row.get(0),
+        )?;
+        assert_eq!(age, 27);
+
+        // Bound as a value, not spliced into the statement
+        let none: Vec<i64> = vfs.query(
+            "people.db",
+            "SELECT id FROM people WHERE name = ?1",
+            params!["x' OR '1'='1"],
+            |row| row.get(0),
+        )?;
+        assert!(none.is_empty());
+
+        vfs.execute_batch_readonly("people.db", "PRAGMA integrity_check; SELECT 1;")?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_query_errors_name_the_statement() -> Result<()> {
+        let archive_path = people_archive()?;
+        let mut vfs = VfsReader::open(&archive_path)?;
+
+        let sql = "SELECT * FROM missing_table";
+        match vfs.query("people.db", sql, [], |row| row.get::<_, i64>(0)) {
+            Err(err @ EngramError::QueryFailed { .. }) => {
+                let message = err.to_string();
+                assert!(message.contains(sql), "{}", message);
+                assert!(message.contains("no such table"), "{}", message);
+            }
+            other => panic!("Expected QueryFailed, got: {:?}", other),
+        }
+
+        assert!(matches!(
+            vfs.query_row(
+                "people.db",
+                "SELECT id FROM people WHERE age > 99",
+                [],
+                |row| { row.get::<_, i64>(0) }
+            ),
+            Err(EngramError::QueryFailed {
+                source: rusqlite::Error::QueryReturnedNoRows,
+                ..
+            })
+        ));
+
+        // The connection is read-only
+        assert!(matches!(
+            vfs.execute_batch_readonly("people.db", "DELETE FROM people"),
+            Err(EngramError::QueryFailed { .. })
+        ));
+
+        // Long statements are shortened in the error
+        let long = format!("SELECT {} FROM missing_table", "1, ".repeat(100));
+        match vfs.execute_batch_readonly("people.db", &long) {
+            Err(EngramError::QueryFailed { sql, .. }) => {
+                assert!(sql.ends_with("..."), "{}", sql);
+                assert!(long.starts_with(sql.trim_end_matches("...")));
+                assert!(sql.len() < long.len());
+            }
+            other => panic!("Expected QueryFailed, got: {:?}", other),
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_database_not_found() {
         let archive_path = tempfile::NamedTempFile::new().unwrap().into_temp_path();
@@ -193,4 +816,328 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_detect_databases_by_content() -> Result<()> {
+        let temp_db = tempfile::NamedTempFile::new()?;
+        {
+            let conn = Connection::open(temp_db.path())?;
+            conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, name TEXT)", [])?;
+        }
+        let db_data = std::fs::read(temp_db.path())?;
+
+        let archive_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&archive_path)?;
+            writer.add_file("data.bin", &db_data)?;
+            writer.add_file("state", &db_data)?;
+            writer.add_file("notes.db", b"just some notes, not a database")?;
+            writer.add_file("real.sqlite", &db_data)?;
+            writer.finalize()?;
+        }
+
+        let mut vfs = VfsReader::open(&archive_path)?;
+        let mut detected = vfs.detect_databases()?;
+        detected.sort();
+        assert_eq!(detected, vec!["data.bin", "real.sqlite", "state"]);
+
+        let mut listed = vfs.list_databases();
+        listed.sort();
+        assert_eq!(listed, vec!["notes.db", "real.sqlite"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_detect_databases_encrypted_falls_back_to_extension() -> Result<()> {
+        let archive_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer =
+                ArchiveWriter::create(&archive_path)?.with_per_file_encryption(&[7u8; 32]);
+            writer.add_file("data.bin", b"SQLite format 3\0 but unreadable")?;
+            writer.add_file("app.db", b"encrypted")?;
+            writer.finalize()?;
+        }
+
+        let mut vfs = VfsReader::open(&archive_path)?;
+        assert_eq!(vfs.detect_databases()?, vec!["app.db"]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_max_database_size() -> Result<()> {
+        let archive_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&archive_path)?;
+            writer.add_file("big.db", &[0u8; 2048])?;
+            writer.finalize()?;
+        }
+
+        let mut vfs = VfsReader::open(&archive_path)?.with_max_database_size(1024);
+        let result = vfs.open_database("big.db");
+        assert!(matches!(
+            result,
+            Err(EngramError::DatabaseTooLarge {
+                size: 2048,
+                limit: 1024,
+                ..
+            })
+        ));
+        assert!(!vfs.is_extracted("big.db"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_corrupted_database_reports_crc_mismatch() -> Result<()> {
+        let db_data = database_bytes("CREATE TABLE t (x); INSERT INTO t VALUES ('marker');")?;
+        let archive_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&archive_path)?;
+            writer.add_file_with_compression("data.db", &db_data, CompressionMethod::None)?;
+            writer.finalize()?;
+        }
+        let mut bytes = std::fs::read(&archive_path)?;
+        let start = bytes
+            .windows(db_data.len())
+            .position(|window| window == db_data)
+            .unwrap();
+        bytes[start + 100] ^= 0xFF;
+        std::fs::write(&archive_path, &bytes)?;
+
+        // The archive error is kept as is, not flattened into a message
+        let mut vfs = VfsReader::open(&archive_path)?;
+        match vfs.open_database("data.db").map(|_| ()).unwrap_err() {
+            EngramError::CrcMismatch { path, .. } => assert_eq!(path, "data.db"),
+            other => panic!("{:?}", other),
+        }
+        assert!(!vfs.is_extracted("data.db"));
+
+        Ok(())
+    }
+
+    /// Helper: Bytes of a SQLite database created by `sql`
+    fn database_bytes(sql: &str) -> Result<Vec<u8>> {
+        let temp_db = tempfile::NamedTempFile::new()?;
+        Connection::open(temp_db.path())?.execute_batch(sql)?;
+        Ok(std::fs::read(temp_db.path())?)
+    }
+
+    #[test]
+    fn test_open_databases_cross_database_join() -> Result<()> {
+        let users = database_bytes(
+            "CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT);
+             INSERT INTO users VALUES (1, 'Alice'), (2, 'Bob');",
+        )?;
+        let events = database_bytes(
+            "CREATE TABLE log (user_id INTEGER, action TEXT);
+             INSERT INTO log VALUES (1, 'login'), (2, 'upload'), (1, 'logout');",
+        )?;
+
+        let archive_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&archive_path)?;
+            writer.add_file("users.db", &users)?;
+            writer.add_file("data/events.db", &events)?;
+            writer.finalize()?;
+        }
+
+        let mut vfs = VfsReader::open(&archive_path)?;
+        assert_eq!(VfsReader::attach_name_for("data/events.db"), "events");
+        let conn = vfs.open_databases(&["users.db", "data/events.db"])?;
+
+        let mut stmt = conn.prepare(
+            "SELECT users.name, log.action FROM users
+             JOIN events.log AS log ON log.user_id = users.id
+             ORDER BY log.rowid",
+        )?;
+        let rows: Vec<(String, String)> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(
+            rows,
+            vec![
+                ("Alice".to_string(), "login".to_string()),
+                ("Bob".to_string(), "upload".to_string()),
+                ("Alice".to_string(), "logout".to_string()),
+            ]
+        );
+
+        // Read-only, and nothing was left extracted
+        assert!(conn
+            .execute("INSERT INTO events.log VALUES (3, 'hack')", [])
+            .is_err());
+        assert!(!vfs.is_extracted("users.db"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_databases_rejects_non_database_and_collisions() -> Result<()> {
+        let db = database_bytes("CREATE TABLE t (x);")?;
+        let archive_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&archive_path)?;
+            writer.add_file("a/app.db", &db)?;
+            writer.add_file("b/app.sqlite", &db)?;
+            writer.add_file("main.db", &db)?;
+            writer.add_file("notes.db", b"just some notes, not a database")?;
+            writer.finalize()?;
+        }
+
+        let mut vfs = VfsReader::open(&archive_path)?;
+        assert!(matches!(
+            vfs.open_databases(&["a/app.db", "notes.db"]),
+            Err(EngramError::NotADatabase(path)) if path == "notes.db"
+        ));
+        assert!(matches!(
+            vfs.open_databases(&["notes.db", "a/app.db"]),
+            Err(EngramError::NotADatabase(_))
+        ));
+        assert!(matches!(
+            vfs.open_databases(&["main.db", "a/app.db", "b/app.sqlite"]),
+            Err(EngramError::InvalidOptions(_))
+        ));
+        assert!(matches!(
+            vfs.open_databases(&["a/app.db", "main.db"]),
+            Err(EngramError::InvalidOptions(_))
+        ));
+        assert!(matches!(
+            vfs.open_databases(&["a/app.db", "missing.db"]),
+            Err(EngramError::DatabaseNotFound(_))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_attach_name_for() {
+        assert_eq!(VfsReader::attach_name_for("users.db"), "users");
+        assert_eq!(
+            VfsReader::attach_name_for("a\\b\\my-data.v2.sqlite"),
+            "my_data_v2"
+        );
+        assert_eq!(
+            VfsReader::attach_name_for("logs/2024-events.sqlite3"),
+            "_2024_events"
+        );
+        assert_eq!(VfsReader::attach_name_for(".hidden"), "_hidden");
+        assert_eq!(VfsReader::attach_name_for("dir/"), "_");
+    }
+
+    #[test]
+    fn test_open_database_writable_save_into() -> Result<()> {
+        let temp_db = tempfile::NamedTempFile::new()?;
+        {
+            let conn = Connection::open(temp_db.path())?;
+            conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, name TEXT)", [])?;
+            conn.execute("INSERT INTO test (name) VALUES (?1)", params!["Alice"])?;
+        }
+        let db_data = std::fs::read(temp_db.path())?;
+
+        let source_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&source_path)?;
+            writer.add_file("data.db", &db_data)?;
+            writer.add_file("data.db-wal", b"stale wal")?;
+            writer.add_file("notes.txt", b"untouched")?;
+            writer.finalize()?;
+        }
+
+        let output_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut vfs = VfsReader::open(&source_path)?;
+            let (conn, handle) = vfs.open_database_writable("data.db")?;
+            conn.execute("INSERT INTO test (name) VALUES (?1)", params!["Bob"])?;
+            drop(conn);
+
+            let mut writer = ArchiveWriter::create(&output_path)?;
+            handle.save_into(&mut writer)?;
+            writer.finalize()?;
+        }
+
+        // Source archive is unchanged
+        let mut vfs = VfsReader::open(&source_path)?;
+        let count: i64 =
+            vfs.open_database("data.db")?
+                .query_row("SELECT COUNT(*) FROM test", [], |row| row.get(0))?;
+        assert_eq!(count, 1);
+
+        // Output archive has the new row, the other files, and no sidecar
+        let mut vfs = VfsReader::open(&output_path)?;
+        assert!(!vfs.archive().contains("data.db-wal"));
+        assert_eq!(vfs.archive_mut().read_file("notes.txt")?, b"untouched");
+        let count: i64 =
+            vfs.open_database("data.db")?
+                .query_row("SELECT COUNT(*) FROM test", [], |row| row.get(0))?;
+        assert_eq!(count, 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_database_mut_commit_database_to() -> Result<()> {
+        let db_data = database_bytes(
+            "CREATE TABLE test (id INTEGER PRIMARY KEY, name TEXT);
+             INSERT INTO test (name) VALUES ('Alice');",
+        )?;
+        let source_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        {
+            let mut writer = ArchiveWriter::create(&source_path)?
+                .with_content_version(7)
+                .with_sorted_directory();
+            writer.add_file("notes.txt", b"untouched")?;
+            writer.add_file("data/app.db", &db_data)?;
+            writer.finalize()?;
+        }
+
+        let output_path = tempfile::NamedTempFile::new()?.into_temp_path();
+        let mut vfs = VfsReader::open(&source_path)?;
+        let conn = vfs.open_database_mut("data/app.db")?;
+        conn.execute("INSERT INTO test (name) VALUES (?1)", params!["Bob"])?;
+        drop(conn);
+
+        // Reopening connects to the same copy
+        let conn = vfs.open_database_mut("data\\app.db")?;
+        let count: i64 = conn.query_row("SELECT COUNT(*) FROM test", [], |row| row.get(0))?;
+        assert_eq!(count, 2);
+        conn.execute("INSERT INTO test (name) VALUES (?1)", params!["Carol"])?;
+
+        // Archives are never changed in place
+        let conn = match vfs.commit_database_to("data/app.db", conn, &source_path) {
+            Err(EngramError::PathError(_)) => vfs.open_database_mut("data/app.db")?,
+            other => panic!("expected PathError, got {:?}", other),
+        };
+        vfs.commit_database_to("data/app.db", conn, &output_path)?;
+
+        // The source is untouched; the new archive has both rows
+        let count: i64 = vfs.query_row("data/app.db", "SELECT COUNT(*) FROM test", [], |row| {
+            row.get(0)
+        })?;
+        assert_eq!(count, 1);
+        let mut committed = VfsReader::open(&output_path)?;
+        let names: Vec<String> = committed.query(
+            "data/app.db",
+            "SELECT name FROM test ORDER BY id",
+            [],
+            |row| row.get(0),
+        )?;
+        assert_eq!(names, ["Alice", "Bob", "Carol"]);
+        assert_eq!(
+            committed.archive_mut().read_file("notes.txt")?,
+            b"untouched"
+        );
+        let header = committed.archive().header();
+        assert_eq!(header.content_version, 7);
+        assert!(header.has_sorted_directory());
+
+        // Only databases opened with open_database_mut can be committed
+        let other = Connection::open_in_memory()?;
+        assert!(matches!(
+            committed.commit_database_to("data/app.db", other, &source_path),
+            Err(EngramError::InvalidOptions(_))
+        ));
+        Ok(())
+    }
 }
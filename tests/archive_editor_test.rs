@@ -0,0 +1,220 @@
+//! ArchiveEditor tests
+//!
+//! Edited archives copy untouched payloads verbatim from the source.
+
+use engram_rs::archive::LocalEntryHeader;
+use engram_rs::{
+    ArchiveEditor, ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, EntryInfo,
+};
+use tempfile::{NamedTempFile, TempDir};
+
+/// Helper: Compressible content for file `i`
+fn content(i: usize) -> Vec<u8> {
+    format!("line {} of a compressible file\n", i)
+        .repeat(500)
+        .into_bytes()
+}
+
+/// Helper: Archive with a few files in nested directories
+fn create_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_content_version(9);
+    writer.add_file("a/first.txt", &content(1)).unwrap();
+    writer
+        .add_file_with_compression("a/second.txt", &content(2), CompressionMethod::Lz4)
+        .unwrap();
+    writer.add_file("secrets/token.txt", b"hunter2").unwrap();
+    writer.add_file("b/third.txt", &content(3)).unwrap();
+    writer.add_directory("empty").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Stored payload bytes of an entry, read straight from the archive file
+fn stored_payload(bytes: &[u8], entry: &EntryInfo) -> Vec<u8> {
+    let loca = &bytes[entry.data_offset as usize..];
+    let start = entry.data_offset as usize
+        + LocalEntryHeader::read_from(loca).unwrap().header_size();
+    bytes[start..start + entry.compressed_size as usize].to_vec()
+}
+
+/// Helper: Assert `path` in `edited` is stored exactly like `source_path` in `source`
+fn assert_copied_verbatim(
+    source: &std::path::Path,
+    source_path: &str,
+    edited: &std::path::Path,
+    path: &str,
+) {
+    let original = ArchiveReader::open_and_init(source).unwrap();
+    let copy = ArchiveReader::open_and_init(edited).unwrap();
+    let before = original.get_entry(source_path).unwrap();
+    let after = copy.get_entry(path).unwrap();
+
+    assert_eq!(after.crc32, before.crc32);
+    assert_eq!(after.compressed_size, before.compressed_size);
+    assert_eq!(after.compression, before.compression);
+    assert_eq!(after.modified_time, before.modified_time);
+    assert_eq!(
+        stored_payload(&std::fs::read(edited).unwrap(), after),
+        stored_payload(&std::fs::read(source).unwrap(), before)
+    );
+}
+
+#[test]
+fn test_remove_middle_entry() {
+    let source = create_archive();
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("removed.eng");
+
+    let mut editor = ArchiveEditor::open(source.path()).unwrap();
+    editor.remove("secrets/token.txt").unwrap();
+    editor.save_to(&dest).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(&dest).unwrap();
+    assert_eq!(
+        reader.list_files(),
+        ["a/first.txt", "a/second.txt", "b/third.txt", "empty"]
+    );
+    assert!(!reader.contains("secrets/token.txt"));
+    assert!(reader.get_entry("empty").unwrap().is_directory());
+    assert_eq!(reader.content_version(), 9);
+    assert!(reader.verify_all().unwrap().is_ok());
+
+    // The removed content is gone from the file, not just the directory
+    let bytes = std::fs::read(&dest).unwrap();
+    assert!(!bytes.windows(7).any(|window| window == b"hunter2"));
+
+    for path in ["a/first.txt", "a/second.txt", "b/third.txt"] {
+        assert_copied_verbatim(source.path(), path, &dest, path);
+    }
+}
+
+#[test]
+fn test_rename_across_directories() {
+    let source = create_archive();
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("renamed.eng");
+
+    let mut editor = ArchiveEditor::open(source.path()).unwrap();
+    editor.rename("a/first.txt", "c\\d\\moved.txt").unwrap();
+    editor.save_to(&dest).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(&dest).unwrap();
+    assert!(!reader.contains("a/first.txt"));
+    assert_eq!(reader.read_file("c/d/moved.txt").unwrap(), content(1));
+    assert!(reader.verify_all().unwrap().is_ok());
+
+    assert_copied_verbatim(source.path(), "a/first.txt", &dest, "c/d/moved.txt");
+    assert_copied_verbatim(source.path(), "b/third.txt", &dest, "b/third.txt");
+}
+
+#[test]
+fn test_rename_validates_path() {
+    let source = create_archive();
+    let mut editor = ArchiveEditor::open(source.path()).unwrap();
+
+    for bad in ["../escape.txt", "/absolute.txt", "", "b/third.txt"] {
+        assert!(
+            matches!(
+                editor.rename("a/first.txt", bad),
+                Err(EngramError::PathError(_))
+            ),
+            "Accepted {:?}",
+            bad
+        );
+    }
+    let too_long = "x".repeat(256);
+    assert!(matches!(
+        editor.rename("a/first.txt", &too_long),
+        Err(EngramError::PathError(_))
+    ));
+    assert!(matches!(
+        editor.rename("missing.txt", "new.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+    assert_eq!(editor.list_files()[0], "a/first.txt");
+}
+
+#[test]
+fn test_replace_with_larger_content() {
+    let source = create_archive();
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("replaced.eng");
+    let larger: Vec<u8> = (0..200_000u32).flat_map(|i| i.to_le_bytes()).collect();
+
+    let mut editor = ArchiveEditor::open(source.path()).unwrap();
+    editor.replace("a/second.txt", &larger).unwrap();
+    assert!(matches!(
+        editor.replace("empty", b"data"),
+        Err(EngramError::PathError(_))
+    ));
+    editor.save_to(&dest).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(&dest).unwrap();
+    assert_eq!(reader.read_file("a/second.txt").unwrap(), larger);
+    assert_eq!(reader.list_files().len(), 5);
+    assert!(reader.verify_all().unwrap().is_ok());
+
+    for path in ["a/first.txt", "secrets/token.txt", "b/third.txt"] {
+        assert_copied_verbatim(source.path(), path, &dest, path);
+    }
+}
+
+#[test]
+fn test_deduplicated_entries_stay_shared() {
+    let source = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(source.path()).unwrap().with_dedup();
+        writer.add_file("original.txt", &content(5)).unwrap();
+        writer.add_file("copy_1.txt", &content(5)).unwrap();
+        writer.add_file("copy_2.txt", &content(5)).unwrap();
+        writer.finalize().unwrap();
+    }
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("dedup.eng");
+
+    let mut editor = ArchiveEditor::open(source.path()).unwrap();
+    editor.remove("original.txt").unwrap();
+    editor.save_to(&dest).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(&dest).unwrap();
+    let first = reader.get_entry("copy_1.txt").unwrap().clone();
+    let second = reader.get_entry("copy_2.txt").unwrap().clone();
+    assert!(!first.is_deduplicated());
+    assert!(second.is_deduplicated());
+    assert_eq!(first.data_offset, second.data_offset);
+    assert_eq!(reader.read_file("copy_2.txt").unwrap(), content(5));
+    assert!(reader.verify_all().unwrap().is_ok());
+}
+
+#[test]
+fn test_save_over_source_rejected() {
+    let source = create_archive();
+    let mut editor = ArchiveEditor::open(source.path()).unwrap();
+    editor.remove("a/first.txt").unwrap();
+    assert!(matches!(
+        editor.save_to(source.path()),
+        Err(EngramError::PathError(_))
+    ));
+    assert!(ArchiveReader::open_and_init(source.path())
+        .unwrap()
+        .contains("a/first.txt"));
+}
+
+#[test]
+fn test_encrypted_archive_rejected() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_per_file_encryption(&[1u8; 32]);
+        writer.add_file("a.txt", b"a").unwrap();
+        writer.finalize().unwrap();
+    }
+    assert!(matches!(
+        ArchiveEditor::open(temp_file.path()),
+        Err(EngramError::Other(_))
+    ));
+}
@@ -0,0 +1,92 @@
+//! Header content version and label tests
+
+use engram_rs::archive::EndRecord;
+use engram_rs::{ArchiveLabel, ArchiveReader, ArchiveWriter};
+use std::fs::File;
+use tempfile::NamedTempFile;
+
+const LABEL: [u8; 16] = *b"backup-schema-07";
+
+#[test]
+fn test_content_version_and_label_roundtrip() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_content_version(7)
+            .with_label(LABEL);
+        writer.add_file("data.json", b"{}").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    // Available before initialization
+    let reader = ArchiveReader::open(temp_file.path()).unwrap();
+    assert_eq!(reader.content_version(), 7);
+    assert_eq!(reader.label(), ArchiveLabel(LABEL));
+    assert!(!reader.label().is_empty());
+    assert_eq!(reader.header().content_version, 7);
+}
+
+#[test]
+fn test_label_survives_archive_encryption() {
+    let key = [0x33u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_archive_encryption(&key)
+            .with_content_version(3)
+            .with_label(LABEL);
+        writer.add_file("secret.txt", b"secret").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    // The header is readable without the key
+    let reader = ArchiveReader::open(temp_file.path()).unwrap();
+    assert_eq!(reader.content_version(), 3);
+    assert_eq!(reader.label().as_bytes(), &LABEL);
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    assert_eq!(reader.label(), ArchiveLabel(LABEL));
+    assert_eq!(reader.read_file("secret.txt").unwrap(), b"secret");
+}
+
+#[test]
+fn test_defaults_read_as_empty() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        writer.add_file("a.txt", b"a").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    // Matches archives written before these fields were exposed
+    let reader = ArchiveReader::open(temp_file.path()).unwrap();
+    assert_eq!(reader.content_version(), 0);
+    assert!(reader.label().is_empty());
+    assert_eq!(reader.label(), ArchiveLabel::default());
+
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    assert!(bytes[36..40].iter().all(|&b| b == 0));
+    assert!(bytes[44..64].iter().all(|&b| b == 0));
+}
+
+#[test]
+fn test_content_version_in_end_record() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_content_version(42);
+        writer.add_file("revision.txt", b"42").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.content_version(), 42);
+
+    // A tail read reports it without touching the header
+    let end_record = EndRecord::read_from_end(File::open(temp_file.path()).unwrap()).unwrap();
+    assert_eq!(end_record.content_version, 42);
+    assert_eq!(end_record.entry_count, 1);
+}
@@ -0,0 +1,200 @@
+//! AsyncArchiveReader tests (`async` feature)
+
+use engram_rs::{
+    train_dictionary, ArchiveReader, ArchiveWriter, AsyncArchiveReader, ChunkerConfig,
+    CompressionMethod, EncryptionMode, EngramError,
+};
+use tempfile::NamedTempFile;
+
+/// Helper: Distinct, compressible content for file `i`
+fn content(i: usize) -> Vec<u8> {
+    format!("async payload {}\n", i).repeat(200).into_bytes()
+}
+
+/// Helper: Archive with files stored with each compression method
+fn create_archive(configure: impl FnOnce(ArchiveWriter) -> ArchiveWriter) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = configure(ArchiveWriter::create(temp_file.path()).unwrap());
+    let methods = [
+        CompressionMethod::None,
+        CompressionMethod::Lz4,
+        CompressionMethod::Zstd,
+    ];
+    for i in 0..6 {
+        writer
+            .add_file_with_compression(&format!("files/{}.txt", i), &content(i), methods[i % 3])
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_concurrent_reads_from_one_archive() {
+    let temp_file = create_archive(|writer| writer);
+    let reader = AsyncArchiveReader::open_and_init(temp_file.path())
+        .await
+        .unwrap();
+    assert_eq!(reader.entry_count(), 6);
+
+    let (a, b, c, d) = futures::join!(
+        reader.read_file("files/0.txt"),
+        reader.read_file("files/1.txt"),
+        reader.read_file("files/2.txt"),
+        reader.read_file("/files\\3.txt"),
+    );
+    assert_eq!(a.unwrap(), content(0));
+    assert_eq!(b.unwrap(), content(1));
+    assert_eq!(c.unwrap(), content(2));
+    assert_eq!(d.unwrap(), content(3));
+
+    let all =
+        futures::future::join_all(reader.list_files().iter().map(|p| reader.read_file(p))).await;
+    for (i, data) in all.into_iter().enumerate() {
+        assert_eq!(data.unwrap(), content(i));
+    }
+}
+
+#[tokio::test]
+async fn test_matches_sync_reader() {
+    let temp_file = create_archive(|writer| writer.with_sorted_directory());
+    let reader = AsyncArchiveReader::open_and_init(temp_file.path())
+        .await
+        .unwrap();
+    let mut sync_reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    assert_eq!(reader.list_files(), sync_reader.list_files());
+    for path in sync_reader.list_files().to_vec() {
+        assert_eq!(
+            reader.read_file(&path).await.unwrap(),
+            sync_reader.read_file(&path).unwrap()
+        );
+    }
+    assert!(matches!(
+        reader.read_file("missing.txt").await,
+        Err(EngramError::FileNotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn test_encrypted_archives() {
+    let key = [0x5Au8; 32];
+    for per_file in [true, false] {
+        let temp_file = create_archive(|writer| {
+            if per_file {
+                writer.with_per_file_encryption(&key)
+            } else {
+                writer.with_archive_encryption(&key)
+            }
+        });
+
+        let reader = AsyncArchiveReader::open_encrypted(temp_file.path(), &key)
+            .await
+            .unwrap();
+        let (a, b) = futures::join!(
+            reader.read_file("files/1.txt"),
+            reader.read_file("files/2.txt")
+        );
+        assert_eq!(a.unwrap(), content(1));
+        assert_eq!(b.unwrap(), content(2));
+
+        let wrong = AsyncArchiveReader::open_encrypted(temp_file.path(), &[0u8; 32]).await;
+        let result = match wrong {
+            Ok(reader) => reader.read_file("files/1.txt").await.map(|_| ()),
+            Err(err) => Err(err),
+        };
+        assert!(matches!(result, Err(EngramError::DecryptionFailed)));
+    }
+}
+
+#[tokio::test]
+async fn test_encryption_state_reported_after_open() {
+    let key = [0x5Au8; 32];
+    for mode in [
+        EncryptionMode::None,
+        EncryptionMode::Archive,
+        EncryptionMode::PerFile,
+    ] {
+        let temp_file = create_archive(|writer| match mode {
+            EncryptionMode::None => writer,
+            EncryptionMode::Archive => writer.with_archive_encryption(&key),
+            EncryptionMode::PerFile => writer.with_per_file_encryption(&key),
+        });
+
+        // Straight from the header, before any key or `initialize`
+        let reader = AsyncArchiveReader::open(temp_file.path()).await.unwrap();
+        assert_eq!(reader.encryption_mode(), mode);
+        assert_eq!(reader.is_encrypted(), mode != EncryptionMode::None);
+        assert_eq!(reader.requires_key(), mode != EncryptionMode::None);
+
+        if mode != EncryptionMode::None {
+            let mut reader = reader.with_decryption_key(&key);
+            assert!(!reader.requires_key());
+            reader.initialize().await.unwrap();
+            assert_eq!(reader.read_file("files/1.txt").await.unwrap(), content(1));
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_dictionary_and_corruption() {
+    let samples: Vec<Vec<u8>> = (0..200)
+        .map(|i| format!("{{\"id\": {}, \"kind\": \"sample\"}}", i).into_bytes())
+        .collect();
+    let sample_refs: Vec<&[u8]> = samples.iter().map(|s| s.as_slice()).collect();
+    let dictionary = train_dictionary(&sample_refs, 1024).unwrap();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_zstd_dictionary(dictionary);
+    writer.add_file("a.json", &samples[0]).unwrap();
+    writer.add_file("b.json", &samples[1]).unwrap();
+    writer
+        .add_file_with_compression("plain.txt", b"stored as is", CompressionMethod::None)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let reader = AsyncArchiveReader::open_and_init(temp_file.path())
+        .await
+        .unwrap();
+    let (a, b) = futures::join!(reader.read_file("a.json"), reader.read_file("b.json"));
+    assert_eq!(a.unwrap(), samples[0]);
+    assert_eq!(b.unwrap(), samples[1]);
+
+    // Corrupt the stored payload: the CRC check still applies
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    let pos = bytes
+        .windows(12)
+        .position(|window| window == b"stored as is")
+        .unwrap();
+    bytes[pos] ^= 0xFF;
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+
+    let reader = AsyncArchiveReader::open_and_init(temp_file.path())
+        .await
+        .unwrap();
+    assert!(matches!(
+        reader.read_file("plain.txt").await,
+        Err(EngramError::CrcMismatch { .. })
+    ));
+}
+
+#[tokio::test]
+async fn test_read_chunked_entry() {
+    let data: Vec<u8> = (0..300_000u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect();
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_cdc_dedup(ChunkerConfig::new(4096));
+    writer.add_file("big.bin", &data).unwrap();
+    writer.finalize().unwrap();
+
+    let reader = AsyncArchiveReader::open_and_init(temp_file.path())
+        .await
+        .unwrap();
+    assert!(reader.get_entry("big.bin").unwrap().is_chunked());
+    assert_eq!(reader.read_file("big.bin").await.unwrap(), data);
+}
@@ -0,0 +1,199 @@
+//! BLAKE3 entry digest tests
+//!
+//! Covers `ArchiveWriter::with_blake3`, the digest table it writes, and
+//! verification of `EntryInfo::blake3` in eager, lazy and edited archives.
+
+use engram_rs::{
+    ArchiveEditor, ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, BLAKE3_TABLE_PATH,
+};
+use tempfile::NamedTempFile;
+
+/// Helper: Sample files covering raw, compressed and empty payloads
+fn sample_files() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("small.txt", b"tiny".to_vec()),
+        ("docs/large.txt", "blake3 digest ".repeat(2000).into_bytes()),
+        ("empty.bin", Vec::new()),
+    ]
+}
+
+/// Helper: Write the sample files with or without BLAKE3 digests
+fn write_archive(blake3: bool) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_dedup()
+        .with_blake3(blake3);
+    for (path, data) in sample_files() {
+        writer.add_file(path, &data).unwrap();
+    }
+    writer
+        .add_file("docs/copy.txt", &sample_files()[1].1)
+        .unwrap();
+    writer.add_directory("empty_dir").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: `data` followed by its own CRC32, which always has the CRC32
+/// 0x2144DF1C, so two such payloads pass each other's CRC check
+fn with_crc_residue(data: &[u8]) -> Vec<u8> {
+    let mut message = data.to_vec();
+    message.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+    message
+}
+
+#[test]
+fn test_blake3_roundtrip() {
+    let temp_file = write_archive(true);
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(reader.header().has_blake3_digests());
+    assert!(reader.contains(BLAKE3_TABLE_PATH));
+
+    for (path, data) in sample_files() {
+        let entry = reader.get_entry(path).unwrap();
+        assert_eq!(entry.blake3(), Some(*blake3::hash(&data).as_bytes()));
+        assert_eq!(reader.read_file(path).unwrap(), data);
+    }
+    // The deduplicated copy shares its original's digest
+    let copy = reader.get_entry("docs/copy.txt").unwrap();
+    assert!(copy.is_deduplicated());
+    assert_eq!(
+        copy.blake3(),
+        Some(*blake3::hash(&sample_files()[1].1).as_bytes())
+    );
+    assert_eq!(reader.get_entry("empty_dir").unwrap().blake3(), None);
+}
+
+#[test]
+fn test_blake3_off_by_default() {
+    let temp_file = write_archive(false);
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(!reader.header().has_blake3_digests());
+    assert!(!reader.contains(BLAKE3_TABLE_PATH));
+    for (path, data) in sample_files() {
+        assert_eq!(reader.get_entry(path).unwrap().blake3(), None);
+        assert_eq!(reader.read_file(path).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_crc_colliding_tamper_detected() {
+    let genuine = with_crc_residue(b"pay 100 to alice");
+    let forged = with_crc_residue(b"pay 999 to mallo");
+    assert_eq!(crc32fast::hash(&genuine), crc32fast::hash(&forged));
+
+    for blake3 in [false, true] {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_blake3(blake3);
+        writer
+            .add_file_with_compression("order.bin", &genuine, CompressionMethod::None)
+            .unwrap();
+        writer.finalize().unwrap();
+
+        let mut bytes = std::fs::read(temp_file.path()).unwrap();
+        let start = bytes
+            .windows(genuine.len())
+            .position(|window| window == genuine)
+            .unwrap();
+        bytes[start..start + forged.len()].copy_from_slice(&forged);
+        std::fs::write(temp_file.path(), &bytes).unwrap();
+
+        let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+        let mut lazy = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+        for reader in [&mut reader, &mut lazy] {
+            let result = reader.read_file("order.bin");
+            if blake3 {
+                assert!(matches!(result, Err(EngramError::HashMismatch { .. })));
+            } else {
+                // CRC32 alone cannot tell
+                assert_eq!(result.unwrap(), forged);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_blake3_digests_survive_edit() {
+    let source = write_archive(true);
+    let dest = NamedTempFile::new().unwrap();
+    {
+        let mut editor = ArchiveEditor::open(source.path()).unwrap();
+        editor.remove("small.txt").unwrap();
+        editor.rename("empty.bin", "moved.bin").unwrap();
+        editor.replace("docs/copy.txt", b"replaced").unwrap();
+        editor.save_to(dest.path()).unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(dest.path()).unwrap();
+    assert!(reader.header().has_blake3_digests());
+    for (path, data) in [
+        ("docs/large.txt", sample_files()[1].1.clone()),
+        ("moved.bin", Vec::new()),
+        ("docs/copy.txt", b"replaced".to_vec()),
+    ] {
+        let entry = reader.get_entry(path).unwrap();
+        assert_eq!(entry.blake3(), Some(*blake3::hash(&data).as_bytes()));
+        assert_eq!(reader.read_file(path).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_blake3_lazy_sorted_directory() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_sorted_directory()
+        .with_blake3(true);
+    let files: Vec<(String, Vec<u8>)> = (0..64)
+        .map(|i| {
+            (
+                format!("file{:02}.txt", i),
+                format!("content {}", i).into_bytes(),
+            )
+        })
+        .collect();
+    for (path, data) in &files {
+        writer.add_file(path, data).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // Looking up the table probes other entries before the digests are known
+    let mut reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    for (path, data) in &files {
+        let entry = reader.get_entry(path).unwrap();
+        assert_eq!(entry.blake3(), Some(*blake3::hash(data).as_bytes()));
+        assert_eq!(&reader.read_file(path).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_blake3_with_per_file_encryption() {
+    let key = [7u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&key)
+        .with_blake3(true);
+    for (path, data) in sample_files() {
+        writer.add_file(path, &data).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open(temp_file.path())
+        .unwrap()
+        .with_decryption_key(&key);
+    reader.initialize().unwrap();
+    for (path, data) in sample_files() {
+        let entry = reader.get_entry(path).unwrap();
+        assert_eq!(entry.blake3(), Some(*blake3::hash(&data).as_bytes()));
+        assert_eq!(reader.read_file(path).unwrap(), data);
+    }
+
+    // The table is encrypted too, so without the key there are no digests
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(reader.header().has_blake3_digests());
+    assert_eq!(reader.get_entry("small.txt").unwrap().blake3(), None);
+}
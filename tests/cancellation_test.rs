@@ -0,0 +1,126 @@
+//! Cancellation tests
+//!
+//! Cancels archive creation and extraction through a shared `CancellationToken`.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, CancellationToken, EngramError, ProgressEvent};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tempfile::{NamedTempFile, TempDir};
+
+const LARGE_SIZE: usize = 200 * 1024 * 1024;
+
+#[test]
+fn test_cancel_large_write_from_another_thread() {
+    let dir = TempDir::new().unwrap();
+    let archive_path = dir.path().join("cancelled.eng");
+
+    let block: Vec<u8> = (0..64 * 1024).map(|i| (i % 251) as u8).collect();
+    let data = block.repeat(LARGE_SIZE / block.len());
+
+    // The progress callback signals the halfway point; another thread cancels
+    let token = CancellationToken::new();
+    let (halfway_tx, halfway_rx) = mpsc::channel();
+    let halfway_tx = Mutex::new(Some(halfway_tx));
+    let cancelled_at = Arc::new(Mutex::new(None));
+
+    let canceller = {
+        let token = token.clone();
+        let cancelled_at = cancelled_at.clone();
+        thread::spawn(move || {
+            if halfway_rx.recv().is_ok() {
+                token.cancel();
+                *cancelled_at.lock().unwrap() = Some(Instant::now());
+            }
+        })
+    };
+
+    let mut writer = ArchiveWriter::create(&archive_path)
+        .unwrap()
+        .with_cancellation(token.clone())
+        .with_progress(Box::new(move |event| {
+            if let ProgressEvent::BytesProcessed { bytes_done, .. } = event {
+                if bytes_done >= (LARGE_SIZE / 2) as u64 {
+                    if let Some(tx) = halfway_tx.lock().unwrap().take() {
+                        tx.send(()).unwrap();
+                    }
+                }
+            }
+        }));
+
+    writer.add_file("small.txt", b"written first").unwrap();
+    let result = writer.add_file("large.bin", &data);
+    let returned_at = Instant::now();
+    drop(writer);
+    canceller.join().unwrap();
+
+    assert!(matches!(result, Err(EngramError::Cancelled)));
+    assert!(token.is_cancelled());
+    assert!(!archive_path.exists(), "Partial archive was not deleted");
+
+    // Cancellation is checked per 64KB frame
+    let cancelled_at = cancelled_at.lock().unwrap().unwrap();
+    assert!(returned_at.saturating_duration_since(cancelled_at) < Duration::from_secs(2));
+}
+
+#[test]
+fn test_cancel_before_finalize_deletes_archive() {
+    let dir = TempDir::new().unwrap();
+    let archive_path = dir.path().join("unfinished.eng");
+
+    let token = CancellationToken::new();
+    let mut writer = ArchiveWriter::create(&archive_path)
+        .unwrap()
+        .with_cancellation(token.clone());
+    writer.add_file("a.txt", b"data").unwrap();
+
+    token.cancel();
+    assert!(matches!(writer.finalize(), Err(EngramError::Cancelled)));
+    assert!(!archive_path.exists());
+}
+
+#[test]
+fn test_cancel_extract_all() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_cancellation(CancellationToken::new());
+        for i in 0..10 {
+            writer
+                .add_file(&format!("file_{}.txt", i), b"contents")
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    // Cancel after the third file has been read
+    let token = CancellationToken::new();
+    let finished = Arc::new(Mutex::new(0));
+    let mut reader = {
+        let token = token.clone();
+        let finished = finished.clone();
+        ArchiveReader::open_and_init(temp_file.path())
+            .unwrap()
+            .with_cancellation(token.clone())
+            .with_progress(Box::new(move |event| {
+                if let ProgressEvent::FileFinished { .. } = event {
+                    let mut count = finished.lock().unwrap();
+                    *count += 1;
+                    if *count == 3 {
+                        token.cancel();
+                    }
+                }
+            }))
+    };
+
+    let out_dir = TempDir::new().unwrap();
+    let result = reader.extract_all(out_dir.path());
+    assert!(matches!(result, Err(EngramError::Cancelled)));
+    assert_eq!(*finished.lock().unwrap(), 3);
+
+    // The remaining files were not extracted
+    let extracted = std::fs::read_dir(out_dir.path()).unwrap().count();
+    assert_eq!(extracted, 3);
+}
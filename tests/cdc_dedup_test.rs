@@ -0,0 +1,180 @@
+//! Content-defined chunking tests
+//!
+//! Covers `ArchiveWriter::with_cdc_dedup`: large files are split into chunks
+//! stored once under `CHUNKS_PREFIX`, and read back transparently through
+//! the chunk list of their entry.
+
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, ChunkerConfig, CompressionMethod, EngramError, CHUNKS_PREFIX,
+};
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+/// Helper: Incompressible pseudo-random bytes
+fn noise(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+/// Helper: Chunks of 16 KiB on average, small enough for a few MB of test data
+fn config() -> ChunkerConfig {
+    ChunkerConfig::new(16 * 1024)
+}
+
+/// Helper: Archive holding `files`, written with `configure`
+fn write_archive(
+    files: &[(&str, &[u8])],
+    configure: impl FnOnce(ArchiveWriter) -> ArchiveWriter,
+) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = configure(ArchiveWriter::create(temp_file.path()).unwrap());
+    for (path, data) in files {
+        writer
+            .add_file_with_compression(path, data, CompressionMethod::Zstd)
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_near_identical_files_share_chunks() {
+    let original = noise(4 << 20, 1);
+    let mut edited = original.clone();
+    edited.splice(1_000_000..1_000_000, b"a small insertion".iter().copied());
+
+    let single = write_archive(&[("original.img", &original)], |w| {
+        w.with_cdc_dedup(config())
+    });
+    let both = write_archive(
+        &[("original.img", &original), ("edited.img", &edited)],
+        |w| w.with_cdc_dedup(config()),
+    );
+    let single_size = std::fs::metadata(single.path()).unwrap().len();
+    let both_size = std::fs::metadata(both.path()).unwrap().len();
+    assert!(
+        both_size * 10 < single_size * 11,
+        "{} bytes for both, {} for one",
+        both_size,
+        single_size
+    );
+
+    let mut reader = ArchiveReader::open_and_init(both.path()).unwrap();
+    assert!(reader.header().has_chunked_entries());
+    assert!(reader.get_entry("edited.img").unwrap().is_chunked());
+    assert_eq!(reader.read_file("original.img").unwrap(), original);
+    assert_eq!(reader.read_file("edited.img").unwrap(), edited);
+    assert_eq!(reader.read_file_at("edited.img").unwrap(), edited);
+
+    let mut streamed = Vec::new();
+    let written = reader.read_file_to("edited.img", &mut streamed).unwrap();
+    assert_eq!(written, edited.len() as u64);
+    assert_eq!(streamed, edited);
+    assert_eq!(
+        reader.read_file_range("edited.img", 999_990, 30).unwrap(),
+        edited[999_990..1_000_020]
+    );
+    assert_eq!(
+        reader.read_prefix("edited.img", 100).unwrap(),
+        edited[..100]
+    );
+    assert!(reader.verify_all().unwrap().is_ok());
+
+    // Chunks are internal entries
+    assert_eq!(reader.list_user_files(), ["original.img", "edited.img"]);
+    assert!(!reader.list_prefix(CHUNKS_PREFIX).is_empty());
+}
+
+#[test]
+fn test_small_files_are_not_chunked() {
+    let data = noise(config().max_size, 2);
+    let temp_file = write_archive(&[("small.bin", &data), ("empty.bin", b"")], |w| {
+        w.with_cdc_dedup(config())
+    });
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(!reader.header().has_chunked_entries());
+    assert_eq!(reader.entry_count(), 2);
+    assert!(!reader.get_entry("small.bin").unwrap().is_chunked());
+    assert_eq!(reader.read_file("small.bin").unwrap(), data);
+}
+
+#[test]
+fn test_chunked_entries_with_per_file_encryption() {
+    let key = [0x42u8; 32];
+    let data = noise(1 << 20, 3);
+    let temp_file = write_archive(&[("a.bin", &data), ("b.bin", &data)], |w| {
+        w.with_per_file_encryption(&key)
+            .with_cdc_dedup(config())
+            .with_strong_checksums(true)
+    });
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    assert_eq!(reader.read_file("a.bin").unwrap(), data);
+    assert_eq!(reader.read_file("b.bin").unwrap(), data);
+    assert!(reader.verify_all().unwrap().is_ok());
+}
+
+#[test]
+fn test_corrupted_chunk_is_detected() {
+    let data = noise(1 << 20, 4);
+    let temp_file = write_archive(&[("big.bin", &data)], |w| w.with_cdc_dedup(config()));
+
+    // Incompressible chunks are stored as is: find one in the file
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let chunk = reader.list_prefix(CHUNKS_PREFIX)[1].clone();
+    let content = reader.read_file(&chunk).unwrap();
+    drop(reader);
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    let start = bytes
+        .windows(content.len())
+        .position(|window| window == content)
+        .unwrap();
+    bytes[start + 100] ^= 0xFF;
+    let mut damaged = NamedTempFile::new().unwrap();
+    damaged.write_all(&bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(damaged.path()).unwrap();
+    let err = reader.read_file("big.bin").unwrap_err();
+    match err.root_cause() {
+        EngramError::CrcMismatch { path, .. } => assert_eq!(path, &chunk),
+        other => panic!("{:?}", other),
+    }
+    assert!(reader.read_file_at("big.bin").is_err());
+    let report = reader.verify_all().unwrap();
+    let failed: Vec<&str> = report
+        .failed
+        .iter()
+        .map(|(path, _)| path.as_str())
+        .collect();
+    assert_eq!(failed, [chunk.as_str(), "big.bin"]);
+}
+
+#[test]
+fn test_cdc_options_are_validated() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_cdc_dedup(ChunkerConfig::new(16));
+    assert!(matches!(
+        writer.add_file("a.txt", b"a"),
+        Err(EngramError::InvalidOptions(_))
+    ));
+
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_format_version(0, 4)
+        .unwrap()
+        .with_cdc_dedup(config());
+    assert!(matches!(
+        writer.add_file("a.txt", b"a"),
+        Err(EngramError::InvalidOptions(_))
+    ));
+}
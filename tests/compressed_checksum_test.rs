@@ -0,0 +1,197 @@
+//! Compressed checksum tests
+//!
+//! Covers `ArchiveWriter::with_compressed_checksums`, the table it writes at
+//! `COMPRESSED_CRC_PATH`, `ArchiveReader::compressed_crc32` and
+//! `ArchiveReader::verify_fast`.
+
+use engram_rs::archive::LocalEntryHeader;
+use engram_rs::{
+    ArchiveEditor, ArchiveReader, ArchiveWriter, CompressionMethod, EngramError,
+    COMPRESSED_CRC_PATH,
+};
+use tempfile::NamedTempFile;
+
+const KEY: [u8; 32] = [0x42u8; 32];
+
+/// Helper: Archive with a mix of compression methods, a directory and a
+/// deduplicated copy, written by `writer`
+fn write_sample(mut writer: ArchiveWriter) {
+    let text = "compressible line of text\n".repeat(2_000).into_bytes();
+    writer
+        .add_file_with_compression("text.lz4", &text, CompressionMethod::Lz4)
+        .unwrap();
+    writer
+        .add_file_with_compression("text.zst", &text, CompressionMethod::Zstd)
+        .unwrap();
+    writer
+        .add_file_with_compression("raw.bin", b"stored as is", CompressionMethod::None)
+        .unwrap();
+    writer
+        .add_file_with_compression("copy.zst", &text, CompressionMethod::Zstd)
+        .unwrap();
+    writer.add_directory("empty").unwrap();
+    writer.finalize().unwrap();
+}
+
+/// Helper: Flip the last stored payload byte of `path` in the archive at `file`
+fn damage_payload(file: &NamedTempFile, path: &str) {
+    let mut bytes = std::fs::read(file.path()).unwrap();
+    let entry = ArchiveReader::open_and_init(file.path())
+        .unwrap()
+        .get_entry(path)
+        .unwrap()
+        .clone();
+    let offset = entry.data_offset as usize;
+    let header = LocalEntryHeader::read_from(&bytes[offset..]).unwrap();
+    bytes[offset + header.header_size() + entry.compressed_size as usize - 1] ^= 0xFF;
+    std::fs::write(file.path(), &bytes).unwrap();
+}
+
+#[test]
+fn test_checksums_recorded_for_every_payload() {
+    let temp_file = NamedTempFile::new().unwrap();
+    write_sample(
+        ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_dedup()
+            .with_compressed_checksums(true),
+    );
+
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(reader.contains(COMPRESSED_CRC_PATH));
+    for path in ["text.lz4", "text.zst", "raw.bin"] {
+        let entry = reader.get_entry(path).unwrap().clone();
+        let offset = entry.data_offset as usize;
+        let start = offset
+            + LocalEntryHeader::read_from(&bytes[offset..])
+                .unwrap()
+                .header_size();
+        let stored = &bytes[start..start + entry.compressed_size as usize];
+        assert_eq!(
+            reader.compressed_crc32(path).unwrap(),
+            Some(crc32fast::hash(stored)),
+            "{}",
+            path
+        );
+    }
+    // The copy shares its original's payload and checksum
+    assert!(reader.get_entry("copy.zst").unwrap().is_deduplicated());
+    assert_eq!(
+        reader.compressed_crc32("copy.zst").unwrap(),
+        reader.compressed_crc32("text.zst").unwrap()
+    );
+    assert_eq!(reader.compressed_crc32("empty").unwrap(), None);
+    assert!(matches!(
+        reader.compressed_crc32("missing.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+
+    let report = reader.verify_fast().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(report.ok.len(), reader.list_files().len());
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+}
+
+#[test]
+fn test_verify_fast_detects_damaged_payload() {
+    let temp_file = NamedTempFile::new().unwrap();
+    write_sample(
+        ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_compressed_checksums(true),
+    );
+    damage_payload(&temp_file, "text.zst");
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let report = reader.verify_fast().unwrap();
+    assert_eq!(report.failed.len(), 1, "{:?}", report.failed);
+    let (path, err) = &report.failed[0];
+    assert_eq!(path, "text.zst");
+    assert!(matches!(err, EngramError::CrcMismatch { .. }), "{:?}", err);
+
+    // The full check finds the same damage
+    let report = reader.verify_all().unwrap();
+    assert!(report.failed.iter().any(|(path, _)| path == "text.zst"));
+}
+
+#[test]
+fn test_verify_fast_checks_encrypted_payloads_as_stored() {
+    let temp_file = NamedTempFile::new().unwrap();
+    write_sample(
+        ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_per_file_encryption(&KEY)
+            .with_compressed_checksums(true),
+    );
+
+    // The table is encrypted like every other entry; the payloads it
+    // covers are compared as stored, without decrypting them
+    let open = || {
+        let reader = ArchiveReader::open_encrypted(temp_file.path(), &KEY).unwrap();
+        reader
+    };
+    let report = open().verify_fast().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+
+    damage_payload(&temp_file, "raw.bin");
+    let report = open().verify_fast().unwrap();
+    assert_eq!(report.failed.len(), 1, "{:?}", report.failed);
+    assert_eq!(report.failed[0].0, "raw.bin");
+    assert!(matches!(
+        report.failed[0].1,
+        EngramError::CrcMismatch { .. }
+    ));
+
+    // Without the key, only the directory can be checked
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let report = reader.verify_fast().unwrap();
+    assert_eq!(report.ok, ["empty"]);
+    assert!(report
+        .failed
+        .iter()
+        .all(|(_, err)| matches!(err, EngramError::MissingDecryptionKey)));
+}
+
+#[test]
+fn test_verify_fast_falls_back_without_table() {
+    let temp_file = NamedTempFile::new().unwrap();
+    write_sample(ArchiveWriter::create(temp_file.path()).unwrap());
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(!reader.contains(COMPRESSED_CRC_PATH));
+    assert_eq!(reader.compressed_crc32("text.zst").unwrap(), None);
+    let report = reader.verify_fast().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+
+    // Damage is still found through the content CRC32
+    damage_payload(&temp_file, "text.lz4");
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let report = reader.verify_fast().unwrap();
+    assert_eq!(report.failed.len(), 1, "{:?}", report.failed);
+    assert_eq!(report.failed[0].0, "text.lz4");
+}
+
+#[test]
+fn test_editor_records_checksums_for_new_offsets() {
+    let source = NamedTempFile::new().unwrap();
+    write_sample(
+        ArchiveWriter::create(source.path())
+            .unwrap()
+            .with_compressed_checksums(true),
+    );
+
+    let dest = NamedTempFile::new().unwrap();
+    let mut editor = ArchiveEditor::open(source.path()).unwrap();
+    editor.remove("text.lz4").unwrap();
+    editor.replace("raw.bin", b"replaced content").unwrap();
+    editor.save_to(dest.path()).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(dest.path()).unwrap();
+    assert!(reader.compressed_crc32("text.zst").unwrap().is_some());
+    assert!(reader.compressed_crc32("raw.bin").unwrap().is_some());
+    let report = reader.verify_fast().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(reader.read_file("raw.bin").unwrap(), b"replaced content");
+}
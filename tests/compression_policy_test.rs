@@ -0,0 +1,107 @@
+//! Compression policy tests
+//!
+//! Covers `ArchiveWriter::with_compression_policy`, `DefaultPolicy` and
+//! `ForceMethod`.
+
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, CompressionMethod, CompressionPolicy, DefaultPolicy, ForceMethod,
+};
+use std::sync::{Arc, Mutex};
+use tempfile::NamedTempFile;
+
+/// Helper: Policy that never compresses and records what it was asked about
+struct NeverCompress {
+    seen: Arc<Mutex<Vec<(String, usize)>>>,
+}
+
+impl CompressionPolicy for NeverCompress {
+    fn choose(&self, path: &str, data: &[u8]) -> CompressionMethod {
+        self.seen
+            .lock()
+            .unwrap()
+            .push((path.to_string(), data.len()));
+        CompressionMethod::None
+    }
+}
+
+#[test]
+fn test_custom_policy_forces_no_compression() {
+    let text = "highly compressible line\n".repeat(2000);
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let disk_file = NamedTempFile::new().unwrap();
+    std::fs::write(disk_file.path(), text.as_bytes()).unwrap();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_compression_policy(Box::new(NeverCompress {
+            seen: Arc::clone(&seen),
+        }));
+    for path in ["notes.txt", "data\\events.json", "blob.bin"] {
+        assert_eq!(
+            writer.add_file(path, text.as_bytes()).unwrap(),
+            CompressionMethod::None
+        );
+    }
+    writer
+        .add_file_from_disk("from_disk.md", disk_file.path())
+        .unwrap();
+    writer.finalize().unwrap();
+
+    // Paths reach the policy normalized, along with the content
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            ("notes.txt".to_string(), text.len()),
+            ("data/events.json".to_string(), text.len()),
+            ("blob.bin".to_string(), text.len()),
+            ("from_disk.md".to_string(), text.len()),
+        ]
+    );
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    for path in reader.list_files().to_vec() {
+        let entry = reader.get_entry(&path).unwrap();
+        assert_eq!(entry.compression, CompressionMethod::None, "{}", path);
+        assert_eq!(entry.compressed_size, entry.uncompressed_size, "{}", path);
+        assert_eq!(reader.read_file(&path).unwrap(), text.as_bytes());
+    }
+}
+
+#[test]
+fn test_builtin_policies() {
+    let text = "line\n".repeat(2000);
+    assert_eq!(
+        DefaultPolicy.choose("notes.txt", text.as_bytes()),
+        CompressionMethod::Zstd
+    );
+    assert_eq!(
+        DefaultPolicy.choose("photo.png", text.as_bytes()),
+        CompressionMethod::None
+    );
+    assert_eq!(
+        DefaultPolicy.choose("tiny.txt", b"small"),
+        CompressionMethod::None
+    );
+    assert_eq!(
+        ForceMethod(CompressionMethod::Lz4).choose("notes.txt", b"small"),
+        CompressionMethod::Lz4
+    );
+
+    // The last of with_default_compression and with_compression_policy wins
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_compression_policy(Box::new(ForceMethod(CompressionMethod::None)))
+        .with_default_compression(CompressionMethod::Lz4);
+    assert_eq!(
+        writer.add_file("notes.txt", text.as_bytes()).unwrap(),
+        CompressionMethod::Lz4
+    );
+    let mut writer = writer.with_compression_policy(Box::new(DefaultPolicy));
+    assert_eq!(
+        writer.add_file("more.txt", text.as_bytes()).unwrap(),
+        CompressionMethod::Zstd
+    );
+    writer.finalize().unwrap();
+}
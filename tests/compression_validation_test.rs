@@ -3,7 +3,7 @@
 //! Tests for automatic compression method selection and effectiveness.
 //! Based on TESTING_PLAN.md Phase 3.2
 
-use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod};
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, CD_ENTRY_SIZE};
 use tempfile::NamedTempFile;
 
 /// Helper: Get the compression method used for a file in an archive
@@ -278,3 +278,89 @@ fn test_mixed_compression_in_archive() {
 
     println!("  ✓ Mixed compression methods in single archive work correctly");
 }
+
+#[test]
+fn test_reported_compression_method() {
+    println!("\n🔍 Testing reported compression method for incompressible data...");
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+
+    let mut writer = ArchiveWriter::create(path).unwrap();
+
+    // Pseudo-random bytes: Zstd output would be larger than the input
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let random: Vec<u8> = (0..64 * 1024)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    let text = "Hello World ".repeat(1000);
+
+    let random_method = writer
+        .add_file_with_compression("random.bin", &random, CompressionMethod::Zstd)
+        .unwrap();
+    let text_method = writer
+        .add_file_with_compression("text.txt", text.as_bytes(), CompressionMethod::Zstd)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    assert_eq!(random_method, CompressionMethod::None);
+    assert_eq!(text_method, CompressionMethod::Zstd);
+
+    // The reported method matches what was recorded in the central directory
+    let mut reader = ArchiveReader::open_and_init(path).unwrap();
+    assert_eq!(reader.get_entry("random.bin").unwrap().compression, random_method);
+    assert_eq!(reader.get_entry("text.txt").unwrap().compression, text_method);
+    assert_eq!(reader.read_file("random.bin").unwrap(), random);
+
+    println!("  ✓ Incompressible data reports CompressionMethod::None");
+}
+
+#[test]
+fn test_unknown_compression_method() {
+    println!("\n🔍 Testing archives with an unknown compression method...");
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+
+    let text = "Hello World ".repeat(1000);
+    let mut writer = ArchiveWriter::create(path).unwrap();
+    writer
+        .add_file_with_compression("future.txt", text.as_bytes(), CompressionMethod::Zstd)
+        .unwrap();
+    writer.add_file("plain.txt", text.as_bytes()).unwrap();
+    writer.finalize().unwrap();
+
+    // Patch the first central directory entry's method byte (offset 40) to 7
+    let cd_offset = ArchiveReader::open(path)
+        .unwrap()
+        .header()
+        .central_directory_offset as usize;
+    let mut bytes = std::fs::read(path).unwrap();
+    assert_eq!(bytes[cd_offset + 40], 2);
+    bytes[cd_offset + 40] = 7;
+    assert_eq!(bytes[cd_offset + CD_ENTRY_SIZE + 40], 2);
+    std::fs::write(path, &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(path).unwrap();
+    assert_eq!(reader.list_files(), ["future.txt", "plain.txt"]);
+    assert_eq!(
+        reader.get_entry("future.txt").unwrap().compression,
+        CompressionMethod::Unknown(7)
+    );
+    assert!(matches!(
+        reader.read_file("future.txt"),
+        Err(EngramError::InvalidCompression(7))
+    ));
+    assert!(matches!(
+        reader.read_file_at("future.txt"),
+        Err(EngramError::InvalidCompression(7))
+    ));
+    assert_eq!(reader.read_file("plain.txt").unwrap(), text.as_bytes());
+
+    println!("  ✓ Unknown methods only fail when the entry is read");
+}
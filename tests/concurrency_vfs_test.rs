@@ -272,3 +272,68 @@ fn test_vfs_database_list_concurrent() {
 
     println!("✓ 10 threads called list_databases() concurrently (1000 total calls)");
 }
+
+#[test]
+fn test_concurrent_extraction_into_custom_temp_dir() {
+    let db_data = create_test_database(50);
+    let temp_file = create_archive_with_databases(&[
+        ("data/app.db", db_data.clone()),
+        // Maps to the same sanitized name as data/app.db
+        ("data_app.db", db_data),
+    ]);
+    let path = temp_file.path();
+    let temp_root = tempfile::tempdir().unwrap();
+
+    // 16 threads, each opening both databases twice
+    let handles: Vec<_> = (0..16)
+        .map(|_| {
+            let path = path.to_path_buf();
+            let temp_root = temp_root.path().to_path_buf();
+            thread::spawn(move || {
+                let mut vfs = VfsReader::open(&path).unwrap().with_temp_dir(&temp_root);
+                let mut connections = Vec::new();
+                for db in ["data/app.db", "data_app.db", "data/app.db", "data_app.db"] {
+                    connections.push(vfs.open_database(db).unwrap());
+                }
+                for conn in &connections {
+                    let count: i64 = conn
+                        .query_row("SELECT COUNT(*) FROM test", [], |row| row.get(0))
+                        .unwrap();
+                    assert_eq!(count, 50);
+                }
+                // Keep the files until every thread is done
+                (vfs, connections)
+            })
+        })
+        .collect();
+    let results: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+    // Every extraction landed in its own file under the custom directory
+    let mut files: Vec<_> = std::fs::read_dir(temp_root.path())
+        .unwrap()
+        .flat_map(|dir| std::fs::read_dir(dir.unwrap().path()).unwrap())
+        .map(|file| file.unwrap().path())
+        .collect();
+    assert_eq!(files.len(), 16 * 4);
+    files.sort();
+    files.dedup();
+    assert_eq!(files.len(), 16 * 4);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        for file in &files {
+            let mode = std::fs::metadata(file).unwrap().permissions().mode();
+            assert_eq!(mode & 0o077, 0, "{} has mode {:o}", file.display(), mode);
+            let dir = file.parent().unwrap();
+            let mode = std::fs::metadata(dir).unwrap().permissions().mode();
+            assert_eq!(mode & 0o077, 0, "{} has mode {:o}", dir.display(), mode);
+        }
+    }
+
+    // Dropping the readers removes their directories
+    drop(results);
+    assert_eq!(std::fs::read_dir(temp_root.path()).unwrap().count(), 0);
+
+    println!("✓ 64 concurrent extractions, no collisions or readable temp files");
+}
@@ -0,0 +1,225 @@
+//! Content lookup tests
+//!
+//! Covers `ArchiveReader::find_by_crc32`, `find_by_sha256`,
+//! `duplicate_groups` and `entries_with_compression`: finding files by
+//! content or storage from the central directory and manifest, without
+//! extracting anything.
+
+use engram_rs::manifest::{Author, Manifest};
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod};
+use sha2::{Digest, Sha256};
+use tempfile::NamedTempFile;
+
+/// Helper: `data` followed by its own CRC32 (little-endian)
+///
+/// Every such message has the CRC32 0x2144DF1C, whatever `data` is, which
+/// gives CRC collisions of any size.
+fn crc_residue(data: &[u8]) -> Vec<u8> {
+    let mut message = data.to_vec();
+    message.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+    message
+}
+
+const RESIDUE_CRC: u32 = 0x2144_DF1C;
+
+/// Helper: Paths of `entries`
+fn paths<'a>(entries: impl IntoIterator<Item = &'a engram_rs::EntryInfo>) -> Vec<&'a str> {
+    entries
+        .into_iter()
+        .map(|entry| entry.path.as_str())
+        .collect()
+}
+
+#[test]
+fn test_find_by_crc32() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_dedup();
+    writer.add_file("a.txt", b"shared content").unwrap();
+    writer.add_file("b.txt", b"other content").unwrap();
+    writer.add_file("copy/a.txt", b"shared content").unwrap();
+    writer.add_file("empty.txt", b"").unwrap();
+    writer.add_directory("dir").unwrap();
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let shared = crc32fast::hash(b"shared content");
+    assert_eq!(paths(reader.find_by_crc32(shared)), ["a.txt", "copy/a.txt"]);
+    assert_eq!(
+        paths(reader.find_by_crc32(crc32fast::hash(b"other content"))),
+        ["b.txt"]
+    );
+    assert!(reader.find_by_crc32(crc32fast::hash(b"absent")).is_empty());
+    // Directories also record CRC 0 but are not content
+    assert_eq!(paths(reader.find_by_crc32(0)), ["empty.txt"]);
+
+    // Lazy readers parse the directory on first use
+    let lazy = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    assert_eq!(paths(lazy.find_by_crc32(shared)), ["a.txt", "copy/a.txt"]);
+}
+
+#[test]
+fn test_duplicate_groups_require_equal_sizes() {
+    let short = crc_residue(b"ab");
+    let short_other = crc_residue(b"cd");
+    let long = crc_residue(b"a much longer message");
+    assert_eq!(crc32fast::hash(&short), RESIDUE_CRC);
+    assert_eq!(crc32fast::hash(&long), RESIDUE_CRC);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("one.bin", b"one").unwrap();
+    writer.add_file("short.bin", &short).unwrap();
+    writer.add_file("long.bin", &long).unwrap();
+    writer.add_file("short_other.bin", &short_other).unwrap();
+    writer.add_file("one_copy.bin", b"one").unwrap();
+    writer.add_file("empty1.txt", b"").unwrap();
+    writer.add_file("empty2.txt", b"").unwrap();
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(
+        paths(reader.find_by_crc32(RESIDUE_CRC)),
+        ["short.bin", "long.bin", "short_other.bin"]
+    );
+
+    // Same CRC but different sizes are not grouped; same size and CRC are,
+    // even with different content. Empty files are left out.
+    let groups: Vec<Vec<&str>> = reader.duplicate_groups().into_iter().map(paths).collect();
+    assert_eq!(
+        groups,
+        [
+            vec!["one.bin", "one_copy.bin"],
+            vec!["short.bin", "short_other.bin"]
+        ]
+    );
+}
+
+#[test]
+fn test_find_by_sha256() {
+    let digest = |data: &[u8]| -> [u8; 32] { Sha256::digest(data).into() };
+    let mut manifest = Manifest::new(
+        "lookup".to_string(),
+        "Lookup".to_string(),
+        Author::new("Test"),
+        "1.0.0".to_string(),
+    );
+    manifest.add_file("listed.txt".to_string(), b"listed", None);
+    // The manifest is trusted over the entry's own prefix
+    manifest.add_file("relisted.txt".to_string(), b"claimed", None);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_strong_checksums(true);
+    writer.add_file("listed.txt", b"listed").unwrap();
+    writer.add_file("relisted.txt", b"actual").unwrap();
+    writer.add_file("strong.txt", b"strong").unwrap();
+    writer.add_file("strong_copy.txt", b"strong").unwrap();
+    writer
+        .add_manifest(&serde_json::to_value(&manifest).unwrap())
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(
+        reader.find_by_sha256(&digest(b"listed")).unwrap(),
+        ["listed.txt"]
+    );
+    assert_eq!(
+        reader.find_by_sha256(&digest(b"claimed")).unwrap(),
+        ["relisted.txt"]
+    );
+    assert!(reader
+        .find_by_sha256(&digest(b"actual"))
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        reader.find_by_sha256(&digest(b"strong")).unwrap(),
+        ["strong.txt", "strong_copy.txt"]
+    );
+    assert!(reader
+        .find_by_sha256(&digest(b"absent"))
+        .unwrap()
+        .is_empty());
+
+    // Without digests nothing is found, as nothing is hashed
+    let plain = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(plain.path()).unwrap();
+    writer.add_file("strong.txt", b"strong").unwrap();
+    writer.finalize().unwrap();
+    let mut reader = ArchiveReader::open_and_init(plain.path()).unwrap();
+    assert!(reader
+        .find_by_sha256(&digest(b"strong"))
+        .unwrap()
+        .is_empty());
+}
+
+#[test]
+fn test_lookups_on_empty_archive() {
+    let temp_file = NamedTempFile::new().unwrap();
+    ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .finalize()
+        .unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(reader.find_by_crc32(0).is_empty());
+    assert!(reader.duplicate_groups().is_empty());
+    assert!(reader.find_by_sha256(&[0u8; 32]).unwrap().is_empty());
+}
+
+#[test]
+fn test_entries_with_compression() {
+    let compressible = "engram ".repeat(2000);
+    let mut state = 0x2545_F491_4F6C_DD1Du64;
+    let incompressible: Vec<u8> = (0..10_000)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    for (path, data, method) in [
+        ("a.zst", compressible.as_bytes(), CompressionMethod::Zstd),
+        ("b.lz4", compressible.as_bytes(), CompressionMethod::Lz4),
+        (
+            "c.deflate",
+            compressible.as_bytes(),
+            CompressionMethod::Deflate,
+        ),
+        ("d.zst", compressible.as_bytes(), CompressionMethod::Zstd),
+        // Falls back to None: compressing would not make it smaller
+        ("noise.bin", &incompressible, CompressionMethod::Zstd),
+        ("small.txt", b"tiny", CompressionMethod::None),
+    ] {
+        writer
+            .add_file_with_compression(path, data, method)
+            .unwrap();
+    }
+    writer.add_directory("empty").unwrap();
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let expected: [(CompressionMethod, &[&str]); 4] = [
+        (CompressionMethod::Zstd, &["a.zst", "d.zst"]),
+        (CompressionMethod::Lz4, &["b.lz4"]),
+        (CompressionMethod::Deflate, &["c.deflate"]),
+        (CompressionMethod::None, &["noise.bin", "small.txt"]),
+    ];
+    for (method, expected) in expected {
+        assert_eq!(
+            paths(reader.entries_with_compression(method)),
+            expected,
+            "{}",
+            method
+        );
+    }
+    assert!(reader
+        .entries_with_compression(CompressionMethod::Unknown(9))
+        .is_empty());
+}
@@ -117,19 +117,15 @@ fn test_corrupted_central_directory_offset() {
     file.write_all(&bytes).unwrap();
     drop(file);
 
-    let result = ArchiveReader::open(path);
+    // The header alone still parses
+    assert!(ArchiveReader::open(path).is_ok());
 
-    // Archive might open (lazy validation) or fail immediately
-    // Both behaviors are acceptable for corrupted CD offset
-    if let Err(err) = result {
-        match err {
-            EngramError::Io(_) => {}, // Expected
-            EngramError::InvalidFormat(_) => {}, // Also acceptable
-            other => panic!("Expected IO or InvalidFormat error, got: {:?}", other),
+    // Initialization rejects the offset before seeking to it
+    match ArchiveReader::open_and_init(path) {
+        Err(EngramError::InvalidFormat(msg)) => {
+            assert!(msg.contains("outside the archive"), "{}", msg)
         }
-    } else {
-        println!("Archive opened despite invalid CD offset (lazy validation)");
-        // This is acceptable - corruption will be detected on actual operations
+        other => panic!("Expected InvalidFormat error, got: {:?}", other.err()),
     }
 }
 
@@ -138,7 +134,7 @@ fn test_corrupted_entry_count() {
     let temp_file = create_test_archive();
     let path = temp_file.path();
 
-    // Corrupt entry count at bytes 24-27 (u32 little-endian)
+    // Corrupt entry count at bytes 32-35 (u32 little-endian)
     // Set to 0 when there are actually 2 files
     let zero: u32 = 0;
     let bytes = zero.to_le_bytes();
@@ -148,17 +144,17 @@ fn test_corrupted_entry_count() {
         .write(true)
         .open(path)
         .unwrap();
-    file.seek(SeekFrom::Start(24)).unwrap();
+    file.seek(SeekFrom::Start(32)).unwrap();
     file.write_all(&bytes).unwrap();
     drop(file);
 
-    let reader = ArchiveReader::open(path).unwrap();
-
-    // Entry count should be 0 according to header
-    assert_eq!(reader.entry_count(), 0);
-
-    // But file list should be empty
-    assert!(reader.list_files().is_empty());
+    // The central directory size no longer matches the entry count
+    match ArchiveReader::open_and_init(path) {
+        Err(EngramError::InvalidFormat(msg)) => {
+            assert!(msg.contains("does not match 0 entries"), "{}", msg)
+        }
+        other => panic!("Expected InvalidFormat error, got: {:?}", other.err()),
+    }
 }
 
 #[test]
@@ -206,23 +202,51 @@ fn test_corrupted_central_directory_entry() {
     let cd_offset = u64::from_le_bytes(offset_bytes);
     drop(file);
 
-    // Corrupt first byte of first CD entry (signature)
-    corrupt_byte_at(path, cd_offset, 0xFF);
-
-    let result = ArchiveReader::open(path);
-
-    // Should fail when parsing central directory
-    if let Err(err) = result {
-        match err {
-            EngramError::InvalidFormat(_) => {}, // Expected
-            EngramError::Io(_) => {}, // Also acceptable
-            other => println!("Got error: {:?}", other),
+    // Corrupt first byte of second CD entry (signature)
+    let entry_offset = cd_offset + 320;
+    corrupt_byte_at(path, entry_offset, 0xFF);
+
+    // Should fail when parsing central directory, naming the entry
+    match ArchiveReader::open_and_init(path) {
+        Err(err) => {
+            assert!(
+                matches!(err, EngramError::DirectoryEntryError { index: 1, offset, .. } if offset == entry_offset),
+                "{:?}", err
+            );
+            assert!(matches!(err.root_cause(), EngramError::InvalidFormat(_)));
+            let message = err.to_string();
+            assert!(message.contains("Central directory entry 1"), "{}", message);
         }
-    } else {
-        println!("Warning: Archive opened despite corrupted CD entry");
+        Ok(_) => panic!("Archive opened despite corrupted CD entry"),
     }
 }
 
+#[test]
+fn test_corrupted_local_header_names_entry() {
+    let temp_file = create_test_archive();
+    let path = temp_file.path();
+
+    let reader = ArchiveReader::open_and_init(path).unwrap();
+    let data_offset = reader.get_entry("data.bin").unwrap().data_offset;
+    drop(reader);
+
+    // Corrupt the LOCA signature of data.bin
+    corrupt_byte_at(path, data_offset, 0xFF);
+
+    let mut reader = ArchiveReader::open_and_init(path).unwrap();
+    assert_eq!(reader.read_file("test.txt").unwrap(), b"Hello, World!");
+
+    let err = reader.read_file("data.bin").unwrap_err();
+    assert!(
+        matches!(&err, EngramError::EntryError { path, offset, .. } if path == "data.bin" && *offset == data_offset),
+        "{:?}", err
+    );
+    let message = err.to_string();
+    assert!(message.contains("'data.bin'"), "{}", message);
+    assert!(message.contains(&format!("offset {}", data_offset)), "{}", message);
+    assert!(message.contains("LOCA"), "{}", message);
+}
+
 #[test]
 fn test_truncated_file_data() {
     let temp_file = create_test_archive();
@@ -235,19 +259,16 @@ fn test_truncated_file_data() {
     // Truncate file to remove last 100 bytes (likely in central directory)
     truncate_at(path, original_size - 100);
 
-    let result = ArchiveReader::open(path);
-
-    // Should fail due to truncated central directory
-    // May fail immediately or on first operation (lazy validation)
-    if let Err(err) = result {
-        match err {
-            EngramError::Io(_) => {}, // Expected
-            EngramError::InvalidFormat(_) => {}, // Also acceptable
-            other => println!("Got error: {:?}", other),
+    // The central directory now runs past the end of the file
+    match ArchiveReader::open_and_init(path) {
+        Err(EngramError::TruncatedArchive {
+            expected_len,
+            actual_len,
+        }) => {
+            assert_eq!(expected_len, original_size);
+            assert_eq!(actual_len, original_size - 100);
         }
-    } else {
-        println!("Archive opened despite truncation (lazy validation)");
-        // Will likely fail on actual operations
+        other => panic!("Expected TruncatedArchive error, got: {:?}", other.err()),
     }
 }
 
@@ -278,7 +299,7 @@ fn test_corrupted_compression_method() {
         if let Err(err) = read_result {
             match err {
                 EngramError::InvalidCompression(_) => {}, // Expected
-                EngramError::DecompressionFailed(_) => {}, // Also acceptable
+                EngramError::DecompressionFailed { .. } => {}, // Also acceptable
                 EngramError::FileNotFound(_) => {}, // Corruption may affect file lookup
                 EngramError::InvalidFormat(_) => {}, // Also possible
                 other => panic!("Expected compression/lookup error, got: {:?}", other),
@@ -394,7 +415,7 @@ fn test_bit_flip_in_compressed_data() {
     let flipped_byte = byte[0] ^ 0x01; // Flip LSB
     corrupt_byte_at(path, middle_offset, flipped_byte);
 
-    let result = ArchiveReader::open(path);
+    let result = ArchiveReader::open_and_init(path);
 
     if let Ok(mut reader) = result {
         // Try to read the compressed file
@@ -402,8 +423,10 @@ fn test_bit_flip_in_compressed_data() {
 
         // Might fail during decompression due to corrupted data
         if let Err(err) = read_result {
-            match err {
-                EngramError::DecompressionFailed(_) => {}, // Expected
+            // Whatever failed, the error names the entry
+            assert!(err.to_string().contains("compressed.txt"), "{}", err);
+            match err.root_cause() {
+                EngramError::DecompressionFailed { .. } => {}, // Expected
                 EngramError::Io(_) => {}, // Also acceptable
                 EngramError::CrcMismatch { .. } => {}, // Also acceptable
                 other => println!("Got error: {:?}", other),
@@ -3,7 +3,7 @@
 //! Tests for incomplete archives, partial writes, and interrupted creation.
 //! Based on TESTING_PLAN.md Phase 2.3
 
-use engram_rs::{ArchiveReader, ArchiveWriter};
+use engram_rs::{ArchiveReader, ArchiveWriter, EngramError};
 use std::fs::{File, OpenOptions};
 use std::io::{Seek, SeekFrom, Write};
 use tempfile::NamedTempFile;
@@ -17,6 +17,23 @@ fn truncate_file(path: &std::path::Path, new_size: u64) {
     file.set_len(new_size).unwrap();
 }
 
+/// Helper: Assert that `path` fails to initialize as a truncated archive
+/// that should have been `expected_len` bytes long
+fn assert_truncated(path: &std::path::Path, expected_len: u64) {
+    let actual = std::fs::metadata(path).unwrap().len();
+    match ArchiveReader::open_and_init(path) {
+        Err(EngramError::TruncatedArchive {
+            expected_len: expected,
+            actual_len,
+        }) => {
+            assert_eq!(expected, expected_len);
+            assert_eq!(actual_len, actual);
+        }
+        Err(other) => panic!("Expected TruncatedArchive, got: {:?}", other),
+        Ok(_) => panic!("Truncated archive ({} bytes) opened", actual),
+    }
+}
+
 /// Helper: Create complete archive for testing
 fn create_complete_archive() -> NamedTempFile {
     let temp_file = NamedTempFile::new().unwrap();
@@ -65,12 +82,8 @@ fn test_partial_write_truncated_at_10_percent() {
     let truncate_size = original_size / 10;
     truncate_file(path, truncate_size);
 
-    // Should fail to open
-    let result = ArchiveReader::open_and_init(path);
-    assert!(
-        result.is_err(),
-        "Archive truncated at 10% should fail to open"
-    );
+    // Should fail to open, naming the missing length
+    assert_truncated(path, original_size);
 
     println!("✓ Archive truncated at 10% correctly rejected");
 }
@@ -84,11 +97,7 @@ fn test_partial_write_truncated_at_30_percent() {
     let truncate_size = (original_size * 30) / 100;
     truncate_file(path, truncate_size);
 
-    let result = ArchiveReader::open_and_init(path);
-    assert!(
-        result.is_err(),
-        "Archive truncated at 30% should fail to open"
-    );
+    assert_truncated(path, original_size);
 
     println!("✓ Archive truncated at 30% correctly rejected");
 }
@@ -102,11 +111,7 @@ fn test_partial_write_truncated_at_50_percent() {
     let truncate_size = original_size / 2;
     truncate_file(path, truncate_size);
 
-    let result = ArchiveReader::open_and_init(path);
-    assert!(
-        result.is_err(),
-        "Archive truncated at 50% should fail to open"
-    );
+    assert_truncated(path, original_size);
 
     println!("✓ Archive truncated at 50% correctly rejected");
 }
@@ -120,11 +125,7 @@ fn test_partial_write_truncated_at_70_percent() {
     let truncate_size = (original_size * 70) / 100;
     truncate_file(path, truncate_size);
 
-    let result = ArchiveReader::open_and_init(path);
-    assert!(
-        result.is_err(),
-        "Archive truncated at 70% should fail to open"
-    );
+    assert_truncated(path, original_size);
 
     println!("✓ Archive truncated at 70% correctly rejected");
 }
@@ -138,11 +139,7 @@ fn test_partial_write_truncated_at_90_percent() {
     let truncate_size = (original_size * 90) / 100;
     truncate_file(path, truncate_size);
 
-    let result = ArchiveReader::open_and_init(path);
-    assert!(
-        result.is_err(),
-        "Archive truncated at 90% should fail to open"
-    );
+    assert_truncated(path, original_size);
 
     println!("✓ Archive truncated at 90% correctly rejected");
 }
@@ -172,11 +169,7 @@ fn test_truncated_just_before_endr() {
     let truncate_size = original_size - 32;
     truncate_file(path, truncate_size);
 
-    let result = ArchiveReader::open_and_init(path);
-    assert!(
-        result.is_err(),
-        "Archive with truncated ENDR should fail to open"
-    );
+    assert_truncated(path, original_size);
 
     println!("✓ Archive with partial ENDR correctly rejected");
 }
@@ -199,15 +192,12 @@ fn test_empty_file_as_archive() {
 fn test_truncated_to_header_size() {
     let temp_file = create_complete_archive();
     let path = temp_file.path();
+    let original_size = std::fs::metadata(path).unwrap().len();
 
     // Truncate to exactly 64 bytes (just the header)
     truncate_file(path, 64);
 
-    let result = ArchiveReader::open_and_init(path);
-    assert!(
-        result.is_err(),
-        "Archive with only header should fail to initialize"
-    );
+    assert_truncated(path, original_size);
 
     println!("✓ Archive with only header correctly rejected");
 }
@@ -304,3 +294,90 @@ fn test_crash_recovery_archive_still_readable_after_failed_second_open() {
 
     println!("✓ Valid archive remains readable across multiple open/close cycles");
 }
+
+#[test]
+fn test_truncated_encrypted_archive() {
+    let key = [0x24u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+
+    let mut writer = ArchiveWriter::create(path)
+        .unwrap()
+        .with_archive_encryption(&key);
+    writer.add_file("secret.txt", b"secret data").unwrap();
+    writer.finalize().unwrap();
+    let original_size = std::fs::metadata(path).unwrap().len();
+
+    // Losing the tail must be reported before decryption is attempted
+    truncate_file(path, original_size - 10);
+    match ArchiveReader::open_encrypted(path, &key) {
+        Err(EngramError::TruncatedArchive {
+            expected_len,
+            actual_len,
+        }) => {
+            assert_eq!(expected_len, original_size);
+            assert_eq!(actual_len, original_size - 10);
+        }
+        Err(other) => panic!("Expected TruncatedArchive, got: {:?}", other),
+        Ok(_) => panic!("Truncated archive opened"),
+    }
+
+    println!("✓ Truncated encrypted archive correctly rejected");
+}
+
+#[test]
+fn test_recover_without_central_directory() {
+    let temp_file = create_complete_archive();
+    let path = temp_file.path();
+
+    // Zero everything from the central directory to the end (CD and ENDR)
+    let mut bytes = std::fs::read(path).unwrap();
+    let cd_offset = u64::from_le_bytes(bytes[16..24].try_into().unwrap()) as usize;
+    bytes[cd_offset..].fill(0);
+    std::fs::write(path, &bytes).unwrap();
+
+    assert!(ArchiveReader::open_and_init(path).is_err());
+
+    let mut reader = ArchiveReader::recover(path).unwrap();
+    assert_eq!(reader.entry_count(), 10);
+    for i in 0..10 {
+        let filename = format!("file{}.txt", i);
+        assert!(reader.contains(&filename));
+        let data = reader.read_file(&filename).unwrap();
+        assert_eq!(data, format!("data{}", i).as_bytes());
+    }
+
+    println!("✓ Recovered all entries without a central directory");
+}
+
+#[test]
+fn test_recover_stops_at_truncated_entry() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+
+    let key = [0x42u8; 32];
+    let mut writer = ArchiveWriter::create(path)
+        .unwrap()
+        .with_per_file_encryption(&key);
+    writer.add_file("first.txt", b"first").unwrap();
+    writer.add_file("second.bin", &vec![7u8; 10_000]).unwrap();
+    writer.add_file("third.txt", b"third").unwrap();
+    writer.finalize().unwrap();
+
+    // Cut the archive in the middle of the second entry's payload
+    let second_offset = {
+        let reader = ArchiveReader::open_encrypted(path, &key).unwrap();
+        reader.get_entry("second.bin").unwrap().data_offset
+    };
+    truncate_file(path, second_offset + 100);
+
+    let mut reader = ArchiveReader::recover(path)
+        .unwrap()
+        .with_decryption_key(&key);
+    assert_eq!(reader.entry_count(), 1);
+    assert_eq!(reader.list_files(), ["first.txt"]);
+    assert_eq!(reader.read_file("first.txt").unwrap(), b"first");
+    assert!(!reader.contains("second.bin"));
+
+    println!("✓ Recovery stops at the first unreadable entry");
+}
@@ -0,0 +1,206 @@
+//! Content deduplication and alias tests
+
+use engram_rs::{ArchiveReader, ArchiveWriter, EngramError};
+use tempfile::NamedTempFile;
+
+/// Helper: Deterministic incompressible data
+fn pseudo_random(len: usize, seed: u64) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6364136223846793005)
+                .wrapping_add(1442695040888963407);
+            (state >> 33) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn test_dedup_identical_blobs() {
+    let blob = pseudo_random(1024 * 1024, 42);
+    let paths = ["icons/app.bin", "assets/shared/app.bin", "copy.bin"];
+
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_dedup();
+        for path in &paths {
+            writer.add_file(path, &blob).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    // ~1MB of payload, not ~3MB
+    let archive_size = std::fs::metadata(temp_file.path()).unwrap().len();
+    assert!(
+        archive_size < 1024 * 1024 + 64 * 1024,
+        "Archive too large: {} bytes",
+        archive_size
+    );
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), 3);
+
+    let first = reader.get_entry(paths[0]).unwrap().clone();
+    assert!(!first.is_deduplicated());
+    for path in &paths[1..] {
+        let entry = reader.get_entry(path).unwrap();
+        assert!(entry.is_deduplicated());
+        assert_eq!(entry.data_offset, first.data_offset);
+    }
+
+    for path in &paths {
+        assert_eq!(reader.read_file(path).unwrap(), blob);
+    }
+}
+
+#[test]
+fn test_without_dedup_stores_every_copy() {
+    let blob = pseudo_random(256 * 1024, 7);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        writer.add_file("a.bin", &blob).unwrap();
+        writer.add_file("b.bin", &blob).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let archive_size = std::fs::metadata(temp_file.path()).unwrap().len();
+    assert!(archive_size > 2 * 256 * 1024);
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(!reader.get_entry("b.bin").unwrap().is_deduplicated());
+}
+
+#[test]
+fn test_dedup_with_per_file_encryption() {
+    let key = [9u8; 32];
+    let blob = pseudo_random(64 * 1024, 3);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_per_file_encryption(&key)
+            .with_dedup();
+        writer.add_file("one.bin", &blob).unwrap();
+        writer.add_file("two.bin", &blob).unwrap();
+        writer.add_file("other.txt", b"different").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+
+    assert_eq!(reader.read_file("one.bin").unwrap(), blob);
+    assert_eq!(reader.read_file("two.bin").unwrap(), blob);
+    assert_eq!(reader.read_file("other.txt").unwrap(), b"different");
+}
+
+#[test]
+fn test_alias_reads_back_original() {
+    let strings = br#"{"greeting": "Hello"}"#;
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        writer.add_file("strings/en.json", strings).unwrap();
+        writer
+            .add_alias("strings/en-GB.json", "strings/en.json")
+            .unwrap();
+        // Alias of an alias
+        writer
+            .add_alias("strings\\en-AU.json", "strings/en-GB.json")
+            .unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), 3);
+    let original = reader.get_entry("strings/en.json").unwrap().clone();
+    assert!(!original.is_alias());
+    for path in ["strings/en-GB.json", "strings/en-AU.json"] {
+        let alias = reader.get_entry(path).unwrap();
+        assert!(alias.is_alias(), "{}", path);
+        assert!(alias.is_deduplicated(), "{}", path);
+        assert_eq!(alias.data_offset, original.data_offset);
+        assert_eq!(alias.crc32, original.crc32);
+        assert_eq!(reader.read_file(path).unwrap(), strings);
+    }
+    assert!(reader.verify_all().unwrap().is_ok());
+
+    // Extraction writes independent files
+    let dest = tempfile::tempdir().unwrap();
+    assert_eq!(reader.extract_all(dest.path()).unwrap(), 3);
+    for name in ["en.json", "en-GB.json", "en-AU.json"] {
+        let path = dest.path().join("strings").join(name);
+        assert!(!std::fs::symlink_metadata(&path).unwrap().is_symlink());
+        assert_eq!(std::fs::read(&path).unwrap(), strings);
+    }
+    std::fs::write(dest.path().join("strings/en-GB.json"), b"changed").unwrap();
+    assert_eq!(
+        std::fs::read(dest.path().join("strings/en.json")).unwrap(),
+        strings
+    );
+}
+
+#[test]
+fn test_alias_keeps_payload_when_target_is_added_again() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        writer.add_file("config.toml", b"version = 1").unwrap();
+        writer.add_alias("config.bak", "config.toml").unwrap();
+        writer.add_file("config.toml", b"version = 2").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    // The later entry wins for its own path; the alias still names the first
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.read_file("config.toml").unwrap(), b"version = 2");
+    assert_eq!(reader.read_file("config.bak").unwrap(), b"version = 1");
+}
+
+#[test]
+fn test_alias_rejected_with_per_file_encryption() {
+    let key = [9u8; 32];
+    let blob = pseudo_random(4096, 7);
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&key);
+    writer.add_file("one.bin", &blob).unwrap();
+    assert!(matches!(
+        writer.add_alias("two.bin", "one.bin"),
+        Err(EngramError::InvalidOptions(_))
+    ));
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    assert_eq!(reader.list_files(), ["one.bin"]);
+    assert_eq!(reader.read_file("one.bin").unwrap(), blob);
+}
+
+#[test]
+fn test_alias_requires_existing_file() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    assert!(matches!(
+        writer.add_alias("b.txt", "a.txt"),
+        Err(EngramError::FileNotFound(path)) if path == "a.txt"
+    ));
+
+    writer.add_directory("assets").unwrap();
+    assert!(matches!(
+        writer.add_alias("art", "assets"),
+        Err(EngramError::InvalidOptions(_))
+    ));
+
+    writer.add_file("a.txt", b"a").unwrap();
+    assert!(matches!(
+        writer.add_alias(".engram/a.txt", "a.txt"),
+        Err(EngramError::ReservedPath(_))
+    ));
+    assert_eq!(writer.pending_entries(), 2);
+}
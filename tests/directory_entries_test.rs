@@ -0,0 +1,68 @@
+//! Explicit directory entry tests
+
+use engram_rs::{ArchiveReader, ArchiveWriter};
+use tempfile::{NamedTempFile, TempDir};
+
+#[test]
+fn test_empty_directory_roundtrip() {
+    let archive_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(archive_file.path()).unwrap();
+        writer.add_directory("skeleton/empty/").unwrap();
+        writer.add_directory("skeleton/logs").unwrap();
+        writer.add_file("skeleton/README.md", b"layout").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(archive_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), 3);
+
+    let dir = reader.get_entry("skeleton/empty").unwrap();
+    assert!(dir.is_directory());
+    assert_eq!(dir.uncompressed_size, 0);
+    assert_eq!(dir.compressed_size, 0);
+    assert!(!reader.get_entry("skeleton/README.md").unwrap().is_directory());
+
+    let out_dir = TempDir::new().unwrap();
+    reader.extract_all(out_dir.path()).unwrap();
+
+    assert!(out_dir.path().join("skeleton/empty").is_dir());
+    assert!(out_dir.path().join("skeleton/logs").is_dir());
+    assert_eq!(
+        std::fs::read(out_dir.path().join("skeleton/README.md")).unwrap(),
+        b"layout"
+    );
+}
+
+#[test]
+fn test_directory_with_per_file_encryption() {
+    let key = [7u8; 32];
+    let archive_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(archive_file.path())
+            .unwrap()
+            .with_per_file_encryption(&key);
+        writer.add_directory("cache").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_encrypted(archive_file.path(), &key).unwrap();
+
+    let dir = reader.get_entry("cache").unwrap();
+    assert!(dir.is_directory());
+    assert_eq!(dir.compressed_size, 0);
+    assert!(reader.read_file("cache").unwrap().is_empty());
+
+    let out_dir = TempDir::new().unwrap();
+    reader.extract_all(out_dir.path()).unwrap();
+    assert!(out_dir.path().join("cache").is_dir());
+}
+
+#[test]
+fn test_directory_rejects_unsafe_path() {
+    let archive_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(archive_file.path()).unwrap();
+
+    assert!(writer.add_directory("../outside").is_err());
+    assert!(writer.add_directory("/").is_err());
+}
@@ -0,0 +1,160 @@
+//! Drain entries tests
+//!
+//! Covers `ArchiveReader::drain_entries`: every file yielded once, in data
+//! offset order, with the same content and checks as `read_file`.
+
+use engram_rs::archive::LocalEntryHeader;
+use engram_rs::{ArchiveReader, ArchiveWriter, CancellationToken, EngramError};
+use std::collections::HashMap;
+use tempfile::NamedTempFile;
+
+const KEY: [u8; 32] = [0x42u8; 32];
+
+/// Helper: Files of varied size and compressibility, in write order
+fn sample_files() -> Vec<(String, Vec<u8>)> {
+    let mut files = Vec::new();
+    for i in 0..40 {
+        // Written in reverse path order, so path and offset order differ
+        let path = format!("dir{}/file{:02}.txt", i % 3, 39 - i);
+        let data = format!("content of file {}\n", i).repeat(i * 50 + 1);
+        files.push((path, data.into_bytes()));
+    }
+    files.push(("empty.bin".to_string(), Vec::new()));
+    files.push((
+        "random.bin".to_string(),
+        (0..10_000u32).map(|i| (i * 7919 % 256) as u8).collect(),
+    ));
+    files
+}
+
+/// Helper: Archive holding `files`, written by `writer`
+fn write_archive(mut writer: ArchiveWriter, files: &[(String, Vec<u8>)]) {
+    for (path, data) in files {
+        writer.add_file(path, data).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_drain_yields_every_file_once() {
+    let files = sample_files();
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_sorted_directory();
+    writer.add_directory("empty_dir").unwrap();
+    write_archive(writer, &files);
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let mut expected: HashMap<String, Vec<u8>> = files.iter().cloned().collect();
+    let mut order = Vec::new();
+    for item in reader.drain_entries() {
+        let (path, data) = item.unwrap();
+        let expected_data = expected
+            .remove(&path)
+            .unwrap_or_else(|| panic!("{} yielded twice or unexpected", path));
+        assert_eq!(data, expected_data, "{}", path);
+        order.push(path);
+    }
+    assert!(expected.is_empty(), "not yielded: {:?}", expected.keys());
+    assert!(!order.iter().any(|path| path.starts_with("empty_dir")));
+
+    // Yielded front to back, which for one writer is the order files were
+    // added, not the sorted directory's path order
+    let offsets: Vec<u64> = order
+        .iter()
+        .map(|path| reader.get_entry(path).unwrap().data_offset)
+        .collect();
+    assert!(offsets.windows(2).all(|pair| pair[0] < pair[1]));
+    let written: Vec<&String> = files.iter().map(|(path, _)| path).collect();
+    assert_eq!(order.iter().collect::<Vec<_>>(), written);
+    let mut sorted = order.clone();
+    sorted.sort();
+    assert_ne!(sorted, order);
+}
+
+#[test]
+fn test_drain_shared_and_encrypted_payloads() {
+    let files = sample_files();
+    let mut with_copies = files.clone();
+    with_copies.push(("copy/random.bin".to_string(), files[41].1.clone()));
+    with_copies.push(("copy/file00.txt".to_string(), files[0].1.clone()));
+
+    for (per_file, archive) in [(true, false), (false, true), (false, false)] {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_dedup();
+        if per_file {
+            writer = writer.with_per_file_encryption(&KEY);
+        }
+        if archive {
+            writer = writer.with_archive_encryption(&KEY);
+        }
+        write_archive(writer, &with_copies);
+
+        let mut reader = ArchiveReader::open(temp_file.path()).unwrap();
+        if per_file || archive {
+            reader = reader.with_decryption_key(&KEY);
+        }
+        reader.initialize().unwrap();
+
+        // Entries sharing a payload are each yielded with the shared content
+        let drained: HashMap<String, Vec<u8>> = reader
+            .drain_entries()
+            .collect::<engram_rs::Result<_>>()
+            .unwrap();
+        assert_eq!(drained.len(), with_copies.len());
+        for (path, data) in &with_copies {
+            assert_eq!(&drained[path], data, "{}", path);
+        }
+    }
+}
+
+#[test]
+fn test_drain_reports_damaged_entries_and_goes_on() {
+    let files = sample_files();
+    let temp_file = NamedTempFile::new().unwrap();
+    write_archive(ArchiveWriter::create(temp_file.path()).unwrap(), &files);
+
+    // Flip the last payload byte of one file
+    let damaged = "dir0/file36.txt";
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    let entry = ArchiveReader::open_and_init(temp_file.path())
+        .unwrap()
+        .get_entry(damaged)
+        .unwrap()
+        .clone();
+    let offset = entry.data_offset as usize;
+    let header = LocalEntryHeader::read_from(&bytes[offset..]).unwrap();
+    bytes[offset + header.header_size() + entry.compressed_size as usize - 1] ^= 0xFF;
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let results: Vec<_> = reader.drain_entries().collect();
+    assert_eq!(results.len(), files.len());
+    let failed: Vec<_> = results.iter().filter(|result| result.is_err()).collect();
+    assert_eq!(failed.len(), 1, "{:?}", failed);
+    assert!(failed[0]
+        .as_ref()
+        .unwrap_err()
+        .to_string()
+        .contains(damaged));
+}
+
+#[test]
+fn test_drain_stops_when_cancelled() {
+    let files = sample_files();
+    let temp_file = NamedTempFile::new().unwrap();
+    write_archive(ArchiveWriter::create(temp_file.path()).unwrap(), &files);
+
+    let token = CancellationToken::new();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path())
+        .unwrap()
+        .with_cancellation(token.clone());
+    let mut drained = reader.drain_entries();
+    assert!(drained.next().unwrap().is_ok());
+    token.cancel();
+    assert!(matches!(drained.next(), Some(Err(EngramError::Cancelled))));
+    assert!(drained.next().is_none());
+}
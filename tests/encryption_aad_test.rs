@@ -0,0 +1,195 @@
+//! Per-file encryption associated data tests
+//!
+//! Per-file encrypted payloads are bound to their central directory path and
+//! size, so ciphertext moved between entries no longer decrypts, including
+//! through a forged deduplicated entry.
+
+use aes_gcm::aead::{Aead, KeyInit, Payload};
+use aes_gcm::{Aes256Gcm, Nonce};
+use engram_rs::archive::LocalEntryHeader;
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, EngramError, EntryInfo, CD_ENTRY_SIZE, ENTRY_FLAG_DEDUPLICATED,
+    HEADER_FLAG_ENTRY_AAD,
+};
+use std::ops::Range;
+use tempfile::NamedTempFile;
+
+const KEY: [u8; 32] = [0x5Au8; 32];
+
+/// Helper: Per-file encrypted archive with two equally sized configs
+fn create_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&KEY);
+    writer
+        .add_file("admin_config.txt", b"role=admin;allow=all")
+        .unwrap();
+    writer
+        .add_file("user_config.txt", b"role=user;allow=none")
+        .unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Byte range of an entry's stored payload in the archive file
+fn payload_range(bytes: &[u8], entry: &EntryInfo) -> Range<usize> {
+    let loca = &bytes[entry.data_offset as usize..];
+    let start =
+        entry.data_offset as usize + LocalEntryHeader::read_from(loca).unwrap().header_size();
+    start..start + entry.compressed_size as usize
+}
+
+/// Helper: Entries of the archive at `path`, without decrypting anything
+fn entries(path: &std::path::Path) -> Vec<EntryInfo> {
+    let reader = ArchiveReader::open_and_init(path).unwrap();
+    reader
+        .list_files()
+        .iter()
+        .map(|p| reader.get_entry(p).unwrap().clone())
+        .collect()
+}
+
+#[test]
+fn test_entry_aad_flag() {
+    let encrypted = create_archive();
+    let reader = ArchiveReader::open(encrypted.path()).unwrap();
+    assert!(reader.header().has_entry_aad());
+    assert_ne!(reader.header().flags & HEADER_FLAG_ENTRY_AAD, 0);
+
+    let plain = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(plain.path()).unwrap();
+        writer.add_file("a.txt", b"a").unwrap();
+        writer.finalize().unwrap();
+    }
+    let reader = ArchiveReader::open(plain.path()).unwrap();
+    assert!(!reader.header().has_entry_aad());
+}
+
+#[test]
+fn test_swapped_ciphertext_fails() {
+    let temp_file = create_archive();
+    let entries = entries(temp_file.path());
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+
+    let admin = payload_range(&bytes, &entries[0]);
+    let user = payload_range(&bytes, &entries[1]);
+    assert_eq!(admin.len(), user.len());
+    let admin_payload = bytes[admin.clone()].to_vec();
+    let user_payload = bytes[user.clone()].to_vec();
+    bytes[admin].copy_from_slice(&user_payload);
+    bytes[user].copy_from_slice(&admin_payload);
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &KEY).unwrap();
+    assert!(matches!(
+        reader.read_file("user_config.txt"),
+        Err(EngramError::DecryptionFailed)
+    ));
+    assert!(matches!(
+        reader.read_file("admin_config.txt"),
+        Err(EngramError::DecryptionFailed)
+    ));
+}
+
+#[test]
+fn test_forged_deduplicated_entry_fails() {
+    let temp_file = create_archive();
+    let entries = entries(temp_file.path());
+    let (admin, user) = (&entries[0], &entries[1]);
+    let cd_offset = ArchiveReader::open(temp_file.path())
+        .unwrap()
+        .header()
+        .central_directory_offset as usize;
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+
+    // Point the user entry at the admin payload, copying its sizes and CRC,
+    // and mark it deduplicated so its LOCA path goes unchecked
+    let cd_entry = &mut bytes[cd_offset + CD_ENTRY_SIZE..cd_offset + 2 * CD_ENTRY_SIZE];
+    assert_eq!(&cd_entry[44..44 + user.path.len()], user.path.as_bytes());
+    cd_entry[4..12].copy_from_slice(&admin.data_offset.to_le_bytes());
+    cd_entry[12..20].copy_from_slice(&admin.uncompressed_size.to_le_bytes());
+    cd_entry[20..28].copy_from_slice(&admin.compressed_size.to_le_bytes());
+    cd_entry[28..32].copy_from_slice(&admin.crc32.to_le_bytes());
+    cd_entry[41] |= ENTRY_FLAG_DEDUPLICATED;
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &KEY).unwrap();
+    assert!(reader
+        .get_entry("user_config.txt")
+        .unwrap()
+        .is_deduplicated());
+    assert!(matches!(
+        reader.read_file("user_config.txt"),
+        Err(EngramError::DecryptionFailed)
+    ));
+    assert_eq!(
+        reader.read_file("admin_config.txt").unwrap(),
+        b"role=admin;allow=all"
+    );
+}
+
+#[test]
+fn test_dedup_stores_copies_in_full() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&KEY)
+        .with_dedup();
+    writer.add_file("a.txt", b"same content").unwrap();
+    writer.add_file("b.txt", b"same content").unwrap();
+    writer.finalize().unwrap();
+
+    let entries = entries(temp_file.path());
+    assert!(entries.iter().all(|entry| !entry.is_deduplicated()));
+    assert_ne!(entries[0].data_offset, entries[1].data_offset);
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &KEY).unwrap();
+    assert_eq!(reader.read_file("b.txt").unwrap(), b"same content");
+}
+
+#[test]
+fn test_archives_without_aad_still_read() {
+    let temp_file = create_archive();
+    let entries = entries(temp_file.path());
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    let cipher = Aes256Gcm::new((&KEY).into());
+
+    // Re-encrypt every payload the way older writers did: no associated data
+    for entry in &entries {
+        let range = payload_range(&bytes, entry);
+        let (nonce, ciphertext) = bytes[range.clone()].split_at(12);
+        let mut aad = entry.path.as_bytes().to_vec();
+        aad.extend_from_slice(&entry.uncompressed_size.to_le_bytes());
+
+        #[allow(deprecated)]
+        let nonce = *Nonce::from_slice(nonce);
+        let plaintext = cipher
+            .decrypt(
+                &nonce,
+                Payload {
+                    msg: ciphertext,
+                    aad: &aad,
+                },
+            )
+            .unwrap();
+        let legacy = cipher.encrypt(&nonce, plaintext.as_slice()).unwrap();
+        bytes[range.start + 12..range.end].copy_from_slice(&legacy);
+    }
+
+    // Clear the flag (header flags live at offset 40)
+    let flags = u32::from_le_bytes(bytes[40..44].try_into().unwrap()) & !HEADER_FLAG_ENTRY_AAD;
+    bytes[40..44].copy_from_slice(&flags.to_le_bytes());
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &KEY).unwrap();
+    assert!(!reader.header().has_entry_aad());
+    assert_eq!(
+        reader.read_file("admin_config.txt").unwrap(),
+        b"role=admin;allow=all"
+    );
+    assert_eq!(
+        reader.read_file("user_config.txt").unwrap(),
+        b"role=user;allow=none"
+    );
+}
@@ -3,7 +3,7 @@
 //! Tests for AES-256-GCM encryption, key handling, and decryption attacks.
 //! Based on TESTING_PLAN.md Phase 1.4
 
-use engram_rs::{ArchiveReader, ArchiveWriter};
+use engram_rs::{ArchiveReader, ArchiveWriter, EncryptionMode, EngramError};
 use tempfile::NamedTempFile;
 
 /// Helper: Generate test key
@@ -35,10 +35,7 @@ fn test_archive_encryption_roundtrip() {
 
     // Read back with correct key
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         assert_eq!(reader.entry_count(), 2);
 
@@ -73,10 +70,7 @@ fn test_per_file_encryption_roundtrip() {
 
     // Read back with correct key
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         assert_eq!(reader.entry_count(), 2);
 
@@ -239,10 +233,7 @@ fn test_archive_encryption_with_compression() {
 
     // Read back
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         let data = reader.read_file("compressed.txt").unwrap();
         assert_eq!(data.len(), 4000);
@@ -270,10 +261,7 @@ fn test_per_file_encryption_with_compression() {
 
     // Read back
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         let data = reader.read_file("file.txt").unwrap();
         assert_eq!(data.len(), 4000);
@@ -299,10 +287,7 @@ fn test_empty_file_with_encryption() {
 
     // Read back
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         // Check if file appears in list
         let files = reader.list_files();
@@ -341,10 +326,7 @@ fn test_multiple_files_archive_encryption() {
 
     // Read back all files
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         assert_eq!(reader.entry_count(), 10);
 
@@ -378,10 +360,7 @@ fn test_binary_data_encryption() {
 
     // Read back
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         let data = reader.read_file("binary.bin").unwrap();
         assert_eq!(data.len(), 256);
@@ -412,10 +391,7 @@ fn test_large_file_encryption() {
 
     // Read back
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         let data = reader.read_file("large.bin").unwrap();
         assert_eq!(data.len(), 1024 * 1024);
@@ -442,10 +418,7 @@ fn test_encryption_preserves_metadata() {
 
     // Read and check metadata
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&key);
-        reader.initialize().unwrap();
+        let reader = ArchiveReader::open_encrypted(path, &key).unwrap();
 
         assert_eq!(reader.entry_count(), 1);
         assert!(reader.contains("test.txt"));
@@ -539,10 +512,7 @@ fn test_zero_key() {
 
     // Should still work (encryption doesn't validate key strength)
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&zero_key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &zero_key).unwrap();
 
         let data = reader.read_file("test.txt").unwrap();
         assert_eq!(data, b"Data");
@@ -567,12 +537,88 @@ fn test_all_ones_key() {
 
     // Read back
     {
-        let mut reader = ArchiveReader::open(path)
-            .unwrap()
-            .with_decryption_key(&ones_key);
-        reader.initialize().unwrap();
+        let mut reader = ArchiveReader::open_encrypted(path, &ones_key).unwrap();
 
         let data = reader.read_file("test.txt").unwrap();
         assert_eq!(data, b"Data");
     }
 }
+
+/// Helper: Write a one-file archive encrypted with `test_key()` in `mode`
+fn create_archive_for_key_errors(mode: EncryptionMode) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let mut writer = match mode {
+        EncryptionMode::Archive => writer.with_archive_encryption(&test_key()),
+        EncryptionMode::PerFile => writer.with_per_file_encryption(&test_key()),
+        EncryptionMode::None => writer,
+    };
+    writer.add_file("secret.txt", b"Secret data").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_archive_encryption_key_errors() {
+    let temp_file = create_archive_for_key_errors(EncryptionMode::Archive);
+
+    // No key
+    let result = ArchiveReader::open(temp_file.path()).unwrap().initialize();
+    assert!(matches!(result, Err(EngramError::MissingDecryptionKey)));
+
+    // Wrong key
+    let result = ArchiveReader::open_encrypted(temp_file.path(), &different_key());
+    assert!(matches!(result, Err(EngramError::DecryptionFailed)));
+}
+
+#[test]
+fn test_per_file_encryption_key_errors() {
+    let temp_file = create_archive_for_key_errors(EncryptionMode::PerFile);
+
+    // No key: the central directory is readable, entries are not
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let result = reader.read_file("secret.txt");
+    assert!(matches!(result, Err(EngramError::MissingDecryptionKey)));
+
+    // Wrong key
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &different_key()).unwrap();
+    let result = reader.read_file("secret.txt");
+    assert!(matches!(result, Err(EngramError::DecryptionFailed)));
+}
+
+#[test]
+fn test_key_for_unencrypted_archive() {
+    let temp_file = create_archive_for_key_errors(EncryptionMode::None);
+
+    let result = ArchiveReader::open_encrypted(temp_file.path(), &test_key());
+    assert!(matches!(result, Err(EngramError::NotEncrypted)));
+
+    // Without a key it opens normally
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.read_file("secret.txt").unwrap(), b"Secret data");
+}
+
+#[test]
+fn test_encryption_state_reported_after_open() {
+    for mode in [
+        EncryptionMode::None,
+        EncryptionMode::Archive,
+        EncryptionMode::PerFile,
+    ] {
+        let temp_file = create_archive_for_key_errors(mode);
+
+        // Straight from the header, before any key or `initialize`
+        let reader = ArchiveReader::open(temp_file.path()).unwrap();
+        assert_eq!(reader.encryption_mode(), mode);
+        assert_eq!(reader.is_encrypted(), mode != EncryptionMode::None);
+        assert_eq!(reader.requires_key(), mode != EncryptionMode::None);
+
+        if mode != EncryptionMode::None {
+            let mut reader = reader.with_decryption_key(&test_key());
+            assert!(reader.is_encrypted());
+            assert!(!reader.requires_key());
+            reader.initialize().unwrap();
+            assert_eq!(reader.read_file("secret.txt").unwrap(), b"Secret data");
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! ENDR signature tests
+//!
+//! Covers `ArchiveWriter::with_endr_signature` and
+//! `ArchiveReader::verify_endr_signature`, and that readers which ignore the
+//! signature block still open signed archives.
+
+use ed25519_dalek::SigningKey;
+use engram_rs::archive::{EndRecord, END_RECORD_SIZE, SIGNATURE_BLOCK_SIZE};
+use engram_rs::{ArchiveReader, ArchiveWriter, EngramError, CD_ENTRY_SIZE, HEADER_SIZE};
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+const KEY: [u8; 32] = [0x5Au8; 32];
+
+/// Helper: Signing key derived from `seed`
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+/// Helper: Signed archive with a few files
+fn create_signed(key: &SigningKey) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_fixed_time(1_700_000_000)
+        .with_content_version(3)
+        .with_endr_signature(key);
+    writer.add_file("readme.txt", b"signed archive").unwrap();
+    writer
+        .add_file("data/log.txt", "entry\n".repeat(2000).as_bytes())
+        .unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Verify `path` against `trusted`
+fn verify(path: &Path, trusted: &[&SigningKey]) -> engram_rs::Result<bool> {
+    let trusted: Vec<_> = trusted.iter().map(|key| key.verifying_key()).collect();
+    ArchiveReader::open(path)?.verify_endr_signature(&trusted)
+}
+
+/// Helper: Copy of `bytes` in a temporary file, with `modify` applied
+fn tampered(bytes: &[u8], modify: impl FnOnce(&mut Vec<u8>)) -> NamedTempFile {
+    let mut bytes = bytes.to_vec();
+    modify(&mut bytes);
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+    temp_file
+}
+
+#[test]
+fn test_signature_verifies_with_trusted_key() {
+    let key = signing_key(1);
+    let temp_file = create_signed(&key);
+
+    assert!(verify(temp_file.path(), &[&key]).unwrap());
+    assert!(verify(temp_file.path(), &[&signing_key(2), &key]).unwrap());
+    assert!(!verify(temp_file.path(), &[&signing_key(2)]).unwrap());
+    assert!(!verify(temp_file.path(), &[]).unwrap());
+
+    // The block sits right before the ENDR, which records its size
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    let end_record = EndRecord::read_from(&bytes[bytes.len() - END_RECORD_SIZE..]).unwrap();
+    assert_eq!(end_record.signature_size as usize, SIGNATURE_BLOCK_SIZE);
+    let block = &bytes[bytes.len() - END_RECORD_SIZE - SIGNATURE_BLOCK_SIZE..];
+    assert_eq!(&block[..4], b"SIGN");
+    assert_eq!(&block[8..16], &1_700_000_000u64.to_le_bytes());
+    assert_eq!(&block[16..48], key.verifying_key().as_bytes());
+}
+
+#[test]
+fn test_unsigned_archive_has_no_signature() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("readme.txt", b"unsigned").unwrap();
+    writer.finalize().unwrap();
+
+    assert!(matches!(
+        verify(temp_file.path(), &[&signing_key(1)]),
+        Err(EngramError::SignatureNotFound)
+    ));
+}
+
+#[test]
+fn test_tampering_is_evident() {
+    let key = signing_key(1);
+    let temp_file = create_signed(&key);
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    let end = bytes.len();
+    let cd_offset = u64::from_le_bytes(bytes[16..24].try_into().unwrap()) as usize;
+
+    // Renamed entry: first byte of the first central directory entry's path
+    let renamed = tampered(&bytes, |b| b[cd_offset + 44] ^= 0x20);
+    assert!(!verify(renamed.path(), &[&key]).unwrap());
+
+    // Changed CRC32 of the second entry
+    let crc = tampered(&bytes, |b| b[cd_offset + CD_ENTRY_SIZE + 28] ^= 0x01);
+    assert!(!verify(crc.path(), &[&key]).unwrap());
+
+    // Changed content version in the ENDR
+    let endr = tampered(&bytes, |b| b[end - END_RECORD_SIZE + 32] ^= 0x01);
+    assert!(!verify(endr.path(), &[&key]).unwrap());
+
+    // Changed timestamp or signature bytes in the block
+    let block = end - END_RECORD_SIZE - SIGNATURE_BLOCK_SIZE;
+    let timestamp = tampered(&bytes, |b| b[block + 8] ^= 0x01);
+    assert!(!verify(timestamp.path(), &[&key]).unwrap());
+    let signature = tampered(&bytes, |b| b[block + 100] ^= 0x01);
+    assert!(!verify(signature.path(), &[&key]).unwrap());
+
+    // Not a signature block at all
+    let magic = tampered(&bytes, |b| b[block] = b'X');
+    assert!(matches!(
+        verify(magic.path(), &[&key]),
+        Err(EngramError::InvalidSignature)
+    ));
+}
+
+#[test]
+fn test_readers_unaware_of_the_block_still_open() {
+    let key = signing_key(1);
+    let temp_file = create_signed(&key);
+    let log = "entry\n".repeat(2000);
+
+    // A reader that ignores the ENDR field sees the same archive as one whose
+    // writer never recorded it: a few unreferenced bytes before the ENDR
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    let end = bytes.len();
+    let unaware = tampered(&bytes, |b| {
+        b[end - END_RECORD_SIZE + 40..end - END_RECORD_SIZE + 44].fill(0)
+    });
+
+    for path in [temp_file.path(), unaware.path()] {
+        let mut reader = ArchiveReader::open_and_init(path).unwrap();
+        assert_eq!(reader.content_version(), 3);
+        assert_eq!(reader.read_file("readme.txt").unwrap(), b"signed archive");
+        assert_eq!(reader.read_file_at("data/log.txt").unwrap(), log.as_bytes());
+        let report = reader.verify_all().unwrap();
+        assert!(report.is_ok(), "{:?}", report.failed);
+
+        let mut lazy = ArchiveReader::open_lazy(path).unwrap();
+        assert_eq!(lazy.read_file("data/log.txt").unwrap(), log.as_bytes());
+
+        let mut recovered = ArchiveReader::recover(path).unwrap();
+        assert_eq!(recovered.entry_count(), 2);
+        assert_eq!(
+            recovered.read_file("readme.txt").unwrap(),
+            b"signed archive"
+        );
+
+        let end_record = EndRecord::read_from_end(std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(end_record.entry_count, 2);
+        assert!(end_record.central_directory_offset >= HEADER_SIZE as u64);
+    }
+
+    assert!(matches!(
+        verify(unaware.path(), &[&key]),
+        Err(EngramError::SignatureNotFound)
+    ));
+}
+
+#[test]
+fn test_signature_with_recipients() {
+    let key = signing_key(4);
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&KEY)
+        .with_endr_signature(&key);
+    writer.add_recipient_key("alice", &[0xA1u8; 32]).unwrap();
+    writer.add_file("secret.txt", b"for alice").unwrap();
+    writer.finalize().unwrap();
+
+    // Verifiable without any decryption key
+    assert!(verify(temp_file.path(), &[&key]).unwrap());
+
+    let mut reader = ArchiveReader::open(temp_file.path())
+        .unwrap()
+        .with_recipient_key("alice", &[0xA1u8; 32]);
+    reader.initialize().unwrap();
+    assert_eq!(reader.recipients().unwrap(), vec!["alice"]);
+    assert_eq!(reader.read_file("secret.txt").unwrap(), b"for alice");
+}
+
+#[test]
+fn test_signature_rejected_where_offsets_would_move() {
+    let temp_file = NamedTempFile::new().unwrap();
+
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_archive_encryption(&KEY)
+        .with_endr_signature(&signing_key(1));
+    assert!(matches!(
+        writer.add_file("a.txt", b"a"),
+        Err(EngramError::InvalidOptions(_))
+    ));
+
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_format_version(0, 4)
+        .unwrap()
+        .with_endr_signature(&signing_key(1));
+    match writer.add_file("a.txt", b"a") {
+        Err(EngramError::InvalidOptions(msg)) => {
+            assert!(msg.contains("ENDR signatures"), "{}", msg)
+        }
+        other => panic!("Expected InvalidOptions, got: {:?}", other),
+    }
+}
@@ -0,0 +1,153 @@
+//! Entry cache tests
+//!
+//! Covers `ArchiveReader::with_cache`, `read_file_cached` and `cache_stats`.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, CacheStats};
+use std::io::{Cursor, Read, Seek, SeekFrom};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tempfile::NamedTempFile;
+
+/// Helper: In-memory archive source that counts reads
+struct CountingSource {
+    inner: Cursor<Vec<u8>>,
+    reads: Arc<AtomicUsize>,
+}
+
+impl Read for CountingSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.inner.read(buf)
+    }
+}
+
+impl Seek for CountingSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+/// Helper: Archive with a shader include, a config file and a larger asset
+fn create_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer
+        .add_file(
+            "shaders/common.glsl",
+            "uniform mat4 mvp;\n".repeat(100).as_bytes(),
+        )
+        .unwrap();
+    writer
+        .add_file("config.toml", b"quality = \"high\"\n")
+        .unwrap();
+    writer
+        .add_file("textures/big.bin", &vec![7u8; 4096])
+        .unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_cache_hit_does_not_touch_source() {
+    let temp_file = create_archive();
+    let reads = Arc::new(AtomicUsize::new(0));
+    let source = CountingSource {
+        inner: Cursor::new(std::fs::read(temp_file.path()).unwrap()),
+        reads: Arc::clone(&reads),
+    };
+    let mut reader = ArchiveReader::from_reader(source)
+        .unwrap()
+        .with_cache(1024 * 1024);
+    reader.initialize().unwrap();
+
+    let first = reader.read_file_cached("shaders/common.glsl").unwrap();
+    let reads_after_first = reads.load(Ordering::Relaxed);
+
+    // Same entry, looked up with a different spelling of the path
+    let second = reader.read_file_cached("/shaders\\common.glsl").unwrap();
+    assert_eq!(reads.load(Ordering::Relaxed), reads_after_first);
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(*second, "uniform mat4 mvp;\n".repeat(100).into_bytes());
+
+    let stats = reader.cache_stats().unwrap();
+    assert_eq!(stats.hits, 1);
+    assert_eq!(stats.misses, 1);
+    assert_eq!(stats.entries, 1);
+    assert_eq!(stats.bytes, first.len());
+}
+
+#[test]
+fn test_cache_evicts_least_recently_used() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    for name in ["a", "b", "c"] {
+        writer
+            .add_file(&format!("{}.txt", name), name.repeat(100).as_bytes())
+            .unwrap();
+    }
+    writer.add_file("big.bin", &vec![7u8; 4096]).unwrap();
+    writer.finalize().unwrap();
+
+    // Room for two entries
+    let mut reader = ArchiveReader::open_and_init(temp_file.path())
+        .unwrap()
+        .with_cache(200);
+    reader.read_file_cached("a.txt").unwrap();
+    reader.read_file_cached("b.txt").unwrap();
+    // Touch a.txt so b.txt becomes the least recently used
+    reader.read_file_cached("a.txt").unwrap();
+    reader.read_file_cached("c.txt").unwrap();
+
+    // Larger than the whole cache: returned but not cached
+    assert_eq!(reader.read_file_cached("big.bin").unwrap().len(), 4096);
+
+    reader.read_file_cached("a.txt").unwrap();
+    assert_eq!(
+        reader.cache_stats().unwrap(),
+        CacheStats {
+            hits: 2,
+            misses: 4,
+            evictions: 1,
+            bytes: 200,
+            entries: 2,
+        }
+    );
+
+    // b.txt was evicted and is read again
+    reader.read_file_cached("b.txt").unwrap();
+    assert_eq!(reader.cache_stats().unwrap().misses, 5);
+}
+
+#[test]
+fn test_cache_encrypted_archive() {
+    let key = [0x42u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&key);
+    writer.add_file("secret.txt", b"cached plaintext").unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key)
+        .unwrap()
+        .with_cache(1024);
+    assert_eq!(
+        *reader.read_file_cached("secret.txt").unwrap(),
+        b"cached plaintext"
+    );
+    assert_eq!(
+        *reader.read_file_cached("secret.txt").unwrap(),
+        b"cached plaintext"
+    );
+    assert_eq!(reader.cache_stats().unwrap().hits, 1);
+}
+
+#[test]
+fn test_read_file_cached_without_cache() {
+    let temp_file = create_archive();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    let data = reader.read_file_cached("config.toml").unwrap();
+    assert_eq!(*data, b"quality = \"high\"\n");
+    assert!(reader.cache_stats().is_none());
+}
@@ -0,0 +1,136 @@
+//! Custom entry metadata tests
+//!
+//! Covers `FileMetadata::extra`, stored in `ENTRY_METADATA_PATH` and read back
+//! with `ArchiveReader::entry_metadata`, including what readers that do not
+//! know the table see.
+
+use engram_rs::archive::MAX_ENTRY_METADATA_SIZE;
+use engram_rs::{
+    ArchiveEditor, ArchiveReader, ArchiveWriter, EngramError, EntryMetadata, FileMetadata,
+    ENTRY_METADATA_PATH,
+};
+use std::collections::BTreeMap;
+use tempfile::NamedTempFile;
+
+/// Helper: Key/value pairs from string slices
+fn pairs(pairs: &[(&str, &str)]) -> BTreeMap<String, String> {
+    pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Helper: Archive with `app.js` carrying custom metadata and plain `b.txt`
+fn archive_with_metadata(extra: &BTreeMap<String, String>) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let metadata = FileMetadata {
+        modified_time: Some(1_700_000_000),
+        mime_type: Some("text/javascript"),
+        extra: Some(extra),
+        ..FileMetadata::default()
+    };
+    writer
+        .add_file_with_metadata("app.js", b"console.log(1);", metadata)
+        .unwrap();
+    writer.add_file("b.txt", b"plain").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_entry_metadata_round_trip() {
+    let extra = pairs(&[
+        ("cache-control", "max-age=3600"),
+        ("source-rev", "4f2a9c1"),
+        ("upstream-sha256", "9f86d081884c7d659a2feaa0c55ad015"),
+    ]);
+    let temp_file = archive_with_metadata(&extra);
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(
+        reader.entry_metadata("app.js").unwrap(),
+        Some(EntryMetadata {
+            modified_time: 1_700_000_000,
+            executable: false,
+            mime_type: Some("text/javascript".to_string()),
+            extra,
+        })
+    );
+    assert_eq!(reader.entry_metadata("b.txt").unwrap(), None);
+    assert!(matches!(
+        reader.entry_metadata("missing.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_readers_without_metadata_support_skip_it() {
+    let extra = pairs(&[("source-rev", "4f2a9c1")]);
+    let with_metadata = archive_with_metadata(&extra);
+    let without_metadata = archive_with_metadata(&BTreeMap::new());
+
+    // Same header flags and version: nothing new for older readers to reject
+    let mut reader = ArchiveReader::open_and_init(with_metadata.path()).unwrap();
+    let plain = ArchiveReader::open_and_init(without_metadata.path()).unwrap();
+    assert_eq!(reader.header().flags, plain.header().flags);
+    assert_eq!(reader.header().version_minor, plain.header().version_minor);
+    assert!(!plain.contains(ENTRY_METADATA_PATH));
+
+    // The table is an ordinary internal entry that every reader can read
+    // past, and entries keep their usual LOCA layout
+    assert_eq!(reader.list_user_files(), ["app.js", "b.txt"]);
+    assert!(reader.contains(ENTRY_METADATA_PATH));
+    assert_eq!(reader.read_file("app.js").unwrap(), b"console.log(1);");
+    assert!(reader.verify_all().unwrap().is_ok());
+    let recovered = ArchiveReader::recover(with_metadata.path()).unwrap();
+    assert_eq!(recovered.entry_count(), reader.entry_count());
+}
+
+#[test]
+fn test_entry_metadata_size_limit() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let too_large = pairs(&[("blob", &"x".repeat(MAX_ENTRY_METADATA_SIZE))]);
+    let metadata = FileMetadata {
+        extra: Some(&too_large),
+        ..FileMetadata::default()
+    };
+    let err = writer
+        .add_file_with_metadata("a.txt", b"a", metadata)
+        .unwrap_err();
+    assert!(
+        matches!(&err, EngramError::InvalidOptions(message) if message.contains("4096")),
+        "{:?}",
+        err
+    );
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), 0);
+    assert!(!reader.contains(ENTRY_METADATA_PATH));
+}
+
+#[test]
+fn test_editor_keeps_metadata_with_renamed_entries() {
+    let extra = pairs(&[("source-rev", "4f2a9c1")]);
+    let source = archive_with_metadata(&extra);
+    let edited = NamedTempFile::new().unwrap();
+
+    let mut editor = ArchiveEditor::open(source.path()).unwrap();
+    editor.rename("app.js", "static/app.js").unwrap();
+    editor.save_to(edited.path()).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(edited.path()).unwrap();
+    let metadata = reader.entry_metadata("static/app.js").unwrap().unwrap();
+    assert_eq!(metadata.extra, extra);
+    assert_eq!(reader.entry_metadata("b.txt").unwrap(), None);
+
+    // Removing the entry drops its metadata and then the table
+    let mut editor = ArchiveEditor::open(edited.path()).unwrap();
+    editor.remove("static/app.js").unwrap();
+    let trimmed = NamedTempFile::new().unwrap();
+    editor.save_to(trimmed.path()).unwrap();
+    let reader = ArchiveReader::open_and_init(trimmed.path()).unwrap();
+    assert!(!reader.contains(ENTRY_METADATA_PATH));
+}
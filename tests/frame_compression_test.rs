@@ -6,7 +6,14 @@
 //! Frame compression is used for files ≥ 50MB (52,428,800 bytes).
 //! Frame size is 64KB (65,536 bytes).
 
-use engram_rs::{ArchiveReader, ArchiveWriter};
+use engram_rs::archive::{FrameIndex, LocalEntryHeader};
+use engram_rs::{
+    ArchiveEditor, ArchiveReader, ArchiveWriter, CompressionMethod, EngramError,
+    FORMAT_VERSION_MINOR,
+};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
 use tempfile::NamedTempFile;
 
 const LARGE_FILE_THRESHOLD: usize = 50 * 1024 * 1024; // 50 MB
@@ -265,3 +272,223 @@ fn test_frame_compression_data_integrity() {
 
     println!("✓ Frame compression preserves data integrity (100MB pattern file)");
 }
+
+/// Helper: Writer that counts bytes instead of keeping them
+#[derive(Default)]
+struct CountingWriter {
+    bytes: u64,
+    writes: usize,
+    largest_write: usize,
+}
+
+impl Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.bytes += buf.len() as u64;
+        self.writes += 1;
+        self.largest_write = self.largest_write.max(buf.len());
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_frame_read_file_to_streams_frames() {
+    let size = LARGE_FILE_THRESHOLD + 100_000;
+    let temp_file = create_archive_with_sized_file("stream.bin", size);
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let mut out = CountingWriter::default();
+    let written = reader.read_file_to("stream.bin", &mut out).unwrap();
+
+    assert_eq!(written, size as u64);
+    assert_eq!(out.bytes, size as u64);
+    assert!(out.writes > 1);
+    assert!(out.largest_write <= 64 * 1024);
+}
+
+#[test]
+fn test_frame_corrupted_middle_frame_caught_by_crc() {
+    let size = LARGE_FILE_THRESHOLD + 100_000;
+
+    // Incompressible data, so each LZ4 frame is mostly literal bytes that still
+    // decode after a bit flip
+    let mut state = 0x9E37_79B9_7F4A_7C15_u64;
+    let data: Vec<u8> = (0..size)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+    let mut writer = ArchiveWriter::create(path).unwrap();
+    writer
+        .add_file_with_compression("noise.bin", &data, CompressionMethod::Lz4)
+        .unwrap();
+    writer.finalize().unwrap();
+    drop(data);
+
+    // Look up the middle frame in the frame index and flip a byte in it
+    let data_offset = {
+        let reader = ArchiveReader::open_and_init(path).unwrap();
+        reader.get_entry("noise.bin").unwrap().data_offset as usize
+    };
+    let mut bytes = std::fs::read(path).unwrap();
+    let payload =
+        data_offset + LocalEntryHeader::read_from(&bytes[data_offset..]).unwrap().header_size();
+    let index = FrameIndex::read_from(&bytes[payload..]).unwrap();
+    let frame = index.frames()[index.frames().len() / 2];
+    bytes[payload + frame.compressed_offset as usize + frame.compressed_size as usize / 2] ^= 0xFF;
+    std::fs::write(path, &bytes).unwrap();
+    drop(bytes);
+
+    // Streamed read: frames reach the writer one at a time, CRC fails at the end
+    let mut reader = ArchiveReader::open_and_init(path).unwrap();
+    let mut out = CountingWriter::default();
+    let result = reader.read_file_to("noise.bin", &mut out);
+    assert!(
+        matches!(result, Err(EngramError::CrcMismatch { .. })),
+        "expected CrcMismatch, got {:?}",
+        result
+    );
+    assert!(out.writes > 1);
+    assert!(out.largest_write <= 64 * 1024);
+
+    // Buffered read hits the same check
+    let result = reader.read_file("noise.bin");
+    assert!(matches!(result, Err(EngramError::CrcMismatch { .. })));
+}
+
+#[test]
+fn test_frame_read_prefix_first_frame() {
+    let temp_file = create_archive_with_sized_file("prefix.bin", LARGE_FILE_THRESHOLD);
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let prefix = reader.read_prefix("prefix.bin", 16).unwrap();
+    assert_eq!(prefix, vec![0xCD; 16]);
+}
+
+/// Helper: In-memory archive source that counts bytes read
+struct CountingSource {
+    inner: std::io::Cursor<Vec<u8>>,
+    bytes_read: Arc<AtomicU64>,
+}
+
+impl Read for CountingSource {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.bytes_read.fetch_add(n as u64, Ordering::Relaxed);
+        Ok(n)
+    }
+}
+
+impl Seek for CountingSource {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
+
+#[test]
+fn test_frame_read_file_range_reads_only_overlapping_frames() {
+    let size = 100 * 1024 * 1024;
+    let data: Vec<u8> = (0..size).map(|i| (i / 7 % 251) as u8).collect();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer
+        .add_file_with_compression("large.bin", &data, CompressionMethod::Lz4)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let bytes_read = Arc::new(AtomicU64::new(0));
+    let source = CountingSource {
+        inner: std::io::Cursor::new(std::fs::read(temp_file.path()).unwrap()),
+        bytes_read: Arc::clone(&bytes_read),
+    };
+    let mut reader = ArchiveReader::from_reader(source).unwrap();
+    reader.initialize().unwrap();
+    let before = bytes_read.load(Ordering::Relaxed);
+
+    // 1KB window straddling the frame boundary in the middle of the file
+    let offset = (size / 2 - 512) as u64;
+    let window = reader.read_file_range("large.bin", offset, 1024).unwrap();
+    assert_eq!(window, data[size / 2 - 512..size / 2 + 512]);
+
+    // The frame index (1600 entries of 24 bytes) and two frames, not 100MB
+    let read = bytes_read.load(Ordering::Relaxed) - before;
+    assert!(read < 256 * 1024, "read {} bytes for a 1KB range", read);
+}
+
+#[test]
+fn test_frame_read_file_range_clamped_to_file() {
+    let size = LARGE_FILE_THRESHOLD + 1000;
+    let data: Vec<u8> = (0..size).map(|i| (i % 256) as u8).collect();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("large.bin", &data).unwrap();
+    writer.add_file("small.txt", b"0123456789").unwrap();
+    writer
+        .add_file_with_compression("plain.bin", &data[..4096], CompressionMethod::None)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let tail = reader
+        .read_file_range("large.bin", (size - 10) as u64, 100)
+        .unwrap();
+    assert_eq!(tail, data[size - 10..]);
+    assert!(reader
+        .read_file_range("large.bin", size as u64 + 5, 10)
+        .unwrap()
+        .is_empty());
+    assert_eq!(reader.read_file_range("small.txt", 3, 4).unwrap(), b"3456");
+    assert_eq!(
+        reader.read_file_range("plain.bin", 4000, 200).unwrap(),
+        data[4000..4096]
+    );
+}
+
+#[test]
+fn test_frame_v10_archive_without_index() {
+    let size = LARGE_FILE_THRESHOLD + 70_000;
+    let data: Vec<u8> = (0..size).map(|i| (i / 3 % 256) as u8).collect();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_format_version(1, 0)
+        .unwrap();
+    writer.add_file("large.bin", &data).unwrap();
+    writer.finalize().unwrap();
+
+    // Interleaved frames: [count][size][frame]...
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.header().version_minor, 0);
+    assert_eq!(reader.read_file("large.bin").unwrap(), data);
+    assert_eq!(reader.read_prefix("large.bin", 8).unwrap(), data[..8]);
+    assert_eq!(
+        reader.read_file_range("large.bin", 70_000, 100).unwrap(),
+        data[70_000..70_100]
+    );
+
+    // Saving through the editor re-frames the entry with an index
+    let upgraded = NamedTempFile::new().unwrap();
+    ArchiveEditor::open(temp_file.path())
+        .unwrap()
+        .save_to(upgraded.path())
+        .unwrap();
+    let mut reader = ArchiveReader::open_and_init(upgraded.path()).unwrap();
+    assert_eq!(reader.header().version_minor, FORMAT_VERSION_MINOR);
+    assert_eq!(reader.read_file("large.bin").unwrap(), data);
+    assert_eq!(
+        reader.read_file_range("large.bin", 70_000, 100).unwrap(),
+        data[70_000..70_100]
+    );
+}
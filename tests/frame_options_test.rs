@@ -0,0 +1,212 @@
+//! Frame options tests
+//!
+//! Covers `ArchiveWriter::with_frame_options`: custom frame sizes and
+//! thresholds, the frame size recorded in the frame index, and archives mixing
+//! entries framed with different options.
+
+use engram_rs::archive::{FrameIndex, LocalEntryHeader, FRAME_SIZE, MIN_FRAME_COMPRESSION_SIZE};
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, FrameOptions, ENTRY_FLAG_FRAMED,
+};
+use tempfile::NamedTempFile;
+
+const MB: usize = 1024 * 1024;
+
+/// Helper: Compressible data that differs from frame to frame
+fn sample_data(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i / 1000 % 251) as u8).collect()
+}
+
+/// Helper: Frame index at the start of `path`'s stored payload
+fn frame_index(bytes: &[u8], reader: &ArchiveReader, path: &str) -> FrameIndex {
+    let entry = reader.get_entry(path).unwrap();
+    let offset = entry.data_offset as usize;
+    let header = LocalEntryHeader::read_from(&bytes[offset..]).unwrap();
+    FrameIndex::read_from(&bytes[offset + header.header_size()..]).unwrap()
+}
+
+#[test]
+fn test_custom_frame_size_roundtrip() {
+    let data = sample_data(9 * MB + 12_345);
+    let options = FrameOptions {
+        frame_size: MB,
+        min_file_size: 8 * MB,
+    };
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_frame_options(options)
+        .unwrap();
+    writer
+        .add_file_with_compression("genome.bin", &data, CompressionMethod::Zstd)
+        .unwrap();
+    writer
+        .add_file_with_compression("small.bin", &data[..MB], CompressionMethod::Zstd)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let entry = reader.get_entry("genome.bin").unwrap();
+    assert_ne!(entry.flags & ENTRY_FLAG_FRAMED, 0);
+    let index = frame_index(&bytes, &reader, "genome.bin");
+    assert_eq!(index.frame_size(), MB as u32);
+    assert_eq!(index.frames().len(), 10);
+    assert_eq!(index.decompressed_size(), data.len() as u64);
+
+    // Below the threshold: compressed as one stream
+    assert_eq!(
+        reader.get_entry("small.bin").unwrap().flags & ENTRY_FLAG_FRAMED,
+        0
+    );
+
+    assert_eq!(reader.read_file("genome.bin").unwrap(), data);
+    assert_eq!(reader.read_file("small.bin").unwrap(), &data[..MB]);
+    let start = 3 * MB - 10;
+    assert_eq!(
+        reader
+            .read_file_range("genome.bin", start as u64, 20)
+            .unwrap(),
+        &data[start..start + 20]
+    );
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+}
+
+#[test]
+fn test_file_exactly_one_frame_long() {
+    let data = sample_data(MB);
+    let options = FrameOptions {
+        frame_size: MB,
+        min_file_size: MB,
+    };
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_frame_options(options)
+        .unwrap();
+    writer
+        .add_file_with_compression("one.bin", &data, CompressionMethod::Lz4)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let index = frame_index(&bytes, &reader, "one.bin");
+    assert_eq!(index.frames().len(), 1);
+    assert_eq!(index.frames()[0].decompressed_size, MB as u32);
+    assert_eq!(reader.read_file("one.bin").unwrap(), data);
+    assert_eq!(
+        reader.read_file_range("one.bin", MB as u64 - 5, 5).unwrap(),
+        &data[MB - 5..]
+    );
+}
+
+#[test]
+fn test_default_and_custom_frames_in_one_archive() {
+    let large = sample_data(MIN_FRAME_COMPRESSION_SIZE + 1000);
+    let medium = sample_data(8 * MB);
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer
+        .add_file_with_compression("default/large.bin", &large, CompressionMethod::Zstd)
+        .unwrap();
+    writer
+        .add_file_with_compression("default/medium.bin", &medium, CompressionMethod::Zstd)
+        .unwrap();
+
+    // Options apply to files added from here on
+    let mut writer = writer
+        .with_frame_options(FrameOptions {
+            frame_size: 256 * 1024,
+            min_file_size: 4 * MB,
+        })
+        .unwrap();
+    writer
+        .add_file_with_compression("custom/large.bin", &large, CompressionMethod::Lz4)
+        .unwrap();
+    writer
+        .add_file_with_compression("custom/medium.bin", &medium, CompressionMethod::Zstd)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    for (path, frame_size) in [
+        ("default/large.bin", FRAME_SIZE as u32),
+        ("custom/large.bin", 256 * 1024),
+        ("custom/medium.bin", 256 * 1024),
+    ] {
+        assert_eq!(frame_index(&bytes, &reader, path).frame_size(), frame_size);
+    }
+    assert_eq!(
+        reader.get_entry("default/medium.bin").unwrap().flags & ENTRY_FLAG_FRAMED,
+        0
+    );
+
+    for (path, data) in [
+        ("default/large.bin", &large),
+        ("default/medium.bin", &medium),
+        ("custom/large.bin", &large),
+        ("custom/medium.bin", &medium),
+    ] {
+        assert_eq!(&reader.read_file(path).unwrap(), data, "{}", path);
+        let start = 5 * MB - 3;
+        assert_eq!(
+            reader.read_file_range(path, start as u64, 100).unwrap(),
+            &data[start..start + 100],
+            "{}",
+            path
+        );
+    }
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+}
+
+#[test]
+fn test_invalid_frame_options_are_rejected() {
+    for options in [
+        FrameOptions {
+            frame_size: 1024,
+            min_file_size: MB,
+        },
+        FrameOptions {
+            frame_size: 16 * MB,
+            min_file_size: 16 * MB,
+        },
+        FrameOptions {
+            frame_size: MB,
+            min_file_size: 1024,
+        },
+        FrameOptions {
+            frame_size: MB,
+            min_file_size: MIN_FRAME_COMPRESSION_SIZE + 1,
+        },
+    ] {
+        let temp_file = NamedTempFile::new().unwrap();
+        let result = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_frame_options(options);
+        assert!(
+            matches!(result, Err(EngramError::InvalidOptions(_))),
+            "{:?}",
+            options
+        );
+    }
+
+    // v1.0 frames have no recorded size
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_format_version(1, 0)
+        .unwrap()
+        .with_frame_options(FrameOptions {
+            frame_size: MB,
+            min_file_size: 8 * MB,
+        })
+        .unwrap();
+    assert!(matches!(
+        writer.add_file("a.txt", b"a"),
+        Err(EngramError::InvalidOptions(_))
+    ));
+}
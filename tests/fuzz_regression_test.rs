@@ -0,0 +1,366 @@
+//! Regression corpus for malformed archives found by fuzzing
+//!
+//! Every input here once made `ArchiveReader` panic or abort on allocation.
+//! Malformed archives must fail with an error (normally `InvalidFormat`)
+//! instead. The fuzz targets themselves live under `fuzz/`.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use engram_rs::archive::{EndRecord, LocalEntryHeader, END_RECORD_SIZE};
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, EntryInfo, FileHeader,
+    CD_ENTRY_SIZE, HEADER_SIZE,
+};
+use std::io::Cursor;
+use tempfile::NamedTempFile;
+
+/// Helper: A small valid archive exercising every entry kind
+fn sample_archive() -> Vec<u8> {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_dedup()
+        .with_fixed_time(1_700_000_000);
+    writer
+        .add_file_with_compression("plain.txt", b"stored as is", CompressionMethod::None)
+        .unwrap();
+    writer
+        .add_file_with_compression("lz4.txt", &b"lz4 ".repeat(100), CompressionMethod::Lz4)
+        .unwrap();
+    writer
+        .add_file_with_compression("zstd.txt", &b"zstd ".repeat(100), CompressionMethod::Zstd)
+        .unwrap();
+    writer
+        .add_file_with_compression("copy.txt", &b"zstd ".repeat(100), CompressionMethod::Zstd)
+        .unwrap();
+    writer.add_directory("empty").unwrap();
+    writer.finalize().unwrap();
+    std::fs::read(temp_file.path()).unwrap()
+}
+
+/// Key of the archive-encrypted samples
+const KEY: [u8; 32] = [0x6Bu8; 32];
+
+/// Helper: A small archive-encrypted archive
+fn encrypted_sample_archive() -> Vec<u8> {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_archive_encryption(&KEY);
+    writer.add_file("a.txt", b"first entry").unwrap();
+    writer.add_file("b.txt", &b"second ".repeat(100)).unwrap();
+    writer.finalize().unwrap();
+    std::fs::read(temp_file.path()).unwrap()
+}
+
+/// Helper: Apply `craft` to the decrypted payload of an archive-encrypted
+/// archive and encrypt it again, so the result opens with `KEY`
+///
+/// The payload is what follows the header, and offsets into it count from
+/// the start of the file.
+fn craft_encrypted(bytes: &[u8], craft: impl FnOnce(&mut Vec<u8>)) -> Vec<u8> {
+    let cipher = Aes256Gcm::new(&KEY.into());
+    let nonce_bytes = &bytes[HEADER_SIZE..HEADER_SIZE + 12];
+    #[allow(deprecated)]
+    let nonce = Nonce::from_slice(nonce_bytes);
+    let trailer = bytes.len() - END_RECORD_SIZE;
+    let mut payload = cipher
+        .decrypt(nonce, &bytes[HEADER_SIZE + 12..trailer])
+        .unwrap();
+    craft(&mut payload);
+
+    let mut crafted = bytes[..HEADER_SIZE + 12].to_vec();
+    crafted.extend(cipher.encrypt(nonce, payload.as_ref()).unwrap());
+    crafted.extend_from_slice(&bytes[trailer..]);
+    crafted
+}
+
+/// Helper: Open, initialize and read every entry of `bytes`
+///
+/// Returns the first error; any panic fails the calling test.
+fn read_everything(bytes: Vec<u8>) -> Result<(), EngramError> {
+    read_all(ArchiveReader::from_reader(Cursor::new(bytes))?)
+}
+
+/// Helper: `read_everything` for archive-encrypted archives, with `KEY`
+fn read_everything_encrypted(bytes: Vec<u8>) -> Result<(), EngramError> {
+    read_all(ArchiveReader::from_reader(Cursor::new(bytes))?.with_decryption_key(&KEY))
+}
+
+fn read_all(mut reader: ArchiveReader) -> Result<(), EngramError> {
+    reader.initialize()?;
+    let paths = reader.list_files().to_vec();
+    let mut first_error = None;
+    for path in &paths {
+        for result in [
+            reader.read_file(path).map(drop),
+            reader.read_prefix(path, 16).map(drop),
+            reader.read_file_range(path, 3, 10).map(drop),
+        ] {
+            if let Err(e) = result {
+                first_error.get_or_insert(e);
+            }
+        }
+    }
+    let _ = reader.verify_all()?;
+    first_error.map_or(Ok(()), Err)
+}
+
+/// Helper: Offset of the `index`-th central directory entry
+fn cd_entry_offset(bytes: &[u8], index: usize) -> usize {
+    let header = FileHeader::read_from(bytes).unwrap();
+    header.central_directory_offset as usize + index * CD_ENTRY_SIZE
+}
+
+fn assert_invalid_format(result: Result<(), EngramError>) {
+    assert!(
+        matches!(
+            result.as_ref().map_err(EngramError::root_cause),
+            Err(EngramError::InvalidFormat(_))
+        ),
+        "expected InvalidFormat, got {:?}",
+        result
+    );
+}
+
+#[test]
+fn test_sample_archive_reads_cleanly() {
+    read_everything(sample_archive()).unwrap();
+}
+
+#[test]
+fn test_cd_path_length_past_path_field() {
+    // Used to panic slicing the 256-byte path buffer
+    let mut bytes = sample_archive();
+    let entry = cd_entry_offset(&bytes, 0);
+    bytes[entry + 42..entry + 44].copy_from_slice(&300u16.to_le_bytes());
+    assert_invalid_format(read_everything(bytes));
+}
+
+#[test]
+fn test_huge_entry_count() {
+    // Used to abort preallocating the central directory
+    let mut bytes = sample_archive();
+    bytes[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_invalid_format(read_everything(bytes));
+}
+
+#[test]
+fn test_huge_entry_count_with_matching_directory_size() {
+    // The size agrees with the count, but neither fits in the file
+    let mut bytes = sample_archive();
+    bytes[32..36].copy_from_slice(&u32::MAX.to_le_bytes());
+    let cd_size = u32::MAX as u64 * CD_ENTRY_SIZE as u64;
+    bytes[24..32].copy_from_slice(&cd_size.to_le_bytes());
+    assert_invalid_format(read_everything(bytes.clone()));
+
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+    let lazy = ArchiveReader::open_lazy(temp_file.path()).map(drop);
+    assert_invalid_format(lazy);
+}
+
+#[test]
+fn test_central_directory_offset_past_end() {
+    let mut bytes = sample_archive();
+    let len = bytes.len() as u64;
+    bytes[16..24].copy_from_slice(&(len + 1).to_le_bytes());
+    assert_invalid_format(read_everything(bytes));
+
+    let mut bytes = sample_archive();
+    bytes[16..24].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert_invalid_format(read_everything(bytes));
+}
+
+#[test]
+fn test_compressed_size_past_end() {
+    // Used to abort allocating the stored payload
+    for size in [u64::MAX, 1 << 40] {
+        let mut bytes = sample_archive();
+        let entry = cd_entry_offset(&bytes, 0);
+        bytes[entry + 20..entry + 28].copy_from_slice(&size.to_le_bytes());
+        assert_invalid_format(read_everything(bytes));
+    }
+}
+
+#[test]
+fn test_data_offset_inside_header() {
+    let mut bytes = sample_archive();
+    let entry = cd_entry_offset(&bytes, 0);
+    bytes[entry + 4..entry + 12].copy_from_slice(&3u64.to_le_bytes());
+    assert_invalid_format(read_everything(bytes));
+}
+
+#[test]
+fn test_local_path_length_past_limit() {
+    let mut bytes = sample_archive();
+    let entry = cd_entry_offset(&bytes, 0);
+    let data_offset = u64::from_le_bytes(bytes[entry + 4..entry + 12].try_into().unwrap());
+    let loca = data_offset as usize;
+    bytes[loca + 34..loca + 36].copy_from_slice(&u16::MAX.to_le_bytes());
+    assert_invalid_format(read_everything(bytes));
+}
+
+#[test]
+fn test_lz4_block_size_out_of_proportion() {
+    // The LZ4 block size is allocated before decoding
+    let mut bytes = sample_archive();
+    let entry = cd_entry_offset(&bytes, 1);
+    let info = EntryInfo::read_from(&bytes[entry..entry + CD_ENTRY_SIZE]).unwrap();
+    assert_eq!(info.compression, CompressionMethod::Lz4);
+    let local = LocalEntryHeader::read_from(&bytes[info.data_offset as usize..]).unwrap();
+    let payload = info.data_offset as usize + local.header_size();
+    bytes[payload..payload + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+
+    let mut reader = ArchiveReader::from_reader(Cursor::new(bytes)).unwrap();
+    reader.initialize().unwrap();
+    assert!(matches!(
+        reader.read_file("lz4.txt"),
+        Err(EngramError::DecompressionFailed { .. })
+    ));
+}
+
+#[test]
+fn test_lz4_block_size_disagrees_with_directory() {
+    // A 2GB block size in front of a 400-byte entry
+    let mut bytes = sample_archive();
+    let entry = cd_entry_offset(&bytes, 1);
+    let info = EntryInfo::read_from(&bytes[entry..entry + CD_ENTRY_SIZE]).unwrap();
+    assert_eq!(info.uncompressed_size, 400);
+    let local = LocalEntryHeader::read_from(&bytes[info.data_offset as usize..]).unwrap();
+    let payload = info.data_offset as usize + local.header_size();
+    bytes[payload..payload + 4].copy_from_slice(&(2u32 << 30).to_le_bytes());
+
+    let mut reader = ArchiveReader::from_reader(Cursor::new(bytes)).unwrap();
+    reader.initialize().unwrap();
+    assert!(matches!(
+        reader.read_file("lz4.txt"),
+        Err(EngramError::DecompressionFailed { .. })
+    ));
+}
+
+#[test]
+fn test_zstd_stream_larger_than_entry() {
+    // Directory and LOCA header agree on a size the stream overruns
+    let mut bytes = sample_archive();
+    let entry = cd_entry_offset(&bytes, 2);
+    let info = EntryInfo::read_from(&bytes[entry..entry + CD_ENTRY_SIZE]).unwrap();
+    assert_eq!(info.compression, CompressionMethod::Zstd);
+    let loca = info.data_offset as usize;
+    bytes[entry + 12..entry + 20].copy_from_slice(&100u64.to_le_bytes());
+    bytes[loca + 4..loca + 12].copy_from_slice(&100u64.to_le_bytes());
+
+    let mut reader = ArchiveReader::from_reader(Cursor::new(bytes)).unwrap();
+    reader.initialize().unwrap();
+    assert_eq!(reader.get_entry("zstd.txt").unwrap().uncompressed_size, 100);
+    let result = reader.read_file("zstd.txt");
+    assert!(
+        matches!(&result, Err(EngramError::DecompressionFailed { reason, .. }) if reason.contains("past the entry size")),
+        "{:?}",
+        result
+    );
+}
+
+#[test]
+fn test_truncated_inputs() {
+    let bytes = sample_archive();
+    for len in [
+        0,
+        8,
+        HEADER_SIZE - 1,
+        HEADER_SIZE,
+        HEADER_SIZE + 10,
+        bytes.len() - 1,
+    ] {
+        assert!(read_everything(bytes[..len].to_vec()).is_err(), "{}", len);
+    }
+}
+
+#[test]
+fn test_parsers_reject_short_and_garbage_input() {
+    let garbage = [0xA5u8; CD_ENTRY_SIZE];
+    assert!(FileHeader::read_from(&garbage[..]).is_err());
+    assert!(EntryInfo::read_from(&garbage[..]).is_err());
+    assert!(LocalEntryHeader::read_from(&garbage[..]).is_err());
+    assert!(EndRecord::read_from(&garbage[..]).is_err());
+    for len in 0..8 {
+        assert!(FileHeader::read_from(&garbage[..len]).is_err());
+        assert!(EntryInfo::read_from(&garbage[..len]).is_err());
+        assert!(LocalEntryHeader::read_from(&garbage[..len]).is_err());
+        assert!(EndRecord::read_from(&garbage[..len]).is_err());
+    }
+}
+
+#[test]
+fn test_deterministic_mutations_never_panic() {
+    let original = sample_archive();
+    // xorshift64, fixed seed so failures reproduce
+    let mut state = 0x2545_F491_4F6C_DD1D_u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    for _ in 0..2000 {
+        let mut bytes = original.clone();
+        for _ in 0..1 + next() % 4 {
+            let pos = (next() % bytes.len() as u64) as usize;
+            bytes[pos] = next() as u8;
+        }
+        if next() % 8 == 0 {
+            bytes.truncate((next() % bytes.len() as u64) as usize);
+        }
+        let _ = read_everything(bytes);
+    }
+}
+
+#[test]
+fn test_encrypted_sample_archive_reads_cleanly() {
+    read_everything_encrypted(encrypted_sample_archive()).unwrap();
+    read_everything_encrypted(craft_encrypted(&encrypted_sample_archive(), |_| {})).unwrap();
+}
+
+#[test]
+fn test_encrypted_central_directory_offset_out_of_range() {
+    // Used to underflow (offset inside the header) or slice past the
+    // decrypted payload
+    for offset in [0, 10, HEADER_SIZE as u64 - 1, u64::MAX / 2, u64::MAX] {
+        let mut bytes = encrypted_sample_archive();
+        bytes[16..24].copy_from_slice(&offset.to_le_bytes());
+        assert_invalid_format(read_everything_encrypted(bytes));
+    }
+}
+
+#[test]
+fn test_encrypted_entry_count_past_payload() {
+    let mut bytes = encrypted_sample_archive();
+    bytes[32..36].copy_from_slice(&1000u32.to_le_bytes());
+    let cd_size = 1000u64 * CD_ENTRY_SIZE as u64;
+    bytes[24..32].copy_from_slice(&cd_size.to_le_bytes());
+    assert_invalid_format(read_everything_encrypted(bytes));
+}
+
+#[test]
+fn test_encrypted_data_offset_out_of_range() {
+    let original = encrypted_sample_archive();
+    let cd_offset = FileHeader::read_from(&original[..])
+        .unwrap()
+        .central_directory_offset as usize;
+    // Offsets count from the start of the file, the payload from the header
+    let entry = cd_offset - HEADER_SIZE;
+
+    for data_offset in [0, 3, u64::MAX / 2, u64::MAX] {
+        let bytes = craft_encrypted(&original, |payload| {
+            payload[entry + 4..entry + 12].copy_from_slice(&data_offset.to_le_bytes());
+        });
+        assert_invalid_format(read_everything_encrypted(bytes));
+    }
+
+    // Stored size running past the end of the payload
+    let bytes = craft_encrypted(&original, |payload| {
+        payload[entry + 20..entry + 28].copy_from_slice(&(1u64 << 40).to_le_bytes());
+    });
+    assert_invalid_format(read_everything_encrypted(bytes));
+}
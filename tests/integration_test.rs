@@ -173,17 +173,14 @@ fn test_vfs_database_access() {
         assert_eq!(dbs.len(), 1);
         assert_eq!(dbs[0], "users.db");
 
-        // Open database
-        let conn = vfs.open_database("users.db").unwrap();
-
         // Query database
-        let mut stmt = conn
-            .prepare("SELECT name, email FROM users ORDER BY id")
-            .unwrap();
-        let users: Vec<(String, String)> = stmt
-            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
-            .unwrap()
-            .collect::<Result<Vec<_>, _>>()
+        let users: Vec<(String, String)> = vfs
+            .query(
+                "users.db",
+                "SELECT name, email FROM users ORDER BY id",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
             .unwrap();
 
         assert_eq!(users.len(), 2);
@@ -363,3 +360,133 @@ fn test_archive_encryption() {
         );
     }
 }
+
+#[test]
+fn test_finalize_summary() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let archive_path = temp_file.path();
+
+    let text = "Compressible line of text\n".repeat(1000);
+    let mut writer = ArchiveWriter::create(archive_path).unwrap();
+    assert_eq!(writer.pending_entries(), 0);
+    assert_eq!(writer.bytes_written_so_far(), 64);
+
+    writer.add_file("a.txt", text.as_bytes()).unwrap();
+    writer.add_file("b.bin", b"raw bytes").unwrap();
+    assert_eq!(writer.pending_entries(), 2);
+    let written = writer.bytes_written_so_far();
+    assert!(written > 64);
+
+    let summary = writer.finalize().unwrap();
+    assert_eq!(summary.path.as_deref(), Some(archive_path));
+    assert_eq!(summary.total_entries, 2);
+    assert_eq!(summary.cd_offset, written);
+    assert_eq!(summary.total_uncompressed, text.len() as u64 + 9);
+    assert!(summary.total_compressed < summary.total_uncompressed);
+    assert_eq!(
+        summary.archive_size,
+        std::fs::metadata(archive_path).unwrap().len()
+    );
+    assert_eq!(summary.archive_size, written + 2 * 320 + 64);
+    assert_eq!(summary.encryption, engram_rs::EncryptionMode::None);
+
+    let reader = ArchiveReader::open_and_init(archive_path).unwrap();
+    assert_eq!(reader.entry_count(), summary.total_entries as usize);
+}
+
+#[test]
+fn test_read_app_manifest_as_typed() {
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct AppConfig {
+        services: Vec<String>,
+        retention_days: u32,
+    }
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let archive_path = temp_file.path();
+    let config = AppConfig {
+        services: vec!["database".to_string(), "logs".to_string()],
+        retention_days: 30,
+    };
+
+    let mut writer = ArchiveWriter::create(archive_path).unwrap();
+    writer
+        .add_file("myapp.json", &serde_json::to_vec(&config).unwrap())
+        .unwrap();
+    writer
+        .add_file("other.json", b"{\"unrelated\": true}")
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(archive_path).unwrap();
+    let read: AppConfig = reader.read_app_manifest_as("myapp").unwrap();
+    assert_eq!(read, config);
+
+    // The schemaless variant still works
+    let value = reader.read_app_manifest("myapp").unwrap();
+    assert_eq!(value["retention_days"], 30);
+
+    // Wrong shape is reported against the file
+    let result: engram_rs::Result<AppConfig> = reader.read_app_manifest_as("other");
+    match result {
+        Err(engram_rs::EngramError::InvalidManifest(message)) => {
+            assert!(message.contains("other.json"), "{}", message)
+        }
+        other => panic!("expected InvalidManifest, got {:?}", other),
+    }
+}
+
+#[test]
+fn test_compressed_manifest_read_back_typed() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let archive_path = temp_file.path();
+
+    // A large file inventory, as in archives with many entries
+    let mut manifest = Manifest::new(
+        "inventory".to_string(),
+        "Inventory".to_string(),
+        Author::new("Test Author"),
+        "1.0.0".to_string(),
+    );
+    for i in 0..5000 {
+        let data = format!("file {}", i);
+        manifest.add_file(format!("data/file_{:05}.txt", i), data.as_bytes(), None);
+    }
+    let json_len = serde_json::to_vec_pretty(&manifest).unwrap().len() as u64;
+
+    for method in [CompressionMethod::Zstd, CompressionMethod::Lz4] {
+        let mut writer = ArchiveWriter::create(archive_path).unwrap();
+        writer
+            .add_manifest_with_compression(&serde_json::to_value(&manifest).unwrap(), method)
+            .unwrap();
+        writer.add_file("data/file_00000.txt", b"file 0").unwrap();
+        writer.finalize().unwrap();
+
+        let mut reader = ArchiveReader::open_and_init(archive_path).unwrap();
+        let entry = reader.get_entry("manifest.json").unwrap();
+        assert_eq!(entry.compression, method);
+        assert_eq!(entry.uncompressed_size, json_len);
+        assert!(entry.compressed_size < json_len / 2);
+
+        let parsed: Manifest = reader.read_manifest_as().unwrap().unwrap();
+        assert_eq!(parsed.id, "inventory");
+        assert_eq!(parsed.files.len(), 5000);
+        assert_eq!(parsed.files[4999].path, "data/file_04999.txt");
+        assert_eq!(parsed.files[123].sha256, manifest.files[123].sha256);
+
+        let value = reader.read_manifest().unwrap().unwrap();
+        assert_eq!(value["files"].as_array().unwrap().len(), 5000);
+    }
+}
+
+#[test]
+fn test_manifest_unknown_compression_rejected() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let result =
+        writer.add_manifest_with_compression(&serde_json::json!({}), CompressionMethod::Unknown(9));
+    assert!(matches!(
+        result,
+        Err(engram_rs::EngramError::InvalidCompression(9))
+    ));
+}
@@ -0,0 +1,93 @@
+//! Archive inventory tests
+//!
+//! Covers `ArchiveReader::inventory` and `inventory_json`.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod};
+use serde_json::json;
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_inventory_json_shape() {
+    let log = "line\n".repeat(1000);
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_fixed_time(1_700_000_000);
+    writer
+        .add_file_with_compression("config.toml", b"quality = 1\n", CompressionMethod::None)
+        .unwrap();
+    writer
+        .add_file_with_compression("logs/app.log", log.as_bytes(), CompressionMethod::Zstd)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let json = reader.inventory_json().unwrap();
+
+    let header = reader.header();
+    assert_eq!(
+        json["version"],
+        format!("{}.{}", header.version_major, header.version_minor)
+    );
+    assert_eq!(json["entry_count"], 2);
+    assert_eq!(json["encryption_mode"], "none");
+
+    let entries = json["entries"].as_array().unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(
+        entries[0],
+        json!({
+            "path": "config.toml",
+            "uncompressed_size": 12,
+            "compressed_size": 12,
+            "crc32": format!("{:08x}", crc32fast::hash(b"quality = 1\n")),
+            "compression": "none",
+            "modified_time": 1_700_000_000u64,
+        })
+    );
+
+    let zstd = reader.get_entry("logs/app.log").unwrap();
+    assert_eq!(entries[1]["path"], "logs/app.log");
+    assert_eq!(entries[1]["compression"], "zstd");
+    assert_eq!(entries[1]["uncompressed_size"], log.len());
+    assert_eq!(entries[1]["compressed_size"], zstd.compressed_size);
+
+    // CRCs are 8 lowercase hex digits
+    for entry in entries {
+        let crc = entry["crc32"].as_str().unwrap();
+        assert_eq!(crc.len(), 8, "{}", crc);
+        assert!(
+            crc.chars()
+                .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c)),
+            "{}",
+            crc
+        );
+    }
+    assert_eq!(
+        u32::from_str_radix(entries[1]["crc32"].as_str().unwrap(), 16).unwrap(),
+        zstd.crc32
+    );
+}
+
+#[test]
+fn test_inventory_lazy_and_encrypted() {
+    let key = [0x42u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&key);
+    writer.add_file("secret.txt", b"secret").unwrap();
+    writer.finalize().unwrap();
+
+    // The directory is not encrypted, so no key is needed to list it
+    let reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    let inventory = reader.inventory().unwrap();
+    assert_eq!(inventory.encryption_mode, "per-file");
+    assert_eq!(inventory.entry_count, 1);
+    assert_eq!(inventory.entries[0].path, "secret.txt");
+    assert_eq!(inventory.entries[0].uncompressed_size, 6);
+    assert!(inventory.entries[0].compressed_size > 6);
+
+    let eager = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    assert_eq!(eager.inventory().unwrap(), inventory);
+}
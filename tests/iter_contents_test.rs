@@ -0,0 +1,128 @@
+//! Contents iterator tests
+//!
+//! Covers `ArchiveReader::iter_contents`: every file yielded in central
+//! directory order with the same content as `read_file`, per-entry errors
+//! surfaced or skipped with `ContentsIter::skip_errors`.
+
+use engram_rs::archive::LocalEntryHeader;
+use engram_rs::{ArchiveReader, ArchiveWriter, CancellationToken, EngramError};
+use tempfile::NamedTempFile;
+
+const KEY: [u8; 32] = [0x42u8; 32];
+
+/// Helper: Files of varied size and compressibility
+fn sample_files() -> Vec<(String, Vec<u8>)> {
+    let mut files: Vec<(String, Vec<u8>)> = (0..20)
+        .map(|i| {
+            let data = format!("record {}\n", i).repeat(i * 100 + 1);
+            (format!("part{}/data{:02}.csv", i % 2, i), data.into_bytes())
+        })
+        .collect();
+    files.push(("empty.bin".to_string(), Vec::new()));
+    files
+}
+
+/// Helper: Archive holding `files` and an empty directory, written by `writer`
+fn write_archive(mut writer: ArchiveWriter, files: &[(String, Vec<u8>)]) {
+    writer.add_directory("empty_dir").unwrap();
+    for (path, data) in files {
+        writer.add_file(path, data).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+/// Helper: Flip the last stored byte of `path`'s payload
+fn damage(archive: &std::path::Path, path: &str) {
+    let entry = ArchiveReader::open_and_init(archive)
+        .unwrap()
+        .get_entry(path)
+        .unwrap()
+        .clone();
+    let mut bytes = std::fs::read(archive).unwrap();
+    let offset = entry.data_offset as usize;
+    let header = LocalEntryHeader::read_from(&bytes[offset..]).unwrap();
+    bytes[offset + header.header_size() + entry.compressed_size as usize - 1] ^= 0xFF;
+    std::fs::write(archive, &bytes).unwrap();
+}
+
+#[test]
+fn test_iteration_matches_individual_reads() {
+    let files = sample_files();
+    let temp_file = NamedTempFile::new().unwrap();
+    let writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&KEY)
+        .with_sorted_directory();
+    write_archive(writer, &files);
+
+    for lazy in [false, true] {
+        let mut reader = ArchiveReader::open(temp_file.path())
+            .unwrap()
+            .with_decryption_key(&KEY)
+            .with_lazy_central_directory(lazy);
+        reader.initialize().unwrap();
+
+        let contents = reader.iter_contents();
+        assert_eq!(contents.size_hint(), (0, Some(files.len() + 1)));
+        let contents: Vec<(String, Vec<u8>)> = contents.collect::<engram_rs::Result<_>>().unwrap();
+
+        // Central directory order, here sorted by path, without directories
+        let paths: Vec<&String> = reader
+            .list_files()
+            .iter()
+            .filter(|path| *path != "empty_dir")
+            .collect();
+        assert_eq!(
+            contents.iter().map(|(path, _)| path).collect::<Vec<_>>(),
+            paths
+        );
+        for (path, data) in contents {
+            assert_eq!(data, reader.read_file(&path).unwrap(), "{}", path);
+        }
+    }
+}
+
+#[test]
+fn test_errors_surfaced_or_skipped_per_entry() {
+    let files = sample_files();
+    let temp_file = NamedTempFile::new().unwrap();
+    write_archive(ArchiveWriter::create(temp_file.path()).unwrap(), &files);
+    let damaged = "part1/data07.csv";
+    damage(temp_file.path(), damaged);
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let results: Vec<_> = reader.iter_contents().collect();
+    assert_eq!(results.len(), files.len());
+    let failed: Vec<&EngramError> = results
+        .iter()
+        .filter_map(|result| result.as_ref().err())
+        .collect();
+    assert_eq!(failed.len(), 1, "{:?}", failed);
+    assert!(failed[0].to_string().contains(damaged));
+
+    let read: Vec<String> = reader
+        .iter_contents()
+        .skip_errors(true)
+        .map(|item| item.unwrap().0)
+        .collect();
+    assert_eq!(read.len(), files.len() - 1);
+    assert!(!read.iter().any(|path| path == damaged));
+}
+
+#[test]
+fn test_iteration_stops_when_cancelled() {
+    let files = sample_files();
+    let temp_file = NamedTempFile::new().unwrap();
+    write_archive(ArchiveWriter::create(temp_file.path()).unwrap(), &files);
+
+    let token = CancellationToken::new();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path())
+        .unwrap()
+        .with_cancellation(token.clone());
+    let mut contents = reader.iter_contents().skip_errors(true);
+    assert!(contents.next().unwrap().is_ok());
+    token.cancel();
+    assert!(matches!(contents.next(), Some(Err(EngramError::Cancelled))));
+    assert!(contents.next().is_none());
+    assert_eq!(contents.size_hint(), (0, Some(0)));
+}
@@ -0,0 +1,232 @@
+//! Lazy central directory tests
+//!
+//! Covers `ArchiveReader::open_lazy`, `ArchiveReader::iter_files` and
+//! `ArchiveWriter::with_sorted_directory`.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod, EngramError};
+use std::io::Cursor;
+use std::time::Instant;
+use tempfile::NamedTempFile;
+
+const FILE_COUNT: usize = 500;
+const LARGE_FILE_COUNT: usize = 50_000;
+
+/// Helper: Deterministic content for file `i`
+fn content(i: usize) -> Vec<u8> {
+    format!("entry {} ", i).repeat(10 + i % 7).into_bytes()
+}
+
+/// Helper: Archive with `count` files added in reverse path order
+fn create_archive(count: usize, sorted: bool) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    if sorted {
+        writer = writer.with_sorted_directory();
+    }
+    for i in (0..count).rev() {
+        writer
+            .add_file_with_compression(
+                &format!("data/{:06}.txt", i),
+                &content(i),
+                CompressionMethod::None,
+            )
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Overwrite the central directory entry at `index` with zeros
+fn wipe_directory_entry(path: &std::path::Path, index: usize) {
+    let reader = ArchiveReader::open(path).unwrap();
+    let start = reader.header().central_directory_offset as usize + index * 320;
+    let mut bytes = std::fs::read(path).unwrap();
+    bytes[start..start + 320].fill(0);
+    std::fs::write(path, &bytes).unwrap();
+}
+
+#[test]
+fn test_lazy_reads_sorted_archive() {
+    let temp_file = create_archive(FILE_COUNT, true);
+    let mut reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), FILE_COUNT);
+
+    for i in (0..FILE_COUNT).step_by(7) {
+        let path = format!("data/{:06}.txt", i);
+        assert_eq!(reader.read_file(&path).unwrap(), content(i));
+        assert_eq!(reader.get_entry(&path).unwrap().path, path);
+    }
+    assert!(reader.contains("/data\\000001.txt"));
+    assert!(!reader.contains("data/999999.txt"));
+    assert!(matches!(
+        reader.read_file("missing.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+
+    // Listing parses the whole directory
+    assert_eq!(reader.list_files().len(), FILE_COUNT);
+    assert_eq!(reader.list_prefix("data/00000").len(), 10);
+}
+
+#[test]
+fn test_lazy_only_parses_probed_entries() {
+    let temp_file = create_archive(FILE_COUNT, true);
+    // Entry 1 is never probed when searching for the middle entry
+    wipe_directory_entry(temp_file.path(), 1);
+    assert!(ArchiveReader::open_and_init(temp_file.path()).is_err());
+
+    let mut reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    let middle = FILE_COUNT / 2;
+    let data = reader
+        .read_file(&format!("data/{:06}.txt", middle))
+        .unwrap();
+    assert_eq!(data, content(middle));
+
+    // A lookup that reaches the damaged entry reports it
+    assert!(reader.read_file("data/000001.txt").is_err());
+    assert!(reader.get_entry("data/000001.txt").is_none());
+}
+
+#[test]
+fn test_lazy_unsorted_only_parses_matching_entries() {
+    let temp_file = create_archive(FILE_COUNT, false);
+    // Entries are written in reverse, so entry 1 holds the second-to-last path
+    wipe_directory_entry(temp_file.path(), 1);
+    assert!(ArchiveReader::open_and_init(temp_file.path()).is_err());
+
+    let mut reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    for i in (0..FILE_COUNT - 2).step_by(11) {
+        let path = format!("data/{:06}.txt", i);
+        assert_eq!(reader.read_file(&path).unwrap(), content(i));
+    }
+    assert!(reader.contains("\\data\\000000.txt"));
+    assert!(!reader.contains(&format!("data/{:06}.txt", FILE_COUNT - 2)));
+    assert!(matches!(
+        reader.read_file("missing.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_iter_files_streams_paths() {
+    let temp_file = create_archive(FILE_COUNT, false);
+    let eager = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let lazy = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+
+    let streamed: Vec<String> = lazy.iter_files().unwrap().map(Result::unwrap).collect();
+    assert_eq!(streamed, eager.list_files());
+    let listed: Vec<String> = eager.iter_files().unwrap().map(Result::unwrap).collect();
+    assert_eq!(listed, eager.list_files());
+
+    // A damaged entry is reported in place; the rest still stream
+    wipe_directory_entry(temp_file.path(), 3);
+    let lazy = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    let results: Vec<_> = lazy.iter_files().unwrap().collect();
+    assert_eq!(results.len(), FILE_COUNT);
+    assert!(matches!(
+        results[3],
+        Err(EngramError::DirectoryEntryError { index: 3, .. })
+    ));
+    assert_eq!(results[4].as_ref().unwrap(), &streamed[4]);
+}
+
+#[test]
+fn test_lazy_unsorted_and_case_insensitive() {
+    let temp_file = create_archive(FILE_COUNT, false);
+    let mut reader = ArchiveReader::open_lazy(temp_file.path())
+        .unwrap()
+        .with_case_insensitive_lookup(true);
+    assert_eq!(reader.read_file("DATA/000042.TXT").unwrap(), content(42));
+    assert_eq!(reader.read_file("data/000043.txt").unwrap(), content(43));
+
+    let sorted = create_archive(FILE_COUNT, true);
+    let mut reader = ArchiveReader::open_lazy(sorted.path())
+        .unwrap()
+        .with_case_insensitive_lookup(true);
+    assert_eq!(reader.read_file("Data/000044.txt").unwrap(), content(44));
+}
+
+#[test]
+fn test_lazy_clone_and_extract() {
+    let temp_file = create_archive(FILE_COUNT, true);
+    let reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    assert_eq!(reader.read_file_at("data/000010.txt").unwrap(), content(10));
+
+    let mut clone = reader.try_clone().unwrap();
+    assert_eq!(clone.read_file("data/000011.txt").unwrap(), content(11));
+
+    let out_dir = tempfile::TempDir::new().unwrap();
+    assert_eq!(clone.extract_all(out_dir.path()).unwrap(), FILE_COUNT);
+    assert_eq!(
+        std::fs::read(out_dir.path().join("data/000012.txt")).unwrap(),
+        content(12)
+    );
+    assert!(clone.verify_all().unwrap().is_ok());
+}
+
+#[test]
+fn test_lazy_archive_encrypted() {
+    let key = [0x24u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_archive_encryption(&key)
+            .with_sorted_directory();
+        for i in 0..FILE_COUNT {
+            writer
+                .add_file(&format!("data/{:06}.txt", i), &content(i))
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open(temp_file.path())
+        .unwrap()
+        .with_decryption_key(&key)
+        .with_lazy_central_directory(true);
+    reader.initialize().unwrap();
+    assert_eq!(reader.read_file("data/000123.txt").unwrap(), content(123));
+    assert_eq!(reader.list_files().len(), FILE_COUNT);
+}
+
+#[test]
+fn test_lazy_from_reader_parses_eagerly() {
+    let temp_file = create_archive(FILE_COUNT, true);
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+
+    // No file to read positioned entries from, so the directory is parsed up front
+    let mut reader = ArchiveReader::from_reader(Cursor::new(bytes))
+        .unwrap()
+        .with_lazy_central_directory(true);
+    reader.initialize().unwrap();
+    assert_eq!(reader.read_file("data/000007.txt").unwrap(), content(7));
+}
+
+#[test]
+fn test_lazy_first_read_latency() {
+    let temp_file = create_archive(LARGE_FILE_COUNT, true);
+    let target = LARGE_FILE_COUNT / 3;
+    let path = format!("data/{:06}.txt", target);
+
+    let start = Instant::now();
+    let mut eager = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(eager.read_file(&path).unwrap(), content(target));
+    let eager_time = start.elapsed();
+
+    let start = Instant::now();
+    let mut lazy = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    assert_eq!(lazy.read_file(&path).unwrap(), content(target));
+    let lazy_time = start.elapsed();
+
+    println!(
+        "First read over {} entries: eager {:?}, lazy {:?}",
+        LARGE_FILE_COUNT, eager_time, lazy_time
+    );
+    assert!(
+        lazy_time < eager_time,
+        "Lazy open took {:?}, eager {:?}",
+        lazy_time,
+        eager_time
+    );
+}
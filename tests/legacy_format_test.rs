@@ -0,0 +1,413 @@
+//! Legacy v0.3/v0.4 archive compatibility tests
+//!
+//! v0.x archives have no LOCA headers and no End Record: central directory
+//! offsets point straight at the stored payload and the archive ends with
+//! the central directory. The fixtures are built here the way the v0.x
+//! writer (the `engram-core` crate) laid them out, and
+//! `with_format_version(0, 4)` must reproduce them.
+
+use aes_gcm::aead::{Aead, KeyInit};
+use aes_gcm::{Aes256Gcm, Nonce};
+use engram_rs::archive::{
+    CompressionMethod, EncryptionMode, FileHeader, LocalEntryHeader, HEADER_SIZE,
+};
+use engram_rs::{ArchiveReader, ArchiveWriter, EngramError, EntryInfo};
+use std::io::{Read, Write};
+use tempfile::NamedTempFile;
+
+const KEY: [u8; 32] = [0x33u8; 32];
+
+/// Helper: Encrypt as `[nonce][ciphertext||tag]`, as v0.4 did
+fn encrypt(data: &[u8]) -> Vec<u8> {
+    let nonce_bytes = [0x07u8; 12];
+    #[allow(deprecated)]
+    let nonce = Nonce::from_slice(&nonce_bytes);
+    let mut out = nonce_bytes.to_vec();
+    out.extend(Aes256Gcm::new(&KEY.into()).encrypt(nonce, data).unwrap());
+    out
+}
+
+/// Helper: Raw Deflate stream, as engram-core wrote for method 3
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut encoder =
+        flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data).unwrap();
+    encoder.finish().unwrap()
+}
+
+/// Helper: Bytes of a v0.`minor` archive holding `files`
+///
+/// Layout: `[header][payload]...[central directory]`, with the payload after
+/// the header encrypted as a whole in archive mode.
+fn legacy_archive(
+    minor: u16,
+    mode: EncryptionMode,
+    files: &[(&str, &[u8], CompressionMethod)],
+) -> Vec<u8> {
+    let mut body = Vec::new();
+    let mut entries = Vec::new();
+    for &(path, data, compression) in files {
+        let compressed = match compression {
+            CompressionMethod::None => data.to_vec(),
+            CompressionMethod::Lz4 => lz4_flex::compress_prepend_size(data),
+            CompressionMethod::Zstd => zstd::encode_all(data, 3).unwrap(),
+            CompressionMethod::Deflate => deflate(data),
+            CompressionMethod::Unknown(_) => unreachable!(),
+        };
+        let stored = match mode {
+            EncryptionMode::PerFile => encrypt(&compressed),
+            _ => compressed,
+        };
+        let mut entry = EntryInfo::default();
+        entry.path = path.to_string();
+        entry.data_offset = (HEADER_SIZE + body.len()) as u64;
+        entry.uncompressed_size = data.len() as u64;
+        entry.compressed_size = stored.len() as u64;
+        entry.crc32 = crc32fast::hash(data);
+        entry.modified_time = 1_700_000_000;
+        entry.compression = compression;
+        entries.push(entry);
+        body.extend(stored);
+    }
+
+    let cd_offset = HEADER_SIZE + body.len();
+    for entry in &entries {
+        entry.write_to(&mut body).unwrap();
+    }
+
+    let mut header = FileHeader::new();
+    header.version_major = 0;
+    header.version_minor = minor;
+    header.central_directory_offset = cd_offset as u64;
+    header.central_directory_size = (HEADER_SIZE + body.len() - cd_offset) as u64;
+    header.entry_count = entries.len() as u32;
+    header.set_encryption_mode(mode);
+    header.header_crc = header.compute_crc();
+
+    let mut bytes = Vec::new();
+    header.write_to(&mut bytes).unwrap();
+    match mode {
+        EncryptionMode::Archive => bytes.extend(encrypt(&body)),
+        _ => bytes.extend(body),
+    }
+    bytes
+}
+
+/// Helper: Write archive bytes to a temporary file
+fn write_temp(bytes: &[u8]) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), bytes).unwrap();
+    temp_file
+}
+
+/// Helper: One entry per compression method
+fn sample_files() -> Vec<(&'static str, &'static [u8], CompressionMethod)> {
+    vec![
+        (
+            "readme.txt",
+            b"legacy archive".as_slice(),
+            CompressionMethod::None,
+        ),
+        (
+            "data/log.txt",
+            b"line\nline\nline\nline\nline\nline\n".as_slice(),
+            CompressionMethod::Zstd,
+        ),
+        (
+            "data/blob.bin",
+            [0xABu8; 512].as_slice(),
+            CompressionMethod::Lz4,
+        ),
+    ]
+}
+
+#[test]
+fn test_v03_archive_round_trip() {
+    let temp_file = write_temp(&legacy_archive(3, EncryptionMode::None, &sample_files()));
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(reader.header().is_legacy());
+    assert_eq!(reader.entry_count(), 3);
+    for (path, data, _) in sample_files() {
+        assert_eq!(reader.read_file(path).unwrap(), data, "{}", path);
+        assert_eq!(reader.read_file_at(path).unwrap(), data, "{}", path);
+    }
+    assert_eq!(reader.read_prefix("data/log.txt", 4).unwrap(), b"line");
+
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(report.ok.len(), 3);
+}
+
+#[test]
+fn test_v04_archive_round_trip() {
+    let temp_file = write_temp(&legacy_archive(4, EncryptionMode::None, &sample_files()));
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    for (path, data, _) in sample_files() {
+        assert_eq!(reader.read_file(path).unwrap(), data, "{}", path);
+    }
+}
+
+#[test]
+fn test_v04_per_file_encrypted_round_trip() {
+    let temp_file = write_temp(&legacy_archive(4, EncryptionMode::PerFile, &sample_files()));
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &KEY).unwrap();
+    for (path, data, _) in sample_files() {
+        assert_eq!(reader.read_file(path).unwrap(), data, "{}", path);
+    }
+}
+
+#[test]
+fn test_v04_archive_encrypted_round_trip() {
+    let temp_file = write_temp(&legacy_archive(4, EncryptionMode::Archive, &sample_files()));
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &KEY).unwrap();
+    for (path, data, _) in sample_files() {
+        assert_eq!(reader.read_file(path).unwrap(), data, "{}", path);
+    }
+}
+
+#[test]
+fn test_engram_core_deflate_entries() {
+    let text = "engram-core wrote Deflate entries\n".repeat(300);
+    let mut files = sample_files();
+    files.push(("notes.txt", text.as_bytes(), CompressionMethod::Deflate));
+    let bytes = legacy_archive(3, EncryptionMode::None, &files);
+    // engram-core's header has no flags or label: bytes 40..64 are reserved
+    assert!(bytes[40..HEADER_SIZE].iter().all(|&b| b == 0));
+    let temp_file = write_temp(&bytes);
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let entry = reader.get_entry("notes.txt").unwrap();
+    assert_eq!(entry.compression, CompressionMethod::Deflate);
+    assert!(entry.compressed_size < text.len() as u64);
+    assert_eq!(reader.read_file("notes.txt").unwrap(), text.as_bytes());
+    assert_eq!(reader.read_file_at("notes.txt").unwrap(), text.as_bytes());
+    assert_eq!(
+        reader.read_prefix("notes.txt", 12).unwrap(),
+        b"engram-core "
+    );
+    assert_eq!(
+        reader.inventory().unwrap().entries[3].compression,
+        "deflate"
+    );
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(report.ok.len(), 4);
+
+    // A stream that inflates past the entry's size fails the CRC check
+    let mut files = sample_files();
+    let oversized = text.repeat(2);
+    files.push((
+        "notes.txt",
+        oversized.as_bytes(),
+        CompressionMethod::Deflate,
+    ));
+    let mut bytes = legacy_archive(3, EncryptionMode::None, &files);
+    let cd_offset = u64::from_le_bytes(bytes[16..24].try_into().unwrap()) as usize;
+    let size_field = cd_offset + 3 * 320 + 12;
+    bytes[size_field..size_field + 8].copy_from_slice(&(text.len() as u64).to_le_bytes());
+    let temp_file = write_temp(&bytes);
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(reader.read_file("notes.txt").is_err());
+}
+
+#[test]
+fn test_deflate_roundtrip() {
+    let text = "deflated by engram-rs\n".repeat(500);
+    let manifest = serde_json::json!({ "name": "deflated", "files": ["notes.txt"] });
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    assert_eq!(
+        writer
+            .add_file_with_compression("notes.txt", text.as_bytes(), CompressionMethod::Deflate)
+            .unwrap(),
+        CompressionMethod::Deflate
+    );
+    // Falls back to None like the other methods when it does not help
+    assert_eq!(
+        writer
+            .add_file_with_compression("tiny.txt", b"x", CompressionMethod::Deflate)
+            .unwrap(),
+        CompressionMethod::None
+    );
+    // Never chosen automatically
+    assert_eq!(
+        writer.add_file("auto.txt", text.as_bytes()).unwrap(),
+        CompressionMethod::Zstd
+    );
+    writer
+        .add_manifest_with_compression(&manifest, CompressionMethod::Deflate)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let entry = reader.get_entry("notes.txt").unwrap();
+    assert_eq!(entry.compression, CompressionMethod::Deflate);
+    assert!(entry.compressed_size < text.len() as u64 / 10);
+    assert_eq!(reader.read_file("notes.txt").unwrap(), text.as_bytes());
+    assert_eq!(reader.read_file_at("notes.txt").unwrap(), text.as_bytes());
+    assert_eq!(reader.read_prefix("notes.txt", 8).unwrap(), b"deflated");
+    assert_eq!(reader.read_manifest().unwrap(), Some(manifest));
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+
+    // The payload is a plain raw Deflate stream
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    let entry = reader.get_entry("notes.txt").unwrap();
+    let offset = entry.data_offset as usize;
+    let start = offset
+        + LocalEntryHeader::read_from(&bytes[offset..])
+            .unwrap()
+            .header_size();
+    let mut inflated = Vec::new();
+    flate2::read::DeflateDecoder::new(&bytes[start..start + entry.compressed_size as usize])
+        .read_to_end(&mut inflated)
+        .unwrap();
+    assert_eq!(inflated, text.as_bytes());
+
+    // Unknown methods are still refused
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    assert!(matches!(
+        writer.add_file_with_compression("a.txt", b"data", CompressionMethod::Unknown(9)),
+        Err(EngramError::InvalidCompression(9))
+    ));
+}
+
+#[test]
+fn test_legacy_crc_still_checked() {
+    let mut bytes = legacy_archive(4, EncryptionMode::None, &sample_files());
+    // First byte of the uncompressed readme payload
+    bytes[HEADER_SIZE] ^= 0xFF;
+    let temp_file = write_temp(&bytes);
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(matches!(
+        reader.read_file("readme.txt"),
+        Err(EngramError::CrcMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_only_newer_major_is_unsupported() {
+    let mut bytes = legacy_archive(4, EncryptionMode::None, &sample_files());
+    bytes[8..10].copy_from_slice(&2u16.to_le_bytes());
+    let temp_file = write_temp(&bytes);
+
+    assert!(matches!(
+        ArchiveReader::open(temp_file.path()),
+        Err(EngramError::UnsupportedVersion(_))
+    ));
+}
+
+#[test]
+fn test_pinned_v04_writer_matches_legacy_layout() {
+    let files = [
+        (
+            "readme.txt",
+            b"legacy archive".as_slice(),
+            CompressionMethod::None,
+        ),
+        (
+            "data/a.bin",
+            [0x5Au8; 100].as_slice(),
+            CompressionMethod::None,
+        ),
+        (
+            "data/deflated.bin",
+            [0x5Au8; 4096].as_slice(),
+            CompressionMethod::Deflate,
+        ),
+    ];
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_format_version(0, 4)
+        .unwrap()
+        .with_fixed_time(1_700_000_000);
+    for (path, data, compression) in files {
+        writer
+            .add_file_with_compression(path, data, compression)
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // Byte for byte what the v0.x writer produced: no LOCA headers, no ENDR
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    assert_eq!(bytes, legacy_archive(4, EncryptionMode::None, &files));
+}
+
+#[test]
+fn test_pinned_v04_writer_round_trip() {
+    let data = "compressible legacy text\n".repeat(400);
+    for key in [None, Some(KEY)] {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_format_version(0, 4)
+            .unwrap()
+            .with_dedup();
+        if let Some(key) = &key {
+            writer = writer.with_archive_encryption(key);
+        }
+        writer.add_file("notes.txt", data.as_bytes()).unwrap();
+        writer.add_file("copy.txt", data.as_bytes()).unwrap();
+        writer.add_file("blob.bin", &[1u8; 8192]).unwrap();
+        writer.finalize().unwrap();
+
+        let mut reader = match &key {
+            Some(key) => ArchiveReader::open_encrypted(temp_file.path(), key).unwrap(),
+            None => ArchiveReader::open_and_init(temp_file.path()).unwrap(),
+        };
+        assert_eq!(
+            (reader.header().version_major, reader.header().version_minor),
+            (0, 4)
+        );
+        assert_eq!(reader.read_file("notes.txt").unwrap(), data.as_bytes());
+        assert_eq!(reader.read_file("copy.txt").unwrap(), data.as_bytes());
+        assert_eq!(reader.read_file("blob.bin").unwrap(), vec![1u8; 8192]);
+    }
+}
+
+#[test]
+fn test_pinned_v04_rejects_newer_features() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_format_version(0, 4)
+        .unwrap()
+        .with_per_file_encryption(&KEY);
+    match writer.add_file("secret.txt", b"secret") {
+        Err(EngramError::InvalidOptions(msg)) => {
+            assert!(msg.contains("Per-file encryption"), "{}", msg)
+        }
+        other => panic!("Expected InvalidOptions, got: {:?}", other),
+    }
+
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_format_version(0, 4)
+        .unwrap();
+    assert!(matches!(
+        writer.add_recipient_key("alice", &KEY),
+        Err(EngramError::InvalidOptions(_))
+    ));
+}
+
+#[test]
+fn test_unknown_format_version_rejected() {
+    let temp_file = NamedTempFile::new().unwrap();
+    for (major, minor) in [(0, 3), (1, 7), (2, 0)] {
+        let result = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_format_version(major, minor);
+        assert!(
+            matches!(result, Err(EngramError::UnsupportedVersion(_))),
+            "v{}.{} should be rejected",
+            major,
+            minor
+        );
+    }
+}
@@ -0,0 +1,262 @@
+//! MIME type tests
+//!
+//! Covers `FileMetadata::mime_type`, inference in `add_file_from_disk`,
+//! `EntryInfo::mime_type` and `ArchiveReader::mime_type`.
+
+use engram_rs::{
+    mime_type_for_path, ArchiveReader, ArchiveWriter, EngramError, FileMetadata,
+    BUILTIN_MIME_TYPES, CD_ENTRY_SIZE, MIME_TABLE_PATH,
+};
+use tempfile::{NamedTempFile, TempDir};
+
+/// Helper: Metadata recording `mime`
+fn with_mime(mime: &str) -> FileMetadata<'_> {
+    FileMetadata {
+        mime_type: Some(mime),
+        ..FileMetadata::default()
+    }
+}
+
+/// Helper: Mode field of the `index`th central directory entry, as stored
+fn stored_mode(bytes: &[u8], index: usize) -> u32 {
+    let cd_offset = u64::from_le_bytes(bytes[16..24].try_into().unwrap()) as usize;
+    let field = cd_offset + index * CD_ENTRY_SIZE + 300;
+    u32::from_le_bytes(bytes[field..field + 4].try_into().unwrap())
+}
+
+#[test]
+fn test_builtin_mime_types_roundtrip() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer
+        .add_file_with_metadata("logo.png", b"\x89PNG", with_mime("image/png"))
+        .unwrap();
+    writer
+        .add_file_with_metadata("data", b"{}", with_mime("Application/JSON"))
+        .unwrap();
+    writer
+        .add_file_with_metadata("page.html", b"<p>", with_mime("text/html"))
+        .unwrap();
+    writer.add_file("plain.txt", b"no type recorded").unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let png = reader.get_entry("logo.png").unwrap();
+    assert_eq!(png.mime_type(), Some("image/png"));
+    // Built-in types are stored as ids, so their case is normalized
+    assert_eq!(
+        reader.get_entry("data").unwrap().mime_type(),
+        Some("application/json")
+    );
+    assert_eq!(
+        reader.mime_type("page.html").unwrap().as_deref(),
+        Some("text/html")
+    );
+    assert_eq!(reader.get_entry("plain.txt").unwrap().mime_type(), None);
+    assert_eq!(reader.mime_type("plain.txt").unwrap(), None);
+    assert!(matches!(
+        reader.mime_type("missing.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+
+    // No table is needed for built-in types
+    assert!(!reader.contains(MIME_TABLE_PATH));
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+}
+
+#[test]
+fn test_custom_mime_types_use_the_table() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer
+        .add_file_with_metadata("a.acme", b"a", with_mime("application/vnd.acme+json"))
+        .unwrap();
+    writer
+        .add_file_with_metadata("b.txt", b"b", with_mime("text/plain; charset=latin1"))
+        .unwrap();
+    writer
+        .add_file_with_metadata("c.acme", b"c", with_mime("application/vnd.acme+json"))
+        .unwrap();
+    writer
+        .add_file_with_metadata("d.png", b"d", with_mime("image/png"))
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(
+        reader.read_file(MIME_TABLE_PATH).unwrap(),
+        b"application/vnd.acme+json\ntext/plain; charset=latin1"
+    );
+
+    // Entries agree on the id of a repeated type; only built-ins resolve on EntryInfo
+    let a = reader.get_entry("a.acme").unwrap().clone();
+    assert_eq!(a.mime_id, reader.get_entry("c.acme").unwrap().mime_id);
+    assert_eq!(a.mime_type(), None);
+
+    for (path, mime) in [
+        ("a.acme", "application/vnd.acme+json"),
+        ("b.txt", "text/plain; charset=latin1"),
+        ("c.acme", "application/vnd.acme+json"),
+        ("d.png", "image/png"),
+    ] {
+        assert_eq!(reader.mime_type(path).unwrap().as_deref(), Some(mime));
+    }
+
+    let mut lazy = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    assert_eq!(
+        lazy.mime_type("b.txt").unwrap().as_deref(),
+        Some("text/plain; charset=latin1")
+    );
+}
+
+#[test]
+fn test_add_file_from_disk_infers_mime_type() {
+    let source_dir = TempDir::new().unwrap();
+    let files = [
+        "index.HTML",
+        "style.css",
+        "photo.jpeg",
+        "Makefile",
+        "blob.xyz",
+    ];
+    for name in files {
+        std::fs::write(source_dir.path().join(name), name.as_bytes()).unwrap();
+    }
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    for name in files {
+        writer
+            .add_file_from_disk(name, &source_dir.path().join(name))
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    for name in files {
+        assert_eq!(
+            reader.mime_type(name).unwrap().as_deref(),
+            mime_type_for_path(name),
+            "{}",
+            name
+        );
+    }
+    assert_eq!(
+        reader.mime_type("index.HTML").unwrap().unwrap(),
+        "text/html"
+    );
+    assert_eq!(reader.mime_type("Makefile").unwrap(), None);
+}
+
+#[test]
+fn test_archives_without_mime_types() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("readme.txt", b"hello").unwrap();
+    writer
+        .add_file_with_metadata("tagged.txt", b"tagged", with_mime("text/plain"))
+        .unwrap();
+    writer.finalize().unwrap();
+
+    // Clearing the upper half of the mode field gives what a writer that
+    // predates MIME types would have written
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    assert_eq!(stored_mode(&bytes, 0), 0);
+    let tagged = stored_mode(&bytes, 1);
+    assert_eq!(tagged >> 16, 2, "text/plain is built-in id 2");
+    let cd_offset = u64::from_le_bytes(bytes[16..24].try_into().unwrap()) as usize;
+    let field = cd_offset + CD_ENTRY_SIZE + 302;
+    bytes[field..field + 2].fill(0);
+    let old = NamedTempFile::new().unwrap();
+    std::fs::write(old.path(), &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(old.path()).unwrap();
+    assert_eq!(reader.mime_type("readme.txt").unwrap(), None);
+    assert_eq!(reader.get_entry("tagged.txt").unwrap().mime_type(), None);
+    assert_eq!(reader.read_file("readme.txt").unwrap(), b"hello");
+
+    // v0.x archives cannot record MIME types
+    let legacy = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(legacy.path())
+        .unwrap()
+        .with_format_version(0, 4)
+        .unwrap();
+    writer
+        .add_file_with_metadata("tagged.txt", b"tagged", with_mime("application/x-custom"))
+        .unwrap();
+    writer.finalize().unwrap();
+    let mut reader = ArchiveReader::open_and_init(legacy.path()).unwrap();
+    assert_eq!(reader.mime_type("tagged.txt").unwrap(), None);
+    assert!(!reader.contains(MIME_TABLE_PATH));
+}
+
+#[test]
+fn test_mode_stays_meaningful_for_older_readers() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer
+        .add_file_with_metadata("page.html", b"<p>", with_mime("text/html"))
+        .unwrap();
+    let script = FileMetadata {
+        executable: true,
+        mime_type: Some("text/x-shellscript"),
+        ..FileMetadata::default()
+    };
+    writer
+        .add_file_with_metadata("run.sh", b"#!/bin/sh\n", script)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    // Readers that take the whole field as the mode still see sane permissions
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    assert_eq!(stored_mode(&bytes, 0) & 0o7777, 0o644);
+    assert_eq!(stored_mode(&bytes, 1) & 0o7777, 0o755);
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let page = reader.get_entry("page.html").unwrap();
+    assert_eq!(page.mode, 0o100644);
+    assert!(!page.is_executable());
+    let run = reader.get_entry("run.sh").unwrap();
+    assert_eq!(run.mode, 0o100755);
+    assert!(run.is_executable());
+}
+
+#[test]
+fn test_invalid_mime_types_are_rejected() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let too_long = format!("application/{}", "x".repeat(300));
+    for invalid in ["", "plain", "text/plain\nimage/png", too_long.as_str()] {
+        assert!(
+            matches!(
+                writer.add_file_with_metadata("file.bin", b"data", with_mime(invalid)),
+                Err(EngramError::InvalidOptions(_))
+            ),
+            "{:?}",
+            invalid
+        );
+    }
+    assert_eq!(writer.pending_entries(), 0);
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_every_builtin_type_roundtrips() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    for (index, mime) in BUILTIN_MIME_TYPES.iter().enumerate() {
+        writer
+            .add_file_with_metadata(&format!("{}.bin", index), b"x", with_mime(mime))
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(!reader.contains(MIME_TABLE_PATH));
+    for (index, mime) in BUILTIN_MIME_TYPES.iter().enumerate() {
+        let entry = reader.get_entry(&format!("{}.bin", index)).unwrap();
+        assert_eq!(entry.mime_type(), Some(*mime));
+        assert_eq!(entry.mime_id as usize, index + 1);
+    }
+}
@@ -0,0 +1,165 @@
+//! Path lookup tests
+//!
+//! Every public accessor matches paths the same way: backslashes and leading
+//! slashes are accepted, and case is ignored only when enabled.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, EngramError, SharedArchive};
+use tempfile::NamedTempFile;
+
+/// Helper: Archive with nested files
+fn create_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("Docs/Guide.md", b"guide").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_mixed_separators_every_accessor() {
+    let temp_file = create_archive();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    for path in [
+        "Docs/Guide.md",
+        "Docs\\Guide.md",
+        "/Docs/Guide.md",
+        "\\Docs\\Guide.md",
+    ] {
+        assert!(reader.contains(path), "contains({:?})", path);
+        assert_eq!(reader.get_entry(path).unwrap().path, "Docs/Guide.md");
+        assert_eq!(reader.read_file(path).unwrap(), b"guide");
+        assert_eq!(reader.read_file_at(path).unwrap(), b"guide");
+    }
+}
+
+#[test]
+fn test_backslash_variant_agrees_across_readers() {
+    let key = [0x24u8; 32];
+    let plain = create_archive();
+    let encrypted = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(encrypted.path())
+        .unwrap()
+        .with_archive_encryption(&key);
+    writer.add_file("Docs/Guide.md", b"guide").unwrap();
+    writer.finalize().unwrap();
+
+    let readers = [
+        ArchiveReader::open_and_init(plain.path()).unwrap(),
+        ArchiveReader::open_lazy(plain.path()).unwrap(),
+        ArchiveReader::open_encrypted(encrypted.path(), &key).unwrap(),
+    ];
+    for mut reader in readers {
+        assert!(reader.contains("Docs\\Guide.md"));
+        assert_eq!(
+            reader.get_entry("Docs\\Guide.md").unwrap().path,
+            "Docs/Guide.md"
+        );
+        assert_eq!(reader.read_file("Docs\\Guide.md").unwrap(), b"guide");
+    }
+
+    let shared = SharedArchive::open(plain.path()).unwrap();
+    assert!(shared.contains("Docs\\Guide.md"));
+    assert_eq!(
+        shared.get_entry("Docs\\Guide.md").unwrap().path,
+        "Docs/Guide.md"
+    );
+    assert_eq!(shared.read_file("Docs\\Guide.md").unwrap(), b"guide");
+}
+
+#[test]
+fn test_case_sensitive_by_default() {
+    let temp_file = create_archive();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    assert!(!reader.contains("docs/guide.md"));
+    assert!(reader.get_entry("docs\\GUIDE.md").is_none());
+    assert!(matches!(
+        reader.read_file("docs/guide.md"),
+        Err(EngramError::FileNotFound(_))
+    ));
+    assert!(matches!(
+        reader.read_file_at("DOCS/GUIDE.MD"),
+        Err(EngramError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_case_insensitive_every_accessor() {
+    let temp_file = create_archive();
+    // Enabled before and after initialization
+    let mut before = ArchiveReader::open(temp_file.path())
+        .unwrap()
+        .with_case_insensitive_lookup(true);
+    before.initialize().unwrap();
+    let after = ArchiveReader::open_and_init(temp_file.path())
+        .unwrap()
+        .with_case_insensitive_lookup(true);
+    let mut clone = after.try_clone().unwrap();
+
+    for reader in [&mut before, &mut clone] {
+        for path in ["docs/guide.md", "DOCS\\GUIDE.MD", "/dOcS/gUiDe.Md"] {
+            assert!(reader.contains(path), "contains({:?})", path);
+            assert_eq!(reader.get_entry(path).unwrap().path, "Docs/Guide.md");
+            assert_eq!(reader.read_file(path).unwrap(), b"guide");
+            assert_eq!(reader.read_file_at(path).unwrap(), b"guide");
+        }
+        assert!(!reader.contains("docs/missing.md"));
+    }
+
+    // Turning it off again restores exact matching
+    let reader = after.with_case_insensitive_lookup(false);
+    assert!(!reader.contains("docs/guide.md"));
+}
+
+#[cfg(feature = "vfs")]
+mod vfs {
+    use super::*;
+    use engram_rs::VfsReader;
+    use rusqlite::Connection;
+
+    /// Helper: Archive holding a SQLite database with one row
+    fn create_database_archive() -> NamedTempFile {
+        let temp_db = NamedTempFile::new().unwrap();
+        let conn = Connection::open(temp_db.path()).unwrap();
+        conn.execute_batch("CREATE TABLE t (v TEXT); INSERT INTO t VALUES ('row');")
+            .unwrap();
+        drop(conn);
+
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        writer
+            .add_file("data/App.db", &std::fs::read(temp_db.path()).unwrap())
+            .unwrap();
+        writer.finalize().unwrap();
+        temp_file
+    }
+
+    #[test]
+    fn test_vfs_lookup() {
+        let temp_file = create_database_archive();
+
+        let mut vfs = VfsReader::open(temp_file.path()).unwrap();
+        let conn = vfs.open_database("data\\App.db").unwrap();
+        let value: String = conn
+            .query_row("SELECT v FROM t", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(value, "row");
+        assert!(vfs.is_extracted("data/App.db"));
+        assert!(vfs.is_extracted("/data\\App.db"));
+        assert!(matches!(
+            vfs.open_database("data/app.db"),
+            Err(EngramError::DatabaseNotFound(_))
+        ));
+
+        let mut vfs = VfsReader::open(temp_file.path())
+            .unwrap()
+            .with_case_insensitive_lookup(true);
+        vfs.open_database("DATA\\app.DB").unwrap();
+        assert!(vfs.is_extracted("data/App.db"));
+        assert!(vfs.get_extracted_path("data/app.db").is_some());
+
+        let (_conn, handle) = vfs.open_database_writable("/Data/APP.db").unwrap();
+        assert_eq!(handle.db_path(), "data/App.db");
+    }
+}
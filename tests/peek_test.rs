@@ -0,0 +1,102 @@
+//! Quick-open tests
+//!
+//! Covers `ArchiveReader::peek`, which summarizes an archive from its header
+//! and End Record without touching the central directory.
+
+use engram_rs::archive::END_RECORD_SIZE;
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, EncryptionMode, EngramError, CD_ENTRY_SIZE, FORMAT_VERSION_MAJOR,
+    FORMAT_VERSION_MINOR,
+};
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_peek_large_archive_without_central_directory() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    for i in 0..10_000 {
+        writer
+            .add_file(&format!("files/{:05}.txt", i), i.to_string().as_bytes())
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+
+    let summary = ArchiveReader::peek(temp_file.path()).unwrap();
+    assert_eq!(summary.entry_count, 10_000);
+    assert_eq!(summary.version_major, FORMAT_VERSION_MAJOR);
+    assert_eq!(summary.version_minor, FORMAT_VERSION_MINOR);
+    assert_eq!(summary.cd_size, 10_000 * CD_ENTRY_SIZE as u64);
+    assert_eq!(summary.encryption_mode, EncryptionMode::None);
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(summary.cd_offset, reader.header().central_directory_offset);
+    assert_eq!(summary.entry_count as usize, reader.entry_count());
+
+    // Damage every central directory entry: `initialize` fails, `peek` never
+    // reads them
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    let cd_start = summary.cd_offset as usize;
+    let cd_end = cd_start + summary.cd_size as usize;
+    for entry in bytes[cd_start..cd_end].chunks_mut(CD_ENTRY_SIZE) {
+        entry[0] ^= 0xFF;
+    }
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+    assert!(ArchiveReader::open_and_init(temp_file.path()).is_err());
+    assert_eq!(ArchiveReader::peek(temp_file.path()).unwrap(), summary);
+}
+
+#[test]
+fn test_peek_encrypted_archives_without_key() {
+    let key = [0x42u8; 32];
+    for mode in [EncryptionMode::Archive, EncryptionMode::PerFile] {
+        let temp_file = NamedTempFile::new().unwrap();
+        let writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        let mut writer = match mode {
+            EncryptionMode::Archive => writer.with_archive_encryption(&key),
+            _ => writer.with_per_file_encryption(&key),
+        };
+        writer.add_file("secret.txt", b"secret").unwrap();
+        writer.add_file("other.txt", b"other").unwrap();
+        writer.finalize().unwrap();
+
+        let summary = ArchiveReader::peek(temp_file.path()).unwrap();
+        assert_eq!(summary.encryption_mode, mode);
+        assert_eq!(summary.entry_count, 2);
+    }
+}
+
+#[test]
+fn test_peek_rejects_mismatched_end_record() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("a.txt", b"a").unwrap();
+    writer.finalize().unwrap();
+
+    // Entry count field of the End Record (offset 24 within it)
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    let count_offset = bytes.len() - END_RECORD_SIZE + 24;
+    bytes[count_offset] = 7;
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+    assert!(matches!(
+        ArchiveReader::peek(temp_file.path()),
+        Err(EngramError::InvalidFormat(_))
+    ));
+
+    assert!(ArchiveReader::peek(temp_file.path().with_extension("missing")).is_err());
+}
+
+#[test]
+fn test_peek_legacy_archive_uses_header() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_format_version(0, 4)
+        .unwrap();
+    writer.add_file("a.txt", b"a").unwrap();
+    writer.finalize().unwrap();
+
+    let summary = ArchiveReader::peek(temp_file.path()).unwrap();
+    assert_eq!((summary.version_major, summary.version_minor), (0, 4));
+    assert_eq!(summary.entry_count, 1);
+    assert_eq!(summary.archive_crc32, 0);
+}
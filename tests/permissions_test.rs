@@ -0,0 +1,168 @@
+//! Unix file mode preservation tests
+
+#![cfg(unix)]
+
+use engram_rs::{ArchiveReader, ArchiveWriter, FileMetadata};
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use tempfile::{NamedTempFile, TempDir};
+
+#[test]
+fn test_executable_mode_roundtrip() {
+    let source_dir = TempDir::new().unwrap();
+    let script_path = source_dir.path().join("run.sh");
+    fs::write(&script_path, b"#!/bin/sh\necho hello\n").unwrap();
+    fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755)).unwrap();
+
+    let notes_path = source_dir.path().join("notes.txt");
+    fs::write(&notes_path, b"not executable").unwrap();
+    fs::set_permissions(&notes_path, fs::Permissions::from_mode(0o640)).unwrap();
+
+    let archive_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(archive_file.path()).unwrap();
+        writer
+            .add_file_from_disk("bin/run.sh", &script_path)
+            .unwrap();
+        writer.add_file_from_disk("notes.txt", &notes_path).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(archive_file.path()).unwrap();
+    assert_eq!(reader.get_entry("bin/run.sh").unwrap().mode & 0o777, 0o755);
+
+    let out_dir = TempDir::new().unwrap();
+    let extracted = reader.extract_all(out_dir.path()).unwrap();
+    assert_eq!(extracted, 2);
+
+    let script_mode = fs::metadata(out_dir.path().join("bin/run.sh"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(script_mode & 0o777, 0o755);
+
+    let notes_mode = fs::metadata(out_dir.path().join("notes.txt"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(notes_mode & 0o777, 0o640);
+}
+
+#[test]
+fn test_special_bits_dropped_unless_requested() {
+    let source_dir = TempDir::new().unwrap();
+    let tool_path = source_dir.path().join("tool");
+    fs::write(&tool_path, b"#!/bin/sh\n").unwrap();
+    fs::set_permissions(&tool_path, fs::Permissions::from_mode(0o4755)).unwrap();
+
+    let archive_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(archive_file.path()).unwrap();
+        writer.add_file_from_disk("bin/tool", &tool_path).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(archive_file.path()).unwrap();
+    assert_eq!(reader.get_entry("bin/tool").unwrap().mode & 0o7777, 0o4755);
+    let out_dir = TempDir::new().unwrap();
+    reader.extract_all(out_dir.path()).unwrap();
+    let mode = fs::metadata(out_dir.path().join("bin/tool"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o7777, 0o755);
+
+    let mut reader = ArchiveReader::open(archive_file.path())
+        .unwrap()
+        .with_special_mode_bits(true);
+    reader.initialize().unwrap();
+    let out_dir = TempDir::new().unwrap();
+    reader.extract_all(out_dir.path()).unwrap();
+    let mode = fs::metadata(out_dir.path().join("bin/tool"))
+        .unwrap()
+        .permissions()
+        .mode();
+    assert_eq!(mode & 0o7777, 0o4755);
+}
+
+#[test]
+fn test_zero_mode_is_unspecified() {
+    let archive_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(archive_file.path()).unwrap();
+        writer.add_file("data.txt", b"in-memory data").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(archive_file.path()).unwrap();
+    assert_eq!(reader.get_entry("data.txt").unwrap().mode, 0);
+
+    // Extraction falls back to regular-file permissions
+    let out_dir = TempDir::new().unwrap();
+    reader.extract_all(out_dir.path()).unwrap();
+    let extracted = out_dir.path().join("data.txt");
+    assert_eq!(fs::read(&extracted).unwrap(), b"in-memory data");
+    assert_eq!(
+        fs::metadata(&extracted).unwrap().permissions().mode() & 0o777,
+        0o644
+    );
+}
+
+#[test]
+fn test_executable_flag_from_disk() {
+    let source_dir = TempDir::new().unwrap();
+    let tool_path = source_dir.path().join("tool");
+    fs::write(&tool_path, b"\x7fELF").unwrap();
+    fs::set_permissions(&tool_path, fs::Permissions::from_mode(0o700)).unwrap();
+    let data_path = source_dir.path().join("data.bin");
+    fs::write(&data_path, b"data").unwrap();
+    fs::set_permissions(&data_path, fs::Permissions::from_mode(0o600)).unwrap();
+
+    let archive_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(archive_file.path()).unwrap();
+    writer.add_file_from_disk("bin/tool", &tool_path).unwrap();
+    writer.add_file_from_disk("data.bin", &data_path).unwrap();
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(archive_file.path()).unwrap();
+    assert!(reader.get_entry("bin/tool").unwrap().is_executable());
+    assert!(!reader.get_entry("data.bin").unwrap().is_executable());
+}
+
+#[test]
+fn test_executable_flag_restored_on_extract() {
+    let key = [0x42u8; 32];
+    let archive_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(archive_file.path())
+        .unwrap()
+        .with_per_file_encryption(&key);
+    let executable = FileMetadata {
+        executable: true,
+        ..FileMetadata::default()
+    };
+    writer
+        .add_file_with_metadata("bin/setup.sh", b"#!/bin/sh\n", executable)
+        .unwrap();
+    writer
+        .add_file_with_metadata("README", b"read me", FileMetadata::default())
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_encrypted(archive_file.path(), &key).unwrap();
+    let entry = reader.get_entry("bin/setup.sh").unwrap();
+    assert!(entry.is_executable());
+    assert_eq!(entry.mode, 0);
+    assert!(!reader.get_entry("README").unwrap().is_executable());
+
+    let out_dir = TempDir::new().unwrap();
+    reader.extract_all(out_dir.path()).unwrap();
+    let mode_of = |path: &str| {
+        fs::metadata(out_dir.path().join(path))
+            .unwrap()
+            .permissions()
+            .mode()
+            & 0o777
+    };
+    assert_eq!(mode_of("bin/setup.sh"), 0o755);
+    assert_eq!(mode_of("README"), 0o644);
+}
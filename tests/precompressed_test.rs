@@ -0,0 +1,177 @@
+//! Pre-compressed input tests
+//!
+//! Covers `ArchiveWriter::add_precompressed`, which stores payloads
+//! compressed outside engram as is, and the optional test-decode from
+//! `with_precompressed_verification`.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod, EngramError};
+use flate2::write::DeflateEncoder;
+use std::io::Write;
+use tempfile::NamedTempFile;
+
+/// Helper: Asset-like content, large enough to span several 64 KB windows
+fn asset() -> Vec<u8> {
+    (0..200_000u32)
+        .map(|i| (i % 251) as u8 ^ (i / 1000) as u8)
+        .collect()
+}
+
+/// Helper: `data` compressed with `method` the way an external tool would
+fn compress(data: &[u8], method: CompressionMethod) -> Vec<u8> {
+    match method {
+        CompressionMethod::Zstd => zstd::encode_all(data, 19).unwrap(),
+        CompressionMethod::Lz4 => lz4_flex::compress_prepend_size(data),
+        CompressionMethod::Deflate => {
+            let mut encoder = DeflateEncoder::new(Vec::new(), flate2::Compression::best());
+            encoder.write_all(data).unwrap();
+            encoder.finish().unwrap()
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn test_precompressed_round_trip() {
+    let data = asset();
+    let methods = [
+        CompressionMethod::Zstd,
+        CompressionMethod::Lz4,
+        CompressionMethod::Deflate,
+    ];
+
+    for verify in [false, true] {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_precompressed_verification(verify);
+        for method in methods {
+            let compressed = compress(&data, method);
+            writer
+                .add_precompressed(
+                    &format!("assets/{}", method),
+                    &compressed,
+                    method,
+                    data.len() as u64,
+                    crc32fast::hash(&data),
+                )
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+
+        let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+        for method in methods {
+            let path = format!("assets/{}", method);
+            let entry = reader.get_entry(&path).unwrap();
+            assert_eq!(entry.compression, method);
+            assert_eq!(entry.compressed_size, compress(&data, method).len() as u64);
+            assert_eq!(reader.read_file(&path).unwrap(), data, "{}", path);
+        }
+        assert!(reader.verify_all().unwrap().is_ok());
+    }
+}
+
+#[test]
+fn test_precompressed_with_per_file_encryption() {
+    let key = [0x42u8; 32];
+    let data = b"small pre-compressed asset".to_vec();
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&key)
+        .with_precompressed_verification(true);
+    writer
+        .add_precompressed(
+            "asset.zst",
+            &compress(&data, CompressionMethod::Zstd),
+            CompressionMethod::Zstd,
+            data.len() as u64,
+            crc32fast::hash(&data),
+        )
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    assert_eq!(reader.read_file("asset.zst").unwrap(), data);
+}
+
+#[test]
+fn test_precompressed_rejects_invalid_declarations() {
+    let data = asset();
+    let compressed = compress(&data, CompressionMethod::Zstd);
+    let crc = crc32fast::hash(&data);
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+
+    for method in [CompressionMethod::None, CompressionMethod::Unknown(9)] {
+        assert!(matches!(
+            writer.add_precompressed("a.bin", &data, method, data.len() as u64, crc),
+            Err(EngramError::InvalidOptions(_))
+        ));
+    }
+    // Far larger than the content it claims to hold
+    assert!(matches!(
+        writer.add_precompressed("b.bin", &compressed, CompressionMethod::Zstd, 100, crc),
+        Err(EngramError::InvalidOptions(_))
+    ));
+    // Small streams are allowed their container overhead
+    let empty = compress(b"", CompressionMethod::Zstd);
+    writer
+        .add_precompressed(
+            "empty.zst",
+            &empty,
+            CompressionMethod::Zstd,
+            0,
+            crc32fast::hash(b""),
+        )
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.list_files(), ["empty.zst"]);
+    assert!(reader.read_file("empty.zst").unwrap().is_empty());
+}
+
+#[test]
+fn test_verification_catches_bad_payloads() {
+    let data = asset();
+    let crc = crc32fast::hash(&data);
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_precompressed_verification(true);
+
+    // Declared as Zstd but LZ4 data
+    let lz4 = compress(&data, CompressionMethod::Lz4);
+    let err = writer
+        .add_precompressed(
+            "wrong.zst",
+            &lz4,
+            CompressionMethod::Zstd,
+            data.len() as u64,
+            crc,
+        )
+        .unwrap_err();
+    assert!(
+        matches!(err, EngramError::DecompressionFailed { .. }),
+        "{:?}",
+        err
+    );
+
+    // Content shorter than declared
+    let short = compress(&data[..1000], CompressionMethod::Zstd);
+    let err = writer
+        .add_precompressed("short.zst", &short, CompressionMethod::Zstd, 2000, crc)
+        .unwrap_err();
+    assert!(matches!(err, EngramError::InvalidOptions(_)), "{:?}", err);
+
+    // Small payloads are decoded in full and checked against the CRC32
+    let small = compress(b"small", CompressionMethod::Zstd);
+    let err = writer
+        .add_precompressed("small.zst", &small, CompressionMethod::Zstd, 5, 0xDEAD_BEEF)
+        .unwrap_err();
+    assert!(matches!(err, EngramError::CrcMismatch { .. }), "{:?}", err);
+
+    writer.finalize().unwrap();
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), 0);
+}
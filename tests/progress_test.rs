@@ -0,0 +1,210 @@
+//! Progress callback tests
+//!
+//! Collects `ProgressEvent`s from `ArchiveWriter::with_progress` and
+//! `ArchiveReader::with_progress` and checks ordering and byte counts.
+
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, ProgressCallback, ProgressEvent,
+};
+use std::sync::{Arc, Mutex};
+use tempfile::{NamedTempFile, TempDir};
+
+/// Size of the frame-compressed entry (just over the 50MB threshold)
+const LARGE_SIZE: usize = 50 * 1024 * 1024 + 1000;
+
+/// Helper: Callback that appends events to a shared Vec
+fn recorder() -> (Arc<Mutex<Vec<ProgressEvent>>>, ProgressCallback) {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    (
+        events,
+        Box::new(move |event| sink.lock().unwrap().push(event)),
+    )
+}
+
+/// Helper: Files to archive, including one frame-compressed entry
+fn sample_files() -> Vec<(String, Vec<u8>)> {
+    let large: Vec<u8> = (0..LARGE_SIZE).map(|i| (i % 251) as u8).collect();
+    vec![
+        ("small.txt".to_string(), b"hello progress".to_vec()),
+        (
+            "docs/medium.md".to_string(),
+            "progress ".repeat(5000).into_bytes(),
+        ),
+        ("large.bin".to_string(), large),
+    ]
+}
+
+/// Check the event stream for one file and return the number of byte updates
+fn check_file_events(events: &[ProgressEvent], path: &str, size: u64, compressed: u64) -> usize {
+    let start = events
+        .iter()
+        .position(|e| matches!(e, ProgressEvent::FileStarted { path: p, .. } if p == path))
+        .unwrap_or_else(|| panic!("No FileStarted for {}", path));
+    assert_eq!(
+        events[start],
+        ProgressEvent::FileStarted {
+            path: path.to_string(),
+            size
+        }
+    );
+
+    let mut last = 0;
+    let mut updates = 0;
+    for event in &events[start + 1..] {
+        match event {
+            ProgressEvent::BytesProcessed {
+                path: p,
+                bytes_done,
+                total,
+            } => {
+                assert_eq!(p, path);
+                assert_eq!(*total, size);
+                assert!(*bytes_done >= last, "Byte count went backwards");
+                last = *bytes_done;
+                updates += 1;
+            }
+            ProgressEvent::FileFinished {
+                path: p,
+                compressed_size,
+            } => {
+                assert_eq!(p, path);
+                assert_eq!(*compressed_size, compressed);
+                assert_eq!(last, size, "Final byte count should equal the total");
+                return updates;
+            }
+            other => panic!("Unexpected event for {}: {:?}", path, other),
+        }
+    }
+    panic!("No FileFinished for {}", path);
+}
+
+#[test]
+fn test_writer_and_reader_progress() {
+    let files = sample_files();
+    let temp_file = NamedTempFile::new().unwrap();
+
+    let (write_events, callback) = recorder();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_progress(callback);
+        for (path, data) in &files {
+            writer.add_file(path, data).unwrap();
+        }
+        writer.add_directory("empty").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let large_entry = reader.get_entry("large.bin").unwrap().clone();
+    assert_ne!(large_entry.compression, CompressionMethod::None);
+
+    let write_events = write_events.lock().unwrap();
+    assert_eq!(write_events.last(), Some(&ProgressEvent::Finalizing));
+    for (path, data) in &files {
+        let entry = reader.get_entry(path).unwrap();
+        let updates = check_file_events(
+            &write_events,
+            path,
+            data.len() as u64,
+            entry.compressed_size,
+        );
+        if path == "large.bin" {
+            // One update per 64KB frame
+            assert_eq!(updates, LARGE_SIZE.div_ceil(64 * 1024));
+        } else {
+            assert_eq!(updates, 1);
+        }
+    }
+    // Directories do not emit events
+    assert!(!write_events
+        .iter()
+        .any(|e| matches!(e, ProgressEvent::FileStarted { path, .. } if path == "empty")));
+
+    // Reader: extract_all reports the same totals
+    let (read_events, callback) = recorder();
+    let mut reader = reader.with_progress(callback);
+    let out_dir = TempDir::new().unwrap();
+    reader.extract_all(out_dir.path()).unwrap();
+
+    let read_events = read_events.lock().unwrap();
+    for (path, data) in &files {
+        let entry = reader.get_entry(path).unwrap();
+        let updates =
+            check_file_events(&read_events, path, data.len() as u64, entry.compressed_size);
+        assert!(updates >= 1);
+    }
+    assert!(!read_events.contains(&ProgressEvent::Finalizing));
+}
+
+#[test]
+fn test_panicking_callback_does_not_corrupt_archive() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_progress(Box::new(|event| {
+                if let ProgressEvent::FileStarted { path, .. } = event {
+                    if path == "bad.txt" {
+                        panic!("progress bar crashed");
+                    }
+                }
+            }));
+
+        writer.add_file("good.txt", b"kept").unwrap();
+        let result = writer.add_file("bad.txt", b"never written");
+        assert!(matches!(result, Err(EngramError::CallbackPanicked)));
+
+        // The callback is gone; the writer keeps working
+        writer.add_file("after.txt", b"also kept").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), 2);
+    assert!(!reader.contains("bad.txt"));
+    assert_eq!(reader.read_file("good.txt").unwrap(), b"kept");
+    assert_eq!(reader.read_file("after.txt").unwrap(), b"also kept");
+}
+
+#[test]
+fn test_event_order_with_plain_closure() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    {
+        let sink = events.clone();
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_progress(move |event| sink.lock().unwrap().push(event));
+        writer.add_file("first.txt", b"one").unwrap();
+        writer
+            .add_file_with_compression("second.txt", &[b'x'; 8192], CompressionMethod::Zstd)
+            .unwrap();
+        writer.finalize().unwrap();
+    }
+
+    // Collapse the stream to (kind, path) pairs
+    let order: Vec<(&str, String)> = events
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|event| match event {
+            ProgressEvent::FileStarted { path, .. } => ("started", path.clone()),
+            ProgressEvent::BytesProcessed { path, .. } => ("bytes", path.clone()),
+            ProgressEvent::FileFinished { path, .. } => ("finished", path.clone()),
+            ProgressEvent::Finalizing => ("finalizing", String::new()),
+        })
+        .collect();
+
+    let expected: Vec<(&str, String)> = vec![
+        ("started", "first.txt".into()),
+        ("bytes", "first.txt".into()),
+        ("finished", "first.txt".into()),
+        ("started", "second.txt".into()),
+        ("bytes", "second.txt".into()),
+        ("finished", "second.txt".into()),
+        ("finalizing", String::new()),
+    ];
+    assert_eq!(order, expected);
+}
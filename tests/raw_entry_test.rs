@@ -0,0 +1,342 @@
+//! Raw entry tests
+//!
+//! Covers `ArchiveReader::read_raw_entry`, `ArchiveReader::read_file_raw` and
+//! `ArchiveWriter::add_raw_entry`: copying stored payloads between archives
+//! without decompressing them.
+
+use engram_rs::archive::LocalEntryHeader;
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, FrameOptions, RawEntry,
+    ENTRY_FLAG_FRAMED, ENTRY_FLAG_ZSTD_DICTIONARY,
+};
+use tempfile::NamedTempFile;
+
+const KEY: [u8; 32] = [0x42u8; 32];
+const OTHER_KEY: [u8; 32] = [0x24u8; 32];
+const MB: usize = 1024 * 1024;
+
+/// Helper: Files covering every compression method, an empty file and a
+/// framed one
+fn sample_files() -> Vec<(&'static str, Vec<u8>, CompressionMethod)> {
+    let text = "a line of compressible text\n".repeat(5_000).into_bytes();
+    let framed: Vec<u8> = (0..2 * MB).map(|i| (i / 1000 % 251) as u8).collect();
+    vec![
+        ("docs/text.lz4", text.clone(), CompressionMethod::Lz4),
+        ("docs/text.zst", text.clone(), CompressionMethod::Zstd),
+        ("docs/text.gz", text, CompressionMethod::Deflate),
+        ("raw.bin", b"stored as is".to_vec(), CompressionMethod::None),
+        ("empty.txt", Vec::new(), CompressionMethod::None),
+        ("big/framed.bin", framed, CompressionMethod::Zstd),
+    ]
+}
+
+/// Helper: Archive of `sample_files`, written by `writer`
+fn write_sample(writer: ArchiveWriter) {
+    let mut writer = writer
+        .with_frame_options(FrameOptions {
+            frame_size: 256 * 1024,
+            min_file_size: MB,
+        })
+        .unwrap();
+    for (path, data, compression) in sample_files() {
+        writer
+            .add_file_with_compression(path, &data, compression)
+            .unwrap();
+    }
+    writer.add_directory("empty_dir").unwrap();
+    writer.finalize().unwrap();
+}
+
+/// Helper: Stored payload of `path`, read straight from the archive bytes
+fn stored_payload(bytes: &[u8], reader: &ArchiveReader, path: &str) -> Vec<u8> {
+    let entry = reader.get_entry(path).unwrap();
+    let offset = entry.data_offset as usize;
+    let start = offset
+        + LocalEntryHeader::read_from(&bytes[offset..])
+            .unwrap()
+            .header_size();
+    bytes[start..start + entry.compressed_size as usize].to_vec()
+}
+
+/// Helper: Open `file`, with `key` when given
+fn open(file: &NamedTempFile, key: Option<&[u8; 32]>) -> ArchiveReader {
+    let mut reader = ArchiveReader::open(file.path()).unwrap();
+    if let Some(key) = key {
+        reader = reader.with_decryption_key(key);
+    }
+    reader.initialize().unwrap();
+    reader
+}
+
+/// Helper: Copy every user file of `source` into `writer` through raw entries
+fn copy_raw(source: &mut ArchiveReader, mut writer: ArchiveWriter) {
+    let paths: Vec<String> = source.list_user_files().into_iter().cloned().collect();
+    for path in paths {
+        if source.get_entry(&path).unwrap().is_directory() {
+            continue;
+        }
+        let raw = source.read_raw_entry(&path).unwrap();
+        writer.add_raw_entry(&path, raw).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_raw_copy_reads_back_identically() {
+    let source_file = NamedTempFile::new().unwrap();
+    write_sample(ArchiveWriter::create(source_file.path()).unwrap());
+    let mut source = open(&source_file, None);
+
+    let raw = source.read_raw_entry("big/framed.bin").unwrap();
+    assert_eq!(raw.compression, CompressionMethod::Zstd);
+    assert_eq!(raw.flags, ENTRY_FLAG_FRAMED);
+    assert_eq!(raw.uncompressed_size, 2 * MB as u64);
+    assert!(!raw.encrypted);
+
+    let dest_file = NamedTempFile::new().unwrap();
+    copy_raw(
+        &mut source,
+        ArchiveWriter::create(dest_file.path()).unwrap(),
+    );
+
+    let source_bytes = std::fs::read(source_file.path()).unwrap();
+    let dest_bytes = std::fs::read(dest_file.path()).unwrap();
+    let mut dest = open(&dest_file, None);
+    for (path, data, compression) in sample_files() {
+        assert_eq!(dest.read_file(path).unwrap(), data, "{}", path);
+        let entry = dest.get_entry(path).unwrap();
+        assert_eq!(entry.compression, compression, "{}", path);
+        assert_eq!(entry.crc32, source.get_entry(path).unwrap().crc32);
+        assert_eq!(
+            stored_payload(&dest_bytes, &dest, path),
+            stored_payload(&source_bytes, &source, path),
+            "{}",
+            path
+        );
+    }
+    assert_eq!(
+        dest.read_file_range("big/framed.bin", MB as u64 - 10, 20)
+            .unwrap(),
+        &sample_files()[5].1[MB - 10..MB + 10]
+    );
+    let report = dest.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+}
+
+#[test]
+fn test_raw_copy_with_encryption() {
+    // Archive encryption covers the whole archive, so raw entries are plain
+    let source_file = NamedTempFile::new().unwrap();
+    write_sample(
+        ArchiveWriter::create(source_file.path())
+            .unwrap()
+            .with_archive_encryption(&KEY),
+    );
+    let mut source = open(&source_file, Some(&KEY));
+    assert!(!source.read_raw_entry("docs/text.zst").unwrap().encrypted);
+
+    // Plain payloads are encrypted by a per-file encrypted writer
+    let per_file = NamedTempFile::new().unwrap();
+    copy_raw(
+        &mut source,
+        ArchiveWriter::create(per_file.path())
+            .unwrap()
+            .with_per_file_encryption(&KEY),
+    );
+    let mut reader = open(&per_file, Some(&KEY));
+    for (path, data, _) in sample_files() {
+        assert_eq!(reader.read_file(path).unwrap(), data, "{}", path);
+    }
+
+    // Per-file payloads stay encrypted and copy under the same key and path
+    let raw = reader.read_raw_entry("docs/text.lz4").unwrap();
+    assert!(raw.encrypted);
+    let copy = NamedTempFile::new().unwrap();
+    copy_raw(
+        &mut reader,
+        ArchiveWriter::create(copy.path())
+            .unwrap()
+            .with_per_file_encryption(&KEY),
+    );
+    let mut copied = open(&copy, Some(&KEY));
+    for (path, data, _) in sample_files() {
+        assert_eq!(copied.read_file(path).unwrap(), data, "{}", path);
+    }
+    // Under another key they no longer decrypt
+    let other = NamedTempFile::new().unwrap();
+    copy_raw(
+        &mut reader,
+        ArchiveWriter::create(other.path())
+            .unwrap()
+            .with_per_file_encryption(&OTHER_KEY),
+    );
+    assert!(open(&other, Some(&OTHER_KEY))
+        .read_file("docs/text.lz4")
+        .is_err());
+
+    // ...and cannot go into an archive without per-file encryption
+    let plain = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(plain.path()).unwrap();
+    assert!(matches!(
+        writer.add_raw_entry("docs/text.lz4", raw),
+        Err(EngramError::InvalidOptions(_))
+    ));
+    assert_eq!(writer.pending_entries(), 0);
+}
+
+#[test]
+fn test_read_file_raw_decrypts_and_readds() {
+    let source_file = NamedTempFile::new().unwrap();
+    let writer = ArchiveWriter::create(source_file.path())
+        .unwrap()
+        .with_per_file_encryption(&KEY)
+        .with_dedup();
+    let mut writer = writer
+        .with_frame_options(FrameOptions {
+            frame_size: 256 * 1024,
+            min_file_size: MB,
+        })
+        .unwrap();
+    for (path, data, compression) in sample_files() {
+        writer
+            .add_file_with_compression(path, &data, compression)
+            .unwrap();
+    }
+    // Stored in full: per-file payloads are bound to their own path
+    writer
+        .add_file_with_compression("copy.zst", &sample_files()[1].1, CompressionMethod::Zstd)
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut source = open(&source_file, Some(&KEY));
+    assert_eq!(
+        source.read_file_raw("raw.bin").unwrap(),
+        (CompressionMethod::None, b"stored as is".to_vec())
+    );
+
+    // Decrypted raw data goes into an unencrypted archive as is
+    let dest_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(dest_file.path()).unwrap();
+    let mut files = sample_files();
+    files.push(("copy.zst", files[1].1.clone(), CompressionMethod::Zstd));
+    for (path, _, _) in &files {
+        let (compression, data) = source.read_file_raw(path).unwrap();
+        let entry = source.get_entry(path).unwrap();
+        let raw = RawEntry {
+            data,
+            compression,
+            uncompressed_size: entry.uncompressed_size,
+            crc32: entry.crc32,
+            encrypted: false,
+            flags: entry.flags & ENTRY_FLAG_FRAMED,
+        };
+        writer.add_raw_entry(path, raw).unwrap();
+    }
+    writer.finalize().unwrap();
+
+    // The copy kept its own Zstd payload
+    let mut dest = open(&dest_file, None);
+    for (path, data, _) in &files {
+        assert_eq!(&dest.read_file(path).unwrap(), data, "{}", path);
+        assert_eq!(
+            dest.get_entry(path).unwrap().compression,
+            source.get_entry(path).unwrap().compression
+        );
+    }
+    assert_eq!(
+        dest.get_entry("copy.zst").unwrap().compression,
+        CompressionMethod::Zstd
+    );
+
+    // Without the key the data cannot be decrypted
+    let mut locked = open(&source_file, None);
+    assert!(matches!(
+        locked.read_file_raw("docs/text.zst"),
+        Err(EngramError::MissingDecryptionKey)
+    ));
+}
+
+#[test]
+fn test_raw_entry_metadata_is_not_checked_on_write() {
+    let source_file = NamedTempFile::new().unwrap();
+    write_sample(ArchiveWriter::create(source_file.path()).unwrap());
+    let mut source = open(&source_file, None);
+    let raw = source.read_raw_entry("docs/text.zst").unwrap();
+
+    let dest_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(dest_file.path()).unwrap();
+    writer
+        .add_raw_entry(
+            "wrong_crc.zst",
+            RawEntry {
+                crc32: raw.crc32 ^ 1,
+                ..raw.clone()
+            },
+        )
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut dest = open(&dest_file, None);
+    assert!(matches!(
+        dest.read_file("wrong_crc.zst"),
+        Err(EngramError::CrcMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_raw_entry_rejections() {
+    let source_file = NamedTempFile::new().unwrap();
+    write_sample(ArchiveWriter::create(source_file.path()).unwrap());
+    let mut source = open(&source_file, None);
+    assert!(matches!(
+        source.read_raw_entry("empty_dir"),
+        Err(EngramError::PathError(_))
+    ));
+    assert!(matches!(
+        source.read_raw_entry("missing.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+
+    let raw = source.read_raw_entry("docs/text.zst").unwrap();
+    let framed = source.read_raw_entry("big/framed.bin").unwrap();
+    let dest_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(dest_file.path()).unwrap();
+    assert!(matches!(
+        writer.add_raw_entry(
+            "dict.zst",
+            RawEntry {
+                flags: ENTRY_FLAG_ZSTD_DICTIONARY,
+                ..raw.clone()
+            }
+        ),
+        Err(EngramError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        writer.add_raw_entry(
+            "short.bin",
+            RawEntry {
+                data: vec![0; 10],
+                compression: CompressionMethod::None,
+                uncompressed_size: 11,
+                ..raw.clone()
+            }
+        ),
+        Err(EngramError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        writer.add_raw_entry("manifest.json", raw),
+        Err(EngramError::ReservedPath(_))
+    ));
+    assert_eq!(writer.pending_entries(), 0);
+
+    // v1.0 frames have no index
+    let old_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(old_file.path())
+        .unwrap()
+        .with_format_version(1, 0)
+        .unwrap();
+    assert!(matches!(
+        writer.add_raw_entry("big/framed.bin", framed),
+        Err(EngramError::InvalidOptions(_))
+    ));
+}
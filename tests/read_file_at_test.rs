@@ -0,0 +1,121 @@
+//! ArchiveReader::read_file_at tests
+//!
+//! One reader shared through an `Arc` serves reads from many threads at once.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod, EngramError};
+use std::io::Cursor;
+use std::sync::Arc;
+use std::thread;
+use tempfile::NamedTempFile;
+
+const FILE_COUNT: usize = 256;
+const THREAD_COUNT: usize = 16;
+
+/// Helper: Deterministic content for file `i`
+fn content(i: usize) -> Vec<u8> {
+    format!("entry {} ", i).repeat(200 + i).into_bytes()
+}
+
+/// Helper: Archive with FILE_COUNT files, alternating compression methods
+fn create_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let methods = [
+        CompressionMethod::None,
+        CompressionMethod::Lz4,
+        CompressionMethod::Zstd,
+    ];
+    for i in 0..FILE_COUNT {
+        writer
+            .add_file_with_compression(
+                &format!("files/{:04}.txt", i),
+                &content(i),
+                methods[i % methods.len()],
+            )
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_parallel_read_file_at() {
+    let temp_file = create_archive();
+    let reader = Arc::new(ArchiveReader::open_and_init(temp_file.path()).unwrap());
+
+    // Every thread reads every file, so positioned reads overlap constantly
+    let handles: Vec<_> = (0..THREAD_COUNT)
+        .map(|t| {
+            let reader = Arc::clone(&reader);
+            thread::spawn(move || {
+                for n in 0..FILE_COUNT {
+                    let i = (n + t * 7) % FILE_COUNT;
+                    let data = reader.read_file_at(&format!("files/{:04}.txt", i)).unwrap();
+                    assert_eq!(data, content(i), "Mismatch for file {}", i);
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn test_read_file_at_matches_read_file() {
+    let temp_file = create_archive();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    for i in (0..FILE_COUNT).step_by(17) {
+        let path = format!("files/{:04}.txt", i);
+        assert_eq!(
+            reader.read_file_at(&path).unwrap(),
+            reader.read_file(&path).unwrap()
+        );
+    }
+    assert!(matches!(
+        reader.read_file_at("missing.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+}
+
+#[test]
+fn test_read_file_at_encrypted() {
+    let key = [0x24u8; 32];
+
+    for per_file in [false, true] {
+        let temp_file = NamedTempFile::new().unwrap();
+        {
+            let writer = ArchiveWriter::create(temp_file.path()).unwrap();
+            let mut writer = if per_file {
+                writer.with_per_file_encryption(&key)
+            } else {
+                writer.with_archive_encryption(&key)
+            };
+            writer.add_file("a.txt", b"first secret").unwrap();
+            writer.add_file("b.txt", b"second secret").unwrap();
+            writer.finalize().unwrap();
+        }
+
+        let reader = Arc::new(ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap());
+        thread::scope(|scope| {
+            scope.spawn(|| assert_eq!(reader.read_file_at("a.txt").unwrap(), b"first secret"));
+            scope.spawn(|| assert_eq!(reader.read_file_at("b.txt").unwrap(), b"second secret"));
+        });
+    }
+}
+
+#[test]
+fn test_read_file_at_requires_path() {
+    let temp_file = create_archive();
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+
+    let mut reader = ArchiveReader::from_reader(Cursor::new(bytes)).unwrap();
+    reader.initialize().unwrap();
+    assert!(matches!(
+        reader.read_file_at("files/0000.txt"),
+        Err(EngramError::Other(_))
+    ));
+    assert_eq!(reader.read_file("files/0000.txt").unwrap(), content(0));
+}
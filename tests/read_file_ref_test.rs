@@ -0,0 +1,70 @@
+//! ArchiveReader::read_file_ref tests
+//!
+//! Uncompressed entries of in-memory archives are served without copying.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod};
+use std::borrow::Cow;
+use tempfile::NamedTempFile;
+
+const LARGE_SIZE: usize = 8 * 1024 * 1024;
+
+/// Helper: Incompressible-looking content of `len` bytes
+fn large_content(len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i * 31 % 251) as u8).collect()
+}
+
+/// Helper: Archive with one large uncompressed entry and one compressed entry
+fn create_archive(key: Option<&[u8; 32]>) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    if let Some(key) = key {
+        writer = writer.with_archive_encryption(key);
+    }
+    writer
+        .add_file_with_compression(
+            "blob.bin",
+            &large_content(LARGE_SIZE),
+            CompressionMethod::None,
+        )
+        .unwrap();
+    writer
+        .add_file_with_compression(
+            "text.txt",
+            "compressible ".repeat(1000).as_bytes(),
+            CompressionMethod::Zstd,
+        )
+        .unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_uncompressed_entry_is_borrowed() {
+    let key = [0x77u8; 32];
+    let temp_file = create_archive(Some(&key));
+    let reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+
+    let first = reader.read_file_ref("blob.bin").unwrap();
+    let second = reader.read_file_ref("blob.bin").unwrap();
+    assert!(matches!(first, Cow::Borrowed(_)));
+    // Both calls point into the same in-memory payload: nothing was copied
+    assert_eq!(first.as_ptr(), second.as_ptr());
+    assert_eq!(first.len(), LARGE_SIZE);
+    assert_eq!(first.as_ref(), large_content(LARGE_SIZE).as_slice());
+
+    let text = reader.read_file_ref("text.txt").unwrap();
+    assert!(matches!(text, Cow::Owned(_)));
+    assert_eq!(text.as_ref(), "compressible ".repeat(1000).as_bytes());
+}
+
+#[test]
+fn test_file_backed_reads_are_owned() {
+    let temp_file = create_archive(None);
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    let blob = reader.read_file_ref("blob.bin").unwrap();
+    assert!(matches!(blob, Cow::Owned(_)));
+    assert_eq!(blob.len(), LARGE_SIZE);
+    let blob = blob.into_owned();
+    assert_eq!(reader.read_file("blob.bin").unwrap(), blob);
+}
@@ -0,0 +1,125 @@
+//! ArchiveReader::try_clone tests
+//!
+//! Clones share the parsed central directory but own their file handle.
+
+use engram_rs::archive::{EndRecord, END_RECORD_SIZE};
+use engram_rs::{ArchiveReader, ArchiveWriter, EngramError};
+use std::io::Cursor;
+use std::thread;
+use tempfile::NamedTempFile;
+
+const FILE_COUNT: usize = 256;
+const THREAD_COUNT: usize = 16;
+
+/// Helper: Deterministic content for file `i`
+fn content(i: usize) -> Vec<u8> {
+    format!("file {} ", i).repeat(100 + i).into_bytes()
+}
+
+/// Helper: Archive with FILE_COUNT files
+fn create_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    for i in 0..FILE_COUNT {
+        writer
+            .add_file(&format!("files/{:04}.txt", i), &content(i))
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Zero the central directory on disk, leaving entry data intact
+fn wipe_central_directory(path: &std::path::Path) {
+    let mut bytes = std::fs::read(path).unwrap();
+    let endr_start = bytes.len() - END_RECORD_SIZE;
+    let endr = EndRecord::read_from(Cursor::new(&bytes[endr_start..])).unwrap();
+    let start = endr.central_directory_offset as usize;
+    let end = start + endr.central_directory_size as usize;
+    bytes[start..end].fill(0);
+    std::fs::write(path, &bytes).unwrap();
+}
+
+#[test]
+fn test_try_clone_across_threads() {
+    let temp_file = create_archive();
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    // The central directory is parsed once. Wiping it on disk proves clones
+    // reuse the parsed entries: a fresh reader can no longer initialize.
+    wipe_central_directory(temp_file.path());
+    assert!(ArchiveReader::open_and_init(temp_file.path()).is_err());
+
+    let handles: Vec<_> = (0..THREAD_COUNT)
+        .map(|t| {
+            let mut handle = reader.try_clone().unwrap();
+            thread::spawn(move || {
+                assert_eq!(handle.entry_count(), FILE_COUNT);
+                // Interleave entries so threads seek all over the file
+                for i in (t..FILE_COUNT).step_by(THREAD_COUNT) {
+                    let data = handle.read_file(&format!("files/{:04}.txt", i)).unwrap();
+                    assert_eq!(data, content(i), "Mismatch for file {}", i);
+                }
+                // Read a shared entry too
+                assert_eq!(handle.read_file("files/0000.txt").unwrap(), content(0));
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+}
+
+#[test]
+fn test_clones_have_independent_positions() {
+    let temp_file = create_archive();
+    let mut first = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let mut second = first.try_clone().unwrap();
+
+    // Alternate reads between handles
+    for i in 0..FILE_COUNT {
+        let (reader, other) = if i % 2 == 0 {
+            (&mut first, &mut second)
+        } else {
+            (&mut second, &mut first)
+        };
+        assert_eq!(
+            reader.read_file(&format!("files/{:04}.txt", i)).unwrap(),
+            content(i)
+        );
+        assert_eq!(
+            other
+                .read_file(&format!("files/{:04}.txt", FILE_COUNT - 1 - i))
+                .unwrap(),
+            content(FILE_COUNT - 1 - i)
+        );
+    }
+}
+
+#[test]
+fn test_try_clone_archive_encrypted() {
+    let key = [0x42u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_archive_encryption(&key);
+        writer.add_file("secret.txt", b"shared plaintext").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    let mut clone = reader.try_clone().unwrap();
+    assert_eq!(clone.read_file("secret.txt").unwrap(), b"shared plaintext");
+}
+
+#[test]
+fn test_try_clone_requires_path() {
+    let temp_file = create_archive();
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+
+    let mut reader = ArchiveReader::from_reader(Cursor::new(bytes)).unwrap();
+    reader.initialize().unwrap();
+    assert!(matches!(reader.try_clone(), Err(EngramError::Other(_))));
+}
@@ -0,0 +1,331 @@
+//! Multi-recipient encryption tests
+//!
+//! The archive's encryption key is wrapped for each recipient, who opens it
+//! with their own symmetric or X25519 secret key.
+
+use engram_rs::archive::{EndRecord, END_RECORD_SIZE, MIN_PASSWORD_ITERATIONS};
+use engram_rs::{
+    recipient_public_key, rekey_archive, update_recipients, ArchiveReader, ArchiveWriter,
+    EncryptionMode, EngramError, RecipientChanges, RekeyOptions, HEADER_FLAG_RECIPIENTS,
+};
+use std::path::Path;
+use tempfile::{NamedTempFile, TempDir};
+
+const ALICE_KEY: [u8; 32] = [0xA1u8; 32];
+const BOB_KEY: [u8; 32] = [0xB0u8; 32];
+const CAROL_SECRET: [u8; 32] = [0xC4u8; 32];
+
+/// Helper: Files stored in every test archive
+fn files() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("notes.txt", b"shared notes".to_vec()),
+        ("data/report.csv", "a,b,c\n1,2,3\n".repeat(300).into_bytes()),
+    ]
+}
+
+/// Helper: Write `files()` and finalize
+fn write_files(mut writer: ArchiveWriter) {
+    for (path, data) in files() {
+        writer.add_file(path, &data).unwrap();
+    }
+    writer.finalize().unwrap();
+}
+
+/// Helper: Archive readable by alice and bob (symmetric keys)
+fn create_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_recipient_key("alice", &ALICE_KEY).unwrap();
+    writer.add_recipient_key("bob", &BOB_KEY).unwrap();
+    write_files(writer);
+    temp_file
+}
+
+/// Helper: Open `path` as recipient `id`
+fn open_as(path: &Path, id: &str, key: &[u8; 32]) -> engram_rs::Result<ArchiveReader> {
+    let mut reader = ArchiveReader::open(path)?.with_recipient_key(id, key);
+    reader.initialize()?;
+    Ok(reader)
+}
+
+/// Helper: Assert recipient `id` reads every file
+fn assert_readable_by(path: &Path, id: &str, key: &[u8; 32]) {
+    let mut reader = open_as(path, id, key).unwrap();
+    for (name, data) in files() {
+        assert_eq!(
+            reader.read_file(name).unwrap(),
+            data,
+            "{} reading {}",
+            id,
+            name
+        );
+    }
+    assert!(reader.verify_all().unwrap().is_ok());
+}
+
+#[test]
+fn test_two_recipients_can_read() {
+    let temp_file = create_archive();
+    let mut reader = ArchiveReader::open(temp_file.path()).unwrap();
+    assert!(reader.header().has_recipients());
+    assert_ne!(reader.header().flags & HEADER_FLAG_RECIPIENTS, 0);
+    // Recipients without an explicit key get archive encryption with a random key
+    assert_eq!(reader.header().encryption_mode(), EncryptionMode::Archive);
+    assert_eq!(reader.recipients().unwrap(), ["alice", "bob"]);
+
+    assert_readable_by(temp_file.path(), "alice", &ALICE_KEY);
+    assert_readable_by(temp_file.path(), "bob", &BOB_KEY);
+}
+
+#[test]
+fn test_non_recipient_fails() {
+    let temp_file = create_archive();
+
+    assert!(matches!(
+        open_as(temp_file.path(), "eve", &[0xEEu8; 32]),
+        Err(EngramError::RecipientNotFound(id)) if id == "eve"
+    ));
+    // A recipient id with someone else's key
+    assert!(matches!(
+        open_as(temp_file.path(), "alice", &BOB_KEY),
+        Err(EngramError::DecryptionFailed)
+    ));
+    // Recipient keys are not the archive key
+    let mut reader = ArchiveReader::open(temp_file.path())
+        .unwrap()
+        .with_decryption_key(&ALICE_KEY);
+    assert!(matches!(
+        reader.initialize(),
+        Err(EngramError::DecryptionFailed)
+    ));
+}
+
+#[test]
+fn test_public_key_recipient_with_per_file_encryption() {
+    let owner_key = [0x0Fu8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&owner_key);
+    writer
+        .add_recipient_pubkey("carol", &recipient_public_key(&CAROL_SECRET))
+        .unwrap();
+    writer.add_recipient_key("alice", &ALICE_KEY).unwrap();
+    assert!(matches!(
+        writer.add_recipient_key("alice", &BOB_KEY),
+        Err(EngramError::InvalidOptions(_))
+    ));
+    assert!(writer.add_recipient_key("", &BOB_KEY).is_err());
+    write_files(writer);
+
+    assert_readable_by(temp_file.path(), "carol", &CAROL_SECRET);
+    assert_readable_by(temp_file.path(), "alice", &ALICE_KEY);
+    assert!(matches!(
+        open_as(temp_file.path(), "carol", &ALICE_KEY),
+        Err(EngramError::DecryptionFailed)
+    ));
+
+    // The explicitly configured key still opens the archive directly
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &owner_key).unwrap();
+    assert_eq!(reader.header().encryption_mode(), EncryptionMode::PerFile);
+    assert_eq!(reader.read_file("notes.txt").unwrap(), b"shared notes");
+}
+
+#[test]
+fn test_private_keys_open_archive_without_recipient_id() {
+    let dave_secret = [0xD4u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer
+        .add_recipient(&recipient_public_key(&CAROL_SECRET))
+        .unwrap();
+    writer
+        .add_recipient(&recipient_public_key(&dave_secret))
+        .unwrap();
+    write_files(writer);
+
+    for secret in [CAROL_SECRET, dave_secret] {
+        let mut reader = ArchiveReader::open(temp_file.path())
+            .unwrap()
+            .with_private_key(&secret);
+        reader.initialize().unwrap();
+        for (name, data) in files() {
+            assert_eq!(reader.read_file(name).unwrap(), data);
+        }
+    }
+
+    // A third key opens neither wrapped key
+    let mut reader = ArchiveReader::open(temp_file.path())
+        .unwrap()
+        .with_private_key(&[0xEEu8; 32]);
+    assert!(matches!(
+        reader.initialize(),
+        Err(EngramError::DecryptionFailed)
+    ));
+
+    // Recipients are listed under their hex-encoded public keys
+    let mut reader = ArchiveReader::open(temp_file.path()).unwrap();
+    let carol_id = hex::encode(recipient_public_key(&CAROL_SECRET));
+    assert_eq!(reader.recipients().unwrap()[0], carol_id);
+    assert_readable_by(temp_file.path(), &carol_id, &CAROL_SECRET);
+}
+
+#[test]
+fn test_password_recipients() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&ALICE_KEY);
+    for (id, password) in [("ops", "correct horse"), ("backup", "battery staple")] {
+        writer
+            .add_recipient_password_with_iterations(id, password, MIN_PASSWORD_ITERATIONS)
+            .unwrap();
+    }
+    write_files(writer);
+
+    for password in ["correct horse", "battery staple"] {
+        let mut reader = ArchiveReader::open_with_password(temp_file.path(), password).unwrap();
+        for (name, data) in files() {
+            assert_eq!(reader.read_file(name).unwrap(), data);
+        }
+    }
+    // The key set on the writer still works, and a wrong password opens nothing
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &ALICE_KEY).unwrap();
+    assert_eq!(reader.read_file("notes.txt").unwrap(), b"shared notes");
+    assert!(matches!(
+        ArchiveReader::open_with_password(temp_file.path(), "Correct horse"),
+        Err(EngramError::DecryptionFailed)
+    ));
+    // Password recipients are not opened by keys
+    assert!(matches!(
+        ArchiveReader::open(temp_file.path())
+            .unwrap()
+            .with_private_key(&ALICE_KEY)
+            .initialize(),
+        Err(EngramError::DecryptionFailed)
+    ));
+}
+
+#[test]
+fn test_password_recipient_validation() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    assert!(matches!(
+        writer.add_recipient_password_with_iterations("ops", "", MIN_PASSWORD_ITERATIONS),
+        Err(EngramError::InvalidOptions(_))
+    ));
+    assert!(matches!(
+        writer.add_recipient_password_with_iterations("ops", "secret", 1000),
+        Err(EngramError::InvalidOptions(_))
+    ));
+}
+
+#[test]
+fn test_update_recipients_keeps_payloads() {
+    let source = create_archive();
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("updated.eng");
+
+    let changes = RecipientChanges::new()
+        .remove("bob")
+        .add_pubkey("carol", &recipient_public_key(&CAROL_SECRET));
+    let report = update_recipients(
+        source.path(),
+        &dest,
+        "alice",
+        &ALICE_KEY,
+        changes,
+        RekeyOptions::default(),
+    )
+    .unwrap();
+    assert_eq!(report.payloads_reencrypted, 0);
+    assert_eq!(report.entries_verified, files().len());
+
+    assert_readable_by(&dest, "alice", &ALICE_KEY);
+    assert_readable_by(&dest, "carol", &CAROL_SECRET);
+    assert!(matches!(
+        open_as(&dest, "bob", &BOB_KEY),
+        Err(EngramError::RecipientNotFound(_))
+    ));
+
+    // The encrypted payload is untouched: only the recipients block and ENDR changed
+    let before = std::fs::read(source.path()).unwrap();
+    let after = std::fs::read(&dest).unwrap();
+    let end_record = EndRecord::read_from_end(std::io::Cursor::new(&before)).unwrap();
+    let payload_end = before.len() - END_RECORD_SIZE - end_record.recipients_size as usize;
+    assert_eq!(before[..payload_end], after[..payload_end]);
+}
+
+#[test]
+fn test_update_recipients_validation() {
+    let source = create_archive();
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("updated.eng");
+    let update = |id: &str, key: &[u8; 32], changes: RecipientChanges| {
+        update_recipients(
+            source.path(),
+            &dest,
+            id,
+            key,
+            changes,
+            RekeyOptions::default(),
+        )
+    };
+
+    assert!(matches!(
+        update("alice", &BOB_KEY, RecipientChanges::new().remove("bob")),
+        Err(EngramError::DecryptionFailed)
+    ));
+    assert!(matches!(
+        update("alice", &ALICE_KEY, RecipientChanges::new().remove("dave")),
+        Err(EngramError::RecipientNotFound(_))
+    ));
+    assert!(update(
+        "alice",
+        &ALICE_KEY,
+        RecipientChanges::new().remove("alice").remove("bob")
+    )
+    .is_err());
+    assert!(update(
+        "alice",
+        &ALICE_KEY,
+        RecipientChanges::new().add_key("bob", &[1u8; 32])
+    )
+    .is_err());
+    assert!(!dest.exists());
+
+    // In place only when asked to
+    assert!(matches!(
+        update_recipients(
+            source.path(),
+            source.path(),
+            "alice",
+            &ALICE_KEY,
+            RecipientChanges::new().remove("bob"),
+            RekeyOptions::default(),
+        ),
+        Err(EngramError::PathError(_))
+    ));
+    update_recipients(
+        source.path(),
+        source.path(),
+        "alice",
+        &ALICE_KEY,
+        RecipientChanges::new().remove("bob"),
+        RekeyOptions { in_place: true },
+    )
+    .unwrap();
+    assert_eq!(
+        ArchiveReader::open(source.path())
+            .unwrap()
+            .recipients()
+            .unwrap(),
+        ["alice"]
+    );
+
+    // Rotating the archive key would need every recipient's key
+    assert!(matches!(
+        rekey_archive(source.path(), &dest, &ALICE_KEY, &BOB_KEY),
+        Err(EngramError::Other(_))
+    ));
+}
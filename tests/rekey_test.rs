@@ -0,0 +1,200 @@
+//! Key rotation tests
+//!
+//! `rekey_archive` re-encrypts archives under a new key for both encryption modes.
+
+use engram_rs::{
+    rekey_archive, rekey_archive_with, ArchiveReader, ArchiveWriter, EncryptionMode, EngramError,
+    RekeyOptions, HEADER_SIZE,
+};
+use tempfile::{NamedTempFile, TempDir};
+
+const OLD_KEY: [u8; 32] = [0x11u8; 32];
+const NEW_KEY: [u8; 32] = [0x22u8; 32];
+
+/// Helper: Files stored in every test archive
+fn files() -> Vec<(String, Vec<u8>)> {
+    vec![
+        ("a.txt".to_string(), b"alpha".to_vec()),
+        (
+            "docs/readme.md".to_string(),
+            "# Readme\n".repeat(500).into_bytes(),
+        ),
+        (
+            "docs/copy.md".to_string(),
+            "# Readme\n".repeat(500).into_bytes(),
+        ),
+        ("empty.bin".to_string(), Vec::new()),
+    ]
+}
+
+/// Helper: Archive encrypted with `OLD_KEY` in the given mode
+fn create_archive(per_file: bool) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_dedup()
+        .with_content_version(4);
+    writer = if per_file {
+        writer.with_per_file_encryption(&OLD_KEY)
+    } else {
+        writer.with_archive_encryption(&OLD_KEY)
+    };
+    for (path, data) in files() {
+        writer.add_file(&path, &data).unwrap();
+    }
+    writer.add_directory("logs").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Assert the archive at `path` opens with `NEW_KEY` only and holds `files()`
+fn assert_rotated(path: &std::path::Path) {
+    let mut reader = ArchiveReader::open_encrypted(path, &NEW_KEY).unwrap();
+    for (name, data) in files() {
+        assert_eq!(reader.read_file(&name).unwrap(), data);
+    }
+    assert!(reader.get_entry("logs").unwrap().is_directory());
+    assert_eq!(reader.content_version(), 4);
+    assert!(reader.verify_all().unwrap().is_ok());
+
+    // The old key no longer decrypts anything
+    let mut old = ArchiveReader::open(path)
+        .unwrap()
+        .with_decryption_key(&OLD_KEY);
+    let failed = match old.header().encryption_mode() {
+        EncryptionMode::Archive => old.initialize().err(),
+        _ => {
+            old.initialize().unwrap();
+            old.read_file("a.txt").err()
+        }
+    };
+    assert!(matches!(failed, Some(EngramError::DecryptionFailed)));
+}
+
+#[test]
+fn test_rekey_per_file() {
+    let source = create_archive(true);
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("rotated.eng");
+
+    let report = rekey_archive(source.path(), &dest, &OLD_KEY, &NEW_KEY).unwrap();
+    assert_eq!(report.encryption_mode, EncryptionMode::PerFile);
+    // Per-file encryption stores the identical copy in full
+    assert_eq!(report.payloads_reencrypted, 4);
+    assert_eq!(report.entries_verified, 5);
+    assert_rotated(&dest);
+
+    // Metadata carries over: only ciphertext bytes differ
+    let before = ArchiveReader::open_and_init(source.path()).unwrap();
+    let after = ArchiveReader::open_and_init(&dest).unwrap();
+    assert_eq!(before.list_files(), after.list_files());
+    for path in before.list_files() {
+        let (a, b) = (
+            before.get_entry(path).unwrap(),
+            after.get_entry(path).unwrap(),
+        );
+        assert_eq!(a.data_offset, b.data_offset);
+        assert_eq!(a.compressed_size, b.compressed_size);
+        assert_eq!(a.crc32, b.crc32);
+        assert_eq!(a.modified_time, b.modified_time);
+        assert_eq!(a.compression, b.compression);
+        assert_eq!(a.flags, b.flags);
+    }
+    let (src_bytes, dest_bytes) = (
+        std::fs::read(source.path()).unwrap(),
+        std::fs::read(&dest).unwrap(),
+    );
+    let cd = before.header().central_directory_offset as usize;
+    assert_eq!(src_bytes.len(), dest_bytes.len());
+    assert_eq!(src_bytes[..HEADER_SIZE], dest_bytes[..HEADER_SIZE]);
+    assert_eq!(src_bytes[cd..], dest_bytes[cd..]);
+    assert_ne!(src_bytes, dest_bytes);
+}
+
+#[test]
+fn test_rekey_archive_mode() {
+    let source = create_archive(false);
+    let dir = TempDir::new().unwrap();
+    let dest = dir.path().join("rotated.eng");
+
+    let report = rekey_archive(source.path(), &dest, &OLD_KEY, &NEW_KEY).unwrap();
+    assert_eq!(report.encryption_mode, EncryptionMode::Archive);
+    assert_eq!(report.payloads_reencrypted, 1);
+    assert_eq!(report.entries_verified, 5);
+    assert_rotated(&dest);
+
+    let before = ArchiveReader::open_encrypted(source.path(), &OLD_KEY).unwrap();
+    let after = ArchiveReader::open_encrypted(&dest, &NEW_KEY).unwrap();
+    for path in before.list_files() {
+        let (a, b) = (
+            before.get_entry(path).unwrap(),
+            after.get_entry(path).unwrap(),
+        );
+        assert_eq!(a.modified_time, b.modified_time);
+        assert_eq!(a.compression, b.compression);
+        assert_eq!(a.flags, b.flags);
+    }
+}
+
+#[test]
+fn test_rekey_wrong_old_key() {
+    for per_file in [true, false] {
+        let source = create_archive(per_file);
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("rotated.eng");
+
+        assert!(matches!(
+            rekey_archive(source.path(), &dest, &[0x33u8; 32], &NEW_KEY),
+            Err(EngramError::DecryptionFailed)
+        ));
+        assert!(!dest.exists());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
+
+#[test]
+fn test_rekey_in_place() {
+    let source = create_archive(true);
+
+    assert!(matches!(
+        rekey_archive(source.path(), source.path(), &OLD_KEY, &NEW_KEY),
+        Err(EngramError::PathError(_))
+    ));
+    // Still readable with the old key
+    let mut reader = ArchiveReader::open_encrypted(source.path(), &OLD_KEY).unwrap();
+    assert_eq!(reader.read_file("a.txt").unwrap(), b"alpha");
+    drop(reader);
+
+    let options = RekeyOptions { in_place: true };
+    rekey_archive_with(source.path(), source.path(), &OLD_KEY, &NEW_KEY, options).unwrap();
+    assert_rotated(source.path());
+
+    // No temporary file is left behind
+    let dir = source.path().parent().unwrap();
+    let name = source.path().file_name().unwrap().to_string_lossy();
+    assert!(!std::fs::read_dir(dir).unwrap().any(|e| e
+        .unwrap()
+        .file_name()
+        .to_string_lossy()
+        .contains(&format!("{}.rekey", name))));
+}
+
+#[test]
+fn test_rekey_unencrypted_rejected() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        writer.add_file("a.txt", b"a").unwrap();
+        writer.finalize().unwrap();
+    }
+    let dir = TempDir::new().unwrap();
+    assert!(matches!(
+        rekey_archive(
+            temp_file.path(),
+            &dir.path().join("out.eng"),
+            &OLD_KEY,
+            &NEW_KEY
+        ),
+        Err(EngramError::Other(_))
+    ));
+}
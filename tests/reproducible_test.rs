@@ -0,0 +1,245 @@
+//! Reproducible archive tests
+//!
+//! Covers `ArchiveWriter::with_fixed_time`, `ArchiveWriter::add_file_with_time`
+//! `ArchiveWriter::with_deterministic_output` and `ArchiveWriter::with_rng`.
+
+use ed25519_dalek::SigningKey;
+use engram_rs::{ArchiveReader, ArchiveWriter, Author, CompressionMethod, Manifest};
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use tempfile::NamedTempFile;
+
+/// 2024-01-01 00:00:00 UTC
+const FIXED_TIME: u64 = 1_704_067_200;
+
+/// Helper: Build an archive from the same inputs with a fixed timestamp
+fn build_archive(path: &std::path::Path) {
+    let mut manifest = Manifest::new(
+        "reproducible".to_string(),
+        "Reproducible Build".to_string(),
+        Author::new("Builder"),
+        "1.0.0".to_string(),
+    );
+    manifest.add_file("readme.txt".to_string(), b"Hello", None);
+
+    let mut writer = ArchiveWriter::create(path)
+        .unwrap()
+        .with_fixed_time(FIXED_TIME);
+    writer.add_file("readme.txt", b"Hello").unwrap();
+    writer
+        .add_file("docs/guide.md", "# Guide\n".repeat(1000).as_bytes())
+        .unwrap();
+    writer.add_directory("empty").unwrap();
+    writer
+        .add_manifest(&serde_json::to_value(&manifest).unwrap())
+        .unwrap();
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_fixed_time_archives_are_byte_identical() {
+    let first = NamedTempFile::new().unwrap();
+    let second = NamedTempFile::new().unwrap();
+
+    build_archive(first.path());
+    // Make sure a wall-clock timestamp would differ between the builds
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    build_archive(second.path());
+
+    let first_bytes = std::fs::read(first.path()).unwrap();
+    let second_bytes = std::fs::read(second.path()).unwrap();
+    assert_eq!(first_bytes, second_bytes);
+
+    let mut reader = ArchiveReader::open_and_init(first.path()).unwrap();
+    for path in reader.list_files() {
+        assert_eq!(reader.get_entry(path).unwrap().modified_time, FIXED_TIME);
+    }
+
+    let manifest = reader.read_manifest().unwrap().unwrap();
+    assert_eq!(manifest["metadata"]["created"], FIXED_TIME);
+}
+
+#[test]
+fn test_add_file_with_time() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_fixed_time(FIXED_TIME);
+        writer
+            .add_file_with_time("old.txt", b"old", CompressionMethod::None, 946_684_800)
+            .unwrap();
+        writer.add_file("fixed.txt", b"fixed").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(
+        reader.get_entry("old.txt").unwrap().modified_time,
+        946_684_800
+    );
+    assert_eq!(
+        reader.get_entry("fixed.txt").unwrap().modified_time,
+        FIXED_TIME
+    );
+}
+
+#[test]
+fn test_fixed_time_keeps_signed_manifest() {
+    let mut manifest = Manifest::new(
+        "signed".to_string(),
+        "Signed".to_string(),
+        Author::new("Signer"),
+        "1.0.0".to_string(),
+    );
+    let original_created = manifest.metadata.created;
+    let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+    manifest.sign(&signing_key, None).unwrap();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_fixed_time(FIXED_TIME);
+        writer
+            .add_manifest(&serde_json::to_value(&manifest).unwrap())
+            .unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let value = reader.read_manifest().unwrap().unwrap();
+    assert_eq!(value["metadata"]["created"], original_created);
+
+    let parsed: Manifest = serde_json::from_value(value).unwrap();
+    assert!(parsed
+        .verify_signatures()
+        .unwrap()
+        .iter()
+        .all(|&valid| valid));
+}
+
+/// Helper: Build a deterministic archive, adding files out of path order
+fn build_deterministic(path: &std::path::Path, key: Option<&[u8; 32]>) {
+    let mut writer = ArchiveWriter::create(path)
+        .unwrap()
+        .with_deterministic_output(true);
+    if let Some(key) = key {
+        let mut counter = 0u64;
+        writer = writer
+            .with_per_file_encryption(key)
+            .with_insecure_nonce_source(move || {
+                counter += 1;
+                let mut nonce = [0u8; 12];
+                nonce[..8].copy_from_slice(&counter.to_le_bytes());
+                nonce
+            });
+    }
+    for name in ["zeta", "alpha", "mid"] {
+        writer
+            .add_file_with_compression(
+                &format!("{}.log", name),
+                format!("{} line\n", name).repeat(2000).as_bytes(),
+                CompressionMethod::Zstd,
+            )
+            .unwrap();
+    }
+    writer.add_directory("empty").unwrap();
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_deterministic_output_is_bit_identical() {
+    let key = [0x5Au8; 32];
+    for key in [None, Some(&key)] {
+        let first = NamedTempFile::new().unwrap();
+        let second = NamedTempFile::new().unwrap();
+        build_deterministic(first.path(), key);
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        build_deterministic(second.path(), key);
+
+        assert_eq!(
+            std::fs::read(first.path()).unwrap(),
+            std::fs::read(second.path()).unwrap()
+        );
+
+        let mut reader = match key {
+            Some(key) => ArchiveReader::open_encrypted(first.path(), key).unwrap(),
+            None => ArchiveReader::open_and_init(first.path()).unwrap(),
+        };
+        let expected_time = std::env::var("SOURCE_DATE_EPOCH")
+            .ok()
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(0);
+        for path in reader.list_files().to_vec() {
+            let entry = reader.get_entry(&path).unwrap();
+            assert_eq!(entry.modified_time, expected_time);
+        }
+        assert_eq!(
+            reader.read_file("mid.log").unwrap(),
+            "mid line\n".repeat(2000).into_bytes()
+        );
+    }
+}
+
+#[test]
+fn test_directory_order_differs_from_data_order() {
+    let temp_file = NamedTempFile::new().unwrap();
+    build_deterministic(temp_file.path(), None);
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(
+        reader.list_files(),
+        ["alpha.log", "empty", "mid.log", "zeta.log"]
+    );
+
+    // Payloads stay in the order the files were added
+    let offset = |path: &str| reader.get_entry(path).unwrap().data_offset;
+    assert!(offset("zeta.log") < offset("alpha.log"));
+    assert!(offset("alpha.log") < offset("mid.log"));
+
+    for name in ["zeta", "alpha", "mid"] {
+        assert_eq!(
+            reader.read_file(&format!("{}.log", name)).unwrap(),
+            format!("{} line\n", name).repeat(2000).into_bytes()
+        );
+    }
+    assert!(reader.verify_all().unwrap().is_ok());
+}
+
+/// Helper: Encrypted deterministic archive with nonces from a ChaCha20 RNG
+/// seeded with `seed`
+fn build_seeded(path: &std::path::Path, archive_mode: bool, seed: u64) {
+    let key = [0x5Au8; 32];
+    let writer = ArchiveWriter::create(path)
+        .unwrap()
+        .with_deterministic_output(true)
+        .with_rng(ChaCha20Rng::seed_from_u64(seed));
+    let mut writer = if archive_mode {
+        writer.with_archive_encryption(&key)
+    } else {
+        writer.with_per_file_encryption(&key)
+    };
+    writer.add_file("a.txt", b"first secret").unwrap();
+    writer.add_file("b.txt", b"second secret").unwrap();
+    writer.finalize().unwrap();
+}
+
+#[test]
+fn test_seeded_rng_gives_identical_ciphertext() {
+    for archive_mode in [false, true] {
+        let first = NamedTempFile::new().unwrap();
+        let second = NamedTempFile::new().unwrap();
+        let other_seed = NamedTempFile::new().unwrap();
+        build_seeded(first.path(), archive_mode, 7);
+        build_seeded(second.path(), archive_mode, 7);
+        build_seeded(other_seed.path(), archive_mode, 8);
+
+        let bytes = std::fs::read(first.path()).unwrap();
+        assert_eq!(bytes, std::fs::read(second.path()).unwrap());
+        assert_ne!(bytes, std::fs::read(other_seed.path()).unwrap());
+
+        let mut reader = ArchiveReader::open_encrypted(first.path(), &[0x5Au8; 32]).unwrap();
+        assert_eq!(reader.read_file("b.txt").unwrap(), b"second secret");
+    }
+}
@@ -0,0 +1,114 @@
+//! Reserved path tests
+//!
+//! `manifest.json` and everything under `.engram/` belong to the format: the
+//! writer only creates them through dedicated APIs, and `list_user_files`
+//! leaves them out.
+
+use engram_rs::{
+    train_dictionary, ArchiveEditor, ArchiveReader, ArchiveWriter, EngramError,
+    ZSTD_DICTIONARY_PATH,
+};
+use tempfile::NamedTempFile;
+
+#[test]
+fn test_add_file_rejects_reserved_paths() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+
+    for path in ["manifest.json", ".engram/dict", ".engram\\future.bin"] {
+        let result = writer.add_file(path, b"junk");
+        assert!(
+            matches!(result, Err(EngramError::ReservedPath(_))),
+            "{} should be reserved, got {:?}",
+            path,
+            result
+        );
+    }
+    assert!(matches!(
+        writer.add_directory(".engram"),
+        Err(EngramError::ReservedPath(_))
+    ));
+
+    // Similar names stay available to applications
+    writer.add_file("docs/manifest.json", b"{}").unwrap();
+    writer.add_file("engram/notes.txt", b"notes").unwrap();
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), 2);
+}
+
+#[test]
+fn test_add_manifest_twice_replaces() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("data.txt", b"data").unwrap();
+    writer
+        .add_manifest(&serde_json::json!({"name": "first"}))
+        .unwrap();
+    writer
+        .add_manifest(&serde_json::json!({"name": "second"}))
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let manifests = reader
+        .list_files()
+        .iter()
+        .filter(|path| *path == "manifest.json")
+        .count();
+    assert_eq!(manifests, 1);
+    assert_eq!(reader.read_manifest().unwrap().unwrap()["name"], "second");
+}
+
+#[test]
+fn test_list_user_files_skips_reserved_entries() {
+    let samples: Vec<Vec<u8>> = (0..200)
+        .map(|i| {
+            format!(
+                "{{\"id\": {}, \"kind\": \"sample\", \"tags\": [\"a\", \"b\"]}}",
+                i
+            )
+            .into_bytes()
+        })
+        .collect();
+    let sample_refs: Vec<&[u8]> = samples.iter().map(|s| s.as_slice()).collect();
+    let dictionary = train_dictionary(&sample_refs, 1024).unwrap();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_zstd_dictionary(dictionary);
+    writer.add_file("a.json", &samples[0]).unwrap();
+    writer.add_file("b.json", &samples[1]).unwrap();
+    writer
+        .add_manifest(&serde_json::json!({"name": "app"}))
+        .unwrap();
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(reader.contains(ZSTD_DICTIONARY_PATH));
+    assert!(reader.contains("manifest.json"));
+
+    let mut user_files = reader.list_user_files();
+    user_files.sort();
+    assert_eq!(user_files, vec!["a.json", "b.json"]);
+}
+
+#[test]
+fn test_editor_rename_to_reserved_path_rejected() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("config.json", b"{}").unwrap();
+    writer.finalize().unwrap();
+
+    let mut editor = ArchiveEditor::open(temp_file.path()).unwrap();
+    assert!(matches!(
+        editor.rename("config.json", "manifest.json"),
+        Err(EngramError::ReservedPath(_))
+    ));
+    assert!(matches!(
+        editor.rename("config.json", ".engram/config.json"),
+        Err(EngramError::ReservedPath(_))
+    ));
+}
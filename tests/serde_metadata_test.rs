@@ -0,0 +1,154 @@
+//! Serde metadata tests
+//!
+//! Covers serialization of the format structs (`EntryInfo`, `FileHeader`,
+//! `EndRecord`, `CompressionMethod`, `EncryptionMode`) and the JSON shape of
+//! `ArchiveReader::metadata_json`, which tools depend on.
+
+use engram_rs::archive::EndRecord;
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, CompressionMethod, EncryptionMode, EntryInfo, FileHeader,
+};
+use serde_json::json;
+use std::fs::File;
+use tempfile::NamedTempFile;
+
+/// Helper: Deterministic archive with a stored file, a deduplicated copy, a
+/// compressed file and a directory
+fn sample_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_fixed_time(1_700_000_000)
+        .with_content_version(3)
+        .with_label(*b"inventory-test\0\0")
+        .with_dedup();
+    writer
+        .add_file_with_compression("config.toml", b"quality = 1\n", CompressionMethod::None)
+        .unwrap();
+    writer
+        .add_file_with_compression("copy.toml", b"quality = 1\n", CompressionMethod::None)
+        .unwrap();
+    writer
+        .add_file_with_compression("notes.txt", b"plain notes\n", CompressionMethod::None)
+        .unwrap();
+    writer.add_directory("empty").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Serialize, deserialize and serialize again, checking both JSON
+/// forms agree
+fn round_trip<T>(value: &T) -> T
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    let json = serde_json::to_value(value).unwrap();
+    let parsed: T = serde_json::from_value(json.clone()).unwrap();
+    assert_eq!(serde_json::to_value(&parsed).unwrap(), json);
+    parsed
+}
+
+#[test]
+fn test_compression_method_serializes_as_name() {
+    for (method, name) in [
+        (CompressionMethod::None, "none"),
+        (CompressionMethod::Lz4, "lz4"),
+        (CompressionMethod::Zstd, "zstd"),
+        (CompressionMethod::Deflate, "deflate"),
+        (CompressionMethod::Unknown(9), "unknown(9)"),
+    ] {
+        assert_eq!(serde_json::to_value(method).unwrap(), json!(name));
+        assert_eq!(method.to_string(), name);
+        assert_eq!(round_trip(&method), method);
+        assert_eq!(name.parse::<CompressionMethod>().unwrap(), method);
+    }
+    for invalid in ["ZSTD", "gzip", "unknown(300)", "unknown()"] {
+        assert!(
+            serde_json::from_value::<CompressionMethod>(json!(invalid)).is_err(),
+            "{}",
+            invalid
+        );
+    }
+    assert!(serde_json::from_value::<CompressionMethod>(json!(2)).is_err());
+}
+
+#[test]
+fn test_encryption_mode_serializes_as_name() {
+    for (mode, name) in [
+        (EncryptionMode::None, "none"),
+        (EncryptionMode::Archive, "archive"),
+        (EncryptionMode::PerFile, "per-file"),
+    ] {
+        assert_eq!(serde_json::to_value(mode).unwrap(), json!(name));
+        assert_eq!(round_trip(&mode), mode);
+    }
+}
+
+#[test]
+fn test_format_structs_round_trip() {
+    let temp_file = sample_archive();
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    let header: FileHeader = round_trip(reader.header());
+    assert_eq!(header.entry_count, 4);
+    assert_eq!(header.content_version, 3);
+    assert_eq!(header.label, reader.header().label);
+    assert_eq!(header.header_crc, reader.header().compute_crc());
+
+    for path in reader.list_files() {
+        let entry = reader.get_entry(path).unwrap();
+        let parsed: EntryInfo = round_trip(entry);
+        assert_eq!(parsed.path, entry.path);
+        assert_eq!(parsed.data_offset, entry.data_offset);
+        assert_eq!(parsed.crc32, entry.crc32);
+        assert_eq!(parsed.compression, entry.compression);
+        assert_eq!(parsed.flags, entry.flags);
+    }
+
+    let end_record = EndRecord::read_from_end(File::open(temp_file.path()).unwrap()).unwrap();
+    let parsed: EndRecord = round_trip(&end_record);
+    assert_eq!(parsed.archive_crc32, end_record.archive_crc32);
+    assert_eq!(parsed.entry_count, 4);
+}
+
+#[test]
+fn test_metadata_json_matches_golden_file() {
+    let temp_file = sample_archive();
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let actual = reader.metadata_json().unwrap();
+
+    let golden: serde_json::Value =
+        serde_json::from_str(include_str!("golden/metadata.json")).unwrap();
+    assert_eq!(
+        actual,
+        golden,
+        "metadata_json changed shape; actual:\n{}",
+        serde_json::to_string_pretty(&actual).unwrap()
+    );
+
+    // Totals count the shared payload once
+    assert_eq!(actual["totals"]["entries"], 4);
+    assert_eq!(actual["totals"]["files"], 3);
+    assert_eq!(actual["totals"]["uncompressed_size"], 36);
+    assert_eq!(actual["totals"]["stored_size"], 24);
+}
+
+#[test]
+fn test_metadata_json_lazy_and_encrypted() {
+    let key = [0x42u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_per_file_encryption(&key);
+    writer.add_file("secret.txt", b"secret").unwrap();
+    writer.finalize().unwrap();
+
+    // Readable without the key or a parsed directory
+    let reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    let json = reader.metadata_json().unwrap();
+    assert_eq!(json["encryption_mode"], "per-file");
+    assert_eq!(json["entries"][0]["path"], "secret.txt");
+    let entry: EntryInfo = serde_json::from_value(json["entries"][0].clone()).unwrap();
+    assert_eq!(entry.uncompressed_size, 6);
+    assert!(entry.compressed_size > 6);
+}
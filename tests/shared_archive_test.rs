@@ -0,0 +1,94 @@
+//! SharedArchive tests
+//!
+//! One archive, opened once, serves reads from many threads through an `Arc`.
+
+use engram_rs::{ArchiveWriter, EngramError, SharedArchive};
+use std::sync::Arc;
+use std::thread;
+use tempfile::NamedTempFile;
+
+const THREAD_COUNT: usize = 100;
+const FILE_COUNT: usize = 100;
+
+/// Helper: Content of file `i`, large enough to be compressed
+fn content(i: usize) -> Vec<u8> {
+    format!("shared archive file {}\n", i)
+        .repeat(200)
+        .into_bytes()
+}
+
+/// Helper: Archive with FILE_COUNT files, optionally per-file encrypted
+fn create_archive(key: Option<&[u8; 32]>) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    if let Some(key) = key {
+        writer = writer.with_per_file_encryption(key);
+    }
+    for i in 0..FILE_COUNT {
+        writer
+            .add_file(&format!("file{}.txt", i), &content(i))
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_shared_archive_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<SharedArchive>();
+}
+
+#[test]
+fn test_100_threads_share_one_archive() {
+    let temp_file = create_archive(None);
+    let archive = Arc::new(SharedArchive::open(temp_file.path()).unwrap());
+    assert_eq!(archive.entry_count(), FILE_COUNT);
+
+    let handles: Vec<_> = (0..THREAD_COUNT)
+        .map(|t| {
+            let archive = Arc::clone(&archive);
+            thread::spawn(move || {
+                for n in 0..FILE_COUNT {
+                    let i = (n + t) % FILE_COUNT;
+                    let data = archive.read_file(&format!("file{}.txt", i)).unwrap();
+                    assert_eq!(data, content(i), "Mismatch for file {}", i);
+                }
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    // Every thread used the same instance
+    assert_eq!(Arc::strong_count(&archive), 1);
+}
+
+#[test]
+fn test_shared_archive_encrypted() {
+    let key = [0x42u8; 32];
+    let temp_file = create_archive(Some(&key));
+    let archive = SharedArchive::open_encrypted(temp_file.path(), &key).unwrap();
+
+    thread::scope(|scope| {
+        for t in 0..8 {
+            let archive = &archive;
+            scope.spawn(move || {
+                for i in (t..FILE_COUNT).step_by(8) {
+                    assert_eq!(
+                        archive.read_file(&format!("file{}.txt", i)).unwrap(),
+                        content(i)
+                    );
+                }
+            });
+        }
+    });
+
+    assert!(archive.contains("file7.txt"));
+    assert_eq!(archive.list_files().len(), FILE_COUNT);
+    assert!(matches!(
+        archive.read_file("missing.txt"),
+        Err(EngramError::FileNotFound(_))
+    ));
+}
@@ -408,3 +408,99 @@ fn test_signature_without_signer_name() {
     // Check that signer is None
     assert!(manifest.signatures[0].signer.is_none());
 }
+
+/// Helper: Manifest signed by `signing_key`, with its timestamp set to `timestamp`
+fn manifest_signed_at(signing_key: &SigningKey, timestamp: u64) -> Manifest {
+    let mut manifest = Manifest::new(
+        "window".to_string(),
+        "Window Test".to_string(),
+        Author::new("Test Author"),
+        "1.0.0".to_string(),
+    );
+    manifest.sign(signing_key, None).unwrap();
+    // The timestamp is not part of the signed hash
+    manifest.signatures[0].timestamp = timestamp;
+    manifest
+}
+
+#[test]
+fn test_signature_window_rejects_future_timestamp() {
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let manifest = manifest_signed_at(&signing_key, now + 86_400);
+
+    // Cryptographically valid, but dated tomorrow
+    assert_eq!(manifest.verify_signatures().unwrap(), vec![true]);
+    assert_eq!(manifest.verify_signatures_within(None, Some(now)).unwrap(), vec![false]);
+    assert_eq!(manifest.verify_signatures_within(None, None).unwrap(), vec![true]);
+}
+
+#[test]
+fn test_signature_window_rejects_expired_signature() {
+    let signing_key = SigningKey::generate(&mut OsRng);
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let ninety_days = 90 * 86_400;
+
+    let mut manifest = manifest_signed_at(&signing_key, now - 2 * ninety_days);
+    let second_key = SigningKey::generate(&mut OsRng);
+    manifest.sign(&second_key, Some("Recent Signer".to_string())).unwrap();
+
+    assert_eq!(manifest.verify_signatures().unwrap(), vec![true, true]);
+    assert_eq!(
+        manifest.verify_signatures_within(Some(now - ninety_days), Some(now + 60)).unwrap(),
+        vec![false, true]
+    );
+
+    // Window bounds are inclusive
+    let signed_at = manifest.signatures[0].timestamp;
+    assert_eq!(
+        manifest.verify_signatures_within(Some(signed_at), Some(signed_at)).unwrap(),
+        vec![true, false]
+    );
+}
+
+#[test]
+fn test_verify_detailed_reports_each_signer() {
+    let alice = SigningKey::generate(&mut OsRng);
+    let bob = SigningKey::generate(&mut OsRng);
+    let mallory = SigningKey::generate(&mut OsRng);
+
+    let mut manifest = Manifest::new(
+        "detailed".to_string(),
+        "Detailed".to_string(),
+        Author::new("Test Author"),
+        "1.0.0".to_string(),
+    );
+    manifest.sign(&alice, Some("Alice".to_string())).unwrap();
+    manifest.sign(&bob, None).unwrap();
+    manifest.sign(&mallory, Some("Mallory".to_string())).unwrap();
+    // Corrupt Mallory's signature
+    let corrupted = &mut manifest.signatures[2].signature;
+    let flipped = if corrupted.starts_with('0') { "1" } else { "0" };
+    corrupted.replace_range(..1, flipped);
+
+    let detailed = manifest.verify_detailed().unwrap();
+    assert_eq!(detailed.len(), 3);
+    let expected = [
+        (Some("Alice"), &alice, true),
+        (None, &bob, true),
+        (Some("Mallory"), &mallory, false),
+    ];
+    for (index, (result, (signer, key, valid))) in detailed.iter().zip(expected).enumerate() {
+        assert_eq!(result.index, index);
+        assert_eq!(result.signer.as_deref(), signer);
+        assert_eq!(result.public_key, hex::encode(key.verifying_key().to_bytes()));
+        assert_eq!(result.algorithm, "ed25519");
+        assert_eq!(result.valid, valid);
+        assert_eq!(result.timestamp, manifest.signatures[index].timestamp);
+    }
+
+    // The boolean form agrees
+    assert_eq!(manifest.verify_signatures().unwrap(), vec![true, true, false]);
+}
@@ -0,0 +1,120 @@
+//! Sorted central directory tests
+//!
+//! Covers `ArchiveWriter::with_sorted_directory` and binary-search lookup.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, HEADER_FLAG_SORTED_DIRECTORY};
+use tempfile::NamedTempFile;
+
+/// Helper: Paths added out of order, including nested and mixed-case names
+fn sample_paths() -> Vec<String> {
+    let mut paths: Vec<String> = (0..200)
+        .rev()
+        .map(|i| format!("dir_{}/file_{:03}.txt", i % 5, i))
+        .collect();
+    paths.extend(
+        ["Zebra.txt", "alpha.txt", "a/b/c.txt", "a.txt", "a/b.txt", "\u{00e9}t\u{00e9}.txt"]
+            .iter()
+            .map(|p| p.to_string()),
+    );
+    paths
+}
+
+/// Helper: Archive holding `sample_paths`, each containing its own path
+fn create_archive(sorted: bool) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    if sorted {
+        writer = writer.with_sorted_directory();
+    }
+    for path in sample_paths() {
+        writer.add_file(&path, path.as_bytes()).unwrap();
+    }
+    writer.add_directory("empty").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+#[test]
+fn test_sorted_flag_roundtrip() {
+    let sorted = create_archive(true);
+    let reader = ArchiveReader::open(sorted.path()).unwrap();
+    assert!(reader.header().has_sorted_directory());
+    assert_ne!(reader.header().flags & HEADER_FLAG_SORTED_DIRECTORY, 0);
+
+    // Raw header bytes: flags live at offset 40
+    let bytes = std::fs::read(sorted.path()).unwrap();
+    let flags = u32::from_le_bytes(bytes[40..44].try_into().unwrap());
+    assert_eq!(flags & HEADER_FLAG_SORTED_DIRECTORY, HEADER_FLAG_SORTED_DIRECTORY);
+
+    let unsorted = create_archive(false);
+    let reader = ArchiveReader::open(unsorted.path()).unwrap();
+    assert!(!reader.header().has_sorted_directory());
+}
+
+#[test]
+fn test_sorted_directory_order() {
+    let temp_file = create_archive(true);
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    let files = reader.list_files();
+    assert_eq!(files.len(), sample_paths().len() + 1);
+    assert!(files.windows(2).all(|pair| pair[0] < pair[1]));
+
+    // Insertion order is kept by default
+    let unsorted = create_archive(false);
+    let reader = ArchiveReader::open_and_init(unsorted.path()).unwrap();
+    assert_eq!(reader.list_files()[0], sample_paths()[0]);
+}
+
+#[test]
+fn test_sorted_lookup() {
+    let temp_file = create_archive(true);
+    for lazy in [false, true] {
+        let mut reader = if lazy {
+            ArchiveReader::open_lazy(temp_file.path()).unwrap()
+        } else {
+            ArchiveReader::open_and_init(temp_file.path()).unwrap()
+        };
+
+        for path in sample_paths() {
+            assert_eq!(reader.read_file(&path).unwrap(), path.as_bytes());
+        }
+        assert!(reader.get_entry("empty").unwrap().is_directory());
+        assert!(reader.contains("/dir_1\\file_001.txt"));
+        assert_eq!(reader.entry_count(), sample_paths().len() + 1);
+
+        // Misses before the first, after the last and between entries
+        for missing in ["", "0.txt", "zzz.txt", "a/b/d.txt", "dir_1/file_002.txt"] {
+            assert!(reader.get_entry(missing).is_none(), "Found {}", missing);
+        }
+        // Sorting is by byte order, so lookups stay case-sensitive
+        assert!(reader.get_entry("zebra.txt").is_none());
+    }
+}
+
+#[test]
+fn test_sorted_with_dedup_and_encryption() {
+    let key = [0x11u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_archive_encryption(&key)
+            .with_dedup()
+            .with_sorted_directory();
+        writer.add_file("z_original.txt", b"same content").unwrap();
+        writer.add_file("a_copy.txt", b"same content").unwrap();
+        writer.add_file("m_other.txt", b"other content").unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    assert_eq!(
+        reader.list_files(),
+        ["a_copy.txt", "m_other.txt", "z_original.txt"]
+    );
+    assert!(reader.get_entry("a_copy.txt").unwrap().is_deduplicated());
+    assert_eq!(reader.read_file("a_copy.txt").unwrap(), b"same content");
+    assert_eq!(reader.read_file("z_original.txt").unwrap(), b"same content");
+    assert!(reader.verify_all().unwrap().is_ok());
+}
@@ -0,0 +1,85 @@
+//! SOURCE_DATE_EPOCH tests
+//!
+//! Covers the timestamps of `Manifest::new`, `Manifest::sign` and
+//! deterministic writers under `SOURCE_DATE_EPOCH`, and their explicit
+//! overrides. Setting the variable affects the whole process, so everything
+//! that depends on it runs in a single test of its own binary.
+
+use ed25519_dalek::SigningKey;
+use engram_rs::{ArchiveReader, ArchiveWriter, Author, Manifest};
+use tempfile::NamedTempFile;
+
+/// 2023-11-14 22:13:20 UTC
+const EPOCH: u64 = 1_700_000_000;
+
+/// Helper: Signed manifest JSON built with the default constructors
+fn manifest_json() -> Vec<u8> {
+    let mut manifest = Manifest::new(
+        "reproducible".to_string(),
+        "Reproducible Build".to_string(),
+        Author::new("Builder"),
+        "1.0.0".to_string(),
+    );
+    manifest.add_file("readme.txt".to_string(), b"Hello", None);
+    manifest
+        .sign(&SigningKey::from_bytes(&[7u8; 32]), None)
+        .unwrap();
+    manifest.to_json().unwrap()
+}
+
+/// Helper: Deterministic archive with a manifest, as bytes
+fn archive_bytes() -> Vec<u8> {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_deterministic_output(true);
+    writer.add_file("readme.txt", b"Hello").unwrap();
+    writer
+        .add_manifest(&serde_json::from_slice(&manifest_json()).unwrap())
+        .unwrap();
+    writer.finalize().unwrap();
+    std::fs::read(temp_file.path()).unwrap()
+}
+
+#[test]
+fn test_source_date_epoch_pins_timestamps() {
+    std::env::set_var("SOURCE_DATE_EPOCH", EPOCH.to_string());
+
+    let json = manifest_json();
+    std::thread::sleep(std::time::Duration::from_millis(1100));
+    assert_eq!(manifest_json(), json);
+    let manifest = Manifest::from_json(&json).unwrap();
+    assert_eq!(manifest.metadata.created, EPOCH);
+    assert_eq!(manifest.signatures[0].timestamp, EPOCH);
+    assert!(manifest.verify_signatures().unwrap()[0]);
+
+    let bytes = archive_bytes();
+    assert_eq!(archive_bytes(), bytes);
+    let temp_file = NamedTempFile::new().unwrap();
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    for path in reader.list_files() {
+        let entry = reader.get_entry(path).unwrap();
+        assert_eq!(entry.modified_time, EPOCH, "{}", path);
+    }
+
+    // Explicit timestamps take precedence
+    let mut manifest = Manifest::new_with_timestamp(
+        "id".to_string(),
+        "Name".to_string(),
+        Author::new("Builder"),
+        "1.0.0".to_string(),
+        42,
+    );
+    manifest
+        .sign_with_timestamp(&SigningKey::from_bytes(&[7u8; 32]), None, 43)
+        .unwrap();
+    assert_eq!(manifest.metadata.created, 42);
+    assert_eq!(manifest.signatures[0].timestamp, 43);
+
+    // Invalid values are ignored in favor of the current time
+    std::env::set_var("SOURCE_DATE_EPOCH", "not a timestamp");
+    let manifest = Manifest::from_json(&manifest_json()).unwrap();
+    assert!(manifest.metadata.created > EPOCH);
+    std::env::remove_var("SOURCE_DATE_EPOCH");
+}
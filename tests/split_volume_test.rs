@@ -0,0 +1,182 @@
+//! Split-volume archive tests
+//!
+//! Covers `ArchiveWriter::create_split` and `ArchiveReader::open_split`.
+
+use engram_rs::archive::{volume_path, LocalEntryHeader, VOLUME_HEADER_SIZE};
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod, EngramError};
+use std::path::{Path, PathBuf};
+use tempfile::TempDir;
+
+const VOLUME_SIZE: u64 = 10 * 1024 * 1024;
+const SMALL_SIZE: usize = 700 * 1024;
+const LARGE_SIZE: usize = 25 * 1024 * 1024;
+
+/// Helper: Incompressible pseudo-random bytes (xorshift)
+fn noise(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+/// Helper: Files written to the split archive
+fn files() -> Vec<(String, Vec<u8>)> {
+    let mut files: Vec<(String, Vec<u8>)> = (0..30)
+        .map(|i| (format!("small/{:02}.bin", i), noise(i, SMALL_SIZE)))
+        .collect();
+    files.insert(12, ("large.bin".to_string(), noise(99, LARGE_SIZE)));
+    files.push(("notes.txt".to_string(), b"end of set".to_vec()));
+    files
+}
+
+/// Helper: Write the split archive and return its volume paths
+fn create_split(base: &Path, files: &[(String, Vec<u8>)]) -> Vec<PathBuf> {
+    let mut writer = ArchiveWriter::create_split(base, VOLUME_SIZE).unwrap();
+    for (path, data) in files {
+        writer
+            .add_file_with_compression(path, data, CompressionMethod::None)
+            .unwrap();
+    }
+    writer.finalize().unwrap();
+
+    (1..)
+        .map(|n| volume_path(base, n))
+        .take_while(|path| path.exists())
+        .collect()
+}
+
+#[test]
+fn test_split_roundtrip() {
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("media.eng");
+    let files = files();
+    let volumes = create_split(&base, &files);
+
+    // ~46MB of data in 10MB volumes
+    assert_eq!(volumes.len(), 5);
+    assert!(!base.exists());
+    let body_lens: Vec<u64> = volumes
+        .iter()
+        .map(|path| std::fs::metadata(path).unwrap().len())
+        .inspect(|&len| assert!(len <= VOLUME_SIZE))
+        .map(|len| len - VOLUME_HEADER_SIZE as u64)
+        .collect();
+
+    let mut reader = ArchiveReader::open_split(&volumes[0]).unwrap();
+    reader.initialize().unwrap();
+    assert_eq!(reader.entry_count(), files.len());
+
+    // Entries that fit in a volume are never split; the large one spans several
+    let volume_of = |offset: u64| {
+        let mut end = 0;
+        body_lens
+            .iter()
+            .position(|len| {
+                end += len;
+                offset < end
+            })
+            .unwrap()
+    };
+    for (path, data) in &files {
+        let entry = reader.get_entry(path).unwrap();
+        let header_size =
+            LocalEntryHeader::new(0, 0, 0, 0, entry.compression, path.clone()).header_size() as u64;
+        let first = volume_of(entry.data_offset);
+        let last = volume_of(entry.data_offset + header_size + entry.compressed_size - 1);
+        if data.len() == LARGE_SIZE {
+            assert!(last - first >= 2, "{} should span several volumes", path);
+        } else {
+            assert_eq!(first, last, "{} was split across volumes", path);
+        }
+    }
+
+    for (path, data) in &files {
+        assert_eq!(
+            &reader.read_file(path).unwrap(),
+            data,
+            "Mismatch for {}",
+            path
+        );
+    }
+    assert!(reader.verify_all().unwrap().is_ok());
+}
+
+#[test]
+fn test_split_missing_volume() {
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("media.eng");
+    let volumes = create_split(&base, &files());
+
+    std::fs::remove_file(&volumes[2]).unwrap();
+    match ArchiveReader::open_split(&volumes[0]) {
+        Err(EngramError::MissingVolume(path)) => assert!(path.ends_with("media.eng.003")),
+        other => panic!("Expected MissingVolume, got {:?}", other.err()),
+    }
+
+    // The final volume holds the central directory
+    let volumes = create_split(&base, &files());
+    std::fs::remove_file(volumes.last().unwrap()).unwrap();
+    let err = ArchiveReader::open_split(&volumes[0]).err().unwrap();
+    assert!(err.to_string().contains("media.eng.005"), "{}", err);
+}
+
+#[test]
+fn test_split_out_of_order_volumes() {
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("media.eng");
+    let volumes = create_split(&base, &files());
+
+    // Swap the second and third volumes
+    let swap = dir.path().join("swap");
+    std::fs::rename(&volumes[1], &swap).unwrap();
+    std::fs::rename(&volumes[2], &volumes[1]).unwrap();
+    std::fs::rename(&swap, &volumes[2]).unwrap();
+
+    let err = ArchiveReader::open_split(&volumes[0]).err().unwrap();
+    assert!(matches!(err, EngramError::InvalidFormat(_)));
+    assert!(err.to_string().contains("media.eng.002"), "{}", err);
+
+    let err = ArchiveReader::open_split(&volumes[1]).err().unwrap();
+    assert!(matches!(err, EngramError::PathError(_)));
+}
+
+#[test]
+fn test_split_archive_encrypted() {
+    let key = [0x5Au8; 32];
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("secure.eng");
+    let data = noise(7, 3 * 1024 * 1024);
+    {
+        let mut writer = ArchiveWriter::create_split(&base, 1024 * 1024)
+            .unwrap()
+            .with_archive_encryption(&key);
+        writer.add_file("secret.bin", &data).unwrap();
+        writer.add_file("note.txt", b"split and encrypted").unwrap();
+        writer.finalize().unwrap();
+    }
+    assert!(volume_path(&base, 4).exists());
+
+    let mut reader = ArchiveReader::open_split(volume_path(&base, 1))
+        .unwrap()
+        .with_decryption_key(&key);
+    reader.initialize().unwrap();
+    assert_eq!(reader.read_file("secret.bin").unwrap(), data);
+    assert_eq!(
+        reader.read_file("note.txt").unwrap(),
+        b"split and encrypted"
+    );
+}
+
+#[test]
+fn test_split_rejects_tiny_volumes() {
+    let dir = TempDir::new().unwrap();
+    assert!(matches!(
+        ArchiveWriter::create_split(dir.path().join("tiny.eng"), 1024),
+        Err(EngramError::InvalidOptions(_))
+    ));
+}
@@ -0,0 +1,106 @@
+//! Stored path validation tests
+//!
+//! Covers the checks on central directory paths when reading: control
+//! characters and data in the padding after a path fail with `PathError`
+//! unless `ArchiveReader::with_lenient_paths` is enabled.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, EngramError, CD_ENTRY_SIZE};
+use tempfile::NamedTempFile;
+
+/// Path buffer offset within a central directory entry
+const PATH_OFFSET: usize = 44;
+
+/// Helper: Archive holding `listing.txt`, with its central directory entry
+/// patched by `patch`
+fn crafted_archive(patch: impl FnOnce(&mut [u8])) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    writer.add_file("listing.txt", b"content").unwrap();
+    writer.finalize().unwrap();
+
+    let cd_offset = ArchiveReader::open_and_init(temp_file.path())
+        .unwrap()
+        .header()
+        .central_directory_offset as usize;
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    patch(&mut bytes[cd_offset..cd_offset + CD_ENTRY_SIZE]);
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+    temp_file
+}
+
+/// Helper: Replace the first bytes of the stored path, keeping its length
+fn spoofed_path(entry: &mut [u8]) {
+    entry[PATH_OFFSET..PATH_OFFSET + 5].copy_from_slice(b"\x1b[31m");
+}
+
+#[test]
+fn test_escape_sequence_in_path_rejected() {
+    let temp_file = crafted_archive(spoofed_path);
+
+    let err = ArchiveReader::open_and_init(temp_file.path())
+        .err()
+        .unwrap();
+    match err.root_cause() {
+        // Reported escaped, never as a raw escape sequence
+        EngramError::PathError(message) => {
+            assert!(message.contains("\\u{1b}"), "{}", message);
+            assert!(!message.contains('\x1b'));
+        }
+        other => panic!("{:?}", other),
+    }
+
+    // Lazy readers check each entry as it is parsed
+    let reader = ArchiveReader::open_lazy(temp_file.path()).unwrap();
+    let paths: Vec<_> = reader.iter_files().unwrap().collect();
+    assert!(matches!(
+        paths[0].as_ref().unwrap_err().root_cause(),
+        EngramError::PathError(_)
+    ));
+}
+
+#[test]
+fn test_non_zero_padding_rejected() {
+    let temp_file = crafted_archive(|entry| entry[PATH_OFFSET + 200] = 0xAA);
+
+    let err = ArchiveReader::open_and_init(temp_file.path())
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err.root_cause(), EngramError::PathError(_)),
+        "{:?}",
+        err
+    );
+}
+
+#[test]
+fn test_lenient_paths_read_non_conforming_archives() {
+    let temp_file = crafted_archive(|entry| {
+        spoofed_path(entry);
+        entry[PATH_OFFSET + 200] = 0xAA;
+    });
+
+    let mut reader = ArchiveReader::open(temp_file.path())
+        .unwrap()
+        .with_lenient_paths(true);
+    reader.initialize().unwrap();
+    assert_eq!(reader.list_files(), ["\x1b[31mng.txt"]);
+    // The LOCA header still holds the original path
+    assert!(reader.read_file("\x1b[31mng.txt").is_err());
+}
+
+#[test]
+fn test_writer_rejects_control_characters() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    for path in ["\x1b[2Jclear.txt", "line\nbreak.txt", "tab\there.txt"] {
+        assert!(matches!(
+            writer.add_file(path, b"data"),
+            Err(EngramError::PathError(_))
+        ));
+    }
+    writer.add_file("plain name.txt", b"data").unwrap();
+    writer.finalize().unwrap();
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.list_files(), ["plain name.txt"]);
+}
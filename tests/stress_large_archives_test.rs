@@ -308,6 +308,199 @@ fn test_500mb_archive() {
     println!("\n✅ 500MB archive test complete!");
 }
 
+/// Helper: Peak resident set size of this process in KB (Linux only)
+#[cfg(target_os = "linux")]
+fn peak_rss_kb() -> u64 {
+    let status = std::fs::read_to_string("/proc/self/status").unwrap();
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+        .unwrap()
+}
+
+/// Helper: Current resident set size of this process in KB (Linux only)
+#[cfg(target_os = "linux")]
+fn resident_kb() -> u64 {
+    let status = std::fs::read_to_string("/proc/self/status").unwrap();
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+        .unwrap()
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+#[ignore] // Run manually: cargo test test_100k_entries_lazy_vs_eager -- --ignored --nocapture
+fn test_100k_entries_lazy_vs_eager() {
+    const COUNT: usize = 100_000;
+    println!("\n🚀 Creating archive with 100,000 entries (unsorted directory)...");
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+    {
+        let mut writer = ArchiveWriter::create(path).unwrap();
+        for i in 0..COUNT {
+            let filename = format!("shard{:02}/entry{:06}.txt", i % 64, i);
+            writer
+                .add_file(&filename, format!("entry {}", i).as_bytes())
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+    let targets: Vec<String> = (0..COUNT)
+        .step_by(997)
+        .map(|i| format!("shard{:02}/entry{:06}.txt", i % 64, i))
+        .collect();
+
+    // Lazy first, so each mode's growth in resident memory is its own
+    let baseline_kb = resident_kb();
+    let start = Instant::now();
+    let mut lazy = ArchiveReader::open_lazy(path).unwrap();
+    let lazy_open = start.elapsed();
+    let lazy_reads: Vec<Vec<u8>> = targets
+        .iter()
+        .map(|target| lazy.read_file(target).unwrap())
+        .collect();
+    let lazy_kb = resident_kb().saturating_sub(baseline_kb);
+
+    let baseline_kb = resident_kb();
+    let start = Instant::now();
+    let mut eager = ArchiveReader::open_and_init(path).unwrap();
+    let eager_open = start.elapsed();
+    let eager_reads: Vec<Vec<u8>> = targets
+        .iter()
+        .map(|target| eager.read_file(target).unwrap())
+        .collect();
+    let eager_kb = resident_kb().saturating_sub(baseline_kb);
+
+    assert_eq!(lazy_reads, eager_reads);
+    let (lazy_entry, eager_entry) = (
+        lazy.get_entry(&targets[7]).unwrap(),
+        eager.get_entry(&targets[7]).unwrap(),
+    );
+    assert_eq!(lazy_entry.data_offset, eager_entry.data_offset);
+    assert_eq!(lazy_entry.crc32, eager_entry.crc32);
+    let streamed = lazy.iter_files().unwrap().map(Result::unwrap);
+    assert!(streamed.eq(eager.list_files().iter().cloned()));
+
+    println!("  Lazy:  open {:?}, +{} KB", lazy_open, lazy_kb);
+    println!("  Eager: open {:?}, +{} KB", eager_open, eager_kb);
+    assert!(lazy_open < eager_open);
+    assert!(lazy_kb <= eager_kb);
+
+    println!("\n✅ Lazy and eager readers agree on 100,000 entries");
+}
+
+#[test]
+#[cfg(all(target_os = "linux", feature = "vfs"))]
+#[ignore] // Run manually: cargo test test_1gb_database_vfs_memory -- --ignored
+fn test_1gb_database_vfs_memory() {
+    println!("\n🚀 Creating 1GB SQLite database...");
+    let db_file = NamedTempFile::new().unwrap();
+    {
+        let conn = rusqlite::Connection::open(db_file.path()).unwrap();
+        conn.execute("CREATE TABLE blobs (id INTEGER PRIMARY KEY, data BLOB)", [])
+            .unwrap();
+        for _ in 0..1024 {
+            conn.execute("INSERT INTO blobs (data) VALUES (zeroblob(1048576))", [])
+                .unwrap();
+        }
+    }
+    let db_size = std::fs::metadata(db_file.path()).unwrap().len();
+    println!("  ✓ Database: {} MB", db_size / 1024 / 1024);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+    let mut writer = ArchiveWriter::create(path).unwrap();
+    writer
+        .add_file_from_disk("analytics.db", db_file.path())
+        .unwrap();
+    writer.finalize().unwrap();
+    drop(db_file);
+
+    // Reset the high-water mark left by building the archive
+    std::fs::write("/proc/self/clear_refs", "5").unwrap();
+    let baseline_kb = peak_rss_kb();
+
+    println!("\n📖 Opening database through the VFS...");
+    let mut vfs = engram_rs::VfsReader::open(path).unwrap();
+    let conn = vfs.open_database("analytics.db").unwrap();
+    let count: i64 = conn
+        .query_row("SELECT COUNT(*) FROM blobs", [], |row| row.get(0))
+        .unwrap();
+    assert_eq!(count, 1024);
+
+    let peak_kb = peak_rss_kb();
+    println!(
+        "  Peak RSS: {} MB (baseline {} MB)",
+        peak_kb / 1024,
+        baseline_kb / 1024
+    );
+    assert!(
+        (peak_kb - baseline_kb) * 1024 < db_size / 4,
+        "extraction used {} KB for a {} byte database",
+        peak_kb - baseline_kb,
+        db_size
+    );
+
+    println!("\n✅ 1GB database extracted without buffering it in memory");
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+#[ignore] // Run manually: cargo test test_1gb_verify_all_memory -- --ignored
+fn test_1gb_verify_all_memory() {
+    use std::io::Write;
+
+    println!("\n🚀 Creating 1GB source file...");
+    let source = NamedTempFile::new().unwrap();
+    {
+        let mut out = std::io::BufWriter::new(source.as_file());
+        let chunk: Vec<u8> = (0..1024 * 1024).map(|i| (i % 251) as u8).collect();
+        for _ in 0..1024 {
+            out.write_all(&chunk).unwrap();
+        }
+    }
+    let source_size = std::fs::metadata(source.path()).unwrap().len();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+    let mut writer = ArchiveWriter::create(path).unwrap();
+    writer.add_file_from_disk("big.bin", source.path()).unwrap();
+    writer.add_file("small.txt", b"small").unwrap();
+    writer.finalize().unwrap();
+    drop(source);
+
+    // Reset the high-water mark left by building the archive
+    std::fs::write("/proc/self/clear_refs", "5").unwrap();
+    let baseline_kb = peak_rss_kb();
+
+    println!("\n📖 Verifying archive...");
+    let mut reader = ArchiveReader::open_and_init(path).unwrap();
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(report.bytes_verified, source_size + 5);
+
+    let peak_kb = peak_rss_kb();
+    println!(
+        "  Verified {} MB in {:?}, peak RSS {} MB (baseline {} MB)",
+        report.bytes_verified / 1024 / 1024,
+        report.elapsed,
+        peak_kb / 1024,
+        baseline_kb / 1024
+    );
+    assert!(
+        (peak_kb - baseline_kb) * 1024 < source_size / 4,
+        "verification used {} KB for a {} byte entry",
+        peak_kb - baseline_kb,
+        source_size
+    );
+
+    println!("\n✅ 1GB archive verified without buffering entries in memory");
+}
+
 #[test]
 fn test_many_small_files_baseline() {
     // Non-ignored baseline test with 1000 files
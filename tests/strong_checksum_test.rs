@@ -0,0 +1,170 @@
+//! SHA-256 entry checksum tests
+//!
+//! Covers `ArchiveWriter::with_strong_checksums`, verification in `read_file`,
+//! and compatibility with readers that ignore the reserved CD bytes.
+
+use engram_rs::archive::{EndRecord, END_RECORD_SIZE};
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, CD_ENTRY_SIZE, ENTRY_FLAG_SHA256,
+};
+use sha2::{Digest, Sha256};
+use std::io::Cursor;
+use tempfile::NamedTempFile;
+
+/// Offset of the SHA-256 prefix within a central directory entry
+const CD_SHA256_OFFSET: usize = 304;
+
+/// Helper: Sample files covering raw, compressed and empty payloads
+fn sample_files() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("small.txt", b"tiny".to_vec()),
+        (
+            "docs/large.txt",
+            "strong checksum ".repeat(2000).into_bytes(),
+        ),
+        ("empty.bin", Vec::new()),
+    ]
+}
+
+/// Helper: Write the sample files with or without strong checksums
+fn write_archive(strong: bool) -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_strong_checksums(strong);
+    for (path, data) in sample_files() {
+        writer.add_file(path, &data).unwrap();
+    }
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Byte range of each central directory entry in the archive
+fn cd_entry_offsets(bytes: &[u8]) -> Vec<usize> {
+    let endr = EndRecord::read_from(Cursor::new(&bytes[bytes.len() - END_RECORD_SIZE..])).unwrap();
+    let start = endr.central_directory_offset as usize;
+    (0..endr.entry_count as usize)
+        .map(|i| start + i * CD_ENTRY_SIZE)
+        .collect()
+}
+
+#[test]
+fn test_strong_checksums_roundtrip() {
+    let temp_file = write_archive(true);
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    for (path, data) in sample_files() {
+        let entry = reader.get_entry(path).unwrap();
+        assert_ne!(entry.flags & ENTRY_FLAG_SHA256, 0);
+        let digest = Sha256::digest(&data);
+        assert_eq!(entry.sha256.unwrap(), digest[..16]);
+        assert_eq!(reader.read_file(path).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_without_strong_checksums() {
+    let temp_file = write_archive(false);
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    for (path, data) in sample_files() {
+        let entry = reader.get_entry(path).unwrap();
+        assert_eq!(entry.flags & ENTRY_FLAG_SHA256, 0);
+        assert!(entry.sha256.is_none());
+        assert_eq!(reader.read_file(path).unwrap(), data);
+    }
+
+    // Reserved bytes stay zeroed
+    let bytes = std::fs::read(temp_file.path()).unwrap();
+    for offset in cd_entry_offsets(&bytes) {
+        let start = offset + CD_SHA256_OFFSET;
+        assert!(bytes[start..offset + CD_ENTRY_SIZE].iter().all(|&b| b == 0));
+    }
+}
+
+#[test]
+fn test_hash_mismatch_detected() {
+    let temp_file = write_archive(true);
+
+    // Flip one digest byte in every CD entry; CRC32 still matches the payload
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    for offset in cd_entry_offsets(&bytes) {
+        bytes[offset + CD_SHA256_OFFSET] ^= 0xFF;
+    }
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let result = reader.read_file("docs/large.txt");
+    assert!(matches!(result, Err(EngramError::HashMismatch { .. })));
+}
+
+#[test]
+fn test_old_reader_compatibility() {
+    let temp_file = write_archive(true);
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+
+    // Readers predating the flag treat bit 3 and the trailing 16 bytes as
+    // reserved. Erasing them must leave an archive that reads identically.
+    for offset in cd_entry_offsets(&bytes) {
+        bytes[offset + 41] &= !ENTRY_FLAG_SHA256;
+        bytes[offset + CD_SHA256_OFFSET..offset + CD_ENTRY_SIZE].fill(0);
+    }
+    let legacy_file = NamedTempFile::new().unwrap();
+    std::fs::write(legacy_file.path(), &bytes).unwrap();
+
+    let strong = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let mut legacy = ArchiveReader::open_and_init(legacy_file.path()).unwrap();
+    assert_eq!(strong.list_files(), legacy.list_files());
+
+    for (path, data) in sample_files() {
+        let a = strong.get_entry(path).unwrap().clone();
+        let b = legacy.get_entry(path).unwrap().clone();
+        assert_eq!(a.data_offset, b.data_offset);
+        assert_eq!(a.crc32, b.crc32);
+        assert_eq!(a.compression, b.compression);
+        assert!(b.sha256.is_none());
+        assert_eq!(legacy.read_file(path).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_crc_colliding_tamper_detected() {
+    // `data` followed by its own CRC32 always has the CRC32 0x2144DF1C, so
+    // the forged payload below passes the CRC check
+    let with_residue = |data: &[u8]| {
+        let mut message = data.to_vec();
+        message.extend_from_slice(&crc32fast::hash(data).to_le_bytes());
+        message
+    };
+    let genuine = with_residue(b"pay 100 to alice");
+    let forged = with_residue(b"pay 999 to mallo");
+    assert_eq!(crc32fast::hash(&genuine), crc32fast::hash(&forged));
+
+    for strong in [false, true] {
+        let temp_file = NamedTempFile::new().unwrap();
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_strong_checksums(strong);
+        writer
+            .add_file_with_compression("order.bin", &genuine, CompressionMethod::None)
+            .unwrap();
+        writer.finalize().unwrap();
+
+        let mut bytes = std::fs::read(temp_file.path()).unwrap();
+        let start = bytes
+            .windows(genuine.len())
+            .position(|window| window == genuine)
+            .unwrap();
+        bytes[start..start + forged.len()].copy_from_slice(&forged);
+        std::fs::write(temp_file.path(), &bytes).unwrap();
+
+        let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+        let result = reader.read_file("order.bin");
+        if strong {
+            assert!(matches!(result, Err(EngramError::HashMismatch { .. })));
+        } else {
+            // CRC32 alone cannot tell
+            assert_eq!(result.unwrap(), forged);
+        }
+    }
+}
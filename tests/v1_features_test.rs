@@ -1,4 +1,4 @@
-use engram_rs::{ArchiveReader, ArchiveWriter};
+use engram_rs::{ArchiveReader, ArchiveWriter, FORMAT_VERSION_MINOR};
 use tempfile::NamedTempFile;
 
 // v1.0 format constants
@@ -175,9 +175,9 @@ fn test_v1_format_version() {
         let reader = ArchiveReader::open(archive_path).unwrap();
         let header = reader.header();
 
-        // Verify version is 1.0
+        // Verify version is the current 1.x
         assert_eq!(header.version_major, 1);
-        assert_eq!(header.version_minor, 0);
+        assert_eq!(header.version_minor, FORMAT_VERSION_MINOR);
     }
 }
 
@@ -226,7 +226,7 @@ fn test_all_v1_features_combined() {
 
         assert_eq!(reader.entry_count(), 3);
         assert_eq!(reader.header().version_major, 1);
-        assert_eq!(reader.header().version_minor, 0);
+        assert_eq!(reader.header().version_minor, FORMAT_VERSION_MINOR);
 
         let small = reader.read_file("small.txt").unwrap();
         assert_eq!(small, b"Hi");
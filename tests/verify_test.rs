@@ -0,0 +1,188 @@
+//! ArchiveReader::verify_all tests
+
+use engram_rs::{
+    ArchiveReader, ArchiveWriter, Author, CompressionMethod, EngramError, EntryStatus,
+    FileMetadata, Manifest, ENTRY_FLAG_EXECUTABLE, HEADER_SIZE, VERIFY_HEADER,
+};
+use tempfile::NamedTempFile;
+
+/// Helper: Distinct content for file `i`, easy to locate in the raw archive
+fn content(i: usize) -> Vec<u8> {
+    format!("<<file {} payload>>", i).repeat(50).into_bytes()
+}
+
+/// Helper: Archive with ten stored and compressed files plus a directory
+fn create_archive() -> NamedTempFile {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    for i in 0..10 {
+        let method = if i % 2 == 0 {
+            CompressionMethod::None
+        } else {
+            CompressionMethod::Zstd
+        };
+        writer
+            .add_file_with_compression(&format!("file_{}.txt", i), &content(i), method)
+            .unwrap();
+    }
+    writer.add_directory("empty").unwrap();
+    writer.finalize().unwrap();
+    temp_file
+}
+
+/// Helper: Alter one byte of the first occurrence of `needle` in the archive
+fn corrupt(path: &std::path::Path, needle: &[u8]) {
+    let mut bytes = std::fs::read(path).unwrap();
+    let pos = bytes
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .unwrap();
+    bytes[pos + needle.len() / 2] ^= 0x01;
+    std::fs::write(path, &bytes).unwrap();
+}
+
+#[test]
+fn test_verify_clean_archive() {
+    let temp_file = create_archive();
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok());
+    assert_eq!(report.ok.len(), 11);
+    let total: usize = (0..10).map(|i| content(i).len()).sum();
+    assert_eq!(report.bytes_verified, total as u64);
+    assert_eq!(report.status("file_3.txt"), Some(EntryStatus::Ok));
+    assert_eq!(report.status("missing.txt"), None);
+}
+
+#[test]
+fn test_verify_reports_only_corrupt_entry() {
+    let temp_file = create_archive();
+    // file_4 is stored uncompressed, so its bytes appear verbatim
+    corrupt(temp_file.path(), &content(4));
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let report = reader.verify_all().unwrap();
+
+    assert_eq!(report.failed.len(), 1);
+    let (path, err) = &report.failed[0];
+    assert_eq!(path, "file_4.txt");
+    assert!(matches!(err, EngramError::CrcMismatch { path, .. } if path == "file_4.txt"));
+
+    assert_eq!(report.ok.len(), 10);
+    assert!(!report.ok.contains(&"file_4.txt".to_string()));
+    assert_eq!(report.status("file_4.txt"), Some(EntryStatus::CrcMismatch));
+}
+
+#[test]
+fn test_verify_reports_loca_mismatch_and_header_crc() {
+    let temp_file = create_archive();
+    // Corrupt the path stored in file_2's LOCA header (the CD copy comes later)
+    corrupt(temp_file.path(), b"file_2.txt");
+
+    // Corrupt the stored header CRC (bytes 12-15)
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    bytes[12] ^= 0xFF;
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let report = reader.verify_all().unwrap();
+
+    let failed: Vec<&str> = report.failed.iter().map(|(p, _)| p.as_str()).collect();
+    assert_eq!(failed, vec![VERIFY_HEADER, "file_2.txt"]);
+    let err = &report.failed[1].1;
+    assert!(matches!(err, EngramError::EntryError { path, .. } if path == "file_2.txt"));
+    assert!(matches!(err.root_cause(), EngramError::InvalidFormat(_)));
+    assert_eq!(report.ok.len(), 10);
+}
+
+#[test]
+fn test_verify_archive_encrypted() {
+    let key = [0x11u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_archive_encryption(&key);
+        writer.add_file("a.txt", &content(0)).unwrap();
+        writer.add_file("b.txt", &content(1)).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+    assert_eq!(report.ok, vec!["a.txt", "b.txt"]);
+}
+
+#[test]
+fn test_verify_reports_executable_flag_mismatch() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        let metadata = FileMetadata {
+            executable: true,
+            ..FileMetadata::default()
+        };
+        writer
+            .add_file_with_metadata("run.sh", b"#!/bin/sh\n", metadata)
+            .unwrap();
+        writer.finalize().unwrap();
+    }
+
+    // Clear the flag in the first LOCA header (flags byte at offset 33)
+    let mut bytes = std::fs::read(temp_file.path()).unwrap();
+    assert_ne!(bytes[HEADER_SIZE + 33] & ENTRY_FLAG_EXECUTABLE, 0);
+    bytes[HEADER_SIZE + 33] &= !ENTRY_FLAG_EXECUTABLE;
+    std::fs::write(temp_file.path(), &bytes).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert!(reader.get_entry("run.sh").unwrap().is_executable());
+    let report = reader.verify_all().unwrap();
+    assert_eq!(report.failed.len(), 1);
+    assert!(matches!(
+        report.failed[0].1.root_cause(),
+        EngramError::InvalidFormat(_)
+    ));
+}
+
+#[test]
+fn test_verify_checks_manifest_hashes() {
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut manifest = Manifest::new(
+            "verify".to_string(),
+            "Verify".to_string(),
+            Author::new("Test"),
+            "1.0.0".to_string(),
+        );
+        manifest.add_file("listed.txt".to_string(), &content(0), None);
+        // Hash of different content than what is stored
+        manifest.add_file("stale.txt".to_string(), &content(1), None);
+
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        writer.add_file("listed.txt", &content(0)).unwrap();
+        writer.add_file("stale.txt", &content(2)).unwrap();
+        writer.add_file("unlisted.txt", &content(3)).unwrap();
+        writer.add_directory("assets").unwrap();
+        writer
+            .add_manifest(&serde_json::to_value(&manifest).unwrap())
+            .unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let report = reader.verify_all().unwrap();
+
+    assert!(!report.is_ok());
+    assert_eq!(report.status("listed.txt"), Some(EntryStatus::Ok));
+    assert_eq!(report.status("stale.txt"), Some(EntryStatus::HashMismatch));
+    assert_eq!(
+        report.status("unlisted.txt"),
+        Some(EntryStatus::MissingFromManifest)
+    );
+    // Directories and the manifest itself need no listing
+    assert_eq!(report.status("assets"), Some(EntryStatus::Ok));
+    assert_eq!(report.status("manifest.json"), Some(EntryStatus::Ok));
+    assert_eq!(report.missing_from_manifest, vec!["unlisted.txt"]);
+}
@@ -0,0 +1,94 @@
+//! ArchiveWriterBuilder tests
+//!
+//! Options are collected and checked before the archive file is created.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod, EncryptionMode, EngramError};
+use tempfile::TempDir;
+
+const KEY: [u8; 32] = [0x21u8; 32];
+
+#[test]
+fn test_builder_rejects_conflicting_encryption() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("conflict.eng");
+
+    let result = ArchiveWriter::builder()
+        .with_archive_encryption(&KEY)
+        .with_per_file_encryption(&KEY)
+        .build(&path);
+    match result {
+        Err(EngramError::InvalidOptions(msg)) => {
+            assert!(msg.contains("cannot be combined"), "{}", msg)
+        }
+        Err(other) => panic!("Expected InvalidOptions, got: {:?}", other),
+        Ok(_) => panic!("Conflicting encryption modes were accepted"),
+    }
+    // Rejected before anything was written
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_builder_rejects_out_of_range_zstd_level() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("level.eng");
+
+    let result = ArchiveWriter::builder().with_zstd_level(1000).build(&path);
+    assert!(matches!(result, Err(EngramError::InvalidOptions(_))));
+
+    let result = ArchiveWriter::builder()
+        .with_compression(CompressionMethod::Unknown(9))
+        .build(&path);
+    assert!(matches!(result, Err(EngramError::InvalidCompression(9))));
+    assert!(!path.exists());
+}
+
+#[test]
+fn test_builder_applies_options() {
+    let temp_dir = TempDir::new().unwrap();
+    let path = temp_dir.path().join("built.eng");
+    let data = "engram builder ".repeat(1000);
+
+    let mut writer = ArchiveWriter::builder()
+        .with_per_file_encryption(&KEY)
+        .with_compression(CompressionMethod::Lz4)
+        .with_zstd_level(19)
+        .with_fixed_time(1_700_000_000)
+        .build(&path)
+        .unwrap();
+    // Text would get Zstd without a default compression
+    assert_eq!(
+        writer.add_file("notes.txt", data.as_bytes()).unwrap(),
+        CompressionMethod::Lz4
+    );
+    let summary = writer.finalize().unwrap();
+    assert_eq!(summary.encryption, EncryptionMode::PerFile);
+
+    let mut reader = ArchiveReader::open_encrypted(&path, &KEY).unwrap();
+    let entry = reader.get_entry("notes.txt").unwrap();
+    assert_eq!(entry.compression, CompressionMethod::Lz4);
+    assert_eq!(entry.modified_time, 1_700_000_000);
+    assert_eq!(reader.read_file("notes.txt").unwrap(), data.as_bytes());
+}
+
+#[test]
+fn test_zstd_level_changes_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let data: Vec<u8> = (0..20_000u32)
+        .flat_map(|i| format!("{{\"id\":{},\"v\":{}}}\n", i, i % 97).into_bytes())
+        .collect();
+
+    let mut sizes = Vec::new();
+    let mut expected = Vec::new();
+    for level in [1, 19] {
+        let path = temp_dir.path().join(format!("level{}.eng", level));
+        let mut writer = ArchiveWriter::create(&path).unwrap().with_zstd_level(level);
+        writer.add_file("data.json", &data).unwrap();
+        sizes.push(writer.finalize().unwrap().total_compressed);
+        expected.push(zstd::encode_all(data.as_slice(), level).unwrap().len() as u64);
+
+        let mut reader = ArchiveReader::open_and_init(&path).unwrap();
+        assert_eq!(reader.read_file("data.json").unwrap(), data);
+    }
+    assert_eq!(sizes, expected);
+    assert_ne!(sizes[0], sizes[1]);
+}
@@ -0,0 +1,109 @@
+//! Writer drop tests
+//!
+//! Covers what happens when an `ArchiveWriter` is dropped without
+//! `finalize`: the warning from `with_drop_warning`, and finishing the
+//! archive with `with_finalize_on_drop`.
+
+use engram_rs::{ArchiveReader, ArchiveWriter};
+use std::sync::{Arc, Mutex};
+use tempfile::NamedTempFile;
+
+/// Helper: Warning hook recording the entry counts it was called with
+fn recording_hook() -> (Arc<Mutex<Vec<usize>>>, impl FnOnce(usize) + Send + 'static) {
+    let calls = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&calls);
+    (calls, move |entries| recorded.lock().unwrap().push(entries))
+}
+
+#[test]
+fn test_drop_without_finalize_warns() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let (calls, hook) = recording_hook();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_drop_warning(hook);
+        writer.add_file("a.txt", b"a").unwrap();
+        writer.add_file("b.txt", b"b").unwrap();
+    }
+
+    assert_eq!(*calls.lock().unwrap(), [2]);
+    // Unchanged otherwise: the archive is left unfinished
+    assert!(ArchiveReader::open_and_init(temp_file.path()).is_err());
+}
+
+#[test]
+fn test_no_warning_when_finalized_or_empty() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let (calls, hook) = recording_hook();
+    let mut writer = ArchiveWriter::create(temp_file.path())
+        .unwrap()
+        .with_drop_warning(hook);
+    writer.add_file("a.txt", b"a").unwrap();
+    writer.finalize().unwrap();
+    assert!(calls.lock().unwrap().is_empty());
+
+    let (calls, hook) = recording_hook();
+    drop(
+        ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_drop_warning(hook),
+    );
+    assert!(calls.lock().unwrap().is_empty());
+}
+
+#[test]
+fn test_finalize_on_drop_produces_valid_archive() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let (calls, hook) = recording_hook();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_finalize_on_drop(true)
+            .with_drop_warning(hook);
+        writer.add_file("a.txt", b"first").unwrap();
+        writer.add_directory("dir").unwrap();
+        writer.add_file("dir/b.txt", b"second").unwrap();
+    }
+    assert!(calls.lock().unwrap().is_empty());
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), 3);
+    assert_eq!(reader.read_file("dir/b.txt").unwrap(), b"second");
+    assert!(reader.verify_all().unwrap().is_ok());
+}
+
+#[test]
+fn test_finalize_on_drop_with_archive_encryption() {
+    let key = [0x42u8; 32];
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_archive_encryption(&key)
+            .with_finalize_on_drop(true);
+        writer.add_file("secret.txt", b"secret").unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_encrypted(temp_file.path(), &key).unwrap();
+    assert_eq!(reader.read_file("secret.txt").unwrap(), b"secret");
+}
+
+#[test]
+fn test_no_finalize_on_drop_while_panicking() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path().to_path_buf();
+    let (calls, hook) = recording_hook();
+    let result = std::panic::catch_unwind(move || {
+        let mut writer = ArchiveWriter::create(&path)
+            .unwrap()
+            .with_finalize_on_drop(true)
+            .with_drop_warning(hook);
+        writer.add_file("a.txt", b"a").unwrap();
+        panic!("caller failed mid-archive");
+    });
+
+    assert!(result.is_err());
+    assert_eq!(*calls.lock().unwrap(), [1]);
+    assert!(ArchiveReader::open_and_init(temp_file.path()).is_err());
+}
@@ -0,0 +1,246 @@
+//! Writer rollback tests
+//!
+//! An entry whose write fails partway must leave no trace: the next entry is
+//! written where it would have started, and a writer that cannot undo the
+//! failure refuses to go on (`EngramError::WriterPoisoned`).
+
+use engram_rs::archive::{volume_path, LocalEntryHeader};
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod, EngramError, ProgressEvent};
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use tempfile::{NamedTempFile, TempDir};
+
+/// Helper: Incompressible pseudo-random bytes (xorshift)
+fn noise(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u8
+        })
+        .collect()
+}
+
+/// Helper: In-memory sink that can be made to fail
+///
+/// With `fail_after` set to `Some(n)`, writes accept `n` more bytes and then
+/// fail, as on a full disk. While `offline`, seeking fails too, as for a
+/// device that went away.
+#[derive(Clone, Default)]
+struct FlakySink {
+    data: Arc<Mutex<Cursor<Vec<u8>>>>,
+    fail_after: Arc<Mutex<Option<usize>>>,
+    offline: Arc<AtomicBool>,
+}
+
+impl FlakySink {
+    fn fail_after(&self, bytes: Option<usize>) {
+        *self.fail_after.lock().unwrap() = bytes;
+    }
+
+    fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::SeqCst);
+        self.fail_after(offline.then_some(0));
+    }
+
+    fn contents(&self) -> Vec<u8> {
+        self.data.lock().unwrap().get_ref().clone()
+    }
+}
+
+impl Write for FlakySink {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut fail_after = self.fail_after.lock().unwrap();
+        let len = match *fail_after {
+            Some(0) => return Err(io::Error::other("injected write failure")),
+            Some(left) => {
+                let len = left.min(buf.len());
+                *fail_after = Some(left - len);
+                len
+            }
+            None => buf.len(),
+        };
+        self.data.lock().unwrap().write(&buf[..len])
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for FlakySink {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.data.lock().unwrap().read(buf)
+    }
+}
+
+impl Seek for FlakySink {
+    fn seek(&mut self, target: SeekFrom) -> io::Result<u64> {
+        if self.offline.load(Ordering::SeqCst) {
+            return Err(io::Error::other("injected seek failure"));
+        }
+        self.data.lock().unwrap().seek(target)
+    }
+}
+
+/// Helper: Reader over archive bytes held in memory
+fn open_bytes(bytes: Vec<u8>) -> ArchiveReader {
+    let mut reader = ArchiveReader::from_reader(Cursor::new(bytes)).unwrap();
+    reader.initialize().unwrap();
+    reader
+}
+
+#[test]
+fn test_failed_write_to_stream_is_rolled_back() {
+    let sink = FlakySink::default();
+    let before = noise(1, 20_000);
+    let failing = noise(2, 100_000);
+    let after = noise(3, 30_000);
+
+    let mut writer = ArchiveWriter::from_writer(sink.clone()).unwrap();
+    writer
+        .add_file_with_compression("before.bin", &before, CompressionMethod::None)
+        .unwrap();
+
+    // The LOCA header and part of the payload reach the sink before it fails
+    sink.fail_after(Some(60_000));
+    let result = writer.add_file_with_compression("failing.bin", &failing, CompressionMethod::None);
+    assert!(matches!(result, Err(EngramError::Io(_))), "{:?}", result);
+    assert_eq!(writer.pending_entries(), 1);
+
+    // The next entry starts where the failed one did
+    sink.fail_after(None);
+    writer
+        .add_file_with_compression("after.bin", &after, CompressionMethod::None)
+        .unwrap();
+    let summary = writer.finalize().unwrap();
+    assert_eq!(summary.path, None);
+    assert_eq!(summary.total_entries, 2);
+
+    let bytes = sink.contents();
+    assert_eq!(summary.archive_size, bytes.len() as u64);
+    let before_offset = {
+        let reader = open_bytes(bytes.clone());
+        let entry = reader.get_entry("before.bin").unwrap();
+        let header = LocalEntryHeader::read_from(&bytes[entry.data_offset as usize..]).unwrap();
+        entry.data_offset + header.header_size() as u64 + entry.compressed_size
+    };
+    let mut reader = open_bytes(bytes);
+    assert_eq!(reader.list_files(), &["before.bin", "after.bin"]);
+    assert_eq!(reader.read_file("before.bin").unwrap(), before);
+    assert_eq!(reader.read_file("after.bin").unwrap(), after);
+    assert_eq!(
+        reader.get_entry("after.bin").unwrap().data_offset,
+        before_offset
+    );
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+}
+
+#[test]
+fn test_failed_write_to_file_leaves_identical_archive() {
+    let write = |path: &std::path::Path, with_failure: bool| {
+        let mut writer = ArchiveWriter::create(path)
+            .unwrap()
+            .with_fixed_time(1_700_000_000)
+            .with_dedup()
+            .with_progress(|event| {
+                if let ProgressEvent::FileFinished { path, .. } = event {
+                    if path == "failing.txt" {
+                        panic!("progress bar crashed");
+                    }
+                }
+            });
+        writer.add_file("first.txt", b"first file").unwrap();
+        if with_failure {
+            // Fails after the entry was completely written
+            let result = writer.add_file("failing.txt", &"never kept\n".repeat(500).into_bytes());
+            assert!(matches!(result, Err(EngramError::CallbackPanicked)));
+        }
+        // Same content as the failed entry: not deduplicated against it
+        writer
+            .add_file("second.txt", &"never kept\n".repeat(500).into_bytes())
+            .unwrap();
+        writer.finalize().unwrap();
+    };
+
+    let reference = NamedTempFile::new().unwrap();
+    write(reference.path(), false);
+    let rolled_back = NamedTempFile::new().unwrap();
+    write(rolled_back.path(), true);
+
+    assert_eq!(
+        std::fs::read(rolled_back.path()).unwrap(),
+        std::fs::read(reference.path()).unwrap()
+    );
+    let mut reader = ArchiveReader::open_and_init(rolled_back.path()).unwrap();
+    assert!(!reader.get_entry("second.txt").unwrap().is_deduplicated());
+    assert_eq!(
+        reader.read_file("second.txt").unwrap(),
+        "never kept\n".repeat(500).as_bytes()
+    );
+}
+
+#[test]
+fn test_failed_write_removes_new_volume() {
+    let dir = TempDir::new().unwrap();
+    let base = dir.path().join("split.eng");
+    let volume_size = 64 * 1024;
+
+    let mut writer = ArchiveWriter::create_split(&base, volume_size)
+        .unwrap()
+        .with_progress(|event| {
+            if let ProgressEvent::FileFinished { path, .. } = event {
+                if path == "failing.bin" {
+                    panic!("progress bar crashed");
+                }
+            }
+        });
+    writer
+        .add_file_with_compression("a.bin", &noise(1, 40_000), CompressionMethod::None)
+        .unwrap();
+    // Does not fit next to a.bin, so it starts the second volume
+    let result =
+        writer.add_file_with_compression("failing.bin", &noise(2, 40_000), CompressionMethod::None);
+    assert!(matches!(result, Err(EngramError::CallbackPanicked)));
+    assert!(!volume_path(&base, 2).exists());
+
+    writer
+        .add_file_with_compression("b.bin", &noise(3, 10_000), CompressionMethod::None)
+        .unwrap();
+    writer.finalize().unwrap();
+    assert!(!volume_path(&base, 2).exists());
+
+    let mut reader = ArchiveReader::open_split(volume_path(&base, 1)).unwrap();
+    reader.initialize().unwrap();
+    assert_eq!(reader.list_files(), &["a.bin", "b.bin"]);
+    assert_eq!(reader.read_file("b.bin").unwrap(), noise(3, 10_000));
+    let report = reader.verify_all().unwrap();
+    assert!(report.is_ok(), "{:?}", report.failed);
+}
+
+#[test]
+fn test_writer_poisoned_when_rollback_fails() {
+    let sink = FlakySink::default();
+    let mut writer = ArchiveWriter::from_writer(sink.clone()).unwrap();
+    writer.add_file("kept.txt", b"kept").unwrap();
+
+    // The sink goes away entirely, so nothing can be undone
+    sink.set_offline(true);
+    let result =
+        writer.add_file_with_compression("lost.bin", &noise(1, 50_000), CompressionMethod::None);
+    assert!(matches!(result, Err(EngramError::Io(_))), "{:?}", result);
+
+    sink.set_offline(false);
+    assert!(matches!(
+        writer.add_file("next.txt", b"next"),
+        Err(EngramError::WriterPoisoned)
+    ));
+    assert!(matches!(
+        writer.finalize(),
+        Err(EngramError::WriterPoisoned)
+    ));
+}
@@ -0,0 +1,171 @@
+//! ZIP <-> Engram conversion tests
+//!
+//! Round-trips file contents through `convert::zip_to_eng` and `convert::eng_to_zip`.
+
+use engram_rs::convert::{eng_to_zip, zip_to_eng, ConversionOptions};
+use engram_rs::{ArchiveReader, ArchiveWriter, CompressionMethod};
+use std::io::{Cursor, Read, Write};
+use tempfile::NamedTempFile;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod as ZipCompression, DateTime, ZipArchive, ZipWriter};
+
+/// Helper: Sample files with a mix of compressible and incompressible content
+fn sample_files() -> Vec<(String, Vec<u8>)> {
+    vec![
+        ("readme.txt".to_string(), b"Hello, ZIP!".to_vec()),
+        (
+            "docs/guide.md".to_string(),
+            "# Guide\n\nSome text.\n".repeat(500).into_bytes(),
+        ),
+        (
+            "assets/data.bin".to_string(),
+            (0..20_000).map(|i| (i * 7 % 256) as u8).collect(),
+        ),
+        ("empty.txt".to_string(), Vec::new()),
+    ]
+}
+
+#[test]
+fn test_zip_to_eng_roundtrip() {
+    let files = sample_files();
+    let modified = DateTime::from_date_and_time(2023, 11, 5, 8, 15, 30).unwrap();
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.add_directory("docs/", SimpleFileOptions::default())
+        .unwrap();
+    for (path, data) in &files {
+        let options = SimpleFileOptions::default()
+            .compression_method(ZipCompression::Deflated)
+            .last_modified_time(modified);
+        zip.start_file(path.as_str(), options).unwrap();
+        zip.write_all(data).unwrap();
+    }
+    let zip_bytes = zip.finish().unwrap().into_inner();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let report = zip_to_eng(
+        Cursor::new(zip_bytes),
+        temp_file.path(),
+        ConversionOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(report.imported, files.len());
+    assert_eq!(report.directories_dropped, 1);
+    assert!(report.skipped.is_empty());
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), files.len());
+    for (path, data) in &files {
+        assert_eq!(
+            &reader.read_file(path).unwrap(),
+            data,
+            "Mismatch for {}",
+            path
+        );
+        // 2023-11-05 08:15:30 UTC
+        assert_eq!(reader.get_entry(path).unwrap().modified_time, 1_699_172_130);
+    }
+}
+
+#[test]
+fn test_eng_to_zip_roundtrip() {
+    let files = sample_files();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+        for (path, data) in &files {
+            writer.add_file(path, data).unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let mut out = Cursor::new(Vec::new());
+    let exported = eng_to_zip(temp_file.path(), &mut out).unwrap();
+    assert_eq!(exported, files.len());
+
+    let mut zip = ZipArchive::new(Cursor::new(out.into_inner())).unwrap();
+    assert_eq!(zip.len(), files.len());
+    for (path, data) in &files {
+        let mut entry = zip.by_name(path).unwrap();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents).unwrap();
+        assert_eq!(&contents, data, "Mismatch for {}", path);
+    }
+}
+
+#[test]
+fn test_zip_to_eng_skips_invalid_paths() {
+    let long_path = format!("{}.txt", "a".repeat(300));
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    for path in ["ok.txt", "../escape.txt", "/abs.txt", long_path.as_str()] {
+        zip.start_file(path, SimpleFileOptions::default()).unwrap();
+        zip.write_all(b"data").unwrap();
+    }
+    let zip_bytes = zip.finish().unwrap().into_inner();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let report = zip_to_eng(
+        Cursor::new(zip_bytes),
+        temp_file.path(),
+        ConversionOptions::default(),
+    )
+    .unwrap();
+
+    assert_eq!(report.imported, 1);
+    assert_eq!(report.skipped.len(), 3);
+
+    let reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    assert_eq!(reader.list_files(), &["ok.txt".to_string()]);
+}
+
+#[test]
+fn test_zip_to_eng_preserve_stored() {
+    let text = "stored but compressible ".repeat(1000);
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(ZipCompression::Stored);
+    zip.start_file("stored.txt", options).unwrap();
+    zip.write_all(text.as_bytes()).unwrap();
+    let zip_bytes = zip.finish().unwrap().into_inner();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let options = ConversionOptions {
+        preserve_compression: true,
+    };
+    zip_to_eng(Cursor::new(zip_bytes), temp_file.path(), options).unwrap();
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    let entry = reader.get_entry("stored.txt").unwrap();
+    assert_eq!(entry.compression, CompressionMethod::None);
+    assert_eq!(reader.read_file("stored.txt").unwrap(), text.as_bytes());
+}
+
+#[test]
+fn test_zip_to_eng_preserve_deflated() {
+    let text = "deflated in the zip ".repeat(1000);
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(ZipCompression::Deflated);
+    zip.start_file("deflated.txt", options).unwrap();
+    zip.write_all(text.as_bytes()).unwrap();
+    let zip_bytes = zip.finish().unwrap().into_inner();
+
+    for (preserve, expected) in [
+        (true, CompressionMethod::Deflate),
+        (false, CompressionMethod::Zstd),
+    ] {
+        let temp_file = NamedTempFile::new().unwrap();
+        let options = ConversionOptions {
+            preserve_compression: preserve,
+        };
+        zip_to_eng(Cursor::new(zip_bytes.clone()), temp_file.path(), options).unwrap();
+
+        let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+        let entry = reader.get_entry("deflated.txt").unwrap();
+        assert_eq!(entry.compression, expected);
+        assert_eq!(reader.read_file("deflated.txt").unwrap(), text.as_bytes());
+    }
+}
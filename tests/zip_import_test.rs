@@ -0,0 +1,99 @@
+//! ZIP import tests
+//!
+//! Builds ZIP archives in memory and imports them with `ArchiveWriter::import_zip`.
+
+use engram_rs::{ArchiveReader, ArchiveWriter, EngramError};
+use std::io::{Cursor, Write};
+use tempfile::NamedTempFile;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod as ZipCompression, DateTime, ZipWriter};
+
+/// Helper: Build a ZIP archive in memory from (path, data) pairs
+fn build_zip(files: &[(&str, &[u8])], directories: &[&str]) -> Vec<u8> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let modified = DateTime::from_date_and_time(2024, 6, 15, 12, 30, 0).unwrap();
+
+    for dir in directories {
+        zip.add_directory(*dir, SimpleFileOptions::default()).unwrap();
+    }
+
+    for (path, data) in files {
+        let options = SimpleFileOptions::default()
+            .compression_method(ZipCompression::Deflated)
+            .last_modified_time(modified);
+        zip.start_file(*path, options).unwrap();
+        zip.write_all(data).unwrap();
+    }
+
+    zip.finish().unwrap().into_inner()
+}
+
+#[test]
+fn test_import_zip_roundtrip() {
+    let large_text = "engram zip import ".repeat(1000);
+    let binary: Vec<u8> = (0..10_000).map(|i| (i % 251) as u8).collect();
+    let files: Vec<(&str, &[u8])> = vec![
+        ("readme.txt", b"Hello from ZIP"),
+        ("docs/large.txt", large_text.as_bytes()),
+        ("data/binary.bin", &binary),
+        ("empty.txt", b""),
+    ];
+    let zip_bytes = build_zip(&files, &["docs/", "empty_dir/"]);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let path = temp_file.path();
+
+    {
+        let mut writer = ArchiveWriter::create(path).unwrap();
+        let imported = writer.import_zip(Cursor::new(zip_bytes)).unwrap();
+        assert_eq!(imported, files.len());
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(path).unwrap();
+    assert_eq!(reader.entry_count(), files.len());
+
+    for (file_path, expected) in &files {
+        let data = reader.read_file(file_path).unwrap();
+        assert_eq!(&data[..], *expected, "Content mismatch for {}", file_path);
+    }
+
+    // Directory entries are not imported
+    assert!(!reader.contains("docs/"));
+    assert!(!reader.contains("empty_dir/"));
+
+    // Modification time comes from the ZIP (2024-06-15 12:30:00 UTC)
+    let entry = reader.get_entry("readme.txt").unwrap();
+    assert_eq!(entry.modified_time, 1_718_454_600);
+}
+
+#[test]
+fn test_import_zip_rejects_traversal() {
+    let zip_bytes = build_zip(&[("../../etc/passwd", b"malicious")], &[]);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let result = writer.import_zip(Cursor::new(zip_bytes));
+
+    assert!(matches!(result, Err(EngramError::PathError(_))));
+}
+
+#[test]
+fn test_import_zip_rejects_absolute_path() {
+    let zip_bytes = build_zip(&[("/etc/passwd", b"malicious")], &[]);
+
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let result = writer.import_zip(Cursor::new(zip_bytes));
+
+    assert!(matches!(result, Err(EngramError::PathError(_))));
+}
+
+#[test]
+fn test_import_invalid_zip() {
+    let temp_file = NamedTempFile::new().unwrap();
+    let mut writer = ArchiveWriter::create(temp_file.path()).unwrap();
+    let result = writer.import_zip(Cursor::new(b"not a zip file".to_vec()));
+
+    assert!(matches!(result, Err(EngramError::ZipError(_))));
+}
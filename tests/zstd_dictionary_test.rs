@@ -0,0 +1,114 @@
+//! Shared Zstd dictionary tests
+
+use engram_rs::{
+    train_dictionary, ArchiveReader, ArchiveWriter, CompressionMethod, ZSTD_DICTIONARY_PATH,
+};
+use tempfile::NamedTempFile;
+
+const FILE_COUNT: usize = 1000;
+
+/// Helper: Small JSON documents sharing structure and vocabulary
+fn sample_documents() -> Vec<(String, Vec<u8>)> {
+    let statuses = ["active", "pending", "archived", "suspended"];
+    (0..FILE_COUNT)
+        .map(|i| {
+            let json = format!(
+                r#"{{"id":{},"type":"user_profile","status":"{}","settings":{{"theme":"dark","language":"en-US","notifications":{{"email":true,"push":false}}}},"created_at":"2024-01-{:02}T12:00:00Z","score":{}}}"#,
+                i,
+                statuses[i % statuses.len()],
+                i % 28 + 1,
+                i * 37 % 1000
+            );
+            (format!("profiles/{:04}.json", i), json.into_bytes())
+        })
+        .collect()
+}
+
+/// Helper: Total stored payload size of all entries
+fn payload_size(path: &std::path::Path) -> u64 {
+    let reader = ArchiveReader::open_and_init(path).unwrap();
+    reader
+        .list_files()
+        .iter()
+        .map(|p| reader.get_entry(p).unwrap().compressed_size)
+        .sum()
+}
+
+#[test]
+fn test_dictionary_improves_ratio() {
+    let documents = sample_documents();
+    let samples: Vec<&[u8]> = documents.iter().map(|(_, data)| data.as_slice()).collect();
+    let dictionary = train_dictionary(&samples, 8 * 1024).unwrap();
+
+    // Baseline: every file Zstd-compressed on its own
+    let plain_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(plain_file.path()).unwrap();
+        for (path, data) in &documents {
+            writer
+                .add_file_with_compression(path, data, CompressionMethod::Zstd)
+                .unwrap();
+        }
+        writer.finalize().unwrap();
+    }
+
+    let dict_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(dict_file.path())
+            .unwrap()
+            .with_zstd_dictionary(dictionary.clone());
+        for (path, data) in &documents {
+            let method = writer.add_file(path, data).unwrap();
+            assert_eq!(method, CompressionMethod::Zstd);
+        }
+        writer.finalize().unwrap();
+    }
+
+    // The dictionary itself is counted against the dictionary archive
+    let plain_size = payload_size(plain_file.path());
+    let dict_size = payload_size(dict_file.path());
+    assert!(
+        dict_size * 2 < plain_size,
+        "Dictionary payload {} bytes vs {} bytes without",
+        dict_size,
+        plain_size
+    );
+
+    let mut reader = ArchiveReader::open_and_init(dict_file.path()).unwrap();
+    assert_eq!(reader.entry_count(), FILE_COUNT + 1);
+    assert_eq!(reader.read_file(ZSTD_DICTIONARY_PATH).unwrap(), dictionary);
+    for (path, data) in &documents {
+        assert!(reader.get_entry(path).unwrap().uses_zstd_dictionary());
+        assert_eq!(&reader.read_file(path).unwrap(), data);
+    }
+}
+
+#[test]
+fn test_dictionary_not_used_for_other_methods() {
+    let documents = sample_documents();
+    let samples: Vec<&[u8]> = documents.iter().map(|(_, data)| data.as_slice()).collect();
+    let dictionary = train_dictionary(&samples, 4 * 1024).unwrap();
+
+    let temp_file = NamedTempFile::new().unwrap();
+    {
+        let mut writer = ArchiveWriter::create(temp_file.path())
+            .unwrap()
+            .with_zstd_dictionary(dictionary);
+        let text = "lz4 data ".repeat(1000);
+        writer
+            .add_file_with_compression("lz4.txt", text.as_bytes(), CompressionMethod::Lz4)
+            .unwrap();
+        writer.add_file("image.png", &[0u8; 100]).unwrap();
+        writer.finalize().unwrap();
+    }
+
+    let mut reader = ArchiveReader::open_and_init(temp_file.path()).unwrap();
+    for path in ["lz4.txt", "image.png", ZSTD_DICTIONARY_PATH] {
+        assert!(!reader.get_entry(path).unwrap().uses_zstd_dictionary());
+    }
+    assert_eq!(
+        reader.get_entry("image.png").unwrap().compression,
+        CompressionMethod::None
+    );
+    assert_eq!(reader.read_file("image.png").unwrap(), vec![0u8; 100]);
+}